@@ -68,6 +68,7 @@ fn list_remotes(param: Value, rpcenv: &mut dyn RpcEnvironment) -> Result<(), Err
             match entry.ty {
                 RemoteType::Pve => println!("Proxmox VE node {}:", entry.id),
                 RemoteType::Pbs => println!("Proxmox Backup Server node {}:", entry.id),
+                RemoteType::Pmg => println!("Proxmox Mail Gateway node {}:", entry.id),
             }
             println!("    auth id: {}", entry.authid);
             println!("    token: {}", entry.token);
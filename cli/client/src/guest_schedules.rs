@@ -0,0 +1,138 @@
+use anyhow::Error;
+
+use proxmox_router::cli::{
+    CliCommand, CliCommandMap, CommandLineInterface, OutputFormat, format_and_print_result,
+};
+use proxmox_schema::api;
+
+use pdm_api_types::guest_schedules::{
+    GUEST_SCHEDULE_ID_SCHEMA, GuestSchedule, GuestScheduleUpdater,
+};
+
+use crate::{client, env};
+
+pub fn cli() -> CommandLineInterface {
+    CliCommandMap::new()
+        .insert("list", CliCommand::new(&API_METHOD_LIST_GUEST_SCHEDULES))
+        .insert(
+            "add",
+            CliCommand::new(&API_METHOD_ADD_GUEST_SCHEDULE).arg_param(&["id"]),
+        )
+        .insert(
+            "update",
+            CliCommand::new(&API_METHOD_UPDATE_GUEST_SCHEDULE).arg_param(&["id"]),
+        )
+        .insert(
+            "delete",
+            CliCommand::new(&API_METHOD_DELETE_GUEST_SCHEDULE).arg_param(&["id"]),
+        )
+        .into()
+}
+
+fn format_time(epoch: Option<i64>) -> String {
+    epoch
+        .and_then(|epoch| proxmox_time::strftime_local("%F %T", epoch).ok())
+        .unwrap_or_else(|| "-".to_string())
+}
+
+#[api]
+/// List the guest schedules with their last and next run.
+async fn list_guest_schedules() -> Result<(), Error> {
+    let entries = client()?.list_guest_schedules().await?;
+
+    let output_format = env().format_args.output_format;
+    if output_format == OutputFormat::Text {
+        if entries.is_empty() {
+            println!("No guest schedules configured");
+            return Ok(());
+        }
+
+        for entry in entries {
+            let job = &entry.config;
+            let disabled = if job.enabled() { "" } else { " (disabled)" };
+            println!("{}{disabled}: {} at '{}'", job.id, job.action, job.schedule);
+            if let Some(view) = &job.view {
+                println!("    view: {view}");
+            }
+            if let Some(search) = &job.search {
+                println!("    search: {search}");
+            }
+            if let Some(keep) = job.keep_snapshots {
+                println!("    keep snapshots: {keep}");
+            }
+            if let Some(comment) = &job.comment {
+                println!("    comment: {comment}");
+            }
+            println!(
+                "    last run: {} ({})",
+                format_time(entry.status.last_run_endtime),
+                entry.status.last_run_state.as_deref().unwrap_or("-"),
+            );
+            println!("    next run: {}", format_time(entry.status.next_run));
+        }
+    } else {
+        format_and_print_result(&entries, &output_format.to_string());
+    }
+    Ok(())
+}
+
+#[api(
+    input: {
+        properties: {
+            schedule: {
+                flatten: true,
+                type: GuestSchedule,
+            },
+        }
+    }
+)]
+/// Add a guest schedule.
+async fn add_guest_schedule(schedule: GuestSchedule) -> Result<(), Error> {
+    client()?.add_guest_schedule(&schedule).await?;
+    Ok(())
+}
+
+#[api(
+    input: {
+        properties: {
+            id: { schema: GUEST_SCHEDULE_ID_SCHEMA },
+            updater: {
+                flatten: true,
+                type: GuestScheduleUpdater,
+            },
+            delete: {
+                description: "List of properties to clear, e.g. 'search' or 'comment'.",
+                type: Array,
+                optional: true,
+                items: {
+                    type: String,
+                    description: "Property name.",
+                },
+            },
+        }
+    }
+)]
+/// Update a guest schedule.
+async fn update_guest_schedule(
+    id: String,
+    updater: GuestScheduleUpdater,
+    delete: Option<Vec<String>>,
+) -> Result<(), Error> {
+    client()?
+        .update_guest_schedule(&id, &updater, delete.as_deref().unwrap_or(&[]))
+        .await?;
+    Ok(())
+}
+
+#[api(
+    input: {
+        properties: {
+            id: { schema: GUEST_SCHEDULE_ID_SCHEMA },
+        }
+    }
+)]
+/// Delete a guest schedule.
+async fn delete_guest_schedule(id: String) -> Result<(), Error> {
+    client()?.delete_guest_schedule(&id).await?;
+    Ok(())
+}
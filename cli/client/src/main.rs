@@ -21,6 +21,7 @@ pub mod upid;
 
 pub mod acl;
 pub mod config;
+pub mod guest_schedules;
 pub mod metric_collection;
 pub mod pbs;
 pub mod pve;
@@ -101,6 +102,7 @@ fn main_do() -> Result<(), Error> {
             GlobalOptions::of::<config::FormatArgs>().completion_cb("color", env::complete_color),
         )
         .insert("acl", acl::cli())
+        .insert("guest-schedule", guest_schedules::cli())
         .insert("login", CliCommand::new(&API_METHOD_LOGIN))
         .insert("metric-collection", metric_collection::cli())
         .insert("pbs", pbs::cli())
@@ -10,13 +10,21 @@ use proxmox_router::cli::{
 use proxmox_rrd_api_types::{RrdMode, RrdTimeframe};
 use proxmox_schema::{ApiType, ArraySchema, ReturnType, Schema, api};
 
+use pdm_api_types::VIEW_ID_SCHEMA;
+use pdm_api_types::pbs::PbsJobType;
 use pdm_api_types::remotes::REMOTE_ID_SCHEMA;
 
+use crate::time::format_epoch_lossy;
 use crate::{client, env};
 
 pub fn cli() -> CommandLineInterface {
     CliCommandMap::new()
+        .insert(
+            "backup-coverage",
+            CliCommand::new(&API_METHOD_BACKUP_COVERAGE),
+        )
         .insert("datastore", datastore_cli())
+        .insert("job", job_cli())
         .insert("snapshot", snapshot_cli())
         .insert("node", node_cli())
         .insert("task", task_cli())
@@ -41,6 +49,16 @@ fn datastore_cli() -> CommandLineInterface {
         .into()
 }
 
+fn job_cli() -> CommandLineInterface {
+    CliCommandMap::new()
+        .insert("list", CliCommand::new(&API_METHOD_LIST_JOBS))
+        .insert(
+            "run",
+            CliCommand::new(&API_METHOD_RUN_JOB).arg_param(&["remote", "job-type", "id"]),
+        )
+        .into()
+}
+
 fn snapshot_cli() -> CommandLineInterface {
     CliCommandMap::new()
         .insert(
@@ -76,6 +94,191 @@ fn task_cli() -> CommandLineInterface {
         .into()
 }
 
+#[api(
+    input: {
+        properties: {
+            hours: {
+                description: "Guests without a backup in this many hours are reported as \
+                    not covered.",
+                type: Integer,
+                minimum: 1,
+                maximum: 100 * 365 * 24,
+                optional: true,
+            },
+            view: {
+                schema: VIEW_ID_SCHEMA,
+                optional: true,
+            },
+            all: {
+                description: "Also list guests with a recent backup.",
+                type: bool,
+                optional: true,
+                default: false,
+            },
+        }
+    }
+)]
+/// List the PVE guests without a recent backup on any PBS remote.
+async fn backup_coverage(hours: Option<u64>, view: Option<String>, all: bool) -> Result<(), Error> {
+    let mut coverage = client()?
+        .pbs_backup_coverage(hours, view.as_deref())
+        .await?;
+
+    if !all {
+        coverage.guests.retain(|guest| !guest.covered);
+    }
+
+    let output_format = env().format_args.output_format;
+    if output_format == OutputFormat::Text {
+        for failed in &coverage.failed_remotes {
+            println!("Errors querying remote {}: {}", failed.name, failed.error);
+        }
+
+        if coverage.guests.is_empty() {
+            println!("No guests without a recent backup found.");
+            return Ok(());
+        }
+
+        coverage
+            .guests
+            .sort_by(|a, b| (&a.remote, a.vmid).cmp(&(&b.remote, b.vmid)));
+        let now = proxmox_time::epoch_i64();
+        for guest in coverage.guests {
+            let covered = if guest.covered { "" } else { " (not covered)" };
+            println!(
+                "{remote}: {vmid} {name} on {node}{covered}",
+                remote = guest.remote,
+                vmid = guest.vmid,
+                name = guest.name,
+                node = guest.node,
+            );
+            match guest.last_backup {
+                Some(last_backup) => {
+                    let location = match &guest.namespace {
+                        Some(ns) => format!(
+                            "{}:{}/{ns}",
+                            guest.backup_remote.unwrap_or_default(),
+                            guest.datastore.unwrap_or_default(),
+                        ),
+                        None => format!(
+                            "{}:{}",
+                            guest.backup_remote.unwrap_or_default(),
+                            guest.datastore.unwrap_or_default(),
+                        ),
+                    };
+                    println!(
+                        "    last backup: {} ({} hours ago) on {location}",
+                        format_epoch_lossy(last_backup),
+                        (now - last_backup) / 3600,
+                    );
+                    if guest.ambiguous {
+                        println!(
+                            "    warning: VMID {} is used on multiple remotes, the backup might \
+                            belong to another guest",
+                            guest.vmid,
+                        );
+                    }
+                }
+                None => println!("    no backup found"),
+            }
+        }
+    } else {
+        format_and_print_result(&coverage, &output_format.to_string());
+    }
+    Ok(())
+}
+
+#[api(
+    input: {
+        properties: {
+            remote: {
+                schema: REMOTE_ID_SCHEMA,
+                optional: true,
+            },
+            view: {
+                schema: VIEW_ID_SCHEMA,
+                optional: true,
+            },
+            failed: {
+                description: "Only list jobs whose last run failed.",
+                type: bool,
+                optional: true,
+                default: false,
+            },
+        }
+    }
+)]
+/// List the garbage collection, prune, sync and verification jobs of the PBS remotes.
+async fn list_jobs(
+    remote: Option<String>,
+    view: Option<String>,
+    failed: bool,
+) -> Result<(), Error> {
+    let mut list = client()?
+        .pbs_list_jobs(remote.as_deref(), view.as_deref())
+        .await?;
+
+    if failed {
+        list.jobs.retain(|job| job.is_failed());
+    }
+
+    let output_format = env().format_args.output_format;
+    if output_format == OutputFormat::Text {
+        for failed in &list.failed_remotes {
+            println!("Errors querying remote {}: {}", failed.name, failed.error);
+        }
+
+        if list.jobs.is_empty() {
+            println!("No jobs found.");
+            return Ok(());
+        }
+
+        list.jobs
+            .sort_by(|a, b| (&a.remote, a.job_type, &a.id).cmp(&(&b.remote, b.job_type, &b.id)));
+        for job in list.jobs {
+            println!(
+                "{remote}: {job_type} {id} on {store}",
+                remote = job.remote,
+                job_type = job.job_type,
+                id = job.id,
+                store = job.store,
+            );
+            match (&job.last_run_state, job.last_run_endtime) {
+                (Some(state), Some(endtime)) => {
+                    println!("    last run: {} ({state})", format_epoch_lossy(endtime))
+                }
+                _ if job.last_run_upid.is_some() => println!("    running"),
+                _ => println!("    never run"),
+            }
+            if let Some(next_run) = job.next_run {
+                println!("    next run: {}", format_epoch_lossy(next_run));
+            }
+        }
+    } else {
+        format_and_print_result(&list, &output_format.to_string());
+    }
+    Ok(())
+}
+
+#[api(
+    input: {
+        properties: {
+            remote: { schema: REMOTE_ID_SCHEMA },
+            "job-type": { type: PbsJobType },
+            id: {
+                description: "The ID of the job, the datastore name for garbage collection.",
+                type: String,
+            },
+        }
+    }
+)]
+/// Run a job of a PBS remote now.
+async fn run_job(remote: String, job_type: PbsJobType, id: String) -> Result<(), Error> {
+    let upid = client()?.pbs_run_job(&remote, job_type, &id).await?;
+    println!("upid: {upid}");
+    Ok(())
+}
+
 #[api(
     input: {
         properties: {
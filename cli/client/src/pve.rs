@@ -5,6 +5,7 @@ use std::time::Duration;
 
 use anyhow::{Error, format_err};
 
+use proxmox_human_byte::HumanByte;
 use proxmox_router::cli::{
     CliCommand, CliCommandMap, CommandLineInterface, OutputFormat, format_and_print_result,
     format_and_print_result_full,
@@ -12,14 +13,34 @@ use proxmox_router::cli::{
 use proxmox_rrd_api_types::{RrdMode, RrdTimeframe};
 use proxmox_schema::{ApiType, ArraySchema, ReturnType, Schema, api};
 
+use pdm_api_types::pve::{
+    BulkGuestDescriptionParams, BulkGuestTagParams, DeployTemplateParams, EvacuateNodeParams,
+    GuestConfigUpdate, GuestConfigUpdateResult, PVE_BACKUP_JOB_ID_SCHEMA, PVE_CONFIG_DIGEST_SCHEMA,
+};
 use pdm_api_types::remotes::REMOTE_ID_SCHEMA;
-use pdm_api_types::{CIDR_FORMAT, NODE_SCHEMA, SNAPSHOT_NAME_SCHEMA, VMID_SCHEMA};
+use pdm_api_types::resource::{BulkGuestActionParams, GuestType};
+use pdm_api_types::{CIDR_FORMAT, NODE_SCHEMA, SNAPSHOT_NAME_SCHEMA, VIEW_ID_SCHEMA, VMID_SCHEMA};
 use pve_api_types::StartQemuMigrationType;
 
+use crate::time::format_epoch_lossy;
 use crate::{client, env};
 
 pub fn cli() -> CommandLineInterface {
     CliCommandMap::new()
+        .insert("backup-job", backup_job_cli())
+        .insert("balancing", balancing_cli())
+        .insert(
+            "deploy",
+            CliCommand::new(&API_METHOD_DEPLOY_TEMPLATE).arg_param(&["remote", "template"]),
+        )
+        .insert(
+            "bulk-action",
+            CliCommand::new(&API_METHOD_BULK_GUEST_ACTION).arg_param(&["action"]),
+        )
+        .insert(
+            "description",
+            CliCommand::new(&API_METHOD_BULK_GUEST_DESCRIPTION).arg_param(&["description"]),
+        )
         .insert("lxc", lxc_cli())
         .insert("node", node_cli())
         .insert("qemu", qemu_cli())
@@ -27,16 +48,35 @@ pub fn cli() -> CommandLineInterface {
             "resources",
             CliCommand::new(&API_METHOD_CLUSTER_RESOURCES).arg_param(&["remote", "kind"]),
         )
+        .insert("tag", tag_cli())
         .insert("task", task_cli())
         .into()
 }
 
+fn backup_job_cli() -> CommandLineInterface {
+    CliCommandMap::new()
+        .insert("list", CliCommand::new(&API_METHOD_LIST_BACKUP_JOBS))
+        .insert(
+            "run",
+            CliCommand::new(&API_METHOD_RUN_BACKUP_JOB).arg_param(&["remote", "id"]),
+        )
+        .into()
+}
+
 fn node_cli() -> CommandLineInterface {
     CliCommandMap::new()
         .insert(
             "list",
             CliCommand::new(&API_METHOD_LIST_NODES).arg_param(&["remote"]),
         )
+        .insert(
+            "evacuate",
+            CliCommand::new(&API_METHOD_EVACUATE_NODE).arg_param(&["remote", "node"]),
+        )
+        .insert(
+            "evacuation-plan",
+            CliCommand::new(&API_METHOD_NODE_EVACUATION_PLAN).arg_param(&["remote", "node"]),
+        )
         .insert(
             "rrddata",
             CliCommand::new(&API_METHOD_GET_NODE_RRD_DATA).arg_param(&[
@@ -55,6 +95,10 @@ fn qemu_cli() -> CommandLineInterface {
             "config",
             CliCommand::new(&API_METHOD_GET_QEMU_CONFIG).arg_param(&["remote", "vmid"]),
         )
+        .insert(
+            "update-config",
+            CliCommand::new(&API_METHOD_UPDATE_QEMU_CONFIG).arg_param(&["remote", "vmid"]),
+        )
         .insert(
             "list",
             CliCommand::new(&API_METHOD_LIST_QEMU).arg_param(&["remote"]),
@@ -128,6 +172,10 @@ fn lxc_cli() -> CommandLineInterface {
             "config",
             CliCommand::new(&API_METHOD_GET_LXC_CONFIG).arg_param(&["remote", "vmid"]),
         )
+        .insert(
+            "update-config",
+            CliCommand::new(&API_METHOD_UPDATE_LXC_CONFIG).arg_param(&["remote", "vmid"]),
+        )
         .insert(
             "list",
             CliCommand::new(&API_METHOD_LIST_LXC).arg_param(&["remote"]),
@@ -195,6 +243,30 @@ fn lxc_snapshot_cli() -> CommandLineInterface {
         .into()
 }
 
+fn balancing_cli() -> CommandLineInterface {
+    CliCommandMap::new()
+        .insert(
+            "list",
+            CliCommand::new(&API_METHOD_LIST_BALANCING_RECOMMENDATIONS),
+        )
+        .insert(
+            "apply",
+            CliCommand::new(&API_METHOD_APPLY_BALANCING_RECOMMENDATION)
+                .arg_param(&["remote", "vmid"]),
+        )
+        .into()
+}
+
+fn tag_cli() -> CommandLineInterface {
+    CliCommandMap::new()
+        .insert("list", CliCommand::new(&API_METHOD_LIST_TAGS))
+        .insert(
+            "change",
+            CliCommand::new(&API_METHOD_BULK_GUEST_TAGS).arg_param(&["action", "tag"]),
+        )
+        .into()
+}
+
 fn task_cli() -> CommandLineInterface {
     CliCommandMap::new()
         .insert(
@@ -208,6 +280,130 @@ fn task_cli() -> CommandLineInterface {
         .into()
 }
 
+#[api(
+    input: {
+        properties: {
+            remote: {
+                schema: REMOTE_ID_SCHEMA,
+                optional: true,
+            },
+            view: {
+                schema: VIEW_ID_SCHEMA,
+                optional: true,
+            },
+            failed: {
+                description: "Only list jobs whose last run failed.",
+                type: bool,
+                optional: true,
+                default: false,
+            },
+            uncovered: {
+                description: "List the guests not covered by any enabled backup job.",
+                type: bool,
+                optional: true,
+                default: false,
+            },
+        }
+    }
+)]
+/// List the backup jobs of the PVE remotes.
+async fn list_backup_jobs(
+    remote: Option<String>,
+    view: Option<String>,
+    failed: bool,
+    uncovered: bool,
+) -> Result<(), Error> {
+    let mut list = client()?
+        .pve_list_backup_jobs(remote.as_deref(), view.as_deref())
+        .await?;
+
+    if failed {
+        list.jobs
+            .retain(|job| job.last_run.as_ref().is_some_and(|run| run.is_failed()));
+    }
+
+    let output_format = env().format_args.output_format;
+    if output_format == OutputFormat::Text {
+        for failed in &list.failed_remotes {
+            println!("Errors querying remote {}: {}", failed.name, failed.error);
+        }
+
+        if uncovered {
+            if list.uncovered_guests.is_empty() {
+                println!("All guests are covered by a backup job.");
+                return Ok(());
+            }
+
+            list.uncovered_guests
+                .sort_by(|a, b| (&a.remote, a.vmid).cmp(&(&b.remote, b.vmid)));
+            for guest in list.uncovered_guests {
+                println!(
+                    "{remote}: {ty} {vmid} ({name}) on {node}",
+                    remote = guest.remote,
+                    ty = match guest.guest_type {
+                        GuestType::Qemu => "qemu",
+                        GuestType::Lxc => "lxc",
+                    },
+                    vmid = guest.vmid,
+                    name = guest.name,
+                    node = guest.node,
+                );
+            }
+            return Ok(());
+        }
+
+        if list.jobs.is_empty() {
+            println!("No backup jobs found.");
+            return Ok(());
+        }
+
+        list.jobs
+            .sort_by(|a, b| (&a.remote, &a.id).cmp(&(&b.remote, &b.id)));
+        for job in list.jobs {
+            let disabled = if job.enabled { "" } else { " (disabled)" };
+            println!(
+                "{remote}: {id} [{schedule}]{disabled}, {count} guests",
+                remote = job.remote,
+                id = job.id,
+                schedule = job.schedule,
+                count = job.guests.len(),
+            );
+            match &job.last_run {
+                Some(run) => match (&run.status, run.endtime) {
+                    (Some(status), Some(endtime)) => {
+                        println!("    last run: {} ({status})", format_epoch_lossy(endtime))
+                    }
+                    _ => println!("    running since {}", format_epoch_lossy(run.starttime)),
+                },
+                None => println!("    never run"),
+            }
+            if let Some(next_run) = job.next_run {
+                println!("    next run: {}", format_epoch_lossy(next_run));
+            }
+        }
+    } else {
+        format_and_print_result(&list, &output_format.to_string());
+    }
+    Ok(())
+}
+
+#[api(
+    input: {
+        properties: {
+            remote: { schema: REMOTE_ID_SCHEMA },
+            id: { schema: PVE_BACKUP_JOB_ID_SCHEMA },
+        }
+    }
+)]
+/// Run a backup job of a PVE remote now.
+async fn run_backup_job(remote: String, id: String) -> Result<(), Error> {
+    let upids = client()?.pve_run_backup_job(&remote, &id).await?;
+    for upid in upids {
+        println!("upid: {upid}");
+    }
+    Ok(())
+}
+
 #[api(
     input: {
         properties: {
@@ -451,6 +647,54 @@ async fn get_qemu_config(
     Ok(())
 }
 
+#[api(
+    input: {
+        properties: {
+            remote: { schema: REMOTE_ID_SCHEMA },
+            node: {
+                schema: NODE_SCHEMA,
+                optional: true,
+            },
+            vmid: { schema: VMID_SCHEMA },
+            update: {
+                type: GuestConfigUpdate,
+                flatten: true,
+            },
+            digest: {
+                schema: PVE_CONFIG_DIGEST_SCHEMA,
+                optional: true,
+            },
+            preview: {
+                type: bool,
+                description: "Only show the changes without applying them.",
+                optional: true,
+                default: false,
+            },
+        }
+    }
+)]
+/// Update the configuration of a VM.
+async fn update_qemu_config(
+    remote: String,
+    node: Option<String>,
+    vmid: u32,
+    update: GuestConfigUpdate,
+    digest: Option<String>,
+    preview: bool,
+) -> Result<(), Error> {
+    let result = client()?
+        .pve_qemu_update_config(
+            &remote,
+            node.as_deref(),
+            vmid,
+            update,
+            digest.as_deref(),
+            preview,
+        )
+        .await?;
+    print_config_update_result(&result)
+}
+
 #[api(
     input: {
         properties: {
@@ -872,6 +1116,54 @@ async fn get_lxc_config(
     Ok(())
 }
 
+#[api(
+    input: {
+        properties: {
+            remote: { schema: REMOTE_ID_SCHEMA },
+            node: {
+                schema: NODE_SCHEMA,
+                optional: true,
+            },
+            vmid: { schema: VMID_SCHEMA },
+            update: {
+                type: GuestConfigUpdate,
+                flatten: true,
+            },
+            digest: {
+                schema: PVE_CONFIG_DIGEST_SCHEMA,
+                optional: true,
+            },
+            preview: {
+                type: bool,
+                description: "Only show the changes without applying them.",
+                optional: true,
+                default: false,
+            },
+        }
+    }
+)]
+/// Update the configuration of a container.
+async fn update_lxc_config(
+    remote: String,
+    node: Option<String>,
+    vmid: u32,
+    update: GuestConfigUpdate,
+    digest: Option<String>,
+    preview: bool,
+) -> Result<(), Error> {
+    let result = client()?
+        .pve_lxc_update_config(
+            &remote,
+            node.as_deref(),
+            vmid,
+            update,
+            digest.as_deref(),
+            preview,
+        )
+        .await?;
+    print_config_update_result(&result)
+}
+
 #[api(
     input: {
         properties: {
@@ -1549,3 +1841,361 @@ async fn lxc_update_snapshot_config(
         .await?;
     Ok(())
 }
+
+#[api(
+    input: {
+        properties: {
+            params: {
+                type: BulkGuestActionParams,
+                flatten: true,
+            },
+        }
+    }
+)]
+/// Start, stop, shut down or resume many guests at once.
+///
+/// Guests are selected by their global resource ID (`remote/<remote>/guest/<vmid>`) or a search
+/// expression. Waits for the PDM worker task, which logs the outcome for every guest.
+async fn bulk_guest_action(params: BulkGuestActionParams) -> Result<(), Error> {
+    let client = client()?;
+    let upid = client.pve_bulk_guest_action(params).await?;
+    println!("upid: {upid}");
+    let status = client.wait_for_local_task(&upid).await?;
+    let exit = status
+        .get("exitstatus")
+        .and_then(|v| v.as_str())
+        .unwrap_or("unknown");
+    if exit == "OK" {
+        println!("Task finished: OK");
+        Ok(())
+    } else {
+        anyhow::bail!("worker task ended with: {exit}");
+    }
+}
+
+#[api(
+    input: {
+        properties: {
+            remote: { schema: REMOTE_ID_SCHEMA },
+            params: {
+                type: DeployTemplateParams,
+                flatten: true,
+            },
+        }
+    }
+)]
+/// Deploy a new guest from a template.
+///
+/// Clones the template, applies the cloud-init options and starts the guest if requested. Waits
+/// for the PDM worker task, which logs the remote tasks of every step.
+async fn deploy_template(remote: String, params: DeployTemplateParams) -> Result<(), Error> {
+    let client = client()?;
+    let upid = client.pve_deploy_template(&remote, params).await?;
+    println!("upid: {upid}");
+    let status = client.wait_for_local_task(&upid).await?;
+    let exit = status
+        .get("exitstatus")
+        .and_then(|v| v.as_str())
+        .unwrap_or("unknown");
+    if exit == "OK" {
+        println!("Task finished: OK");
+        Ok(())
+    } else {
+        anyhow::bail!("worker task ended with: {exit}");
+    }
+}
+
+#[api(
+    input: {
+        properties: {
+            timeframe: {
+                type: RrdTimeframe,
+                optional: true,
+            },
+            view: {
+                schema: VIEW_ID_SCHEMA,
+                optional: true,
+            },
+        }
+    }
+)]
+/// List recommended guest migrations to relieve overloaded nodes and balance clusters.
+async fn list_balancing_recommendations(
+    timeframe: Option<RrdTimeframe>,
+    view: Option<String>,
+) -> Result<(), Error> {
+    let recommendations = client()?
+        .pve_balancing_recommendations(view.as_deref(), timeframe)
+        .await?;
+
+    let output_format = env().format_args.output_format;
+    if output_format != OutputFormat::Text {
+        format_and_print_result(&recommendations, &output_format.to_string());
+        return Ok(());
+    }
+
+    if recommendations.is_empty() {
+        println!("No recommendations, all nodes are within the thresholds.");
+        return Ok(());
+    }
+    for recommendation in recommendations {
+        println!(
+            "{}/{} ({}): {} -> {}/{}",
+            recommendation.remote,
+            recommendation.vmid,
+            recommendation.name,
+            recommendation.source.node,
+            recommendation.target_remote,
+            recommendation.target.node,
+        );
+        println!("    {}", recommendation.reason);
+    }
+    Ok(())
+}
+
+#[api(
+    input: {
+        properties: {
+            remote: { schema: REMOTE_ID_SCHEMA },
+            vmid: { schema: VMID_SCHEMA },
+            timeframe: {
+                type: RrdTimeframe,
+                optional: true,
+            },
+            view: {
+                schema: VIEW_ID_SCHEMA,
+                optional: true,
+            },
+        }
+    }
+)]
+/// Start the migration recommended for a guest.
+async fn apply_balancing_recommendation(
+    remote: String,
+    vmid: u32,
+    timeframe: Option<RrdTimeframe>,
+    view: Option<String>,
+) -> Result<(), Error> {
+    let client = client()?;
+    let recommendation = client
+        .pve_balancing_recommendations(view.as_deref(), timeframe)
+        .await?
+        .into_iter()
+        .find(|recommendation| recommendation.remote == remote && recommendation.vmid == vmid)
+        .ok_or_else(|| format_err!("no migration recommended for guest {vmid} on '{remote}'"))?;
+
+    println!("{}", recommendation.reason);
+    let upid = client
+        .pve_apply_balancing_recommendation(&recommendation)
+        .await?;
+    println!("upid: {upid}");
+    Ok(())
+}
+
+#[api(
+    input: {
+        properties: {
+            view: {
+                schema: VIEW_ID_SCHEMA,
+                optional: true,
+            },
+        }
+    }
+)]
+/// List the tags of the guests of all PVE remotes with their usage.
+async fn list_tags(view: Option<String>) -> Result<(), Error> {
+    let tags = client()?.pve_list_tags(view.as_deref()).await?;
+
+    let output_format = env().format_args.output_format;
+    if output_format == OutputFormat::Text {
+        if tags.is_empty() {
+            println!("No tags found.");
+            return Ok(());
+        }
+
+        for usage in tags {
+            println!(
+                "{}: {} guest(s) on {}",
+                usage.tag,
+                usage.count,
+                usage.remotes.join(", ")
+            );
+        }
+    } else {
+        format_and_print_result(&tags, &output_format.to_string());
+    }
+    Ok(())
+}
+
+#[api(
+    input: {
+        properties: {
+            params: {
+                type: BulkGuestTagParams,
+                flatten: true,
+            },
+        }
+    }
+)]
+/// Add, remove or rename a tag on many guests at once.
+///
+/// Guests are selected by their global resource ID (`remote/<remote>/guest/<vmid>`) or a search
+/// expression. Without either, removing and renaming affect all guests carrying the tag. Waits
+/// for the PDM worker task, which logs the outcome for every guest.
+async fn bulk_guest_tags(params: BulkGuestTagParams) -> Result<(), Error> {
+    let client = client()?;
+    let upid = client.pve_bulk_guest_tags(params).await?;
+    println!("upid: {upid}");
+    let status = client.wait_for_local_task(&upid).await?;
+    let exit = status
+        .get("exitstatus")
+        .and_then(|v| v.as_str())
+        .unwrap_or("unknown");
+    if exit == "OK" {
+        println!("Task finished: OK");
+        Ok(())
+    } else {
+        anyhow::bail!("worker task ended with: {exit}");
+    }
+}
+
+#[api(
+    input: {
+        properties: {
+            params: {
+                type: BulkGuestDescriptionParams,
+                flatten: true,
+            },
+        }
+    }
+)]
+/// Set or extend the description of many guests at once.
+///
+/// Guests are selected by their global resource ID (`remote/<remote>/guest/<vmid>`) or a search
+/// expression. Waits for the PDM worker task, which logs the outcome for every guest.
+async fn bulk_guest_description(params: BulkGuestDescriptionParams) -> Result<(), Error> {
+    let client = client()?;
+    let upid = client.pve_bulk_guest_description(params).await?;
+    println!("upid: {upid}");
+    let status = client.wait_for_local_task(&upid).await?;
+    let exit = status
+        .get("exitstatus")
+        .and_then(|v| v.as_str())
+        .unwrap_or("unknown");
+    if exit == "OK" {
+        println!("Task finished: OK");
+        Ok(())
+    } else {
+        anyhow::bail!("worker task ended with: {exit}");
+    }
+}
+
+#[api(
+    input: {
+        properties: {
+            remote: { schema: REMOTE_ID_SCHEMA },
+            node: { schema: NODE_SCHEMA },
+        }
+    }
+)]
+/// Show the plan for migrating all guests off a node.
+async fn node_evacuation_plan(remote: String, node: String) -> Result<(), Error> {
+    let plan = client()?.pve_node_evacuation_plan(&remote, &node).await?;
+
+    let output_format = env().format_args.output_format;
+    if output_format != OutputFormat::Text {
+        format_and_print_result(&plan, &output_format.to_string());
+        return Ok(());
+    }
+
+    if plan.migrations.is_empty() && plan.unplaced.is_empty() {
+        println!("No guests on node '{node}'.");
+        return Ok(());
+    }
+    for migration in &plan.migrations {
+        let ty = match migration.guest_type {
+            GuestType::Qemu => "qemu",
+            GuestType::Lxc => "lxc",
+        };
+        println!("{ty} {} -> {}", migration.vmid, migration.target);
+    }
+    for vmid in &plan.unplaced {
+        println!("{vmid}: no node with enough free memory");
+    }
+    println!();
+    println!("Projected load:");
+    for target in &plan.targets {
+        println!(
+            "    {}: CPU {:.1}% of {}, memory {} of {}",
+            target.node,
+            target.cpu * 100.0,
+            target.maxcpu,
+            HumanByte::new_binary(target.mem as f64),
+            HumanByte::new_binary(target.maxmem as f64),
+        );
+    }
+    Ok(())
+}
+
+#[api(
+    input: {
+        properties: {
+            remote: { schema: REMOTE_ID_SCHEMA },
+            node: { schema: NODE_SCHEMA },
+            params: {
+                type: EvacuateNodeParams,
+                flatten: true,
+            },
+        }
+    }
+)]
+/// Migrate all guests off a node.
+///
+/// Without explicit migrations the computed plan is used, see `evacuation-plan`. Waits for the
+/// PDM worker task, which logs the outcome of every migration.
+async fn evacuate_node(
+    remote: String,
+    node: String,
+    params: EvacuateNodeParams,
+) -> Result<(), Error> {
+    let client = client()?;
+    let upid = client.pve_evacuate_node(&remote, &node, params).await?;
+    println!("upid: {upid}");
+    let status = client.wait_for_local_task(&upid).await?;
+    let exit = status
+        .get("exitstatus")
+        .and_then(|v| v.as_str())
+        .unwrap_or("unknown");
+    if exit == "OK" {
+        println!("Task finished: OK");
+        Ok(())
+    } else {
+        anyhow::bail!("worker task ended with: {exit}");
+    }
+}
+
+fn print_config_update_result(result: &GuestConfigUpdateResult) -> Result<(), Error> {
+    let output_format = env().format_args.output_format;
+    if output_format != OutputFormat::Text {
+        format_and_print_result(result, &output_format.to_string());
+        return Ok(());
+    }
+
+    if result.changes.is_empty() {
+        println!("No changes.");
+    }
+    for change in &result.changes {
+        let pending = if change.pending { " (pending)" } else { "" };
+        println!("{}:{pending}", change.key);
+        if let Some(old) = &change.old {
+            println!("    - {old}");
+        }
+        if let Some(new) = &change.new {
+            println!("    + {new}");
+        }
+    }
+    for upid in &result.tasks {
+        println!("upid: {upid}");
+    }
+    Ok(())
+}
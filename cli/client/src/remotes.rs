@@ -21,6 +21,10 @@ pub fn cli() -> CommandLineInterface {
             "add",
             CliCommand::new(&API_METHOD_ADD_REMOTE).arg_param(&["type", "id"]),
         )
+        .insert(
+            "quick-add",
+            CliCommand::new(&API_METHOD_QUICK_ADD_REMOTE).arg_param(&["id", "join-info"]),
+        )
         .insert(
             "delete",
             CliCommand::new(&API_METHOD_DELETE_REMOTE).arg_param(&["id"]),
@@ -41,6 +45,11 @@ pub fn cli() -> CommandLineInterface {
             "set-fingerprint",
             CliCommand::new(&API_METHOD_SET_FINGERPRINT).arg_param(&["id", "node"]),
         )
+        .insert(
+            "rotate-token",
+            CliCommand::new(&API_METHOD_ROTATE_TOKEN).arg_param(&["id"]),
+        )
+        .insert("rotate-tokens", CliCommand::new(&API_METHOD_ROTATE_TOKENS))
         .into()
 }
 
@@ -48,6 +57,7 @@ pub fn cli() -> CommandLineInterface {
 /// List all the remotes this instance is managing.
 async fn list_remotes() -> Result<(), Error> {
     let entries = client()?.list_remotes().await?;
+    let now = proxmox_time::epoch_i64();
 
     let output_format = env().format_args.output_format;
     if output_format == OutputFormat::Text {
@@ -60,9 +70,25 @@ async fn list_remotes() -> Result<(), Error> {
             match entry.ty {
                 RemoteType::Pve => println!("Proxmox VE node {}:", entry.id),
                 RemoteType::Pbs => println!("Proxmox Backup Server node {}:", entry.id),
+                RemoteType::Pmg => println!("Proxmox Mail Gateway node {}:", entry.id),
+            }
+            if let Some(folder) = &entry.folder {
+                println!("    folder: {folder}");
             }
             println!("    auth id: {}", entry.authid);
             println!("    token: {}", entry.token);
+            if let Some(age) = entry.token_age(now) {
+                println!("    token age: {} days", age / (24 * 60 * 60));
+            }
+            if let Some(timeout) = entry.timeout {
+                println!("    timeout: {timeout}s");
+            }
+            if let Some(http_proxy) = &entry.http_proxy {
+                println!("    http proxy: {http_proxy}");
+            }
+            if let Some(max_connections) = entry.max_connections {
+                println!("    max connections: {max_connections}");
+            }
             if entry.nodes.len() == 1 {
                 println!("    node: {}", property_string::print(&*entry.nodes[0])?);
             } else {
@@ -99,9 +125,78 @@ const CREATE_TOKEN_SCHEMA: Schema = pdm_api_types::PROXMOX_TOKEN_NAME_SCHEMA
     }
 )]
 /// Add a new remote.
+///
+/// If creating the token requires a second factor, it is queried interactively.
 async fn add_remote(entry: Remote, create_token: Option<String>) -> Result<(), Error> {
+    let client = client()?;
+
+    let Some(challenge) = client
+        .add_remote(&entry, create_token.as_deref(), None)
+        .await?
+    else {
+        return Ok(());
+    };
+
+    let tfa: proxmox_client::TfaChallenge = serde_json::from_str(&challenge.challenge)
+        .map_err(|err| anyhow::format_err!("invalid TFA challenge from remote - {err}"))?;
+    let response =
+        env().query_second_factor(&remote_api_url(&entry)?, entry.authid.user(), &tfa)?;
+
+    if client
+        .add_remote(
+            &entry,
+            create_token.as_deref(),
+            Some((&challenge.id, &response)),
+        )
+        .await?
+        .is_some()
+    {
+        anyhow::bail!("remote requested another second factor");
+    }
+    Ok(())
+}
+
+/// The API URL of the first node of a remote.
+fn remote_api_url(entry: &Remote) -> Result<http::Uri, Error> {
+    let node = entry
+        .nodes
+        .first()
+        .ok_or_else(|| anyhow::format_err!("no nodes given for remote"))?;
+    let authority: http::uri::Authority = node.hostname.parse()?;
+    Ok(format!(
+        "https://{}:{}",
+        authority.host(),
+        authority.port_u16().unwrap_or(entry.ty.default_port())
+    )
+    .parse()?)
+}
+
+#[api(
+    input: {
+        properties: {
+            id: { schema: REMOTE_ID_SCHEMA },
+            "join-info": {
+                type: String,
+                description: "The join information, as base64 encoded or plain JSON.",
+            },
+            "create-token": {
+                optional: true,
+                schema: CREATE_TOKEN_SCHEMA,
+            },
+        }
+    }
+)]
+/// Add a new remote from pasted join information.
+///
+/// The certificate fingerprints are verified against the join information and a new API token
+/// is created on the remote.
+async fn quick_add_remote(
+    id: String,
+    join_info: String,
+    create_token: Option<String>,
+) -> Result<(), Error> {
     client()?
-        .add_remote(&entry, create_token.as_deref())
+        .quick_add_remote(&id, &join_info, create_token.as_deref())
         .await?;
     Ok(())
 }
@@ -157,6 +252,66 @@ async fn delete_remote(id: String, delete_token: bool) -> Result<(), Error> {
     Ok(())
 }
 
+#[api(
+    input: {
+        properties: {
+            id: { schema: REMOTE_ID_SCHEMA },
+        }
+    }
+)]
+/// Rotate the API token of a remote now.
+///
+/// Creates a new token with the same privileges, switches the remote over to it once it was
+/// verified and deletes the old token. Waits for the PDM worker task.
+async fn rotate_token(id: String) -> Result<(), Error> {
+    let client = client()?;
+    let upid = client.rotate_remote_token(&id).await?;
+    println!("upid: {upid}");
+    let status = client.wait_for_local_task(&upid).await?;
+    let exit = status
+        .get("exitstatus")
+        .and_then(|v| v.as_str())
+        .unwrap_or("unknown");
+    if exit == "OK" {
+        println!("Task finished: OK");
+        Ok(())
+    } else {
+        anyhow::bail!("worker task ended with: {exit}");
+    }
+}
+
+#[api(
+    input: {
+        properties: {
+            "max-age": {
+                description: "Only rotate tokens older than this many days.",
+                type: Integer,
+                minimum: 1,
+                optional: true,
+            },
+        }
+    }
+)]
+/// Rotate the API tokens of all remotes now.
+///
+/// Waits for the PDM worker task, which logs the outcome for every remote.
+async fn rotate_tokens(max_age: Option<u64>) -> Result<(), Error> {
+    let client = client()?;
+    let upid = client.rotate_remote_tokens(max_age).await?;
+    println!("upid: {upid}");
+    let status = client.wait_for_local_task(&upid).await?;
+    let exit = status
+        .get("exitstatus")
+        .and_then(|v| v.as_str())
+        .unwrap_or("unknown");
+    if exit == "OK" {
+        println!("Task finished: OK");
+        Ok(())
+    } else {
+        anyhow::bail!("worker task ended with: {exit}");
+    }
+}
+
 #[api(
     input: {
         properties: {
@@ -27,12 +27,18 @@ pub fn cli() -> CommandLineInterface {
                 description: "Maximum age of cached remote resources.",
                 optional: true,
             },
+            search: {
+                description: "Search expression to filter for, e.g. 'type:qemu AND cpu>0.8'.",
+                optional: true,
+            },
         }
     }
 )]
 /// List all the remotes this instance is managing.
-async fn get_resources(max_age: Option<u64>) -> Result<(), Error> {
-    let mut resources = client()?.resources(max_age, None).await?;
+async fn get_resources(max_age: Option<u64>, search: Option<String>) -> Result<(), Error> {
+    let mut resources = client()?
+        .resources(max_age, None, search.as_deref())
+        .await?;
     let output_format = env().format_args.output_format;
     if output_format == OutputFormat::Text {
         if resources.is_empty() {
@@ -56,6 +62,7 @@ async fn get_resources(max_age: Option<u64>) -> Result<(), Error> {
                     Resource::PveNetwork(r) => println!("{}", PrintResource(r)),
                     Resource::PbsNode(r) => println!("{}", PrintResource(r)),
                     Resource::PbsDatastore(r) => println!("{}", PrintResource(r)),
+                    Resource::PmgNode(r) => println!("{}", PrintResource(r)),
                 }
             }
         }
@@ -75,6 +82,8 @@ fn resource_order(item: &Resource) -> usize {
 
         Resource::PbsNode(_) => 0,
         Resource::PbsDatastore(_) => 1,
+
+        Resource::PmgNode(_) => 0,
     }
 }
 
@@ -90,6 +99,7 @@ impl fmt::Display for PrintResource<resource::PveStorageResource> {
             ref storage,
             ref node,
             ref status,
+            estimated_full,
         } = self.0;
         write!(
             f,
@@ -101,7 +111,7 @@ impl fmt::Display for PrintResource<resource::PveStorageResource> {
                 term::Position(90, term::FractionAsBlock(disk as f64 / maxdisk as f64))
             )
         )?;
-        Ok(())
+        print_estimated_full(f, estimated_full)
     }
 }
 
@@ -188,12 +198,36 @@ impl fmt::Display for PrintResource<resource::PbsNodeResource> {
     }
 }
 
+impl fmt::Display for PrintResource<resource::PmgNodeResource> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let resource::PmgNodeResource {
+            cpu,
+            maxcpu: _,
+            ref node,
+            mem,
+            maxmem,
+            ..
+        } = self.0;
+        writeln!(f, "    Node {node}")?;
+        write!(
+            f,
+            "        cpu: {cpu}, mem: {mem} ({memcur} of {memmax})",
+            cpu = term::FractionAsBar(cpu),
+            mem = term::FractionAsBar(mem as f64 / maxmem as f64),
+            memcur = HumanByte::new_binary(mem as f64),
+            memmax = HumanByte::new_binary(maxmem as f64),
+        )?;
+        Ok(())
+    }
+}
+
 impl fmt::Display for PrintResource<resource::PbsDatastoreResource> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         let resource::PbsDatastoreResource {
             ref name,
             maxdisk,
             disk,
+            estimated_full,
             ..
         } = self.0;
 
@@ -206,6 +240,14 @@ impl fmt::Display for PrintResource<resource::PbsDatastoreResource> {
                 100,
                 term::Position(90, term::FractionAsBlock(disk as f64 / maxdisk as f64))
             ),
-        )
+        )?;
+        print_estimated_full(f, estimated_full)
+    }
+}
+
+fn print_estimated_full(f: &mut fmt::Formatter, estimated_full: Option<i64>) -> fmt::Result {
+    match estimated_full.and_then(|full| proxmox_time::epoch_to_rfc3339_utc(full).ok()) {
+        Some(full) => write!(f, ", estimated full: {full}"),
+        None => Ok(()),
     }
 }
@@ -280,6 +280,14 @@ impl proxmox_access_control::init::AccessControlConfig for AccessControlConfig {
                 if components_len <= 2 {
                     return Ok(());
                 }
+                // `/resource/folder/{folder}[/{subfolder}...]`
+                if components[1] == crate::remotes::REMOTE_FOLDER_ACL_COMPONENT {
+                    let folder = components[2..].join("/");
+                    return crate::remotes::REMOTE_FOLDER_SCHEMA
+                        .parse_simple_value(&folder)
+                        .map(drop)
+                        .with_context(|| format!("invalid acl path '{path}'."));
+                }
                 // `/resource/{remote-id}/{resource-type=guest,storage}/{resource-id}`
                 match components[2] {
                     "guest" | "storage" => {
@@ -297,8 +305,8 @@ impl proxmox_access_control::init::AccessControlConfig for AccessControlConfig {
                     return Ok(());
                 }
                 match components[1] {
-                    "auto-installation" | "certificates" | "disks" | "log" | "notifications"
-                    | "status" | "tasks" | "time" => {
+                    "alerts" | "auto-installation" | "certificates" | "disks"
+                    | "guest-schedules" | "log" | "notifications" | "status" | "tasks" | "time" => {
                         if components_len == 2 {
                             return Ok(());
                         }
@@ -343,3 +351,38 @@ impl proxmox_access_control::init::AccessControlConfig for AccessControlConfig {
         Err(format_err!("invalid acl path '{}'.", path))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use proxmox_access_control::init::AccessControlConfig as _;
+
+    use super::*;
+
+    #[test]
+    fn acl_paths() {
+        for path in [
+            "/",
+            "/system/alerts",
+            "/system/guest-schedules",
+            "/system/services/proxmox-datacenter-api",
+            "/resource/pve1/guest/100",
+            "/resource/folder",
+            "/resource/folder/site-a",
+            "/resource/folder/site-a/rack1",
+            "/view/lab",
+        ] {
+            assert!(AccessControlConfig.check_acl_path(path).is_ok(), "{path}");
+        }
+
+        for path in [
+            "/system/guest-schedules/lab",
+            "/system/unknown",
+            "/resource/pve1/guest/100/disk",
+            "/resource/folder/site a",
+            "/resource/folder/-site",
+            "/resource/folder/site-a/rack$1",
+        ] {
+            assert!(AccessControlConfig.check_acl_path(path).is_err(), "{path}");
+        }
+    }
+}
@@ -0,0 +1,413 @@
+//! Threshold based alert rules, evaluated against the collected metrics.
+
+use std::sync::OnceLock;
+
+use serde::{Deserialize, Serialize};
+
+use proxmox_schema::{ApiType, IntegerSchema, Schema, StringSchema, Updater, api};
+use proxmox_section_config::{SectionConfig, SectionConfigPlugin, typed::ApiSectionDataEntry};
+
+use crate::views::{FILTER_RULE_LIST_SCHEMA, FilterRule};
+use crate::{PROXMOX_SAFE_ID_FORMAT, SINGLE_LINE_COMMENT_SCHEMA, VIEW_ID_SCHEMA};
+
+pub const ALERT_CONFIG_ID_SCHEMA: Schema = StringSchema::new("Alert rule or silence ID.")
+    .format(&PROXMOX_SAFE_ID_FORMAT)
+    .min_length(2)
+    .max_length(32)
+    .schema();
+
+pub const ALERT_DURATION_SCHEMA: Schema =
+    IntegerSchema::new("Time in seconds the condition has to hold before the alert fires.")
+        .minimum(0)
+        .maximum(7 * 86400)
+        .default(0)
+        .schema();
+
+#[api]
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[serde(rename_all = "kebab-case")]
+/// The metric an alert rule is evaluated on.
+pub enum AlertMetric {
+    /// CPU usage of PVE and PBS nodes, in percent.
+    NodeCpu,
+    /// Memory usage of PVE and PBS nodes, in percent.
+    NodeMemory,
+    /// Usage of PVE storages, in percent.
+    StorageUsage,
+    /// Usage of PBS datastores, in percent.
+    DatastoreUsage,
+    /// Estimated days until a PBS datastore is full.
+    DatastoreFullDays,
+    /// Response time of a remote during metric collection, in milliseconds.
+    RemoteResponseTime,
+}
+
+impl AlertMetric {
+    /// The unit of the metric values, as used in the threshold.
+    pub fn unit(&self) -> &'static str {
+        match self {
+            AlertMetric::NodeCpu
+            | AlertMetric::NodeMemory
+            | AlertMetric::StorageUsage
+            | AlertMetric::DatastoreUsage => "%",
+            AlertMetric::DatastoreFullDays => "days",
+            AlertMetric::RemoteResponseTime => "ms",
+        }
+    }
+}
+
+serde_plain::derive_display_from_serialize!(AlertMetric);
+
+#[api]
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+/// How a metric value is compared with the threshold.
+pub enum AlertCondition {
+    /// The condition holds if the value is above the threshold.
+    #[default]
+    Above,
+    /// The condition holds if the value is below the threshold.
+    Below,
+}
+
+#[api]
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "kebab-case")]
+/// Severity of an alert.
+pub enum AlertSeverity {
+    /// Something should be looked at.
+    #[default]
+    Warning,
+    /// Something needs immediate attention.
+    Critical,
+}
+
+serde_plain::derive_display_from_serialize!(AlertSeverity);
+
+#[api(
+    properties: {
+        id: { schema: ALERT_CONFIG_ID_SCHEMA },
+        enable: {
+            type: bool,
+            optional: true,
+            default: true,
+        },
+        metric: { type: AlertMetric },
+        condition: {
+            type: AlertCondition,
+            optional: true,
+        },
+        threshold: {
+            type: Number,
+        },
+        duration: {
+            schema: ALERT_DURATION_SCHEMA,
+            optional: true,
+        },
+        hysteresis: {
+            type: Number,
+            minimum: 0.0,
+            optional: true,
+            default: 0.0,
+        },
+        severity: {
+            type: AlertSeverity,
+            optional: true,
+        },
+        view: {
+            schema: VIEW_ID_SCHEMA,
+            optional: true,
+        },
+        include: {
+            schema: FILTER_RULE_LIST_SCHEMA,
+            optional: true,
+        },
+        exclude: {
+            schema: FILTER_RULE_LIST_SCHEMA,
+            optional: true,
+        },
+        comment: {
+            schema: SINGLE_LINE_COMMENT_SCHEMA,
+            optional: true,
+        },
+    },
+)]
+#[derive(Clone, Debug, Deserialize, Serialize, Updater, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+/// An alert rule, e.g. "node CPU above 90% for 15 minutes".
+///
+/// The rule is evaluated for every resource matching its scope, which is given by a view and/or
+/// include and exclude filter rules. Without any scope, all resources are considered.
+pub struct AlertRule {
+    /// The rule ID.
+    #[updater(skip)]
+    pub id: String,
+
+    /// Enables or disables the rule.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub enable: Option<bool>,
+
+    /// The evaluated metric.
+    pub metric: AlertMetric,
+
+    /// How the value is compared with the threshold, defaults to 'above'.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub condition: Option<AlertCondition>,
+
+    /// The threshold, in the unit of the metric.
+    pub threshold: f64,
+
+    /// Time in seconds the condition has to hold before the alert fires.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duration: Option<u64>,
+
+    /// A firing alert only resolves once the value is this far on the other side of the
+    /// threshold, which avoids flapping alerts.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hysteresis: Option<f64>,
+
+    /// The severity of alerts of this rule, defaults to 'warning'.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub severity: Option<AlertSeverity>,
+
+    /// Only consider resources of this view.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub view: Option<String>,
+
+    /// Only consider resources matching any of these rules.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[updater(serde(skip_serializing_if = "Option::is_none"))]
+    pub include: Vec<FilterRule>,
+
+    /// Ignore resources matching any of these rules.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[updater(serde(skip_serializing_if = "Option::is_none"))]
+    pub exclude: Vec<FilterRule>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub comment: Option<String>,
+}
+
+impl AlertRule {
+    /// Whether the rule is enabled.
+    pub fn enabled(&self) -> bool {
+        self.enable.unwrap_or(true)
+    }
+
+    /// Check if a value fulfills the condition of this rule.
+    pub fn condition_met(&self, value: f64) -> bool {
+        match self.condition.unwrap_or_default() {
+            AlertCondition::Above => value > self.threshold,
+            AlertCondition::Below => value < self.threshold,
+        }
+    }
+
+    /// Check if a value is far enough from the threshold to resolve a firing alert.
+    pub fn resolved_by(&self, value: f64) -> bool {
+        let hysteresis = self.hysteresis.unwrap_or(0.0);
+        match self.condition.unwrap_or_default() {
+            AlertCondition::Above => value <= self.threshold - hysteresis,
+            AlertCondition::Below => value >= self.threshold + hysteresis,
+        }
+    }
+}
+
+#[api(
+    properties: {
+        id: { schema: ALERT_CONFIG_ID_SCHEMA },
+        rule: {
+            schema: ALERT_CONFIG_ID_SCHEMA,
+            optional: true,
+        },
+        include: {
+            schema: FILTER_RULE_LIST_SCHEMA,
+            optional: true,
+        },
+        comment: {
+            schema: SINGLE_LINE_COMMENT_SCHEMA,
+            optional: true,
+        },
+    },
+)]
+#[derive(Clone, Debug, Deserialize, Serialize, Updater, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+/// Silences matching alerts until a given time, no notifications are sent for them.
+pub struct AlertSilence {
+    /// The silence ID.
+    #[updater(skip)]
+    pub id: String,
+
+    /// Only silence alerts of this rule.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rule: Option<String>,
+
+    /// Only silence alerts of resources matching any of these rules.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[updater(serde(skip_serializing_if = "Option::is_none"))]
+    pub include: Vec<FilterRule>,
+
+    /// End of the silence (UNIX epoch).
+    pub until: i64,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub comment: Option<String>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+/// Enum for the different sections in the 'alerts.cfg' file.
+pub enum AlertConfigEntry {
+    /// 'rule' section
+    Rule(AlertRule),
+    /// 'silence' section
+    Silence(AlertSilence),
+}
+
+const RULE_SECTION_NAME: &str = "rule";
+const SILENCE_SECTION_NAME: &str = "silence";
+
+impl ApiSectionDataEntry for AlertConfigEntry {
+    fn section_config() -> &'static SectionConfig {
+        static CONFIG: OnceLock<SectionConfig> = OnceLock::new();
+
+        CONFIG.get_or_init(|| {
+            let mut this = SectionConfig::new(&ALERT_CONFIG_ID_SCHEMA);
+
+            this.register_plugin(SectionConfigPlugin::new(
+                RULE_SECTION_NAME.into(),
+                Some("id".to_string()),
+                AlertRule::API_SCHEMA.unwrap_object_schema(),
+            ));
+            this.register_plugin(SectionConfigPlugin::new(
+                SILENCE_SECTION_NAME.into(),
+                Some("id".to_string()),
+                AlertSilence::API_SCHEMA.unwrap_object_schema(),
+            ));
+            this
+        })
+    }
+
+    fn section_type(&self) -> &'static str {
+        match self {
+            AlertConfigEntry::Rule(_) => RULE_SECTION_NAME,
+            AlertConfigEntry::Silence(_) => SILENCE_SECTION_NAME,
+        }
+    }
+}
+
+#[api]
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+/// State of an alert.
+pub enum AlertState {
+    /// The condition holds, but not yet for the duration of the rule.
+    Pending,
+    /// The alert fired and is not resolved yet.
+    Firing,
+    /// The alert fired, but the condition does not hold anymore.
+    Resolved,
+}
+
+serde_plain::derive_display_from_serialize!(AlertState);
+
+#[api(
+    properties: {
+        rule: { schema: ALERT_CONFIG_ID_SCHEMA },
+        remote: { schema: crate::remotes::REMOTE_ID_SCHEMA },
+    },
+)]
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+/// An alert of a rule for a single resource.
+pub struct Alert {
+    /// The rule which created this alert.
+    pub rule: String,
+    /// The global ID of the affected resource.
+    pub resource: String,
+    /// The remote of the affected resource.
+    pub remote: String,
+    pub metric: AlertMetric,
+    pub severity: AlertSeverity,
+    pub state: AlertState,
+    /// The most recent value of the metric.
+    pub value: f64,
+    /// The threshold of the rule.
+    pub threshold: f64,
+    /// Since when the condition holds (UNIX epoch).
+    pub since: i64,
+    /// When the alert fired (UNIX epoch).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fired: Option<i64>,
+    /// When the alert was resolved (UNIX epoch).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resolved: Option<i64>,
+    /// The user who acknowledged the alert.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub acknowledged_by: Option<String>,
+    /// When the alert was acknowledged (UNIX epoch).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub acknowledged: Option<i64>,
+    /// Whether the alert is currently silenced.
+    #[serde(default)]
+    pub silenced: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn config_smoke_test() {
+        let config = "
+rule: node-cpu
+    metric node-cpu
+    threshold 90
+    duration 900
+    hysteresis 5
+    severity critical
+    include glob:remote=prod-*
+
+rule: datastore-full
+    metric datastore-full-days
+    condition below
+    threshold 14
+    view backup
+
+silence: maintenance
+    rule node-cpu
+    include exact:remote=prod-1
+    until 1700000000
+";
+        AlertConfigEntry::parse_section_config("alerts.cfg", config).unwrap();
+    }
+
+    #[test]
+    fn hysteresis() {
+        let mut rule = AlertRule {
+            id: "test".into(),
+            enable: None,
+            metric: AlertMetric::NodeCpu,
+            condition: None,
+            threshold: 90.0,
+            duration: None,
+            hysteresis: Some(5.0),
+            severity: None,
+            view: None,
+            include: Vec::new(),
+            exclude: Vec::new(),
+            comment: None,
+        };
+
+        assert!(rule.condition_met(91.0));
+        assert!(!rule.condition_met(90.0));
+        assert!(!rule.resolved_by(88.0));
+        assert!(rule.resolved_by(85.0));
+
+        rule.condition = Some(AlertCondition::Below);
+        rule.threshold = 14.0;
+        rule.hysteresis = Some(2.0);
+        assert!(rule.condition_met(13.5));
+        assert!(!rule.resolved_by(15.0));
+        assert!(rule.resolved_by(16.0));
+    }
+}
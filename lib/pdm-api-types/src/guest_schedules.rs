@@ -0,0 +1,225 @@
+//! Scheduled power actions and snapshots of PVE guests.
+
+use std::sync::OnceLock;
+
+use serde::{Deserialize, Serialize};
+
+use pbs_api_types::JobScheduleStatus;
+use proxmox_schema::{ApiStringFormat, ApiType, Schema, StringSchema, Updater, api, const_regex};
+use proxmox_section_config::{SectionConfig, SectionConfigPlugin, typed::ApiSectionDataEntry};
+
+use crate::{SINGLE_LINE_COMMENT_SCHEMA, VIEW_ID_SCHEMA};
+
+const_regex! {
+    // the ID is part of the snapshot names, which PVE limits to these characters
+    GUEST_SCHEDULE_ID_REGEX = r"^[A-Za-z0-9][A-Za-z0-9_\-]*$";
+}
+
+const GUEST_SCHEDULE_ID_FORMAT: ApiStringFormat =
+    ApiStringFormat::Pattern(&GUEST_SCHEDULE_ID_REGEX);
+
+pub const GUEST_SCHEDULE_ID_SCHEMA: Schema = StringSchema::new("Guest schedule ID.")
+    .format(&GUEST_SCHEDULE_ID_FORMAT)
+    .min_length(2)
+    .max_length(20)
+    .schema();
+
+pub const GUEST_SCHEDULE_EVENT_SCHEMA: Schema =
+    StringSchema::new("Run the job at these times, as systemd calendar event.")
+        .format(&ApiStringFormat::VerifyFn(
+            proxmox_time::verify_calendar_event,
+        ))
+        .type_text("<calendar-event>")
+        .schema();
+
+#[api]
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+/// The action a guest schedule applies to its guests.
+pub enum ScheduledGuestAction {
+    /// Start the guests which are not running.
+    Start,
+    /// Cleanly shut down the running guests.
+    Shutdown,
+    /// Stop the running guests immediately.
+    Stop,
+    /// Take a snapshot of the guests.
+    Snapshot,
+}
+
+serde_plain::derive_display_from_serialize!(ScheduledGuestAction);
+
+#[api(
+    properties: {
+        id: { schema: GUEST_SCHEDULE_ID_SCHEMA },
+        enable: {
+            type: bool,
+            optional: true,
+            default: true,
+        },
+        schedule: { schema: GUEST_SCHEDULE_EVENT_SCHEMA },
+        action: { type: ScheduledGuestAction },
+        search: {
+            type: String,
+            optional: true,
+        },
+        view: {
+            schema: VIEW_ID_SCHEMA,
+            optional: true,
+        },
+        "keep-snapshots": {
+            type: Integer,
+            optional: true,
+            minimum: 1,
+            maximum: 100,
+        },
+        comment: {
+            schema: SINGLE_LINE_COMMENT_SCHEMA,
+            optional: true,
+        },
+    },
+)]
+#[derive(Clone, Debug, Deserialize, Serialize, Updater, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+/// A job applying an action to PVE guests at scheduled times, e.g. "shut down the test lab at
+/// 20:00 on workdays".
+///
+/// The affected guests are the guests of the view and/or the guests matching the search, which
+/// are looked up on every run.
+pub struct GuestSchedule {
+    /// The job ID.
+    #[updater(skip)]
+    pub id: String,
+
+    /// Enables or disables the job.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub enable: Option<bool>,
+
+    /// The times the job runs at.
+    pub schedule: String,
+
+    /// The action applied to the guests.
+    pub action: ScheduledGuestAction,
+
+    /// Only consider guests matching this search expression.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub search: Option<String>,
+
+    /// Only consider guests of this view.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub view: Option<String>,
+
+    /// Number of snapshots taken by the job to keep per guest, older ones are deleted. Only for
+    /// the snapshot action, all snapshots are kept if not set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub keep_snapshots: Option<u32>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub comment: Option<String>,
+}
+
+impl GuestSchedule {
+    /// Whether the job is enabled.
+    pub fn enabled(&self) -> bool {
+        self.enable.unwrap_or(true)
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+/// Enum for the different sections in the 'guest-schedules.cfg' file.
+pub enum GuestScheduleConfigEntry {
+    /// 'guest-schedule' section
+    GuestSchedule(GuestSchedule),
+}
+
+const GUEST_SCHEDULE_SECTION_NAME: &str = "guest-schedule";
+
+impl ApiSectionDataEntry for GuestScheduleConfigEntry {
+    fn section_config() -> &'static SectionConfig {
+        static CONFIG: OnceLock<SectionConfig> = OnceLock::new();
+
+        CONFIG.get_or_init(|| {
+            let mut this = SectionConfig::new(&GUEST_SCHEDULE_ID_SCHEMA);
+
+            this.register_plugin(SectionConfigPlugin::new(
+                GUEST_SCHEDULE_SECTION_NAME.into(),
+                Some("id".to_string()),
+                GuestSchedule::API_SCHEMA.unwrap_object_schema(),
+            ));
+            this
+        })
+    }
+
+    fn section_type(&self) -> &'static str {
+        match self {
+            GuestScheduleConfigEntry::GuestSchedule(_) => GUEST_SCHEDULE_SECTION_NAME,
+        }
+    }
+}
+
+#[api(
+    properties: {
+        config: { type: GuestSchedule },
+        status: { type: JobScheduleStatus },
+    },
+)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+/// A guest schedule together with the state of its last and next run.
+pub struct GuestScheduleStatus {
+    #[serde(flatten)]
+    pub config: GuestSchedule,
+    #[serde(flatten)]
+    pub status: JobScheduleStatus,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn config_smoke_test() {
+        let config = "
+guest-schedule: lab-night
+    schedule mon..fri 20:00
+    action shutdown
+    view test-lab
+    comment shut down the test lab over night
+
+guest-schedule: lab-morning
+    schedule mon..fri 07:00
+    action start
+    view test-lab
+
+guest-schedule: snapshots
+    enable false
+    schedule daily
+    action snapshot
+    search remote:prod type:qemu
+";
+        GuestScheduleConfigEntry::parse_section_config("guest-schedules.cfg", config).unwrap();
+    }
+
+    #[test]
+    fn invalid_schedule() {
+        let config = "
+guest-schedule: broken
+    schedule every now and then
+    action stop
+";
+        assert!(
+            GuestScheduleConfigEntry::parse_section_config("guest-schedules.cfg", config).is_err()
+        );
+    }
+
+    #[test]
+    fn schedule_ids() {
+        for id in ["lab-night", "snapshots_daily", "0night"] {
+            assert!(GUEST_SCHEDULE_ID_SCHEMA.parse_simple_value(id).is_ok());
+        }
+        for id in ["lab.night", "-night", "a", "snapshots-of-the-lab-vms"] {
+            assert!(GUEST_SCHEDULE_ID_SCHEMA.parse_simple_value(id).is_err());
+        }
+    }
+}
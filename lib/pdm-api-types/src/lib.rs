@@ -17,6 +17,10 @@ pub use acl::*;
 
 pub mod pbs;
 
+pub mod pmg;
+
+pub mod pve;
+
 mod node_config;
 pub use node_config::*;
 
@@ -27,7 +31,7 @@ mod remote_upid;
 pub use remote_upid::*;
 
 mod proxy;
-pub use proxy::HTTP_PROXY_SCHEMA;
+pub use proxy::{HTTP_PROXY_SCHEMA, REMOTE_PROXY_SCHEMA};
 
 mod translation;
 pub use translation::Translation;
@@ -120,8 +124,14 @@ pub mod sdn;
 
 pub mod views;
 
+pub mod metric_servers;
+
 pub mod acme;
 
+pub mod alerts;
+
+pub mod guest_schedules;
+
 const_regex! {
     // just a rough check - dummy acceptor is used before persisting
     pub OPENSSL_CIPHERS_REGEX = r"^[0-9A-Za-z_:, +!\-@=.]+$";
@@ -0,0 +1,490 @@
+//! Configuration of external metric servers, which receive a copy of all collected metrics.
+
+use std::sync::OnceLock;
+
+use serde::{Deserialize, Serialize};
+
+use proxmox_schema::{ApiStringFormat, ApiType, ArraySchema, Schema, StringSchema, Updater, api};
+use proxmox_section_config::{SectionConfig, SectionConfigPlugin, typed::ApiSectionDataEntry};
+
+use crate::remotes::REMOTE_ID_SCHEMA;
+use crate::{
+    HOST_PORT_SCHEMA, HTTP_URL_SCHEMA, PROXMOX_SAFE_ID_FORMAT, SINGLE_LINE_COMMENT_SCHEMA,
+};
+
+pub const METRIC_SERVER_ID_SCHEMA: Schema = StringSchema::new("Metric server ID.")
+    .format(&PROXMOX_SAFE_ID_FORMAT)
+    .min_length(2)
+    .max_length(32)
+    .schema();
+
+pub const METRIC_SERVER_REMOTES_SCHEMA: Schema = ArraySchema::new(
+    "Only send metrics of these remotes. Metrics of all remotes are sent if not set.",
+    &REMOTE_ID_SCHEMA,
+)
+.schema();
+
+pub const INFLUXDB_BUCKET_SCHEMA: Schema = StringSchema::new("InfluxDB bucket.")
+    .format(&ApiStringFormat::Pattern(&crate::PROXMOX_SAFE_ID_REGEX))
+    .min_length(3)
+    .max_length(32)
+    .default("proxmox")
+    .schema();
+
+pub const INFLUXDB_ORGANIZATION_SCHEMA: Schema = StringSchema::new("InfluxDB organization.")
+    .format(&ApiStringFormat::Pattern(&crate::PROXMOX_SAFE_ID_REGEX))
+    .min_length(3)
+    .max_length(32)
+    .default("proxmox")
+    .schema();
+
+pub const GRAPHITE_PATH_SCHEMA: Schema =
+    StringSchema::new("Root path of all metrics sent to the graphite server.")
+        .format(&ApiStringFormat::Pattern(&crate::PROXMOX_SAFE_ID_REGEX))
+        .max_length(64)
+        .default("proxmox")
+        .schema();
+
+#[api(
+    properties: {
+        name: { schema: METRIC_SERVER_ID_SCHEMA },
+        enable: {
+            type: bool,
+            optional: true,
+            default: true,
+        },
+        url: { schema: HTTP_URL_SCHEMA },
+        organization: {
+            schema: INFLUXDB_ORGANIZATION_SCHEMA,
+            optional: true,
+        },
+        bucket: {
+            schema: INFLUXDB_BUCKET_SCHEMA,
+            optional: true,
+        },
+        "max-body-size": {
+            type: usize,
+            optional: true,
+            default: 25_000_000,
+        },
+        "verify-tls": {
+            type: bool,
+            optional: true,
+            default: true,
+        },
+        remotes: {
+            schema: METRIC_SERVER_REMOTES_SCHEMA,
+            optional: true,
+        },
+        comment: {
+            schema: SINGLE_LINE_COMMENT_SCHEMA,
+            optional: true,
+        },
+    },
+)]
+#[derive(Clone, Debug, Deserialize, Serialize, Updater, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+/// InfluxDB server, written to via the HTTP API (v2).
+pub struct InfluxDbHttp {
+    /// The metric server name.
+    #[updater(skip)]
+    pub name: String,
+
+    /// Enables or disables the metric server.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub enable: Option<bool>,
+
+    /// The base url of the InfluxDB server.
+    pub url: String,
+
+    /// The organization.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub organization: Option<String>,
+
+    /// The bucket.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bucket: Option<String>,
+
+    /// The (optional) API token.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
+
+    /// Maximum size of a single HTTP request body.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_body_size: Option<usize>,
+
+    /// Whether the TLS certificate of the server has to be valid.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub verify_tls: Option<bool>,
+
+    /// Remote filter.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub remotes: Option<Vec<String>>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub comment: Option<String>,
+}
+
+#[api(
+    properties: {
+        name: { schema: METRIC_SERVER_ID_SCHEMA },
+        enable: {
+            type: bool,
+            optional: true,
+            default: true,
+        },
+        host: { schema: HOST_PORT_SCHEMA },
+        mtu: {
+            type: u16,
+            optional: true,
+            default: 1500,
+            minimum: 512,
+        },
+        remotes: {
+            schema: METRIC_SERVER_REMOTES_SCHEMA,
+            optional: true,
+        },
+        comment: {
+            schema: SINGLE_LINE_COMMENT_SCHEMA,
+            optional: true,
+        },
+    },
+)]
+#[derive(Clone, Debug, Deserialize, Serialize, Updater, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+/// InfluxDB server, written to via UDP.
+pub struct InfluxDbUdp {
+    /// The metric server name.
+    #[updater(skip)]
+    pub name: String,
+
+    /// Enables or disables the metric server.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub enable: Option<bool>,
+
+    /// The host and port of the UDP listener.
+    pub host: String,
+
+    /// The MTU, limits the size of the sent datagrams.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mtu: Option<u16>,
+
+    /// Remote filter.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub remotes: Option<Vec<String>>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub comment: Option<String>,
+}
+
+#[api]
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+/// Transport protocol used to send metrics to a graphite server.
+pub enum GraphiteProtocol {
+    /// Plain TCP.
+    #[default]
+    Tcp,
+    /// UDP datagrams.
+    Udp,
+}
+
+#[api(
+    properties: {
+        name: { schema: METRIC_SERVER_ID_SCHEMA },
+        enable: {
+            type: bool,
+            optional: true,
+            default: true,
+        },
+        host: { schema: HOST_PORT_SCHEMA },
+        proto: {
+            type: GraphiteProtocol,
+            optional: true,
+        },
+        path: {
+            schema: GRAPHITE_PATH_SCHEMA,
+            optional: true,
+        },
+        mtu: {
+            type: u16,
+            optional: true,
+            default: 1500,
+            minimum: 512,
+        },
+        remotes: {
+            schema: METRIC_SERVER_REMOTES_SCHEMA,
+            optional: true,
+        },
+        comment: {
+            schema: SINGLE_LINE_COMMENT_SCHEMA,
+            optional: true,
+        },
+    },
+)]
+#[derive(Clone, Debug, Deserialize, Serialize, Updater, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+/// Graphite server, written to via the plaintext protocol.
+pub struct Graphite {
+    /// The metric server name.
+    #[updater(skip)]
+    pub name: String,
+
+    /// Enables or disables the metric server.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub enable: Option<bool>,
+
+    /// The host and port of the graphite server.
+    pub host: String,
+
+    /// The protocol, defaults to TCP.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub proto: Option<GraphiteProtocol>,
+
+    /// The root path of the metrics.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+
+    /// The MTU, limits the size of the sent datagrams when using UDP.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mtu: Option<u16>,
+
+    /// Remote filter.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub remotes: Option<Vec<String>>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub comment: Option<String>,
+}
+
+#[api]
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq)]
+/// Type of a metric server.
+pub enum MetricServerType {
+    /// InfluxDB HTTP API.
+    #[serde(rename = "influxdb-http")]
+    InfluxDbHttp,
+    /// InfluxDB UDP.
+    #[serde(rename = "influxdb-udp")]
+    InfluxDbUdp,
+    /// Graphite.
+    #[serde(rename = "graphite")]
+    Graphite,
+}
+
+impl MetricServerType {
+    /// Returns the section type name.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MetricServerType::InfluxDbHttp => INFLUXDB_HTTP_SECTION_NAME,
+            MetricServerType::InfluxDbUdp => INFLUXDB_UDP_SECTION_NAME,
+            MetricServerType::Graphite => GRAPHITE_SECTION_NAME,
+        }
+    }
+}
+
+impl std::fmt::Display for MetricServerType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+/// Enum for the different sections in the 'metricserver.cfg' file.
+pub enum MetricServerEntry {
+    /// 'influxdb-http' section
+    #[serde(rename = "influxdb-http")]
+    InfluxDbHttp(InfluxDbHttp),
+    /// 'influxdb-udp' section
+    #[serde(rename = "influxdb-udp")]
+    InfluxDbUdp(InfluxDbUdp),
+    /// 'graphite' section
+    #[serde(rename = "graphite")]
+    Graphite(Graphite),
+}
+
+const INFLUXDB_HTTP_SECTION_NAME: &str = "influxdb-http";
+const INFLUXDB_UDP_SECTION_NAME: &str = "influxdb-udp";
+const GRAPHITE_SECTION_NAME: &str = "graphite";
+
+impl MetricServerEntry {
+    /// The name of the metric server.
+    pub fn name(&self) -> &str {
+        match self {
+            MetricServerEntry::InfluxDbHttp(entry) => &entry.name,
+            MetricServerEntry::InfluxDbUdp(entry) => &entry.name,
+            MetricServerEntry::Graphite(entry) => &entry.name,
+        }
+    }
+
+    /// The type of the metric server.
+    pub fn ty(&self) -> MetricServerType {
+        match self {
+            MetricServerEntry::InfluxDbHttp(_) => MetricServerType::InfluxDbHttp,
+            MetricServerEntry::InfluxDbUdp(_) => MetricServerType::InfluxDbUdp,
+            MetricServerEntry::Graphite(_) => MetricServerType::Graphite,
+        }
+    }
+
+    /// Whether the metric server is enabled.
+    pub fn enabled(&self) -> bool {
+        match self {
+            MetricServerEntry::InfluxDbHttp(entry) => entry.enable,
+            MetricServerEntry::InfluxDbUdp(entry) => entry.enable,
+            MetricServerEntry::Graphite(entry) => entry.enable,
+        }
+        .unwrap_or(true)
+    }
+
+    /// The list of remotes whose metrics are sent, `None` means all remotes.
+    pub fn remotes(&self) -> Option<&[String]> {
+        match self {
+            MetricServerEntry::InfluxDbHttp(entry) => entry.remotes.as_deref(),
+            MetricServerEntry::InfluxDbUdp(entry) => entry.remotes.as_deref(),
+            MetricServerEntry::Graphite(entry) => entry.remotes.as_deref(),
+        }
+    }
+
+    /// Check if metrics of a remote should be sent to this metric server.
+    pub fn includes_remote(&self, remote: &str) -> bool {
+        match self.remotes() {
+            Some(remotes) => remotes.iter().any(|r| r == remote),
+            None => true,
+        }
+    }
+
+    /// Short description of where the metrics are sent to.
+    pub fn target(&self) -> &str {
+        match self {
+            MetricServerEntry::InfluxDbHttp(entry) => &entry.url,
+            MetricServerEntry::InfluxDbUdp(entry) => &entry.host,
+            MetricServerEntry::Graphite(entry) => &entry.host,
+        }
+    }
+
+    /// The comment of the metric server.
+    pub fn comment(&self) -> Option<&str> {
+        match self {
+            MetricServerEntry::InfluxDbHttp(entry) => entry.comment.as_deref(),
+            MetricServerEntry::InfluxDbUdp(entry) => entry.comment.as_deref(),
+            MetricServerEntry::Graphite(entry) => entry.comment.as_deref(),
+        }
+    }
+}
+
+impl ApiSectionDataEntry for MetricServerEntry {
+    fn section_config() -> &'static SectionConfig {
+        static CONFIG: OnceLock<SectionConfig> = OnceLock::new();
+
+        CONFIG.get_or_init(|| {
+            let mut this = SectionConfig::new(&METRIC_SERVER_ID_SCHEMA);
+
+            this.register_plugin(SectionConfigPlugin::new(
+                INFLUXDB_HTTP_SECTION_NAME.into(),
+                Some("name".to_string()),
+                InfluxDbHttp::API_SCHEMA.unwrap_object_schema(),
+            ));
+            this.register_plugin(SectionConfigPlugin::new(
+                INFLUXDB_UDP_SECTION_NAME.into(),
+                Some("name".to_string()),
+                InfluxDbUdp::API_SCHEMA.unwrap_object_schema(),
+            ));
+            this.register_plugin(SectionConfigPlugin::new(
+                GRAPHITE_SECTION_NAME.into(),
+                Some("name".to_string()),
+                Graphite::API_SCHEMA.unwrap_object_schema(),
+            ));
+            this
+        })
+    }
+
+    fn section_type(&self) -> &'static str {
+        self.ty().as_str()
+    }
+}
+
+#[api(
+    properties: {
+        name: { schema: METRIC_SERVER_ID_SCHEMA },
+        "type": { type: MetricServerType },
+        comment: {
+            schema: SINGLE_LINE_COMMENT_SCHEMA,
+            optional: true,
+        },
+    },
+)]
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+/// Basic information about a metric server.
+pub struct MetricServerInfo {
+    /// The metric server name.
+    pub name: String,
+    /// The type of the metric server.
+    #[serde(rename = "type")]
+    pub ty: MetricServerType,
+    /// Whether the metric server is enabled.
+    pub enable: bool,
+    /// The URL or host the metrics are sent to.
+    pub target: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub comment: Option<String>,
+}
+
+impl From<&MetricServerEntry> for MetricServerInfo {
+    fn from(entry: &MetricServerEntry) -> Self {
+        Self {
+            name: entry.name().to_string(),
+            ty: entry.ty(),
+            enable: entry.enabled(),
+            target: entry.target().to_string(),
+            comment: entry.comment().map(str::to_string),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn config_smoke_test() {
+        let config = "
+influxdb-http: influx
+    url https://influx.example.com:8086
+    organization example
+    bucket pdm
+    token secret
+    remotes cluster-a,cluster-b
+
+influxdb-udp: influx-udp
+    host 192.0.2.10:8089
+    enable false
+
+graphite: graphite
+    host graphite.example.com:2003
+    proto udp
+    path datacenter
+";
+        let data = MetricServerEntry::parse_section_config("metricserver.cfg", config).unwrap();
+
+        let MetricServerEntry::InfluxDbHttp(influx) = data.get("influx").unwrap() else {
+            panic!("expected an influxdb-http section");
+        };
+        assert_eq!(influx.bucket.as_deref(), Some("pdm"));
+        assert_eq!(
+            influx.remotes.as_deref(),
+            Some(&["cluster-a".to_string(), "cluster-b".to_string()][..])
+        );
+
+        let udp = data.get("influx-udp").unwrap();
+        assert!(!udp.enabled());
+        assert!(udp.includes_remote("anything"));
+
+        let graphite = data.get("graphite").unwrap();
+        assert_eq!(graphite.ty(), MetricServerType::Graphite);
+        assert!(data.get("influx").unwrap().includes_remote("cluster-b"));
+        assert!(!data.get("influx").unwrap().includes_remote("cluster-c"));
+
+        MetricServerEntry::write_section_config("metricserver.cfg", &data).unwrap();
+    }
+}
@@ -29,6 +29,10 @@ use crate::{
             schema: Translation::API_SCHEMA,
             optional: true,
         },
+        "remote-token-max-age": {
+            minimum: 1,
+            optional: true,
+        },
     },
 )]
 #[derive(Deserialize, Serialize, Updater)]
@@ -52,4 +56,8 @@ pub struct NodeConfig {
     /// Default language used in the GUI
     #[serde(skip_serializing_if = "Option::is_none")]
     pub default_lang: Option<String>,
+
+    /// Rotate the API tokens of remotes once they are older than this many days.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub remote_token_max_age: Option<u64>,
 }
@@ -2,6 +2,10 @@ use serde::{Deserialize, Serialize};
 
 use proxmox_schema::api;
 
+use crate::RemoteUpid;
+use crate::remotes::REMOTE_ID_SCHEMA;
+use crate::resource::{FailedRemote, GuestType};
+
 #[api]
 #[derive(Debug, Deserialize, Serialize, PartialEq)]
 #[serde(rename_all = "kebab-case")]
@@ -55,3 +59,223 @@ impl TaskStatus {
         self.status == IsRunning::Running
     }
 }
+
+#[api(
+    properties: {
+        remote: { schema: REMOTE_ID_SCHEMA },
+        "backup-remote": {
+            schema: REMOTE_ID_SCHEMA,
+            optional: true,
+        },
+    },
+)]
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+/// The most recent backup of a PVE guest found on any PBS remote.
+pub struct GuestBackupCoverage {
+    /// The PVE remote of the guest.
+    pub remote: String,
+    /// The resource ID of the guest.
+    pub id: String,
+    /// The VMID of the guest.
+    pub vmid: u32,
+    /// The type of the guest.
+    pub guest_type: GuestType,
+    /// The name of the guest.
+    pub name: String,
+    /// The node the guest is on.
+    pub node: String,
+    /// The time (UNIX epoch) of the last successful backup.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_backup: Option<i64>,
+    /// The PBS remote holding the last backup.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub backup_remote: Option<String>,
+    /// The datastore holding the last backup.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub datastore: Option<String>,
+    /// The namespace holding the last backup, not set for the root namespace.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub namespace: Option<String>,
+    /// Whether the last backup is recent enough.
+    pub covered: bool,
+    /// Whether guests with the same type and VMID exist on multiple PVE remotes, so the backup
+    /// might belong to another guest.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub ambiguous: bool,
+}
+
+#[api(
+    properties: {
+        guests: {
+            type: Array,
+            items: { type: GuestBackupCoverage },
+        },
+        "failed-remotes": {
+            type: Array,
+            items: { type: FailedRemote },
+        },
+    },
+)]
+#[derive(Clone, Default, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+/// Backup coverage of the PVE guests.
+pub struct BackupCoverage {
+    /// The guests with their most recent backup.
+    pub guests: Vec<GuestBackupCoverage>,
+    /// PVE and PBS remotes which could not be queried, the coverage may be incomplete.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub failed_remotes: Vec<FailedRemote>,
+}
+
+#[api]
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+/// An entry in the catalog of a snapshot.
+pub struct CatalogEntry {
+    /// The base64 encoded path of the entry, used to browse and download it.
+    pub filepath: String,
+    /// The name of the entry.
+    pub text: String,
+    /// The type of the entry, e.g. `d` for directories, `f` for files or `v` for archives.
+    #[serde(rename = "type")]
+    pub ty: String,
+    /// Whether the entry has no children.
+    pub leaf: bool,
+    /// The size of a file.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub size: Option<u64>,
+    /// The modification time of a file (UNIX epoch).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mtime: Option<i64>,
+}
+
+#[api]
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[serde(rename_all = "kebab-case")]
+/// The type of a job on a PBS remote.
+pub enum PbsJobType {
+    /// Garbage collection of a datastore.
+    GarbageCollection,
+    /// Prune job.
+    Prune,
+    /// Sync job.
+    Sync,
+    /// Verification job.
+    Verify,
+}
+
+serde_plain::derive_display_from_serialize!(PbsJobType);
+serde_plain::derive_fromstr_from_deserialize!(PbsJobType);
+
+#[api(
+    properties: {
+        remote: { schema: REMOTE_ID_SCHEMA },
+        "last-run-upid": {
+            type: RemoteUpid,
+            optional: true,
+        },
+    },
+)]
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+/// The configuration and state of a job on a PBS remote.
+pub struct PbsJobStatus {
+    /// The PBS remote of the job.
+    pub remote: String,
+    /// The type of the job.
+    pub job_type: PbsJobType,
+    /// The ID of the job, the datastore name for garbage collection.
+    pub id: String,
+    /// The datastore the job operates on.
+    pub store: String,
+    /// The schedule of the job.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub schedule: Option<String>,
+    /// The comment of the job.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub comment: Option<String>,
+    /// The next scheduled run (UNIX epoch).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub next_run: Option<i64>,
+    /// The state of the last run, `OK` on success.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_run_state: Option<String>,
+    /// The end time of the last run (UNIX epoch).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_run_endtime: Option<i64>,
+    /// The task of the last run.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_run_upid: Option<RemoteUpid>,
+}
+
+impl PbsJobStatus {
+    /// Checks if the last run of the job failed. Runs with warnings are not considered failed.
+    pub fn is_failed(&self) -> bool {
+        match self.last_run_state.as_deref() {
+            Some(state) => state != "OK" && !state.starts_with("WARNINGS"),
+            None => false,
+        }
+    }
+}
+
+#[api(
+    properties: {
+        jobs: {
+            type: Array,
+            items: { type: PbsJobStatus },
+        },
+        "failed-remotes": {
+            type: Array,
+            items: { type: FailedRemote },
+        },
+    },
+)]
+#[derive(Clone, Default, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+/// The jobs of the PBS remotes.
+pub struct PbsJobList {
+    /// The jobs.
+    pub jobs: Vec<PbsJobStatus>,
+    /// PBS remotes which could not be queried.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub failed_remotes: Vec<FailedRemote>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn job(last_run_state: Option<&str>) -> PbsJobStatus {
+        PbsJobStatus {
+            remote: "pbs".into(),
+            job_type: PbsJobType::Sync,
+            id: "s-1".into(),
+            store: "store".into(),
+            schedule: None,
+            comment: None,
+            next_run: None,
+            last_run_state: last_run_state.map(String::from),
+            last_run_endtime: None,
+            last_run_upid: None,
+        }
+    }
+
+    #[test]
+    fn job_failed() {
+        assert!(!job(None).is_failed());
+        assert!(!job(Some("OK")).is_failed());
+        assert!(!job(Some("WARNINGS: 2")).is_failed());
+        assert!(job(Some("some error")).is_failed());
+        assert!(job(Some("unknown")).is_failed());
+    }
+
+    #[test]
+    fn job_type_names() {
+        assert_eq!(
+            PbsJobType::GarbageCollection.to_string(),
+            "garbage-collection"
+        );
+        assert_eq!("verify".parse::<PbsJobType>().unwrap(), PbsJobType::Verify);
+    }
+}
@@ -0,0 +1,64 @@
+use serde::{Deserialize, Serialize};
+
+use proxmox_schema::api;
+
+// TODO: There is no pmg-api-types crate yet, these only contain the fields PDM actually uses.
+
+#[api]
+/// An entry of the PMG node index.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub struct NodeIndexEntry {
+    /// The node name.
+    pub node: String,
+}
+
+#[api]
+/// CPU information of a PMG node.
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq)]
+#[serde(default)]
+pub struct NodeCpuInfo {
+    /// Number of logical CPUs.
+    pub cpus: u64,
+
+    /// The CPU model.
+    pub model: String,
+
+    /// Number of CPU sockets.
+    pub sockets: u64,
+}
+
+#[api]
+/// Memory usage of a PMG node.
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq)]
+#[serde(default)]
+pub struct NodeMemoryInfo {
+    /// Total memory in bytes.
+    pub total: u64,
+
+    /// Used memory in bytes.
+    pub used: u64,
+
+    /// Free memory in bytes.
+    pub free: u64,
+}
+
+#[api(
+    properties: {
+        cpuinfo: { type: NodeCpuInfo },
+        memory: { type: NodeMemoryInfo },
+    },
+)]
+/// Status of a PMG node.
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq)]
+#[serde(default)]
+pub struct NodeStatus {
+    /// Current CPU utilization.
+    pub cpu: f64,
+
+    pub cpuinfo: NodeCpuInfo,
+
+    pub memory: NodeMemoryInfo,
+
+    /// Node uptime in seconds.
+    pub uptime: u64,
+}
@@ -0,0 +1,906 @@
+use serde::{Deserialize, Serialize};
+
+use proxmox_schema::property_string::PropertyString;
+use proxmox_schema::{ApiStringFormat, ApiType, Schema, StringSchema, api, const_regex};
+
+use crate::remotes::REMOTE_ID_SCHEMA;
+use crate::resource::{FailedRemote, GuestType};
+use crate::{
+    DNS_NAME_FORMAT, NODE_SCHEMA, PROXMOX_SAFE_ID_FORMAT, PVE_STORAGE_ID_SCHEMA, RemoteUpid,
+    VMID_SCHEMA,
+};
+
+pub const PVE_BACKUP_JOB_ID_SCHEMA: Schema = StringSchema::new("Backup job ID.")
+    .format(&PROXMOX_SAFE_ID_FORMAT)
+    .min_length(3)
+    .max_length(50)
+    .schema();
+
+#[api(
+    properties: {
+        upids: {
+            type: Array,
+            items: { type: RemoteUpid },
+        },
+    },
+)]
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+/// A run of a backup job, made up of the `vzdump` tasks of all nodes.
+pub struct PveBackupJobRun {
+    /// The `vzdump` tasks of the run.
+    pub upids: Vec<RemoteUpid>,
+    /// The start time of the first task (UNIX epoch).
+    pub starttime: i64,
+    /// The end time of the last task (UNIX epoch), not set while tasks are running.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub endtime: Option<i64>,
+    /// The status of the run, the first status of a task which did not end with `OK`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<String>,
+}
+
+impl PveBackupJobRun {
+    /// Checks if a task of the run failed. Tasks with warnings are not considered failed.
+    pub fn is_failed(&self) -> bool {
+        match self.status.as_deref() {
+            Some(status) => status != "OK" && !status.starts_with("WARNINGS"),
+            None => false,
+        }
+    }
+}
+
+#[api(
+    properties: {
+        remote: { schema: REMOTE_ID_SCHEMA },
+        id: { schema: PVE_BACKUP_JOB_ID_SCHEMA },
+        guests: {
+            type: Array,
+            items: {
+                type: Integer,
+                description: "VMID",
+            },
+        },
+        "last-run": {
+            type: PveBackupJobRun,
+            optional: true,
+        },
+    },
+)]
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+/// A backup job of a PVE remote.
+pub struct PveBackupJob {
+    /// The PVE remote of the job.
+    pub remote: String,
+    /// The ID of the job.
+    pub id: String,
+    /// Whether the job is enabled.
+    pub enabled: bool,
+    /// The schedule of the job.
+    pub schedule: String,
+    /// The target storage.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub storage: Option<String>,
+    /// Only guests on this node are backed up.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub node: Option<String>,
+    /// Whether all guests are backed up.
+    #[serde(default)]
+    pub all: bool,
+    /// The guests selected by VMID.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub vmid: Option<String>,
+    /// The pool whose guests are backed up.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pool: Option<String>,
+    /// The guests excluded when backing up all guests.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exclude: Option<String>,
+    /// The comment of the job.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub comment: Option<String>,
+    /// The next scheduled run (UNIX epoch).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub next_run: Option<i64>,
+    /// The guests currently targeted by the job.
+    pub guests: Vec<u32>,
+    /// The last run of the job found in the remote task cache.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_run: Option<PveBackupJobRun>,
+}
+
+#[api(
+    properties: {
+        remote: { schema: REMOTE_ID_SCHEMA },
+    },
+)]
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+/// A PVE guest which is not targeted by any enabled backup job.
+pub struct PveUncoveredGuest {
+    /// The PVE remote of the guest.
+    pub remote: String,
+    /// The VMID of the guest.
+    pub vmid: u32,
+    /// The type of the guest.
+    pub guest_type: GuestType,
+    /// The name of the guest.
+    pub name: String,
+    /// The node the guest is on.
+    pub node: String,
+}
+
+#[api(
+    properties: {
+        jobs: {
+            type: Array,
+            items: { type: PveBackupJob },
+        },
+        "uncovered-guests": {
+            type: Array,
+            items: { type: PveUncoveredGuest },
+        },
+        "failed-remotes": {
+            type: Array,
+            items: { type: FailedRemote },
+        },
+    },
+)]
+#[derive(Clone, Default, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+/// The backup jobs of the PVE remotes.
+pub struct PveBackupJobList {
+    /// The backup jobs.
+    pub jobs: Vec<PveBackupJob>,
+    /// The guests not targeted by any enabled backup job.
+    pub uncovered_guests: Vec<PveUncoveredGuest>,
+    /// PVE remotes which could not be queried.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub failed_remotes: Vec<FailedRemote>,
+}
+
+const_regex! {
+    PVE_CONFIG_DIGEST_REGEX = r"^[a-f0-9]{40}$";
+    GUEST_NET_ID_REGEX = r"^net\d+$";
+    GUEST_BRIDGE_REGEX = r"^[a-zA-Z][a-zA-Z0-9_.-]{0,14}$";
+    GUEST_DISK_ID_REGEX = r"^(?:(?:ide|sata|scsi|virtio|mp)\d+|rootfs)$";
+    GUEST_DISK_SIZE_REGEX = r"^\+?\d+(?:\.\d+)?[KMGT]?$";
+    GUEST_TAG_REGEX = r"^(?i)[a-z0-9_][a-z0-9_\-+.]*$";
+    GUEST_TAG_LIST_REGEX = r"^(?i)[a-z0-9_][a-z0-9_\-+.]*(?:[;, ]+[a-z0-9_][a-z0-9_\-+.]*)*[;, ]*$";
+}
+
+pub const PVE_CONFIG_DIGEST_SCHEMA: Schema = StringSchema::new(
+    "Prevent changes if the current configuration file has a different SHA1 digest.",
+)
+.format(&ApiStringFormat::Pattern(&PVE_CONFIG_DIGEST_REGEX))
+.schema();
+
+pub const GUEST_NET_ID_SCHEMA: Schema = StringSchema::new("Network device, for example 'net0'.")
+    .format(&ApiStringFormat::Pattern(&GUEST_NET_ID_REGEX))
+    .schema();
+
+pub const GUEST_BRIDGE_SCHEMA: Schema =
+    StringSchema::new("The bridge to attach the network device to.")
+        .format(&ApiStringFormat::Pattern(&GUEST_BRIDGE_REGEX))
+        .schema();
+
+pub const GUEST_TAG_LIST_SCHEMA: Schema =
+    StringSchema::new("The tags of the guest, separated by ';', ',' or spaces.")
+        .format(&ApiStringFormat::Pattern(&GUEST_TAG_LIST_REGEX))
+        .max_length(8192)
+        .schema();
+
+pub const GUEST_DISK_ID_SCHEMA: Schema =
+    StringSchema::new("Disk or mount point, for example 'scsi0' or 'rootfs'.")
+        .format(&ApiStringFormat::Pattern(&GUEST_DISK_ID_REGEX))
+        .schema();
+
+pub const GUEST_DISK_SIZE_SCHEMA: Schema = StringSchema::new(
+    "The new size. With a leading '+' the size is added to the current size, \
+    an optional unit suffix (K, M, G or T) is supported.",
+)
+.format(&ApiStringFormat::Pattern(&GUEST_DISK_SIZE_REGEX))
+.schema();
+
+#[api(
+    properties: {
+        id: { schema: GUEST_NET_ID_SCHEMA },
+        bridge: {
+            schema: GUEST_BRIDGE_SCHEMA,
+            optional: true,
+        },
+        tag: {
+            type: Integer,
+            optional: true,
+            minimum: 0,
+            maximum: 4094,
+        },
+        rate: {
+            type: Number,
+            optional: true,
+            minimum: 0.0,
+        },
+    },
+    default_key: "id",
+)]
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+/// Changes to a network device of a guest. Properties not set are left unchanged.
+pub struct GuestNetUpdate {
+    /// The network device.
+    pub id: String,
+    /// The bridge to attach the network device to.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bridge: Option<String>,
+    /// The VLAN tag, `0` removes the tag.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tag: Option<u16>,
+    /// The rate limit in MB/s, `0` removes the limit.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rate: Option<f64>,
+}
+
+#[api(
+    properties: {
+        disk: { schema: GUEST_DISK_ID_SCHEMA },
+        size: { schema: GUEST_DISK_SIZE_SCHEMA },
+    },
+    default_key: "disk",
+)]
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+/// Resize a disk of a guest. Disks can only grow.
+pub struct GuestDiskResize {
+    /// The disk to resize.
+    pub disk: String,
+    /// The new size of the disk.
+    pub size: String,
+}
+
+#[api]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+/// Properties of [`GuestConfigUpdate`] which can be deleted or whose pending change can be
+/// reverted.
+pub enum DeletableGuestConfigProperty {
+    /// The number of cores per socket.
+    Cores,
+    /// The number of CPU sockets, only for VMs.
+    Sockets,
+    /// The amount of memory.
+    Memory,
+    /// The minimum amount of memory for the balloon device, only for VMs.
+    Balloon,
+    /// The description of the guest.
+    Description,
+    /// The tags of the guest.
+    Tags,
+    /// Whether the guest is started on boot.
+    Onboot,
+}
+
+serde_plain::derive_display_from_serialize!(DeletableGuestConfigProperty);
+serde_plain::derive_fromstr_from_deserialize!(DeletableGuestConfigProperty);
+
+#[api(
+    properties: {
+        cores: {
+            type: Integer,
+            optional: true,
+            minimum: 1,
+            maximum: 8192,
+        },
+        sockets: {
+            type: Integer,
+            optional: true,
+            minimum: 1,
+            maximum: 16,
+        },
+        memory: {
+            type: Integer,
+            optional: true,
+            minimum: 16,
+        },
+        balloon: {
+            type: Integer,
+            optional: true,
+            minimum: 0,
+        },
+        tags: {
+            schema: GUEST_TAG_LIST_SCHEMA,
+            optional: true,
+        },
+        net: {
+            type: Array,
+            optional: true,
+            items: {
+                type: String,
+                description: "Changes to a network device.",
+                format: &ApiStringFormat::PropertyString(&GuestNetUpdate::API_SCHEMA),
+            },
+        },
+        resize: {
+            type: Array,
+            optional: true,
+            items: {
+                type: String,
+                description: "A disk to resize.",
+                format: &ApiStringFormat::PropertyString(&GuestDiskResize::API_SCHEMA),
+            },
+        },
+        delete: {
+            type: Array,
+            optional: true,
+            items: { type: DeletableGuestConfigProperty },
+        },
+        revert: {
+            type: Array,
+            optional: true,
+            items: { type: DeletableGuestConfigProperty },
+        },
+    },
+)]
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+/// The commonly edited subset of a guest configuration.
+///
+/// Only set properties are changed. Changes which cannot be applied to a running guest become
+/// pending changes, just like in PVE.
+pub struct GuestConfigUpdate {
+    /// The number of cores per socket.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cores: Option<u32>,
+    /// The number of CPU sockets, only for VMs.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sockets: Option<u32>,
+    /// The amount of memory in MiB.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub memory: Option<u64>,
+    /// The minimum amount of memory in MiB for the balloon device, `0` disables it. Only for VMs.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub balloon: Option<u64>,
+    /// The description of the guest.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// The tags of the guest, separated by `;`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tags: Option<String>,
+    /// Whether the guest is started on boot.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub onboot: Option<bool>,
+    /// Changes to network devices.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub net: Option<Vec<PropertyString<GuestNetUpdate>>>,
+    /// Disks to resize.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resize: Option<Vec<PropertyString<GuestDiskResize>>>,
+    /// Configuration keys to delete.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub delete: Option<Vec<DeletableGuestConfigProperty>>,
+    /// Configuration keys whose pending changes are reverted.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub revert: Option<Vec<DeletableGuestConfigProperty>>,
+}
+
+#[api]
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+/// A change of a single configuration key.
+pub struct GuestConfigChange {
+    /// The configuration key.
+    pub key: String,
+    /// The value before the change, including pending changes.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub old: Option<String>,
+    /// The value after the change, not set if the key is deleted.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub new: Option<String>,
+    /// Whether the change is pending until the guest is restarted.
+    #[serde(default)]
+    pub pending: bool,
+}
+
+#[api(
+    properties: {
+        changes: {
+            type: Array,
+            items: { type: GuestConfigChange },
+        },
+        tasks: {
+            type: Array,
+            items: { type: RemoteUpid },
+        },
+    },
+)]
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+/// The result of a guest configuration update.
+pub struct GuestConfigUpdateResult {
+    /// The digest of the configuration the changes were computed from.
+    pub digest: String,
+    /// The changes, only a preview if the update was not applied.
+    pub changes: Vec<GuestConfigChange>,
+    /// The tasks started for resizing disks.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tasks: Vec<RemoteUpid>,
+}
+
+pub const GUEST_TAG_SCHEMA: Schema = StringSchema::new("A guest tag.")
+    .format(&ApiStringFormat::Pattern(&GUEST_TAG_REGEX))
+    .min_length(1)
+    .max_length(128)
+    .schema();
+
+#[api]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+/// A change to the tags of guests.
+pub enum GuestTagAction {
+    /// Add the tag.
+    Add,
+    /// Remove the tag.
+    Remove,
+    /// Replace the tag with a new tag.
+    Rename,
+}
+
+serde_plain::derive_display_from_serialize!(GuestTagAction);
+serde_plain::derive_fromstr_from_deserialize!(GuestTagAction);
+
+#[api(
+    properties: {
+        tag: { schema: GUEST_TAG_SCHEMA },
+        "new-tag": {
+            schema: GUEST_TAG_SCHEMA,
+            optional: true,
+        },
+        guests: {
+            type: Array,
+            optional: true,
+            items: {
+                type: String,
+                description: "The resource ID of a guest, for example 'remote/pve/guest/100'.",
+            },
+        },
+        search: {
+            type: String,
+            optional: true,
+        },
+        "max-connections": {
+            type: Integer,
+            optional: true,
+            minimum: 1,
+            maximum: 64,
+        },
+        "max-connections-per-remote": {
+            type: Integer,
+            optional: true,
+            minimum: 1,
+            maximum: 64,
+        },
+    },
+)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+/// Parameters for changing the tags of many guests across remotes.
+///
+/// Without `guests` or `search`, removing and renaming affect all guests carrying the tag.
+/// Renaming a tag also updates the tag rules of views.
+pub struct BulkGuestTagParams {
+    /// The change to apply.
+    pub action: GuestTagAction,
+    /// The tag to add, remove or rename.
+    pub tag: String,
+    /// The new name of the tag, required for renaming.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub new_tag: Option<String>,
+    /// The guests to change.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub guests: Option<Vec<String>>,
+    /// Change all guests matching this search expression.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub search: Option<String>,
+    /// Maximum number of parallel requests to all remotes.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_connections: Option<usize>,
+    /// Maximum number of parallel requests to a single remote.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_connections_per_remote: Option<usize>,
+}
+
+#[api(
+    properties: {
+        description: {
+            type: String,
+            max_length: 8192,
+        },
+        append: {
+            type: bool,
+            optional: true,
+            default: false,
+        },
+        guests: {
+            type: Array,
+            optional: true,
+            items: {
+                type: String,
+                description: "The resource ID of a guest, for example 'remote/pve/guest/100'.",
+            },
+        },
+        search: {
+            type: String,
+            optional: true,
+        },
+        "max-connections": {
+            type: Integer,
+            optional: true,
+            minimum: 1,
+            maximum: 64,
+        },
+        "max-connections-per-remote": {
+            type: Integer,
+            optional: true,
+            minimum: 1,
+            maximum: 64,
+        },
+    },
+)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+/// Parameters for changing the description of many guests across remotes.
+///
+/// Either `guests` or `search` has to be set.
+pub struct BulkGuestDescriptionParams {
+    /// The new description, an empty description removes it.
+    pub description: String,
+    /// Append the description to the existing one on a new line instead of replacing it.
+    #[serde(default)]
+    pub append: bool,
+    /// The guests to change.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub guests: Option<Vec<String>>,
+    /// Change all guests matching this search expression.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub search: Option<String>,
+    /// Maximum number of parallel requests to all remotes.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_connections: Option<usize>,
+    /// Maximum number of parallel requests to a single remote.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_connections_per_remote: Option<usize>,
+}
+
+#[api(
+    properties: {
+        remotes: {
+            type: Array,
+            items: { schema: REMOTE_ID_SCHEMA },
+        },
+    },
+)]
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+/// The usage of a guest tag.
+pub struct GuestTagUsage {
+    /// The tag.
+    pub tag: String,
+    /// The number of guests carrying the tag.
+    pub count: u64,
+    /// The remotes with guests carrying the tag.
+    pub remotes: Vec<String>,
+}
+
+#[api(
+    properties: {
+        template: { schema: VMID_SCHEMA },
+        target: {
+            schema: NODE_SCHEMA,
+            optional: true,
+        },
+        newid: {
+            schema: VMID_SCHEMA,
+            optional: true,
+        },
+        name: {
+            type: String,
+            optional: true,
+            format: &DNS_NAME_FORMAT,
+        },
+        storage: {
+            schema: PVE_STORAGE_ID_SCHEMA,
+            optional: true,
+        },
+        full: {
+            type: bool,
+            optional: true,
+            default: false,
+        },
+        ciuser: {
+            type: String,
+            optional: true,
+        },
+        sshkeys: {
+            type: String,
+            optional: true,
+        },
+        ipconfig: {
+            type: String,
+            optional: true,
+        },
+        start: {
+            type: bool,
+            optional: true,
+            default: false,
+        },
+    },
+)]
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+/// Parameters for deploying a new guest from a template.
+pub struct DeployTemplateParams {
+    /// The VMID of the template.
+    pub template: u32,
+    /// The node to create the guest on, defaults to the node of the template.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target: Option<String>,
+    /// The VMID of the new guest, defaults to the next free VMID of the cluster.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub newid: Option<u32>,
+    /// The name of the new guest, the hostname for containers.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// The target storage for a full clone.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub storage: Option<String>,
+    /// Create a full clone instead of a linked clone.
+    #[serde(default)]
+    pub full: bool,
+    /// The cloud-init user, only for VMs.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ciuser: Option<String>,
+    /// Public SSH keys for cloud-init, one key per line. Only for VMs.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sshkeys: Option<String>,
+    /// The cloud-init IP configuration of the first network device, for example
+    /// `ip=dhcp` or `ip=192.0.2.10/24,gw=192.0.2.1`. Only for VMs.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ipconfig: Option<String>,
+    /// Start the guest once it is deployed.
+    #[serde(default)]
+    pub start: bool,
+}
+
+impl DeployTemplateParams {
+    /// Checks if any cloud-init option is set.
+    pub fn has_cloud_init(&self) -> bool {
+        self.ciuser.is_some() || self.sshkeys.is_some() || self.ipconfig.is_some()
+    }
+}
+
+#[api(
+    default_key: "vmid",
+    properties: {
+        vmid: { schema: VMID_SCHEMA },
+        "guest-type": { type: GuestType },
+        target: { schema: NODE_SCHEMA },
+    },
+)]
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+/// A single migration of a node evacuation.
+pub struct EvacuationMigration {
+    /// The guest to migrate.
+    pub vmid: u32,
+    /// The type of the guest.
+    pub guest_type: GuestType,
+    /// The node to migrate the guest to.
+    pub target: String,
+}
+
+#[api]
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+/// The projected load of a node once all planned migrations are done.
+pub struct EvacuationNodeLoad {
+    /// The node.
+    pub node: String,
+    /// Projected CPU usage, as fraction of `maxcpu`.
+    pub cpu: f64,
+    /// Number of CPUs of the node.
+    pub maxcpu: f64,
+    /// Projected memory usage in bytes.
+    pub mem: u64,
+    /// Memory of the node in bytes.
+    pub maxmem: u64,
+}
+
+#[api(
+    properties: {
+        remote: { schema: REMOTE_ID_SCHEMA },
+        node: { schema: NODE_SCHEMA },
+        migrations: {
+            type: Array,
+            items: { type: EvacuationMigration },
+        },
+        unplaced: {
+            type: Array,
+            items: { schema: VMID_SCHEMA },
+        },
+        targets: {
+            type: Array,
+            items: { type: EvacuationNodeLoad },
+        },
+    },
+)]
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+/// A placement plan for moving all guests off a node.
+pub struct EvacuationPlan {
+    /// The remote of the node.
+    pub remote: String,
+    /// The node to evacuate.
+    pub node: String,
+    /// The planned migrations, largest guests first.
+    pub migrations: Vec<EvacuationMigration>,
+    /// Guests which do not fit on any other node.
+    pub unplaced: Vec<u32>,
+    /// The projected load of the target nodes.
+    pub targets: Vec<EvacuationNodeLoad>,
+}
+
+#[api(
+    properties: {
+        migrations: {
+            type: Array,
+            optional: true,
+            items: {
+                type: String,
+                description: "A migration of the plan.",
+                format: &ApiStringFormat::PropertyString(&EvacuationMigration::API_SCHEMA),
+            },
+        },
+        "max-parallel": {
+            type: Integer,
+            optional: true,
+            minimum: 1,
+            maximum: 16,
+        },
+        bwlimit: {
+            type: Integer,
+            optional: true,
+            minimum: 1,
+        },
+        retries: {
+            type: Integer,
+            optional: true,
+            minimum: 0,
+            maximum: 5,
+        },
+    },
+)]
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+/// Parameters for evacuating a node.
+pub struct EvacuateNodeParams {
+    /// The migrations to run, defaults to the computed plan.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub migrations: Option<Vec<PropertyString<EvacuationMigration>>>,
+    /// Maximum number of migrations running at the same time, defaults to 1.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_parallel: Option<usize>,
+    /// I/O bandwidth limit of every migration in KiB/s.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bwlimit: Option<u64>,
+    /// How often a failed migration is retried before it is skipped, defaults to 1.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retries: Option<u8>,
+}
+
+#[api]
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+/// The averaged load of a node before and after a recommended migration.
+pub struct BalancingNodeLoad {
+    /// The node.
+    pub node: String,
+    /// Average CPU usage, as fraction of the CPUs of the node.
+    pub cpu: f64,
+    /// Average memory usage, as fraction of the memory of the node.
+    pub mem: f64,
+    /// Projected CPU usage after the migration.
+    pub projected_cpu: f64,
+    /// Projected memory usage after the migration.
+    pub projected_mem: f64,
+}
+
+#[api(
+    properties: {
+        remote: { schema: REMOTE_ID_SCHEMA },
+        vmid: { schema: VMID_SCHEMA },
+        "guest-type": { type: GuestType },
+        "target-remote": { schema: REMOTE_ID_SCHEMA },
+        source: { type: BalancingNodeLoad },
+        target: { type: BalancingNodeLoad },
+    },
+)]
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+/// A recommended guest migration to balance the load of nodes.
+pub struct BalancingRecommendation {
+    /// The remote of the guest.
+    pub remote: String,
+    /// The guest to migrate.
+    pub vmid: u32,
+    /// The type of the guest.
+    pub guest_type: GuestType,
+    /// The name of the guest.
+    pub name: String,
+    /// Average CPU usage of the guest, in number of CPUs.
+    pub guest_cpu: f64,
+    /// Average memory usage of the guest in bytes.
+    pub guest_mem: u64,
+    /// The remote to migrate the guest to, differs from `remote` for remote migrations.
+    pub target_remote: String,
+    /// The endpoint of the target node, only set for remote migrations.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target_endpoint: Option<String>,
+    /// The load of the node the guest currently runs on.
+    pub source: BalancingNodeLoad,
+    /// The load of the node the guest is migrated to.
+    pub target: BalancingNodeLoad,
+    /// Why the migration is recommended.
+    pub reason: String,
+}
+
+impl BalancingRecommendation {
+    /// Checks if the guest is migrated to another remote.
+    pub fn is_remote_migration(&self) -> bool {
+        self.remote != self.target_remote
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn guest_config_schemas() {
+        for bridge in ["vmbr0", "vmbr0.100", "my_bridge-1", "abcdefghijklmno"] {
+            assert!(
+                GUEST_BRIDGE_SCHEMA.parse_simple_value(bridge).is_ok(),
+                "{bridge}"
+            );
+        }
+        for bridge in ["", "0vmbr", "vmbr0,tag=5", "abcdefghijklmnop"] {
+            assert!(
+                GUEST_BRIDGE_SCHEMA.parse_simple_value(bridge).is_err(),
+                "{bridge}"
+            );
+        }
+
+        for tags in ["prod", "prod;web", "prod, web db", "a+b.c_d-e;"] {
+            assert!(
+                GUEST_TAG_LIST_SCHEMA.parse_simple_value(tags).is_ok(),
+                "{tags}"
+            );
+        }
+        for tags in ["", ";prod", "prod;-web", "prod\nonboot: 1", "web=1"] {
+            assert!(
+                GUEST_TAG_LIST_SCHEMA.parse_simple_value(tags).is_err(),
+                "{tags}"
+            );
+        }
+
+        for disk in ["scsi0", "virtio15", "mp3", "rootfs"] {
+            assert!(
+                GUEST_DISK_ID_SCHEMA.parse_simple_value(disk).is_ok(),
+                "{disk}"
+            );
+        }
+        for disk in ["efidisk0", "tpmstate0", "unused0", "scsi"] {
+            assert!(
+                GUEST_DISK_ID_SCHEMA.parse_simple_value(disk).is_err(),
+                "{disk}"
+            );
+        }
+    }
+}
@@ -17,6 +17,7 @@ pub struct RemoteUpid {
     remote: String,
     remote_type: RemoteType,
     // This can either be a PVE UPID or a PBS UPID, both have distinct, incompatible formats.
+    // PMG uses the same format as PVE, so its type cannot be deduced from the UPID alone.
     upid: String,
 }
 
@@ -24,6 +25,7 @@ pub struct RemoteUpid {
 pub enum NativeUpid {
     PveUpid(pve_api_types::PveUpid),
     PbsUpid(pbs_api_types::UPID),
+    PmgUpid(pve_api_types::PveUpid),
 }
 
 impl NativeUpid {
@@ -32,6 +34,16 @@ impl NativeUpid {
         match self {
             NativeUpid::PveUpid(upid) => upid.node.as_str(),
             NativeUpid::PbsUpid(upid) => upid.node.as_str(),
+            NativeUpid::PmgUpid(upid) => upid.node.as_str(),
+        }
+    }
+
+    /// Convenience getter to query the 'worker_type' property of a task.
+    pub fn worker_type(&self) -> &str {
+        match self {
+            NativeUpid::PveUpid(upid) => upid.worker_type.as_str(),
+            NativeUpid::PbsUpid(upid) => upid.worker_type.as_str(),
+            NativeUpid::PmgUpid(upid) => upid.worker_type.as_str(),
         }
     }
 }
@@ -77,28 +89,43 @@ impl RemoteUpid {
         Ok(match self.remote_type() {
             RemoteType::Pve => NativeUpid::PveUpid(self.upid.parse()?),
             RemoteType::Pbs => NativeUpid::PbsUpid(self.upid.parse()?),
+            RemoteType::Pmg => NativeUpid::PmgUpid(self.upid.parse()?),
         })
     }
 
     /// Get the parsed PVE UPID.
     ///
-    /// If the UPID could not be parsed, or has an unexpected format (PBS),
+    /// If the UPID could not be parsed, or has an unexpected format (PBS, PMG),
     /// an error is returned.
     pub fn pve_upid(&self) -> Result<pve_api_types::PveUpid, Error> {
         match self.native_upid()? {
             NativeUpid::PveUpid(pve_upid) => Ok(pve_upid),
             NativeUpid::PbsUpid(_) => bail!("got a PBS UPID when expecting a PVE UPID"),
+            NativeUpid::PmgUpid(_) => bail!("got a PMG UPID when expecting a PVE UPID"),
         }
     }
 
     /// Get the parsed PBS UPID.
     ///
-    /// If the UPID could not be parsed, or has an unexpected format (PVE),
+    /// If the UPID could not be parsed, or has an unexpected format (PVE, PMG),
     /// an error is returned.
     pub fn pbs_upid(&self) -> Result<pbs_api_types::UPID, Error> {
         match self.native_upid()? {
             NativeUpid::PveUpid(_) => bail!("got a PVE UPID when expecting a PBS UPID"),
             NativeUpid::PbsUpid(pbs_upid) => Ok(pbs_upid),
+            NativeUpid::PmgUpid(_) => bail!("got a PMG UPID when expecting a PBS UPID"),
+        }
+    }
+
+    /// Get the parsed PMG UPID.
+    ///
+    /// If the UPID could not be parsed, or has an unexpected format (PVE, PBS),
+    /// an error is returned.
+    pub fn pmg_upid(&self) -> Result<pve_api_types::PveUpid, Error> {
+        match self.native_upid()? {
+            NativeUpid::PveUpid(_) => bail!("got a PVE UPID when expecting a PMG UPID"),
+            NativeUpid::PbsUpid(_) => bail!("got a PBS UPID when expecting a PMG UPID"),
+            NativeUpid::PmgUpid(pmg_upid) => Ok(pmg_upid),
         }
     }
 
@@ -294,4 +321,42 @@ mod tests {
             "pbs:pbs-remote!UPID:pbs:000002B2:00000158:00000000:674D828C:logrotate::root@pam:"
         );
     }
+
+    #[test]
+    fn test_pmg_upid() {
+        let pmg_upid: RemoteUpid =
+            "pmg:pmg-remote!UPID:pmg:0000A1B2:00C0FFEE:68F1E2D3:aptupdate::root@pam:"
+                .parse()
+                .unwrap();
+
+        assert_eq!(pmg_upid.remote(), "pmg-remote");
+        assert_eq!(pmg_upid.remote_type(), RemoteType::Pmg);
+        assert_eq!(pmg_upid.pmg_upid().unwrap().node, "pmg");
+        assert!(pmg_upid.pve_upid().is_err());
+
+        assert_eq!(
+            pmg_upid.to_string(),
+            "pmg:pmg-remote!UPID:pmg:0000A1B2:00C0FFEE:68F1E2D3:aptupdate::root@pam:"
+        );
+    }
+
+    #[test]
+    fn test_native_upid_fields() {
+        let pve_upid: RemoteUpid =
+            "pve:pve-remote!UPID:pve1:00039E4D:002638B8:67B4A9D1:stopall::root@pam:"
+                .parse()
+                .unwrap();
+        let native = pve_upid.native_upid().unwrap();
+        assert_eq!(native.node(), "pve1");
+        assert_eq!(native.worker_type(), "stopall");
+
+        // PBS UPIDs have an additional task id field
+        let pbs_upid: RemoteUpid =
+            "pbs:pbs-remote!UPID:pbs1:000002B2:00000158:00000000:674D828C:logrotate::root@pam:"
+                .parse()
+                .unwrap();
+        let native = pbs_upid.native_upid().unwrap();
+        assert_eq!(native.node(), "pbs1");
+        assert_eq!(native.worker_type(), "logrotate");
+    }
 }
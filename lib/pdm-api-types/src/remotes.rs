@@ -1,21 +1,68 @@
 use std::sync::OnceLock;
 
+use const_format::concatcp;
 use http::Uri;
 use serde::{Deserialize, Serialize};
 
+use proxmox_schema::api_types::SAFE_ID_REGEX_STR;
 use proxmox_schema::property_string::PropertyString;
-use proxmox_schema::{ApiType, Schema, StringSchema, Updater, api};
+use proxmox_schema::{ApiStringFormat, ApiType, Schema, StringSchema, Updater, api, const_regex};
 use proxmox_section_config::typed::ApiSectionDataEntry;
 use proxmox_section_config::{SectionConfig, SectionConfigPlugin};
 
-use crate::{Authid, HOST_OPTIONAL_PORT_FORMAT};
+use crate::{Authid, HOST_OPTIONAL_PORT_FORMAT, REMOTE_PROXY_SCHEMA};
 
 pub const REMOTE_ID_SCHEMA: Schema = StringSchema::new("Remote ID.")
-    .format(&crate::PROXMOX_SAFE_ID_FORMAT)
+    .format(&ApiStringFormat::VerifyFn(verify_remote_id))
     .min_length(2)
     .max_length(32)
     .schema();
 
+/// Remote IDs are safe IDs, except for the ACL path component holding the folders.
+fn verify_remote_id(id: &str) -> Result<(), anyhow::Error> {
+    if !crate::PROXMOX_SAFE_ID_REGEX.is_match(id) {
+        anyhow::bail!("value does not match the regex pattern");
+    }
+    if id == REMOTE_FOLDER_ACL_COMPONENT {
+        anyhow::bail!("'{id}' is reserved and can not be used as remote ID");
+    }
+    Ok(())
+}
+
+const_regex! {
+    /// Regex for folder paths, nested folders are separated by slashes.
+    pub REMOTE_FOLDER_REGEX = concatcp!(r"^", SAFE_ID_REGEX_STR, r"(?:/", SAFE_ID_REGEX_STR, r")*$");
+}
+
+pub const REMOTE_FOLDER_FORMAT: ApiStringFormat = ApiStringFormat::Pattern(&REMOTE_FOLDER_REGEX);
+
+pub const REMOTE_FOLDER_SCHEMA: Schema =
+    StringSchema::new("Folder path, nested folders are separated by '/', e.g. 'site-a/rack1'.")
+        .format(&REMOTE_FOLDER_FORMAT)
+        .max_length(128)
+        .schema();
+
+/// The ACL path component below `/resource` which holds the folders.
+///
+/// This can not be used as a remote ID.
+pub const REMOTE_FOLDER_ACL_COMPONENT: &str = "folder";
+
+/// The ACL path of a folder, e.g. `["resource", "folder", "site-a", "rack1"]`.
+pub fn folder_acl_path(folder: &str) -> Vec<&str> {
+    let mut path = vec!["resource", REMOTE_FOLDER_ACL_COMPONENT];
+    path.extend(folder.split('/'));
+    path
+}
+
+/// Iterate over a folder and all of its parent folders, starting with the folder itself.
+///
+/// For `site-a/rack1` this yields `site-a/rack1` and `site-a`.
+pub fn folder_and_parents(folder: &str) -> impl Iterator<Item = &str> {
+    std::iter::successors(Some(folder), |folder| {
+        folder.rsplit_once('/').map(|(parent, _)| parent)
+    })
+}
+
 #[api(
     properties: {
         hostname: {
@@ -52,6 +99,8 @@ pub enum RemoteType {
     Pve,
     /// A Proxmox Backup Server node.
     Pbs,
+    /// A Proxmox Mail Gateway node.
+    Pmg,
 }
 
 impl RemoteType {
@@ -59,6 +108,7 @@ impl RemoteType {
         match self {
             RemoteType::Pve => 8006,
             RemoteType::Pbs => 8007,
+            RemoteType::Pmg => 8006,
         }
     }
 }
@@ -81,6 +131,24 @@ serde_plain::derive_fromstr_from_deserialize!(RemoteType);
             type: String,
             optional: true,
         },
+        folder: {
+            schema: REMOTE_FOLDER_SCHEMA,
+            optional: true,
+        },
+        timeout: {
+            minimum: 1,
+            maximum: 3600,
+            optional: true,
+        },
+        "http-proxy": {
+            schema: REMOTE_PROXY_SCHEMA,
+            optional: true,
+        },
+        "max-connections": {
+            minimum: 1,
+            maximum: 64,
+            optional: true,
+        },
     },
 )]
 /// The information required to connect to a remote instance.
@@ -120,6 +188,46 @@ pub struct Remote {
         skip_serializing_if = "Option::is_none"
     )]
     pub web_url: Option<Uri>,
+
+    /// The folder the remote is placed in. Privileges on the folder apply to the remote.
+    #[updater(serde(skip_serializing_if = "Option::is_none"))]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub folder: Option<String>,
+
+    /// Timeout for API requests to the remote in seconds. Defaults to 60.
+    #[updater(serde(skip_serializing_if = "Option::is_none"))]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout: Option<u64>,
+
+    /// HTTP or SOCKS5 proxy used to connect to the remote instead of connecting directly.
+    #[updater(serde(skip_serializing_if = "Option::is_none"))]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub http_proxy: Option<String>,
+
+    /// Maximum number of concurrent API requests to the remote.
+    #[updater(serde(skip_serializing_if = "Option::is_none"))]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_connections: Option<u64>,
+
+    /// Time the API token was added or last rotated (UNIX epoch).
+    #[updater(skip)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token_created: Option<i64>,
+}
+
+impl Remote {
+    /// The age of the API token in seconds, if known.
+    pub fn token_age(&self, now: i64) -> Option<i64> {
+        self.token_created.map(|created| now - created)
+    }
+
+    /// Check whether the remote is placed in `folder` or one of its subfolders.
+    pub fn in_folder(&self, folder: &str) -> bool {
+        match &self.folder {
+            Some(own) => folder_and_parents(own).any(|f| f == folder),
+            None => false,
+        }
+    }
 }
 
 impl ApiSectionDataEntry for Remote {
@@ -132,7 +240,7 @@ impl ApiSectionDataEntry for Remote {
 
         CONFIG.get_or_init(|| {
             let mut this = SectionConfig::new(&REMOTE_ID_SCHEMA).with_type_key("type");
-            for ty in ["pve", "pbs"] {
+            for ty in ["pve", "pbs", "pmg"] {
                 this.register_plugin(SectionConfigPlugin::new(
                     ty.to_string(),
                     Some("id".to_string()),
@@ -148,6 +256,7 @@ impl ApiSectionDataEntry for Remote {
         match self.ty {
             RemoteType::Pve => "pve",
             RemoteType::Pbs => "pbs",
+            RemoteType::Pmg => "pmg",
         }
     }
 }
@@ -182,7 +291,7 @@ impl ApiSectionDataEntry for RemoteShadow {
 
         CONFIG.get_or_init(|| {
             let mut this = SectionConfig::new(&REMOTE_ID_SCHEMA).with_type_key("type");
-            for ty in ["pve", "pbs"] {
+            for ty in ["pve", "pbs", "pmg"] {
                 this.register_plugin(SectionConfigPlugin::new(
                     ty.to_string(),
                     Some("id".to_string()),
@@ -198,6 +307,7 @@ impl ApiSectionDataEntry for RemoteShadow {
         match self.ty {
             RemoteType::Pve => "pve",
             RemoteType::Pbs => "pbs",
+            RemoteType::Pmg => "pmg",
         }
     }
 }
@@ -258,3 +368,248 @@ pub struct RemoteListEntry {
     /// An id for this entry.
     pub remote: String,
 }
+
+#[api(
+    properties: {
+        "type": { type: RemoteType },
+        nodes: {
+            type: Array,
+            items: { type: NodeUrl },
+        },
+        authid: { type: Authid },
+        secret: {
+            type: String,
+            description: "The secret of the join token.",
+        },
+        expire: {
+            type: Integer,
+            optional: true,
+        },
+    },
+)]
+/// Everything needed to add a remote in one step, pasted as base64 encoded or plain JSON.
+///
+/// The join token is a short-lived API token on the remote, which is only used to create the
+/// actual API token of the remote.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub struct RemoteJoinInfo {
+    #[serde(rename = "type")]
+    pub ty: RemoteType,
+
+    /// The node addresses together with their certificate fingerprints.
+    pub nodes: Vec<NodeUrl>,
+
+    /// The ID of the join token.
+    pub authid: Authid,
+
+    pub secret: String,
+
+    /// Expiration time of the join token (UNIX epoch).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expire: Option<i64>,
+}
+
+impl RemoteJoinInfo {
+    /// Decode join information from base64 encoded or plain JSON.
+    pub fn decode(blob: &str) -> Result<Self, anyhow::Error> {
+        let blob = blob.trim();
+        let json = if blob.starts_with('{') {
+            blob.as_bytes().to_vec()
+        } else {
+            // pasting may break long lines
+            let blob: String = blob.split_whitespace().collect();
+            proxmox_base64::decode(&blob)
+                .map_err(|err| anyhow::format_err!("invalid base64 encoding - {err}"))?
+        };
+
+        let value: serde_json::Value = serde_json::from_slice(&json)
+            .map_err(|err| anyhow::format_err!("invalid join information - {err}"))?;
+        Self::API_SCHEMA
+            .verify_json(&value)
+            .map_err(|err| anyhow::format_err!("invalid join information - {err}"))?;
+        let info: Self = serde_json::from_value(value)
+            .map_err(|err| anyhow::format_err!("invalid join information - {err}"))?;
+
+        if info.nodes.is_empty() {
+            anyhow::bail!("join information contains no nodes");
+        }
+        if !info.authid.is_token() {
+            anyhow::bail!("join information does not contain an API token");
+        }
+
+        Ok(info)
+    }
+
+    /// Encode the join information as base64 encoded JSON.
+    pub fn encode(&self) -> Result<String, anyhow::Error> {
+        Ok(proxmox_base64::encode(serde_json::to_vec(self)?))
+    }
+
+    /// Check whether the join token expired at the given time.
+    pub fn is_expired(&self, now: i64) -> bool {
+        self.expire.is_some_and(|expire| expire <= now)
+    }
+}
+
+pub const REMOTE_TFA_CHALLENGE_SCHEMA: Schema = StringSchema::new(
+    "The id of the TFA challenge returned by a previous request, to continue its login.",
+)
+.max_length(64)
+.schema();
+
+pub const REMOTE_TFA_RESPONSE_SCHEMA: Schema =
+    StringSchema::new("The response to the TFA challenge, e.g. 'totp:123456'.")
+        .max_length(64 * 1024)
+        .schema();
+
+#[api]
+/// A pending login to a remote which requires a second factor.
+///
+/// To continue the login, repeat the request with the `id` as `tfa-challenge` and the answer to
+/// the challenge as `tfa-response`.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub struct RemoteTfaChallenge {
+    /// Identifies the pending login.
+    pub id: String,
+
+    /// The TFA challenge of the remote, as JSON encoded `proxmox_login::TfaChallenge`.
+    pub challenge: String,
+}
+
+#[api(
+    properties: {
+        remote: {
+            type: Remote,
+            optional: true,
+        },
+        "tfa-challenge": {
+            type: RemoteTfaChallenge,
+            optional: true,
+        },
+    },
+)]
+/// The result of scanning a remote.
+///
+/// If the login requires a second factor, only the `tfa-challenge` is set.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub struct RemoteScanResult {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub remote: Option<Remote>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tfa_challenge: Option<RemoteTfaChallenge>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn remote_id() {
+        assert!(REMOTE_ID_SCHEMA.parse_simple_value("pve-1").is_ok());
+        assert!(REMOTE_ID_SCHEMA.parse_simple_value("folders").is_ok());
+        assert!(REMOTE_ID_SCHEMA.parse_simple_value("folder").is_err());
+        assert!(REMOTE_ID_SCHEMA.parse_simple_value("pve/1").is_err());
+    }
+
+    #[test]
+    fn join_info_round_trip() {
+        let info = RemoteJoinInfo {
+            ty: RemoteType::Pve,
+            nodes: vec![NodeUrl {
+                hostname: "pve1.example.com".into(),
+                fingerprint: Some(
+                    "9f:7a:aa:1b:3d:b5:49:62:9b:c5:0a:a2:4d:a1:4b:b9:\
+                     6d:1b:c6:ec:2d:7c:c3:80:a6:12:e0:e6:ec:b8:49:74"
+                        .into(),
+                ),
+            }],
+            authid: "root@pam!pdm-join".parse().unwrap(),
+            secret: "3d1a5f80-8b1c-4d4e-a8e6-4b4a0a7e2b8c".into(),
+            expire: Some(1_700_000_000),
+        };
+
+        let blob = info.encode().unwrap();
+        assert_eq!(RemoteJoinInfo::decode(&blob).unwrap(), info);
+
+        // line breaks from pasting are ignored
+        let (start, end) = blob.split_at(20);
+        assert_eq!(
+            RemoteJoinInfo::decode(&format!("{start}\n{end}\n")).unwrap(),
+            info
+        );
+
+        let json = serde_json::to_string(&info).unwrap();
+        assert_eq!(RemoteJoinInfo::decode(&json).unwrap(), info);
+
+        assert!(info.is_expired(1_700_000_000));
+        assert!(!info.is_expired(1_699_999_999));
+    }
+
+    #[test]
+    fn join_info_requires_token() {
+        let json =
+            r#"{"type":"pbs","nodes":[{"hostname":"pbs"}],"authid":"root@pam","secret":"x"}"#;
+        assert!(RemoteJoinInfo::decode(json).is_err());
+
+        let json = r#"{"type":"pbs","nodes":[],"authid":"root@pam!join","secret":"x"}"#;
+        assert!(RemoteJoinInfo::decode(json).is_err());
+
+        assert!(RemoteJoinInfo::decode("not base64!").is_err());
+    }
+
+    #[test]
+    fn join_info_is_verified() {
+        let decode = |node: serde_json::Value| {
+            let info = serde_json::json!({
+                "type": "pve",
+                "nodes": [node],
+                "authid": "root@pam!join",
+                "secret": "x",
+            });
+            RemoteJoinInfo::decode(&info.to_string())
+        };
+
+        assert!(decode(serde_json::json!({ "hostname": "pve1:8006" })).is_ok());
+        assert!(decode(serde_json::json!({ "hostname": "pve1 --x" })).is_err());
+        assert!(decode(serde_json::json!({ "hostname": "pve1", "fingerprint": "aa:bb" })).is_err());
+    }
+
+    #[test]
+    fn folders() {
+        for folder in ["site-a", "site-a/rack1", "a/b/c"] {
+            assert!(REMOTE_FOLDER_SCHEMA.parse_simple_value(folder).is_ok());
+        }
+        for folder in ["", "/site-a", "site-a/", "site-a//rack1", "site a"] {
+            assert!(REMOTE_FOLDER_SCHEMA.parse_simple_value(folder).is_err());
+        }
+
+        assert_eq!(
+            folder_and_parents("a/b/c").collect::<Vec<_>>(),
+            ["a/b/c", "a/b", "a"]
+        );
+        assert_eq!(
+            folder_acl_path("site-a/rack1"),
+            ["resource", "folder", "site-a", "rack1"]
+        );
+
+        let mut remote: Remote = serde_json::from_value(serde_json::json!({
+            "type": "pve",
+            "id": "pve1",
+            "nodes": ["pve1.example.com"],
+            "authid": "root@pam!pdm-admin",
+            "token": "-",
+        }))
+        .unwrap();
+        assert!(!remote.in_folder("site-a"));
+
+        remote.folder = Some("site-a/rack1".into());
+        assert!(remote.in_folder("site-a"));
+        assert!(remote.in_folder("site-a/rack1"));
+        assert!(!remote.in_folder("site-a/rack"));
+        assert!(!remote.in_folder("rack1"));
+    }
+}
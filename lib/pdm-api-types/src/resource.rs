@@ -39,6 +39,8 @@ pub enum Resource {
     PbsNode(PbsNodeResource),
     /// Datastore on a PBS node.
     PbsDatastore(PbsDatastoreResource),
+    /// A PMG node.
+    PmgNode(PmgNodeResource),
 }
 
 impl Resource {
@@ -61,6 +63,7 @@ impl Resource {
             }
             Resource::PbsNode(r) => format!("node/{}", r.name),
             Resource::PbsDatastore(r) => r.name.clone(),
+            Resource::PmgNode(r) => format!("node/{}", r.node),
         }
     }
 
@@ -75,6 +78,7 @@ impl Resource {
             Resource::PveNetwork(r) => r.id(),
             Resource::PbsNode(r) => r.id.as_str(),
             Resource::PbsDatastore(r) => r.id.as_str(),
+            Resource::PmgNode(r) => r.id.as_str(),
         }
     }
 
@@ -89,6 +93,7 @@ impl Resource {
             Resource::PveNetwork(r) => r.name(),
             Resource::PbsNode(r) => r.name.as_str(),
             Resource::PbsDatastore(r) => r.name.as_str(),
+            Resource::PmgNode(r) => r.node.as_str(),
         }
     }
 
@@ -98,7 +103,9 @@ impl Resource {
             Resource::PveQemu(_) => ResourceType::PveQemu,
             Resource::PveLxc(_) => ResourceType::PveLxc,
             Resource::PveNetwork(_) => ResourceType::PveNetwork,
-            Resource::PveNode(_) | Resource::PbsNode(_) => ResourceType::Node,
+            Resource::PveNode(_) | Resource::PbsNode(_) | Resource::PmgNode(_) => {
+                ResourceType::Node
+            }
             Resource::PbsDatastore(_) => ResourceType::PbsDatastore,
         }
     }
@@ -124,6 +131,13 @@ impl Resource {
                     "under-maintenance"
                 }
             }
+            Resource::PmgNode(r) => {
+                if r.uptime > 0 {
+                    "online"
+                } else {
+                    "offline"
+                }
+            }
         }
     }
 
@@ -373,6 +387,9 @@ pub struct PveStorageResource {
     pub status: String,
     /// shared flag
     pub shared: bool,
+    /// Estimated time (UNIX epoch) when the storage is full, based on its usage history
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub estimated_full: Option<i64>,
 }
 
 #[api]
@@ -556,6 +573,27 @@ pub struct PbsNodeResource {
     pub uptime: u64,
 }
 
+#[api]
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+/// PMG node remote resource
+pub struct PmgNodeResource {
+    /// Current CPU utilization
+    pub cpu: f64,
+    /// Maximum CPU utilization (Number of CPUs)
+    pub maxcpu: f64,
+    /// Resource ID
+    pub id: String,
+    /// Current memory usage
+    pub mem: u64,
+    /// System memory
+    pub maxmem: u64,
+    /// Node name
+    pub node: String,
+    /// Uptime
+    pub uptime: u64,
+}
+
 #[api]
 #[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq)]
 #[serde(rename_all = "kebab-case")]
@@ -580,6 +618,9 @@ pub struct PbsDatastoreResource {
     /// Datastore backend type
     #[serde(skip_serializing_if = "Option::is_none")]
     pub backend_type: Option<String>,
+    /// Estimated time (UNIX epoch) when the datastore is full, based on its usage history
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub estimated_full: Option<i64>,
 }
 
 #[api(
@@ -731,6 +772,9 @@ pub struct ResourcesStatus {
     pub pbs_nodes: NodeStatusCount,
     /// Status of PBS Datastores
     pub pbs_datastores: PbsDatastoreStatusCount,
+    /// Status of PMG Nodes
+    #[serde(default)]
+    pub pmg_nodes: NodeStatusCount,
     /// Combined CPU statistics for all PVE remotes
     pub pve_cpu_stats: CpuStatistics,
     /// Combined CPU statistics for all PBS remotes
@@ -861,11 +905,18 @@ pub struct TopEntity {
                 type: TopEntity,
             },
         },
+        "soonest-full": {
+            type: Array,
+            items: {
+                type: TopEntity,
+            },
+        },
     },
 )]
 #[derive(Serialize, Deserialize, Clone, PartialEq)]
 #[serde(rename_all = "kebab-case")]
-/// Contains a list for "top entities" for Guest-CPU, Node-CPU and Node-Memory
+/// Contains a list for "top entities" for Guest-CPU, Node-CPU, Node-Memory and the storages
+/// which are estimated to be full the soonest
 pub struct TopEntities {
     /// The top entries for Guest CPU
     pub guest_cpu: Vec<TopEntity>,
@@ -873,11 +924,100 @@ pub struct TopEntities {
     pub node_cpu: Vec<TopEntity>,
     /// The top entries for Node Memory
     pub node_memory: Vec<TopEntity>,
+    /// The storages and datastores estimated to be full the soonest, with their disk usage
+    #[serde(default)]
+    pub soonest_full: Vec<TopEntity>,
 }
 
-#[derive(PartialEq, Clone, Copy, Serialize, Deserialize)]
+#[api]
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, Serialize, Deserialize)]
 #[serde(rename_all = "kebab-case")]
+/// Type of a PVE guest.
 pub enum GuestType {
+    /// A virtual machine.
     Qemu,
+    /// A container.
     Lxc,
 }
+
+#[api]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+/// Power state change which can be applied to many guests at once.
+pub enum GuestAction {
+    /// Start the guest.
+    Start,
+    /// Stop the guest immediately.
+    Stop,
+    /// Cleanly shut down the guest.
+    Shutdown,
+    /// Resume a paused or suspended guest.
+    Resume,
+}
+
+impl GuestAction {
+    /// Returns the name of the action, as used in the guest status API paths.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            GuestAction::Start => "start",
+            GuestAction::Stop => "stop",
+            GuestAction::Shutdown => "shutdown",
+            GuestAction::Resume => "resume",
+        }
+    }
+}
+
+impl std::fmt::Display for GuestAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[api(
+    properties: {
+        action: { type: GuestAction },
+        guests: {
+            type: Array,
+            optional: true,
+            items: {
+                type: String,
+                description: "Global resource ID of a guest, e.g. 'remote/<remote>/guest/<vmid>'.",
+            },
+        },
+        search: {
+            type: String,
+            optional: true,
+        },
+        "max-connections": {
+            type: Integer,
+            optional: true,
+            minimum: 1,
+            maximum: 64,
+        },
+        "max-connections-per-remote": {
+            type: Integer,
+            optional: true,
+            minimum: 1,
+            maximum: 64,
+        },
+    },
+)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+/// Parameters for applying a [`GuestAction`] to many guests across remotes.
+pub struct BulkGuestActionParams {
+    /// The action to apply.
+    pub action: GuestAction,
+    /// The guests to apply the action to.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub guests: Option<Vec<String>>,
+    /// Apply the action to all guests matching this search expression.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub search: Option<String>,
+    /// Maximum number of parallel requests to all remotes.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_connections: Option<usize>,
+    /// Maximum number of parallel requests to a single remote.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_connections_per_remote: Option<usize>,
+}
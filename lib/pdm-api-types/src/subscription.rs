@@ -257,11 +257,12 @@ impl ProductType {
         }
     }
 
-    /// Whether PDM currently knows how to drive a remote of this product type.
+    /// Whether PDM currently knows how to assign keys of this product type to a remote.
     ///
-    /// PDM only manages PVE and PBS remotes today, and the schema regex rejects everything else
-    /// at insert time. This method covers in-memory paths for forward-compat, for example
-    /// existing pool entries loaded after the regex is widened in a future release.
+    /// PDM only assigns keys to PVE and PBS remotes today, and the schema regex rejects everything
+    /// else at insert time. PMG remotes are managed, but their keys are not pooled yet. This
+    /// method covers in-memory paths for forward-compat, for example existing pool entries loaded
+    /// after the regex is widened in a future release.
     pub fn matches_remote_type(self, remote_type: RemoteType) -> bool {
         matches!(
             (self, remote_type),
@@ -3,7 +3,7 @@ use std::fmt::{Debug, Display};
 use std::str::FromStr;
 use std::sync::OnceLock;
 
-use anyhow::{Error, bail};
+use anyhow::{Error, bail, format_err};
 use const_format::concatcp;
 use serde::{Deserialize, Serialize};
 
@@ -15,7 +15,7 @@ use proxmox_section_config::{SectionConfig, SectionConfigPlugin, typed::ApiSecti
 
 use crate::{
     PROXMOX_SAFE_ID_REGEX, VIEW_ID_SCHEMA,
-    remotes::{REMOTE_ID_SCHEMA, RemoteType},
+    remotes::{REMOTE_FOLDER_SCHEMA, REMOTE_ID_SCHEMA, RemoteType},
     resource::{GuestType, ResourceType},
 };
 
@@ -29,10 +29,11 @@ pub const FILTER_RULE_SCHEMA: Schema = StringSchema::new("Filter rule for resour
     .format(&ApiStringFormat::VerifyFn(verify_filter_rule))
     .type_text(
         "[exact:]resource-type=<storage|qemu|lxc|sdn-zone|datastore|node>\
-            |[exact:]resource-pool=<pool-name>\
-            |[exact:]tag=<tag-name>\
-            |[exact:]remote=<remote-name>\
-            |[exact:]resource=id:<resource-id>",
+            |[exact:|glob:|regex:]resource-pool=<pool-name>\
+            |[exact:|glob:|regex:]tag=<tag-name>\
+            |[exact:|glob:|regex:]remote=<remote-name>\
+            |[exact:|glob:|regex:]folder=<folder-path>\
+            |[exact:|glob:|regex:]resource=id:<resource-id>",
     )
     .schema();
 
@@ -123,10 +124,80 @@ impl ApiSectionDataEntry for ViewConfigEntry {
     }
 }
 
+/// Upper limit for the compiled size of `glob:` and `regex:` patterns.
+const MATCH_PATTERN_SIZE_LIMIT: usize = 1024 * 1024;
+
+#[derive(Clone, Debug)]
+/// A compiled `glob:` or `regex:` pattern, always anchored at both ends.
+///
+/// Two patterns are considered equal if their source text is equal.
+pub struct MatchPattern {
+    pattern: String,
+    regex: regex::Regex,
+}
+
+impl MatchPattern {
+    /// Compile a shell-style glob, where `*` matches any number and `?` a single character.
+    pub fn glob(pattern: &str) -> Result<Self, Error> {
+        let mut regex = String::with_capacity(pattern.len() + 8);
+        let mut buf = [0u8; 4];
+        for c in pattern.chars() {
+            match c {
+                '*' => regex.push_str(".*"),
+                '?' => regex.push('.'),
+                c => regex.push_str(&regex::escape(c.encode_utf8(&mut buf))),
+            }
+        }
+        Self::compile(pattern, &regex)
+    }
+
+    /// Compile a regular expression, which has to match the whole value.
+    pub fn regex(pattern: &str) -> Result<Self, Error> {
+        Self::compile(pattern, pattern)
+    }
+
+    fn compile(pattern: &str, regex: &str) -> Result<Self, Error> {
+        if pattern.is_empty() {
+            bail!("pattern must not be empty");
+        }
+
+        let regex = regex::RegexBuilder::new(&format!("^(?:{regex})$"))
+            .size_limit(MATCH_PATTERN_SIZE_LIMIT)
+            .build()
+            .map_err(|err| format_err!("invalid pattern '{pattern}' - {err}"))?;
+
+        Ok(Self {
+            pattern: pattern.to_string(),
+            regex,
+        })
+    }
+
+    /// The source text of the pattern.
+    pub fn as_str(&self) -> &str {
+        &self.pattern
+    }
+
+    /// Check if a given string matches.
+    pub fn is_match(&self, value: &str) -> bool {
+        self.regex.is_match(value)
+    }
+}
+
+impl PartialEq for MatchPattern {
+    fn eq(&self, other: &Self) -> bool {
+        self.pattern == other.pattern
+    }
+}
+
 #[derive(Clone, Debug, PartialEq)]
 /// Matcher for string-based values.
 pub enum StringMatcher {
+    /// `exact:`, the value has to be equal.
     Exact(String),
+    /// `glob:`, e.g. `prod-*`.
+    Glob(MatchPattern),
+    /// `regex:`, e.g. `db-.*`.
+    Regex(MatchPattern),
 }
 
 impl StringMatcher {
@@ -134,8 +205,56 @@ impl StringMatcher {
     pub fn matches(&self, value: &str) -> bool {
         match self {
             StringMatcher::Exact(matched_value) => value == matched_value,
+            StringMatcher::Glob(pattern) | StringMatcher::Regex(pattern) => pattern.is_match(value),
+        }
+    }
+
+    /// The value or pattern text of the matcher.
+    pub fn value(&self) -> &str {
+        match self {
+            StringMatcher::Exact(value) => value,
+            StringMatcher::Glob(pattern) | StringMatcher::Regex(pattern) => pattern.as_str(),
+        }
+    }
+
+    /// The prefix used for this matcher in filter rules, without the colon.
+    pub fn mode(&self) -> &'static str {
+        match self {
+            StringMatcher::Exact(_) => "exact",
+            StringMatcher::Glob(_) => "glob",
+            StringMatcher::Regex(_) => "regex",
         }
     }
+
+    /// Parse a value with the matching mode given by `mode`.
+    ///
+    /// `verify` checks exact values. Glob patterns are checked with every wildcard replaced by a
+    /// regular character, so they can only match values that would pass `verify`. Regular
+    /// expressions are only compiled.
+    fn parse(
+        mode: MatchMode,
+        value: &str,
+        verify: impl Fn(&str) -> Result<(), Error>,
+    ) -> Result<Self, Error> {
+        Ok(match mode {
+            MatchMode::Exact => {
+                verify(value)?;
+                StringMatcher::Exact(value.into())
+            }
+            MatchMode::Glob => {
+                verify(&value.replace(['*', '?'], "x"))?;
+                StringMatcher::Glob(MatchPattern::glob(value)?)
+            }
+            MatchMode::Regex => StringMatcher::Regex(MatchPattern::regex(value)?),
+        })
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum MatchMode {
+    Exact,
+    Glob,
+    Regex,
 }
 
 #[derive(Clone, Debug, PartialEq)]
@@ -162,6 +281,8 @@ pub enum FilterRule {
     Tag(StringMatcher),
     /// Match a remote.
     Remote(StringMatcher),
+    /// Match the folder of a remote, remotes in subfolders match as well.
+    Folder(StringMatcher),
 }
 
 impl FromStr for FilterRule {
@@ -169,46 +290,68 @@ impl FromStr for FilterRule {
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         if let Some(s) = s.strip_prefix("exact:") {
-            parse_filter_rule(s)
+            parse_filter_rule(s, MatchMode::Exact)
+        } else if let Some(s) = s.strip_prefix("glob:") {
+            parse_filter_rule(s, MatchMode::Glob)
+        } else if let Some(s) = s.strip_prefix("regex:") {
+            parse_filter_rule(s, MatchMode::Regex)
         } else {
-            parse_filter_rule(s)
+            parse_filter_rule(s, MatchMode::Exact)
         }
     }
 }
 
-fn parse_filter_rule(s: &str) -> Result<FilterRule, Error> {
+fn parse_filter_rule(s: &str, mode: MatchMode) -> Result<FilterRule, Error> {
     Ok(match s.split_once('=') {
-        Some(("resource-type", value)) => FilterRule::ResourceType(EnumMatcher(value.parse()?)),
-        Some(("resource-pool", value)) => {
-            if !PROXMOX_SAFE_ID_REGEX.is_match(value) {
-                bail!("invalid resource-pool value: {value}");
+        Some(("resource-type", value)) => {
+            if mode != MatchMode::Exact {
+                bail!("resource-type only supports exact matches");
             }
-
-            let val = StringMatcher::Exact(value.into());
+            FilterRule::ResourceType(EnumMatcher(value.parse()?))
+        }
+        Some(("resource-pool", value)) => {
+            let val = StringMatcher::parse(mode, value, |value| {
+                if !PROXMOX_SAFE_ID_REGEX.is_match(value) {
+                    bail!("invalid resource-pool value: {value}");
+                }
+                Ok(())
+            })?;
             FilterRule::ResourcePool(val)
         }
         Some(("resource-id", value)) => {
-            if !GLOBAL_RESOURCE_ID_REGEX.is_match(value) {
-                bail!("invalid resource-id value: {value}");
-            }
-
-            let val = StringMatcher::Exact(value.into());
+            let val = StringMatcher::parse(mode, value, |value| {
+                if !GLOBAL_RESOURCE_ID_REGEX.is_match(value) {
+                    bail!("invalid resource-id value: {value}");
+                }
+                Ok(())
+            })?;
             FilterRule::ResourceId(val)
         }
         Some(("tag", value)) => {
-            if !PROXMOX_SAFE_ID_REGEX.is_match(value) {
-                bail!("invalid tag value: {value}");
-            }
-            let val = StringMatcher::Exact(value.into());
+            let val = StringMatcher::parse(mode, value, |value| {
+                if !PROXMOX_SAFE_ID_REGEX.is_match(value) {
+                    bail!("invalid tag value: {value}");
+                }
+                Ok(())
+            })?;
             FilterRule::Tag(val)
         }
         Some(("remote", value)) => {
-            if !PROXMOX_SAFE_ID_REGEX.is_match(value) {
-                let _ = REMOTE_ID_SCHEMA.parse_simple_value(value)?;
-            }
-            let val = StringMatcher::Exact(value.into());
+            let val = StringMatcher::parse(mode, value, |value| {
+                if !PROXMOX_SAFE_ID_REGEX.is_match(value) {
+                    let _ = REMOTE_ID_SCHEMA.parse_simple_value(value)?;
+                }
+                Ok(())
+            })?;
             FilterRule::Remote(val)
         }
+        Some(("folder", value)) => {
+            let val = StringMatcher::parse(mode, value, |value| {
+                let _ = REMOTE_FOLDER_SCHEMA.parse_simple_value(value)?;
+                Ok(())
+            })?;
+            FilterRule::Folder(val)
+        }
         Some((ty, _)) => bail!("invalid type: {ty}"),
         None => bail!("invalid filter rule: {s}"),
     })
@@ -221,13 +364,18 @@ impl Display for FilterRule {
             FilterRule::ResourceType(EnumMatcher(resource_type)) => {
                 write!(f, "exact:resource-type={resource_type}")
             }
-            FilterRule::ResourceId(StringMatcher::Exact(value)) => {
-                write!(f, "exact:resource-id={value}")
+            FilterRule::ResourceId(matcher) => {
+                write!(f, "{}:resource-id={}", matcher.mode(), matcher.value())
+            }
+            FilterRule::Tag(matcher) => write!(f, "{}:tag={}", matcher.mode(), matcher.value()),
+            FilterRule::Remote(matcher) => {
+                write!(f, "{}:remote={}", matcher.mode(), matcher.value())
             }
-            FilterRule::Tag(StringMatcher::Exact(value)) => write!(f, "exact:tag={value}"),
-            FilterRule::Remote(StringMatcher::Exact(value)) => write!(f, "exact:remote={value}"),
-            FilterRule::ResourcePool(StringMatcher::Exact(value)) => {
-                write!(f, "exact:resource-pool={value}")
+            FilterRule::Folder(matcher) => {
+                write!(f, "{}:folder={}", matcher.mode(), matcher.value())
+            }
+            FilterRule::ResourcePool(matcher) => {
+                write!(f, "{}:resource-pool={}", matcher.mode(), matcher.value())
             }
         }
     }
@@ -335,6 +483,10 @@ pub enum WidgetType {
     },
     /// A simple map
     Map,
+    /// List of active alerts
+    Alerts,
+    /// List of failed PBS jobs
+    PbsFailedJobs,
     #[serde(untagged)]
     #[serde(rename_all = "kebab-case")]
     /// Catches all widgets for unknown types.
@@ -352,6 +504,8 @@ pub enum LeaderboardType {
     GuestCpu,
     NodeCpu,
     NodeMemory,
+    /// Storages and datastores which are estimated to be full the soonest
+    SoonestFull,
 }
 
 #[derive(Serialize, Deserialize, PartialEq, Clone, Copy)]
@@ -397,6 +551,58 @@ mod test {
 
         assert!(parse_and_check_display("exact:remote=someremote").unwrap());
         assert!(parse_and_check_display("remote:a").is_err());
+
+        assert!(parse_and_check_display("exact:folder=site-a").unwrap());
+        assert!(parse_and_check_display("exact:folder=site-a/rack1").unwrap());
+        assert!(parse_and_check_display("exact:folder=site-a/").is_err());
+        assert!(parse_and_check_display("glob:folder=site-*/rack1").unwrap());
+    }
+
+    #[test]
+    fn test_filter_rule_patterns() {
+        assert!(parse_and_check_display("glob:remote=prod-*").unwrap());
+        assert!(parse_and_check_display("glob:tag=db-??").unwrap());
+        assert!(parse_and_check_display("glob:resource-pool=pool*").unwrap());
+        assert!(parse_and_check_display("glob:resource-id=remote/*/guest/1*").unwrap());
+        assert!(parse_and_check_display("glob:tag=inv@lid*").is_err());
+        assert!(parse_and_check_display("glob:tag=").is_err());
+        assert!(parse_and_check_display("glob:resource-type=qemu").is_err());
+
+        assert!(parse_and_check_display("regex:tag=db-.*").unwrap());
+        assert!(parse_and_check_display("regex:remote=(prod|stage)-[0-9]+").unwrap());
+        assert!(parse_and_check_display("regex:resource-id=remote/.*/guest/10[0-9]").unwrap());
+        assert!(parse_and_check_display("regex:tag=db-(").is_err());
+        assert!(parse_and_check_display("regex:resource-type=qemu").is_err());
+
+        let rule: FilterRule = "glob:remote=prod-*".parse().unwrap();
+        let FilterRule::Remote(matcher) = rule else {
+            panic!("expected remote rule");
+        };
+        assert!(matcher.matches("prod-1"));
+        assert!(matcher.matches("prod-"));
+        assert!(!matcher.matches("preprod-1"));
+
+        let rule: FilterRule = "glob:tag=a.b?".parse().unwrap();
+        let FilterRule::Tag(matcher) = rule else {
+            panic!("expected tag rule");
+        };
+        assert!(matcher.matches("a.bc"));
+        assert!(!matcher.matches("axbc"));
+
+        // regular expressions have to match the whole value
+        let rule: FilterRule = "regex:tag=db-.*".parse().unwrap();
+        let FilterRule::Tag(matcher) = rule else {
+            panic!("expected tag rule");
+        };
+        assert!(matcher.matches("db-main"));
+        assert!(!matcher.matches("mydb-main"));
+
+        let rule: FilterRule = "regex:tag=a|b".parse().unwrap();
+        let FilterRule::Tag(matcher) = rule else {
+            panic!("expected tag rule");
+        };
+        assert!(matcher.matches("a"));
+        assert!(!matcher.matches("ab"));
     }
 
     #[test]
@@ -414,6 +620,7 @@ view: some-view
     include exact:tag=sometag
     include resource-pool=somepool
     include exact:resource-pool=somepool
+    include folder=site-a/rack1
     exclude remote=someremote
     exclude exact:remote=someremote
     exclude resource-type=qemu
@@ -424,6 +631,10 @@ view: some-view
     exclude exact:tag=sometag
     exclude resource-pool=somepool
     exclude exact:resource-pool=somepool
+    include glob:remote=prod-*
+    include regex:tag=db-.*
+    exclude glob:resource-id=remote/*/guest/9??
+    exclude regex:resource-pool=(test|dev)
 ";
         ViewConfigEntry::parse_section_config("views.cfg", config).unwrap();
     }
@@ -82,6 +82,15 @@ pub const PDM_SUBSCRIPTION_FN: &str = configdir!("/subscription");
 
 pub const APT_PKG_STATE_FN: &str = concat!(PDM_STATE_DIR_M!(), "/pkg-state.json");
 
+/// Directory of the notification templates shipped with PDM.
+pub const PDM_BASE_TEMPLATE_DIR: &str = "/usr/share/proxmox-datacenter-manager/templates";
+
+/// Directory for admin provided notification templates, overriding the shipped ones.
+pub const PDM_OVERRIDE_TEMPLATE_DIR: &str = configdir!("/notification-templates");
+
+/// Spool directory for notifications which are sent by the privileged API daemon.
+pub const PDM_NOTIFICATION_SPOOL_DIR: &str = concat!(PDM_STATE_DIR_M!(), "/notifications");
+
 /// Prepend configuration directory to a file name
 ///
 /// This is a simply way to get the full path for configuration files.
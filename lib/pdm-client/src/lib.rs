@@ -30,7 +30,7 @@ use types::*;
 pub mod types {
     pub use proxmox_access_control::types::{User, UserWithTokens};
 
-    pub use pdm_api_types::remotes::Remote;
+    pub use pdm_api_types::remotes::{Remote, RemoteScanResult, RemoteTfaChallenge};
     pub use pdm_api_types::{AclListItem, Authid, ConfigurationState, RemoteUpid};
 
     pub use pve_api_types::{ClusterNodeIndexResponse, ClusterNodeIndexResponseStatus};
@@ -52,7 +52,16 @@ pub mod types {
         QemuConfigUnused, QemuConfigVirtio,
     };
 
-    pub use pdm_api_types::resource::{Resource, ResourceRrdData};
+    pub use pdm_api_types::pve::{
+        BalancingNodeLoad, BalancingRecommendation, BulkGuestDescriptionParams, BulkGuestTagParams,
+        DeployTemplateParams, EvacuateNodeParams, EvacuationMigration, EvacuationNodeLoad,
+        EvacuationPlan, GuestConfigChange, GuestConfigUpdate, GuestConfigUpdateResult,
+        GuestDiskResize, GuestNetUpdate, GuestTagAction, GuestTagUsage,
+    };
+
+    pub use pdm_api_types::resource::{
+        BulkGuestActionParams, GuestAction, GuestType, Resource, ResourceRrdData,
+    };
 
     pub use pve_api_types::NodeStatus;
 
@@ -130,11 +139,16 @@ impl<T: HttpApiClient> PdmClient<T> {
             .data)
     }
 
+    /// Add a remote.
+    ///
+    /// If logging in to create the token requires a second factor, the remote is not added and
+    /// the challenge is returned. Pass its id together with the response as `tfa` to retry.
     pub async fn add_remote(
         &self,
         remote: &Remote,
         create_token: Option<&str>,
-    ) -> Result<(), proxmox_client::Error> {
+        tfa: Option<(&str, &str)>,
+    ) -> Result<Option<RemoteTfaChallenge>, proxmox_client::Error> {
         #[derive(Serialize)]
         #[serde(rename_all = "kebab-case")]
         struct AddRemoteParams<'a> {
@@ -142,17 +156,74 @@ impl<T: HttpApiClient> PdmClient<T> {
             remote: &'a Remote,
             #[serde(skip_serializing_if = "Option::is_none")]
             create_token: Option<&'a str>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            tfa_challenge: Option<&'a str>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            tfa_response: Option<&'a str>,
         }
-        self.0
+        Ok(self
+            .0
             .post(
                 "/api2/extjs/remotes/remote",
                 &AddRemoteParams {
                     remote,
                     create_token,
+                    tfa_challenge: tfa.map(|(id, _)| id),
+                    tfa_response: tfa.map(|(_, response)| response),
                 },
             )
             .await?
-            .nodata()
+            .expect_json()?
+            .data)
+    }
+
+    /// Add a remote from pasted join information, see [`RemoteJoinInfo`].
+    ///
+    /// [`RemoteJoinInfo`]: pdm_api_types::remotes::RemoteJoinInfo
+    pub async fn quick_add_remote(
+        &self,
+        id: &str,
+        join_info: &str,
+        create_token: Option<&str>,
+    ) -> Result<(), Error> {
+        #[derive(Serialize)]
+        #[serde(rename_all = "kebab-case")]
+        struct QuickAddParams<'a> {
+            id: &'a str,
+            join_info: &'a str,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            create_token: Option<&'a str>,
+        }
+        self.0
+            .post(
+                "/api2/extjs/remotes/quick-add",
+                &QuickAddParams {
+                    id,
+                    join_info,
+                    create_token,
+                },
+            )
+            .await?
+            .nodata()?;
+        Ok(())
+    }
+
+    /// Rotate the API token of a remote.
+    ///
+    /// Returns the UPID of the PDM worker task doing the rotation.
+    pub async fn rotate_remote_token(&self, id: &str) -> Result<String, Error> {
+        let path = format!("/api2/extjs/remotes/remote/{id}/rotate-token");
+        Ok(self.0.post_without_body(&path).await?.expect_json()?.data)
+    }
+
+    /// Rotate the API tokens of all remotes, or only of those older than `max_age` days.
+    ///
+    /// Returns the UPID of the PDM worker task doing the rotation.
+    pub async fn rotate_remote_tokens(&self, max_age: Option<u64>) -> Result<String, Error> {
+        let path = ApiPathBuilder::new("/api2/extjs/remotes/rotate-tokens")
+            .maybe_arg("max-age", &max_age)
+            .build();
+        Ok(self.0.post_without_body(&path).await?.expect_json()?.data)
     }
 
     pub async fn update_remote(
@@ -613,6 +684,30 @@ impl<T: HttpApiClient> PdmClient<T> {
         Ok(self.0.get(&query).await?.expect_json()?.data)
     }
 
+    /// List the backup jobs of a PVE remote, or of all PVE remotes if `remote` is `None`.
+    pub async fn pve_list_backup_jobs(
+        &self,
+        remote: Option<&str>,
+        view: Option<&str>,
+    ) -> Result<pdm_api_types::pve::PveBackupJobList, Error> {
+        let path = match remote {
+            Some(remote) => format!("/api2/extjs/pve/remotes/{remote}/backup-jobs"),
+            None => ApiPathBuilder::new("/api2/extjs/pve/backup-jobs".to_string())
+                .maybe_arg("view", &view)
+                .build(),
+        };
+        Ok(self.0.get(&path).await?.expect_json()?.data)
+    }
+
+    pub async fn pve_run_backup_job(
+        &self,
+        remote: &str,
+        id: &str,
+    ) -> Result<Vec<RemoteUpid>, Error> {
+        let path = format!("/api2/extjs/pve/remotes/{remote}/backup-jobs/{id}/run");
+        Ok(self.0.post_without_body(&path).await?.expect_json()?.data)
+    }
+
     pub async fn pve_cluster_updates(&self, remote: &str) -> Result<RemoteUpdateSummary, Error> {
         let url = format!("/api2/extjs/pve/remotes/{remote}/updates");
         Ok(self.0.get(&url).await?.expect_json()?.data)
@@ -681,6 +776,59 @@ impl<T: HttpApiClient> PdmClient<T> {
         Ok(self.0.get(&path).await?.expect_json()?.data)
     }
 
+    async fn pve_update_guest_config(
+        &self,
+        remote: &str,
+        node: Option<&str>,
+        vmid: u32,
+        vmtype: &str,
+        update: GuestConfigUpdate,
+        digest: Option<&str>,
+        preview: bool,
+    ) -> Result<GuestConfigUpdateResult, Error> {
+        let path = format!("/api2/extjs/pve/remotes/{remote}/{vmtype}/{vmid}/config");
+        let mut request = serde_json::to_value(&update).expect("failed to build json string");
+        if let Some(node) = node {
+            request["node"] = node.into();
+        }
+        if let Some(digest) = digest {
+            request["digest"] = digest.into();
+        }
+        if preview {
+            request["preview"] = true.into();
+        }
+        Ok(self.0.put(&path, &request).await?.expect_json()?.data)
+    }
+
+    /// Update the configuration of a VM. With `preview` set, only the changes are computed.
+    pub async fn pve_qemu_update_config(
+        &self,
+        remote: &str,
+        node: Option<&str>,
+        vmid: u32,
+        update: GuestConfigUpdate,
+        digest: Option<&str>,
+        preview: bool,
+    ) -> Result<GuestConfigUpdateResult, Error> {
+        self.pve_update_guest_config(remote, node, vmid, "qemu", update, digest, preview)
+            .await
+    }
+
+    /// Update the configuration of a container. With `preview` set, only the changes are
+    /// computed.
+    pub async fn pve_lxc_update_config(
+        &self,
+        remote: &str,
+        node: Option<&str>,
+        vmid: u32,
+        update: GuestConfigUpdate,
+        digest: Option<&str>,
+        preview: bool,
+    ) -> Result<GuestConfigUpdateResult, Error> {
+        self.pve_update_guest_config(remote, node, vmid, "lxc", update, digest, preview)
+            .await
+    }
+
     pub async fn pve_qemu_status(
         &self,
         remote: &str,
@@ -1050,6 +1198,126 @@ impl<T: HttpApiClient> PdmClient<T> {
             .await
     }
 
+    /// Apply a power action to many guests, possibly across multiple remotes.
+    ///
+    /// Returns the UPID of the PDM worker task which reports the outcome for every guest.
+    pub async fn pve_bulk_guest_action(
+        &self,
+        params: BulkGuestActionParams,
+    ) -> Result<String, Error> {
+        let path = "/api2/extjs/pve/bulk-action";
+        Ok(self.0.post(path, &params).await?.expect_json()?.data)
+    }
+
+    /// Get recommended guest migrations to relieve overloaded nodes and balance clusters.
+    pub async fn pve_balancing_recommendations(
+        &self,
+        view: Option<&str>,
+        timeframe: Option<RrdTimeframe>,
+    ) -> Result<Vec<BalancingRecommendation>, Error> {
+        let path = ApiPathBuilder::new("/api2/extjs/pve/balancing".to_string())
+            .maybe_arg("view", &view)
+            .maybe_arg("timeframe", &timeframe)
+            .build();
+        Ok(self.0.get(&path).await?.expect_json()?.data)
+    }
+
+    /// Apply a load balancing recommendation by starting the matching migration.
+    ///
+    /// Running VMs are migrated online, running containers are restarted. Remote migrations keep
+    /// the storage and bridge names.
+    pub async fn pve_apply_balancing_recommendation(
+        &self,
+        recommendation: &BalancingRecommendation,
+    ) -> Result<RemoteUpid, Error> {
+        let remote = &recommendation.remote;
+        let node = Some(recommendation.source.node.as_str());
+        let vmid = recommendation.vmid;
+        let target = recommendation.target.node.clone();
+
+        if recommendation.is_remote_migration() {
+            let target_remote = recommendation.target_remote.clone();
+            let endpoint = recommendation.target_endpoint.as_deref();
+            match recommendation.guest_type {
+                GuestType::Qemu => {
+                    let params = RemoteMigrateQemu::new()
+                        .online(true)
+                        .map_storage("*", "*")
+                        .map_bridge("*", "*");
+                    self.pve_qemu_remote_migrate(
+                        remote,
+                        node,
+                        vmid,
+                        target_remote,
+                        endpoint,
+                        params,
+                    )
+                    .await
+                }
+                GuestType::Lxc => {
+                    let params = RemoteMigrateLxc::new()
+                        .restart(true, None)
+                        .map_storage("*", "*")
+                        .map_bridge("*", "*");
+                    self.pve_lxc_remote_migrate(remote, node, vmid, target_remote, endpoint, params)
+                        .await
+                }
+            }
+        } else {
+            match recommendation.guest_type {
+                GuestType::Qemu => {
+                    let params = MigrateQemu::new().online(true).with_local_disks(true);
+                    self.pve_qemu_migrate(remote, node, vmid, target, params)
+                        .await
+                }
+                GuestType::Lxc => {
+                    let params = MigrateLxc::new().restart(true, None);
+                    self.pve_lxc_migrate(remote, node, vmid, target, params)
+                        .await
+                }
+            }
+        }
+    }
+
+    /// List the tags of the guests of all PVE remotes with their usage.
+    pub async fn pve_list_tags(&self, view: Option<&str>) -> Result<Vec<GuestTagUsage>, Error> {
+        let path = ApiPathBuilder::new("/api2/extjs/pve/tags".to_string())
+            .maybe_arg("view", &view)
+            .build();
+        Ok(self.0.get(&path).await?.expect_json()?.data)
+    }
+
+    /// Add, remove or rename a tag on many guests, possibly across multiple remotes.
+    ///
+    /// Returns the UPID of the PDM worker task which reports the outcome for every guest.
+    pub async fn pve_bulk_guest_tags(&self, params: BulkGuestTagParams) -> Result<String, Error> {
+        let path = "/api2/extjs/pve/tags";
+        Ok(self.0.post(path, &params).await?.expect_json()?.data)
+    }
+
+    /// Set or extend the description of many guests, possibly across multiple remotes.
+    ///
+    /// Returns the UPID of the PDM worker task which reports the outcome for every guest.
+    pub async fn pve_bulk_guest_description(
+        &self,
+        params: BulkGuestDescriptionParams,
+    ) -> Result<String, Error> {
+        let path = "/api2/extjs/pve/descriptions";
+        Ok(self.0.post(path, &params).await?.expect_json()?.data)
+    }
+
+    /// Deploy a new guest from a template.
+    ///
+    /// Returns the UPID of the PDM worker task which clones, configures and starts the guest.
+    pub async fn pve_deploy_template(
+        &self,
+        remote: &str,
+        params: DeployTemplateParams,
+    ) -> Result<String, Error> {
+        let path = format!("/api2/extjs/pve/remotes/{remote}/deploy");
+        Ok(self.0.post(&path, &params).await?.expect_json()?.data)
+    }
+
     pub async fn pve_lxc_migrate(
         &self,
         remote: &str,
@@ -1267,6 +1535,97 @@ impl<T: HttpApiClient> PdmClient<T> {
         Ok(self.0.get(&path).await?.expect_json()?.data)
     }
 
+    pub async fn pbs_list_groups(
+        &self,
+        remote: &str,
+        store: &str,
+        namespace: Option<&str>,
+    ) -> Result<Vec<pbs_api_types::GroupListItem>, Error> {
+        let path = ApiPathBuilder::new(format!(
+            "/api2/extjs/pbs/remotes/{remote}/datastore/{store}/groups"
+        ))
+        .maybe_arg("ns", &namespace)
+        .build();
+        Ok(self.0.get(&path).await?.expect_json()?.data)
+    }
+
+    pub async fn pbs_snapshot_files(
+        &self,
+        remote: &str,
+        store: &str,
+        namespace: Option<&str>,
+        snapshot: &pbs_api_types::BackupDir,
+    ) -> Result<Vec<pbs_api_types::BackupContent>, Error> {
+        let path = ApiPathBuilder::new(format!(
+            "/api2/extjs/pbs/remotes/{remote}/datastore/{store}/files"
+        ))
+        .maybe_arg("ns", &namespace)
+        .arg("backup-type", snapshot.group.ty)
+        .arg("backup-id", &snapshot.group.id)
+        .arg("backup-time", snapshot.time)
+        .build();
+        Ok(self.0.get(&path).await?.expect_json()?.data)
+    }
+
+    pub async fn pbs_catalog(
+        &self,
+        remote: &str,
+        store: &str,
+        namespace: Option<&str>,
+        snapshot: &pbs_api_types::BackupDir,
+        filepath: Option<&str>,
+    ) -> Result<Vec<pdm_api_types::pbs::CatalogEntry>, Error> {
+        let path = ApiPathBuilder::new(format!(
+            "/api2/extjs/pbs/remotes/{remote}/datastore/{store}/catalog"
+        ))
+        .maybe_arg("ns", &namespace)
+        .arg("backup-type", snapshot.group.ty)
+        .arg("backup-id", &snapshot.group.id)
+        .arg("backup-time", snapshot.time)
+        .maybe_arg("filepath", &filepath)
+        .build();
+        Ok(self.0.get(&path).await?.expect_json()?.data)
+    }
+
+    pub async fn pbs_list_jobs(
+        &self,
+        remote: Option<&str>,
+        view: Option<&str>,
+    ) -> Result<pdm_api_types::pbs::PbsJobList, Error> {
+        let path = ApiPathBuilder::new("/api2/extjs/pbs/jobs".to_string())
+            .maybe_arg("remote", &remote)
+            .maybe_arg("view", &view)
+            .build();
+        Ok(self.0.get(&path).await?.expect_json()?.data)
+    }
+
+    pub async fn pbs_run_job(
+        &self,
+        remote: &str,
+        job_type: pdm_api_types::pbs::PbsJobType,
+        id: &str,
+    ) -> Result<RemoteUpid, Error> {
+        let path = format!("/api2/extjs/pbs/remotes/{remote}/jobs/run");
+        Ok(self
+            .0
+            .post(&path, &json!({ "job-type": job_type, "id": id }))
+            .await?
+            .expect_json()?
+            .data)
+    }
+
+    pub async fn pbs_backup_coverage(
+        &self,
+        hours: Option<u64>,
+        view: Option<&str>,
+    ) -> Result<pdm_api_types::pbs::BackupCoverage, Error> {
+        let path = ApiPathBuilder::new("/api2/extjs/pbs/backup-coverage".to_string())
+            .maybe_arg("hours", &hours)
+            .maybe_arg("view", &view)
+            .build();
+        Ok(self.0.get(&path).await?.expect_json()?.data)
+    }
+
     pub async fn pbs_node_rrddata(
         &self,
         remote: &str,
@@ -1317,14 +1676,54 @@ impl<T: HttpApiClient> PdmClient<T> {
         Ok(self.0.get(&path).await?.expect_json()?.data)
     }
 
+    pub async fn pmg_list_nodes(
+        &self,
+        remote: &str,
+    ) -> Result<Vec<pdm_api_types::pmg::NodeIndexEntry>, Error> {
+        let path = format!("/api2/extjs/pmg/remotes/{remote}/nodes");
+        Ok(self.0.get(&path).await?.expect_json()?.data)
+    }
+
+    pub async fn pmg_node_status(
+        &self,
+        remote: &str,
+        node: &str,
+    ) -> Result<pdm_api_types::pmg::NodeStatus, Error> {
+        let path = format!("/api2/extjs/pmg/remotes/{remote}/nodes/{node}/status");
+        Ok(self.0.get(&path).await?.expect_json()?.data)
+    }
+
+    pub async fn pmg_list_tasks(
+        &self,
+        remote: &str,
+        node: Option<&str>,
+    ) -> Result<Vec<pve_api_types::ListTasksResponse>, Error> {
+        let path = ApiPathBuilder::new(format!("/api2/extjs/pmg/remotes/{remote}/tasks"))
+            .maybe_arg("node", &node)
+            .build();
+        Ok(self.0.get(&path).await?.expect_json()?.data)
+    }
+
+    pub async fn pmg_task_status(
+        &self,
+        upid: &RemoteUpid,
+    ) -> Result<pve_api_types::TaskStatus, Error> {
+        let remote = upid.remote();
+        let upid = upid.to_string();
+        let path = format!("/api2/extjs/pmg/remotes/{remote}/tasks/{upid}/status");
+        Ok(self.0.get(&path).await?.expect_json()?.data)
+    }
+
     pub async fn resources(
         &self,
         max_age: Option<u64>,
         view: Option<&str>,
+        search: Option<&str>,
     ) -> Result<Vec<RemoteResources>, Error> {
         let path = ApiPathBuilder::new("/api2/extjs/resources/list")
             .maybe_arg("max-age", &max_age)
             .maybe_arg("view", &view)
+            .maybe_arg("search", &search)
             .build();
         Ok(self.0.get(&path).await?.expect_json()?.data)
     }
@@ -1742,23 +2141,52 @@ impl<T: HttpApiClient> PdmClient<T> {
         Ok(self.0.get(&path).await?.expect_json()?.data)
     }
 
+    /// Compute a plan for migrating all guests off a node.
+    pub async fn pve_node_evacuation_plan(
+        &self,
+        remote: &str,
+        node: &str,
+    ) -> Result<EvacuationPlan, Error> {
+        let path = format!("/api2/extjs/pve/remotes/{remote}/nodes/{node}/evacuate");
+        Ok(self.0.get(&path).await?.expect_json()?.data)
+    }
+
+    /// Migrate all guests off a node.
+    ///
+    /// Returns the UPID of the PDM worker task running the migrations.
+    pub async fn pve_evacuate_node(
+        &self,
+        remote: &str,
+        node: &str,
+        params: EvacuateNodeParams,
+    ) -> Result<String, Error> {
+        let path = format!("/api2/extjs/pve/remotes/{remote}/nodes/{node}/evacuate");
+        Ok(self.0.post(&path, &params).await?.expect_json()?.data)
+    }
+
     pub async fn pve_node_status(&self, remote: &str, node: &str) -> Result<NodeStatus, Error> {
         let path = format!("/api2/extjs/pve/remotes/{remote}/nodes/{node}/status");
         Ok(self.0.get(&path).await?.expect_json()?.data)
     }
 
+    /// Get the migration preconditions of a VM.
+    ///
+    /// With a `target` node, this also fails if a (mapped) storage or a bridge used by the VM is
+    /// not available on that node.
     pub async fn pve_qemu_migrate_preconditions(
         &self,
         remote: &str,
         node: Option<&str>,
         vmid: u32,
         target: Option<String>,
+        target_storage: Option<&str>,
     ) -> Result<QemuMigratePreconditions, Error> {
         let path = ApiPathBuilder::new(format!(
             "/api2/extjs/pve/remotes/{remote}/qemu/{vmid}/migrate"
         ))
         .maybe_arg("node", &node)
         .maybe_arg("target", &target)
+        .maybe_arg("target-storage", &target_storage)
         .build();
         Ok(self.0.get(&path).await?.expect_json()?.data)
     }
@@ -1779,8 +2207,9 @@ impl<T: HttpApiClient> PdmClient<T> {
         fingerprint: Option<&str>,
         authid: &str,
         token: &str,
-    ) -> Result<Remote, Error> {
-        self.scan_remote(hostname, fingerprint, authid, token, RemoteType::Pve)
+        tfa: Option<(&str, &str)>,
+    ) -> Result<RemoteScanResult, Error> {
+        self.scan_remote(hostname, fingerprint, authid, token, tfa, RemoteType::Pve)
             .await
     }
 
@@ -1869,6 +2298,15 @@ impl<T: HttpApiClient> PdmClient<T> {
         self.probe_tls(hostname, fingerprint, RemoteType::Pbs).await
     }
 
+    /// uses /pmg/probe-tls to probe the tls connection to the given host
+    pub async fn pmg_probe_tls(
+        &self,
+        hostname: &str,
+        fingerprint: Option<&str>,
+    ) -> Result<TlsProbeOutcome, Error> {
+        self.probe_tls(hostname, fingerprint, RemoteType::Pmg).await
+    }
+
     /// uses /{remote-type}/probe-tls to probe the tls connection to the given host
     async fn probe_tls(
         &self,
@@ -1893,20 +2331,38 @@ impl<T: HttpApiClient> PdmClient<T> {
         fingerprint: Option<&str>,
         authid: &str,
         token: &str,
-    ) -> Result<Remote, Error> {
-        self.scan_remote(hostname, fingerprint, authid, token, RemoteType::Pbs)
+        tfa: Option<(&str, &str)>,
+    ) -> Result<RemoteScanResult, Error> {
+        self.scan_remote(hostname, fingerprint, authid, token, tfa, RemoteType::Pbs)
+            .await
+    }
+
+    /// Uses /pmg/scan to scan the remote for node/fingerprint information
+    pub async fn pmg_scan_remote(
+        &self,
+        hostname: &str,
+        fingerprint: Option<&str>,
+        authid: &str,
+        token: &str,
+        tfa: Option<(&str, &str)>,
+    ) -> Result<RemoteScanResult, Error> {
+        self.scan_remote(hostname, fingerprint, authid, token, tfa, RemoteType::Pmg)
             .await
     }
 
     /// Uses /{remote-type}/scan to scan the remote for node/fingerprint information
+    ///
+    /// If the login requires a second factor, only the challenge is returned. Pass its id
+    /// together with the response as `tfa` to continue the scan.
     pub async fn scan_remote(
         &self,
         hostname: &str,
         fingerprint: Option<&str>,
         authid: &str,
         token: &str,
+        tfa: Option<(&str, &str)>,
         remote_type: RemoteType,
-    ) -> Result<Remote, Error> {
+    ) -> Result<RemoteScanResult, Error> {
         let path = format!("/api2/extjs/{remote_type}/scan");
         let mut params = json!({
             "hostname": hostname,
@@ -1916,6 +2372,10 @@ impl<T: HttpApiClient> PdmClient<T> {
         if let Some(fp) = fingerprint {
             params["fingerprint"] = fp.into();
         }
+        if let Some((challenge, response)) = tfa {
+            params["tfa-challenge"] = challenge.into();
+            params["tfa-response"] = response.into();
+        }
         Ok(self.0.post(&path, &params).await?.expect_json()?.data)
     }
 
@@ -1961,6 +2421,53 @@ impl<T: HttpApiClient> PdmClient<T> {
             .data)
     }
 
+    /// List the guest schedules together with the state of their last and next run.
+    pub async fn list_guest_schedules(
+        &self,
+    ) -> Result<Vec<pdm_api_types::guest_schedules::GuestScheduleStatus>, Error> {
+        Ok(self
+            .0
+            .get("/api2/extjs/config/guest-schedules")
+            .await?
+            .expect_json()?
+            .data)
+    }
+
+    /// Add a guest schedule.
+    pub async fn add_guest_schedule(
+        &self,
+        schedule: &pdm_api_types::guest_schedules::GuestSchedule,
+    ) -> Result<(), Error> {
+        self.0
+            .post("/api2/extjs/config/guest-schedules", schedule)
+            .await?
+            .nodata()?;
+        Ok(())
+    }
+
+    /// Update a guest schedule.
+    pub async fn update_guest_schedule(
+        &self,
+        id: &str,
+        updater: &pdm_api_types::guest_schedules::GuestScheduleUpdater,
+        delete: &[String],
+    ) -> Result<(), Error> {
+        let path = format!("/api2/extjs/config/guest-schedules/{id}");
+        let mut request = serde_json::to_value(updater).expect("failed to serialize updater");
+        if !delete.is_empty() {
+            request["delete"] = serde_json::to_value(delete).expect("failed to serialize delete");
+        }
+        self.0.put(&path, &request).await?.nodata()?;
+        Ok(())
+    }
+
+    /// Delete a guest schedule.
+    pub async fn delete_guest_schedule(&self, id: &str) -> Result<(), Error> {
+        let path = format!("/api2/extjs/config/guest-schedules/{id}");
+        self.0.delete(&path).await?.nodata()?;
+        Ok(())
+    }
+
     /// Retrieves all known installations done by auto-installer.
     pub async fn get_autoinst_installations(&self) -> Result<Vec<Installation>, Error> {
         Ok(self
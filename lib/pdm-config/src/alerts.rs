@@ -0,0 +1,33 @@
+use anyhow::Error;
+
+use proxmox_product_config::{ApiLockGuard, open_api_lockfile, replace_config};
+use proxmox_section_config::typed::{ApiSectionDataEntry, SectionConfigData};
+
+use pdm_api_types::{ConfigDigest, alerts::AlertConfigEntry};
+
+use pdm_buildcfg::configdir;
+
+const ALERT_CFG_FILENAME: &str = configdir!("/alerts.cfg");
+const ALERT_CFG_LOCKFILE: &str = configdir!("/.alerts.lock");
+
+/// Get the `alerts.cfg` config file contents.
+pub fn config() -> Result<(SectionConfigData<AlertConfigEntry>, ConfigDigest), Error> {
+    let content =
+        proxmox_sys::fs::file_read_optional_string(ALERT_CFG_FILENAME)?.unwrap_or_default();
+
+    let digest = openssl::sha::sha256(content.as_bytes());
+
+    let data = AlertConfigEntry::parse_section_config(ALERT_CFG_FILENAME, &content)?;
+    Ok((data, digest.into()))
+}
+
+/// Get exclusive lock
+pub fn lock_config() -> Result<ApiLockGuard, Error> {
+    open_api_lockfile(ALERT_CFG_LOCKFILE, None, true)
+}
+
+pub fn save_config(config: &SectionConfigData<AlertConfigEntry>) -> Result<(), Error> {
+    let raw = AlertConfigEntry::write_section_config(ALERT_CFG_FILENAME, config)?;
+    replace_config(ALERT_CFG_FILENAME, raw.as_bytes())?;
+    Ok(())
+}
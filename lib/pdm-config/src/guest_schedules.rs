@@ -0,0 +1,34 @@
+use anyhow::Error;
+
+use proxmox_product_config::{ApiLockGuard, open_api_lockfile, replace_config};
+use proxmox_section_config::typed::{ApiSectionDataEntry, SectionConfigData};
+
+use pdm_api_types::{ConfigDigest, guest_schedules::GuestScheduleConfigEntry};
+
+use pdm_buildcfg::configdir;
+
+const GUEST_SCHEDULE_CFG_FILENAME: &str = configdir!("/guest-schedules.cfg");
+const GUEST_SCHEDULE_CFG_LOCKFILE: &str = configdir!("/.guest-schedules.lock");
+
+/// Get the `guest-schedules.cfg` config file contents.
+pub fn config() -> Result<(SectionConfigData<GuestScheduleConfigEntry>, ConfigDigest), Error> {
+    let content = proxmox_sys::fs::file_read_optional_string(GUEST_SCHEDULE_CFG_FILENAME)?
+        .unwrap_or_default();
+
+    let digest = openssl::sha::sha256(content.as_bytes());
+
+    let data =
+        GuestScheduleConfigEntry::parse_section_config(GUEST_SCHEDULE_CFG_FILENAME, &content)?;
+    Ok((data, digest.into()))
+}
+
+/// Get exclusive lock
+pub fn lock_config() -> Result<ApiLockGuard, Error> {
+    open_api_lockfile(GUEST_SCHEDULE_CFG_LOCKFILE, None, true)
+}
+
+pub fn save_config(config: &SectionConfigData<GuestScheduleConfigEntry>) -> Result<(), Error> {
+    let raw = GuestScheduleConfigEntry::write_section_config(GUEST_SCHEDULE_CFG_FILENAME, config)?;
+    replace_config(GUEST_SCHEDULE_CFG_FILENAME, raw.as_bytes())?;
+    Ok(())
+}
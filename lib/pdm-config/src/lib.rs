@@ -2,11 +2,15 @@ use anyhow::{Error, format_err};
 use nix::unistd::{Gid, Group, Uid, User};
 pub use pdm_buildcfg::{BACKUP_GROUP_NAME, BACKUP_USER_NAME};
 
+pub mod alerts;
 pub mod auto_install;
 pub mod ceph;
 pub mod certificate_config;
 pub mod domains;
+pub mod guest_schedules;
+pub mod metric_servers;
 pub mod node;
+pub mod notifications;
 pub mod remotes;
 pub mod setup;
 pub mod subscriptions;
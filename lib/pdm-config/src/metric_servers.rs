@@ -0,0 +1,33 @@
+use anyhow::Error;
+
+use proxmox_product_config::{ApiLockGuard, open_api_lockfile, replace_config};
+use proxmox_section_config::typed::{ApiSectionDataEntry, SectionConfigData};
+
+use pdm_api_types::{ConfigDigest, metric_servers::MetricServerEntry};
+
+use pdm_buildcfg::configdir;
+
+const METRIC_SERVER_CFG_FILENAME: &str = configdir!("/metricserver.cfg");
+const METRIC_SERVER_CFG_LOCKFILE: &str = configdir!("/.metricserver.lock");
+
+/// Get the `metricserver.cfg` config file contents.
+pub fn config() -> Result<(SectionConfigData<MetricServerEntry>, ConfigDigest), Error> {
+    let content =
+        proxmox_sys::fs::file_read_optional_string(METRIC_SERVER_CFG_FILENAME)?.unwrap_or_default();
+
+    let digest = openssl::sha::sha256(content.as_bytes());
+
+    let data = MetricServerEntry::parse_section_config(METRIC_SERVER_CFG_FILENAME, &content)?;
+    Ok((data, digest.into()))
+}
+
+/// Get exclusive lock
+pub fn lock_config() -> Result<ApiLockGuard, Error> {
+    open_api_lockfile(METRIC_SERVER_CFG_LOCKFILE, None, true)
+}
+
+pub fn save_config(config: &SectionConfigData<MetricServerEntry>) -> Result<(), Error> {
+    let raw = MetricServerEntry::write_section_config(METRIC_SERVER_CFG_FILENAME, config)?;
+    replace_config(METRIC_SERVER_CFG_FILENAME, raw.as_bytes())?;
+    Ok(())
+}
@@ -2,39 +2,69 @@
 //!
 //! Provides methods to filter an item over a combination of such terms and
 //! construct them from text, and serialize them back to text.
+//!
+//! The text syntax is a superset of a plain list of whitespace separated terms:
+//!
+//! * `value`, `category:value`: an optional term, at least one optional term must match
+//! * `+value`, `+category:value`: a required term, all required terms must match
+//! * `category=value`, `category<value`, `category<=value`, `category>value`,
+//!   `category>=value`: compare the value of a category, e.g. `cpu>0.8` or `mem>=16G`
+//! * `"quoted value"`, `category:"quoted value"`: values containing whitespace or special
+//!   characters, `\"` and `\\` escape a quote or backslash
+//! * `NOT`, `AND`, `OR` (in order of precedence) and parentheses to combine terms, e.g.
+//!   `type:qemu AND (cpu>0.8 OR NOT status:running)`
+//! * `+(...)` marks a parenthesized expression as required
+//!
+//! Juxtaposed expressions bind the loosest and keep the required/optional semantics from above,
+//! so `a b OR c` is the same as `a (b OR c)`.
 use std::fmt;
 
-#[derive(Default, Clone)]
+mod parse;
+
+pub use parse::ParseError;
+
+#[derive(Debug, Default, Clone, PartialEq)]
 pub struct Search {
-    required_terms: Vec<SearchTerm>,
-    optional_terms: Vec<SearchTerm>,
+    required_terms: Vec<Expression>,
+    optional_terms: Vec<Expression>,
 }
 
 impl FromIterator<SearchTerm> for Search {
     fn from_iter<T: IntoIterator<Item = SearchTerm>>(iter: T) -> Self {
-        let (optional_terms, required_terms) = iter.into_iter().partition(|term| term.optional);
-
-        Self {
-            required_terms,
-            optional_terms,
+        let mut search = Search::new();
+        for term in iter {
+            search.add_term(term);
         }
+        search
     }
 }
 
+/// Parses the search, but never fails.
+///
+/// If the text is not a valid search expression, e.g. because of unbalanced parentheses, it is
+/// split on whitespace and every part is treated as a plain [`SearchTerm`]. Use
+/// [`str::parse`] to get the error instead.
 impl<S: AsRef<str>> From<S> for Search {
     fn from(value: S) -> Self {
+        let value = value.as_ref();
         value
-            .as_ref()
-            .split_whitespace()
-            .map(SearchTerm::from)
-            .collect()
+            .parse()
+            .unwrap_or_else(|_| value.split_whitespace().map(SearchTerm::from).collect())
+    }
+}
+
+impl std::str::FromStr for Search {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse::parse_search(s)
     }
 }
 
 impl Search {
     /// Create a new empty [`Search`]
     pub fn new() -> Self {
-        Self::with_terms(Vec::new())
+        Self::default()
     }
 
     /// Returns true if no [`SearchTerm`] exist
@@ -52,16 +82,27 @@ impl Search {
     /// Returns true if it matches considering the constraints:
     /// if there are no filters, returns true
     pub fn matches<F: FnMut(&SearchTerm) -> bool>(&self, mut matches: F) -> bool {
+        self.matches_with(&mut matches)
+    }
+
+    fn matches_with<F: FnMut(&SearchTerm) -> bool>(&self, matches: &mut F) -> bool {
         if self.is_empty() {
             return true;
         }
 
-        if self.required_terms.iter().map(&mut matches).any(|f| !f) {
+        if self
+            .required_terms
+            .iter()
+            .any(|expr| !expr.matches_with(matches))
+        {
             return false;
         }
 
         if !self.optional_terms.is_empty()
-            && self.optional_terms.iter().map(&mut matches).all(|f| !f)
+            && !self
+                .optional_terms
+                .iter()
+                .any(|expr| expr.matches_with(matches))
         {
             return false;
         }
@@ -72,18 +113,55 @@ impl Search {
     /// Add a term to the search
     pub fn add_term(&mut self, term: SearchTerm) {
         if term.is_optional() {
-            self.optional_terms.push(term);
+            self.optional_terms.push(Expression::Term(term));
         } else {
-            self.required_terms.push(term);
+            self.required_terms.push(Expression::Term(term));
         }
     }
+
+    /// Add an expression to the search, at least one optional expression has to match.
+    pub fn add_optional(&mut self, expr: Expression) {
+        self.optional_terms.push(expr.with_term_optional(true));
+    }
+
+    /// Add an expression to the search that is required to match.
+    pub fn add_required(&mut self, expr: Expression) {
+        self.required_terms.push(expr.with_term_optional(false));
+    }
+
+    /// The expressions that all have to match.
+    pub fn required(&self) -> &[Expression] {
+        &self.required_terms
+    }
+
+    /// The expressions of which at least one has to match, if there are any.
+    pub fn optional(&self) -> &[Expression] {
+        &self.optional_terms
+    }
+
+    /// Iterate over all [`SearchTerm`]s of this search, including nested ones.
+    pub fn terms(&self) -> impl Iterator<Item = &SearchTerm> {
+        let mut terms = Vec::new();
+        for expr in self.required_terms.iter().chain(self.optional_terms.iter()) {
+            expr.collect_terms(&mut terms);
+        }
+        terms.into_iter()
+    }
 }
 
 impl fmt::Display for Search {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let mut sep = "";
-        for term in self.required_terms.iter().chain(self.optional_terms.iter()) {
-            write!(f, "{sep}{term}")?;
+        for expr in &self.required_terms {
+            f.write_str(sep)?;
+            match expr {
+                Expression::Term(_) | Expression::Group(_) => write!(f, "+{expr}")?,
+                _ => write!(f, "+({expr})")?,
+            }
+            sep = " ";
+        }
+        for expr in &self.optional_terms {
+            write!(f, "{sep}{expr}")?;
             sep = " ";
         }
 
@@ -91,11 +169,172 @@ impl fmt::Display for Search {
     }
 }
 
+/// A node of a search expression.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expression {
+    /// A single term.
+    Term(SearchTerm),
+    /// A parenthesized list of required and optional expressions.
+    Group(Search),
+    /// Matches if the inner expression does not.
+    Not(Box<Expression>),
+    /// Matches if all expressions match.
+    And(Vec<Expression>),
+    /// Matches if any expression matches.
+    Or(Vec<Expression>),
+}
+
+impl From<SearchTerm> for Expression {
+    fn from(term: SearchTerm) -> Self {
+        Expression::Term(term)
+    }
+}
+
+impl Expression {
+    /// Test if the expression matches, using `matches` to decide for the single terms.
+    pub fn matches<F: FnMut(&SearchTerm) -> bool>(&self, mut matches: F) -> bool {
+        self.matches_with(&mut matches)
+    }
+
+    fn matches_with<F: FnMut(&SearchTerm) -> bool>(&self, matches: &mut F) -> bool {
+        match self {
+            Expression::Term(term) => matches(term),
+            Expression::Group(search) => search.matches_with(matches),
+            Expression::Not(expr) => !expr.matches_with(matches),
+            Expression::And(list) => list.iter().all(|expr| expr.matches_with(matches)),
+            Expression::Or(list) => list.iter().any(|expr| expr.matches_with(matches)),
+        }
+    }
+
+    fn collect_terms<'a>(&'a self, terms: &mut Vec<&'a SearchTerm>) {
+        match self {
+            Expression::Term(term) => terms.push(term),
+            Expression::Group(search) => terms.extend(search.terms()),
+            Expression::Not(expr) => expr.collect_terms(terms),
+            Expression::And(list) | Expression::Or(list) => {
+                for expr in list {
+                    expr.collect_terms(terms);
+                }
+            }
+        }
+    }
+
+    // keeps the `optional` flag of a top level term in sync with its position in a `Search`
+    fn with_term_optional(self, optional: bool) -> Self {
+        match self {
+            Expression::Term(term) => Expression::Term(term.optional(optional)),
+            other => other,
+        }
+    }
+
+    fn needs_parens_in(&self, parent: &Expression) -> bool {
+        matches!(
+            (parent, self),
+            (
+                Expression::Not(_) | Expression::And(_),
+                Expression::And(_) | Expression::Or(_)
+            ) | (Expression::Or(_), Expression::Or(_))
+        )
+    }
+
+    fn fmt_child(&self, child: &Expression, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if child.needs_parens_in(self) {
+            write!(f, "({child})")
+        } else {
+            write!(f, "{child}")
+        }
+    }
+}
+
+impl fmt::Display for Expression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Expression::Term(term) => term.fmt_body(f),
+            Expression::Group(search) => write!(f, "({search})"),
+            Expression::Not(expr) => {
+                f.write_str("NOT ")?;
+                self.fmt_child(expr, f)
+            }
+            Expression::And(list) | Expression::Or(list) => {
+                let op = if matches!(self, Expression::And(_)) {
+                    " AND "
+                } else {
+                    " OR "
+                };
+                let mut sep = "";
+                for expr in list {
+                    f.write_str(sep)?;
+                    self.fmt_child(expr, f)?;
+                    sep = op;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// How the value of a [`SearchTerm`] is compared to the category.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Operator {
+    /// `:`, the value is matched in a category specific way, e.g. as prefix or substring.
+    #[default]
+    Match,
+    /// `=`, the value has to be equal.
+    Equal,
+    /// `<`
+    Less,
+    /// `<=`
+    LessEqual,
+    /// `>`
+    Greater,
+    /// `>=`
+    GreaterEqual,
+}
+
+impl Operator {
+    /// Returns the textual representation of the operator.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Operator::Match => ":",
+            Operator::Equal => "=",
+            Operator::Less => "<",
+            Operator::LessEqual => "<=",
+            Operator::Greater => ">",
+            Operator::GreaterEqual => ">=",
+        }
+    }
+
+    /// Returns true for the ordering operators `<`, `<=`, `>` and `>=`.
+    pub fn is_ordering(&self) -> bool {
+        !matches!(self, Operator::Match | Operator::Equal)
+    }
+
+    /// Compare a numeric `value` to the value of the search term.
+    ///
+    /// [`Operator::Match`] and [`Operator::Equal`] both test for equality.
+    pub fn compare(&self, value: f64, term_value: f64) -> bool {
+        match self {
+            Operator::Match | Operator::Equal => value == term_value,
+            Operator::Less => value < term_value,
+            Operator::LessEqual => value <= term_value,
+            Operator::Greater => value > term_value,
+            Operator::GreaterEqual => value >= term_value,
+        }
+    }
+}
+
+impl fmt::Display for Operator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct SearchTerm {
     optional: bool,
     pub value: String,
     pub category: Option<String>,
+    pub operator: Operator,
 }
 
 impl SearchTerm {
@@ -105,6 +344,7 @@ impl SearchTerm {
             value: term.into(),
             optional: false,
             category: None,
+            operator: Operator::Match,
         }
     }
 
@@ -114,6 +354,12 @@ impl SearchTerm {
         self
     }
 
+    /// Builder style method to set the operator, only used if a category is set
+    pub fn operator(mut self, operator: Operator) -> Self {
+        self.operator = operator;
+        self
+    }
+
     /// Builder style method to mark this [`SearchTerm`] as optional
     pub fn optional(mut self, optional: bool) -> Self {
         self.optional = optional;
@@ -124,32 +370,96 @@ impl SearchTerm {
     pub fn is_optional(&self) -> bool {
         self.optional
     }
-}
 
-impl<S: AsRef<str>> From<S> for SearchTerm {
-    fn from(value: S) -> Self {
-        let term = value.as_ref();
-        let mut optional = true;
-        let term = if let Some(rest) = term.strip_prefix("+") {
-            if rest.is_empty() {
-                term
-            } else {
-                optional = false;
-                rest
-            }
-        } else {
-            term
+    /// Parse the value as a plain number, a `%` suffix divides it by 100.
+    pub fn value_as_number(&self) -> Option<f64> {
+        match self.value.strip_suffix('%') {
+            Some(percent) => parse_number(percent).map(|value| value / 100.0),
+            None => parse_number(&self.value),
+        }
+    }
+
+    /// Parse the value as a byte size, e.g. `512`, `16G`, `1.5TiB`.
+    ///
+    /// Units are always interpreted as powers of 1024.
+    pub fn value_as_bytes(&self) -> Option<f64> {
+        let (number, unit) = split_unit(&self.value);
+        let exponent = match unit.to_lowercase().as_str() {
+            "" | "b" => 0,
+            "k" | "kb" | "kib" => 1,
+            "m" | "mb" | "mib" => 2,
+            "g" | "gb" | "gib" => 3,
+            "t" | "tb" | "tib" => 4,
+            "p" | "pb" | "pib" => 5,
+            _ => return None,
         };
+        parse_number(number).map(|value| value * 1024f64.powi(exponent))
+    }
 
-        let (term, category) = match term.split_once(':') {
-            Some((category, new_term)) if category.is_empty() || new_term.is_empty() => {
-                (term, None)
+    /// Parse the value as a duration in seconds, e.g. `30`, `90s`, `15m`, `1h`, `2d` or `1w`.
+    pub fn value_as_seconds(&self) -> Option<f64> {
+        let (number, unit) = split_unit(&self.value);
+        let factor = match unit {
+            "" | "s" => 1.0,
+            "m" | "min" => 60.0,
+            "h" => 3600.0,
+            "d" => 86400.0,
+            "w" => 7.0 * 86400.0,
+            _ => return None,
+        };
+        parse_number(number).map(|value| value * factor)
+    }
+
+    // writes the term without the required marker, quoting the value if necessary
+    fn fmt_body(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let operator = match &self.category {
+            Some(category) => {
+                f.write_str(category)?;
+                self.operator
             }
-            Some((category, new_term)) => (new_term, Some(category)),
-            None => (term, None),
+            None => Operator::Match,
         };
 
-        SearchTerm::new(term).optional(optional).category(category)
+        if parse::needs_quotes(self.category.as_deref(), operator, &self.value) {
+            if self.category.is_some() {
+                f.write_str(operator.as_str())?;
+            }
+            f.write_str("\"")?;
+            for c in self.value.chars() {
+                if c == '"' || c == '\\' {
+                    f.write_str("\\")?;
+                }
+                write!(f, "{c}")?;
+            }
+            f.write_str("\"")
+        } else {
+            if self.category.is_some() {
+                f.write_str(operator.as_str())?;
+            }
+            f.write_str(&self.value)
+        }
+    }
+}
+
+fn parse_number(value: &str) -> Option<f64> {
+    value
+        .trim()
+        .parse::<f64>()
+        .ok()
+        .filter(|value| value.is_finite())
+}
+
+fn split_unit(value: &str) -> (&str, &str) {
+    let pos = value
+        .find(|c: char| c.is_ascii_alphabetic())
+        .unwrap_or(value.len());
+    value.split_at(pos)
+}
+
+impl<S: AsRef<str>> From<S> for SearchTerm {
+    fn from(value: S) -> Self {
+        let (required, term) = parse::split_term(value.as_ref());
+        term.optional(!required)
     }
 }
 
@@ -159,18 +469,13 @@ impl fmt::Display for SearchTerm {
             f.write_str("+")?;
         }
 
-        if let Some(cat) = &self.category {
-            f.write_str(cat)?;
-            f.write_str(":")?;
-        }
-
-        f.write_str(&self.value)
+        self.fmt_body(f)
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::{Search, SearchTerm};
+    use crate::{Expression, Operator, Search, SearchTerm};
 
     #[test]
     fn parse_test_simple_filter() {
@@ -208,6 +513,133 @@ mod tests {
         assert_eq!(SearchTerm::from(":"), SearchTerm::new(":").optional(true));
     }
 
+    #[test]
+    fn parse_test_operator_filter() {
+        assert_eq!(
+            SearchTerm::from("cpu>0.8"),
+            SearchTerm::new("0.8")
+                .optional(true)
+                .category(Some("cpu"))
+                .operator(Operator::Greater)
+        );
+        assert_eq!(
+            SearchTerm::from("+mem>=16G"),
+            SearchTerm::new("16G")
+                .category(Some("mem"))
+                .operator(Operator::GreaterEqual)
+        );
+        assert_eq!(
+            SearchTerm::from("uptime<=1h"),
+            SearchTerm::new("1h")
+                .optional(true)
+                .category(Some("uptime"))
+                .operator(Operator::LessEqual)
+        );
+        assert_eq!(
+            SearchTerm::from("name=foo"),
+            SearchTerm::new("foo")
+                .optional(true)
+                .category(Some("name"))
+                .operator(Operator::Equal)
+        );
+        // no valid category, keep the whole text as value
+        assert_eq!(
+            SearchTerm::from("a b>1"),
+            SearchTerm::new("a b>1").optional(true)
+        );
+        assert_eq!(SearchTerm::from(">1"), SearchTerm::new(">1").optional(true));
+        assert_eq!(
+            SearchTerm::from("cpu>"),
+            SearchTerm::new("cpu>").optional(true)
+        );
+    }
+
+    #[test]
+    fn parse_expression() {
+        let term = |value: &str| Expression::Term(SearchTerm::from(value));
+
+        let search: Search = "type:qemu AND (cpu>0.8 OR NOT status:running)"
+            .parse()
+            .unwrap();
+        let mut expected = Search::new();
+        expected.add_optional(Expression::And(vec![
+            term("type:qemu"),
+            Expression::Or(vec![
+                term("cpu>0.8"),
+                Expression::Not(Box::new(term("status:running"))),
+            ]),
+        ]));
+        assert_eq!(search, expected);
+
+        // NOT binds stronger than AND, AND stronger than OR
+        let search: Search = "NOT a AND b OR c".parse().unwrap();
+        let mut expected = Search::new();
+        expected.add_optional(Expression::Or(vec![
+            Expression::And(vec![Expression::Not(Box::new(term("a"))), term("b")]),
+            term("c"),
+        ]));
+        assert_eq!(search, expected);
+
+        // juxtaposition binds the loosest and keeps the required/optional semantics
+        let search: Search = "+a b OR c +(d e)".parse().unwrap();
+        let mut expected = Search::new();
+        expected.add_required(term("a"));
+        expected.add_optional(Expression::Or(vec![term("b"), term("c")]));
+        expected.add_required(Expression::Group(Search::with_terms([
+            SearchTerm::from("d"),
+            SearchTerm::from("e"),
+        ])));
+        assert_eq!(search, expected);
+
+        // lower case keywords are plain values
+        let search: Search = "a and b".parse().unwrap();
+        assert_eq!(
+            search,
+            Search::with_terms(["a", "and", "b"].map(SearchTerm::from))
+        );
+    }
+
+    #[test]
+    fn parse_quoted() {
+        let search: Search = r#"name:"my vm" "a b" +"OR" "\"\\""#.parse().unwrap();
+        assert_eq!(
+            search,
+            Search::with_terms([
+                SearchTerm::new("my vm")
+                    .optional(true)
+                    .category(Some("name")),
+                SearchTerm::new("a b").optional(true),
+                SearchTerm::new("OR"),
+                SearchTerm::new(r#""\"#).optional(true),
+            ])
+        );
+    }
+
+    #[test]
+    fn parse_errors() {
+        for input in [
+            "(a",
+            "a)",
+            "()",
+            "a AND",
+            "OR a",
+            "NOT",
+            "a AND OR b",
+            "\"unterminated",
+            "name:\"a\"b",
+            "a OR +b",
+            "foo bar\"baz\"",
+        ] {
+            assert!(input.parse::<Search>().is_err(), "input: {input}");
+        }
+
+        // the infallible conversion falls back to plain terms
+        assert_eq!(
+            Search::from("(a b"),
+            Search::with_terms([SearchTerm::from("(a"), SearchTerm::from("b")])
+        );
+    }
+
     #[test]
     fn match_tests() {
         let search = Search::from_iter(vec![
@@ -253,6 +685,35 @@ mod tests {
         }
     }
 
+    #[test]
+    fn match_expression() {
+        let search: Search = "a AND (b OR NOT c)".parse().unwrap();
+
+        // each case contains results for a, b, c and if it should match or not
+        let cases = [
+            ((true, true, true), true),
+            ((true, false, false), true),
+            ((true, false, true), false),
+            ((false, true, false), false),
+        ];
+        for (input, expected) in cases {
+            let matches = search.matches(|term| match term.value.as_str() {
+                "a" => input.0,
+                "b" => input.1,
+                "c" => input.2,
+                _ => unreachable!(),
+            });
+            assert_eq!(matches, expected, "input: {input:?}");
+        }
+    }
+
+    #[test]
+    fn test_terms() {
+        let search: Search = "+view:foo a OR NOT (b AND c:d)".parse().unwrap();
+        let values: Vec<&str> = search.terms().map(|term| term.value.as_str()).collect();
+        assert_eq!(values, ["foo", "a", "b", "d"]);
+    }
+
     #[test]
     fn test_display() {
         let term = SearchTerm::new("foo");
@@ -272,5 +733,76 @@ mod tests {
 
         let term = SearchTerm::new("foo").optional(false).category(Some("bar"));
         assert_eq!("+bar:foo", &term.to_string());
+
+        let term = SearchTerm::new("16G")
+            .category(Some("mem"))
+            .operator(Operator::GreaterEqual);
+        assert_eq!("+mem>=16G", &term.to_string());
+
+        let term = SearchTerm::new("my vm")
+            .optional(true)
+            .category(Some("name"));
+        assert_eq!(r#"name:"my vm""#, &term.to_string());
+
+        let term = SearchTerm::new("a:b").optional(true);
+        assert_eq!(r#""a:b""#, &term.to_string());
+
+        let term = SearchTerm::new("NOT").optional(true);
+        assert_eq!(r#""NOT""#, &term.to_string());
+    }
+
+    #[test]
+    fn test_display_round_trip() {
+        for input in [
+            "foo",
+            "+foo bar",
+            "+cat: :bar",
+            "type:qemu AND (cpu>0.8 OR mem>=16G)",
+            "NOT (a OR b) AND c",
+            "+(a OR b) c d",
+            "+(a b) (c +d)",
+            "uptime<1h OR NOT status:running",
+            r#"name:"my vm" OR "AND" OR "a\"b""#,
+            "(a AND b) AND c",
+        ] {
+            let search: Search = input.parse().unwrap();
+            let text = search.to_string();
+            let reparsed: Search = text.parse().unwrap();
+            assert_eq!(search, reparsed, "input: {input}, text: {text}");
+        }
+
+        let search: Search = "+a OR b  c".parse().unwrap();
+        assert_eq!(search.to_string(), "+(a OR b) c");
+
+        let search: Search = "type:qemu AND (cpu>0.8 OR mem>=16G)".parse().unwrap();
+        assert_eq!(search.to_string(), "type:qemu AND (cpu>0.8 OR mem>=16G)");
+    }
+
+    #[test]
+    fn test_value_units() {
+        let value = |value: &str| SearchTerm::new(value);
+
+        assert_eq!(value("0.8").value_as_number(), Some(0.8));
+        assert_eq!(value("80%").value_as_number(), Some(0.8));
+        assert_eq!(value("foo").value_as_number(), None);
+
+        assert_eq!(value("512").value_as_bytes(), Some(512.0));
+        assert_eq!(
+            value("16G").value_as_bytes(),
+            Some(16.0 * 1024.0 * 1024.0 * 1024.0)
+        );
+        assert_eq!(value("1.5KiB").value_as_bytes(), Some(1536.0));
+        assert_eq!(value("2mb").value_as_bytes(), Some(2.0 * 1024.0 * 1024.0));
+        assert_eq!(value("1X").value_as_bytes(), None);
+
+        assert_eq!(value("30").value_as_seconds(), Some(30.0));
+        assert_eq!(value("15m").value_as_seconds(), Some(900.0));
+        assert_eq!(value("1h").value_as_seconds(), Some(3600.0));
+        assert_eq!(value("2d").value_as_seconds(), Some(172800.0));
+        assert_eq!(value("1y").value_as_seconds(), None);
+
+        assert!(Operator::Greater.compare(0.9, 0.8));
+        assert!(!Operator::Less.compare(0.9, 0.8));
+        assert!(Operator::GreaterEqual.compare(0.8, 0.8));
     }
 }
@@ -0,0 +1,336 @@
+//! Tokenizer and recursive descent parser for the search syntax described in the crate docs.
+
+use std::fmt;
+
+use crate::{Expression, Operator, Search, SearchTerm};
+
+/// Error returned when a search expression cannot be parsed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseError {
+    message: String,
+}
+
+impl ParseError {
+    fn new<S: Into<String>>(message: S) -> Self {
+        Self {
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid search - {}", self.message)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+#[derive(Debug)]
+enum Token {
+    LParen,
+    RParen,
+    /// A `+` directly in front of an opening parenthesis.
+    Plus,
+    And,
+    Or,
+    Not,
+    Term {
+        required: bool,
+        term: SearchTerm,
+    },
+}
+
+fn is_category_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '-' || c == '_'
+}
+
+fn is_operator_char(c: char) -> bool {
+    matches!(c, ':' | '<' | '>' | '=')
+}
+
+// Finds the first operator in `text`, returns its position, the operator and its length.
+fn find_operator(text: &str) -> Option<(usize, Operator, usize)> {
+    let pos = text.find(is_operator_char)?;
+    let rest = &text[pos..];
+    let (operator, len) = if rest.starts_with("<=") {
+        (Operator::LessEqual, 2)
+    } else if rest.starts_with(">=") {
+        (Operator::GreaterEqual, 2)
+    } else {
+        match rest.as_bytes()[0] {
+            b':' => (Operator::Match, 1),
+            b'<' => (Operator::Less, 1),
+            b'>' => (Operator::Greater, 1),
+            _ => (Operator::Equal, 1),
+        }
+    };
+    Some((pos, operator, len))
+}
+
+fn valid_category(category: &str, operator: Operator) -> bool {
+    // `:` allowed arbitrary categories before, keep that for compatibility
+    !category.is_empty() && (operator == Operator::Match || category.chars().all(is_category_char))
+}
+
+/// Splits an unquoted word into the required marker and a term.
+///
+/// If the text before the first operator is not a valid category, or nothing follows the
+/// operator, the whole word is used as value.
+pub(crate) fn split_term(word: &str) -> (bool, SearchTerm) {
+    let (required, word) = match word.strip_prefix('+') {
+        Some(rest) if !rest.is_empty() => (true, rest),
+        _ => (false, word),
+    };
+
+    if let Some((pos, operator, len)) = find_operator(word) {
+        let category = &word[..pos];
+        let value = &word[pos + len..];
+        if valid_category(category, operator) && !value.is_empty() {
+            let term = SearchTerm::new(value)
+                .category(Some(category))
+                .operator(operator);
+            return (required, term);
+        }
+    }
+
+    (required, SearchTerm::new(word))
+}
+
+/// Checks if a value has to be quoted to be parsed back into the same term.
+pub(crate) fn needs_quotes(category: Option<&str>, operator: Operator, value: &str) -> bool {
+    if value.is_empty()
+        || value
+            .chars()
+            .any(|c| c.is_whitespace() || matches!(c, '"' | '(' | ')'))
+    {
+        return true;
+    }
+
+    let text = match category {
+        Some(category) => format!("{category}{operator}{value}"),
+        None => {
+            if matches!(value, "AND" | "OR" | "NOT") {
+                return true;
+            }
+            value.to_string()
+        }
+    };
+
+    let (required, term) = split_term(&text);
+    required
+        || term.category.as_deref() != category
+        || term.value != value
+        || (category.is_some() && term.operator != operator)
+}
+
+// Parses the part in front of a quoted value, e.g. `+name:`.
+fn parse_prefix(prefix: &str, value: String) -> Result<(bool, SearchTerm), ParseError> {
+    let (required, rest) = match prefix.strip_prefix('+') {
+        Some(rest) => (true, rest),
+        None => (false, prefix),
+    };
+
+    if rest.is_empty() {
+        return Ok((required, SearchTerm::new(value)));
+    }
+
+    match find_operator(rest) {
+        Some((pos, operator, len))
+            if pos + len == rest.len() && valid_category(&rest[..pos], operator) =>
+        {
+            let term = SearchTerm::new(value)
+                .category(Some(&rest[..pos]))
+                .operator(operator);
+            Ok((required, term))
+        }
+        _ => Err(ParseError::new(format!(
+            "invalid category in front of quoted value '{prefix}'"
+        ))),
+    }
+}
+
+fn tokenize(text: &str) -> Result<Vec<Token>, ParseError> {
+    let mut tokens = Vec::new();
+    let mut chars = text.chars().peekable();
+
+    while let Some(&c) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+            continue;
+        }
+        if c == '(' || c == ')' {
+            chars.next();
+            tokens.push(if c == '(' {
+                Token::LParen
+            } else {
+                Token::RParen
+            });
+            continue;
+        }
+
+        let mut prefix = String::new();
+        while let Some(&c) = chars.peek() {
+            if c.is_whitespace() || matches!(c, '(' | ')' | '"') {
+                break;
+            }
+            prefix.push(c);
+            chars.next();
+        }
+
+        if chars.peek() != Some(&'"') {
+            let token = match prefix.as_str() {
+                "AND" => Token::And,
+                "OR" => Token::Or,
+                "NOT" => Token::Not,
+                "+" if chars.peek() == Some(&'(') => Token::Plus,
+                _ => {
+                    let (required, term) = split_term(&prefix);
+                    Token::Term { required, term }
+                }
+            };
+            tokens.push(token);
+            continue;
+        }
+
+        chars.next(); // opening quote
+        let mut value = String::new();
+        loop {
+            match chars.next() {
+                Some('"') => break,
+                Some('\\') => match chars.next() {
+                    Some(c) => value.push(c),
+                    None => return Err(ParseError::new("unterminated quoted value")),
+                },
+                Some(c) => value.push(c),
+                None => return Err(ParseError::new("unterminated quoted value")),
+            }
+        }
+
+        match chars.peek() {
+            None | Some('(') | Some(')') => {}
+            Some(c) if c.is_whitespace() => {}
+            Some(c) => {
+                return Err(ParseError::new(format!(
+                    "unexpected character '{c}' after quoted value"
+                )));
+            }
+        }
+
+        let (required, term) = parse_prefix(&prefix, value)?;
+        tokens.push(Token::Term { required, term });
+    }
+
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: std::iter::Peekable<std::vec::IntoIter<Token>>,
+}
+
+impl Parser {
+    fn parse_search(&mut self, nested: bool) -> Result<Search, ParseError> {
+        let mut search = Search::new();
+
+        loop {
+            match self.tokens.peek_mut() {
+                None if nested => return Err(ParseError::new("missing closing parenthesis")),
+                None => break,
+                Some(Token::RParen) if nested => break,
+                Some(Token::RParen) => {
+                    return Err(ParseError::new("unexpected closing parenthesis"));
+                }
+                Some(Token::Plus) => {
+                    self.tokens.next();
+                    let expr = self.parse_or()?;
+                    search.add_required(expr);
+                }
+                Some(Token::Term { required, .. }) if *required => {
+                    *required = false;
+                    let expr = self.parse_or()?;
+                    search.add_required(expr);
+                }
+                Some(_) => {
+                    let expr = self.parse_or()?;
+                    search.add_optional(expr);
+                }
+            }
+        }
+
+        Ok(search)
+    }
+
+    fn parse_or(&mut self) -> Result<Expression, ParseError> {
+        let mut list = vec![self.parse_and()?];
+        while let Some(Token::Or) = self.tokens.peek() {
+            self.tokens.next();
+            list.push(self.parse_and()?);
+        }
+
+        Ok(if list.len() == 1 {
+            list.remove(0)
+        } else {
+            Expression::Or(list)
+        })
+    }
+
+    fn parse_and(&mut self) -> Result<Expression, ParseError> {
+        let mut list = vec![self.parse_not()?];
+        while let Some(Token::And) = self.tokens.peek() {
+            self.tokens.next();
+            list.push(self.parse_not()?);
+        }
+
+        Ok(if list.len() == 1 {
+            list.remove(0)
+        } else {
+            Expression::And(list)
+        })
+    }
+
+    fn parse_not(&mut self) -> Result<Expression, ParseError> {
+        if let Some(Token::Not) = self.tokens.peek() {
+            self.tokens.next();
+            return Ok(Expression::Not(Box::new(self.parse_not()?)));
+        }
+
+        self.parse_primary()
+    }
+
+    fn parse_primary(&mut self) -> Result<Expression, ParseError> {
+        match self.tokens.next() {
+            Some(Token::LParen) => {
+                let mut inner = self.parse_search(true)?;
+                self.tokens.next(); // the closing parenthesis, checked by `parse_search`
+                if inner.is_empty() {
+                    return Err(ParseError::new("empty parentheses"));
+                }
+                if inner.required_terms.is_empty() && inner.optional_terms.len() == 1 {
+                    // parentheses only used for precedence
+                    return Ok(inner.optional_terms.remove(0));
+                }
+                Ok(Expression::Group(inner))
+            }
+            Some(Token::Term {
+                required: false,
+                term,
+            }) => Ok(Expression::Term(term.optional(true))),
+            Some(Token::Term { required: true, .. }) | Some(Token::Plus) => Err(ParseError::new(
+                "'+' is only allowed at the start of a search term",
+            )),
+            Some(Token::RParen) => Err(ParseError::new("unexpected closing parenthesis")),
+            Some(Token::And) => Err(ParseError::new("unexpected 'AND'")),
+            Some(Token::Or) => Err(ParseError::new("unexpected 'OR'")),
+            Some(Token::Not) => unreachable!("handled by parse_not"),
+            None => Err(ParseError::new("unexpected end of search")),
+        }
+    }
+}
+
+pub(crate) fn parse_search(text: &str) -> Result<Search, ParseError> {
+    let tokens = tokenize(text)?;
+    let mut parser = Parser {
+        tokens: tokens.into_iter().peekable(),
+    };
+    parser.parse_search(false)
+}
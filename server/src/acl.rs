@@ -1,3 +1,16 @@
+//! Access control setup and privilege lookups.
+
+use std::collections::HashMap;
+use std::sync::{Arc, OnceLock};
+
+use anyhow::Error;
+
+use proxmox_access_control::acl::{AclTree, AclTreeNode};
+use proxmox_router::{UserInformation, http_err};
+
+use pdm_api_types::remotes::{REMOTE_FOLDER_ACL_COMPONENT, folder_acl_path};
+use pdm_api_types::{Authid, Userid};
+
 pub(crate) fn init() {
     static ACCESS_CONTROL_CONFIG: pdm_api_types::AccessControlConfig =
         pdm_api_types::AccessControlConfig;
@@ -5,3 +18,274 @@ pub(crate) fn init() {
     proxmox_access_control::init::init(&ACCESS_CONTROL_CONFIG, pdm_buildcfg::configdir!("/access"))
         .expect("failed to setup access control config");
 }
+
+/// The remote an ACL path below `/resource` belongs to, if any.
+fn remote_of_path<'a>(path: &[&'a str]) -> Option<&'a str> {
+    match path {
+        ["resource", remote, ..] if *remote != REMOTE_FOLDER_ACL_COMPONENT => Some(remote),
+        _ => None,
+    }
+}
+
+/// Check whether `auth_id` has ACL entries on the part of `path` which belongs to the remote,
+/// i.e. on `/resource/{remote}` or on any of its sub paths leading to `path`.
+///
+/// For API tokens, entries of the owning user count as well.
+fn has_remote_acl_entries(tree: &AclTree, auth_id: &Authid, path: &[&str]) -> bool {
+    let user = auth_id
+        .is_token()
+        .then(|| Authid::from(auth_id.user().clone()));
+    let has_entries = |node: &AclTreeNode, leaf: bool| {
+        !node.extract_roles(auth_id, leaf).is_empty()
+            || user
+                .as_ref()
+                .is_some_and(|user| !node.extract_roles(user, leaf).is_empty())
+    };
+
+    let mut node = &tree.root;
+    for (depth, component) in path.iter().enumerate() {
+        node = match node.children.get(*component) {
+            Some(child) => child,
+            None => return false,
+        };
+        // skip the `/resource` node itself
+        if depth > 0 && has_entries(node, depth + 1 == path.len()) {
+            return true;
+        }
+    }
+    false
+}
+
+/// Combine the privileges looked up on a remote's ACL path with the ones of its folder.
+///
+/// The folder is a level between `/resource` and `/resource/{remote}`: without ACL entries on the
+/// remote's part of the path, the remote inherits what propagates from the folder, which already
+/// includes everything inherited from `/resource` and the parent folders. ACL entries on the
+/// remote's part of the path are more specific and take precedence.
+fn remote_privs_details(
+    path_details: (u64, u64),
+    folder_details: (u64, u64),
+    has_remote_entries: bool,
+) -> (u64, u64) {
+    if has_remote_entries {
+        path_details
+    } else {
+        let (_privs, propagated) = folder_details;
+        (propagated, propagated)
+    }
+}
+
+/// Privilege lookups which honor remote folders.
+///
+/// Wraps [`proxmox_access_control::CachedUserInfo`]. Remote folders act as a level between
+/// `/resource` and `/resource/{remote}`, so a remote's ACL path (`/resource/{remote}` and
+/// everything below) inherits the privileges propagated from `/resource/folder/{folder}`, unless
+/// there are more specific ACL entries on the remote's path.
+pub struct CachedUserInfo {
+    user_info: Arc<proxmox_access_control::CachedUserInfo>,
+    folders: OnceLock<HashMap<String, String>>,
+    acl_tree: OnceLock<Option<AclTree>>,
+}
+
+impl CachedUserInfo {
+    /// Returns the cached user information, the remote folders are loaded on first use.
+    pub fn new() -> Result<Arc<Self>, Error> {
+        Ok(Arc::new(Self {
+            user_info: proxmox_access_control::CachedUserInfo::new()?,
+            folders: OnceLock::new(),
+            acl_tree: OnceLock::new(),
+        }))
+    }
+
+    fn folder_of(&self, remote: &str) -> Option<&str> {
+        let folders = self
+            .folders
+            .get_or_init(|| match pdm_config::remotes::config() {
+                Ok((remotes, _)) => remotes
+                    .into_iter()
+                    .filter_map(|(id, remote)| Some((id, remote.folder?)))
+                    .collect(),
+                Err(err) => {
+                    log::error!("unable to read remote config, ignoring folder privileges - {err}");
+                    HashMap::new()
+                }
+            });
+        folders.get(remote).map(String::as_str)
+    }
+
+    fn acl_tree(&self) -> Option<&AclTree> {
+        self.acl_tree
+            .get_or_init(|| match proxmox_access_control::acl::config() {
+                Ok((tree, _digest)) => Some(tree),
+                Err(err) => {
+                    log::error!("unable to read ACL config, ignoring folder privileges - {err}");
+                    None
+                }
+            })
+            .as_ref()
+    }
+
+    /// The folder ACL path to take into account for `path`, if it belongs to a remote in a folder.
+    fn folder_acl_path_of(&self, path: &[&str]) -> Option<Vec<&str>> {
+        remote_of_path(path)
+            .and_then(|remote| self.folder_of(remote))
+            .map(folder_acl_path)
+    }
+
+    pub fn is_superuser(&self, auth_id: &Authid) -> bool {
+        self.user_info.is_superuser(auth_id)
+    }
+
+    pub fn is_active_auth_id(&self, auth_id: &Authid) -> bool {
+        self.user_info.is_active_auth_id(auth_id)
+    }
+
+    pub fn is_active_user_id(&self, userid: &Userid) -> bool {
+        self.user_info.is_active_user_id(userid)
+    }
+
+    /// Look up the privileges of `auth_id` on `path`, including the ones of remote folders.
+    pub fn lookup_privs(&self, auth_id: &Authid, path: &[&str]) -> u64 {
+        self.lookup_privs_details(auth_id, path).0
+    }
+
+    /// Like [`lookup_privs`](Self::lookup_privs), but returns the privileges and the subset of
+    /// them which propagate.
+    pub fn lookup_privs_details(&self, auth_id: &Authid, path: &[&str]) -> (u64, u64) {
+        let path_details = self.user_info.lookup_privs_details(auth_id, path);
+
+        let (Some(folder_path), Some(tree)) = (self.folder_acl_path_of(path), self.acl_tree())
+        else {
+            return path_details;
+        };
+
+        remote_privs_details(
+            path_details,
+            self.user_info.lookup_privs_details(auth_id, &folder_path),
+            has_remote_acl_entries(tree, auth_id, path),
+        )
+    }
+
+    /// Check the privileges of `auth_id` on `path`, including the ones of remote folders.
+    ///
+    /// With `partial` set, having any of the `required_privs` is enough.
+    pub fn check_privs(
+        &self,
+        auth_id: &Authid,
+        path: &[&str],
+        required_privs: u64,
+        partial: bool,
+    ) -> Result<(), Error> {
+        if self.folder_acl_path_of(path).is_none() {
+            return self
+                .user_info
+                .check_privs(auth_id, path, required_privs, partial);
+        }
+
+        let privs = self.lookup_privs(auth_id, path);
+        let allowed = if partial {
+            privs & required_privs != 0
+        } else {
+            privs & required_privs == required_privs
+        };
+        if !allowed {
+            return Err(http_err!(FORBIDDEN, "permission check failed"));
+        }
+        Ok(())
+    }
+
+    /// Check if `auth_id` has any of `privs` on `path` or any path below it.
+    pub fn any_privs_below(
+        &self,
+        auth_id: &Authid,
+        path: &[&str],
+        privs: u64,
+    ) -> Result<bool, Error> {
+        if self.lookup_privs(auth_id, path) & privs != 0 {
+            return Ok(true);
+        }
+        self.user_info.any_privs_below(auth_id, path, privs)
+    }
+}
+
+impl UserInformation for CachedUserInfo {
+    fn is_superuser(&self, userid: &str) -> bool {
+        UserInformation::is_superuser(&*self.user_info, userid)
+    }
+
+    fn is_group_member(&self, userid: &str, group: &str) -> bool {
+        UserInformation::is_group_member(&*self.user_info, userid, group)
+    }
+
+    fn lookup_privs(&self, auth_id: &str, path: &[&str]) -> u64 {
+        match auth_id.parse::<Authid>() {
+            Ok(auth_id) => CachedUserInfo::lookup_privs(self, &auth_id, path),
+            Err(_) => 0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pdm_api_types::{ROLE_ADMINISTRATOR, ROLE_AUDITOR, ROLE_NO_ACCESS};
+
+    use super::*;
+
+    #[test]
+    fn remote_paths() {
+        assert_eq!(remote_of_path(&["resource", "pve1"]), Some("pve1"));
+        assert_eq!(
+            remote_of_path(&["resource", "pve1", "guest", "100"]),
+            Some("pve1")
+        );
+        assert_eq!(remote_of_path(&["resource"]), None);
+        assert_eq!(remote_of_path(&["resource", "folder", "site-a"]), None);
+        assert_eq!(remote_of_path(&["view", "pve1"]), None);
+    }
+
+    #[test]
+    fn remote_entries_override_folder() {
+        let user: Authid = "user@pdm".parse().unwrap();
+        let token: Authid = "user@pdm!token".parse().unwrap();
+
+        let mut tree = AclTree::new();
+        tree.insert_user_role("/resource/folder/site-a", &user, "Administrator", true);
+        tree.insert_user_role("/resource/pve1", &user, "NoAccess", true);
+        tree.insert_user_role("/resource/pve2/guest/100", &user, "NoAccess", true);
+        tree.insert_user_role("/resource/pve3/guest", &user, "Auditor", false);
+
+        for (path, expected) in [
+            (&["resource", "pve1"][..], true),
+            (&["resource", "pve1", "guest", "100"], true),
+            (&["resource", "pve2"], false),
+            (&["resource", "pve2", "guest", "100"], true),
+            (&["resource", "pve2", "guest", "101"], false),
+            (&["resource", "pve3", "guest"], true),
+            // not propagated to the guests
+            (&["resource", "pve3", "guest", "100"], false),
+            (&["resource", "pve4"], false),
+        ] {
+            assert_eq!(
+                has_remote_acl_entries(&tree, &user, path),
+                expected,
+                "{path:?}"
+            );
+            // the owner's entries count for its tokens
+            assert_eq!(
+                has_remote_acl_entries(&tree, &token, path),
+                expected,
+                "{path:?}"
+            );
+        }
+
+        let folder = (ROLE_ADMINISTRATOR, ROLE_ADMINISTRATOR);
+        let no_access = (ROLE_NO_ACCESS, ROLE_NO_ACCESS);
+        assert_eq!(remote_privs_details(no_access, folder, true), no_access);
+        assert_eq!(remote_privs_details(no_access, folder, false), folder);
+        // privileges which do not propagate from the folder do not apply to the remote
+        assert_eq!(
+            remote_privs_details(no_access, (ROLE_AUDITOR, 0), false),
+            no_access
+        );
+    }
+}
@@ -0,0 +1,358 @@
+//! Threshold based alerts, evaluated against the collected metrics.
+//!
+//! The rules from `alerts.cfg` are evaluated periodically in the API daemon, which owns the RRD
+//! cache. The state of all alerts is kept in memory and persisted to the state directory, so
+//! that pending and firing alerts survive a restart.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::{LazyLock, Mutex};
+
+use anyhow::{Error, format_err};
+
+use proxmox_rrd_api_types::RrdTimeframe;
+
+use pdm_api_types::alerts::{Alert, AlertConfigEntry, AlertMetric, AlertSilence};
+use pdm_api_types::resource::Resource;
+use pdm_api_types::views::{FilterRule, ViewConfig};
+use pdm_buildcfg::PDM_STATE_DIR_M;
+
+use crate::metric_collection::forecast;
+use crate::metric_collection::rrd_cache::{self, RrdCache};
+use crate::views::View;
+
+mod state;
+use state::{AlertStates, Sample, Transition};
+
+const ALERT_STATE_FN: &str = concat!(PDM_STATE_DIR_M!(), "/alerts.json");
+
+/// Values older than this (in seconds) are considered outdated and not evaluated.
+const MAX_VALUE_AGE: i64 = 10 * 60;
+
+static STATE: LazyLock<Mutex<AlertStates>> = LazyLock::new(|| Mutex::new(load_state()));
+
+fn load_state() -> AlertStates {
+    let content = match proxmox_sys::fs::file_read_optional_string(ALERT_STATE_FN) {
+        Ok(Some(content)) => content,
+        Ok(None) => return AlertStates::default(),
+        Err(err) => {
+            log::error!("could not read alert state - {err}");
+            return AlertStates::default();
+        }
+    };
+
+    serde_json::from_str(&content)
+        .inspect_err(|err| log::error!("could not parse alert state - {err}"))
+        .unwrap_or_default()
+}
+
+fn save_state(states: &AlertStates) -> Result<(), Error> {
+    let data = serde_json::to_vec_pretty(states)?;
+    proxmox_sys::fs::replace_file(
+        ALERT_STATE_FN,
+        &data,
+        proxmox_product_config::default_create_options(),
+        true,
+    )?;
+
+    Ok(())
+}
+
+/// Get all pending, firing and recently resolved alerts.
+pub fn alerts() -> Vec<Alert> {
+    STATE.lock().unwrap().alerts().to_vec()
+}
+
+/// Acknowledge an alert in the name of `user`.
+pub fn acknowledge(rule: &str, resource: &str, user: &str) -> Result<(), Error> {
+    let mut states = STATE.lock().unwrap();
+
+    let alert = states
+        .get_mut(rule, resource)
+        .ok_or_else(|| format_err!("no alert of rule '{rule}' for '{resource}'"))?;
+    alert.acknowledged_by = Some(user.to_string());
+    alert.acknowledged = Some(proxmox_time::epoch_i64());
+
+    save_state(&states)
+}
+
+/// The resources of all remotes, as found in the resource cache.
+pub struct CachedRemoteResources {
+    remotes: Vec<(String, Vec<Resource>)>,
+}
+
+impl CachedRemoteResources {
+    /// Load the cached resources of all remotes.
+    pub fn load() -> Result<Self, Error> {
+        let (remotes, _) = pdm_config::remotes::config()?;
+
+        let mut result = Vec::new();
+        for (remote, _) in remotes.into_iter() {
+            let resources =
+                crate::api::resources::get_cached_resources_blocking(&remote, i64::MAX as u64)?
+                    .map(|cached| cached.resources)
+                    .unwrap_or_default();
+            result.push((remote, resources));
+        }
+
+        Ok(Self { remotes: result })
+    }
+
+    fn by_global_id(&self) -> HashMap<&str, &Resource> {
+        self.remotes
+            .iter()
+            .flat_map(|(_, resources)| resources)
+            .map(|resource| (resource.global_id(), resource))
+            .collect()
+    }
+
+    /// Check if the resource of an alert is part of a view.
+    pub fn alert_in_view(&self, view: &View, alert: &Alert) -> bool {
+        let resource = self
+            .remotes
+            .iter()
+            .filter(|(remote, _)| *remote == alert.remote)
+            .flat_map(|(_, resources)| resources)
+            .find(|resource| resource.global_id() == alert.resource);
+
+        Scope::matches_view(view, &alert.remote, resource)
+    }
+}
+
+/// The resources a rule or silence applies to.
+struct Scope {
+    view: Option<View>,
+    filter: View,
+}
+
+impl Scope {
+    fn new(
+        view: Option<View>,
+        include: Vec<FilterRule>,
+        exclude: Vec<FilterRule>,
+    ) -> Result<Self, Error> {
+        let filter = crate::views::load_remote_folders(View::new(ViewConfig {
+            include_all: Some(include.is_empty()),
+            include,
+            exclude,
+            ..Default::default()
+        }))?;
+
+        Ok(Self { view, filter })
+    }
+
+    fn matches(&self, remote: &str, resource: Option<&Resource>) -> bool {
+        self.view
+            .as_ref()
+            .is_none_or(|view| Self::matches_view(view, remote, resource))
+            && Self::matches_view(&self.filter, remote, resource)
+    }
+
+    // Alerts about a remote itself have no resource, the remote has to be included as a whole.
+    fn matches_view(view: &View, remote: &str, resource: Option<&Resource>) -> bool {
+        match resource {
+            Some(resource) => view.resource_matches(remote, resource),
+            None => view.is_remote_explicitly_included(remote),
+        }
+    }
+}
+
+struct Silence {
+    rule: Option<String>,
+    scope: Scope,
+}
+
+impl Silence {
+    fn new(silence: AlertSilence) -> Result<Self, Error> {
+        Ok(Self {
+            rule: silence.rule,
+            scope: Scope::new(None, silence.include, Vec::new())?,
+        })
+    }
+
+    fn matches(&self, alert: &Alert, resources: &HashMap<&str, &Resource>) -> bool {
+        self.rule.as_ref().is_none_or(|rule| *rule == alert.rule)
+            && self.scope.matches(
+                &alert.remote,
+                resources.get(alert.resource.as_str()).copied(),
+            )
+    }
+}
+
+/// Evaluate all alert rules, notify about fired and resolved alerts and persist the new state.
+pub async fn evaluate() -> Result<(), Error> {
+    tokio::task::spawn_blocking(evaluate_blocking).await?
+}
+
+fn evaluate_blocking() -> Result<(), Error> {
+    let now = proxmox_time::epoch_i64();
+    let (config, _) = pdm_config::alerts::config()?;
+
+    let mut rules = Vec::new();
+    let mut silences = Vec::new();
+    for (_, entry) in config.into_iter() {
+        match entry {
+            AlertConfigEntry::Rule(rule) if rule.enabled() => rules.push(rule),
+            AlertConfigEntry::Silence(silence) if silence.until > now => {
+                silences.push(Silence::new(silence)?)
+            }
+            _ => {}
+        }
+    }
+
+    let remotes = CachedRemoteResources::load()?;
+    let resources = remotes.by_global_id();
+    let cache = rrd_cache::get_cache();
+
+    let mut rule_samples = Vec::new();
+    for rule in &rules {
+        let view = match crate::views::get_optional_view(rule.view.as_deref()) {
+            Ok(view) => view,
+            Err(err) => {
+                log::warn!("skipping alert rule '{}' - {err}", rule.id);
+                continue;
+            }
+        };
+        let scope = match Scope::new(view, rule.include.clone(), rule.exclude.clone()) {
+            Ok(scope) => scope,
+            Err(err) => {
+                log::warn!("skipping alert rule '{}' - {err}", rule.id);
+                continue;
+            }
+        };
+
+        rule_samples.push((
+            rule,
+            collect_samples(&cache, rule.metric, &remotes, &scope, now),
+        ));
+    }
+
+    let is_silenced = |alert: &Alert| {
+        silences
+            .iter()
+            .any(|silence| silence.matches(alert, &resources))
+    };
+
+    let mut states = STATE.lock().unwrap();
+
+    let mut transitions = Vec::new();
+    for (rule, samples) in rule_samples {
+        transitions.extend(states.evaluate(rule, samples, now));
+    }
+
+    let active_rules: HashSet<&str> = rules.iter().map(|rule| rule.id.as_str()).collect();
+    states.cleanup(&active_rules, now);
+
+    for alert in states.alerts_mut() {
+        alert.silenced = is_silenced(alert);
+    }
+
+    let result = save_state(&states);
+    drop(states);
+
+    for transition in transitions {
+        let (Transition::Fired(alert) | Transition::Resolved(alert)) = transition;
+        if !is_silenced(&alert) {
+            crate::notifications::send_alert(&alert);
+        }
+    }
+
+    result
+}
+
+fn collect_samples(
+    cache: &RrdCache,
+    metric: AlertMetric,
+    remotes: &CachedRemoteResources,
+    scope: &Scope,
+    now: i64,
+) -> Vec<Sample> {
+    let mut samples = Vec::new();
+
+    for (remote, resources) in &remotes.remotes {
+        if metric == AlertMetric::RemoteResponseTime {
+            if scope.matches(remote, None) {
+                samples.push(Sample {
+                    resource: format!("remote/{remote}"),
+                    remote: remote.clone(),
+                    value: metric_value(cache, metric, &format!("remotes/{remote}"), now),
+                });
+            }
+            continue;
+        }
+
+        for resource in resources {
+            let Some(basedir) = rrd_basedir(metric, remote, resource) else {
+                continue;
+            };
+            if !scope.matches(remote, Some(resource)) {
+                continue;
+            }
+
+            samples.push(Sample {
+                resource: resource.global_id().to_string(),
+                remote: remote.clone(),
+                value: metric_value(cache, metric, &basedir, now),
+            });
+        }
+    }
+
+    samples
+}
+
+// The RRD base directory of a resource, if the metric applies to it.
+fn rrd_basedir(metric: AlertMetric, remote: &str, resource: &Resource) -> Option<String> {
+    match (metric, resource) {
+        (AlertMetric::NodeCpu | AlertMetric::NodeMemory, Resource::PveNode(node)) => {
+            Some(format!("pve/{remote}/node/{}", node.node))
+        }
+        // pbs node datapoints are always saved with 'host' instead of nodename
+        (AlertMetric::NodeCpu | AlertMetric::NodeMemory, Resource::PbsNode(_)) => {
+            Some(format!("pbs/{remote}/host"))
+        }
+        (AlertMetric::StorageUsage, Resource::PveStorage(storage)) => Some(format!(
+            "pve/{remote}/storage/{}/{}",
+            storage.node, storage.storage
+        )),
+        (
+            AlertMetric::DatastoreUsage | AlertMetric::DatastoreFullDays,
+            Resource::PbsDatastore(datastore),
+        ) => Some(format!("pbs/{remote}/datastore/{}", datastore.name)),
+        _ => None,
+    }
+}
+
+fn metric_value(cache: &RrdCache, metric: AlertMetric, basedir: &str, now: i64) -> Option<f64> {
+    match metric {
+        AlertMetric::NodeCpu => {
+            latest_value(cache, basedir, "cpu_current", now).map(|cpu| cpu * 100.0)
+        }
+        AlertMetric::NodeMemory => usage(cache, basedir, "mem_used", "mem_total", now),
+        AlertMetric::StorageUsage | AlertMetric::DatastoreUsage => {
+            usage(cache, basedir, "disk_used", "disk_total", now)
+        }
+        AlertMetric::DatastoreFullDays => {
+            // only estimate with recent data
+            latest_value(cache, basedir, "disk_used", now)?;
+            let full = forecast::cached_estimate_disk_full(cache, basedir, now)
+                .unwrap_or(now + forecast::MAX_FORECAST);
+            Some((full - now) as f64 / 86400.0)
+        }
+        AlertMetric::RemoteResponseTime => {
+            latest_value(cache, basedir, "metric-collection-response-time", now)
+        }
+    }
+}
+
+fn latest_value(cache: &RrdCache, basedir: &str, metric: &str, now: i64) -> Option<f64> {
+    forecast::rrd_points(cache, basedir, metric, RrdTimeframe::Hour)
+        .last()
+        .filter(|(time, _)| now - (*time as i64) <= MAX_VALUE_AGE)
+        .map(|(_, value)| *value)
+}
+
+fn usage(cache: &RrdCache, basedir: &str, used: &str, total: &str, now: i64) -> Option<f64> {
+    let used = latest_value(cache, basedir, used, now)?;
+    let total = latest_value(cache, basedir, total, now)?;
+
+    (total > 0.0).then(|| used * 100.0 / total)
+}
@@ -0,0 +1,288 @@
+//! The firing/resolved state machine of alerts.
+
+use std::collections::HashSet;
+
+use serde::{Deserialize, Serialize};
+
+use pdm_api_types::alerts::{Alert, AlertRule, AlertState};
+
+/// Resolved alerts are kept this long (in seconds), so they still show up in the alert list.
+const RESOLVED_RETENTION: i64 = 86400;
+
+/// A state change of an alert which should be notified about.
+#[derive(Debug, PartialEq)]
+pub enum Transition {
+    Fired(Alert),
+    Resolved(Alert),
+}
+
+/// The current value of a rule's metric for a resource.
+pub struct Sample {
+    /// The global ID of the resource.
+    pub resource: String,
+    pub remote: String,
+    /// The most recent value, `None` if there is no recent data.
+    pub value: Option<f64>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+#[serde(rename_all = "kebab-case")]
+/// All pending, firing and recently resolved alerts.
+pub struct AlertStates {
+    alerts: Vec<Alert>,
+}
+
+impl AlertStates {
+    /// All alerts.
+    pub fn alerts(&self) -> &[Alert] {
+        &self.alerts
+    }
+
+    /// Mutable access to all alerts.
+    pub fn alerts_mut(&mut self) -> impl Iterator<Item = &mut Alert> {
+        self.alerts.iter_mut()
+    }
+
+    /// Mutable access to the alert of a rule for a resource.
+    pub fn get_mut(&mut self, rule: &str, resource: &str) -> Option<&mut Alert> {
+        self.alerts
+            .iter_mut()
+            .find(|alert| alert.rule == rule && alert.resource == resource)
+    }
+
+    /// Update the alerts of a rule with the current samples.
+    ///
+    /// Firing alerts of resources without a sample are resolved, e.g. if the resource is gone or
+    /// not in the scope of the rule anymore. Alerts of resources without recent data are left
+    /// untouched.
+    pub fn evaluate(
+        &mut self,
+        rule: &AlertRule,
+        samples: impl IntoIterator<Item = Sample>,
+        now: i64,
+    ) -> Vec<Transition> {
+        let mut transitions = Vec::new();
+        let mut seen = HashSet::new();
+        let mut dropped = HashSet::new();
+
+        for sample in samples {
+            seen.insert(sample.resource.clone());
+
+            let Some(value) = sample.value else {
+                continue;
+            };
+
+            let index = self
+                .alerts
+                .iter()
+                .position(|alert| alert.rule == rule.id && alert.resource == sample.resource);
+
+            let alert = match index {
+                Some(index) => &mut self.alerts[index],
+                None if rule.condition_met(value) => {
+                    self.alerts.push(Alert {
+                        rule: rule.id.clone(),
+                        resource: sample.resource,
+                        remote: sample.remote,
+                        metric: rule.metric,
+                        severity: rule.severity.unwrap_or_default(),
+                        state: AlertState::Pending,
+                        value,
+                        threshold: rule.threshold,
+                        since: now,
+                        fired: None,
+                        resolved: None,
+                        acknowledged_by: None,
+                        acknowledged: None,
+                        silenced: false,
+                    });
+                    self.alerts.last_mut().unwrap()
+                }
+                None => continue,
+            };
+
+            alert.value = value;
+            alert.metric = rule.metric;
+            alert.severity = rule.severity.unwrap_or_default();
+            alert.threshold = rule.threshold;
+
+            match alert.state {
+                AlertState::Pending if !rule.condition_met(value) => {
+                    dropped.insert(alert.resource.clone());
+                    continue;
+                }
+                AlertState::Firing if rule.resolved_by(value) => {
+                    alert.state = AlertState::Resolved;
+                    alert.resolved = Some(now);
+                    transitions.push(Transition::Resolved(alert.clone()));
+                    continue;
+                }
+                AlertState::Resolved if rule.condition_met(value) => {
+                    alert.state = AlertState::Pending;
+                    alert.since = now;
+                    alert.fired = None;
+                    alert.resolved = None;
+                    alert.acknowledged_by = None;
+                    alert.acknowledged = None;
+                }
+                _ => {}
+            }
+
+            if alert.state == AlertState::Pending
+                && now - alert.since >= rule.duration.unwrap_or(0) as i64
+            {
+                alert.state = AlertState::Firing;
+                alert.fired = Some(now);
+                transitions.push(Transition::Fired(alert.clone()));
+            }
+        }
+
+        for alert in self.alerts.iter_mut() {
+            if alert.rule == rule.id
+                && alert.state == AlertState::Firing
+                && !seen.contains(&alert.resource)
+            {
+                alert.state = AlertState::Resolved;
+                alert.resolved = Some(now);
+                transitions.push(Transition::Resolved(alert.clone()));
+            }
+        }
+
+        // pending alerts never fired, so there is nothing to keep
+        self.alerts.retain(|alert| {
+            alert.rule != rule.id
+                || alert.state != AlertState::Pending
+                || seen.contains(&alert.resource) && !dropped.contains(&alert.resource)
+        });
+
+        transitions
+    }
+
+    /// Remove alerts of rules which do not exist or are disabled, and old resolved alerts.
+    pub fn cleanup(&mut self, active_rules: &HashSet<&str>, now: i64) {
+        self.alerts.retain(|alert| {
+            active_rules.contains(alert.rule.as_str())
+                && match alert.resolved {
+                    Some(resolved) => now - resolved < RESOLVED_RETENTION,
+                    None => true,
+                }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pdm_api_types::alerts::{AlertCondition, AlertMetric};
+
+    use super::*;
+
+    fn rule() -> AlertRule {
+        AlertRule {
+            id: "cpu".into(),
+            enable: None,
+            metric: AlertMetric::NodeCpu,
+            condition: Some(AlertCondition::Above),
+            threshold: 90.0,
+            duration: Some(900),
+            hysteresis: Some(5.0),
+            severity: None,
+            view: None,
+            include: Vec::new(),
+            exclude: Vec::new(),
+            comment: None,
+        }
+    }
+
+    fn sample(value: Option<f64>) -> Sample {
+        Sample {
+            resource: "remote/a/node/n1".into(),
+            remote: "a".into(),
+            value,
+        }
+    }
+
+    fn state(states: &AlertStates) -> Option<AlertState> {
+        states.alerts().first().map(|alert| alert.state)
+    }
+
+    #[test]
+    fn fire_and_resolve() {
+        let rule = rule();
+        let mut states = AlertStates::default();
+
+        assert!(states.evaluate(&rule, [sample(Some(50.0))], 0).is_empty());
+        assert_eq!(state(&states), None);
+
+        assert!(states.evaluate(&rule, [sample(Some(95.0))], 100).is_empty());
+        assert_eq!(state(&states), Some(AlertState::Pending));
+
+        // no recent data does not change anything
+        assert!(states.evaluate(&rule, [sample(None)], 500).is_empty());
+        assert_eq!(state(&states), Some(AlertState::Pending));
+
+        let transitions = states.evaluate(&rule, [sample(Some(92.0))], 1000);
+        assert!(matches!(transitions.as_slice(), [Transition::Fired(_)]));
+        assert_eq!(state(&states), Some(AlertState::Firing));
+
+        // within the hysteresis
+        assert!(
+            states
+                .evaluate(&rule, [sample(Some(88.0))], 1100)
+                .is_empty()
+        );
+        assert_eq!(state(&states), Some(AlertState::Firing));
+
+        let transitions = states.evaluate(&rule, [sample(Some(80.0))], 1200);
+        assert!(matches!(transitions.as_slice(), [Transition::Resolved(_)]));
+        assert_eq!(state(&states), Some(AlertState::Resolved));
+
+        // the resolved alert becomes pending again
+        assert!(
+            states
+                .evaluate(&rule, [sample(Some(95.0))], 1300)
+                .is_empty()
+        );
+        assert_eq!(state(&states), Some(AlertState::Pending));
+        assert_eq!(states.alerts()[0].since, 1300);
+        assert_eq!(states.alerts()[0].fired, None);
+
+        let mut cleanup_rules = HashSet::new();
+        states.cleanup(&cleanup_rules, 1400);
+        assert_eq!(state(&states), None);
+
+        cleanup_rules.insert("cpu");
+        states.evaluate(&rule, [sample(Some(95.0))], 2000);
+        states.evaluate(&rule, [sample(Some(95.0))], 3000);
+        states.evaluate(&rule, [sample(Some(50.0))], 3100);
+        states.cleanup(&cleanup_rules, 3100 + RESOLVED_RETENTION - 1);
+        assert_eq!(state(&states), Some(AlertState::Resolved));
+        states.cleanup(&cleanup_rules, 3100 + RESOLVED_RETENTION);
+        assert_eq!(state(&states), None);
+    }
+
+    #[test]
+    fn pending_alert_is_dropped() {
+        let rule = rule();
+        let mut states = AlertStates::default();
+
+        states.evaluate(&rule, [sample(Some(95.0))], 0);
+        assert_eq!(state(&states), Some(AlertState::Pending));
+
+        assert!(states.evaluate(&rule, [sample(Some(50.0))], 100).is_empty());
+        assert_eq!(state(&states), None);
+    }
+
+    #[test]
+    fn vanished_resource_resolves() {
+        let mut rule = rule();
+        rule.duration = None;
+        let mut states = AlertStates::default();
+
+        let transitions = states.evaluate(&rule, [sample(Some(95.0))], 0);
+        assert!(matches!(transitions.as_slice(), [Transition::Fired(_)]));
+
+        let transitions = states.evaluate(&rule, [], 100);
+        assert!(matches!(transitions.as_slice(), [Transition::Resolved(_)]));
+        assert_eq!(state(&states), Some(AlertState::Resolved));
+    }
+}
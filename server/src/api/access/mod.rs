@@ -4,7 +4,6 @@ use std::collections::{HashMap, HashSet};
 
 use anyhow::{Context, Error, bail};
 
-use proxmox_access_control::CachedUserInfo;
 use proxmox_access_control::acl::AclTreeNode;
 use proxmox_router::{Permission, RpcEnvironment};
 use proxmox_router::{Router, SubdirMap, list_subdirs_api_method};
@@ -13,6 +12,8 @@ use proxmox_sortable_macro::sortable;
 
 use pdm_api_types::{ACL_PATH_SCHEMA, Authid, PRIV_ACCESS_AUDIT, PRIVILEGES};
 
+use crate::acl::CachedUserInfo;
+
 mod domains;
 mod openid;
 mod tfa;
@@ -4,7 +4,6 @@ use hyper::Response;
 use hyper::http::request::Parts;
 use serde_json::{Value, json};
 
-use proxmox_access_control::CachedUserInfo;
 use proxmox_access_control::types::{EMAIL_SCHEMA, FIRST_NAME_SCHEMA, LAST_NAME_SCHEMA, User};
 use proxmox_auth_api::api::{ApiTicket, AuthContext, assemble_csrf_prevention_token};
 use proxmox_auth_api::ticket::Ticket;
@@ -23,6 +22,7 @@ use pdm_api_types::{
 };
 use pdm_buildcfg::PDM_RUN_DIR_M;
 
+use crate::acl::CachedUserInfo;
 use crate::auth;
 
 fn openid_authenticator(
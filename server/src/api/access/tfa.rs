@@ -2,7 +2,6 @@
 
 use anyhow::{Context, Error};
 
-use proxmox_access_control::CachedUserInfo;
 use proxmox_access_control::types::User;
 use proxmox_router::{Permission, Router, RpcEnvironment, http_bail, http_err};
 use proxmox_schema::api;
@@ -10,6 +9,7 @@ use proxmox_tfa::api::methods;
 
 use pdm_api_types::{Authid, PASSWORD_SCHEMA, PRIV_ACCESS_MODIFY, PRIV_SYS_AUDIT, Userid};
 
+use crate::acl::CachedUserInfo;
 use crate::auth::tfa::UserAccess;
 
 pub const ROUTER: Router = Router::new()
@@ -3,7 +3,6 @@
 use anyhow::{Context, Error, bail};
 use std::collections::HashMap;
 
-use proxmox_access_control::CachedUserInfo;
 use proxmox_access_control::types::{ApiToken, User, UserUpdater, UserWithTokens};
 use proxmox_router::{ApiMethod, Permission, Router, RpcEnvironment, SubdirMap};
 use proxmox_schema::api;
@@ -13,6 +12,8 @@ use pdm_api_types::{
     PRIV_SYS_AUDIT, Userid,
 };
 
+use crate::acl::CachedUserInfo;
+
 fn new_user_with_tokens(user: User) -> UserWithTokens {
     UserWithTokens {
         user,
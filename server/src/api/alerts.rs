@@ -0,0 +1,136 @@
+//! Active alerts of the threshold based alert rules.
+
+use anyhow::{Context, Error};
+
+use proxmox_router::{Permission, Router, RpcEnvironment, SubdirMap, http_bail};
+use proxmox_schema::api;
+use proxmox_sortable_macro::sortable;
+
+use pdm_api_types::alerts::{ALERT_CONFIG_ID_SCHEMA, Alert, AlertState};
+use pdm_api_types::{Authid, PRIV_RESOURCE_AUDIT, PRIV_RESOURCE_MODIFY, VIEW_ID_SCHEMA};
+
+use crate::acl::CachedUserInfo;
+use crate::alerts::{self, CachedRemoteResources};
+use crate::views;
+
+#[sortable]
+const SUBDIRS: SubdirMap = &sorted!([(
+    "acknowledge",
+    &Router::new().post(&API_METHOD_ACKNOWLEDGE_ALERT)
+),]);
+
+pub const ROUTER: Router = Router::new().get(&API_METHOD_LIST_ALERTS).subdirs(SUBDIRS);
+
+#[api(
+    input: {
+        properties: {
+            view: {
+                schema: VIEW_ID_SCHEMA,
+                optional: true,
+            },
+            "include-resolved": {
+                description: "Also return recently resolved alerts.",
+                type: bool,
+                optional: true,
+                default: false,
+            },
+        },
+    },
+    access: {
+        permission: &Permission::Anybody,
+        description: "The user needs `Resource.Audit` on `/view/{view}` if a view is given. \
+            Otherwise only alerts of remotes with `Resource.Audit` on `/resource/{remote}` are \
+            returned.",
+    },
+    returns: {
+        description: "List of alerts.",
+        type: Array,
+        items: { type: Alert },
+    },
+)]
+/// List pending and firing alerts.
+async fn list_alerts(
+    view: Option<String>,
+    include_resolved: bool,
+    rpcenv: &mut dyn RpcEnvironment,
+) -> Result<Vec<Alert>, Error> {
+    let user_info = CachedUserInfo::new()?;
+    let auth_id: Authid = rpcenv
+        .get_auth_id()
+        .context("no authid available")?
+        .parse()?;
+
+    if let Some(view) = &view {
+        user_info.check_privs(&auth_id, &["view", view], PRIV_RESOURCE_AUDIT, false)?;
+    } else if !user_info.any_privs_below(&auth_id, &["resource"], PRIV_RESOURCE_AUDIT)? {
+        http_bail!(FORBIDDEN, "user has no access to resources");
+    }
+
+    let mut list: Vec<Alert> = alerts::alerts()
+        .into_iter()
+        .filter(|alert| include_resolved || alert.state != AlertState::Resolved)
+        .collect();
+
+    match view {
+        Some(view) => {
+            tokio::task::spawn_blocking(move || -> Result<Vec<Alert>, Error> {
+                let view = views::get_view(&view)?;
+                let resources = CachedRemoteResources::load()?;
+                list.retain(|alert| resources.alert_in_view(&view, alert));
+                Ok(list)
+            })
+            .await?
+        }
+        None => {
+            list.retain(|alert| {
+                user_info.lookup_privs(&auth_id, &["resource", &alert.remote]) & PRIV_RESOURCE_AUDIT
+                    != 0
+            });
+            Ok(list)
+        }
+    }
+}
+
+#[api(
+    input: {
+        properties: {
+            rule: { schema: ALERT_CONFIG_ID_SCHEMA },
+            resource: {
+                description: "The global ID of the resource the alert is about.",
+                type: String,
+            },
+        },
+    },
+    access: {
+        permission: &Permission::Anybody,
+        description: "The user needs `Resource.Modify` on `/resource/{remote}` of the alert.",
+    },
+)]
+/// Acknowledge an alert.
+fn acknowledge_alert(
+    rule: String,
+    resource: String,
+    rpcenv: &mut dyn RpcEnvironment,
+) -> Result<(), Error> {
+    let auth_id: Authid = rpcenv
+        .get_auth_id()
+        .context("no authid available")?
+        .parse()?;
+
+    let Some(alert) = alerts::alerts()
+        .into_iter()
+        .find(|alert| alert.rule == rule && alert.resource == resource)
+    else {
+        http_bail!(NOT_FOUND, "no alert of rule '{rule}' for '{resource}'");
+    };
+
+    let user_info = CachedUserInfo::new()?;
+    user_info.check_privs(
+        &auth_id,
+        &["resource", &alert.remote],
+        PRIV_RESOURCE_MODIFY,
+        false,
+    )?;
+
+    alerts::acknowledge(&rule, &resource, &auth_id.to_string())
+}
@@ -0,0 +1,40 @@
+//! Configuration of alert rules and silences.
+
+use anyhow::Error;
+
+use proxmox_config_digest::ConfigDigest;
+use proxmox_router::{Router, SubdirMap, list_subdirs_api_method};
+use proxmox_section_config::typed::SectionConfigData;
+use proxmox_sortable_macro::sortable;
+
+use pdm_api_types::alerts::AlertConfigEntry;
+
+mod rules;
+mod silences;
+
+#[sortable]
+const SUBDIRS: SubdirMap = &sorted!([("rules", &rules::ROUTER), ("silences", &silences::ROUTER),]);
+
+pub const ROUTER: Router = Router::new()
+    .get(&list_subdirs_api_method!(SUBDIRS))
+    .subdirs(SUBDIRS);
+
+/// Lock and load the config, check the digest and save the config after `func` modified it.
+///
+/// Rules and silences share the same namespace for their IDs.
+fn modify_config<F>(digest: Option<ConfigDigest>, func: F) -> Result<(), Error>
+where
+    F: FnOnce(&mut SectionConfigData<AlertConfigEntry>) -> Result<(), Error>,
+{
+    let _lock = pdm_config::alerts::lock_config()?;
+
+    let (mut config, config_digest) = pdm_config::alerts::config()?;
+
+    config_digest.detect_modification(digest.as_ref())?;
+
+    func(&mut config)?;
+
+    pdm_config::alerts::save_config(&config)?;
+
+    Ok(())
+}
@@ -0,0 +1,246 @@
+use anyhow::Error;
+use serde::{Deserialize, Serialize};
+
+use proxmox_config_digest::ConfigDigest;
+use proxmox_router::{Permission, Router, RpcEnvironment, http_bail, http_err};
+use proxmox_schema::{api, param_bail};
+
+use pdm_api_types::alerts::{
+    ALERT_CONFIG_ID_SCHEMA, AlertConfigEntry, AlertRule, AlertRuleUpdater,
+};
+use pdm_api_types::{PRIV_SYS_AUDIT, PRIV_SYS_MODIFY};
+
+use super::modify_config;
+
+const ITEM_ROUTER: Router = Router::new()
+    .get(&API_METHOD_READ_RULE)
+    .put(&API_METHOD_UPDATE_RULE)
+    .delete(&API_METHOD_DELETE_RULE);
+
+pub const ROUTER: Router = Router::new()
+    .get(&API_METHOD_LIST_RULES)
+    .post(&API_METHOD_CREATE_RULE)
+    .match_all("id", &ITEM_ROUTER);
+
+#[api(
+    access: {
+        permission: &Permission::Privilege(&["system", "alerts"], PRIV_SYS_AUDIT, false),
+    },
+    returns: {
+        description: "List of alert rules.",
+        type: Array,
+        items: { type: AlertRule },
+    },
+)]
+/// List alert rules.
+pub fn list_rules(rpcenv: &mut dyn RpcEnvironment) -> Result<Vec<AlertRule>, Error> {
+    let (config, digest) = pdm_config::alerts::config()?;
+
+    rpcenv["digest"] = digest.to_hex().into();
+
+    Ok(config
+        .into_iter()
+        .filter_map(|(_, entry)| match entry {
+            AlertConfigEntry::Rule(rule) => Some(rule),
+            _ => None,
+        })
+        .collect())
+}
+
+fn check_view(view: Option<&str>) -> Result<(), Error> {
+    if let Err(err) = crate::views::get_optional_view(view) {
+        param_bail!("view", err);
+    }
+    Ok(())
+}
+
+#[api(
+    input: {
+        properties: {
+            rule: {
+                type: AlertRule,
+                flatten: true,
+            },
+        },
+    },
+    access: {
+        permission: &Permission::Privilege(&["system", "alerts"], PRIV_SYS_MODIFY, false),
+    },
+)]
+/// Add an alert rule.
+pub fn create_rule(rule: AlertRule) -> Result<(), Error> {
+    check_view(rule.view.as_deref())?;
+
+    modify_config(None, |config| {
+        let id = rule.id.clone();
+        if config.contains_key(&id) {
+            param_bail!("id", "alert rule or silence '{id}' already exists.");
+        }
+        config.insert(id, AlertConfigEntry::Rule(rule));
+        Ok(())
+    })
+}
+
+#[api(
+    input: {
+        properties: {
+            id: { schema: ALERT_CONFIG_ID_SCHEMA },
+        },
+    },
+    access: {
+        permission: &Permission::Privilege(&["system", "alerts"], PRIV_SYS_AUDIT, false),
+    },
+    returns: { type: AlertRule },
+)]
+/// Read an alert rule.
+pub fn read_rule(id: String, rpcenv: &mut dyn RpcEnvironment) -> Result<AlertRule, Error> {
+    let (config, digest) = pdm_config::alerts::config()?;
+
+    rpcenv["digest"] = digest.to_hex().into();
+
+    match config.get(&id) {
+        Some(AlertConfigEntry::Rule(rule)) => Ok(rule.clone()),
+        _ => http_bail!(NOT_FOUND, "no such alert rule '{id}'"),
+    }
+}
+
+#[api()]
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+/// Deletable property name
+pub enum DeletableProperty {
+    /// Delete the enable flag.
+    Enable,
+    /// Delete the condition.
+    Condition,
+    /// Delete the duration.
+    Duration,
+    /// Delete the hysteresis.
+    Hysteresis,
+    /// Delete the severity.
+    Severity,
+    /// Delete the view.
+    View,
+    /// Delete the include filters.
+    Include,
+    /// Delete the exclude filters.
+    Exclude,
+    /// Delete the comment.
+    Comment,
+}
+
+#[api(
+    input: {
+        properties: {
+            id: { schema: ALERT_CONFIG_ID_SCHEMA },
+            update: {
+                type: AlertRuleUpdater,
+                flatten: true,
+            },
+            delete: {
+                description: "List of properties to delete.",
+                type: Array,
+                optional: true,
+                items: { type: DeletableProperty },
+            },
+            digest: {
+                type: ConfigDigest,
+                optional: true,
+            },
+        },
+    },
+    access: {
+        permission: &Permission::Privilege(&["system", "alerts"], PRIV_SYS_MODIFY, false),
+    },
+)]
+/// Update an alert rule.
+pub fn update_rule(
+    id: String,
+    update: AlertRuleUpdater,
+    delete: Option<Vec<DeletableProperty>>,
+    digest: Option<ConfigDigest>,
+) -> Result<(), Error> {
+    check_view(update.view.as_deref())?;
+
+    modify_config(digest, |config| {
+        let rule = match config.get_mut(&id) {
+            Some(AlertConfigEntry::Rule(rule)) => rule,
+            _ => return Err(http_err!(NOT_FOUND, "no such alert rule '{id}'")),
+        };
+
+        for delete_prop in delete.unwrap_or_default() {
+            match delete_prop {
+                DeletableProperty::Enable => rule.enable = None,
+                DeletableProperty::Condition => rule.condition = None,
+                DeletableProperty::Duration => rule.duration = None,
+                DeletableProperty::Hysteresis => rule.hysteresis = None,
+                DeletableProperty::Severity => rule.severity = None,
+                DeletableProperty::View => rule.view = None,
+                DeletableProperty::Include => rule.include = Vec::new(),
+                DeletableProperty::Exclude => rule.exclude = Vec::new(),
+                DeletableProperty::Comment => rule.comment = None,
+            }
+        }
+
+        if update.enable.is_some() {
+            rule.enable = update.enable;
+        }
+        if let Some(metric) = update.metric {
+            rule.metric = metric;
+        }
+        if update.condition.is_some() {
+            rule.condition = update.condition;
+        }
+        if let Some(threshold) = update.threshold {
+            rule.threshold = threshold;
+        }
+        if update.duration.is_some() {
+            rule.duration = update.duration;
+        }
+        if update.hysteresis.is_some() {
+            rule.hysteresis = update.hysteresis;
+        }
+        if update.severity.is_some() {
+            rule.severity = update.severity;
+        }
+        if update.view.is_some() {
+            rule.view = update.view;
+        }
+        if let Some(include) = update.include {
+            rule.include = include;
+        }
+        if let Some(exclude) = update.exclude {
+            rule.exclude = exclude;
+        }
+        if update.comment.is_some() {
+            rule.comment = update.comment;
+        }
+
+        Ok(())
+    })
+}
+
+#[api(
+    input: {
+        properties: {
+            id: { schema: ALERT_CONFIG_ID_SCHEMA },
+            digest: {
+                type: ConfigDigest,
+                optional: true,
+            },
+        },
+    },
+    access: {
+        permission: &Permission::Privilege(&["system", "alerts"], PRIV_SYS_MODIFY, false),
+    },
+)]
+/// Delete an alert rule, its alerts are removed with the next evaluation.
+pub fn delete_rule(id: String, digest: Option<ConfigDigest>) -> Result<(), Error> {
+    modify_config(digest, |config| {
+        if !matches!(config.get(&id), Some(AlertConfigEntry::Rule(_))) {
+            http_bail!(NOT_FOUND, "no such alert rule '{id}'");
+        }
+        config.remove(&id);
+        Ok(())
+    })
+}
@@ -0,0 +1,196 @@
+use anyhow::Error;
+use serde::{Deserialize, Serialize};
+
+use proxmox_config_digest::ConfigDigest;
+use proxmox_router::{Permission, Router, RpcEnvironment, http_bail, http_err};
+use proxmox_schema::{api, param_bail};
+
+use pdm_api_types::alerts::{
+    ALERT_CONFIG_ID_SCHEMA, AlertConfigEntry, AlertSilence, AlertSilenceUpdater,
+};
+use pdm_api_types::{PRIV_SYS_AUDIT, PRIV_SYS_MODIFY};
+
+use super::modify_config;
+
+const ITEM_ROUTER: Router = Router::new()
+    .get(&API_METHOD_READ_SILENCE)
+    .put(&API_METHOD_UPDATE_SILENCE)
+    .delete(&API_METHOD_DELETE_SILENCE);
+
+pub const ROUTER: Router = Router::new()
+    .get(&API_METHOD_LIST_SILENCES)
+    .post(&API_METHOD_CREATE_SILENCE)
+    .match_all("id", &ITEM_ROUTER);
+
+#[api(
+    access: {
+        permission: &Permission::Privilege(&["system", "alerts"], PRIV_SYS_AUDIT, false),
+    },
+    returns: {
+        description: "List of alert silences.",
+        type: Array,
+        items: { type: AlertSilence },
+    },
+)]
+/// List alert silences, including expired ones.
+pub fn list_silences(rpcenv: &mut dyn RpcEnvironment) -> Result<Vec<AlertSilence>, Error> {
+    let (config, digest) = pdm_config::alerts::config()?;
+
+    rpcenv["digest"] = digest.to_hex().into();
+
+    Ok(config
+        .into_iter()
+        .filter_map(|(_, entry)| match entry {
+            AlertConfigEntry::Silence(silence) => Some(silence),
+            _ => None,
+        })
+        .collect())
+}
+
+#[api(
+    input: {
+        properties: {
+            silence: {
+                type: AlertSilence,
+                flatten: true,
+            },
+        },
+    },
+    access: {
+        permission: &Permission::Privilege(&["system", "alerts"], PRIV_SYS_MODIFY, false),
+    },
+)]
+/// Add an alert silence.
+pub fn create_silence(silence: AlertSilence) -> Result<(), Error> {
+    modify_config(None, |config| {
+        let id = silence.id.clone();
+        if config.contains_key(&id) {
+            param_bail!("id", "alert rule or silence '{id}' already exists.");
+        }
+        config.insert(id, AlertConfigEntry::Silence(silence));
+        Ok(())
+    })
+}
+
+#[api(
+    input: {
+        properties: {
+            id: { schema: ALERT_CONFIG_ID_SCHEMA },
+        },
+    },
+    access: {
+        permission: &Permission::Privilege(&["system", "alerts"], PRIV_SYS_AUDIT, false),
+    },
+    returns: { type: AlertSilence },
+)]
+/// Read an alert silence.
+pub fn read_silence(id: String, rpcenv: &mut dyn RpcEnvironment) -> Result<AlertSilence, Error> {
+    let (config, digest) = pdm_config::alerts::config()?;
+
+    rpcenv["digest"] = digest.to_hex().into();
+
+    match config.get(&id) {
+        Some(AlertConfigEntry::Silence(silence)) => Ok(silence.clone()),
+        _ => http_bail!(NOT_FOUND, "no such alert silence '{id}'"),
+    }
+}
+
+#[api()]
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+/// Deletable property name
+pub enum DeletableProperty {
+    /// Delete the rule.
+    Rule,
+    /// Delete the include filters.
+    Include,
+    /// Delete the comment.
+    Comment,
+}
+
+#[api(
+    input: {
+        properties: {
+            id: { schema: ALERT_CONFIG_ID_SCHEMA },
+            update: {
+                type: AlertSilenceUpdater,
+                flatten: true,
+            },
+            delete: {
+                description: "List of properties to delete.",
+                type: Array,
+                optional: true,
+                items: { type: DeletableProperty },
+            },
+            digest: {
+                type: ConfigDigest,
+                optional: true,
+            },
+        },
+    },
+    access: {
+        permission: &Permission::Privilege(&["system", "alerts"], PRIV_SYS_MODIFY, false),
+    },
+)]
+/// Update an alert silence.
+pub fn update_silence(
+    id: String,
+    update: AlertSilenceUpdater,
+    delete: Option<Vec<DeletableProperty>>,
+    digest: Option<ConfigDigest>,
+) -> Result<(), Error> {
+    modify_config(digest, |config| {
+        let silence = match config.get_mut(&id) {
+            Some(AlertConfigEntry::Silence(silence)) => silence,
+            _ => return Err(http_err!(NOT_FOUND, "no such alert silence '{id}'")),
+        };
+
+        for delete_prop in delete.unwrap_or_default() {
+            match delete_prop {
+                DeletableProperty::Rule => silence.rule = None,
+                DeletableProperty::Include => silence.include = Vec::new(),
+                DeletableProperty::Comment => silence.comment = None,
+            }
+        }
+
+        if update.rule.is_some() {
+            silence.rule = update.rule;
+        }
+        if let Some(include) = update.include {
+            silence.include = include;
+        }
+        if let Some(until) = update.until {
+            silence.until = until;
+        }
+        if update.comment.is_some() {
+            silence.comment = update.comment;
+        }
+
+        Ok(())
+    })
+}
+
+#[api(
+    input: {
+        properties: {
+            id: { schema: ALERT_CONFIG_ID_SCHEMA },
+            digest: {
+                type: ConfigDigest,
+                optional: true,
+            },
+        },
+    },
+    access: {
+        permission: &Permission::Privilege(&["system", "alerts"], PRIV_SYS_MODIFY, false),
+    },
+)]
+/// Delete an alert silence.
+pub fn delete_silence(id: String, digest: Option<ConfigDigest>) -> Result<(), Error> {
+    modify_config(digest, |config| {
+        if !matches!(config.get(&id), Some(AlertConfigEntry::Silence(_))) {
+            http_bail!(NOT_FOUND, "no such alert silence '{id}'");
+        }
+        config.remove(&id);
+        Ok(())
+    })
+}
@@ -0,0 +1,311 @@
+//! Configuration of scheduled guest power actions and snapshots.
+
+use anyhow::Error;
+use serde::{Deserialize, Serialize};
+
+use proxmox_config_digest::ConfigDigest;
+use proxmox_router::{Permission, Router, RpcEnvironment, http_bail, http_err};
+use proxmox_schema::{api, param_bail};
+use proxmox_section_config::typed::SectionConfigData;
+
+use pdm_api_types::guest_schedules::{
+    GUEST_SCHEDULE_ID_SCHEMA, GuestSchedule, GuestScheduleConfigEntry, GuestScheduleStatus,
+    GuestScheduleUpdater, ScheduledGuestAction,
+};
+use pdm_api_types::{PRIV_RESOURCE_MANAGE, PRIV_SYS_AUDIT, PRIV_SYS_MODIFY};
+use pdm_search::Search;
+
+use crate::guest_schedules::WORKER_TYPE;
+use crate::jobstate::{self, JobState};
+
+const ITEM_ROUTER: Router = Router::new()
+    .get(&API_METHOD_READ_SCHEDULE)
+    .put(&API_METHOD_UPDATE_SCHEDULE)
+    .delete(&API_METHOD_DELETE_SCHEDULE);
+
+pub const ROUTER: Router = Router::new()
+    .get(&API_METHOD_LIST_SCHEDULES)
+    .post(&API_METHOD_CREATE_SCHEDULE)
+    .match_all("id", &ITEM_ROUTER);
+
+/// Lock and load the config, check the digest and save the config after `func` modified it.
+fn modify_config<F>(digest: Option<ConfigDigest>, func: F) -> Result<(), Error>
+where
+    F: FnOnce(&mut SectionConfigData<GuestScheduleConfigEntry>) -> Result<(), Error>,
+{
+    let _lock = pdm_config::guest_schedules::lock_config()?;
+
+    let (mut config, config_digest) = pdm_config::guest_schedules::config()?;
+
+    config_digest.detect_modification(digest.as_ref())?;
+
+    func(&mut config)?;
+
+    pdm_config::guest_schedules::save_config(&config)?;
+
+    Ok(())
+}
+
+/// Check that the guests of a job are given by an existing view and/or a valid search.
+///
+/// Jobs without either would affect every guest, which is most likely not intended.
+fn check_targets(view: Option<&str>, search: Option<&str>) -> Result<(), Error> {
+    if view.is_none() && search.is_none() {
+        http_bail!(BAD_REQUEST, "either 'view' or 'search' has to be set");
+    }
+    if let Err(err) = crate::views::get_optional_view(view) {
+        param_bail!("view", err);
+    }
+    if let Some(Err(err)) = search.map(str::parse::<Search>) {
+        param_bail!("search", err);
+    }
+    Ok(())
+}
+
+/// Check that the snapshot retention is only set for snapshot jobs.
+fn check_keep_snapshots(job: &GuestSchedule) -> Result<(), Error> {
+    if job.keep_snapshots.is_some() && job.action != ScheduledGuestAction::Snapshot {
+        param_bail!(
+            "keep-snapshots",
+            "only supported for the '{}' action",
+            ScheduledGuestAction::Snapshot
+        );
+    }
+    Ok(())
+}
+
+#[api(
+    access: {
+        permission: &Permission::Privilege(&["system", "guest-schedules"], PRIV_SYS_AUDIT, false),
+    },
+    returns: {
+        description: "List of guest schedules.",
+        type: Array,
+        items: { type: GuestScheduleStatus },
+    },
+)]
+/// List guest schedules together with the state of their last and next run.
+pub fn list_schedules(rpcenv: &mut dyn RpcEnvironment) -> Result<Vec<GuestScheduleStatus>, Error> {
+    let (config, digest) = pdm_config::guest_schedules::config()?;
+
+    rpcenv["digest"] = digest.to_hex().into();
+
+    let mut list = Vec::new();
+    for (_, entry) in config {
+        let GuestScheduleConfigEntry::GuestSchedule(job) = entry;
+
+        let last_state = JobState::load(WORKER_TYPE, &job.id).map_err(|err| {
+            http_err!(
+                INTERNAL_SERVER_ERROR,
+                "could not open statefile for {}: {err}",
+                job.id
+            )
+        })?;
+        let mut status = jobstate::compute_schedule_status(&last_state, Some(&job.schedule))?;
+        if !job.enabled() {
+            status.next_run = None;
+        }
+
+        list.push(GuestScheduleStatus {
+            config: job,
+            status,
+        });
+    }
+
+    Ok(list)
+}
+
+#[api(
+    input: {
+        properties: {
+            schedule: {
+                type: GuestSchedule,
+                flatten: true,
+            },
+        },
+    },
+    access: {
+        permission: &Permission::And(&[
+            &Permission::Privilege(&["system", "guest-schedules"], PRIV_SYS_MODIFY, false),
+            &Permission::Privilege(&["resource"], PRIV_RESOURCE_MANAGE, false),
+        ]),
+    },
+)]
+/// Add a guest schedule.
+pub fn create_schedule(schedule: GuestSchedule) -> Result<(), Error> {
+    check_targets(schedule.view.as_deref(), schedule.search.as_deref())?;
+    check_keep_snapshots(&schedule)?;
+
+    modify_config(None, |config| {
+        let id = schedule.id.clone();
+        if config.contains_key(&id) {
+            param_bail!("id", "guest schedule '{id}' already exists.");
+        }
+        config.insert(
+            id.clone(),
+            GuestScheduleConfigEntry::GuestSchedule(schedule),
+        );
+
+        jobstate::create_state_file(WORKER_TYPE, &id)?;
+
+        Ok(())
+    })
+}
+
+#[api(
+    input: {
+        properties: {
+            id: { schema: GUEST_SCHEDULE_ID_SCHEMA },
+        },
+    },
+    access: {
+        permission: &Permission::Privilege(&["system", "guest-schedules"], PRIV_SYS_AUDIT, false),
+    },
+    returns: { type: GuestSchedule },
+)]
+/// Read a guest schedule.
+pub fn read_schedule(id: String, rpcenv: &mut dyn RpcEnvironment) -> Result<GuestSchedule, Error> {
+    let (config, digest) = pdm_config::guest_schedules::config()?;
+
+    rpcenv["digest"] = digest.to_hex().into();
+
+    match config.get(&id) {
+        Some(GuestScheduleConfigEntry::GuestSchedule(job)) => Ok(job.clone()),
+        None => http_bail!(NOT_FOUND, "no such guest schedule '{id}'"),
+    }
+}
+
+#[api()]
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+/// Deletable property name
+pub enum DeletableProperty {
+    /// Delete the enable flag.
+    Enable,
+    /// Delete the search.
+    Search,
+    /// Delete the view.
+    View,
+    /// Delete the number of snapshots to keep.
+    KeepSnapshots,
+    /// Delete the comment.
+    Comment,
+}
+
+#[api(
+    input: {
+        properties: {
+            id: { schema: GUEST_SCHEDULE_ID_SCHEMA },
+            update: {
+                type: GuestScheduleUpdater,
+                flatten: true,
+            },
+            delete: {
+                description: "List of properties to delete.",
+                type: Array,
+                optional: true,
+                items: { type: DeletableProperty },
+            },
+            digest: {
+                type: ConfigDigest,
+                optional: true,
+            },
+        },
+    },
+    access: {
+        permission: &Permission::And(&[
+            &Permission::Privilege(&["system", "guest-schedules"], PRIV_SYS_MODIFY, false),
+            &Permission::Privilege(&["resource"], PRIV_RESOURCE_MANAGE, false),
+        ]),
+    },
+)]
+/// Update a guest schedule.
+pub fn update_schedule(
+    id: String,
+    update: GuestScheduleUpdater,
+    delete: Option<Vec<DeletableProperty>>,
+    digest: Option<ConfigDigest>,
+) -> Result<(), Error> {
+    modify_config(digest, |config| {
+        let job = match config.get_mut(&id) {
+            Some(GuestScheduleConfigEntry::GuestSchedule(job)) => job,
+            None => return Err(http_err!(NOT_FOUND, "no such guest schedule '{id}'")),
+        };
+
+        for delete_prop in delete.unwrap_or_default() {
+            match delete_prop {
+                DeletableProperty::Enable => job.enable = None,
+                DeletableProperty::Search => job.search = None,
+                DeletableProperty::View => job.view = None,
+                DeletableProperty::KeepSnapshots => job.keep_snapshots = None,
+                DeletableProperty::Comment => job.comment = None,
+            }
+        }
+
+        let mut schedule_changed = false;
+
+        if update.enable.is_some() {
+            job.enable = update.enable;
+        }
+        if let Some(schedule) = update.schedule {
+            schedule_changed = job.schedule != schedule;
+            job.schedule = schedule;
+        }
+        if let Some(action) = update.action {
+            job.action = action;
+        }
+        if update.search.is_some() {
+            job.search = update.search;
+        }
+        if update.view.is_some() {
+            job.view = update.view;
+        }
+        if update.keep_snapshots.is_some() {
+            job.keep_snapshots = update.keep_snapshots;
+        }
+        if update.comment.is_some() {
+            job.comment = update.comment;
+        }
+
+        check_targets(job.view.as_deref(), job.search.as_deref())?;
+        check_keep_snapshots(job)?;
+
+        if schedule_changed {
+            jobstate::update_job_last_run_time(WORKER_TYPE, &id)?;
+        }
+
+        Ok(())
+    })
+}
+
+#[api(
+    input: {
+        properties: {
+            id: { schema: GUEST_SCHEDULE_ID_SCHEMA },
+            digest: {
+                type: ConfigDigest,
+                optional: true,
+            },
+        },
+    },
+    access: {
+        permission: &Permission::And(&[
+            &Permission::Privilege(&["system", "guest-schedules"], PRIV_SYS_MODIFY, false),
+            &Permission::Privilege(&["resource"], PRIV_RESOURCE_MANAGE, false),
+        ]),
+    },
+)]
+/// Delete a guest schedule.
+pub fn delete_schedule(id: String, digest: Option<ConfigDigest>) -> Result<(), Error> {
+    modify_config(digest, |config| {
+        if config.remove(&id).is_none() {
+            http_bail!(NOT_FOUND, "no such guest schedule '{id}'");
+        }
+
+        if let Err(err) = jobstate::remove_state_file(WORKER_TYPE, &id) {
+            log::warn!("could not remove job state of guest schedule '{id}': {err}");
+        }
+
+        Ok(())
+    })
+}
@@ -0,0 +1,219 @@
+use anyhow::Error;
+use serde::{Deserialize, Serialize};
+
+use proxmox_config_digest::ConfigDigest;
+use proxmox_router::{Permission, Router, RpcEnvironment, http_bail, http_err};
+use proxmox_schema::{api, param_bail};
+
+use pdm_api_types::metric_servers::{
+    Graphite, GraphiteUpdater, METRIC_SERVER_ID_SCHEMA, MetricServerEntry,
+};
+use pdm_api_types::{PRIV_SYS_AUDIT, PRIV_SYS_MODIFY};
+
+use super::modify_config;
+
+const ITEM_ROUTER: Router = Router::new()
+    .get(&API_METHOD_READ_GRAPHITE)
+    .put(&API_METHOD_UPDATE_GRAPHITE)
+    .delete(&API_METHOD_DELETE_GRAPHITE);
+
+pub const ROUTER: Router = Router::new()
+    .get(&API_METHOD_LIST_GRAPHITE)
+    .post(&API_METHOD_CREATE_GRAPHITE)
+    .match_all("name", &ITEM_ROUTER);
+
+#[api(
+    access: {
+        permission: &Permission::Privilege(&["system"], PRIV_SYS_AUDIT, false),
+    },
+    returns: {
+        description: "List of configured Graphite metric servers.",
+        type: Array,
+        items: { type: Graphite },
+    },
+)]
+/// List Graphite metric servers.
+pub fn list_graphite(rpcenv: &mut dyn RpcEnvironment) -> Result<Vec<Graphite>, Error> {
+    let (config, digest) = pdm_config::metric_servers::config()?;
+
+    rpcenv["digest"] = digest.to_hex().into();
+
+    Ok(config
+        .into_iter()
+        .filter_map(|(_, entry)| match entry {
+            MetricServerEntry::Graphite(entry) => Some(entry),
+            _ => None,
+        })
+        .collect())
+}
+
+#[api(
+    input: {
+        properties: {
+            config: {
+                type: Graphite,
+                flatten: true,
+            },
+        },
+    },
+    access: {
+        permission: &Permission::Privilege(&["system"], PRIV_SYS_MODIFY, false),
+    },
+)]
+/// Add a Graphite metric server.
+pub fn create_graphite(config: Graphite) -> Result<(), Error> {
+    modify_config(None, |servers| {
+        let name = config.name.clone();
+        if servers.contains_key(&name) {
+            param_bail!("name", "metric server '{name}' already exists.");
+        }
+        servers.insert(name, MetricServerEntry::Graphite(config));
+        Ok(())
+    })
+}
+
+#[api(
+    input: {
+        properties: {
+            name: { schema: METRIC_SERVER_ID_SCHEMA },
+        },
+    },
+    access: {
+        permission: &Permission::Privilege(&["system"], PRIV_SYS_AUDIT, false),
+    },
+    returns: { type: Graphite },
+)]
+/// Read a Graphite metric server configuration.
+pub fn read_graphite(name: String, rpcenv: &mut dyn RpcEnvironment) -> Result<Graphite, Error> {
+    let (config, digest) = pdm_config::metric_servers::config()?;
+
+    rpcenv["digest"] = digest.to_hex().into();
+
+    match config.get(&name) {
+        Some(MetricServerEntry::Graphite(entry)) => Ok(entry.clone()),
+        _ => http_bail!(NOT_FOUND, "no such Graphite metric server '{name}'"),
+    }
+}
+
+#[api()]
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+/// Deletable property name
+pub enum DeletableProperty {
+    /// Delete the enable flag.
+    Enable,
+    /// Delete the protocol.
+    Proto,
+    /// Delete the path.
+    Path,
+    /// Delete the MTU.
+    Mtu,
+    /// Delete the remote filter.
+    Remotes,
+    /// Delete the comment.
+    Comment,
+}
+
+#[api(
+    input: {
+        properties: {
+            name: { schema: METRIC_SERVER_ID_SCHEMA },
+            update: {
+                type: GraphiteUpdater,
+                flatten: true,
+            },
+            delete: {
+                description: "List of properties to delete.",
+                type: Array,
+                optional: true,
+                items: { type: DeletableProperty },
+            },
+            digest: {
+                type: ConfigDigest,
+                optional: true,
+            },
+        },
+    },
+    access: {
+        permission: &Permission::Privilege(&["system"], PRIV_SYS_MODIFY, false),
+    },
+)]
+/// Update a Graphite metric server.
+pub fn update_graphite(
+    name: String,
+    update: GraphiteUpdater,
+    delete: Option<Vec<DeletableProperty>>,
+    digest: Option<ConfigDigest>,
+) -> Result<(), Error> {
+    modify_config(digest, |servers| {
+        let entry = match servers.get_mut(&name) {
+            Some(MetricServerEntry::Graphite(entry)) => entry,
+            _ => {
+                return Err(http_err!(
+                    NOT_FOUND,
+                    "no such Graphite metric server '{name}'"
+                ));
+            }
+        };
+
+        for delete_prop in delete.unwrap_or_default() {
+            match delete_prop {
+                DeletableProperty::Enable => entry.enable = None,
+                DeletableProperty::Proto => entry.proto = None,
+                DeletableProperty::Path => entry.path = None,
+                DeletableProperty::Mtu => entry.mtu = None,
+                DeletableProperty::Remotes => entry.remotes = None,
+                DeletableProperty::Comment => entry.comment = None,
+            }
+        }
+
+        if let Some(host) = update.host {
+            entry.host = host;
+        }
+        if update.enable.is_some() {
+            entry.enable = update.enable;
+        }
+        if update.proto.is_some() {
+            entry.proto = update.proto;
+        }
+        if update.path.is_some() {
+            entry.path = update.path;
+        }
+        if update.mtu.is_some() {
+            entry.mtu = update.mtu;
+        }
+        if update.remotes.is_some() {
+            entry.remotes = update.remotes;
+        }
+        if update.comment.is_some() {
+            entry.comment = update.comment;
+        }
+
+        Ok(())
+    })
+}
+
+#[api(
+    input: {
+        properties: {
+            name: { schema: METRIC_SERVER_ID_SCHEMA },
+            digest: {
+                type: ConfigDigest,
+                optional: true,
+            },
+        },
+    },
+    access: {
+        permission: &Permission::Privilege(&["system"], PRIV_SYS_MODIFY, false),
+    },
+)]
+/// Delete a Graphite metric server.
+pub fn delete_graphite(name: String, digest: Option<ConfigDigest>) -> Result<(), Error> {
+    modify_config(digest, |servers| {
+        if !matches!(servers.get(&name), Some(MetricServerEntry::Graphite(_))) {
+            http_bail!(NOT_FOUND, "no such Graphite metric server '{name}'");
+        }
+        servers.remove(&name);
+        Ok(())
+    })
+}
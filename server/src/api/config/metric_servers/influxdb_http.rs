@@ -0,0 +1,240 @@
+use anyhow::Error;
+use serde::{Deserialize, Serialize};
+
+use proxmox_config_digest::ConfigDigest;
+use proxmox_router::{Permission, Router, RpcEnvironment, http_bail, http_err};
+use proxmox_schema::{api, param_bail};
+
+use pdm_api_types::metric_servers::{
+    InfluxDbHttp, InfluxDbHttpUpdater, METRIC_SERVER_ID_SCHEMA, MetricServerEntry,
+};
+use pdm_api_types::{PRIV_SYS_AUDIT, PRIV_SYS_MODIFY};
+
+use super::modify_config;
+
+const ITEM_ROUTER: Router = Router::new()
+    .get(&API_METHOD_READ_INFLUXDB_HTTP)
+    .put(&API_METHOD_UPDATE_INFLUXDB_HTTP)
+    .delete(&API_METHOD_DELETE_INFLUXDB_HTTP);
+
+pub const ROUTER: Router = Router::new()
+    .get(&API_METHOD_LIST_INFLUXDB_HTTP)
+    .post(&API_METHOD_CREATE_INFLUXDB_HTTP)
+    .match_all("name", &ITEM_ROUTER);
+
+// The token is only ever written, never returned.
+fn without_token(mut entry: InfluxDbHttp) -> InfluxDbHttp {
+    entry.token = None;
+    entry
+}
+
+#[api(
+    access: {
+        permission: &Permission::Privilege(&["system"], PRIV_SYS_AUDIT, false),
+    },
+    returns: {
+        description: "List of configured InfluxDB HTTP metric servers.",
+        type: Array,
+        items: { type: InfluxDbHttp },
+    },
+)]
+/// List InfluxDB HTTP metric servers.
+pub fn list_influxdb_http(rpcenv: &mut dyn RpcEnvironment) -> Result<Vec<InfluxDbHttp>, Error> {
+    let (config, digest) = pdm_config::metric_servers::config()?;
+
+    rpcenv["digest"] = digest.to_hex().into();
+
+    Ok(config
+        .into_iter()
+        .filter_map(|(_, entry)| match entry {
+            MetricServerEntry::InfluxDbHttp(entry) => Some(without_token(entry)),
+            _ => None,
+        })
+        .collect())
+}
+
+#[api(
+    input: {
+        properties: {
+            config: {
+                type: InfluxDbHttp,
+                flatten: true,
+            },
+        },
+    },
+    access: {
+        permission: &Permission::Privilege(&["system"], PRIV_SYS_MODIFY, false),
+    },
+)]
+/// Add an InfluxDB HTTP metric server.
+pub fn create_influxdb_http(config: InfluxDbHttp) -> Result<(), Error> {
+    modify_config(None, |servers| {
+        let name = config.name.clone();
+        if servers.contains_key(&name) {
+            param_bail!("name", "metric server '{name}' already exists.");
+        }
+        servers.insert(name, MetricServerEntry::InfluxDbHttp(config));
+        Ok(())
+    })
+}
+
+#[api(
+    input: {
+        properties: {
+            name: { schema: METRIC_SERVER_ID_SCHEMA },
+        },
+    },
+    access: {
+        permission: &Permission::Privilege(&["system"], PRIV_SYS_AUDIT, false),
+    },
+    returns: { type: InfluxDbHttp },
+)]
+/// Read an InfluxDB HTTP metric server configuration.
+pub fn read_influxdb_http(
+    name: String,
+    rpcenv: &mut dyn RpcEnvironment,
+) -> Result<InfluxDbHttp, Error> {
+    let (config, digest) = pdm_config::metric_servers::config()?;
+
+    rpcenv["digest"] = digest.to_hex().into();
+
+    match config.get(&name) {
+        Some(MetricServerEntry::InfluxDbHttp(entry)) => Ok(without_token(entry.clone())),
+        _ => http_bail!(NOT_FOUND, "no such InfluxDB HTTP metric server '{name}'"),
+    }
+}
+
+#[api()]
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+/// Deletable property name
+pub enum DeletableProperty {
+    /// Delete the enable flag.
+    Enable,
+    /// Delete the organization.
+    Organization,
+    /// Delete the bucket.
+    Bucket,
+    /// Delete the token.
+    Token,
+    /// Delete the maximum body size.
+    MaxBodySize,
+    /// Delete the verify-tls flag.
+    VerifyTls,
+    /// Delete the remote filter.
+    Remotes,
+    /// Delete the comment.
+    Comment,
+}
+
+#[api(
+    input: {
+        properties: {
+            name: { schema: METRIC_SERVER_ID_SCHEMA },
+            update: {
+                type: InfluxDbHttpUpdater,
+                flatten: true,
+            },
+            delete: {
+                description: "List of properties to delete.",
+                type: Array,
+                optional: true,
+                items: { type: DeletableProperty },
+            },
+            digest: {
+                type: ConfigDigest,
+                optional: true,
+            },
+        },
+    },
+    access: {
+        permission: &Permission::Privilege(&["system"], PRIV_SYS_MODIFY, false),
+    },
+)]
+/// Update an InfluxDB HTTP metric server.
+pub fn update_influxdb_http(
+    name: String,
+    update: InfluxDbHttpUpdater,
+    delete: Option<Vec<DeletableProperty>>,
+    digest: Option<ConfigDigest>,
+) -> Result<(), Error> {
+    modify_config(digest, |servers| {
+        let entry = match servers.get_mut(&name) {
+            Some(MetricServerEntry::InfluxDbHttp(entry)) => entry,
+            _ => {
+                return Err(http_err!(
+                    NOT_FOUND,
+                    "no such InfluxDB HTTP metric server '{name}'"
+                ));
+            }
+        };
+
+        for delete_prop in delete.unwrap_or_default() {
+            match delete_prop {
+                DeletableProperty::Enable => entry.enable = None,
+                DeletableProperty::Organization => entry.organization = None,
+                DeletableProperty::Bucket => entry.bucket = None,
+                DeletableProperty::Token => entry.token = None,
+                DeletableProperty::MaxBodySize => entry.max_body_size = None,
+                DeletableProperty::VerifyTls => entry.verify_tls = None,
+                DeletableProperty::Remotes => entry.remotes = None,
+                DeletableProperty::Comment => entry.comment = None,
+            }
+        }
+
+        if let Some(url) = update.url {
+            entry.url = url;
+        }
+        if update.enable.is_some() {
+            entry.enable = update.enable;
+        }
+        if update.organization.is_some() {
+            entry.organization = update.organization;
+        }
+        if update.bucket.is_some() {
+            entry.bucket = update.bucket;
+        }
+        if update.token.is_some() {
+            entry.token = update.token;
+        }
+        if update.max_body_size.is_some() {
+            entry.max_body_size = update.max_body_size;
+        }
+        if update.verify_tls.is_some() {
+            entry.verify_tls = update.verify_tls;
+        }
+        if update.remotes.is_some() {
+            entry.remotes = update.remotes;
+        }
+        if update.comment.is_some() {
+            entry.comment = update.comment;
+        }
+
+        Ok(())
+    })
+}
+
+#[api(
+    input: {
+        properties: {
+            name: { schema: METRIC_SERVER_ID_SCHEMA },
+            digest: {
+                type: ConfigDigest,
+                optional: true,
+            },
+        },
+    },
+    access: {
+        permission: &Permission::Privilege(&["system"], PRIV_SYS_MODIFY, false),
+    },
+)]
+/// Delete an InfluxDB HTTP metric server.
+pub fn delete_influxdb_http(name: String, digest: Option<ConfigDigest>) -> Result<(), Error> {
+    modify_config(digest, |servers| {
+        if !matches!(servers.get(&name), Some(MetricServerEntry::InfluxDbHttp(_))) {
+            http_bail!(NOT_FOUND, "no such InfluxDB HTTP metric server '{name}'");
+        }
+        servers.remove(&name);
+        Ok(())
+    })
+}
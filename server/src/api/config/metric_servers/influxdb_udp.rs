@@ -0,0 +1,210 @@
+use anyhow::Error;
+use serde::{Deserialize, Serialize};
+
+use proxmox_config_digest::ConfigDigest;
+use proxmox_router::{Permission, Router, RpcEnvironment, http_bail, http_err};
+use proxmox_schema::{api, param_bail};
+
+use pdm_api_types::metric_servers::{
+    InfluxDbUdp, InfluxDbUdpUpdater, METRIC_SERVER_ID_SCHEMA, MetricServerEntry,
+};
+use pdm_api_types::{PRIV_SYS_AUDIT, PRIV_SYS_MODIFY};
+
+use super::modify_config;
+
+const ITEM_ROUTER: Router = Router::new()
+    .get(&API_METHOD_READ_INFLUXDB_UDP)
+    .put(&API_METHOD_UPDATE_INFLUXDB_UDP)
+    .delete(&API_METHOD_DELETE_INFLUXDB_UDP);
+
+pub const ROUTER: Router = Router::new()
+    .get(&API_METHOD_LIST_INFLUXDB_UDP)
+    .post(&API_METHOD_CREATE_INFLUXDB_UDP)
+    .match_all("name", &ITEM_ROUTER);
+
+#[api(
+    access: {
+        permission: &Permission::Privilege(&["system"], PRIV_SYS_AUDIT, false),
+    },
+    returns: {
+        description: "List of configured InfluxDB UDP metric servers.",
+        type: Array,
+        items: { type: InfluxDbUdp },
+    },
+)]
+/// List InfluxDB UDP metric servers.
+pub fn list_influxdb_udp(rpcenv: &mut dyn RpcEnvironment) -> Result<Vec<InfluxDbUdp>, Error> {
+    let (config, digest) = pdm_config::metric_servers::config()?;
+
+    rpcenv["digest"] = digest.to_hex().into();
+
+    Ok(config
+        .into_iter()
+        .filter_map(|(_, entry)| match entry {
+            MetricServerEntry::InfluxDbUdp(entry) => Some(entry),
+            _ => None,
+        })
+        .collect())
+}
+
+#[api(
+    input: {
+        properties: {
+            config: {
+                type: InfluxDbUdp,
+                flatten: true,
+            },
+        },
+    },
+    access: {
+        permission: &Permission::Privilege(&["system"], PRIV_SYS_MODIFY, false),
+    },
+)]
+/// Add an InfluxDB UDP metric server.
+pub fn create_influxdb_udp(config: InfluxDbUdp) -> Result<(), Error> {
+    modify_config(None, |servers| {
+        let name = config.name.clone();
+        if servers.contains_key(&name) {
+            param_bail!("name", "metric server '{name}' already exists.");
+        }
+        servers.insert(name, MetricServerEntry::InfluxDbUdp(config));
+        Ok(())
+    })
+}
+
+#[api(
+    input: {
+        properties: {
+            name: { schema: METRIC_SERVER_ID_SCHEMA },
+        },
+    },
+    access: {
+        permission: &Permission::Privilege(&["system"], PRIV_SYS_AUDIT, false),
+    },
+    returns: { type: InfluxDbUdp },
+)]
+/// Read an InfluxDB UDP metric server configuration.
+pub fn read_influxdb_udp(
+    name: String,
+    rpcenv: &mut dyn RpcEnvironment,
+) -> Result<InfluxDbUdp, Error> {
+    let (config, digest) = pdm_config::metric_servers::config()?;
+
+    rpcenv["digest"] = digest.to_hex().into();
+
+    match config.get(&name) {
+        Some(MetricServerEntry::InfluxDbUdp(entry)) => Ok(entry.clone()),
+        _ => http_bail!(NOT_FOUND, "no such InfluxDB UDP metric server '{name}'"),
+    }
+}
+
+#[api()]
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+/// Deletable property name
+pub enum DeletableProperty {
+    /// Delete the enable flag.
+    Enable,
+    /// Delete the MTU.
+    Mtu,
+    /// Delete the remote filter.
+    Remotes,
+    /// Delete the comment.
+    Comment,
+}
+
+#[api(
+    input: {
+        properties: {
+            name: { schema: METRIC_SERVER_ID_SCHEMA },
+            update: {
+                type: InfluxDbUdpUpdater,
+                flatten: true,
+            },
+            delete: {
+                description: "List of properties to delete.",
+                type: Array,
+                optional: true,
+                items: { type: DeletableProperty },
+            },
+            digest: {
+                type: ConfigDigest,
+                optional: true,
+            },
+        },
+    },
+    access: {
+        permission: &Permission::Privilege(&["system"], PRIV_SYS_MODIFY, false),
+    },
+)]
+/// Update an InfluxDB UDP metric server.
+pub fn update_influxdb_udp(
+    name: String,
+    update: InfluxDbUdpUpdater,
+    delete: Option<Vec<DeletableProperty>>,
+    digest: Option<ConfigDigest>,
+) -> Result<(), Error> {
+    modify_config(digest, |servers| {
+        let entry = match servers.get_mut(&name) {
+            Some(MetricServerEntry::InfluxDbUdp(entry)) => entry,
+            _ => {
+                return Err(http_err!(
+                    NOT_FOUND,
+                    "no such InfluxDB UDP metric server '{name}'"
+                ));
+            }
+        };
+
+        for delete_prop in delete.unwrap_or_default() {
+            match delete_prop {
+                DeletableProperty::Enable => entry.enable = None,
+                DeletableProperty::Mtu => entry.mtu = None,
+                DeletableProperty::Remotes => entry.remotes = None,
+                DeletableProperty::Comment => entry.comment = None,
+            }
+        }
+
+        if let Some(host) = update.host {
+            entry.host = host;
+        }
+        if update.enable.is_some() {
+            entry.enable = update.enable;
+        }
+        if update.mtu.is_some() {
+            entry.mtu = update.mtu;
+        }
+        if update.remotes.is_some() {
+            entry.remotes = update.remotes;
+        }
+        if update.comment.is_some() {
+            entry.comment = update.comment;
+        }
+
+        Ok(())
+    })
+}
+
+#[api(
+    input: {
+        properties: {
+            name: { schema: METRIC_SERVER_ID_SCHEMA },
+            digest: {
+                type: ConfigDigest,
+                optional: true,
+            },
+        },
+    },
+    access: {
+        permission: &Permission::Privilege(&["system"], PRIV_SYS_MODIFY, false),
+    },
+)]
+/// Delete an InfluxDB UDP metric server.
+pub fn delete_influxdb_udp(name: String, digest: Option<ConfigDigest>) -> Result<(), Error> {
+    modify_config(digest, |servers| {
+        if !matches!(servers.get(&name), Some(MetricServerEntry::InfluxDbUdp(_))) {
+            http_bail!(NOT_FOUND, "no such InfluxDB UDP metric server '{name}'");
+        }
+        servers.remove(&name);
+        Ok(())
+    })
+}
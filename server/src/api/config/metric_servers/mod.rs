@@ -0,0 +1,71 @@
+//! Configuration of external metric servers.
+
+use anyhow::Error;
+
+use proxmox_config_digest::ConfigDigest;
+use proxmox_router::{Permission, Router, RpcEnvironment, SubdirMap};
+use proxmox_schema::api;
+use proxmox_section_config::typed::SectionConfigData;
+use proxmox_sortable_macro::sortable;
+
+use pdm_api_types::PRIV_SYS_AUDIT;
+use pdm_api_types::metric_servers::{MetricServerEntry, MetricServerInfo};
+
+mod graphite;
+mod influxdb_http;
+mod influxdb_udp;
+
+#[sortable]
+const SUBDIRS: SubdirMap = &sorted!([
+    ("graphite", &graphite::ROUTER),
+    ("influxdb-http", &influxdb_http::ROUTER),
+    ("influxdb-udp", &influxdb_udp::ROUTER),
+]);
+
+pub const ROUTER: Router = Router::new()
+    .get(&API_METHOD_LIST_METRIC_SERVERS)
+    .subdirs(SUBDIRS);
+
+#[api(
+    access: {
+        permission: &Permission::Privilege(&["system"], PRIV_SYS_AUDIT, false),
+    },
+    returns: {
+        description: "List of configured metric servers.",
+        type: Array,
+        items: { type: MetricServerInfo },
+    },
+)]
+/// List all configured metric servers.
+pub fn list_metric_servers(
+    rpcenv: &mut dyn RpcEnvironment,
+) -> Result<Vec<MetricServerInfo>, Error> {
+    let (config, digest) = pdm_config::metric_servers::config()?;
+
+    rpcenv["digest"] = digest.to_hex().into();
+
+    Ok(config
+        .iter()
+        .map(|(_, entry)| MetricServerInfo::from(entry))
+        .collect())
+}
+
+/// Lock and load the config, check the digest and save the config after `func` modified it.
+///
+/// Metric server names are unique across all types.
+fn modify_config<F>(digest: Option<ConfigDigest>, func: F) -> Result<(), Error>
+where
+    F: FnOnce(&mut SectionConfigData<MetricServerEntry>) -> Result<(), Error>,
+{
+    let _lock = pdm_config::metric_servers::lock_config()?;
+
+    let (mut config, config_digest) = pdm_config::metric_servers::config()?;
+
+    config_digest.detect_modification(digest.as_ref())?;
+
+    func(&mut config)?;
+
+    pdm_config::metric_servers::save_config(&config)?;
+
+    Ok(())
+}
@@ -4,16 +4,24 @@ use proxmox_sortable_macro::sortable;
 
 pub mod access;
 pub mod acme;
+pub mod alerts;
 pub mod certificate;
+pub mod guest_schedules;
+pub mod metric_servers;
 pub mod notes;
+pub mod notifications;
 pub mod views;
 
 #[sortable]
 const SUBDIRS: SubdirMap = &sorted!([
     ("access", &access::ROUTER),
     ("acme", &acme::ROUTER),
+    ("alerts", &alerts::ROUTER),
     ("certificate", &certificate::ROUTER),
+    ("guest-schedules", &guest_schedules::ROUTER),
+    ("metric-servers", &metric_servers::ROUTER),
     ("notes", &notes::ROUTER),
+    ("notifications", &notifications::ROUTER),
     ("views", &views::ROUTER)
 ]);
 
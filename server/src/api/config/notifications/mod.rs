@@ -0,0 +1,217 @@
+//! Notification targets, matchers and related configuration.
+
+use anyhow::Error;
+use serde::Serialize;
+use serde_json::Value;
+
+use proxmox_notify::api::Target;
+use proxmox_notify::schema::ENTITY_NAME_SCHEMA;
+use proxmox_router::{
+    ApiMethod, Permission, Router, RpcEnvironment, SubdirMap, http_err, list_subdirs_api_method,
+};
+use proxmox_schema::api;
+use proxmox_sortable_macro::sortable;
+
+use pdm_api_types::alerts::AlertConfigEntry;
+use pdm_api_types::{PRIV_SYS_AUDIT, PRIV_SYS_MODIFY};
+
+use crate::notifications::NOTIFICATION_TYPES;
+
+mod gotify;
+mod matchers;
+mod sendmail;
+mod smtp;
+mod webhook;
+
+#[sortable]
+const SUBDIRS: SubdirMap = &sorted!([
+    ("endpoints", &ENDPOINT_ROUTER),
+    ("matcher-fields", &FIELD_ROUTER),
+    ("matcher-field-values", &VALUE_ROUTER),
+    ("matchers", &matchers::ROUTER),
+    ("targets", &TARGET_ROUTER),
+]);
+
+pub const ROUTER: Router = Router::new()
+    .get(&list_subdirs_api_method!(SUBDIRS))
+    .subdirs(SUBDIRS);
+
+#[sortable]
+const ENDPOINT_SUBDIRS: SubdirMap = &sorted!([
+    ("gotify", &gotify::ROUTER),
+    ("sendmail", &sendmail::ROUTER),
+    ("smtp", &smtp::ROUTER),
+    ("webhook", &webhook::ROUTER),
+]);
+
+const ENDPOINT_ROUTER: Router = Router::new()
+    .get(&list_subdirs_api_method!(ENDPOINT_SUBDIRS))
+    .subdirs(ENDPOINT_SUBDIRS);
+
+const FIELD_ROUTER: Router = Router::new().get(&API_METHOD_GET_FIELDS);
+const VALUE_ROUTER: Router = Router::new().get(&API_METHOD_GET_VALUES);
+
+const TARGET_ROUTER: Router = Router::new()
+    .get(&API_METHOD_LIST_TARGETS)
+    .match_all("name", &SINGLE_TARGET_ROUTER);
+
+#[sortable]
+const SINGLE_TARGET_SUBDIRS: SubdirMap = &sorted!([("test", &TEST_TARGET_ROUTER)]);
+
+const SINGLE_TARGET_ROUTER: Router = Router::new()
+    .get(&list_subdirs_api_method!(SINGLE_TARGET_SUBDIRS))
+    .subdirs(SINGLE_TARGET_SUBDIRS);
+
+const TEST_TARGET_ROUTER: Router = Router::new().post(&API_METHOD_TEST_TARGET);
+
+#[api]
+#[derive(Serialize)]
+/// A matchable field.
+pub struct MatchableField {
+    /// Name of the field
+    name: String,
+}
+
+#[api]
+#[derive(Serialize)]
+/// A matchable metadata field value.
+pub struct MatchableValue {
+    /// Field this value belongs to.
+    field: String,
+    /// Notification metadata value known by the system.
+    value: String,
+    /// Additional comment for this value.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    comment: Option<String>,
+}
+
+#[api(
+    protected: false,
+    input: {
+        properties: {},
+    },
+    returns: {
+        description: "List of known metadata fields.",
+        type: Array,
+        items: { type: MatchableField },
+    },
+    access: {
+        permission: &Permission::Privilege(&["system", "notifications"], PRIV_SYS_AUDIT, false),
+    },
+)]
+/// Get all known metadata fields.
+pub fn get_fields() -> Result<Vec<MatchableField>, Error> {
+    let fields = ["hostname", "remote", "rule", "task-type", "type"]
+        .into_iter()
+        .map(|name| MatchableField {
+            name: name.to_string(),
+        })
+        .collect();
+
+    Ok(fields)
+}
+
+#[api(
+    protected: false,
+    input: {
+        properties: {},
+    },
+    returns: {
+        description: "List of known metadata field values.",
+        type: Array,
+        items: { type: MatchableValue },
+    },
+    access: {
+        permission: &Permission::Privilege(&["system", "notifications"], PRIV_SYS_AUDIT, false),
+    },
+)]
+/// List all known, matchable metadata field values.
+pub fn get_values() -> Result<Vec<MatchableValue>, Error> {
+    let mut values = vec![MatchableValue {
+        field: "hostname".into(),
+        value: proxmox_sys::nodename().into(),
+        comment: None,
+    }];
+
+    for ty in NOTIFICATION_TYPES {
+        values.push(MatchableValue {
+            field: "type".into(),
+            value: ty.to_string(),
+            comment: None,
+        });
+    }
+
+    let (alerts, _digest) = pdm_config::alerts::config()?;
+    for (id, entry) in alerts.iter() {
+        if let AlertConfigEntry::Rule(rule) = entry {
+            values.push(MatchableValue {
+                field: "rule".into(),
+                value: id.to_string(),
+                comment: rule.comment.clone(),
+            });
+        }
+    }
+
+    let (remotes, _digest) = pdm_config::remotes::config()?;
+    for (name, remote) in remotes.iter() {
+        values.push(MatchableValue {
+            field: "remote".into(),
+            value: name.to_string(),
+            comment: Some(remote.ty.to_string()),
+        });
+    }
+
+    Ok(values)
+}
+
+#[api(
+    protected: true,
+    input: {
+        properties: {},
+    },
+    returns: {
+        description: "List of all notification targets.",
+        type: Array,
+        items: { type: Target },
+    },
+    access: {
+        permission: &Permission::Privilege(&["system", "notifications"], PRIV_SYS_AUDIT, false),
+    },
+)]
+/// List all notification targets.
+pub fn list_targets(_param: Value, _rpcenv: &mut dyn RpcEnvironment) -> Result<Vec<Target>, Error> {
+    let config = pdm_config::notifications::config()?;
+    let targets = proxmox_notify::api::get_targets(&config)?;
+
+    Ok(targets)
+}
+
+#[api(
+    protected: true,
+    input: {
+        properties: {
+            name: { schema: ENTITY_NAME_SCHEMA },
+        },
+    },
+    access: {
+        permission: &Permission::Privilege(&["system", "notifications"], PRIV_SYS_MODIFY, false),
+    },
+)]
+/// Send a test notification to a target.
+pub fn test_target(
+    name: String,
+    _info: &ApiMethod,
+    _rpcenv: &mut dyn RpcEnvironment,
+) -> Result<(), Error> {
+    let config = pdm_config::notifications::config()?;
+    proxmox_notify::api::common::test_target(&config, &name)?;
+    Ok(())
+}
+
+// The notification config has its own digest handling, passed as raw bytes.
+fn decode_digest(digest: Option<String>) -> Result<Option<Vec<u8>>, Error> {
+    digest
+        .map(hex::decode)
+        .transpose()
+        .map_err(|err| http_err!(BAD_REQUEST, "invalid digest - {err}"))
+}
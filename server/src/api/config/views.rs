@@ -1,7 +1,6 @@
 use anyhow::{Context, Error, format_err};
 use serde::{Deserialize, Serialize};
 
-use proxmox_access_control::CachedUserInfo;
 use proxmox_config_digest::ConfigDigest;
 use proxmox_router::{Permission, Router, RpcEnvironment, http_bail, http_err};
 use proxmox_schema::{api, param_bail};
@@ -11,6 +10,8 @@ use pdm_api_types::{
     views::{ViewConfig, ViewConfigEntry, ViewConfigUpdater, ViewTemplate},
 };
 
+use crate::acl::CachedUserInfo;
+
 const VIEW_ROUTER: Router = Router::new()
     .put(&API_METHOD_UPDATE_VIEW)
     .delete(&API_METHOD_REMOVE_VIEW)
@@ -0,0 +1,82 @@
+//! OpenMetrics export of the most recent collected metrics, e.g. for scraping by Prometheus.
+
+use anyhow::Context;
+use futures::FutureExt;
+use http::request::Parts;
+use http::{Response, StatusCode, header};
+use serde_json::Value;
+
+use proxmox_http::Body;
+use proxmox_router::{
+    ApiHandler, ApiMethod, ApiResponseFuture, Permission, Router, RpcEnvironment, http_bail,
+};
+use proxmox_schema::ObjectSchema;
+
+use pdm_api_types::{Authid, PRIV_RESOURCE_AUDIT, PRIV_SYS_AUDIT};
+
+use crate::acl::CachedUserInfo;
+use crate::metric_collection::openmetrics::{self, MetricSource};
+
+const OPENMETRICS_CONTENT_TYPE: &str = "application/openmetrics-text; version=1.0.0; charset=utf-8";
+
+pub const ROUTER: Router = Router::new().get(&API_METHOD_GET_METRICS);
+
+pub const API_METHOD_GET_METRICS: ApiMethod = ApiMethod::new(
+    &ApiHandler::AsyncHttp(&get_metrics),
+    &ObjectSchema::new(
+        "Return the latest collected metrics in the OpenMetrics text format.",
+        &[],
+    ),
+)
+.access(
+    Some(
+        "Metrics of a remote are included with Resource.Audit on /resource/{remote}, metrics \
+        of the PDM host with Sys.Audit on /system.",
+    ),
+    &Permission::Anybody,
+);
+
+fn get_metrics(
+    _parts: Parts,
+    _req_body: hyper::body::Incoming,
+    _param: Value,
+    _info: &ApiMethod,
+    rpcenv: Box<dyn RpcEnvironment>,
+) -> ApiResponseFuture {
+    async move {
+        let auth_id: Authid = rpcenv
+            .get_auth_id()
+            .context("no authid available")?
+            .parse()?;
+
+        let user_info = CachedUserInfo::new()?;
+
+        let host_allowed = user_info
+            .check_privs(&auth_id, &["system"], PRIV_SYS_AUDIT, false)
+            .is_ok();
+        if !host_allowed
+            && !user_info.any_privs_below(&auth_id, &["resource"], PRIV_RESOURCE_AUDIT)?
+        {
+            http_bail!(FORBIDDEN, "user has no access to metrics");
+        }
+
+        let (remotes, _) = pdm_config::remotes::config()?;
+
+        let output = openmetrics::render(|source| match *source {
+            MetricSource::Host => host_allowed,
+            MetricSource::Remote(remote) => {
+                remotes.contains_key(remote)
+                    && user_info
+                        .check_privs(&auth_id, &["resource", remote], PRIV_RESOURCE_AUDIT, false)
+                        .is_ok()
+            }
+        });
+
+        Ok(Response::builder()
+            .status(StatusCode::OK)
+            .header(header::CONTENT_TYPE, OPENMETRICS_CONTENT_TYPE)
+            .body(Body::from(output))
+            .unwrap())
+    }
+    .boxed()
+}
@@ -9,11 +9,14 @@ use proxmox_schema::api;
 use proxmox_sortable_macro::sortable;
 
 pub mod access;
+pub mod alerts;
 pub mod auto_installer;
 pub mod ceph;
 pub mod config;
+pub mod metrics;
 pub mod nodes;
 pub mod pbs;
+pub mod pmg;
 pub mod pve;
 pub mod remotes;
 pub mod resources;
@@ -24,12 +27,15 @@ pub mod subscriptions;
 #[sortable]
 const SUBDIRS: SubdirMap = &sorted!([
     ("access", &access::ROUTER),
+    ("alerts", &alerts::ROUTER),
     ("auto-install", &auto_installer::ROUTER),
     ("ceph", &ceph::ROUTER),
     ("config", &config::ROUTER),
+    ("metrics", &metrics::ROUTER),
     ("ping", &Router::new().get(&API_METHOD_PING)),
     ("pve", &pve::ROUTER),
     ("pbs", &pbs::ROUTER),
+    ("pmg", &pmg::ROUTER),
     ("remotes", &remotes::ROUTER),
     ("resources", &resources::ROUTER),
     ("nodes", &nodes::ROUTER),
@@ -105,9 +105,8 @@ pub fn apt_update_database(
         proxmox_apt::update_database(
             pdm_buildcfg::APT_PKG_STATE_FN,
             &options,
-            |_updates: &[&APTUpdateInfo]| {
-                // fixme: add update notification
-                // crate::server::send_updates_available(updates);
+            |updates: &[&APTUpdateInfo]| {
+                crate::notifications::send_updates_available(updates);
                 Ok(())
             },
         )?;
@@ -50,6 +50,8 @@ pub enum DeletableProperty {
     CiphersTls1_2,
     /// Delete the default-lang property.
     DefaultLang,
+    /// Delete the remote-token-max-age property.
+    RemoteTokenMaxAge,
 }
 
 #[api(
@@ -108,6 +110,9 @@ pub fn update_node_config(
                 DeletableProperty::DefaultLang => {
                     config.default_lang = None;
                 }
+                DeletableProperty::RemoteTokenMaxAge => {
+                    config.remote_token_max_age = None;
+                }
             }
         }
     }
@@ -127,6 +132,9 @@ pub fn update_node_config(
     if update.default_lang.is_some() {
         config.default_lang = update.default_lang;
     }
+    if update.remote_token_max_age.is_some() {
+        config.remote_token_max_age = update.remote_token_max_age;
+    }
 
     pdm_config::node::save_config(&config)?;
 
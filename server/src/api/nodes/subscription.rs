@@ -168,7 +168,10 @@ pub async fn check_subscription() -> Result<(), Error> {
 
     let mut found = false;
     'outer: for (remote_name, (remote_type, remote_info)) in infos.iter() {
-        if *remote_type != RemoteType::Pve && *remote_type != RemoteType::Pbs {
+        if !matches!(
+            remote_type,
+            RemoteType::Pve | RemoteType::Pbs | RemoteType::Pmg
+        ) {
             log::warn!("skipping unknown remote type {remote_type}");
             continue;
         }
@@ -3,7 +3,6 @@ use http::request::Parts;
 use http::{Response, StatusCode, header};
 use serde_json::{Value, json};
 
-use proxmox_access_control::CachedUserInfo;
 use proxmox_async::stream::AsyncReaderStream;
 use proxmox_http::Body;
 use proxmox_rest_server::{TaskState, upid_log_path, upid_read_status};
@@ -17,6 +16,8 @@ use pdm_api_types::{
     TaskStateType, Tokenname, UPID, UPID_SCHEMA, Userid,
 };
 
+use crate::acl::CachedUserInfo;
+
 pub const ROUTER: Router = Router::new()
     .get(&API_METHOD_LIST_TASKS)
     .match_all("upid", &UPID_API_ROUTER);
@@ -0,0 +1,414 @@
+//! Backup coverage of the PVE guests by the backups on the PBS remotes.
+
+use std::collections::{HashMap, HashSet};
+
+use anyhow::{Context, Error};
+use futures::future::join_all;
+
+use pbs_api_types::BackupType;
+use proxmox_router::{Permission, Router, RpcEnvironment};
+use proxmox_schema::api;
+
+use pdm_api_types::pbs::{BackupCoverage, GuestBackupCoverage};
+use pdm_api_types::remotes::{Remote, RemoteType};
+use pdm_api_types::resource::{FailedRemote, GuestType, RemoteResources, Resource};
+use pdm_api_types::{Authid, PRIV_RESOURCE_AUDIT, VIEW_ID_SCHEMA};
+
+use crate::acl::CachedUserInfo;
+use crate::api::resources::get_resources_impl;
+use crate::pbs_client::{self, DatstoreListNamespaces};
+
+pub const ROUTER: Router = Router::new().get(&API_METHOD_GET_BACKUP_COVERAGE);
+
+/// The most recent backup of a guest.
+#[derive(Clone, Debug, PartialEq)]
+struct LastBackup {
+    time: i64,
+    remote: String,
+    datastore: String,
+    namespace: Option<String>,
+}
+
+type LastBackups = HashMap<(GuestType, u32), LastBackup>;
+
+fn merge_last_backup(backups: &mut LastBackups, key: (GuestType, u32), backup: LastBackup) {
+    match backups.get(&key) {
+        Some(existing) if existing.time >= backup.time => {}
+        _ => {
+            backups.insert(key, backup);
+        }
+    }
+}
+
+/// Get the most recent backup of all guest backup groups in all namespaces of the datastores of
+/// a PBS remote.
+///
+/// PBS only lists finished snapshots as the last backup of a group, so failed or running backups
+/// are not considered.
+async fn fetch_last_backups(
+    remote: &Remote,
+    datastore_allowed: impl Fn(&str) -> bool,
+) -> Result<LastBackups, Error> {
+    let client = pbs_client::connect(remote)?;
+    let mut backups = LastBackups::new();
+
+    for datastore in client.list_datastores().await? {
+        if !datastore_allowed(&datastore.name) {
+            continue;
+        }
+
+        let namespaces = client
+            .list_datastore_namespaces(DatstoreListNamespaces {
+                datastore: datastore.name.clone(),
+                parent: None,
+                max_depth: None,
+            })
+            .await
+            .with_context(|| format!("failed to list namespaces of '{}'", datastore.name))?;
+
+        for namespace in namespaces {
+            let namespace = (!namespace.ns.is_root()).then(|| namespace.ns.to_string());
+            let groups = client
+                .list_groups(&datastore.name, namespace.as_deref())
+                .await
+                .with_context(|| format!("failed to list backup groups of '{}'", datastore.name))?;
+
+            for group in groups {
+                let guest_type = match group.backup.ty {
+                    BackupType::Vm => GuestType::Qemu,
+                    BackupType::Ct => GuestType::Lxc,
+                    BackupType::Host => continue,
+                };
+                let Ok(vmid) = group.backup.id.parse() else {
+                    continue;
+                };
+
+                merge_last_backup(
+                    &mut backups,
+                    (guest_type, vmid),
+                    LastBackup {
+                        time: group.last_backup,
+                        remote: remote.id.clone(),
+                        datastore: datastore.name.clone(),
+                        namespace: namespace.clone(),
+                    },
+                );
+            }
+        }
+    }
+
+    Ok(backups)
+}
+
+/// The type and VMID of a guest resource, templates are skipped.
+fn guest_key(resource: &Resource) -> Option<(GuestType, u32)> {
+    match resource {
+        Resource::PveQemu(qemu) if !qemu.template => Some((GuestType::Qemu, qemu.vmid)),
+        Resource::PveLxc(lxc) if !lxc.template => Some((GuestType::Lxc, lxc.vmid)),
+        _ => None,
+    }
+}
+
+/// Get the guests which exist with the same type and VMID on more than one PVE remote.
+fn ambiguous_guests<'a>(
+    remotes: impl IntoIterator<Item = &'a [Resource]>,
+) -> HashSet<(GuestType, u32)> {
+    let mut seen = HashSet::new();
+    let mut ambiguous = HashSet::new();
+
+    for resources in remotes {
+        let keys: HashSet<_> = resources.iter().filter_map(guest_key).collect();
+        for key in keys {
+            if !seen.insert(key) {
+                ambiguous.insert(key);
+            }
+        }
+    }
+
+    ambiguous
+}
+
+/// Look up the last backup of every guest and check whether it is newer than `since`.
+///
+/// Backup groups are matched by guest type and VMID only, so a backup cannot be attributed to a
+/// cluster. Guests in `ambiguous` are flagged, since their backup might belong to a guest with the
+/// same VMID on another remote.
+fn correlate(
+    remote: &str,
+    resources: Vec<Resource>,
+    backups: &LastBackups,
+    ambiguous: &HashSet<(GuestType, u32)>,
+    since: i64,
+) -> Vec<GuestBackupCoverage> {
+    resources
+        .into_iter()
+        .filter_map(|resource| {
+            let key = guest_key(&resource)?;
+            let (id, name, node) = match resource {
+                Resource::PveQemu(qemu) => (qemu.id, qemu.name, qemu.node),
+                Resource::PveLxc(lxc) => (lxc.id, lxc.name, lxc.node),
+                _ => return None,
+            };
+            let (guest_type, vmid) = key;
+
+            let backup = backups.get(&key);
+
+            Some(GuestBackupCoverage {
+                remote: remote.to_string(),
+                id,
+                vmid,
+                guest_type,
+                name,
+                node,
+                last_backup: backup.map(|backup| backup.time),
+                backup_remote: backup.map(|backup| backup.remote.clone()),
+                datastore: backup.map(|backup| backup.datastore.clone()),
+                namespace: backup.and_then(|backup| backup.namespace.clone()),
+                covered: backup.is_some_and(|backup| backup.time >= since),
+                ambiguous: backup.is_some() && ambiguous.contains(&key),
+            })
+        })
+        .collect()
+}
+
+#[api(
+    input: {
+        properties: {
+            hours: {
+                description: "Guests without a backup in this many hours are reported as \
+                    not covered.",
+                type: Integer,
+                minimum: 1,
+                maximum: 100 * 365 * 24,
+                default: 48,
+                optional: true,
+            },
+            "max-age": {
+                description: "Maximum age (in seconds) of cached remote resources.",
+                default: 30,
+                optional: true,
+            },
+            view: {
+                schema: VIEW_ID_SCHEMA,
+                optional: true,
+            },
+        },
+    },
+    access: {
+        permission: &Permission::Anybody,
+        description: "Guests are listed like for the resources API call. Only the datastores \
+            with `Resource.Audit` on `/resource/{remote}/datastore/{datastore}` are checked for \
+            backups.",
+    },
+    returns: { type: BackupCoverage },
+)]
+/// Report the most recent backup of every PVE guest found on any PBS remote.
+///
+/// Guests with the same type and VMID on multiple PVE remotes cannot be told apart by their
+/// backup group, so their coverage is flagged as ambiguous.
+async fn get_backup_coverage(
+    hours: u64,
+    max_age: u64,
+    view: Option<String>,
+    rpcenv: &mut dyn RpcEnvironment,
+) -> Result<BackupCoverage, Error> {
+    let user_info = CachedUserInfo::new()?;
+    let auth_id: Authid = rpcenv
+        .get_auth_id()
+        .context("no authid available")?
+        .parse()?;
+
+    let guests: Vec<RemoteResources> =
+        get_resources_impl(max_age, None, None, view.as_deref(), Some(rpcenv))
+            .await?
+            .into_iter()
+            .map(Into::into)
+            .collect();
+
+    let (remotes, _) = pdm_config::remotes::config()?;
+
+    // check against all PVE remotes, not only those visible to the user, since their backups end
+    // up in the same backup groups
+    let all_guests: Vec<RemoteResources> = get_resources_impl(max_age, None, None, None, None)
+        .await?
+        .into_iter()
+        .map(Into::into)
+        .collect();
+    let ambiguous = ambiguous_guests(
+        all_guests
+            .iter()
+            .filter(|remote_resources| {
+                remotes
+                    .get(&remote_resources.remote)
+                    .is_some_and(|remote| remote.ty == RemoteType::Pve)
+            })
+            .map(|remote_resources| remote_resources.resources.as_slice()),
+    );
+
+    let pbs_remotes = remotes
+        .iter()
+        .map(|(_, remote)| remote)
+        .filter(|remote| remote.ty == RemoteType::Pbs)
+        .filter(|remote| {
+            user_info
+                .any_privs_below(&auth_id, &["resource", &remote.id], PRIV_RESOURCE_AUDIT)
+                .unwrap_or(false)
+        });
+
+    let (user_info, auth_id) = (&user_info, &auth_id);
+    let results = join_all(pbs_remotes.map(|remote| async move {
+        let datastore_allowed = |datastore: &str| {
+            user_info.lookup_privs(auth_id, &["resource", &remote.id, "datastore", datastore])
+                & PRIV_RESOURCE_AUDIT
+                != 0
+        };
+        (remote, fetch_last_backups(remote, datastore_allowed).await)
+    }))
+    .await;
+
+    let mut coverage = BackupCoverage::default();
+    let mut backups = LastBackups::new();
+
+    for (remote, result) in results {
+        match result {
+            Ok(remote_backups) => {
+                for (key, backup) in remote_backups {
+                    merge_last_backup(&mut backups, key, backup);
+                }
+            }
+            Err(err) => coverage.failed_remotes.push(FailedRemote {
+                name: remote.id.clone(),
+                error: format!("{err:#}"),
+                remote_type: remote.ty,
+            }),
+        }
+    }
+
+    let since = proxmox_time::epoch_i64() - (hours * 3600) as i64;
+
+    for remote_resources in guests {
+        let Some(remote) = remotes.get(&remote_resources.remote) else {
+            continue;
+        };
+        if remote.ty != RemoteType::Pve {
+            continue;
+        }
+
+        if let Some(error) = remote_resources.error {
+            coverage.failed_remotes.push(FailedRemote {
+                name: remote_resources.remote,
+                error,
+                remote_type: remote.ty,
+            });
+            continue;
+        }
+
+        coverage.guests.extend(correlate(
+            &remote_resources.remote,
+            remote_resources.resources,
+            &backups,
+            &ambiguous,
+            since,
+        ));
+    }
+
+    Ok(coverage)
+}
+
+#[cfg(test)]
+mod tests {
+    use pdm_api_types::resource::PveQemuResource;
+
+    use super::*;
+
+    fn backup(time: i64, datastore: &str) -> LastBackup {
+        LastBackup {
+            time,
+            remote: "pbs".into(),
+            datastore: datastore.into(),
+            namespace: None,
+        }
+    }
+
+    fn qemu(vmid: u32, template: bool) -> Resource {
+        Resource::PveQemu(PveQemuResource {
+            cpu: 0.0,
+            maxcpu: 1.0,
+            disk: 0,
+            maxdisk: 0,
+            id: format!("remote/pve/guest/{vmid}"),
+            maxmem: 0,
+            mem: 0,
+            name: format!("vm-{vmid}"),
+            node: "node".into(),
+            pool: String::new(),
+            status: "running".into(),
+            tags: Vec::new(),
+            template,
+            uptime: 0,
+            vmid,
+        })
+    }
+
+    #[test]
+    fn newest_backup_wins() {
+        let mut backups = LastBackups::new();
+        merge_last_backup(&mut backups, (GuestType::Qemu, 100), backup(10, "a"));
+        merge_last_backup(&mut backups, (GuestType::Qemu, 100), backup(30, "b"));
+        merge_last_backup(&mut backups, (GuestType::Qemu, 100), backup(20, "c"));
+
+        assert_eq!(backups[&(GuestType::Qemu, 100)], backup(30, "b"));
+    }
+
+    #[test]
+    fn coverage() {
+        let mut backups = LastBackups::new();
+        backups.insert((GuestType::Qemu, 100), backup(1000, "a"));
+        backups.insert((GuestType::Qemu, 101), backup(10, "a"));
+        // a container backup does not cover a VM with the same VMID
+        backups.insert((GuestType::Lxc, 102), backup(1000, "a"));
+
+        let resources = vec![
+            qemu(100, false),
+            qemu(101, false),
+            qemu(102, false),
+            qemu(103, true),
+        ];
+
+        let coverage = correlate("pve", resources, &backups, &HashSet::new(), 500);
+        let coverage: Vec<_> = coverage
+            .iter()
+            .map(|guest| (guest.vmid, guest.last_backup, guest.covered))
+            .collect();
+
+        assert_eq!(
+            coverage,
+            [
+                (100, Some(1000), true),
+                (101, Some(10), false),
+                (102, None, false),
+            ]
+        );
+    }
+
+    #[test]
+    fn ambiguous_vmids() {
+        let first = [qemu(100, false), qemu(101, false), qemu(102, true)];
+        let second = [qemu(100, false), qemu(102, false)];
+
+        let ambiguous = ambiguous_guests([&first[..], &second[..]]);
+        assert_eq!(ambiguous, HashSet::from([(GuestType::Qemu, 100)]));
+
+        let mut backups = LastBackups::new();
+        backups.insert((GuestType::Qemu, 100), backup(1000, "a"));
+
+        let coverage = correlate("pve", first.to_vec(), &backups, &ambiguous, 500);
+        let coverage: Vec<_> = coverage
+            .iter()
+            .map(|guest| (guest.vmid, guest.ambiguous))
+            .collect();
+
+        // guests without any backup are not ambiguous
+        assert_eq!(coverage, [(100, true), (101, false)]);
+    }
+}
@@ -0,0 +1,301 @@
+//! Browse the content of the datastores of a PBS remote and download single files.
+
+use anyhow::{Error, format_err};
+use futures::FutureExt;
+use http::request::Parts;
+use http::{Response, StatusCode, header};
+use serde_json::Value;
+
+use pbs_api_types::{
+    BACKUP_ID_SCHEMA, BACKUP_NAMESPACE_SCHEMA, BACKUP_TIME_SCHEMA, BackupDir, BackupType,
+    DATASTORE_SCHEMA,
+};
+use proxmox_http::Body;
+use proxmox_router::{ApiHandler, ApiMethod, ApiResponseFuture, Permission, RpcEnvironment};
+use proxmox_schema::{ApiType, BooleanSchema, ObjectSchema, StringSchema, api};
+use proxmox_sortable_macro::sortable;
+
+use pdm_api_types::pbs::CatalogEntry;
+use pdm_api_types::remotes::REMOTE_ID_SCHEMA;
+use pdm_api_types::{PRIV_RESOURCE_AUDIT, PRIV_RESOURCE_MANAGE};
+
+use crate::pbs_client;
+
+const FILEPATH_SCHEMA: proxmox_schema::Schema =
+    StringSchema::new("Base64 encoded path of a catalog entry.").schema();
+
+#[api(
+    input: {
+        properties: {
+            remote: { schema: REMOTE_ID_SCHEMA },
+            datastore: { schema: DATASTORE_SCHEMA },
+            ns: {
+                schema: BACKUP_NAMESPACE_SCHEMA,
+                optional: true,
+            },
+        },
+    },
+    access: {
+        permission: &Permission::Privilege(&["resource", "{remote}", "datastore", "{datastore}"], PRIV_RESOURCE_AUDIT, false),
+    },
+    returns: {
+        type: Array,
+        description: "List of backup groups.",
+        items: { type: pbs_api_types::GroupListItem },
+    },
+)]
+/// List the backup groups of a PBS remote's datastore.
+pub async fn list_groups(
+    remote: String,
+    datastore: String,
+    ns: Option<String>,
+) -> Result<Vec<pbs_api_types::GroupListItem>, Error> {
+    Ok(pbs_client::connect_to_remote_by_id(&remote)?
+        .list_groups(&datastore, ns.as_deref())
+        .await?)
+}
+
+#[api(
+    input: {
+        properties: {
+            remote: { schema: REMOTE_ID_SCHEMA },
+            datastore: { schema: DATASTORE_SCHEMA },
+            ns: {
+                schema: BACKUP_NAMESPACE_SCHEMA,
+                optional: true,
+            },
+            "backup-dir": {
+                type: BackupDir,
+                flatten: true,
+            },
+        },
+    },
+    access: {
+        permission: &Permission::Privilege(&["resource", "{remote}", "datastore", "{datastore}"], PRIV_RESOURCE_AUDIT, false),
+    },
+    returns: {
+        type: Array,
+        description: "List of the files of the snapshot.",
+        items: { type: pbs_api_types::BackupContent },
+    },
+)]
+/// List the files of a snapshot.
+pub async fn list_snapshot_files(
+    remote: String,
+    datastore: String,
+    ns: Option<String>,
+    backup_dir: BackupDir,
+) -> Result<Vec<pbs_api_types::BackupContent>, Error> {
+    Ok(pbs_client::connect_to_remote_by_id(&remote)?
+        .list_snapshot_files(&datastore, ns.as_deref(), &backup_dir)
+        .await?)
+}
+
+#[api(
+    input: {
+        properties: {
+            remote: { schema: REMOTE_ID_SCHEMA },
+            datastore: { schema: DATASTORE_SCHEMA },
+            ns: {
+                schema: BACKUP_NAMESPACE_SCHEMA,
+                optional: true,
+            },
+            "backup-dir": {
+                type: BackupDir,
+                flatten: true,
+            },
+            filepath: {
+                schema: FILEPATH_SCHEMA,
+                optional: true,
+            },
+        },
+    },
+    access: {
+        permission: &Permission::Privilege(&["resource", "{remote}", "datastore", "{datastore}"], PRIV_RESOURCE_AUDIT, false),
+    },
+    returns: {
+        type: Array,
+        description: "The entries of the directory.",
+        items: { type: CatalogEntry },
+    },
+)]
+/// List a directory of the catalog of a snapshot.
+///
+/// Without a `filepath`, the archives of the snapshot are listed.
+pub async fn catalog(
+    remote: String,
+    datastore: String,
+    ns: Option<String>,
+    backup_dir: BackupDir,
+    filepath: Option<String>,
+) -> Result<Vec<CatalogEntry>, Error> {
+    let filepath = filepath.unwrap_or_else(|| "root".to_string());
+
+    Ok(pbs_client::connect_to_remote_by_id(&remote)?
+        .catalog(&datastore, ns.as_deref(), &backup_dir, &filepath)
+        .await?)
+}
+
+#[sortable]
+pub const API_METHOD_PXAR_FILE_DOWNLOAD: ApiMethod = ApiMethod::new(
+    &ApiHandler::AsyncHttp(&pxar_file_download),
+    &ObjectSchema::new(
+        "Download a single file, or a directory as zip archive, from a pxar archive of a snapshot.",
+        &sorted!([
+            ("backup-id", false, &BACKUP_ID_SCHEMA),
+            ("backup-time", false, &BACKUP_TIME_SCHEMA),
+            ("backup-type", false, &BackupType::API_SCHEMA),
+            ("datastore", false, &DATASTORE_SCHEMA),
+            ("filepath", false, &FILEPATH_SCHEMA),
+            ("ns", true, &BACKUP_NAMESPACE_SCHEMA),
+            ("remote", false, &REMOTE_ID_SCHEMA),
+            (
+                "tar",
+                true,
+                &BooleanSchema::new("Download directories as zstd compressed tar archive.")
+                    .default(false)
+                    .schema()
+            ),
+        ]),
+    ),
+)
+.access(
+    Some(
+        "The user needs Resource.Manage on /resource/{remote}/datastore/{datastore}, since the \
+        content of the backups is accessed.",
+    ),
+    &Permission::Privilege(
+        &["resource", "{remote}", "datastore", "{datastore}"],
+        PRIV_RESOURCE_MANAGE,
+        false,
+    ),
+);
+
+fn pxar_file_download(
+    _parts: Parts,
+    _req_body: hyper::body::Incoming,
+    param: Value,
+    _info: &ApiMethod,
+    _rpcenv: Box<dyn RpcEnvironment>,
+) -> ApiResponseFuture {
+    async move {
+        let remote = proxmox_schema::param::required_string_param(&param, "remote")?;
+        let datastore = proxmox_schema::param::required_string_param(&param, "datastore")?;
+        let filepath = proxmox_schema::param::required_string_param(&param, "filepath")?;
+        let ns = param["ns"].as_str();
+        let tar = param["tar"].as_bool().unwrap_or(false);
+        let backup_dir: BackupDir = serde_json::from_value(param.clone())?;
+
+        let response = pbs_client::connect_to_remote_by_id(remote)?
+            .pxar_file_download(datastore, ns, &backup_dir, filepath, tar)
+            .await?;
+
+        let content_type = response
+            .content_type
+            .unwrap_or_else(|| "application/octet-stream".to_string());
+        let disposition = content_disposition(&download_filename(filepath, &content_type)?);
+
+        Ok(Response::builder()
+            .status(StatusCode::OK)
+            .header(header::CONTENT_TYPE, content_type)
+            .header(header::CONTENT_DISPOSITION, disposition)
+            .body(response.body.unwrap_or_else(Body::empty))
+            .unwrap())
+    }
+    .boxed()
+}
+
+/// Name of a downloaded file, the last component of the decoded `filepath`.
+///
+/// Directories are sent as zip or zstd compressed tar archive, which is reflected in the extension.
+fn download_filename(filepath: &str, content_type: &str) -> Result<String, Error> {
+    let filepath = proxmox_base64::decode(filepath)
+        .map_err(|err| format_err!("invalid filepath, not base64 encoded - {err}"))?;
+    let filepath = String::from_utf8_lossy(&filepath);
+
+    let name = filepath
+        .trim_end_matches('/')
+        .rsplit('/')
+        .next()
+        .filter(|name| !name.is_empty())
+        .ok_or_else(|| format_err!("invalid filepath, no file name in '{filepath}'"))?;
+
+    Ok(match content_type {
+        "application/zip" => format!("{name}.zip"),
+        "application/zstd" | "application/x-zstd" => format!("{name}.tar.zst"),
+        _ => name.to_string(),
+    })
+}
+
+/// `attachment` disposition for a file name, falling back to an RFC 5987 encoded name for
+/// anything but printable ASCII.
+fn content_disposition(filename: &str) -> String {
+    let ascii: String = filename
+        .chars()
+        .map(|c| match c {
+            '"' | '\\' => '_',
+            c if c.is_ascii_graphic() || c == ' ' => c,
+            _ => '_',
+        })
+        .collect();
+
+    if ascii == filename {
+        format!("attachment; filename=\"{filename}\"")
+    } else {
+        let encoded =
+            percent_encoding::utf8_percent_encode(filename, percent_encoding::NON_ALPHANUMERIC);
+        format!("attachment; filename=\"{ascii}\"; filename*=UTF-8''{encoded}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{content_disposition, download_filename};
+
+    fn encode(path: &str) -> String {
+        proxmox_base64::encode(path)
+    }
+
+    #[test]
+    fn download_filenames() {
+        let file = encode("root.pxar.didx/etc/hosts");
+        assert_eq!(
+            download_filename(&file, "application/octet-stream").unwrap(),
+            "hosts"
+        );
+
+        let dir = encode("root.pxar.didx/etc/");
+        assert_eq!(
+            download_filename(&dir, "application/zip").unwrap(),
+            "etc.zip"
+        );
+        assert_eq!(
+            download_filename(&dir, "application/zstd").unwrap(),
+            "etc.tar.zst"
+        );
+
+        assert_eq!(
+            download_filename(&encode("root.pxar.didx"), "application/zip").unwrap(),
+            "root.pxar.didx.zip"
+        );
+
+        assert!(download_filename(&encode("/"), "application/zip").is_err());
+        assert!(download_filename("not base64!", "application/zip").is_err());
+    }
+
+    #[test]
+    fn content_dispositions() {
+        assert_eq!(
+            content_disposition("hosts"),
+            "attachment; filename=\"hosts\""
+        );
+        assert_eq!(
+            content_disposition("a \"b\".txt"),
+            "attachment; filename=\"a _b_.txt\"; filename*=UTF-8''a%20%22b%22%2Etxt"
+        );
+        assert_eq!(
+            content_disposition("\u{e4}.txt"),
+            "attachment; filename=\"_.txt\"; filename*=UTF-8''%C3%A4%2Etxt"
+        );
+    }
+}
@@ -0,0 +1,342 @@
+//! Garbage collection, prune, sync and verification jobs of the PBS remotes.
+
+use std::collections::HashMap;
+
+use anyhow::{Context, Error};
+use futures::future::join_all;
+
+use pbs_api_types::{DATASTORE_SCHEMA, JOB_ID_SCHEMA, JobScheduleStatus};
+use proxmox_router::{
+    Permission, Router, RpcEnvironment, SubdirMap, http_bail, list_subdirs_api_method,
+};
+use proxmox_schema::api;
+use proxmox_sortable_macro::sortable;
+
+use pdm_api_types::pbs::{PbsJobList, PbsJobStatus, PbsJobType};
+use pdm_api_types::remotes::{REMOTE_ID_SCHEMA, Remote, RemoteType};
+use pdm_api_types::resource::{FailedRemote, Resource};
+use pdm_api_types::{
+    Authid, PRIV_RESOURCE_AUDIT, PRIV_RESOURCE_MODIFY, RemoteUpid, VIEW_ID_SCHEMA,
+};
+
+use crate::acl::CachedUserInfo;
+use crate::pbs_client;
+use crate::views::{self, View};
+
+pub const ROUTER: Router = Router::new().get(&API_METHOD_LIST_JOBS);
+
+#[sortable]
+const REMOTE_JOBS_SUBDIRS: SubdirMap =
+    &sorted!([("run", &Router::new().post(&API_METHOD_RUN_JOB)),]);
+
+pub const REMOTE_JOBS_ROUTER: Router = Router::new()
+    .get(&list_subdirs_api_method!(REMOTE_JOBS_SUBDIRS))
+    .subdirs(REMOTE_JOBS_SUBDIRS);
+
+struct JobInfo<'a> {
+    job_type: PbsJobType,
+    id: String,
+    store: String,
+    schedule: Option<String>,
+    comment: Option<String>,
+    status: JobScheduleStatus,
+    remote: &'a str,
+}
+
+impl From<JobInfo<'_>> for PbsJobStatus {
+    fn from(job: JobInfo<'_>) -> Self {
+        let JobScheduleStatus {
+            next_run,
+            last_run_state,
+            last_run_upid,
+            last_run_endtime,
+        } = job.status;
+
+        PbsJobStatus {
+            remote: job.remote.to_string(),
+            job_type: job.job_type,
+            id: job.id,
+            store: job.store,
+            schedule: job.schedule,
+            comment: job.comment,
+            next_run,
+            last_run_state,
+            last_run_endtime,
+            last_run_upid: last_run_upid
+                .map(|upid| RemoteUpid::new(job.remote.to_string(), RemoteType::Pbs, upid)),
+        }
+    }
+}
+
+/// Get the garbage collection status of all datastores and the status of all prune, sync and
+/// verification jobs of a PBS remote.
+async fn fetch_jobs(remote: &Remote) -> Result<Vec<PbsJobStatus>, Error> {
+    let client = pbs_client::connect(remote)?;
+    let remote = remote.id.as_str();
+
+    let (gc, prune, sync, verify) = futures::try_join!(
+        client.list_gc_jobs(),
+        client.list_prune_jobs(),
+        client.list_sync_jobs(),
+        client.list_verify_jobs(),
+    )?;
+
+    let gc = gc.into_iter().map(|gc| JobInfo {
+        job_type: PbsJobType::GarbageCollection,
+        id: gc.store.clone(),
+        store: gc.store,
+        schedule: gc.schedule,
+        comment: None,
+        status: JobScheduleStatus {
+            next_run: gc.next_run,
+            last_run_state: gc.last_run_state,
+            last_run_upid: gc.last_run_upid,
+            last_run_endtime: gc.last_run_endtime,
+        },
+        remote,
+    });
+
+    let prune = prune.into_iter().map(|job| JobInfo {
+        job_type: PbsJobType::Prune,
+        id: job.config.id,
+        store: job.config.store,
+        schedule: Some(job.config.schedule),
+        comment: job.config.comment,
+        status: job.status,
+        remote,
+    });
+
+    let sync = sync.into_iter().map(|job| JobInfo {
+        job_type: PbsJobType::Sync,
+        id: job.config.id,
+        store: job.config.store,
+        schedule: job.config.schedule,
+        comment: job.config.comment,
+        status: job.status,
+        remote,
+    });
+
+    let verify = verify.into_iter().map(|job| JobInfo {
+        job_type: PbsJobType::Verify,
+        id: job.config.id,
+        store: job.config.store,
+        schedule: job.config.schedule,
+        comment: job.config.comment,
+        status: job.status,
+        remote,
+    });
+
+    Ok(gc
+        .chain(prune)
+        .chain(sync)
+        .chain(verify)
+        .map(PbsJobStatus::from)
+        .collect())
+}
+
+/// Check if the datastore of a job is part of a view.
+///
+/// If the datastore is not in the resource cache (yet), the remote has to be included as a whole.
+fn job_in_view(view: &View, resources: &[Resource], job: &PbsJobStatus) -> bool {
+    let datastore = resources.iter().find(|resource| match resource {
+        Resource::PbsDatastore(datastore) => datastore.name == job.store,
+        _ => false,
+    });
+
+    match datastore {
+        Some(datastore) => view.resource_matches(&job.remote, datastore),
+        None => view.is_remote_explicitly_included(&job.remote),
+    }
+}
+
+#[api(
+    input: {
+        properties: {
+            remote: {
+                schema: REMOTE_ID_SCHEMA,
+                optional: true,
+            },
+            view: {
+                schema: VIEW_ID_SCHEMA,
+                optional: true,
+            },
+        },
+    },
+    access: {
+        permission: &Permission::Anybody,
+        description: "The user needs `Resource.Audit` on `/view/{view}` if a view is given. \
+            Otherwise only jobs of datastores with `Resource.Audit` on \
+            `/resource/{remote}/datastore/{datastore}` are returned.",
+    },
+    returns: { type: PbsJobList },
+)]
+/// List the garbage collection, prune, sync and verification jobs of the PBS remotes.
+async fn list_jobs(
+    remote: Option<String>,
+    view: Option<String>,
+    rpcenv: &mut dyn RpcEnvironment,
+) -> Result<PbsJobList, Error> {
+    let user_info = CachedUserInfo::new()?;
+    let auth_id: Authid = rpcenv
+        .get_auth_id()
+        .context("no authid available")?
+        .parse()?;
+
+    let view = match &view {
+        Some(view) => {
+            user_info.check_privs(&auth_id, &["view", view], PRIV_RESOURCE_AUDIT, false)?;
+            Some(views::get_view(view)?)
+        }
+        None => {
+            if !user_info.any_privs_below(&auth_id, &["resource"], PRIV_RESOURCE_AUDIT)? {
+                http_bail!(FORBIDDEN, "user has no access to resources");
+            }
+            None
+        }
+    };
+
+    let (remotes, _) = pdm_config::remotes::config()?;
+
+    let pbs_remotes = remotes
+        .iter()
+        .map(|(_, remote)| remote)
+        .filter(|remote| remote.ty == RemoteType::Pbs)
+        .filter(|pbs| remote.as_ref().is_none_or(|remote| *remote == pbs.id))
+        .filter(|remote| match &view {
+            Some(view) => !view.can_skip_remote(&remote.id),
+            None => user_info
+                .any_privs_below(&auth_id, &["resource", &remote.id], PRIV_RESOURCE_AUDIT)
+                .unwrap_or(false),
+        });
+
+    let results =
+        join_all(pbs_remotes.map(|remote| async move { (remote, fetch_jobs(remote).await) })).await;
+
+    let mut list = PbsJobList::default();
+
+    for (remote, result) in results {
+        match result {
+            Ok(jobs) => list.jobs.extend(jobs),
+            Err(err) => list.failed_remotes.push(FailedRemote {
+                name: remote.id.clone(),
+                error: format!("{err:#}"),
+                remote_type: remote.ty,
+            }),
+        }
+    }
+
+    match view {
+        Some(view) => {
+            tokio::task::spawn_blocking(move || -> Result<PbsJobList, Error> {
+                let mut resources = HashMap::new();
+                for job in &list.jobs {
+                    if !resources.contains_key(&job.remote) {
+                        let cached = crate::api::resources::get_cached_resources_blocking(
+                            &job.remote,
+                            i64::MAX as u64,
+                        )?
+                        .map(|cached| cached.resources)
+                        .unwrap_or_default();
+                        resources.insert(job.remote.clone(), cached);
+                    }
+                }
+                list.jobs
+                    .retain(|job| job_in_view(&view, &resources[&job.remote], job));
+                Ok(list)
+            })
+            .await?
+        }
+        None => {
+            list.jobs.retain(|job| {
+                user_info.lookup_privs(
+                    &auth_id,
+                    &["resource", &job.remote, "datastore", &job.store],
+                ) & PRIV_RESOURCE_AUDIT
+                    != 0
+            });
+            Ok(list)
+        }
+    }
+}
+
+#[api(
+    input: {
+        properties: {
+            remote: { schema: REMOTE_ID_SCHEMA },
+            "job-type": { type: PbsJobType },
+            id: { schema: JOB_ID_SCHEMA },
+        },
+    },
+    access: {
+        permission: &Permission::Privilege(&["resource", "{remote}"], PRIV_RESOURCE_MODIFY, false),
+    },
+    returns: { type: RemoteUpid },
+)]
+/// Run a job of a PBS remote now.
+///
+/// The ID of a garbage collection job is the name of its datastore.
+async fn run_job(remote: String, job_type: PbsJobType, id: String) -> Result<RemoteUpid, Error> {
+    let client = pbs_client::connect_to_remote_by_id(&remote)?;
+
+    let upid = match job_path(job_type) {
+        Some(path) => client.run_job(path, &id).await?,
+        None => {
+            DATASTORE_SCHEMA
+                .parse_simple_value(&id)
+                .context("invalid datastore name")?;
+            client.start_gc(&id).await?
+        }
+    };
+
+    super::new_remote_upid(remote, upid).await
+}
+
+/// The path component of a job type in the PBS API, `None` for garbage collection which is
+/// started on the datastore.
+fn job_path(job_type: PbsJobType) -> Option<&'static str> {
+    match job_type {
+        PbsJobType::GarbageCollection => None,
+        PbsJobType::Prune => Some("prune"),
+        PbsJobType::Sync => Some("sync"),
+        PbsJobType::Verify => Some("verify"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn job_paths() {
+        assert_eq!(job_path(PbsJobType::GarbageCollection), None);
+        assert_eq!(job_path(PbsJobType::Prune), Some("prune"));
+        assert_eq!(job_path(PbsJobType::Sync), Some("sync"));
+        assert_eq!(job_path(PbsJobType::Verify), Some("verify"));
+    }
+
+    #[test]
+    fn gc_job_status() {
+        let job = JobInfo {
+            job_type: PbsJobType::GarbageCollection,
+            id: "store1".into(),
+            store: "store1".into(),
+            schedule: Some("daily".into()),
+            comment: None,
+            status: JobScheduleStatus {
+                next_run: Some(1000),
+                last_run_state: Some("OK".into()),
+                last_run_upid: None,
+                last_run_endtime: Some(500),
+            },
+            remote: "pbs1",
+        };
+
+        let status = PbsJobStatus::from(job);
+        assert_eq!(status.remote, "pbs1");
+        assert_eq!(status.job_type, PbsJobType::GarbageCollection);
+        assert_eq!(status.id, "store1");
+        assert_eq!(status.next_run, Some(1000));
+        assert_eq!(status.last_run_upid, None);
+        assert!(!status.is_failed());
+    }
+}
@@ -7,7 +7,8 @@ use proxmox_schema::property_string::PropertyString;
 use proxmox_sortable_macro::sortable;
 
 use pdm_api_types::remotes::{
-    NodeUrl, REMOTE_ID_SCHEMA, Remote, RemoteListEntry, RemoteType, TlsProbeOutcome,
+    NodeUrl, REMOTE_ID_SCHEMA, REMOTE_TFA_CHALLENGE_SCHEMA, REMOTE_TFA_RESPONSE_SCHEMA, Remote,
+    RemoteListEntry, RemoteScanResult, RemoteType, TlsProbeOutcome,
 };
 use pdm_api_types::{
     Authid, HOST_OPTIONAL_PORT_FORMAT, PRIV_RESOURCE_AUDIT, PRIV_SYS_MODIFY, RemoteUpid,
@@ -20,6 +21,9 @@ use crate::{
 
 use crate::remote_tasks;
 
+mod backup_coverage;
+mod content;
+mod jobs;
 mod node;
 mod rrddata;
 pub mod tasks;
@@ -30,6 +34,8 @@ pub const ROUTER: Router = Router::new()
 
 #[sortable]
 const SUBDIRS: SubdirMap = &sorted!([
+    ("backup-coverage", &backup_coverage::ROUTER),
+    ("jobs", &jobs::ROUTER),
     ("remotes", &REMOTES_ROUTER),
     ("scan", &Router::new().post(&API_METHOD_SCAN_REMOTE_PBS)),
     ("probe-tls", &Router::new().post(&API_METHOD_PROBE_TLS)),
@@ -55,6 +61,7 @@ const REMOTE_SUBDIRS: SubdirMap = &sorted!([
     ("status", &Router::new().get(&API_METHOD_GET_STATUS)),
     ("rrddata", &rrddata::PBS_NODE_RRD_ROUTER),
     ("datastore", &DATASTORE_ROUTER),
+    ("jobs", &jobs::REMOTE_JOBS_ROUTER),
     ("tasks", &tasks::ROUTER),
 ]);
 
@@ -69,10 +76,23 @@ const DATASTORE_ITEM_ROUTER: Router = Router::new()
 #[sortable]
 const DATASTORE_ITEM_SUBDIRS: SubdirMap = &sorted!([
     ("rrddata", &rrddata::PBS_DATASTORE_RRD_ROUTER),
+    ("catalog", &Router::new().get(&content::API_METHOD_CATALOG)),
+    (
+        "files",
+        &Router::new().get(&content::API_METHOD_LIST_SNAPSHOT_FILES)
+    ),
+    (
+        "groups",
+        &Router::new().get(&content::API_METHOD_LIST_GROUPS)
+    ),
     (
         "namespaces",
         &Router::new().get(&API_METHOD_LIST_NAMESPACES)
     ),
+    (
+        "pxar-file-download",
+        &Router::new().download(&content::API_METHOD_PXAR_FILE_DOWNLOAD)
+    ),
     ("snapshots", &Router::new().get(&API_METHOD_LIST_SNAPSHOTS)),
 ]);
 
@@ -226,7 +246,7 @@ async fn probe_tls(
 
 pub async fn connect_or_login(
     remote: &Remote,
-) -> Result<Box<PbsClient<proxmox_client::Client>>, Error> {
+) -> Result<Box<PbsClient<connection::LimitedClient>>, Error> {
     connection::make_pbs_client_and_login(remote).await
 }
 
@@ -250,13 +270,21 @@ pub async fn connect_or_login(
                 type: String,
                 description: "The token secret or the user password.",
             },
+            "tfa-challenge": {
+                schema: REMOTE_TFA_CHALLENGE_SCHEMA,
+                optional: true,
+            },
+            "tfa-response": {
+                schema: REMOTE_TFA_RESPONSE_SCHEMA,
+                optional: true,
+            },
         },
     },
     access: {
         permission:
             &Permission::Privilege(&["/"], PRIV_SYS_MODIFY, false),
     },
-    returns: { type: Remote }
+    returns: { type: RemoteScanResult }
 )]
 /// Scans the given connection info for pbs host information.
 ///
@@ -266,7 +294,9 @@ pub async fn scan_remote_pbs(
     fingerprint: Option<String>,
     authid: Authid,
     token: String,
-) -> Result<Remote, Error> {
+    tfa_challenge: Option<String>,
+    tfa_response: Option<String>,
+) -> Result<RemoteScanResult, Error> {
     let remote = Remote {
         ty: RemoteType::Pbs,
         id: hostname.clone(),
@@ -277,13 +307,32 @@ pub async fn scan_remote_pbs(
         authid: authid.clone(),
         token,
         web_url: None,
+        folder: None,
+        timeout: None,
+        http_proxy: None,
+        max_connections: None,
+        token_created: None,
     };
 
+    if let Some(tfa_challenge) =
+        connection::login_remote(&remote, tfa_challenge.as_deref(), tfa_response.as_deref())
+            .await
+            .map_err(|err| format_err!("could not login: {err}"))?
+    {
+        return Ok(RemoteScanResult {
+            remote: None,
+            tfa_challenge: Some(tfa_challenge),
+        });
+    }
+
     let _client = connect_or_login(&remote)
         .await
         .map_err(|err| format_err!("could not login: {err}"))?;
 
-    Ok(remote)
+    Ok(RemoteScanResult {
+        remote: Some(remote),
+        tfa_challenge: None,
+    })
 }
 
 #[api(
@@ -329,6 +378,11 @@ pub async fn list_realm_remote_pbs(
         authid: "root@pam".parse()?,
         token: String::new(),
         web_url: None,
+        folder: None,
+        timeout: None,
+        http_proxy: None,
+        max_connections: None,
+        token_created: None,
     };
 
     let client = connection::make_pbs_client(&remote)?;
@@ -0,0 +1,245 @@
+use anyhow::{Error, format_err};
+
+use proxmox_router::{Permission, Router, SubdirMap, http_bail, list_subdirs_api_method};
+use proxmox_schema::api;
+use proxmox_schema::property_string::PropertyString;
+use proxmox_sortable_macro::sortable;
+
+use pdm_api_types::remotes::{
+    NodeUrl, REMOTE_ID_SCHEMA, REMOTE_TFA_CHALLENGE_SCHEMA, REMOTE_TFA_RESPONSE_SCHEMA, Remote,
+    RemoteListEntry, RemoteScanResult, RemoteType, TlsProbeOutcome,
+};
+use pdm_api_types::{
+    Authid, HOST_OPTIONAL_PORT_FORMAT, NODE_SCHEMA, PRIV_RESOURCE_AUDIT, PRIV_SYS_MODIFY,
+    RemoteUpid,
+};
+
+use crate::connection::{self, probe_tls_connection};
+use crate::{pmg_client, remote_tasks};
+
+pub mod tasks;
+
+pub const ROUTER: Router = Router::new()
+    .get(&list_subdirs_api_method!(SUBDIRS))
+    .subdirs(SUBDIRS);
+
+#[sortable]
+const SUBDIRS: SubdirMap = &sorted!([
+    ("remotes", &REMOTES_ROUTER),
+    ("scan", &Router::new().post(&API_METHOD_SCAN_REMOTE_PMG)),
+    ("probe-tls", &Router::new().post(&API_METHOD_PROBE_TLS)),
+]);
+
+const REMOTES_ROUTER: Router = Router::new()
+    .get(&API_METHOD_LIST_REMOTES)
+    .match_all("remote", &MAIN_ROUTER);
+
+pub const MAIN_ROUTER: Router = Router::new()
+    .get(&list_subdirs_api_method!(REMOTE_SUBDIRS))
+    .subdirs(REMOTE_SUBDIRS);
+
+#[sortable]
+const REMOTE_SUBDIRS: SubdirMap = &sorted!([("nodes", &NODES_ROUTER), ("tasks", &tasks::ROUTER),]);
+
+const NODES_ROUTER: Router = Router::new()
+    .get(&API_METHOD_LIST_NODES)
+    .match_all("node", &NODE_ROUTER);
+
+const NODE_ROUTER: Router = Router::new()
+    .get(&list_subdirs_api_method!(NODE_SUBDIRS))
+    .subdirs(NODE_SUBDIRS);
+
+#[sortable]
+const NODE_SUBDIRS: SubdirMap =
+    &sorted!([("status", &Router::new().get(&API_METHOD_GET_NODE_STATUS))]);
+
+// converts a remote + PMG UPID into a RemoteUpid and starts tracking it
+pub async fn new_remote_upid(
+    remote: String,
+    upid: pve_api_types::PveUpid,
+) -> Result<RemoteUpid, Error> {
+    let remote_upid = remote_tasks::track_running_pmg_task(remote, upid).await?;
+    Ok(remote_upid)
+}
+
+#[api(
+    returns: {
+        type: Array,
+        description: "List of PMG remotes",
+        items: {
+            type: pdm_api_types::remotes::RemoteListEntry,
+        },
+    },
+)]
+/// Return the list of PMG remotes
+fn list_remotes() -> Result<Vec<RemoteListEntry>, Error> {
+    Ok(super::remotes::RemoteIterator::new()?
+        .remote_type(RemoteType::Pmg)
+        .into_names()
+        .map(|name| RemoteListEntry { remote: name })
+        .collect())
+}
+
+#[api(
+    input: {
+        properties: {
+            remote: { schema: REMOTE_ID_SCHEMA },
+        },
+    },
+    access: {
+        permission: &Permission::Privilege(&["resource", "{remote}"], PRIV_RESOURCE_AUDIT, false),
+    },
+    returns: {
+        type: Array,
+        description: "List of PMG cluster nodes.",
+        items: { type: pdm_api_types::pmg::NodeIndexEntry },
+    },
+)]
+/// List the nodes of a PMG remote.
+async fn list_nodes(remote: String) -> Result<Vec<pdm_api_types::pmg::NodeIndexEntry>, Error> {
+    Ok(pmg_client::connect_to_remote_by_id(&remote)?
+        .list_nodes()
+        .await?)
+}
+
+#[api(
+    input: {
+        properties: {
+            remote: { schema: REMOTE_ID_SCHEMA },
+            node: { schema: NODE_SCHEMA },
+        },
+    },
+    access: {
+        permission: &Permission::Privilege(&["resource", "{remote}", "node", "{node}"], PRIV_RESOURCE_AUDIT, false),
+    },
+    returns: { type: pdm_api_types::pmg::NodeStatus },
+)]
+/// Get the status of a PMG node.
+async fn get_node_status(
+    remote: String,
+    node: String,
+) -> Result<pdm_api_types::pmg::NodeStatus, Error> {
+    Ok(pmg_client::connect_to_remote_by_id(&remote)?
+        .node_status(&node)
+        .await?)
+}
+
+#[api(
+    input: {
+        properties: {
+            hostname: {
+                type: String,
+                format: &HOST_OPTIONAL_PORT_FORMAT,
+                description: "Hostname (with optional port) of the target remote",
+            },
+            fingerprint: {
+                type: String,
+                description: "Fingerprint of the target remote.",
+                optional: true,
+            },
+        },
+    },
+    access: {
+        permission:
+            &Permission::Privilege(&["/"], PRIV_SYS_MODIFY, false),
+    },
+)]
+/// Probe the hosts TLS certificate.
+///
+/// If the certificate is not trusted with the given parameters, returns the certificate
+/// information.
+async fn probe_tls(
+    hostname: String,
+    fingerprint: Option<String>,
+) -> Result<TlsProbeOutcome, Error> {
+    probe_tls_connection(RemoteType::Pmg, hostname, fingerprint).await
+}
+
+#[api(
+    input: {
+        properties: {
+            hostname: {
+                type: String,
+                format: &HOST_OPTIONAL_PORT_FORMAT,
+                description: "Hostname (with optional port) of the target remote",
+            },
+            fingerprint: {
+                type: String,
+                description: "Fingerprint of the target remote.",
+                optional: true,
+            },
+            "authid": {
+                type: Authid,
+            },
+            "token": {
+                type: String,
+                description: "The user password, PMG has no API tokens.",
+            },
+            "tfa-challenge": {
+                schema: REMOTE_TFA_CHALLENGE_SCHEMA,
+                optional: true,
+            },
+            "tfa-response": {
+                schema: REMOTE_TFA_RESPONSE_SCHEMA,
+                optional: true,
+            },
+        },
+    },
+    access: {
+        permission:
+            &Permission::Privilege(&["/"], PRIV_SYS_MODIFY, false),
+    },
+    returns: { type: RemoteScanResult }
+)]
+/// Scans the given connection info for pmg host information.
+///
+/// Checks login using the provided credentials.
+pub async fn scan_remote_pmg(
+    hostname: String,
+    fingerprint: Option<String>,
+    authid: Authid,
+    token: String,
+    tfa_challenge: Option<String>,
+    tfa_response: Option<String>,
+) -> Result<RemoteScanResult, Error> {
+    if authid.is_token() {
+        http_bail!(BAD_REQUEST, "{}", pmg_client::NO_API_TOKENS);
+    }
+
+    let remote = Remote {
+        ty: RemoteType::Pmg,
+        id: hostname.clone(),
+        nodes: vec![PropertyString::new(NodeUrl {
+            hostname,
+            fingerprint,
+        })],
+        authid: authid.clone(),
+        token,
+        web_url: None,
+        folder: None,
+        timeout: None,
+        http_proxy: None,
+        max_connections: None,
+        token_created: None,
+    };
+
+    if let Some(tfa_challenge) =
+        connection::login_remote(&remote, tfa_challenge.as_deref(), tfa_response.as_deref())
+            .await
+            .map_err(|err| format_err!("could not login: {err}"))?
+    {
+        return Ok(RemoteScanResult {
+            remote: None,
+            tfa_challenge: Some(tfa_challenge),
+        });
+    }
+
+    let _client = pmg_client::connect_or_login(&remote)
+        .await
+        .map_err(|err| format_err!("could not login: {err}"))?;
+
+    Ok(RemoteScanResult {
+        remote: Some(remote),
+        tfa_challenge: None,
+    })
+}
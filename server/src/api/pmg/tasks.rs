@@ -0,0 +1,185 @@
+//! Access to PMG tasks.
+
+use anyhow::Error;
+
+use proxmox_router::{Permission, Router, RpcEnvironment, SubdirMap, list_subdirs_api_method};
+use proxmox_schema::api;
+use proxmox_sortable_macro::sortable;
+
+use pdm_api_types::remotes::{REMOTE_ID_SCHEMA, RemoteType};
+use pdm_api_types::{
+    NODE_SCHEMA, PRIV_RESOURCE_AUDIT, PRIV_RESOURCE_MANAGE, RemoteUpid,
+    TASKLOG_DOWNLOAD_PARAM_SCHEMA, TASKLOG_LIMIT_PARAM_SCHEMA, TASKLOG_START_PARAM_SCHEMA,
+};
+
+use crate::pmg_client;
+
+pub const ROUTER: Router = Router::new()
+    .get(&API_METHOD_LIST_TASKS)
+    .match_all("upid", &UPID_API_ROUTER);
+
+pub const UPID_API_ROUTER: Router = Router::new()
+    .get(&list_subdirs_api_method!(UPID_API_SUBDIRS))
+    .delete(&API_METHOD_STOP_TASK)
+    .subdirs(UPID_API_SUBDIRS);
+
+#[sortable]
+const UPID_API_SUBDIRS: SubdirMap = &sorted!([
+    ("log", &Router::new().get(&API_METHOD_READ_TASK_LOG)),
+    ("status", &Router::new().get(&API_METHOD_GET_TASK_STATUS)),
+]);
+
+#[api(
+    input: {
+        properties: {
+            remote: { schema: REMOTE_ID_SCHEMA },
+            node: {
+                schema: NODE_SCHEMA,
+                optional: true,
+            },
+        },
+    },
+    access: {
+        // FIXME: fine-grained task filtering?
+        permission: &Permission::Privilege(&["resource", "{remote}"], PRIV_RESOURCE_AUDIT, false),
+    },
+    returns: {
+        type: Array,
+        description: "A list of tasks.",
+        items: { type: pve_api_types::ListTasksResponse },
+    },
+)]
+/// Get the list of tasks either for a specific node, or query all at once.
+async fn list_tasks(
+    remote: String,
+    node: Option<String>,
+) -> Result<Vec<pve_api_types::ListTasksResponse>, Error> {
+    let client = pmg_client::connect_to_remote_by_id(&remote)?;
+
+    if let Some(node) = node {
+        Ok(client.get_task_list(&node, Default::default()).await?)
+    } else {
+        let mut entry = Vec::new();
+        for node in client.list_nodes().await? {
+            entry.extend(client.get_task_list(&node.node, Default::default()).await?);
+        }
+        Ok(entry)
+    }
+}
+
+#[api(
+    input: {
+        properties: {
+            remote: { schema: REMOTE_ID_SCHEMA },
+            upid: { type: RemoteUpid },
+        },
+    },
+    access: {
+        // FIXME: fine-grained task filtering?
+        permission: &Permission::Privilege(&["resource", "{remote}"], PRIV_RESOURCE_MANAGE, false),
+    },
+)]
+/// Stop a task on a Proxmox Mail Gateway instance.
+async fn stop_task(remote: String, upid: RemoteUpid) -> Result<(), Error> {
+    crate::api::verify_upid(&remote, RemoteType::Pmg, &upid)?;
+
+    let pmg_upid = upid.pmg_upid()?;
+    let client = pmg_client::connect_to_remote_by_id(upid.remote())?;
+
+    Ok(client.stop_task(&pmg_upid.node, upid.upid()).await?)
+}
+
+#[api(
+    input: {
+        properties: {
+            remote: { schema: REMOTE_ID_SCHEMA },
+            upid: { type: RemoteUpid },
+            wait: {
+                description: "wait for the task to finish before returning its result",
+                type: Boolean,
+                optional: true,
+                default: false,
+            },
+        },
+    },
+    access: {
+        // FIXME: fine-grained task filtering?
+        permission: &Permission::Privilege(&["resource", "{remote}"], PRIV_RESOURCE_AUDIT, false),
+    },
+    returns: { type: pve_api_types::TaskStatus },
+)]
+/// Get the status of a task from a Proxmox Mail Gateway instance.
+pub async fn get_task_status(
+    remote: String,
+    upid: RemoteUpid,
+    wait: bool,
+) -> Result<pve_api_types::TaskStatus, Error> {
+    crate::api::verify_upid(&remote, RemoteType::Pmg, &upid)?;
+
+    let pmg_upid = upid.pmg_upid()?;
+    let client = pmg_client::connect_to_remote_by_id(upid.remote())?;
+
+    loop {
+        let status = client.get_task_status(&pmg_upid.node, upid.upid()).await?;
+        if !wait || !status.is_running() {
+            break Ok(status);
+        }
+    }
+}
+
+// FIXME: make *actually* streaming with router support!
+#[api(
+    input: {
+        properties: {
+            remote: { schema: REMOTE_ID_SCHEMA },
+            upid: { type: RemoteUpid },
+            start: {
+                schema: TASKLOG_START_PARAM_SCHEMA,
+                optional: true,
+            },
+            limit: {
+                schema: TASKLOG_LIMIT_PARAM_SCHEMA,
+                optional: true,
+            },
+            download: {
+                schema: TASKLOG_DOWNLOAD_PARAM_SCHEMA,
+                optional: true,
+            }
+        },
+    },
+    access: {
+        // FIXME: fine-grained task filtering?
+        permission: &Permission::Privilege(&["resource", "{remote}"], PRIV_RESOURCE_AUDIT, false),
+    },
+    returns: {
+        type: Array,
+        items: {
+            type: pve_api_types::TaskLogLine,
+        },
+        description: "Array of task log lines",
+    },
+)]
+/// Read a task log.
+async fn read_task_log(
+    remote: String,
+    upid: RemoteUpid,
+    download: Option<bool>,
+    start: Option<u64>,
+    limit: Option<u64>,
+    rpcenv: &mut dyn RpcEnvironment,
+) -> Result<Vec<pve_api_types::TaskLogLine>, Error> {
+    crate::api::verify_upid(&remote, RemoteType::Pmg, &upid)?;
+
+    let pmg_upid = upid.pmg_upid()?;
+    let client = pmg_client::connect_to_remote_by_id(upid.remote())?;
+
+    let response = client
+        .get_task_log(&pmg_upid.node, upid.upid(), download, limit, start)
+        .await?;
+
+    for (key, value) in response.attribs {
+        rpcenv[&key] = value;
+    }
+
+    Ok(response.data)
+}
@@ -0,0 +1,693 @@
+//! Backup (`vzdump`) jobs of the PVE remotes.
+
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+use anyhow::{Context, Error};
+use futures::future::join_all;
+use serde::Deserialize;
+use serde_json::Value;
+
+use proxmox_client::HttpApiClient;
+use proxmox_router::{
+    Permission, Router, RpcEnvironment, SubdirMap, http_bail, list_subdirs_api_method,
+};
+use proxmox_schema::api;
+use proxmox_sortable_macro::sortable;
+use proxmox_time::CalendarEvent;
+
+use pdm_api_types::pve::{
+    PVE_BACKUP_JOB_ID_SCHEMA, PveBackupJob, PveBackupJobList, PveBackupJobRun, PveUncoveredGuest,
+};
+use pdm_api_types::remotes::{REMOTE_ID_SCHEMA, Remote, RemoteType};
+use pdm_api_types::resource::{FailedRemote, GuestType, Resource};
+use pdm_api_types::{
+    Authid, PRIV_RESOURCE_AUDIT, PRIV_RESOURCE_MODIFY, RemoteUpid, TaskFilters, TaskListItem,
+    VIEW_ID_SCHEMA,
+};
+use pve_api_types::{ClusterResourceKind, ClusterResourceType};
+
+use crate::acl::CachedUserInfo;
+use crate::views::{self, View};
+use crate::{connection, remote_tasks};
+
+pub const ROUTER: Router = Router::new().get(&API_METHOD_LIST_ALL_BACKUP_JOBS);
+
+pub const REMOTE_ROUTER: Router = Router::new()
+    .get(&API_METHOD_LIST_BACKUP_JOBS)
+    .match_all("id", &JOB_ROUTER);
+
+const JOB_ROUTER: Router = Router::new()
+    .get(&list_subdirs_api_method!(JOB_SUBDIRS))
+    .subdirs(JOB_SUBDIRS);
+
+#[sortable]
+const JOB_SUBDIRS: SubdirMap =
+    &sorted!([("run", &Router::new().post(&API_METHOD_RUN_BACKUP_JOB)),]);
+
+/// Scheduled `vzdump` tasks are expected to start within this many seconds after the scheduled
+/// time.
+const SCHEDULE_SLACK: i64 = 120;
+
+/// Properties of a backup job which are not parameters of `vzdump`.
+const NON_VZDUMP_PROPERTIES: &[&str] = &[
+    "comment",
+    "digest",
+    "dow",
+    "enabled",
+    "id",
+    "next-run",
+    "node",
+    "repeat-missed",
+    "schedule",
+    "starttime",
+    "type",
+];
+
+fn default_true() -> bool {
+    true
+}
+
+// Only contains the properties of `/cluster/backup` PDM actually uses.
+#[derive(Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct VzdumpJobConfig {
+    id: String,
+    #[serde(
+        default = "default_true",
+        deserialize_with = "proxmox_serde::perl::deserialize_bool"
+    )]
+    enabled: bool,
+    #[serde(default)]
+    schedule: String,
+    storage: Option<String>,
+    node: Option<String>,
+    #[serde(default, deserialize_with = "proxmox_serde::perl::deserialize_bool")]
+    all: bool,
+    vmid: Option<String>,
+    pool: Option<String>,
+    exclude: Option<String>,
+    comment: Option<String>,
+    next_run: Option<i64>,
+}
+
+struct Guest {
+    vmid: u32,
+    guest_type: GuestType,
+    name: String,
+    node: String,
+    pool: Option<String>,
+    template: bool,
+}
+
+async fn fetch_guests(remote: &Remote) -> Result<Vec<Guest>, Error> {
+    let guests = connection::make_pve_client(remote)?
+        .cluster_resources(Some(ClusterResourceKind::Vm))
+        .await?
+        .into_iter()
+        .filter_map(|resource| {
+            let guest_type = match resource.ty {
+                ClusterResourceType::Qemu => GuestType::Qemu,
+                ClusterResourceType::Lxc => GuestType::Lxc,
+                _ => return None,
+            };
+            Some(Guest {
+                vmid: resource.vmid?,
+                guest_type,
+                name: resource.name.unwrap_or_default(),
+                node: resource.node.unwrap_or_default(),
+                pool: resource.pool,
+                template: resource.template.unwrap_or_default(),
+            })
+        })
+        .collect();
+
+    Ok(guests)
+}
+
+async fn fetch_vzdump_tasks(remote: &str) -> Result<Vec<TaskListItem>, Error> {
+    let filters = TaskFilters {
+        start: 0,
+        limit: 0,
+        errors: false,
+        running: false,
+        userfilter: None,
+        since: None,
+        until: None,
+        typefilter: Some("vzdump".to_string()),
+        statusfilter: None,
+    };
+
+    remote_tasks::get_tasks(filters, Some(remote.to_string()), |_| true, None).await
+}
+
+fn parse_vmid_list(list: &str) -> HashSet<u32> {
+    list.split([',', ';', ' '])
+        .filter_map(|vmid| vmid.trim().parse().ok())
+        .collect()
+}
+
+/// Get the guests backed up by a job, selected the same way `vzdump` does.
+fn targeted_guests<'a>(job: &VzdumpJobConfig, guests: &'a [Guest]) -> Vec<&'a Guest> {
+    let vmids = job.vmid.as_deref().map(parse_vmid_list).unwrap_or_default();
+    let exclude = job
+        .exclude
+        .as_deref()
+        .map(parse_vmid_list)
+        .unwrap_or_default();
+
+    guests
+        .iter()
+        .filter(|guest| job.node.as_ref().is_none_or(|node| *node == guest.node))
+        .filter(|guest| {
+            if job.all {
+                !exclude.contains(&guest.vmid)
+            } else if let Some(pool) = &job.pool {
+                guest.pool.as_ref() == Some(pool)
+            } else {
+                vmids.contains(&guest.vmid)
+            }
+        })
+        .collect()
+}
+
+/// The largest UTC offset of a time zone, in seconds.
+const MAX_UTC_OFFSET: i64 = 14 * 3600;
+
+/// A schedule evaluated in the time zone of a remote, which is `offset` seconds ahead of UTC.
+struct RemoteSchedule {
+    event: CalendarEvent,
+    offset: i64,
+}
+
+impl RemoteSchedule {
+    /// Parse a schedule and derive the time zone of the remote from the next run the remote
+    /// computed for it.
+    ///
+    /// The first UTC offset (in steps of 15 minutes, closest to UTC first) for which the schedule
+    /// fires at `next_run` is used. Without a next run the schedule is evaluated in the time zone
+    /// of PDM.
+    fn new(schedule: &str, next_run: Option<i64>) -> Option<Self> {
+        let Some(next_run) = next_run else {
+            return Some(Self {
+                event: schedule.parse().ok()?,
+                offset: 0,
+            });
+        };
+
+        let event: CalendarEvent = if schedule.trim_end().ends_with("UTC") {
+            schedule.parse().ok()?
+        } else {
+            format!("{schedule} UTC").parse().ok()?
+        };
+
+        let offset = (0..=MAX_UTC_OFFSET / 900)
+            .flat_map(|step| [step * 900, -step * 900])
+            .find(|offset| {
+                event
+                    .compute_next_event(next_run + offset - 1)
+                    .is_ok_and(|next| next == Some(next_run + offset))
+            })?;
+
+        Some(Self { event, offset })
+    }
+
+    fn compute_next_event(&self, last: i64) -> Option<i64> {
+        let next = self.event.compute_next_event(last + self.offset).ok()??;
+        Some(next - self.offset)
+    }
+}
+
+/// Find the most recent run of a backup job in the `vzdump` tasks of its remote.
+///
+/// The tasks do not reference the job they were started by, so a task started by the scheduler is
+/// attributed to a job if the schedule of the job fired shortly before the task started. Jobs with
+/// the same schedule therefore share their runs. The schedule is evaluated in the time zone of the
+/// remote, as derived from the `next_run` of the job, see [`RemoteSchedule::new`].
+fn find_last_run(
+    schedule: &str,
+    next_run: Option<i64>,
+    node: Option<&str>,
+    tasks: &[TaskListItem],
+) -> Option<PveBackupJobRun> {
+    let event = RemoteSchedule::new(schedule, next_run)?;
+
+    let mut runs: Vec<(i64, &TaskListItem)> = tasks
+        .iter()
+        .filter(|task| task.worker_type == "vzdump" && task.user == "root@pam")
+        .filter(|task| node.is_none_or(|node| task.node == node))
+        .filter_map(|task| {
+            let scheduled = event.compute_next_event(task.starttime - SCHEDULE_SLACK)?;
+            (scheduled <= task.starttime).then_some((scheduled, task))
+        })
+        .collect();
+
+    let last = runs.iter().map(|(scheduled, _)| *scheduled).max()?;
+    runs.retain(|(scheduled, _)| *scheduled == last);
+
+    let running = runs.iter().any(|(_, task)| task.endtime.is_none());
+    let (endtime, status) = if running {
+        (None, None)
+    } else {
+        let status = runs
+            .iter()
+            .filter_map(|(_, task)| task.status.as_deref())
+            .find(|status| *status != "OK")
+            .unwrap_or("OK");
+        (
+            runs.iter().filter_map(|(_, task)| task.endtime).max(),
+            Some(status.to_string()),
+        )
+    };
+
+    Some(PveBackupJobRun {
+        upids: runs
+            .iter()
+            .filter_map(|(_, task)| task.upid.parse().ok())
+            .collect(),
+        starttime: runs.iter().map(|(_, task)| task.starttime).min()?,
+        endtime,
+        status,
+    })
+}
+
+/// Correlate the backup jobs of a remote with its guests and `vzdump` tasks.
+fn correlate(
+    remote: &str,
+    configs: Vec<VzdumpJobConfig>,
+    guests: &[Guest],
+    tasks: &[TaskListItem],
+) -> (Vec<PveBackupJob>, Vec<PveUncoveredGuest>) {
+    let mut covered = HashSet::new();
+
+    let jobs = configs
+        .into_iter()
+        .map(|job| {
+            let targeted: Vec<u32> = targeted_guests(&job, guests)
+                .into_iter()
+                .map(|guest| guest.vmid)
+                .collect();
+            if job.enabled {
+                covered.extend(targeted.iter().copied());
+            }
+
+            PveBackupJob {
+                remote: remote.to_string(),
+                last_run: find_last_run(&job.schedule, job.next_run, job.node.as_deref(), tasks),
+                id: job.id,
+                enabled: job.enabled,
+                schedule: job.schedule,
+                storage: job.storage,
+                node: job.node,
+                all: job.all,
+                vmid: job.vmid,
+                pool: job.pool,
+                exclude: job.exclude,
+                comment: job.comment,
+                next_run: job.next_run,
+                guests: targeted,
+            }
+        })
+        .collect();
+
+    let uncovered = guests
+        .iter()
+        .filter(|guest| !guest.template && !covered.contains(&guest.vmid))
+        .map(|guest| PveUncoveredGuest {
+            remote: remote.to_string(),
+            vmid: guest.vmid,
+            guest_type: guest.guest_type,
+            name: guest.name.clone(),
+            node: guest.node.clone(),
+        })
+        .collect();
+
+    (jobs, uncovered)
+}
+
+async fn fetch_backup_jobs(
+    remote: &Remote,
+) -> Result<(Vec<PveBackupJob>, Vec<PveUncoveredGuest>), Error> {
+    let configs: Vec<VzdumpJobConfig> = connection::make_raw_client(remote)?
+        .get("/api2/extjs/cluster/backup")
+        .await?
+        .expect_json()?
+        .data;
+    let guests = fetch_guests(remote).await?;
+    let tasks = fetch_vzdump_tasks(&remote.id).await?;
+
+    Ok(correlate(&remote.id, configs, &guests, &tasks))
+}
+
+#[api(
+    input: {
+        properties: {
+            remote: { schema: REMOTE_ID_SCHEMA },
+        },
+    },
+    access: {
+        permission: &Permission::Privilege(&["resource", "{remote}"], PRIV_RESOURCE_AUDIT, false),
+    },
+    returns: { type: PveBackupJobList },
+)]
+/// List the backup jobs of a PVE remote and the guests not covered by any of them.
+async fn list_backup_jobs(remote: String) -> Result<PveBackupJobList, Error> {
+    let (remotes, _) = pdm_config::remotes::config()?;
+    let remote = super::get_remote(&remotes, &remote)?;
+
+    let (jobs, uncovered_guests) = fetch_backup_jobs(remote).await?;
+
+    Ok(PveBackupJobList {
+        jobs,
+        uncovered_guests,
+        failed_remotes: Vec::new(),
+    })
+}
+
+/// Check if an uncovered guest is part of a view.
+///
+/// If the guest is not in the resource cache (yet), the remote has to be included as a whole.
+fn guest_in_view(view: &View, resources: &[Resource], guest: &PveUncoveredGuest) -> bool {
+    let resource = resources.iter().find(|resource| match resource {
+        Resource::PveQemu(qemu) => qemu.vmid == guest.vmid,
+        Resource::PveLxc(lxc) => lxc.vmid == guest.vmid,
+        _ => false,
+    });
+
+    match resource {
+        Some(resource) => view.resource_matches(&guest.remote, resource),
+        None => view.is_remote_explicitly_included(&guest.remote),
+    }
+}
+
+#[api(
+    input: {
+        properties: {
+            view: {
+                schema: VIEW_ID_SCHEMA,
+                optional: true,
+            },
+        },
+    },
+    access: {
+        permission: &Permission::Anybody,
+        description: "The user needs `Resource.Audit` on `/view/{view}` if a view is given. \
+            Otherwise only jobs of remotes with `Resource.Audit` on `/resource/{remote}` and \
+            guests with `Resource.Audit` on `/resource/{remote}/guest/{vmid}` are returned.",
+    },
+    returns: { type: PveBackupJobList },
+)]
+/// List the backup jobs of all PVE remotes and the guests not covered by any of them.
+async fn list_all_backup_jobs(
+    view: Option<String>,
+    rpcenv: &mut dyn RpcEnvironment,
+) -> Result<PveBackupJobList, Error> {
+    let user_info = CachedUserInfo::new()?;
+    let auth_id: Authid = rpcenv
+        .get_auth_id()
+        .context("no authid available")?
+        .parse()?;
+
+    let view = match &view {
+        Some(view) => {
+            user_info.check_privs(&auth_id, &["view", view], PRIV_RESOURCE_AUDIT, false)?;
+            Some(views::get_view(view)?)
+        }
+        None => {
+            if !user_info.any_privs_below(&auth_id, &["resource"], PRIV_RESOURCE_AUDIT)? {
+                http_bail!(FORBIDDEN, "user has no access to resources");
+            }
+            None
+        }
+    };
+
+    let (remotes, _) = pdm_config::remotes::config()?;
+
+    let pve_remotes = remotes
+        .iter()
+        .map(|(_, remote)| remote)
+        .filter(|remote| remote.ty == RemoteType::Pve)
+        .filter(|remote| match &view {
+            Some(view) => !view.can_skip_remote(&remote.id),
+            None => {
+                user_info.lookup_privs(&auth_id, &["resource", &remote.id]) & PRIV_RESOURCE_AUDIT
+                    != 0
+            }
+        });
+
+    let results = join_all(
+        pve_remotes.map(|remote| async move { (remote, fetch_backup_jobs(remote).await) }),
+    )
+    .await;
+
+    let mut list = PveBackupJobList::default();
+
+    for (remote, result) in results {
+        match result {
+            Ok((jobs, uncovered_guests)) => {
+                list.jobs.extend(jobs);
+                list.uncovered_guests.extend(uncovered_guests);
+            }
+            Err(err) => list.failed_remotes.push(FailedRemote {
+                name: remote.id.clone(),
+                error: format!("{err:#}"),
+                remote_type: remote.ty,
+            }),
+        }
+    }
+
+    match view {
+        Some(view) => {
+            tokio::task::spawn_blocking(move || -> Result<PveBackupJobList, Error> {
+                let mut resources = HashMap::new();
+                for guest in &list.uncovered_guests {
+                    if !resources.contains_key(&guest.remote) {
+                        let cached = crate::api::resources::get_cached_resources_blocking(
+                            &guest.remote,
+                            i64::MAX as u64,
+                        )?
+                        .map(|cached| cached.resources)
+                        .unwrap_or_default();
+                        resources.insert(guest.remote.clone(), cached);
+                    }
+                }
+                list.uncovered_guests
+                    .retain(|guest| guest_in_view(&view, &resources[&guest.remote], guest));
+                Ok(list)
+            })
+            .await?
+        }
+        None => {
+            list.uncovered_guests.retain(|guest| {
+                user_info.lookup_privs(
+                    &auth_id,
+                    &["resource", &guest.remote, "guest", &guest.vmid.to_string()],
+                ) & PRIV_RESOURCE_AUDIT
+                    != 0
+            });
+            Ok(list)
+        }
+    }
+}
+
+#[api(
+    input: {
+        properties: {
+            remote: { schema: REMOTE_ID_SCHEMA },
+            id: { schema: PVE_BACKUP_JOB_ID_SCHEMA },
+        },
+    },
+    access: {
+        permission: &Permission::Privilege(&["resource", "{remote}"], PRIV_RESOURCE_MODIFY, false),
+    },
+    returns: {
+        type: Array,
+        description: "The started vzdump tasks, one per node.",
+        items: { type: RemoteUpid },
+    },
+)]
+/// Run a backup job now.
+///
+/// Like in PVE, a `vzdump` task with the parameters of the job is started on every node with
+/// guests targeted by the job.
+async fn run_backup_job(remote: String, id: String) -> Result<Vec<RemoteUpid>, Error> {
+    let (remotes, _) = pdm_config::remotes::config()?;
+    let remote_config = super::get_remote(&remotes, &remote)?;
+    let client = connection::make_raw_client(remote_config)?;
+
+    let mut params: Value = client
+        .get(&format!("/api2/extjs/cluster/backup/{id}"))
+        .await?
+        .expect_json()?
+        .data;
+    let job: VzdumpJobConfig = serde_json::from_value(params.clone())?;
+
+    if let Some(params) = params.as_object_mut() {
+        for property in NON_VZDUMP_PROPERTIES {
+            params.remove(*property);
+        }
+    }
+
+    let guests = fetch_guests(remote_config).await?;
+    let nodes: BTreeSet<&str> = targeted_guests(&job, &guests)
+        .into_iter()
+        .map(|guest| guest.node.as_str())
+        .collect();
+
+    if nodes.is_empty() {
+        http_bail!(BAD_REQUEST, "backup job '{id}' does not target any guest");
+    }
+
+    let mut upids = Vec::new();
+    for node in nodes {
+        let upid: String = client
+            .post(&format!("/api2/extjs/nodes/{node}/vzdump"), &params)
+            .await?
+            .expect_json()?
+            .data;
+        upids.push(super::new_remote_upid(remote.clone(), upid.parse()?).await?);
+    }
+
+    Ok(upids)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn guest(vmid: u32, node: &str, pool: Option<&str>) -> Guest {
+        Guest {
+            vmid,
+            guest_type: GuestType::Qemu,
+            name: format!("vm-{vmid}"),
+            node: node.into(),
+            pool: pool.map(String::from),
+            template: false,
+        }
+    }
+
+    fn job(value: Value) -> VzdumpJobConfig {
+        serde_json::from_value(value).unwrap()
+    }
+
+    fn task(node: &str, starttime: i64, status: Option<&str>) -> TaskListItem {
+        TaskListItem {
+            upid: format!(
+                "pve:pve!UPID:{node}:00000001:00000002:{starttime:08X}:vzdump::root@pam:"
+            ),
+            node: node.into(),
+            pid: 1,
+            pstart: 2,
+            starttime,
+            worker_type: "vzdump".into(),
+            worker_id: None,
+            user: "root@pam".into(),
+            endtime: status.map(|_| starttime + 60),
+            status: status.map(String::from),
+        }
+    }
+
+    fn vmids(job: &VzdumpJobConfig, guests: &[Guest]) -> Vec<u32> {
+        targeted_guests(job, guests)
+            .into_iter()
+            .map(|guest| guest.vmid)
+            .collect()
+    }
+
+    #[test]
+    fn guest_selection() {
+        let guests = [
+            guest(100, "a", None),
+            guest(101, "a", Some("prod")),
+            guest(102, "b", Some("prod")),
+        ];
+
+        let all = job(serde_json::json!({ "id": "all", "all": 1, "exclude": "100" }));
+        assert_eq!(vmids(&all, &guests), [101, 102]);
+
+        let node = job(serde_json::json!({ "id": "node", "all": 1, "node": "a" }));
+        assert_eq!(vmids(&node, &guests), [100, 101]);
+
+        let pool = job(serde_json::json!({ "id": "pool", "pool": "prod" }));
+        assert_eq!(vmids(&pool, &guests), [101, 102]);
+
+        let list = job(serde_json::json!({ "id": "list", "vmid": "100,102,999" }));
+        assert_eq!(vmids(&list, &guests), [100, 102]);
+    }
+
+    #[test]
+    fn uncovered_guests() {
+        let mut guests = vec![guest(100, "a", None), guest(101, "a", None)];
+        guests.push(Guest {
+            template: true,
+            ..guest(102, "a", None)
+        });
+
+        let configs = vec![
+            job(serde_json::json!({ "id": "one", "vmid": "100" })),
+            job(serde_json::json!({ "id": "off", "vmid": "101", "enabled": 0 })),
+        ];
+
+        let (jobs, uncovered) = correlate("pve", configs, &guests, &[]);
+        assert_eq!(jobs.len(), 2);
+        assert!(jobs[1].last_run.is_none());
+        let uncovered: Vec<u32> = uncovered.iter().map(|guest| guest.vmid).collect();
+        assert_eq!(uncovered, [101]);
+    }
+
+    #[test]
+    fn last_run() {
+        // every 5 minutes, independent of the time zone
+        let schedule = "*:0/5";
+        let base = 1_700_000_100; // a multiple of 300
+
+        let tasks = [
+            task("a", base + 10, Some("OK")),
+            task("a", base + 300 + 5, Some("OK")),
+            task("b", base + 300 + 20, Some("job errors")),
+            // not started by the schedule
+            task("a", base + 300 + 200, Some("OK")),
+        ];
+
+        let run = find_last_run(schedule, None, None, &tasks).unwrap();
+        assert_eq!(run.upids.len(), 2);
+        assert_eq!(run.starttime, base + 305);
+        assert_eq!(run.endtime, Some(base + 380));
+        assert_eq!(run.status.as_deref(), Some("job errors"));
+        assert!(run.is_failed());
+
+        let run = find_last_run(schedule, None, Some("a"), &tasks).unwrap();
+        assert_eq!(run.upids.len(), 1);
+        assert_eq!(run.status.as_deref(), Some("OK"));
+
+        let running = [task("a", base + 5, None)];
+        let run = find_last_run(schedule, None, None, &running).unwrap();
+        assert_eq!(run.status, None);
+        assert!(!run.is_failed());
+    }
+
+    #[test]
+    fn last_run_in_remote_time_zone() {
+        // daily at 21:00 on a remote two hours ahead of UTC, that is 19:00 UTC
+        let schedule = "21:00";
+        let yesterday = 1_699_988_400; // 2023-11-14 19:00 UTC
+        let next_run = yesterday + 86400;
+
+        let tasks = [
+            task("a", yesterday - 86400 + 30, Some("OK")),
+            task("a", yesterday + 30, Some("backup failed")),
+            // not started by the schedule
+            task("a", yesterday + 3 * 3600, Some("OK")),
+        ];
+
+        let run = find_last_run(schedule, Some(next_run), None, &tasks).unwrap();
+        assert_eq!(run.starttime, yesterday + 30);
+        assert_eq!(run.status.as_deref(), Some("backup failed"));
+
+        let event = RemoteSchedule::new(schedule, Some(next_run)).unwrap();
+        assert_eq!(event.offset, 7200);
+        assert_eq!(event.compute_next_event(yesterday + 1), Some(next_run));
+
+        // the next run must be a scheduled time
+        assert!(RemoteSchedule::new(schedule, Some(next_run + 60)).is_none());
+    }
+}
@@ -0,0 +1,131 @@
+//! Advisory load balancing recommendations for PVE remotes.
+
+use anyhow::{Context, Error};
+
+use proxmox_router::{Permission, Router, RpcEnvironment, http_bail};
+use proxmox_rrd_api_types::RrdTimeframe;
+use proxmox_schema::api;
+
+use pdm_api_types::pve::BalancingRecommendation;
+use pdm_api_types::resource::Resource;
+use pdm_api_types::{Authid, PRIV_RESOURCE_AUDIT, VIEW_ID_SCHEMA};
+
+use crate::acl::CachedUserInfo;
+use crate::metric_collection::balancing::{self, Thresholds};
+use crate::views;
+
+pub const ROUTER: Router = Router::new().get(&API_METHOD_LIST_RECOMMENDATIONS);
+
+#[api(
+    input: {
+        properties: {
+            timeframe: {
+                type: RrdTimeframe,
+                optional: true,
+            },
+            view: {
+                schema: VIEW_ID_SCHEMA,
+                optional: true,
+            },
+            "cpu-threshold": {
+                description: "Average CPU usage in percent above which a node is overloaded.",
+                type: Integer,
+                optional: true,
+                minimum: 1,
+                maximum: 100,
+                default: 80,
+            },
+            "memory-threshold": {
+                description: "Average memory usage in percent above which a node is overloaded.",
+                type: Integer,
+                optional: true,
+                minimum: 1,
+                maximum: 100,
+                default: 85,
+            },
+            "imbalance-threshold": {
+                description: "Difference in percentage points between the load of the busiest and \
+                    the least busy node of a cluster above which the cluster is imbalanced.",
+                type: Integer,
+                optional: true,
+                minimum: 1,
+                maximum: 100,
+                default: 30,
+            },
+        },
+    },
+    returns: {
+        type: Array,
+        description: "The recommended migrations.",
+        items: { type: BalancingRecommendation },
+    },
+    access: {
+        permission: &Permission::Anybody,
+        description: "Only remotes with Resource.Audit on /resource/{remote} are considered, both \
+            as source and as target of migrations.",
+    },
+)]
+/// Recommend guest migrations to relieve overloaded nodes and balance clusters.
+///
+/// The recommendations are based on the average CPU and memory usage over the timeframe and are
+/// advisory only. They can be applied with the regular migrate API calls.
+fn list_recommendations(
+    timeframe: Option<RrdTimeframe>,
+    view: Option<String>,
+    cpu_threshold: Option<u64>,
+    memory_threshold: Option<u64>,
+    imbalance_threshold: Option<u64>,
+    rpcenv: &mut dyn RpcEnvironment,
+) -> Result<Vec<BalancingRecommendation>, Error> {
+    let user_info = CachedUserInfo::new()?;
+    let auth_id: Authid = rpcenv
+        .get_auth_id()
+        .context("no authid available")?
+        .parse()?;
+
+    if let Some(view) = &view {
+        user_info.check_privs(&auth_id, &["view", view], PRIV_RESOURCE_AUDIT, false)?;
+    } else if !user_info.any_privs_below(&auth_id, &["resource"], PRIV_RESOURCE_AUDIT)? {
+        http_bail!(FORBIDDEN, "user has no access to resources");
+    }
+
+    let view = views::get_optional_view(view.as_deref())?;
+
+    let (remotes_config, _) = pdm_config::remotes::config()?;
+
+    let check_remote_privs = |remote_name: &str| {
+        if let Some(view) = &view {
+            !view.can_skip_remote(remote_name)
+        } else {
+            user_info.lookup_privs(&auth_id, &["resource", remote_name]) & PRIV_RESOURCE_AUDIT != 0
+        }
+    };
+
+    let is_resource_included = |remote: &str, resource: &Resource| {
+        if let Some(view) = &view {
+            view.resource_matches(remote, resource)
+        } else {
+            true
+        }
+    };
+
+    let defaults = Thresholds::default();
+    let to_ratio = |percent: Option<u64>, default: f64| {
+        percent
+            .map(|percent| percent as f64 / 100.0)
+            .unwrap_or(default)
+    };
+    let thresholds = Thresholds {
+        cpu: to_ratio(cpu_threshold, defaults.cpu),
+        mem: to_ratio(memory_threshold, defaults.mem),
+        imbalance: to_ratio(imbalance_threshold, defaults.imbalance),
+    };
+
+    Ok(balancing::calculate_recommendations(
+        &remotes_config,
+        timeframe.unwrap_or(RrdTimeframe::Day),
+        thresholds,
+        check_remote_privs,
+        is_resource_included,
+    ))
+}
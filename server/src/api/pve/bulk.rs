@@ -0,0 +1,541 @@
+//! Apply power actions to many guests across PVE remotes at once.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::sync::Arc;
+
+use anyhow::{Context, Error, bail};
+
+use proxmox_rest_server::WorkerTask;
+use proxmox_router::{Permission, Router, RpcEnvironment, http_bail};
+use proxmox_schema::api;
+
+use pdm_api_types::remotes::{Remote, RemoteType};
+use pdm_api_types::resource::{BulkGuestActionParams, GuestAction, GuestType, Resource};
+use pdm_api_types::{Authid, PRIV_RESOURCE_MANAGE, RemoteUpid, UPID};
+
+use crate::acl::CachedUserInfo;
+use crate::connection::PveClient;
+use crate::parallel_fetcher::ParallelFetcher;
+use crate::remote_tasks::wait_for_pve_task;
+
+use super::{connect, new_remote_upid};
+
+pub const ROUTER: Router = Router::new().post(&API_METHOD_BULK_GUEST_ACTION);
+
+/// Maximum age of the cached resources used to look up the guests.
+const RESOURCE_MAX_AGE: u64 = 30;
+
+/// Default for the maximum number of parallel requests to all remotes.
+const DEFAULT_MAX_CONNECTIONS: usize = 10;
+/// Default for the maximum number of parallel requests to a single remote.
+const DEFAULT_MAX_CONNECTIONS_PER_REMOTE: usize = 2;
+
+#[derive(Clone)]
+pub(crate) struct BulkGuest {
+    pub id: String,
+    pub vmid: u32,
+    pub ty: GuestType,
+}
+
+/// Guests grouped by remote and node.
+pub(crate) type GuestsByNode = BTreeMap<(String, String), Vec<BulkGuest>>;
+
+/// An action applied to many guests at once.
+pub(crate) enum BulkAction {
+    Power(GuestAction),
+    Snapshot {
+        name: String,
+        /// Delete older snapshots once the snapshot was taken.
+        retention: Option<SnapshotRetention>,
+    },
+}
+
+/// Keep only the `keep` most recent snapshots named by [`snapshot_name`] with `prefix`.
+///
+/// Other snapshots, e.g. taken manually, are never deleted.
+pub(crate) struct SnapshotRetention {
+    pub prefix: String,
+    pub keep: usize,
+}
+
+impl SnapshotRetention {
+    /// Whether the snapshot `name` was named by [`snapshot_name`] with the prefix.
+    fn matches(&self, name: &str) -> bool {
+        name.strip_prefix(&self.prefix).is_some_and(|time| {
+            time.len() == SNAPSHOT_TIME_LENGTH && time.bytes().all(|b| b.is_ascii_digit())
+        })
+    }
+}
+
+/// The length of the time in snapshot names, see [`snapshot_name`].
+const SNAPSHOT_TIME_LENGTH: usize = 14;
+
+/// The name of a snapshot taken at `time`, `prefix` identifies who took it.
+///
+/// The time is in UTC, so that the names sort in the order the snapshots were taken, also across
+/// DST changes.
+pub(crate) fn snapshot_name(prefix: &str, time: i64) -> Result<String, Error> {
+    Ok(format!(
+        "{prefix}{}",
+        proxmox_time::strftime_utc("%Y%m%d%H%M%S", time)?
+    ))
+}
+
+impl std::fmt::Display for BulkAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BulkAction::Power(action) => f.write_str(action.as_str()),
+            BulkAction::Snapshot { .. } => f.write_str("snapshot"),
+        }
+    }
+}
+
+struct BulkContext {
+    action: BulkAction,
+    guests: GuestsByNode,
+    /// Wait for the remote tasks to finish, so the outcome includes their exit status.
+    wait: bool,
+}
+
+type GuestResults = Vec<(String, Result<RemoteUpid, Error>)>;
+
+#[api(
+    input: {
+        properties: {
+            params: {
+                type: BulkGuestActionParams,
+                flatten: true,
+            },
+        },
+    },
+    access: {
+        permission: &Permission::Anybody,
+        description: "Resource.Manage privileges are needed on /resource/{remote}/guest/{vmid} \
+            for every affected guest.",
+    },
+    returns: { type: UPID },
+)]
+/// Apply a power action to a list of guests, or to all guests matching a search.
+///
+/// Every guest action is tracked as remote task, the returned worker task reports the outcome
+/// for each guest.
+async fn bulk_guest_action(
+    params: BulkGuestActionParams,
+    rpcenv: &mut dyn RpcEnvironment,
+) -> Result<UPID, Error> {
+    let auth_id: Authid = rpcenv
+        .get_auth_id()
+        .context("no authid available")?
+        .parse()?;
+
+    if params.guests.is_none() && params.search.is_none() {
+        http_bail!(BAD_REQUEST, "either 'guests' or 'search' has to be set");
+    }
+
+    let mut requested: BTreeSet<String> = params.guests.unwrap_or_default().into_iter().collect();
+    let has_guest_list = !requested.is_empty();
+
+    let remote_resources = crate::api::resources::get_resources_impl(
+        RESOURCE_MAX_AGE,
+        params.search,
+        None,
+        None,
+        Some(rpcenv),
+    )
+    .await?;
+
+    let user_info = CachedUserInfo::new()?;
+    let mut guests: BTreeMap<(String, String), Vec<BulkGuest>> = BTreeMap::new();
+    let mut guest_count = 0;
+
+    for remote in remote_resources {
+        let remote: pdm_api_types::resource::RemoteResources = remote.into();
+        for resource in remote.resources {
+            let (id, node, vmid, ty) = match resource {
+                Resource::PveQemu(r) => (r.id, r.node, r.vmid, GuestType::Qemu),
+                Resource::PveLxc(r) => (r.id, r.node, r.vmid, GuestType::Lxc),
+                _ => continue,
+            };
+
+            if has_guest_list && !requested.remove(&id) {
+                continue;
+            }
+
+            let vmid_str = vmid.to_string();
+            if user_info
+                .check_privs(
+                    &auth_id,
+                    &["resource", &remote.remote, "guest", &vmid_str],
+                    PRIV_RESOURCE_MANAGE,
+                    false,
+                )
+                .is_err()
+            {
+                http_bail!(FORBIDDEN, "missing permissions for guest '{id}'");
+            }
+
+            guests
+                .entry((remote.remote.clone(), node))
+                .or_default()
+                .push(BulkGuest { id, vmid, ty });
+            guest_count += 1;
+        }
+    }
+
+    // whatever is left could not be found in the (permitted) resources
+    let missing: Vec<String> = requested.into_iter().collect();
+
+    if guest_count == 0 && missing.is_empty() {
+        http_bail!(BAD_REQUEST, "no guests matched");
+    }
+
+    let action = BulkAction::Power(params.action);
+    let max_connections = params.max_connections.unwrap_or(DEFAULT_MAX_CONNECTIONS);
+    let max_connections_per_remote = params
+        .max_connections_per_remote
+        .unwrap_or(DEFAULT_MAX_CONNECTIONS_PER_REMOTE);
+
+    let upid_str = WorkerTask::spawn(
+        "bulk-guest-action",
+        Some(params.action.to_string()),
+        auth_id.to_string(),
+        true,
+        move |_worker| async move {
+            run_bulk_action(
+                action,
+                guests,
+                missing,
+                false,
+                max_connections,
+                max_connections_per_remote,
+            )
+            .await
+        },
+    )?;
+
+    upid_str.parse()
+}
+
+/// Apply an action to the guests and log the outcome for every guest, including the `missing`
+/// ones which could not be found.
+///
+/// With `wait`, the remote tasks are awaited and a guest only counts as successful if its task
+/// finished successfully, otherwise it is enough that the task was started. A snapshot retention
+/// is only applied when waiting.
+///
+/// Meant to be run in a worker task, fails if the action failed for any guest.
+pub(crate) async fn run_bulk_action(
+    action: BulkAction,
+    guests: GuestsByNode,
+    missing: Vec<String>,
+    wait: bool,
+    max_connections: usize,
+    max_connections_per_remote: usize,
+) -> Result<(), Error> {
+    let guest_count: usize = guests.values().map(Vec::len).sum();
+
+    let (remotes_config, _) = pdm_config::remotes::config()?;
+    let remotes: Vec<Remote> = remotes_config
+        .into_iter()
+        .filter(|(name, remote)| {
+            remote.ty == RemoteType::Pve && guests.keys().any(|(remote, _)| remote == name)
+        })
+        .map(|(_, remote)| remote)
+        .collect();
+
+    let context = Arc::new(BulkContext {
+        action,
+        guests,
+        wait,
+    });
+
+    let fetcher = ParallelFetcher::builder(Arc::clone(&context))
+        .max_connections(max_connections)
+        .max_connections_per_remote(max_connections_per_remote)
+        .build();
+
+    log::info!(
+        "applying '{}' to {guest_count} guest(s) on {} remote(s)",
+        context.action,
+        remotes.len(),
+    );
+
+    let response = fetcher
+        .do_for_all_remote_nodes(remotes.into_iter(), apply_action_on_node)
+        .await;
+
+    let mut results: HashMap<String, Result<RemoteUpid, Error>> = HashMap::new();
+    for remote_response in response {
+        let (remote, node_responses) = remote_response.into_remote_and_nodes();
+        let node_responses = match node_responses {
+            Ok(node_responses) => node_responses,
+            Err(err) => {
+                log::error!("could not connect to remote '{remote}': {err:#}");
+                continue;
+            }
+        };
+
+        for node_response in node_responses {
+            let node = node_response.node_name().to_string();
+            match node_response.into_data() {
+                Ok(data) => results.extend(data),
+                Err(err) => {
+                    log::error!("could not connect to node '{node}' of '{remote}': {err:#}");
+                }
+            }
+        }
+    }
+
+    let mut failed = 0;
+    for guest in context.guests.values().flatten() {
+        match results.remove(&guest.id) {
+            Some(Ok(upid)) => log::info!("{}: OK - {upid}", guest.id),
+            Some(Err(err)) => {
+                log::error!("{}: failed - {err:#}", guest.id);
+                failed += 1;
+            }
+            None => {
+                log::error!("{}: failed - node of the guest not reachable", guest.id);
+                failed += 1;
+            }
+        }
+    }
+
+    for id in &missing {
+        log::error!("{id}: failed - no such guest");
+        failed += 1;
+    }
+
+    let total = guest_count + missing.len();
+    if failed > 0 {
+        bail!(
+            "'{}' failed for {failed} of {total} guest(s)",
+            context.action
+        );
+    }
+
+    log::info!("'{}' succeeded for all {total} guest(s)", context.action);
+
+    Ok(())
+}
+
+async fn apply_action_on_node(
+    context: Arc<BulkContext>,
+    remote: Remote,
+    node: String,
+) -> Result<GuestResults, Error> {
+    let Some(guests) = context.guests.get(&(remote.id.clone(), node.clone())) else {
+        return Ok(Vec::new());
+    };
+
+    let pve = connect(&remote)?;
+
+    let mut started = Vec::with_capacity(guests.len());
+    for guest in guests {
+        let result = apply_action(&pve, &remote.id, &node, guest, &context.action).await;
+        started.push((guest, result));
+    }
+
+    if !context.wait {
+        return Ok(started
+            .into_iter()
+            .map(|(guest, result)| (guest.id.clone(), result))
+            .collect());
+    }
+
+    // the tasks were all started already, so wait for them concurrently
+    let (pve, remote, node) = (&pve, &remote.id, &node);
+    let action = &context.action;
+    let results =
+        futures::future::join_all(started.into_iter().map(|(guest, result)| async move {
+            let result = match result {
+                Ok(upid) => finish_action(pve, remote, node, guest, action, upid).await,
+                Err(err) => Err(err),
+            };
+            (guest.id.clone(), result)
+        }))
+        .await;
+
+    Ok(results)
+}
+
+/// Wait for the task of an action and apply the snapshot retention once it finished.
+async fn finish_action(
+    pve: &PveClient,
+    remote: &str,
+    node: &str,
+    guest: &BulkGuest,
+    action: &BulkAction,
+    upid: RemoteUpid,
+) -> Result<RemoteUpid, Error> {
+    wait_for_pve_task(pve, &upid).await?;
+
+    if let BulkAction::Snapshot {
+        retention: Some(retention),
+        ..
+    } = action
+    {
+        prune_snapshots(pve, remote, node, guest, retention)
+            .await
+            .context("snapshot taken, but deleting older snapshots failed")?;
+    }
+
+    Ok(upid)
+}
+
+/// The snapshots to delete to keep only the `keep` most recent of `names`.
+fn snapshots_to_delete(mut names: Vec<String>, keep: usize) -> Vec<String> {
+    names.sort();
+    let count = names.len().saturating_sub(keep);
+    names.truncate(count);
+    names
+}
+
+async fn prune_snapshots(
+    pve: &PveClient,
+    remote: &str,
+    node: &str,
+    guest: &BulkGuest,
+    retention: &SnapshotRetention,
+) -> Result<(), Error> {
+    let vmid = guest.vmid;
+    let names: Vec<String> = match guest.ty {
+        GuestType::Qemu => pve
+            .qemu_list_snapshots(node, vmid)
+            .await?
+            .into_iter()
+            .map(|snapshot| snapshot.name)
+            .collect(),
+        GuestType::Lxc => pve
+            .lxc_list_snapshots(node, vmid)
+            .await?
+            .into_iter()
+            .map(|snapshot| snapshot.name)
+            .collect(),
+    };
+    let names = names
+        .into_iter()
+        .filter(|name| retention.matches(name))
+        .collect();
+
+    for name in snapshots_to_delete(names, retention.keep) {
+        let upid = match guest.ty {
+            GuestType::Qemu => {
+                pve.delete_qemu_snapshot(node, vmid, &name, Default::default())
+                    .await?
+            }
+            GuestType::Lxc => {
+                pve.delete_lxc_snapshot(node, vmid, &name, Default::default())
+                    .await?
+            }
+        };
+        let upid = new_remote_upid(remote.to_string(), upid).await?;
+        wait_for_pve_task(pve, &upid).await?;
+    }
+
+    Ok(())
+}
+
+async fn apply_action(
+    pve: &PveClient,
+    remote: &str,
+    node: &str,
+    guest: &BulkGuest,
+    action: &BulkAction,
+) -> Result<RemoteUpid, Error> {
+    let vmid = guest.vmid;
+
+    let action = match action {
+        BulkAction::Power(action) => *action,
+        BulkAction::Snapshot { name, .. } => {
+            let upid = match guest.ty {
+                GuestType::Qemu => {
+                    let params = pve_api_types::CreateQemuSnapshot {
+                        snapname: name.clone(),
+                        description: None,
+                        vmstate: None,
+                    };
+                    pve.snapshot_qemu(node, vmid, params).await?
+                }
+                GuestType::Lxc => {
+                    let params = pve_api_types::CreateLxcSnapshot {
+                        snapname: name.clone(),
+                        description: None,
+                    };
+                    pve.snapshot_lxc(node, vmid, params).await?
+                }
+            };
+            return new_remote_upid(remote.to_string(), upid).await;
+        }
+    };
+
+    let upid = match (guest.ty, action) {
+        (GuestType::Qemu, GuestAction::Start) => {
+            pve.start_qemu_async(node, vmid, Default::default()).await?
+        }
+        (GuestType::Qemu, GuestAction::Stop) => {
+            pve.stop_qemu_async(node, vmid, Default::default()).await?
+        }
+        (GuestType::Qemu, GuestAction::Shutdown) => {
+            pve.shutdown_qemu_async(node, vmid, Default::default())
+                .await?
+        }
+        (GuestType::Qemu, GuestAction::Resume) => {
+            pve.resume_qemu_async(node, vmid, Default::default())
+                .await?
+        }
+        (GuestType::Lxc, GuestAction::Start) => {
+            pve.start_lxc_async(node, vmid, Default::default()).await?
+        }
+        (GuestType::Lxc, GuestAction::Stop) => {
+            pve.stop_lxc_async(node, vmid, Default::default()).await?
+        }
+        (GuestType::Lxc, GuestAction::Shutdown) => {
+            pve.shutdown_lxc_async(node, vmid, Default::default())
+                .await?
+        }
+        (GuestType::Lxc, GuestAction::Resume) => pve.resume_lxc_async(node, vmid).await?,
+    };
+
+    new_remote_upid(remote.to_string(), upid).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{SnapshotRetention, snapshot_name, snapshots_to_delete};
+
+    #[test]
+    fn snapshot_retention() {
+        let retention = SnapshotRetention {
+            prefix: "pdm_daily_".to_string(),
+            keep: 2,
+        };
+        assert!(retention.matches("pdm_daily_20260101000000"));
+        // snapshots of other jobs and manual ones are kept
+        assert!(!retention.matches("pdm_daily_x_20260101000000"));
+        assert!(!retention.matches("pdm_daily_before-upgrade"));
+        assert!(!retention.matches("pdm_20260101000000"));
+
+        assert_eq!(
+            snapshot_name("pdm_daily_", 1_767_225_600).unwrap(),
+            "pdm_daily_20260101000000"
+        );
+
+        let names = vec![
+            "pdm_daily_20260103000000".to_string(),
+            "pdm_daily_20260101000000".to_string(),
+            "pdm_daily_20260102000000".to_string(),
+        ];
+
+        assert_eq!(
+            snapshots_to_delete(names.clone(), 2),
+            ["pdm_daily_20260101000000"]
+        );
+        assert_eq!(
+            snapshots_to_delete(names.clone(), 1),
+            ["pdm_daily_20260101000000", "pdm_daily_20260102000000"]
+        );
+        assert!(snapshots_to_delete(names.clone(), 3).is_empty());
+        assert!(snapshots_to_delete(names, 5).is_empty());
+    }
+}
@@ -0,0 +1,286 @@
+//! Deploy new guests from templates.
+
+use anyhow::{Context, Error, bail};
+use serde_json::{Value, json};
+
+use proxmox_client::HttpApiClient;
+use proxmox_rest_server::WorkerTask;
+use proxmox_router::{Permission, Router, RpcEnvironment, http_bail, http_err};
+use proxmox_schema::api;
+
+use pdm_api_types::pve::DeployTemplateParams;
+use pdm_api_types::remotes::REMOTE_ID_SCHEMA;
+use pdm_api_types::resource::GuestType;
+use pdm_api_types::{
+    Authid, PRIV_RESOURCE_AUDIT, PRIV_RESOURCE_CREATE, PRIV_RESOURCE_MANAGE, UPID,
+};
+use pve_api_types::{ClusterResourceKind, ClusterResourceType};
+
+use crate::acl::CachedUserInfo;
+use crate::connection;
+use crate::remote_tasks::wait_for_pve_task;
+
+use super::{get_remote, new_remote_upid};
+
+pub const ROUTER: Router = Router::new().post(&API_METHOD_DEPLOY_TEMPLATE);
+
+#[api(
+    input: {
+        properties: {
+            remote: { schema: REMOTE_ID_SCHEMA },
+            params: {
+                type: DeployTemplateParams,
+                flatten: true,
+            },
+        },
+    },
+    returns: { type: UPID },
+    access: {
+        permission: &Permission::Privilege(&["resource", "{remote}"], PRIV_RESOURCE_CREATE, false),
+        description: "Additionally requires Resource.Audit on /resource/{remote}/guest/{template} \
+            and, to start the guest, Resource.Manage on /resource/{remote}/guest/{newid}.",
+    },
+)]
+/// Deploy a new guest by cloning a template.
+///
+/// The returned worker task clones the template, applies the cloud-init options and starts the
+/// guest if requested. The clone and start tasks on the remote are tracked as remote tasks.
+async fn deploy_template(
+    remote: String,
+    params: DeployTemplateParams,
+    rpcenv: &mut dyn RpcEnvironment,
+) -> Result<UPID, Error> {
+    let auth_id: Authid = rpcenv
+        .get_auth_id()
+        .context("no authid available")?
+        .parse()?;
+    let user_info = CachedUserInfo::new()?;
+
+    let template = params.template;
+    user_info.check_privs(
+        &auth_id,
+        &["resource", &remote, "guest", &template.to_string()],
+        PRIV_RESOURCE_AUDIT,
+        false,
+    )?;
+
+    let (remotes, _) = pdm_config::remotes::config()?;
+    let remote_config = get_remote(&remotes, &remote)?.clone();
+    let pve = connection::make_pve_client(&remote_config)?;
+
+    let resource = pve
+        .cluster_resources(Some(ClusterResourceKind::Vm))
+        .await?
+        .into_iter()
+        .find(|resource| resource.vmid == Some(template))
+        .ok_or_else(|| http_err!(NOT_FOUND, "no such guest '{template}'"))?;
+
+    if !resource.template.unwrap_or_default() {
+        http_bail!(BAD_REQUEST, "guest '{template}' is not a template");
+    }
+    let guest_type = match resource.ty {
+        ClusterResourceType::Qemu => GuestType::Qemu,
+        ClusterResourceType::Lxc => GuestType::Lxc,
+        _ => http_bail!(BAD_REQUEST, "guest '{template}' is not a template"),
+    };
+    if guest_type == GuestType::Lxc && params.has_cloud_init() {
+        http_bail!(BAD_REQUEST, "cloud-init options are only supported for VMs");
+    }
+
+    let node = resource.node.unwrap_or_default();
+    let target = params.target.clone().unwrap_or_else(|| node.clone());
+
+    let newid = match params.newid {
+        Some(newid) => newid,
+        None => pve.cluster_nextid(None).await?.into(),
+    };
+
+    if params.start {
+        user_info.check_privs(
+            &auth_id,
+            &["resource", &remote, "guest", &newid.to_string()],
+            PRIV_RESOURCE_MANAGE,
+            false,
+        )?;
+    }
+
+    let upid_str = WorkerTask::spawn(
+        "deploy-template",
+        Some(format!("{remote}:{template}")),
+        auth_id.to_string(),
+        true,
+        move |_worker| async move {
+            let client = connection::make_raw_client(&remote_config)?;
+            let ty = match guest_type {
+                GuestType::Qemu => "qemu",
+                GuestType::Lxc => "lxc",
+            };
+
+            let clone_params = clone_params(&params, guest_type, newid, &target);
+
+            let upid: String = client
+                .post(
+                    &format!("/api2/extjs/nodes/{node}/{ty}/{template}/clone"),
+                    &clone_params,
+                )
+                .await?
+                .expect_json()?
+                .data;
+            let upid = new_remote_upid(remote.clone(), upid.parse()?).await?;
+            log::info!("cloning template {template} to {newid} on node '{target}' - {upid}");
+            wait_for_pve_task(pve.as_ref(), &upid).await?;
+
+            if params.has_cloud_init() {
+                let config = cloud_init_config(&params);
+
+                client
+                    .put(
+                        &format!("/api2/extjs/nodes/{target}/qemu/{newid}/config"),
+                        &config,
+                    )
+                    .await?
+                    .nodata()?;
+                log::info!("applied cloud-init configuration");
+            }
+
+            if params.start {
+                let upid = match guest_type {
+                    GuestType::Qemu => {
+                        pve.start_qemu_async(&target, newid, Default::default())
+                            .await?
+                    }
+                    GuestType::Lxc => {
+                        pve.start_lxc_async(&target, newid, Default::default())
+                            .await?
+                    }
+                };
+                let upid = new_remote_upid(remote.clone(), upid).await?;
+                log::info!("starting guest {newid} - {upid}");
+                if let Err(err) = wait_for_pve_task(pve.as_ref(), &upid).await {
+                    bail!("guest {newid} was deployed but could not be started - {err:#}");
+                }
+            }
+
+            log::info!("deployed guest {newid} from template {template}");
+
+            Ok(())
+        },
+    )?;
+
+    upid_str.parse()
+}
+
+/// Build the parameters for the clone API call of the template.
+fn clone_params(
+    params: &DeployTemplateParams,
+    guest_type: GuestType,
+    newid: u32,
+    target: &str,
+) -> Value {
+    let mut clone_params = json!({
+        "newid": newid,
+        "target": target,
+        "full": params.full,
+    });
+    if let Some(name) = &params.name {
+        let key = match guest_type {
+            GuestType::Qemu => "name",
+            GuestType::Lxc => "hostname",
+        };
+        clone_params[key] = name.clone().into();
+    }
+    if let Some(storage) = &params.storage {
+        clone_params["storage"] = storage.clone().into();
+    }
+    clone_params
+}
+
+/// Build the cloud-init configuration update for the new VM.
+fn cloud_init_config(params: &DeployTemplateParams) -> Value {
+    let mut config = json!({});
+    if let Some(ciuser) = &params.ciuser {
+        config["ciuser"] = ciuser.clone().into();
+    }
+    if let Some(sshkeys) = &params.sshkeys {
+        // PVE expects the keys URI encoded
+        config["sshkeys"] =
+            percent_encoding::utf8_percent_encode(sshkeys, percent_encoding::NON_ALPHANUMERIC)
+                .to_string()
+                .into();
+    }
+    if let Some(ipconfig) = &params.ipconfig {
+        config["ipconfig0"] = ipconfig.clone().into();
+    }
+    config
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use pdm_api_types::pve::DeployTemplateParams;
+    use pdm_api_types::resource::GuestType;
+
+    use super::{clone_params, cloud_init_config};
+
+    #[test]
+    fn clone_parameters() {
+        let mut params = DeployTemplateParams {
+            template: 9000,
+            ..Default::default()
+        };
+        assert_eq!(
+            clone_params(&params, GuestType::Qemu, 100, "node1"),
+            json!({ "newid": 100, "target": "node1", "full": false }),
+        );
+
+        params.name = Some("web1".into());
+        params.storage = Some("local-lvm".into());
+        params.full = true;
+        assert_eq!(
+            clone_params(&params, GuestType::Qemu, 100, "node1"),
+            json!({
+                "newid": 100,
+                "target": "node1",
+                "full": true,
+                "name": "web1",
+                "storage": "local-lvm",
+            }),
+        );
+        // containers are named by their hostname
+        assert_eq!(
+            clone_params(&params, GuestType::Lxc, 101, "node2"),
+            json!({
+                "newid": 101,
+                "target": "node2",
+                "full": true,
+                "hostname": "web1",
+                "storage": "local-lvm",
+            }),
+        );
+    }
+
+    #[test]
+    fn cloud_init() {
+        let mut params = DeployTemplateParams {
+            template: 9000,
+            name: Some("web1".into()),
+            ..Default::default()
+        };
+        assert!(!params.has_cloud_init());
+        assert_eq!(cloud_init_config(&params), json!({}));
+
+        params.ciuser = Some("admin".into());
+        params.sshkeys = Some("ssh-ed25519 AAAA user@host".into());
+        params.ipconfig = Some("ip=dhcp".into());
+        assert!(params.has_cloud_init());
+        assert_eq!(
+            cloud_init_config(&params),
+            json!({
+                "ciuser": "admin",
+                "sshkeys": "ssh%2Ded25519%20AAAA%20user%40host",
+                "ipconfig0": "ip=dhcp",
+            }),
+        );
+    }
+}
@@ -0,0 +1,546 @@
+//! Move all guests off a PVE node, for example before hardware maintenance.
+
+use std::cmp::Ordering;
+use std::collections::HashSet;
+
+use anyhow::{Context, Error, bail};
+use futures::StreamExt;
+
+use proxmox_rest_server::WorkerTask;
+use proxmox_router::{Permission, Router, RpcEnvironment, http_bail};
+use proxmox_schema::api;
+
+use pdm_api_types::pve::{
+    EvacuateNodeParams, EvacuationMigration, EvacuationNodeLoad, EvacuationPlan,
+};
+use pdm_api_types::remotes::REMOTE_ID_SCHEMA;
+use pdm_api_types::resource::{GuestType, PveNodeResource, PveResource};
+use pdm_api_types::{
+    Authid, NODE_SCHEMA, PRIV_RESOURCE_AUDIT, PRIV_RESOURCE_MIGRATE, RemoteUpid, UPID,
+};
+
+use crate::acl::CachedUserInfo;
+use crate::connection::PveClient;
+use crate::remote_tasks::wait_for_pve_task;
+
+use super::{connect_to_remote_by_id, map_pve_resource, new_remote_upid};
+
+pub const ROUTER: Router = Router::new()
+    .get(&API_METHOD_GET_EVACUATION_PLAN)
+    .post(&API_METHOD_EVACUATE_NODE);
+
+/// Default for the number of migrations running at the same time.
+const DEFAULT_MAX_PARALLEL: usize = 1;
+/// Default for how often a failed migration is retried.
+const DEFAULT_RETRIES: u8 = 1;
+
+/// A guest as seen by the placement planner.
+struct PlanGuest {
+    vmid: u32,
+    guest_type: GuestType,
+    /// CPU usage in number of CPUs.
+    cpu: f64,
+    mem: u64,
+}
+
+impl PlanGuest {
+    fn from_resource(resource: &PveResource) -> Option<Self> {
+        let (vmid, guest_type, cpu, maxcpu, mem, running) = match resource {
+            PveResource::Qemu(qemu) => (
+                qemu.vmid,
+                GuestType::Qemu,
+                qemu.cpu,
+                qemu.maxcpu,
+                qemu.mem,
+                qemu.status == "running",
+            ),
+            PveResource::Lxc(lxc) => (
+                lxc.vmid,
+                GuestType::Lxc,
+                lxc.cpu,
+                lxc.maxcpu,
+                lxc.mem,
+                lxc.status == "running",
+            ),
+            _ => return None,
+        };
+
+        // stopped guests do not add any load to their target
+        let (cpu, mem) = if running {
+            (cpu * maxcpu, mem)
+        } else {
+            (0.0, 0)
+        };
+
+        Some(Self {
+            vmid,
+            guest_type,
+            cpu,
+            mem,
+        })
+    }
+}
+
+/// The projected load of a target node while planning.
+struct TargetLoad {
+    node: String,
+    /// CPU usage in number of CPUs.
+    cpu: f64,
+    maxcpu: f64,
+    mem: u64,
+    maxmem: u64,
+}
+
+impl TargetLoad {
+    /// The higher of the projected CPU and memory usage ratios with `guest` added.
+    fn score_with(&self, guest: &PlanGuest) -> f64 {
+        let mem = (self.mem + guest.mem) as f64 / self.maxmem.max(1) as f64;
+        let cpu = (self.cpu + guest.cpu) / self.maxcpu.max(1.0);
+        mem.max(cpu)
+    }
+
+    fn fits(&self, guest: &PlanGuest) -> bool {
+        self.mem + guest.mem <= self.maxmem
+    }
+}
+
+/// Compute a placement plan for the guests of `node`.
+///
+/// Guests are placed largest first onto the online node with the lowest projected CPU or memory
+/// usage. Guests whose memory does not fit on any node are left unplaced.
+fn plan_evacuation(
+    remote: &str,
+    node: &str,
+    nodes: &[PveNodeResource],
+    mut guests: Vec<PlanGuest>,
+) -> EvacuationPlan {
+    let mut targets: Vec<TargetLoad> = nodes
+        .iter()
+        .filter(|target| target.node != node && target.status == "online")
+        .map(|target| TargetLoad {
+            node: target.node.clone(),
+            cpu: target.cpu * target.maxcpu,
+            maxcpu: target.maxcpu,
+            mem: target.mem,
+            maxmem: target.maxmem,
+        })
+        .collect();
+
+    guests.sort_by(|a, b| {
+        b.mem
+            .cmp(&a.mem)
+            .then_with(|| b.cpu.partial_cmp(&a.cpu).unwrap_or(Ordering::Equal))
+            .then_with(|| a.vmid.cmp(&b.vmid))
+    });
+
+    let mut migrations = Vec::new();
+    let mut unplaced = Vec::new();
+
+    for guest in guests {
+        let target = targets
+            .iter_mut()
+            .filter(|target| target.fits(&guest))
+            .min_by(|a, b| {
+                a.score_with(&guest)
+                    .partial_cmp(&b.score_with(&guest))
+                    .unwrap_or(Ordering::Equal)
+            });
+
+        match target {
+            Some(target) => {
+                target.cpu += guest.cpu;
+                target.mem += guest.mem;
+                migrations.push(EvacuationMigration {
+                    vmid: guest.vmid,
+                    guest_type: guest.guest_type,
+                    target: target.node.clone(),
+                });
+            }
+            None => unplaced.push(guest.vmid),
+        }
+    }
+
+    EvacuationPlan {
+        remote: remote.to_string(),
+        node: node.to_string(),
+        migrations,
+        unplaced,
+        targets: targets
+            .into_iter()
+            .map(|target| EvacuationNodeLoad {
+                node: target.node,
+                cpu: target.cpu / target.maxcpu.max(1.0),
+                maxcpu: target.maxcpu,
+                mem: target.mem,
+                maxmem: target.maxmem,
+            })
+            .collect(),
+    }
+}
+
+async fn compute_plan(
+    pve: &PveClient,
+    remote: &str,
+    node: &str,
+) -> Result<(EvacuationPlan, Vec<PveNodeResource>), Error> {
+    let resources: Vec<PveResource> = pve
+        .cluster_resources(None)
+        .await?
+        .into_iter()
+        .filter_map(|resource| map_pve_resource(remote, resource))
+        .collect();
+
+    let mut nodes = Vec::new();
+    let mut guests = Vec::new();
+    for resource in &resources {
+        match resource {
+            PveResource::Node(n) => nodes.push(n.clone()),
+            PveResource::Qemu(qemu) if qemu.node == node => {
+                guests.extend(PlanGuest::from_resource(resource))
+            }
+            PveResource::Lxc(lxc) if lxc.node == node => {
+                guests.extend(PlanGuest::from_resource(resource))
+            }
+            _ => {}
+        }
+    }
+
+    if !nodes.iter().any(|n| n.node == node) {
+        http_bail!(NOT_FOUND, "no such node '{node}'");
+    }
+
+    Ok((plan_evacuation(remote, node, &nodes, guests), nodes))
+}
+
+#[api(
+    input: {
+        properties: {
+            remote: { schema: REMOTE_ID_SCHEMA },
+            node: { schema: NODE_SCHEMA },
+        },
+    },
+    returns: { type: EvacuationPlan },
+    access: {
+        permission: &Permission::Privilege(&["resource", "{remote}", "node", "{node}"], PRIV_RESOURCE_AUDIT, false),
+    },
+)]
+/// Compute a plan for migrating all guests off a node.
+///
+/// The plan is based on the current CPU and memory usage of the nodes and guests and can be
+/// edited before passing it to the evacuation.
+async fn get_evacuation_plan(remote: String, node: String) -> Result<EvacuationPlan, Error> {
+    let pve = connect_to_remote_by_id(&remote)?;
+    let (plan, _) = compute_plan(pve.as_ref(), &remote, &node).await?;
+    Ok(plan)
+}
+
+#[api(
+    input: {
+        properties: {
+            remote: { schema: REMOTE_ID_SCHEMA },
+            node: { schema: NODE_SCHEMA },
+            params: {
+                type: EvacuateNodeParams,
+                flatten: true,
+            },
+        },
+    },
+    returns: { type: UPID },
+    access: {
+        permission: &Permission::Privilege(&["resource", "{remote}", "node", "{node}"], PRIV_RESOURCE_AUDIT | PRIV_RESOURCE_MIGRATE, false),
+        description: "Additionally requires Resource.Migrate on /resource/{remote}/guest/{vmid} \
+            for every migrated guest.",
+    },
+)]
+/// Migrate all guests off a node.
+///
+/// Runs the given migrations, or the computed plan, as a queue. Every migration is tracked as a
+/// remote task, failed migrations are retried and skipped once all retries failed. The returned
+/// worker task summarizes the outcome.
+async fn evacuate_node(
+    remote: String,
+    node: String,
+    params: EvacuateNodeParams,
+    rpcenv: &mut dyn RpcEnvironment,
+) -> Result<UPID, Error> {
+    let auth_id: Authid = rpcenv
+        .get_auth_id()
+        .context("no authid available")?
+        .parse()?;
+
+    let pve = connect_to_remote_by_id(&remote)?;
+    let (plan, nodes) = compute_plan(pve.as_ref(), &remote, &node).await?;
+
+    let migrations = match params.migrations {
+        Some(migrations) => {
+            let on_node: HashSet<u32> = plan
+                .migrations
+                .iter()
+                .map(|m| m.vmid)
+                .chain(plan.unplaced.iter().copied())
+                .collect();
+            let mut seen = HashSet::new();
+            let mut list = Vec::new();
+            for migration in migrations {
+                let migration = migration.into_inner();
+                if !on_node.contains(&migration.vmid) {
+                    http_bail!(
+                        BAD_REQUEST,
+                        "guest {} is not on node '{node}'",
+                        migration.vmid
+                    );
+                }
+                if !seen.insert(migration.vmid) {
+                    http_bail!(
+                        BAD_REQUEST,
+                        "guest {} is migrated more than once",
+                        migration.vmid
+                    );
+                }
+                if migration.target == node
+                    || !nodes
+                        .iter()
+                        .any(|n| n.node == migration.target && n.status == "online")
+                {
+                    http_bail!(
+                        BAD_REQUEST,
+                        "invalid target '{}' for guest {}",
+                        migration.target,
+                        migration.vmid
+                    );
+                }
+                list.push(migration);
+            }
+            list
+        }
+        None => {
+            if !plan.unplaced.is_empty() {
+                http_bail!(
+                    BAD_REQUEST,
+                    "no target node found for guests {:?}, pass the migrations explicitly",
+                    plan.unplaced
+                );
+            }
+            plan.migrations
+        }
+    };
+
+    if migrations.is_empty() {
+        http_bail!(BAD_REQUEST, "nothing to migrate");
+    }
+
+    let user_info = CachedUserInfo::new()?;
+    for migration in &migrations {
+        user_info.check_privs(
+            &auth_id,
+            &["resource", &remote, "guest", &migration.vmid.to_string()],
+            PRIV_RESOURCE_MIGRATE,
+            false,
+        )?;
+    }
+
+    let max_parallel = params.max_parallel.unwrap_or(DEFAULT_MAX_PARALLEL);
+    let retries = params.retries.unwrap_or(DEFAULT_RETRIES);
+    let bwlimit = params.bwlimit;
+
+    let upid_str = WorkerTask::spawn(
+        "evacuate-node",
+        Some(format!("{remote}:{node}")),
+        auth_id.to_string(),
+        true,
+        move |_worker| async move {
+            let total = migrations.len();
+            log::info!(
+                "evacuating node '{node}' of remote '{remote}': {total} migration(s), \
+                up to {max_parallel} in parallel"
+            );
+
+            let pve = pve.as_ref();
+            let (remote, node) = (&remote, &node);
+            let results: Vec<(EvacuationMigration, Result<(), Error>)> =
+                futures::stream::iter(migrations)
+                    .map(|migration| async move {
+                        let result =
+                            run_migration(pve, remote, node, &migration, bwlimit, retries).await;
+                        (migration, result)
+                    })
+                    .buffer_unordered(max_parallel)
+                    .collect()
+                    .await;
+
+            let mut failed = 0;
+            for (migration, result) in &results {
+                if let Err(err) = result {
+                    failed += 1;
+                    log::error!(
+                        "skipped guest {} (target '{}') - {err:#}",
+                        migration.vmid,
+                        migration.target
+                    );
+                }
+            }
+
+            log::info!("{} of {total} migration(s) succeeded", total - failed);
+            if failed > 0 {
+                bail!("{failed} of {total} migration(s) failed");
+            }
+
+            Ok(())
+        },
+    )?;
+
+    upid_str.parse()
+}
+
+/// Run a single migration, retrying it up to `retries` times.
+async fn run_migration(
+    pve: &PveClient,
+    remote: &str,
+    node: &str,
+    migration: &EvacuationMigration,
+    bwlimit: Option<u64>,
+    retries: u8,
+) -> Result<(), Error> {
+    let vmid = migration.vmid;
+    let target = &migration.target;
+
+    let mut attempt = 0;
+    loop {
+        let result = async {
+            let upid = start_migration(pve, remote, node, migration, bwlimit).await?;
+            log::info!("migrating guest {vmid} to '{target}' - {upid}");
+            wait_for_pve_task(pve, &upid).await
+        }
+        .await;
+
+        match result {
+            Ok(()) => {
+                log::info!("migrated guest {vmid} to '{target}'");
+                return Ok(());
+            }
+            Err(err) if attempt < retries => {
+                attempt += 1;
+                log::warn!(
+                    "migration of guest {vmid} failed, retrying ({attempt}/{retries}) - {err:#}"
+                );
+            }
+            Err(err) => return Err(err),
+        }
+    }
+}
+
+async fn start_migration(
+    pve: &PveClient,
+    remote: &str,
+    node: &str,
+    migration: &EvacuationMigration,
+    bwlimit: Option<u64>,
+) -> Result<RemoteUpid, Error> {
+    // running guests are migrated online (VMs) or restarted (containers), stopped guests are
+    // migrated offline by PVE
+    let upid = match migration.guest_type {
+        GuestType::Qemu => {
+            let params = pve_api_types::MigrateQemu {
+                bwlimit,
+                force: None,
+                migration_network: None,
+                migration_type: None,
+                online: Some(true),
+                target: migration.target.clone(),
+                targetstorage: None,
+                with_local_disks: Some(true),
+                with_conntrack_state: None,
+            };
+            pve.migrate_qemu(node, migration.vmid, params).await?
+        }
+        GuestType::Lxc => {
+            let params = pve_api_types::MigrateLxc {
+                bwlimit: bwlimit.map(|limit| limit as f64),
+                online: None,
+                restart: Some(true),
+                target: migration.target.clone(),
+                target_storage: None,
+                timeout: None,
+            };
+            pve.migrate_lxc(node, migration.vmid, params).await?
+        }
+    };
+
+    new_remote_upid(remote.to_string(), upid).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GIB: u64 = 1024 * 1024 * 1024;
+
+    fn node(name: &str, cpu: f64, mem: u64, status: &str) -> PveNodeResource {
+        PveNodeResource {
+            cgroup_mode: 2,
+            cpu,
+            maxcpu: 16.0,
+            id: format!("remote/pve/node/{name}"),
+            maxmem: 64 * GIB,
+            mem,
+            node: name.to_string(),
+            uptime: 1000,
+            status: status.to_string(),
+            level: String::new(),
+        }
+    }
+
+    fn guest(vmid: u32, cpu: f64, mem: u64) -> PlanGuest {
+        PlanGuest {
+            vmid,
+            guest_type: GuestType::Qemu,
+            cpu,
+            mem,
+        }
+    }
+
+    fn target_of(plan: &EvacuationPlan, vmid: u32) -> Option<&str> {
+        plan.migrations
+            .iter()
+            .find(|m| m.vmid == vmid)
+            .map(|m| m.target.as_str())
+    }
+
+    #[test]
+    fn spreads_guests_by_load() {
+        let nodes = [
+            node("pve1", 0.5, 32 * GIB, "online"),
+            node("pve2", 0.1, 8 * GIB, "online"),
+            node("pve3", 0.1, 40 * GIB, "online"),
+        ];
+        let guests = vec![guest(100, 2.0, 16 * GIB), guest(101, 1.0, 8 * GIB)];
+
+        let plan = plan_evacuation("pve", "pve1", &nodes, guests);
+
+        assert!(plan.unplaced.is_empty());
+        // the largest guest goes to the least loaded node, the next one still fits better on
+        // pve2 (24 + 8 GiB) than on pve3 (40 + 8 GiB)
+        assert_eq!(target_of(&plan, 100), Some("pve2"));
+        assert_eq!(target_of(&plan, 101), Some("pve2"));
+        assert_eq!(plan.migrations[0].vmid, 100);
+
+        let pve2 = plan.targets.iter().find(|t| t.node == "pve2").unwrap();
+        assert_eq!(pve2.mem, 32 * GIB);
+    }
+
+    #[test]
+    fn skips_offline_nodes_and_reports_unplaced() {
+        let nodes = [
+            node("pve1", 0.5, 32 * GIB, "online"),
+            node("pve2", 0.1, 60 * GIB, "online"),
+            node("pve3", 0.0, 0, "offline"),
+        ];
+        let guests = vec![guest(100, 1.0, 8 * GIB), guest(101, 0.0, 0)];
+
+        let plan = plan_evacuation("pve", "pve1", &nodes, guests);
+
+        assert_eq!(plan.unplaced, [100]);
+        assert_eq!(target_of(&plan, 101), Some("pve2"));
+        assert!(plan.targets.iter().all(|t| t.node != "pve3"));
+    }
+}
@@ -0,0 +1,492 @@
+//! Editing the commonly changed subset of a guest configuration.
+
+use anyhow::{Error, bail, format_err};
+use serde::Deserialize;
+use serde_json::{Map, Value, json};
+
+use proxmox_client::HttpApiClient;
+use proxmox_router::http_bail;
+
+use pdm_api_types::pve::{GuestConfigChange, GuestConfigUpdate, GuestConfigUpdateResult};
+use pdm_api_types::resource::GuestType;
+
+use crate::connection;
+
+use super::{connect_to_remote, find_node_for_vm, get_remote, new_remote_upid};
+
+/// A configuration key as returned by the `pending` API of PVE guests.
+#[derive(Deserialize)]
+struct PendingEntry {
+    key: String,
+    #[serde(default)]
+    value: Option<Value>,
+    #[serde(default)]
+    pending: Option<Value>,
+    #[serde(default)]
+    delete: Option<u8>,
+}
+
+impl PendingEntry {
+    /// The value the key will have once pending changes are applied.
+    fn effective_value(&self) -> Option<String> {
+        if self.delete.unwrap_or(0) > 0 {
+            return None;
+        }
+        self.pending
+            .as_ref()
+            .or(self.value.as_ref())
+            .map(value_to_string)
+    }
+
+    fn has_pending_change(&self) -> bool {
+        self.pending.is_some() || self.delete.unwrap_or(0) > 0
+    }
+}
+
+fn value_to_string(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// The computed changes of an update.
+#[derive(Debug, Default, PartialEq)]
+struct ConfigChanges {
+    /// Parameters for the `config` PUT call.
+    params: Map<String, Value>,
+    /// Keys to delete.
+    delete: Vec<String>,
+    /// Keys whose pending changes are reverted.
+    revert: Vec<String>,
+    /// Disks to resize, with the requested size.
+    resize: Vec<(String, String)>,
+    /// The changes for the diff.
+    changes: Vec<GuestConfigChange>,
+}
+
+/// Split a property string into its key-value pairs, keeping their order.
+fn split_property_string(value: &str) -> Vec<(String, String)> {
+    value
+        .split(',')
+        .filter(|part| !part.is_empty())
+        .map(|part| match part.split_once('=') {
+            Some((key, value)) => (key.to_string(), value.to_string()),
+            None => (String::new(), part.to_string()),
+        })
+        .collect()
+}
+
+fn join_property_string(parts: &[(String, String)]) -> String {
+    parts
+        .iter()
+        .map(|(key, value)| match key.is_empty() {
+            true => value.clone(),
+            false => format!("{key}={value}"),
+        })
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// Set `key` in a property string, or remove it if `value` is `None`.
+fn set_property(parts: &mut Vec<(String, String)>, key: &str, value: Option<String>) {
+    match value {
+        Some(value) => match parts.iter_mut().find(|(k, _)| k == key) {
+            Some((_, v)) => *v = value,
+            None => parts.push((key.to_string(), value)),
+        },
+        None => parts.retain(|(k, _)| k != key),
+    }
+}
+
+fn compute_changes(
+    guest_type: GuestType,
+    current: &[PendingEntry],
+    update: GuestConfigUpdate,
+) -> Result<ConfigChanges, Error> {
+    let current_value = |key: &str| {
+        current
+            .iter()
+            .find(|entry| entry.key == key)
+            .and_then(|entry| entry.effective_value())
+    };
+
+    if guest_type == GuestType::Lxc {
+        if update.sockets.is_some() {
+            http_bail!(BAD_REQUEST, "containers have no CPU sockets");
+        }
+        if update.balloon.is_some() {
+            http_bail!(BAD_REQUEST, "containers have no balloon device");
+        }
+    }
+
+    let mut result = ConfigChanges::default();
+
+    let mut set = |key: &str, new: String| {
+        let old = current_value(key);
+        if old.as_deref() != Some(new.as_str()) {
+            result.params.insert(key.to_string(), new.clone().into());
+            result.changes.push(GuestConfigChange {
+                key: key.to_string(),
+                old,
+                new: Some(new),
+                pending: false,
+            });
+        }
+    };
+
+    if let Some(cores) = update.cores {
+        set("cores", cores.to_string());
+    }
+    if let Some(sockets) = update.sockets {
+        set("sockets", sockets.to_string());
+    }
+    if let Some(memory) = update.memory {
+        set("memory", memory.to_string());
+    }
+    if let Some(balloon) = update.balloon {
+        set("balloon", balloon.to_string());
+    }
+    if let Some(description) = update.description {
+        set("description", description);
+    }
+    if let Some(tags) = update.tags {
+        set("tags", tags);
+    }
+    if let Some(onboot) = update.onboot {
+        set("onboot", u8::from(onboot).to_string());
+    }
+
+    for net in update.net.into_iter().flatten() {
+        let net = net.into_inner();
+        let Some(old) = current_value(&net.id) else {
+            http_bail!(NOT_FOUND, "no such network device '{}'", net.id);
+        };
+
+        let mut parts = split_property_string(&old);
+        if let Some(bridge) = net.bridge {
+            set_property(&mut parts, "bridge", Some(bridge));
+        }
+        if let Some(tag) = net.tag {
+            set_property(&mut parts, "tag", (tag > 0).then(|| tag.to_string()));
+        }
+        if let Some(rate) = net.rate {
+            set_property(&mut parts, "rate", (rate > 0.0).then(|| rate.to_string()));
+        }
+        set(&net.id, join_property_string(&parts));
+    }
+
+    for key in update.delete.into_iter().flatten() {
+        let key = key.to_string();
+        if result.params.contains_key(&key) {
+            http_bail!(
+                BAD_REQUEST,
+                "cannot set and delete '{key}' at the same time"
+            );
+        }
+        if let Some(old) = current_value(&key) {
+            result.changes.push(GuestConfigChange {
+                key: key.clone(),
+                old: Some(old),
+                new: None,
+                pending: false,
+            });
+            result.delete.push(key);
+        }
+    }
+
+    for key in update.revert.into_iter().flatten() {
+        let key = key.to_string();
+        let Some(entry) = current.iter().find(|entry| entry.key == key) else {
+            continue;
+        };
+        if !entry.has_pending_change() {
+            continue;
+        }
+        result.changes.push(GuestConfigChange {
+            key: key.clone(),
+            old: entry.effective_value(),
+            new: entry.value.as_ref().map(value_to_string),
+            pending: false,
+        });
+        result.revert.push(key);
+    }
+
+    for resize in update.resize.into_iter().flatten() {
+        let resize = resize.into_inner();
+        let Some(old) = current_value(&resize.disk) else {
+            http_bail!(NOT_FOUND, "no such disk '{}'", resize.disk);
+        };
+        let old_size = split_property_string(&old)
+            .into_iter()
+            .find_map(|(key, value)| (key == "size").then_some(value));
+
+        result.changes.push(GuestConfigChange {
+            key: resize.disk.clone(),
+            old: old_size,
+            new: Some(resize.size.clone()),
+            pending: false,
+        });
+        result.resize.push((resize.disk, resize.size));
+    }
+
+    Ok(result)
+}
+
+/// Update the configuration of a guest, or only compute the changes if `preview` is set.
+///
+/// The changes are computed against the configuration including pending changes. The update is
+/// rejected if the configuration changed since it was read, or if `digest` is set and does not
+/// match the digest of the current configuration.
+pub(super) async fn update_guest_config(
+    remote: String,
+    node: Option<String>,
+    vmid: u32,
+    guest_type: GuestType,
+    update: GuestConfigUpdate,
+    digest: Option<String>,
+    preview: bool,
+) -> Result<GuestConfigUpdateResult, Error> {
+    let (remotes, _) = pdm_config::remotes::config()?;
+    let pve = connect_to_remote(&remotes, &remote)?;
+    let node = find_node_for_vm(node, vmid, pve.as_ref()).await?;
+
+    let client = connection::make_raw_client(get_remote(&remotes, &remote)?)?;
+    let ty = match guest_type {
+        GuestType::Qemu => "qemu",
+        GuestType::Lxc => "lxc",
+    };
+    let base_path = format!("/api2/extjs/nodes/{node}/{ty}/{vmid}");
+
+    let config: Value = client
+        .get(&format!("{base_path}/config"))
+        .await?
+        .expect_json()?
+        .data;
+    let current_digest = config["digest"]
+        .as_str()
+        .ok_or_else(|| format_err!("guest configuration has no digest"))?
+        .to_string();
+
+    if let Some(digest) = digest {
+        if digest != current_digest {
+            bail!("detected modified configuration - file changed by other user? Try again.");
+        }
+    }
+
+    let pending: Vec<PendingEntry> = client
+        .get(&format!("{base_path}/pending"))
+        .await?
+        .expect_json()?
+        .data;
+
+    let mut changes = compute_changes(guest_type, &pending, update)?;
+
+    if preview {
+        return Ok(GuestConfigUpdateResult {
+            digest: current_digest,
+            changes: changes.changes,
+            tasks: Vec::new(),
+        });
+    }
+
+    let mut resize_digest = Some(current_digest.clone());
+
+    if !changes.params.is_empty() || !changes.delete.is_empty() || !changes.revert.is_empty() {
+        let mut params = Value::Object(std::mem::take(&mut changes.params));
+        params["digest"] = current_digest.clone().into();
+        if !changes.delete.is_empty() {
+            params["delete"] = changes.delete.join(",").into();
+        }
+        if !changes.revert.is_empty() {
+            params["revert"] = changes.revert.join(",").into();
+        }
+        client
+            .put(&format!("{base_path}/config"), &params)
+            .await?
+            .nodata()?;
+        // the config changed, the digest can no longer be used for the resize calls
+        resize_digest = None;
+    }
+
+    let mut tasks = Vec::new();
+    for (disk, size) in changes.resize {
+        let mut params = json!({ "disk": disk, "size": size });
+        if let Some(digest) = resize_digest.take() {
+            params["digest"] = digest.into();
+        }
+        let upid: Option<String> = client
+            .put(&format!("{base_path}/resize"), &params)
+            .await?
+            .expect_json()?
+            .data;
+        // older versions resize qemu disks synchronously and do not return a task
+        if let Some(upid) = upid {
+            tasks.push(new_remote_upid(remote.clone(), upid.parse()?).await?);
+        }
+    }
+
+    let pending: Vec<PendingEntry> = client
+        .get(&format!("{base_path}/pending"))
+        .await?
+        .expect_json()?
+        .data;
+    for change in changes.changes.iter_mut() {
+        change.pending = pending
+            .iter()
+            .any(|entry| entry.key == change.key && entry.has_pending_change());
+    }
+
+    Ok(GuestConfigUpdateResult {
+        digest: current_digest,
+        changes: changes.changes,
+        tasks,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use proxmox_schema::property_string::PropertyString;
+
+    use pdm_api_types::pve::DeletableGuestConfigProperty;
+
+    use super::*;
+
+    fn entry(key: &str, value: Option<Value>, pending: Option<Value>) -> PendingEntry {
+        PendingEntry {
+            key: key.to_string(),
+            value,
+            pending,
+            delete: None,
+        }
+    }
+
+    fn qemu_config() -> Vec<PendingEntry> {
+        vec![
+            entry("cores", Some(json!(2)), None),
+            entry("memory", Some(json!("2048")), Some(json!(4096))),
+            entry("onboot", Some(json!(1)), None),
+            entry(
+                "net0",
+                Some(json!(
+                    "virtio=BC:24:11:00:00:01,bridge=vmbr0,firewall=1,tag=10"
+                )),
+                None,
+            ),
+            entry(
+                "scsi0",
+                Some(json!("local-lvm:vm-100-disk-0,iothread=1,size=32G")),
+                None,
+            ),
+            entry("description", Some(json!("web server")), None),
+        ]
+    }
+
+    #[test]
+    fn scalar_changes() {
+        let update = GuestConfigUpdate {
+            cores: Some(2),
+            memory: Some(8192),
+            onboot: Some(false),
+            ..Default::default()
+        };
+        let changes = compute_changes(GuestType::Qemu, &qemu_config(), update).unwrap();
+
+        // cores is unchanged, memory is compared against the pending value
+        assert_eq!(
+            changes.changes,
+            vec![
+                GuestConfigChange {
+                    key: "memory".into(),
+                    old: Some("4096".into()),
+                    new: Some("8192".into()),
+                    pending: false,
+                },
+                GuestConfigChange {
+                    key: "onboot".into(),
+                    old: Some("1".into()),
+                    new: Some("0".into()),
+                    pending: false,
+                },
+            ]
+        );
+        assert_eq!(changes.params.len(), 2);
+        assert_eq!(changes.params["onboot"], json!("0"));
+    }
+
+    #[test]
+    fn network_changes() {
+        let update = GuestConfigUpdate {
+            net: Some(vec![
+                "net0,bridge=vmbr1,tag=0,rate=12.5"
+                    .parse::<PropertyString<_>>()
+                    .unwrap(),
+            ]),
+            ..Default::default()
+        };
+        let changes = compute_changes(GuestType::Qemu, &qemu_config(), update).unwrap();
+        assert_eq!(
+            changes.params["net0"],
+            json!("virtio=BC:24:11:00:00:01,bridge=vmbr1,firewall=1,rate=12.5")
+        );
+
+        let update = GuestConfigUpdate {
+            net: Some(vec![
+                "net1,bridge=vmbr1".parse::<PropertyString<_>>().unwrap(),
+            ]),
+            ..Default::default()
+        };
+        assert!(compute_changes(GuestType::Qemu, &qemu_config(), update).is_err());
+    }
+
+    #[test]
+    fn delete_revert_and_resize() {
+        let update = GuestConfigUpdate {
+            delete: Some(vec![
+                DeletableGuestConfigProperty::Description,
+                DeletableGuestConfigProperty::Balloon,
+            ]),
+            revert: Some(vec![
+                DeletableGuestConfigProperty::Memory,
+                DeletableGuestConfigProperty::Cores,
+            ]),
+            resize: Some(vec![
+                "scsi0,size=+10G".parse::<PropertyString<_>>().unwrap(),
+            ]),
+            ..Default::default()
+        };
+        let changes = compute_changes(GuestType::Qemu, &qemu_config(), update).unwrap();
+
+        // keys which are not set or have no pending change are ignored
+        assert_eq!(changes.delete, vec!["description".to_string()]);
+        assert_eq!(changes.revert, vec!["memory".to_string()]);
+        assert_eq!(
+            changes.resize,
+            vec![("scsi0".to_string(), "+10G".to_string())]
+        );
+        assert!(changes.params.is_empty());
+
+        let keys: Vec<(&str, Option<&str>, Option<&str>)> = changes
+            .changes
+            .iter()
+            .map(|c| (c.key.as_str(), c.old.as_deref(), c.new.as_deref()))
+            .collect();
+        assert_eq!(
+            keys,
+            vec![
+                ("description", Some("web server"), None),
+                ("memory", Some("4096"), Some("2048")),
+                ("scsi0", Some("32G"), Some("+10G")),
+            ]
+        );
+    }
+
+    #[test]
+    fn container_restrictions() {
+        let update = GuestConfigUpdate {
+            sockets: Some(2),
+            ..Default::default()
+        };
+        assert!(compute_changes(GuestType::Lxc, &[], update).is_err());
+    }
+}
@@ -1,7 +1,6 @@
 use anyhow::{Context, Error, bail};
 use serde_json::Value;
 
-use proxmox_access_control::CachedUserInfo;
 use proxmox_router::{ApiHandler, ApiMethod, ApiResponseFuture};
 use proxmox_router::{
     Permission, Router, RpcEnvironment, SubdirMap, http_bail, list_subdirs_api_method,
@@ -10,21 +9,26 @@ use proxmox_schema::{IntegerSchema, ObjectSchema, StringSchema, api};
 use proxmox_sortable_macro::sortable;
 use pve_api_types::PendingConfigValue;
 
+use pdm_api_types::pve::{GuestConfigUpdate, GuestConfigUpdateResult, PVE_CONFIG_DIGEST_SCHEMA};
 use pdm_api_types::remotes::REMOTE_ID_SCHEMA;
 use pdm_api_types::remotes::Remote;
+use pdm_api_types::resource::GuestType;
 use pdm_api_types::{
     Authid, ConfigurationState, NODE_SCHEMA, PRIV_RESOURCE_AUDIT, PRIV_RESOURCE_MANAGE,
-    PRIV_RESOURCE_MIGRATE, PRIV_SYS_CONSOLE, RemoteUpid, SNAPSHOT_NAME_SCHEMA, VMID_SCHEMA,
+    PRIV_RESOURCE_MIGRATE, PRIV_RESOURCE_MODIFY, PRIV_SYS_CONSOLE, RemoteUpid,
+    SNAPSHOT_NAME_SCHEMA, VMID_SCHEMA,
 };
 
+use crate::acl::CachedUserInfo;
 use crate::api::nodes::vncwebsocket::required_integer_param;
 use crate::api::pve::get_remote;
 use crate::api::remotes::shell::TermTicketType;
 
 use super::{
     check_guest_delete_perms, check_guest_list_permissions, check_guest_permissions,
-    connect_to_remote, connect_to_remote_by_id, new_remote_upid,
+    connect_to_remote, connect_to_remote_by_id, guest_config, migrate, new_remote_upid,
 };
+use migrate::MigrationTarget;
 
 use super::find_node_for_vm;
 
@@ -37,7 +41,12 @@ const LXC_VM_ROUTER: Router = Router::new()
     .subdirs(LXC_VM_SUBDIRS);
 #[sortable]
 const LXC_VM_SUBDIRS: SubdirMap = &sorted!([
-    ("config", &Router::new().get(&API_METHOD_LXC_GET_CONFIG)),
+    (
+        "config",
+        &Router::new()
+            .get(&API_METHOD_LXC_GET_CONFIG)
+            .put(&API_METHOD_LXC_UPDATE_CONFIG)
+    ),
     ("pending", &Router::new().get(&API_METHOD_LXC_GET_PENDING)),
     ("firewall", &super::firewall::LXC_FW_ROUTER),
     ("rrddata", &super::rrddata::LXC_RRD_ROUTER),
@@ -211,6 +220,47 @@ pub async fn lxc_get_pending(
     Ok(pve.lxc_get_pending(&node, vmid).await?)
 }
 
+#[api(
+    input: {
+        properties: {
+            remote: { schema: REMOTE_ID_SCHEMA },
+            node: {
+                schema: NODE_SCHEMA,
+                optional: true,
+            },
+            vmid: { schema: VMID_SCHEMA },
+            update: {
+                type: GuestConfigUpdate,
+                flatten: true,
+            },
+            digest: { schema: PVE_CONFIG_DIGEST_SCHEMA, optional: true },
+            preview: {
+                type: bool,
+                description: "Only return the changes without applying them.",
+                optional: true,
+                default: false,
+            },
+        },
+    },
+    returns: { type: GuestConfigUpdateResult },
+    access: {
+        permission: &Permission::Privilege(&["resource", "{remote}", "guest", "{vmid}"], PRIV_RESOURCE_MODIFY, false),
+    },
+)]
+/// Update the configuration of an lxc container from a remote. Changes which cannot be applied to
+/// the running container become pending changes.
+pub async fn lxc_update_config(
+    remote: String,
+    node: Option<String>,
+    vmid: u32,
+    update: GuestConfigUpdate,
+    digest: Option<String>,
+    preview: bool,
+) -> Result<GuestConfigUpdateResult, Error> {
+    guest_config::update_guest_config(remote, node, vmid, GuestType::Lxc, update, digest, preview)
+        .await
+}
+
 #[api(
     input: {
         properties: {
@@ -579,7 +629,9 @@ pub async fn lxc_migrate(
 
     log::info!("in-cluster migration requested for remote {remote:?} ct {vmid} to node {target:?}");
 
-    let pve = connect_to_remote_by_id(&remote)?;
+    let (remotes, _) = pdm_config::remotes::config()?;
+    let remote_config = get_remote(&remotes, &remote)?;
+    let pve = connect_to_remote(&remotes, &remote)?;
 
     let node = find_node_for_vm(node, vmid, pve.as_ref()).await?;
 
@@ -587,6 +639,17 @@ pub async fn lxc_migrate(
         bail!("refusing migration to the same node");
     }
 
+    migrate::check_migration_mappings(
+        remote_config,
+        &node,
+        GuestType::Lxc,
+        vmid,
+        &MigrationTarget::node(remote_config, &target)?,
+        target_storage.as_deref().unwrap_or_default(),
+        &[],
+    )
+    .await?;
+
     let params = pve_api_types::MigrateLxc {
         bwlimit,
         online,
@@ -725,6 +788,17 @@ pub async fn lxc_remote_migrate(
     let node = find_node_for_vm(node, vmid, source_conn.as_ref()).await?;
 
     let target_node = super::select_migration_target_node(target, target_endpoint.as_deref())?;
+    migrate::check_migration_mappings(
+        get_remote(&remotes, &source)?,
+        &node,
+        GuestType::Lxc,
+        vmid,
+        &MigrationTarget::endpoint(target, target_node)?,
+        &target_storage,
+        &target_bridge,
+    )
+    .await?;
+
     let target_endpoint = super::build_migration_endpoint(target, target_node)?;
 
     log::info!("forwarding remote migration requested");
@@ -0,0 +1,379 @@
+//! Validation of storage and bridge mappings before starting a migration.
+
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::sync::Arc;
+
+use anyhow::{Error, bail};
+use serde_json::{Map, Value};
+
+use proxmox_client::HttpApiClient;
+use proxmox_router::{http_bail, http_err};
+
+use pdm_api_types::remotes::{NodeUrl, Remote};
+use pdm_api_types::resource::GuestType;
+use pve_api_types::{ListNetworksType, StorageContent};
+
+use crate::connection::{self, PveClient};
+
+/// A storage or bridge mapping as accepted by the migrate API of PVE.
+///
+/// Entries are either `source:target` pairs, a single ID all remaining sources are mapped to, or
+/// `1` to map every remaining source to the identically named target.
+#[derive(Debug, Default)]
+struct Mapping {
+    entries: HashMap<String, String>,
+    fallback: Option<String>,
+}
+
+impl Mapping {
+    fn parse(list: &[String]) -> Result<Self, Error> {
+        let mut mapping = Self::default();
+        let mut has_fallback = false;
+
+        for item in list.iter().flat_map(|entry| entry.split([',', ';'])) {
+            let item = item.trim();
+            if item.is_empty() {
+                continue;
+            }
+
+            match item.split_once(':') {
+                Some((source, target)) => {
+                    if mapping
+                        .entries
+                        .insert(source.to_string(), target.to_string())
+                        .is_some()
+                    {
+                        bail!("duplicate mapping for '{source}'");
+                    }
+                }
+                None => {
+                    if has_fallback {
+                        bail!("only a single default mapping is allowed");
+                    }
+                    has_fallback = true;
+                    if item != "1" {
+                        mapping.fallback = Some(item.to_string());
+                    }
+                }
+            }
+        }
+
+        Ok(mapping)
+    }
+
+    fn map<'a>(&'a self, source: &'a str) -> &'a str {
+        self.entries
+            .get(source)
+            .or(self.fallback.as_ref())
+            .map(String::as_str)
+            .unwrap_or(source)
+    }
+}
+
+/// Checks whether `key` is `prefix` followed by a numeric index.
+fn is_indexed_key(key: &str, prefix: &str) -> bool {
+    key.strip_prefix(prefix)
+        .is_some_and(|index| !index.is_empty() && index.bytes().all(|b| b.is_ascii_digit()))
+}
+
+fn is_disk_key(guest_type: GuestType, key: &str) -> bool {
+    let prefixes: &[&str] = match guest_type {
+        GuestType::Qemu => &[
+            "ide", "sata", "scsi", "virtio", "efidisk", "tpmstate", "unused",
+        ],
+        GuestType::Lxc => {
+            if key == "rootfs" {
+                return true;
+            }
+            &["mp", "unused"]
+        }
+    };
+    prefixes.iter().any(|prefix| is_indexed_key(key, prefix))
+}
+
+/// The storages used by the disks of a guest configuration.
+///
+/// CD-ROM drives, pass-through devices and bind mounts are not migrated and therefore skipped.
+fn guest_storages(guest_type: GuestType, config: &Map<String, Value>) -> BTreeSet<String> {
+    let mut storages = BTreeSet::new();
+
+    for (key, value) in config {
+        if !is_disk_key(guest_type, key) {
+            continue;
+        }
+        let Some(value) = value.as_str() else {
+            continue;
+        };
+        if value.split(',').any(|option| option == "media=cdrom") {
+            continue;
+        }
+
+        let volume = value.split(',').next().unwrap_or_default();
+        let volume = volume
+            .strip_prefix("file=")
+            .or_else(|| volume.strip_prefix("volume="))
+            .unwrap_or(volume);
+        if let Some((storage, _)) = volume.split_once(':') {
+            storages.insert(storage.to_string());
+        }
+    }
+
+    storages
+}
+
+/// The bridges the network devices of a guest configuration are attached to.
+fn guest_bridges(config: &Map<String, Value>) -> BTreeSet<String> {
+    config
+        .iter()
+        .filter(|(key, _)| is_indexed_key(key, "net"))
+        .filter_map(|(_, value)| value.as_str())
+        .filter_map(|value| {
+            value
+                .split(',')
+                .find_map(|option| option.strip_prefix("bridge="))
+        })
+        .map(str::to_string)
+        .collect()
+}
+
+/// Compute the list of problems with the mappings of a guest configuration.
+fn check_mappings(
+    guest_type: GuestType,
+    config: &Map<String, Value>,
+    storage_map: &Mapping,
+    bridge_map: &Mapping,
+    target_storages: &HashSet<String>,
+    target_bridges: &HashSet<String>,
+) -> Vec<String> {
+    let mut problems = Vec::new();
+
+    for source in guest_storages(guest_type, config) {
+        let target = storage_map.map(&source);
+        if !target_storages.contains(target) {
+            if target == source {
+                problems.push(format!("storage '{source}' is not available on the target"));
+            } else {
+                problems.push(format!(
+                    "storage '{target}' (mapped from '{source}') is not available on the target"
+                ));
+            }
+        }
+    }
+
+    for source in guest_bridges(config) {
+        let target = bridge_map.map(&source);
+        if !target_bridges.contains(target) {
+            if target == source {
+                problems.push(format!("bridge '{source}' does not exist on the target"));
+            } else {
+                problems.push(format!(
+                    "bridge '{target}' (mapped from '{source}') does not exist on the target"
+                ));
+            }
+        }
+    }
+
+    problems
+}
+
+/// The node a guest is migrated to.
+pub(super) struct MigrationTarget {
+    client: Arc<PveClient>,
+    node: String,
+}
+
+impl MigrationTarget {
+    /// A node of the cluster the guest currently lives in.
+    pub fn node(remote: &Remote, node: &str) -> Result<Self, Error> {
+        Ok(Self {
+            client: connection::make_pve_client(remote)?,
+            node: node.to_string(),
+        })
+    }
+
+    /// The node of another remote used as endpoint for a remote migration.
+    pub fn endpoint(remote: &Remote, endpoint: &NodeUrl) -> Result<Self, Error> {
+        Ok(Self {
+            client: connection::make_pve_client_with_endpoint(remote, Some(&endpoint.hostname))?,
+            // the endpoint itself receives the guest
+            node: "localhost".to_string(),
+        })
+    }
+}
+
+/// Check that all storages and bridges used by a guest are available on the migration target.
+///
+/// Fails with a `BAD_REQUEST` listing all problems, so they are reported before any task is
+/// started on the remote.
+pub(super) async fn check_migration_mappings(
+    source: &Remote,
+    node: &str,
+    guest_type: GuestType,
+    vmid: u32,
+    target: &MigrationTarget,
+    target_storage: &[String],
+    target_bridge: &[String],
+) -> Result<(), Error> {
+    let storage_map = Mapping::parse(target_storage)
+        .map_err(|err| http_err!(BAD_REQUEST, "invalid storage mapping - {err}"))?;
+    let bridge_map = Mapping::parse(target_bridge)
+        .map_err(|err| http_err!(BAD_REQUEST, "invalid bridge mapping - {err}"))?;
+
+    let (ty, content) = match guest_type {
+        GuestType::Qemu => ("qemu", StorageContent::Images),
+        GuestType::Lxc => ("lxc", StorageContent::Rootdir),
+    };
+
+    let config: Map<String, Value> = connection::make_raw_client(source)?
+        .get(&format!("/api2/extjs/nodes/{node}/{ty}/{vmid}/config"))
+        .await?
+        .expect_json()?
+        .data;
+
+    let target_storages = target
+        .client
+        .list_storages(
+            &target.node,
+            Some(vec![content]),
+            Some(true),
+            None,
+            None,
+            None,
+        )
+        .await?
+        .into_iter()
+        .map(|storage| storage.storage)
+        .collect();
+    let target_bridges = target
+        .client
+        .list_networks(&target.node, Some(ListNetworksType::AnyBridge))
+        .await?
+        .into_iter()
+        .map(|interface| interface.iface)
+        .collect();
+
+    let problems = check_mappings(
+        guest_type,
+        &config,
+        &storage_map,
+        &bridge_map,
+        &target_storages,
+        &target_bridges,
+    );
+    if !problems.is_empty() {
+        http_bail!(
+            BAD_REQUEST,
+            "migration preconditions not met:\n{}",
+            problems.join("\n")
+        );
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn config(value: Value) -> Map<String, Value> {
+        match value {
+            Value::Object(map) => map,
+            _ => unreachable!(),
+        }
+    }
+
+    fn set(items: &[&str]) -> HashSet<String> {
+        items.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn parse_mapping() {
+        let mapping = Mapping::parse(&["local-lvm:ceph".into(), "1".into()]).unwrap();
+        assert_eq!(mapping.map("local-lvm"), "ceph");
+        assert_eq!(mapping.map("nfs"), "nfs");
+
+        let mapping = Mapping::parse(&["a:b,zfs".into()]).unwrap();
+        assert_eq!(mapping.map("a"), "b");
+        assert_eq!(mapping.map("c"), "zfs");
+
+        let mapping = Mapping::parse(&[]).unwrap();
+        assert_eq!(mapping.map("local"), "local");
+
+        assert!(Mapping::parse(&["a:b".into(), "a:c".into()]).is_err());
+        assert!(Mapping::parse(&["1".into(), "zfs".into()]).is_err());
+    }
+
+    #[test]
+    fn extract_storages_and_bridges() {
+        let qemu = config(json!({
+            "scsi0": "local-lvm:vm-100-disk-0,size=32G",
+            "efidisk0": "ceph:vm-100-disk-1,efitype=4m,size=1M",
+            "ide2": "local:iso/debian.iso,media=cdrom",
+            "sata1": "/dev/disk/by-id/ata-disk,size=1T",
+            "unused0": "nfs:100/vm-100-disk-2.qcow2",
+            "scsihw": "virtio-scsi-pci",
+            "net0": "virtio=BC:24:11:00:00:01,bridge=vmbr0,firewall=1",
+            "net1": "virtio=BC:24:11:00:00:02,bridge=vmbr1",
+        }));
+        assert_eq!(
+            guest_storages(GuestType::Qemu, &qemu),
+            ["ceph", "local-lvm", "nfs"].map(String::from).into()
+        );
+        assert_eq!(
+            guest_bridges(&qemu),
+            ["vmbr0", "vmbr1"].map(String::from).into()
+        );
+
+        let lxc = config(json!({
+            "rootfs": "local-zfs:subvol-101-disk-0,size=8G",
+            "mp0": "/mnt/data,mp=/data",
+            "mp1": "volume=tank:subvol-101-disk-1,mp=/srv",
+            "net0": "name=eth0,bridge=vmbr0,ip=dhcp",
+        }));
+        assert_eq!(
+            guest_storages(GuestType::Lxc, &lxc),
+            ["local-zfs", "tank"].map(String::from).into()
+        );
+    }
+
+    #[test]
+    fn report_missing_targets() {
+        let qemu = config(json!({
+            "scsi0": "local-lvm:vm-100-disk-0,size=32G",
+            "scsi1": "nfs:100/vm-100-disk-1.qcow2,size=32G",
+            "net0": "virtio=BC:24:11:00:00:01,bridge=vmbr0",
+            "net1": "virtio=BC:24:11:00:00:02,bridge=vmbr1",
+        }));
+
+        let storage_map = Mapping::parse(&["local-lvm:ceph".into()]).unwrap();
+        let bridge_map = Mapping::parse(&["vmbr1:vmbr0".into()]).unwrap();
+
+        let problems = check_mappings(
+            GuestType::Qemu,
+            &qemu,
+            &storage_map,
+            &bridge_map,
+            &set(&["ceph", "nfs"]),
+            &set(&["vmbr0"]),
+        );
+        assert!(problems.is_empty(), "{problems:?}");
+
+        let problems = check_mappings(
+            GuestType::Qemu,
+            &qemu,
+            &storage_map,
+            &Mapping::default(),
+            &set(&["local-lvm", "nfs"]),
+            &set(&["vmbr0"]),
+        );
+        assert_eq!(
+            problems,
+            [
+                "storage 'ceph' (mapped from 'local-lvm') is not available on the target",
+                "bridge 'vmbr1' does not exist on the target",
+            ]
+        );
+    }
+}
@@ -4,7 +4,6 @@ use std::sync::Arc;
 
 use anyhow::{Context, Error, bail, format_err};
 
-use proxmox_access_control::CachedUserInfo;
 use proxmox_router::{
     Permission, Router, RpcEnvironment, SubdirMap, http_bail, http_err, list_subdirs_api_method,
 };
@@ -15,7 +14,8 @@ use proxmox_sortable_macro::sortable;
 
 use pdm_api_types::remote_updates::RemoteUpdateSummary;
 use pdm_api_types::remotes::{
-    NodeUrl, REMOTE_ID_SCHEMA, Remote, RemoteListEntry, RemoteType, TlsProbeOutcome,
+    NodeUrl, REMOTE_ID_SCHEMA, REMOTE_TFA_CHALLENGE_SCHEMA, REMOTE_TFA_RESPONSE_SCHEMA, Remote,
+    RemoteListEntry, RemoteScanResult, RemoteType, TlsProbeOutcome,
 };
 use pdm_api_types::resource::PveResource;
 use pdm_api_types::{
@@ -30,17 +30,26 @@ use pve_api_types::{ClusterResourceKind, ClusterResourceType};
 
 use super::resources::{map_pve_lxc, map_pve_node, map_pve_qemu, map_pve_storage};
 
+use crate::acl::CachedUserInfo;
 use crate::connection::PveClient;
 use crate::connection::{self, probe_tls_connection};
 use crate::remote_tasks;
 use crate::remote_updates::get_available_updates_for_remote;
 
+mod backup_jobs;
+mod balancing;
+pub(crate) mod bulk;
+mod deploy;
+mod evacuate;
 mod firewall;
+mod guest_config;
 mod lxc;
+mod migrate;
 mod node;
 mod qemu;
 mod rrddata;
 mod storage;
+mod tags;
 pub mod tasks;
 
 pub const ROUTER: Router = Router::new()
@@ -50,9 +59,14 @@ pub const ROUTER: Router = Router::new()
 #[sortable]
 const SUBDIRS: SubdirMap = &sorted!([
     ("remotes", &REMOTES_ROUTER),
+    ("backup-jobs", &backup_jobs::ROUTER),
+    ("balancing", &balancing::ROUTER),
+    ("bulk-action", &bulk::ROUTER),
+    ("descriptions", &tags::DESCRIPTION_ROUTER),
     ("firewall", &firewall::PVE_FW_ROUTER),
     ("probe-tls", &Router::new().post(&API_METHOD_PROBE_TLS)),
     ("scan", &Router::new().post(&API_METHOD_SCAN_REMOTE_PVE)),
+    ("tags", &tags::ROUTER),
     (
         "realms",
         &Router::new().get(&API_METHOD_LIST_REALM_REMOTE_PVE)
@@ -69,6 +83,8 @@ const MAIN_ROUTER: Router = Router::new()
 
 #[sortable]
 const REMOTE_SUBDIRS: SubdirMap = &sorted!([
+    ("backup-jobs", &backup_jobs::REMOTE_ROUTER),
+    ("deploy", &deploy::ROUTER),
     ("lxc", &lxc::ROUTER),
     ("firewall", &firewall::CLUSTER_FW_ROUTER),
     ("nodes", &NODES_ROUTER),
@@ -459,13 +475,21 @@ async fn probe_tls(
                 type: String,
                 description: "The token secret or the user password.",
             },
+            "tfa-challenge": {
+                schema: REMOTE_TFA_CHALLENGE_SCHEMA,
+                optional: true,
+            },
+            "tfa-response": {
+                schema: REMOTE_TFA_RESPONSE_SCHEMA,
+                optional: true,
+            },
         },
     },
     access: {
         permission:
             &Permission::Privilege(&["/"], PRIV_SYS_MODIFY, false),
     },
-    returns: { type: Remote },
+    returns: { type: RemoteScanResult },
 )]
 /// Scans the given connection info for pve cluster information
 ///
@@ -476,7 +500,9 @@ pub async fn scan_remote_pve(
     fingerprint: Option<String>,
     authid: Authid,
     token: String,
-) -> Result<Remote, Error> {
+    tfa_challenge: Option<String>,
+    tfa_response: Option<String>,
+) -> Result<RemoteScanResult, Error> {
     let mut remote = Remote {
         ty: RemoteType::Pve,
         id: String::new(),
@@ -487,8 +513,24 @@ pub async fn scan_remote_pve(
         authid: authid.clone(),
         token,
         web_url: None,
+        folder: None,
+        timeout: None,
+        http_proxy: None,
+        max_connections: None,
+        token_created: None,
     };
 
+    if let Some(tfa_challenge) =
+        connection::login_remote(&remote, tfa_challenge.as_deref(), tfa_response.as_deref())
+            .await
+            .map_err(|err| format_err!("could not login: {err}"))?
+    {
+        return Ok(RemoteScanResult {
+            remote: None,
+            tfa_challenge: Some(tfa_challenge),
+        });
+    }
+
     let client = connect_or_login(&remote)
         .await
         .map_err(|err| format_err!("could not login: {err}"))?;
@@ -532,7 +574,10 @@ pub async fn scan_remote_pve(
             .unwrap_or_default();
     }
 
-    Ok(remote)
+    Ok(RemoteScanResult {
+        remote: Some(remote),
+        tfa_challenge: None,
+    })
 }
 
 #[api(
@@ -578,6 +623,11 @@ pub async fn list_realm_remote_pve(
         authid: "root@pam".parse()?,
         token: String::new(),
         web_url: None,
+        folder: None,
+        timeout: None,
+        http_proxy: None,
+        max_connections: None,
+        token_created: None,
     };
 
     let client = connection::make_pve_client(&remote)?;
@@ -17,6 +17,7 @@ pub const ROUTER: Router = Router::new()
 const SUBDIRS: SubdirMap = &sorted!([
     ("apt", &crate::api::remotes::updates::APT_ROUTER),
     ("config", &Router::new().get(&API_METHOD_GET_CONFIG)),
+    ("evacuate", &super::evacuate::ROUTER),
     ("firewall", &super::firewall::NODE_FW_ROUTER),
     ("rrddata", &super::rrddata::NODE_RRD_ROUTER),
     ("network", &Router::new().get(&API_METHOD_GET_NETWORK)),
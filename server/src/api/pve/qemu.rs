@@ -1,31 +1,35 @@
 use anyhow::{Context, Error, bail};
 
-use proxmox_access_control::CachedUserInfo;
 use proxmox_router::{
     ApiMethod, Permission, Router, RpcEnvironment, SubdirMap, http_bail, list_subdirs_api_method,
 };
 use proxmox_schema::{IntegerSchema, ObjectSchema, StringSchema, api};
 use proxmox_sortable_macro::sortable;
 
+use pdm_api_types::pve::{GuestConfigUpdate, GuestConfigUpdateResult, PVE_CONFIG_DIGEST_SCHEMA};
 use pdm_api_types::remotes::REMOTE_ID_SCHEMA;
 use pdm_api_types::remotes::Remote;
+use pdm_api_types::resource::GuestType;
 use pdm_api_types::{
     Authid, CIDR_FORMAT, ConfigurationState, NODE_SCHEMA, PRIV_RESOURCE_AUDIT,
-    PRIV_RESOURCE_MANAGE, PRIV_RESOURCE_MIGRATE, PRIV_SYS_CONSOLE, RemoteUpid,
-    SNAPSHOT_NAME_SCHEMA, VMID_SCHEMA,
+    PRIV_RESOURCE_MANAGE, PRIV_RESOURCE_MIGRATE, PRIV_RESOURCE_MODIFY, PRIV_SYS_CONSOLE,
+    RemoteUpid, SNAPSHOT_NAME_SCHEMA, VMID_SCHEMA,
 };
 
 use pve_api_types::{PendingConfigValue, QemuMigratePreconditions, StartQemuMigrationType};
 use serde_json::Value;
 
+use crate::acl::CachedUserInfo;
 use crate::api::nodes::vncwebsocket::required_integer_param;
 use crate::api::pve::get_remote;
 use crate::api::remotes::shell::TermTicketType;
 
 use super::{
     check_guest_delete_perms, check_guest_list_permissions, check_guest_permissions,
-    connect_to_remote, connect_to_remote_by_id, find_node_for_vm, new_remote_upid,
+    connect_to_remote, connect_to_remote_by_id, find_node_for_vm, guest_config, migrate,
+    new_remote_upid,
 };
+use migrate::MigrationTarget;
 
 pub const ROUTER: Router = Router::new()
     .get(&API_METHOD_LIST_QEMU)
@@ -36,7 +40,12 @@ const QEMU_VM_ROUTER: Router = Router::new()
     .subdirs(QEMU_VM_SUBDIRS);
 #[sortable]
 const QEMU_VM_SUBDIRS: SubdirMap = &sorted!([
-    ("config", &Router::new().get(&API_METHOD_QEMU_GET_CONFIG)),
+    (
+        "config",
+        &Router::new()
+            .get(&API_METHOD_QEMU_GET_CONFIG)
+            .put(&API_METHOD_QEMU_UPDATE_CONFIG)
+    ),
     ("pending", &Router::new().get(&API_METHOD_QEMU_GET_PENDING)),
     ("firewall", &super::firewall::QEMU_FW_ROUTER),
     ("rrddata", &super::rrddata::QEMU_RRD_ROUTER),
@@ -217,6 +226,47 @@ pub async fn qemu_get_pending(
     Ok(pve.qemu_get_pending(&node, vmid).await?)
 }
 
+#[api(
+    input: {
+        properties: {
+            remote: { schema: REMOTE_ID_SCHEMA },
+            node: {
+                schema: NODE_SCHEMA,
+                optional: true,
+            },
+            vmid: { schema: VMID_SCHEMA },
+            update: {
+                type: GuestConfigUpdate,
+                flatten: true,
+            },
+            digest: { schema: PVE_CONFIG_DIGEST_SCHEMA, optional: true },
+            preview: {
+                type: bool,
+                description: "Only return the changes without applying them.",
+                optional: true,
+                default: false,
+            },
+        },
+    },
+    returns: { type: GuestConfigUpdateResult },
+    access: {
+        permission: &Permission::Privilege(&["resource", "{remote}", "guest", "{vmid}"], PRIV_RESOURCE_MODIFY, false),
+    },
+)]
+/// Update the configuration of a qemu VM from a remote. Changes which cannot be applied to the
+/// running VM become pending changes.
+pub async fn qemu_update_config(
+    remote: String,
+    node: Option<String>,
+    vmid: u32,
+    update: GuestConfigUpdate,
+    digest: Option<String>,
+    preview: bool,
+) -> Result<GuestConfigUpdateResult, Error> {
+    guest_config::update_guest_config(remote, node, vmid, GuestType::Qemu, update, digest, preview)
+        .await
+}
+
 #[api(
     input: {
         properties: {
@@ -638,7 +688,9 @@ pub async fn qemu_migrate(
 ) -> Result<RemoteUpid, Error> {
     log::info!("in-cluster migration requested for remote {remote:?} vm {vmid} to node {target:?}");
 
-    let pve = connect_to_remote_by_id(&remote)?;
+    let (remotes, _) = pdm_config::remotes::config()?;
+    let remote_config = get_remote(&remotes, &remote)?;
+    let pve = connect_to_remote(&remotes, &remote)?;
 
     let node = find_node_for_vm(node, vmid, pve.as_ref()).await?;
 
@@ -646,6 +698,17 @@ pub async fn qemu_migrate(
         bail!("refusing migration to the same node");
     }
 
+    migrate::check_migration_mappings(
+        remote_config,
+        &node,
+        GuestType::Qemu,
+        vmid,
+        &MigrationTarget::node(remote_config, &target)?,
+        target_storage.as_deref().unwrap_or_default(),
+        &[],
+    )
+    .await?;
+
     let params = pve_api_types::MigrateQemu {
         bwlimit,
         force,
@@ -675,6 +738,15 @@ pub async fn qemu_migrate(
                 optional: true,
             },
             vmid: { schema: VMID_SCHEMA },
+            "target-storage": {
+                description: "List of storage mappings to validate against the target node.",
+                optional: true,
+                items: {
+                    description: "Mappings of source storages to target storages.",
+                    type: String,
+                },
+                type: Array,
+            },
         }
     },
     access: {
@@ -685,16 +757,35 @@ pub async fn qemu_migrate(
     returns: { type: QemuMigratePreconditions }
 )]
 /// Qemu (local) migrate preconditions
+///
+/// If a target node is given, this also checks that the (mapped) storages of all disks and the
+/// bridges of all network devices are available there and fails listing the problems otherwise.
 async fn qemu_migrate_preconditions(
     remote: String,
     node: Option<String>,
     target: Option<String>,
     vmid: u32,
+    target_storage: Option<Vec<String>>,
 ) -> Result<QemuMigratePreconditions, Error> {
-    let pve = connect_to_remote_by_id(&remote)?;
+    let (remotes, _) = pdm_config::remotes::config()?;
+    let remote_config = get_remote(&remotes, &remote)?;
+    let pve = connect_to_remote(&remotes, &remote)?;
 
     let node = find_node_for_vm(node, vmid, pve.as_ref()).await?;
 
+    if let Some(target) = target.as_deref().filter(|target| *target != node) {
+        migrate::check_migration_mappings(
+            remote_config,
+            &node,
+            GuestType::Qemu,
+            vmid,
+            &MigrationTarget::node(remote_config, target)?,
+            target_storage.as_deref().unwrap_or_default(),
+            &[],
+        )
+        .await?;
+    }
+
     let res = pve.qemu_migrate_preconditions(&node, vmid, target).await?;
     Ok(res)
 }
@@ -815,6 +906,17 @@ pub async fn qemu_remote_migrate(
     let node = find_node_for_vm(node, vmid, source_conn.as_ref()).await?;
 
     let target_node = super::select_migration_target_node(target, target_endpoint.as_deref())?;
+    migrate::check_migration_mappings(
+        get_remote(&remotes, &source)?,
+        &node,
+        GuestType::Qemu,
+        vmid,
+        &MigrationTarget::endpoint(target, target_node)?,
+        &target_storage,
+        &target_bridge,
+    )
+    .await?;
+
     let target_endpoint = super::build_migration_endpoint(target, target_node)?;
 
     log::info!("forwarding remote migration requested");
@@ -0,0 +1,839 @@
+//! Manage guest tags and descriptions across PVE remotes.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::sync::Arc;
+
+use anyhow::{Context, Error, bail};
+use serde_json::{Value, json};
+
+use proxmox_client::HttpApiClient;
+use proxmox_rest_server::WorkerTask;
+use proxmox_router::{Permission, Router, RpcEnvironment, http_bail};
+use proxmox_schema::api;
+
+use pdm_api_types::pve::{
+    BulkGuestDescriptionParams, BulkGuestTagParams, GuestTagAction, GuestTagUsage,
+};
+use pdm_api_types::remotes::{Remote, RemoteType};
+use pdm_api_types::resource::{GuestType, RemoteResources, Resource};
+use pdm_api_types::views::{FilterRule, StringMatcher, ViewConfigEntry};
+use pdm_api_types::{Authid, PRIV_RESOURCE_MODIFY, UPID, VIEW_ID_SCHEMA};
+
+use crate::acl::CachedUserInfo;
+use crate::connection;
+use crate::parallel_fetcher::ParallelFetcher;
+
+pub const ROUTER: Router = Router::new()
+    .get(&API_METHOD_LIST_TAGS)
+    .post(&API_METHOD_BULK_GUEST_TAGS);
+
+pub const DESCRIPTION_ROUTER: Router = Router::new().post(&API_METHOD_BULK_GUEST_DESCRIPTION);
+
+/// Maximum age of the cached resources used to look up the guests.
+const RESOURCE_MAX_AGE: u64 = 30;
+
+/// Default for the maximum number of parallel requests to all remotes.
+const DEFAULT_MAX_CONNECTIONS: usize = 10;
+/// Default for the maximum number of parallel requests to a single remote.
+const DEFAULT_MAX_CONNECTIONS_PER_REMOTE: usize = 2;
+
+#[derive(Clone)]
+struct BulkGuest {
+    id: String,
+    vmid: u32,
+    ty: GuestType,
+}
+
+/// A change applied to the configuration of every selected guest.
+enum GuestChange {
+    Tag {
+        action: GuestTagAction,
+        tag: String,
+        new_tag: Option<String>,
+    },
+    Description {
+        description: String,
+        append: bool,
+    },
+}
+
+impl GuestChange {
+    /// The configuration key which is changed.
+    fn key(&self) -> &'static str {
+        match self {
+            GuestChange::Tag { .. } => "tags",
+            GuestChange::Description { .. } => "description",
+        }
+    }
+
+    /// Compute the new value of the key from its current value. Returns `None` if it is
+    /// unchanged.
+    fn apply(&self, current: &str) -> Option<String> {
+        match self {
+            GuestChange::Tag {
+                action,
+                tag,
+                new_tag,
+            } => apply_tag_action(current, *action, tag, new_tag.as_deref()),
+            GuestChange::Description {
+                description,
+                append,
+            } => {
+                let new = match *append {
+                    true if description.is_empty() => return None,
+                    true if !current.is_empty() => format!("{current}\n{description}"),
+                    _ => description.clone(),
+                };
+                (new != current).then_some(new)
+            }
+        }
+    }
+}
+
+struct ChangeContext {
+    change: GuestChange,
+    // guests grouped by remote and node
+    guests: BTreeMap<(String, String), Vec<BulkGuest>>,
+}
+
+/// The guests selected for a bulk change.
+struct SelectedGuests {
+    // guests grouped by remote and node
+    guests: BTreeMap<(String, String), Vec<BulkGuest>>,
+    count: usize,
+    // requested guests which could not be found in the (permitted) resources
+    missing: Vec<String>,
+}
+
+type GuestResults = Vec<(String, Result<bool, Error>)>;
+
+fn guest_tags(resource: &Resource) -> Option<&[String]> {
+    match resource {
+        Resource::PveQemu(r) => Some(&r.tags),
+        Resource::PveLxc(r) => Some(&r.tags),
+        _ => None,
+    }
+}
+
+#[api(
+    input: {
+        properties: {
+            view: {
+                schema: VIEW_ID_SCHEMA,
+                optional: true,
+            },
+        },
+    },
+    returns: {
+        type: Array,
+        description: "The tags used by guests, sorted by name.",
+        items: { type: GuestTagUsage },
+    },
+    access: {
+        permission: &Permission::Anybody,
+        description: "Only guests with Resource.Audit privileges on \
+            /resource/{remote}/guest/{vmid}, or which are part of the view, are counted.",
+    },
+)]
+/// List the tags of the guests of all PVE remotes with their usage.
+async fn list_tags(
+    view: Option<String>,
+    rpcenv: &mut dyn RpcEnvironment,
+) -> Result<Vec<GuestTagUsage>, Error> {
+    let remote_resources = crate::api::resources::get_resources_impl(
+        RESOURCE_MAX_AGE,
+        None,
+        None,
+        view.as_deref(),
+        Some(rpcenv),
+    )
+    .await?;
+
+    Ok(tag_usage(remote_resources.into_iter().map(Into::into)))
+}
+
+fn tag_usage(remotes: impl Iterator<Item = RemoteResources>) -> Vec<GuestTagUsage> {
+    let mut usage: BTreeMap<String, (u64, BTreeSet<String>)> = BTreeMap::new();
+
+    for remote in remotes {
+        for resource in &remote.resources {
+            for tag in guest_tags(resource).into_iter().flatten() {
+                let (count, remotes) = usage.entry(tag.clone()).or_default();
+                *count += 1;
+                remotes.insert(remote.remote.clone());
+            }
+        }
+    }
+
+    usage
+        .into_iter()
+        .map(|(tag, (count, remotes))| GuestTagUsage {
+            tag,
+            count,
+            remotes: remotes.into_iter().collect(),
+        })
+        .collect()
+}
+
+/// Look up the guests selected by their resource IDs or a search expression.
+///
+/// If neither is given, all guests carrying `with_tag` are selected. Resource.Modify is required
+/// on every selected guest.
+async fn select_guests(
+    guests: Option<Vec<String>>,
+    search: Option<String>,
+    with_tag: Option<&str>,
+    auth_id: &Authid,
+    rpcenv: &mut dyn RpcEnvironment,
+) -> Result<SelectedGuests, Error> {
+    let everywhere = guests.is_none() && search.is_none();
+    let mut requested: BTreeSet<String> = guests.unwrap_or_default().into_iter().collect();
+    let has_guest_list = !requested.is_empty();
+
+    let remote_resources = crate::api::resources::get_resources_impl(
+        RESOURCE_MAX_AGE,
+        search,
+        None,
+        None,
+        Some(rpcenv),
+    )
+    .await?;
+
+    let user_info = CachedUserInfo::new()?;
+    let mut selected = SelectedGuests {
+        guests: BTreeMap::new(),
+        count: 0,
+        missing: Vec::new(),
+    };
+
+    for remote in remote_resources {
+        let remote: RemoteResources = remote.into();
+        for resource in remote.resources {
+            if everywhere
+                && !with_tag.is_some_and(|with_tag| {
+                    guest_tags(&resource).is_some_and(|tags| tags.iter().any(|t| t == with_tag))
+                })
+            {
+                continue;
+            }
+
+            let (id, node, vmid, ty) = match resource {
+                Resource::PveQemu(r) => (r.id, r.node, r.vmid, GuestType::Qemu),
+                Resource::PveLxc(r) => (r.id, r.node, r.vmid, GuestType::Lxc),
+                _ => continue,
+            };
+
+            if has_guest_list && !requested.remove(&id) {
+                continue;
+            }
+
+            let vmid_str = vmid.to_string();
+            if user_info
+                .check_privs(
+                    auth_id,
+                    &["resource", &remote.remote, "guest", &vmid_str],
+                    PRIV_RESOURCE_MODIFY,
+                    false,
+                )
+                .is_err()
+            {
+                http_bail!(FORBIDDEN, "missing permissions for guest '{id}'");
+            }
+
+            selected
+                .guests
+                .entry((remote.remote.clone(), node))
+                .or_default()
+                .push(BulkGuest { id, vmid, ty });
+            selected.count += 1;
+        }
+    }
+
+    selected.missing = requested.into_iter().collect();
+
+    if selected.count == 0 && selected.missing.is_empty() {
+        http_bail!(BAD_REQUEST, "no guests matched");
+    }
+
+    Ok(selected)
+}
+
+/// The PVE remotes with selected guests.
+fn selected_remotes(
+    guests: &BTreeMap<(String, String), Vec<BulkGuest>>,
+) -> Result<Vec<Remote>, Error> {
+    let (remotes_config, _) = pdm_config::remotes::config()?;
+    Ok(remotes_config
+        .into_iter()
+        .filter(|(name, remote)| {
+            remote.ty == RemoteType::Pve && guests.keys().any(|(remote, _)| remote == name)
+        })
+        .map(|(_, remote)| remote)
+        .collect())
+}
+
+#[api(
+    input: {
+        properties: {
+            params: {
+                type: BulkGuestTagParams,
+                flatten: true,
+            },
+        },
+    },
+    access: {
+        permission: &Permission::Anybody,
+        description: "Resource.Modify privileges are needed on /resource/{remote}/guest/{vmid} \
+            for every affected guest. When renaming, only views with Resource.Modify on \
+            /view/{view} are updated.",
+    },
+    returns: { type: UPID },
+)]
+/// Add, remove or rename a tag on many guests at once.
+///
+/// The returned worker task reports the outcome for each guest. Renaming a tag also renames it in
+/// the exact tag filter rules of the views. If only some guests are renamed, the rules for the new
+/// tag are added next to the existing ones instead.
+async fn bulk_guest_tags(
+    params: BulkGuestTagParams,
+    rpcenv: &mut dyn RpcEnvironment,
+) -> Result<UPID, Error> {
+    let auth_id: Authid = rpcenv
+        .get_auth_id()
+        .context("no authid available")?
+        .parse()?;
+
+    let everywhere = params.guests.is_none() && params.search.is_none();
+    match params.action {
+        GuestTagAction::Add if everywhere => {
+            http_bail!(BAD_REQUEST, "either 'guests' or 'search' has to be set");
+        }
+        GuestTagAction::Rename if params.new_tag.is_none() => {
+            http_bail!(BAD_REQUEST, "renaming a tag requires 'new-tag'");
+        }
+        _ => (),
+    }
+
+    let selected = select_guests(
+        params.guests,
+        params.search,
+        Some(&params.tag),
+        &auth_id,
+        rpcenv,
+    )
+    .await?;
+    let remotes = selected_remotes(&selected.guests)?;
+
+    let (action, tag, new_tag) = (params.action, params.tag, params.new_tag);
+    let context = Arc::new(ChangeContext {
+        change: GuestChange::Tag {
+            action,
+            tag: tag.clone(),
+            new_tag: new_tag.clone(),
+        },
+        guests: selected.guests,
+    });
+
+    let fetcher = ParallelFetcher::builder(Arc::clone(&context))
+        .max_connections(params.max_connections.unwrap_or(DEFAULT_MAX_CONNECTIONS))
+        .max_connections_per_remote(
+            params
+                .max_connections_per_remote
+                .unwrap_or(DEFAULT_MAX_CONNECTIONS_PER_REMOTE),
+        )
+        .build();
+
+    let upid_str = WorkerTask::spawn(
+        "bulk-guest-tags",
+        Some(action.to_string()),
+        auth_id.to_string(),
+        true,
+        move |_worker| async move {
+            let guest_count = selected.count;
+
+            match &new_tag {
+                Some(new_tag) => {
+                    log::info!("renaming tag '{tag}' to '{new_tag}' on {guest_count} guest(s)")
+                }
+                None => log::info!(
+                    "{} tag '{tag}' on {guest_count} guest(s)",
+                    match action {
+                        GuestTagAction::Add => "adding",
+                        _ => "removing",
+                    },
+                ),
+            }
+
+            let failed = change_all_guests(fetcher, remotes, context, &selected.missing).await;
+
+            if let (GuestTagAction::Rename, Some(new_tag)) = (action, new_tag) {
+                let keep_old = !everywhere || failed > 0;
+                tokio::task::spawn_blocking(move || {
+                    rename_view_tags(&auth_id, &tag, &new_tag, keep_old)
+                })
+                .await??;
+            }
+
+            let total = guest_count + selected.missing.len();
+            if failed > 0 {
+                bail!("changing tags failed for {failed} of {total} guest(s)");
+            }
+
+            log::info!("changed tags of all {total} guest(s)");
+
+            Ok(())
+        },
+    )?;
+
+    upid_str.parse()
+}
+
+#[api(
+    input: {
+        properties: {
+            params: {
+                type: BulkGuestDescriptionParams,
+                flatten: true,
+            },
+        },
+    },
+    access: {
+        permission: &Permission::Anybody,
+        description: "Resource.Modify privileges are needed on /resource/{remote}/guest/{vmid} \
+            for every affected guest.",
+    },
+    returns: { type: UPID },
+)]
+/// Set or extend the description of many guests at once.
+///
+/// The returned worker task reports the outcome for each guest.
+async fn bulk_guest_description(
+    params: BulkGuestDescriptionParams,
+    rpcenv: &mut dyn RpcEnvironment,
+) -> Result<UPID, Error> {
+    let auth_id: Authid = rpcenv
+        .get_auth_id()
+        .context("no authid available")?
+        .parse()?;
+
+    if params.guests.is_none() && params.search.is_none() {
+        http_bail!(BAD_REQUEST, "either 'guests' or 'search' has to be set");
+    }
+
+    let selected = select_guests(params.guests, params.search, None, &auth_id, rpcenv).await?;
+    let remotes = selected_remotes(&selected.guests)?;
+
+    let context = Arc::new(ChangeContext {
+        change: GuestChange::Description {
+            description: params.description,
+            append: params.append,
+        },
+        guests: selected.guests,
+    });
+
+    let fetcher = ParallelFetcher::builder(Arc::clone(&context))
+        .max_connections(params.max_connections.unwrap_or(DEFAULT_MAX_CONNECTIONS))
+        .max_connections_per_remote(
+            params
+                .max_connections_per_remote
+                .unwrap_or(DEFAULT_MAX_CONNECTIONS_PER_REMOTE),
+        )
+        .build();
+
+    let upid_str = WorkerTask::spawn(
+        "bulk-guest-description",
+        None,
+        auth_id.to_string(),
+        true,
+        move |_worker| async move {
+            let guest_count = selected.count;
+            log::info!(
+                "{} description of {guest_count} guest(s)",
+                if params.append {
+                    "extending"
+                } else {
+                    "setting"
+                },
+            );
+
+            let failed = change_all_guests(fetcher, remotes, context, &selected.missing).await;
+
+            let total = guest_count + selected.missing.len();
+            if failed > 0 {
+                bail!("changing the description failed for {failed} of {total} guest(s)");
+            }
+
+            log::info!("changed the description of all {total} guest(s)");
+
+            Ok(())
+        },
+    )?;
+
+    upid_str.parse()
+}
+
+/// Apply the change of the context to all its guests and log the outcome for every guest.
+///
+/// Returns the number of guests which could not be changed, including the `missing` ones.
+async fn change_all_guests(
+    fetcher: ParallelFetcher<Arc<ChangeContext>>,
+    remotes: Vec<Remote>,
+    context: Arc<ChangeContext>,
+    missing: &[String],
+) -> usize {
+    let response = fetcher
+        .do_for_all_remote_nodes(remotes.into_iter(), change_guests_on_node)
+        .await;
+
+    let mut results: HashMap<String, Result<bool, Error>> = HashMap::new();
+    for remote_response in response {
+        let (remote, node_responses) = remote_response.into_remote_and_nodes();
+        let node_responses = match node_responses {
+            Ok(node_responses) => node_responses,
+            Err(err) => {
+                log::error!("could not connect to remote '{remote}': {err:#}");
+                continue;
+            }
+        };
+
+        for node_response in node_responses {
+            let node = node_response.node_name().to_string();
+            match node_response.into_data() {
+                Ok(data) => results.extend(data),
+                Err(err) => {
+                    log::error!("could not connect to node '{node}' of '{remote}': {err:#}");
+                }
+            }
+        }
+    }
+
+    let mut failed = 0;
+    for guest in context.guests.values().flatten() {
+        match results.remove(&guest.id) {
+            Some(Ok(true)) => log::info!("{}: OK", guest.id),
+            Some(Ok(false)) => log::info!("{}: OK - unchanged", guest.id),
+            Some(Err(err)) => {
+                log::error!("{}: failed - {err:#}", guest.id);
+                failed += 1;
+            }
+            None => {
+                log::error!("{}: failed - node of the guest not reachable", guest.id);
+                failed += 1;
+            }
+        }
+    }
+
+    for id in missing {
+        log::error!("{id}: failed - no such guest");
+        failed += 1;
+    }
+
+    failed
+}
+
+async fn change_guests_on_node(
+    context: Arc<ChangeContext>,
+    remote: Remote,
+    node: String,
+) -> Result<GuestResults, Error> {
+    let Some(guests) = context.guests.get(&(remote.id.clone(), node.clone())) else {
+        return Ok(Vec::new());
+    };
+
+    let client = connection::make_raw_client(&remote)?;
+
+    let mut results = Vec::with_capacity(guests.len());
+    for guest in guests {
+        let result = change_guest(client.as_ref(), &node, guest, &context.change).await;
+        results.push((guest.id.clone(), result));
+    }
+
+    Ok(results)
+}
+
+/// Change the configuration of a single guest. Returns whether the configuration was changed.
+async fn change_guest(
+    client: &impl HttpApiClient,
+    node: &str,
+    guest: &BulkGuest,
+    change: &GuestChange,
+) -> Result<bool, Error> {
+    let ty = match guest.ty {
+        GuestType::Qemu => "qemu",
+        GuestType::Lxc => "lxc",
+    };
+    let path = format!("/api2/extjs/nodes/{node}/{ty}/{}/config", guest.vmid);
+
+    let config: Value = client.get(&path).await?.expect_json()?.data;
+    let key = change.key();
+    let current = config[key].as_str().unwrap_or_default();
+
+    let Some(new_value) = change.apply(current) else {
+        return Ok(false);
+    };
+
+    let mut params = json!({ "digest": config["digest"] });
+    if new_value.is_empty() {
+        params["delete"] = key.into();
+    } else {
+        params[key] = new_value.into();
+    }
+    client.put(&path, &params).await?.nodata()?;
+
+    Ok(true)
+}
+
+/// Rename `tag` to `new_tag` in the exact tag filter rules of all views the user may modify.
+///
+/// With `keep_old`, rules for `new_tag` are added next to the rules for `tag` instead. Glob and
+/// regex rules are left alone.
+fn rename_view_tags(
+    auth_id: &Authid,
+    tag: &str,
+    new_tag: &str,
+    keep_old: bool,
+) -> Result<(), Error> {
+    let user_info = CachedUserInfo::new()?;
+
+    let _lock = pdm_config::views::lock_config()?;
+    let (mut config, _) = pdm_config::views::config()?;
+
+    let ids: Vec<String> = config.iter().map(|(id, _)| id.to_string()).collect();
+    let mut changed = false;
+
+    for id in ids {
+        let Some(ViewConfigEntry::View(view)) = config.get_mut(&id) else {
+            continue;
+        };
+
+        if !view
+            .include
+            .iter()
+            .chain(view.exclude.iter())
+            .any(|rule| is_exact_tag_rule(rule, tag))
+        {
+            continue;
+        }
+
+        if user_info.lookup_privs(auth_id, &["view", &id]) & PRIV_RESOURCE_MODIFY == 0 {
+            log::warn!("not updating the tag rules of view '{id}' - missing permissions");
+            continue;
+        }
+
+        rename_tag_rules(&mut view.include, tag, new_tag, keep_old);
+        rename_tag_rules(&mut view.exclude, tag, new_tag, keep_old);
+        log::info!("updated the tag rules of view '{id}'");
+        changed = true;
+    }
+
+    if changed {
+        pdm_config::views::save_config(&config)?;
+    }
+
+    Ok(())
+}
+
+fn is_exact_tag_rule(rule: &FilterRule, tag: &str) -> bool {
+    matches!(rule, FilterRule::Tag(StringMatcher::Exact(value)) if value == tag)
+}
+
+/// Replace the exact filter rules for `tag` with a rule for `new_tag`, or add it next to them with
+/// `keep_old`.
+fn rename_tag_rules(rules: &mut Vec<FilterRule>, tag: &str, new_tag: &str, keep_old: bool) {
+    let new_rule = FilterRule::Tag(StringMatcher::Exact(new_tag.to_string()));
+    let mut result = Vec::with_capacity(rules.len() + 1);
+
+    for rule in std::mem::take(rules) {
+        if is_exact_tag_rule(&rule, tag) {
+            if keep_old {
+                result.push(rule);
+            }
+            if !result.contains(&new_rule) {
+                result.push(new_rule.clone());
+            }
+        } else if rule != new_rule || !result.contains(&new_rule) {
+            result.push(rule);
+        }
+    }
+
+    *rules = result;
+}
+
+/// Apply a tag action to a PVE tag list. Returns the new tag list if it changed.
+fn apply_tag_action(
+    tags: &str,
+    action: GuestTagAction,
+    tag: &str,
+    new_tag: Option<&str>,
+) -> Option<String> {
+    let mut list: Vec<&str> = tags
+        .split([';', ',', ' '])
+        .filter(|tag| !tag.is_empty())
+        .collect();
+    let has_tag = list.contains(&tag);
+
+    match (action, new_tag) {
+        (GuestTagAction::Add, _) if !has_tag => list.push(tag),
+        (GuestTagAction::Remove, _) if has_tag => list.retain(|t| *t != tag),
+        (GuestTagAction::Rename, Some(new_tag)) if has_tag => {
+            if list.contains(&new_tag) {
+                list.retain(|t| *t != tag);
+            } else {
+                for t in list.iter_mut().filter(|t| **t == tag) {
+                    *t = new_tag;
+                }
+            }
+        }
+        _ => return None,
+    }
+
+    Some(list.join(";"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tag_actions() {
+        let add = GuestTagAction::Add;
+        let remove = GuestTagAction::Remove;
+        let rename = GuestTagAction::Rename;
+
+        assert_eq!(
+            apply_tag_action("", add, "web", None).as_deref(),
+            Some("web")
+        );
+        assert_eq!(
+            apply_tag_action("db;prod", add, "web", None).as_deref(),
+            Some("db;prod;web")
+        );
+        assert_eq!(apply_tag_action("db;web", add, "web", None), None);
+
+        assert_eq!(
+            apply_tag_action("db,web prod", remove, "web", None).as_deref(),
+            Some("db;prod")
+        );
+        assert_eq!(
+            apply_tag_action("web", remove, "web", None).as_deref(),
+            Some("")
+        );
+        assert_eq!(apply_tag_action("db", remove, "web", None), None);
+
+        assert_eq!(
+            apply_tag_action("db;legacy;prod", rename, "legacy", Some("eol")).as_deref(),
+            Some("db;eol;prod")
+        );
+        assert_eq!(
+            apply_tag_action("eol;legacy", rename, "legacy", Some("eol")).as_deref(),
+            Some("eol")
+        );
+        assert_eq!(apply_tag_action("db", rename, "legacy", Some("eol")), None);
+    }
+
+    #[test]
+    fn description_changes() {
+        let set = GuestChange::Description {
+            description: "new".into(),
+            append: false,
+        };
+        assert_eq!(set.apply("old").as_deref(), Some("new"));
+        assert_eq!(set.apply("new"), None);
+
+        let append = GuestChange::Description {
+            description: "more".into(),
+            append: true,
+        };
+        assert_eq!(append.apply("old").as_deref(), Some("old\nmore"));
+        assert_eq!(append.apply("").as_deref(), Some("more"));
+
+        let clear = GuestChange::Description {
+            description: String::new(),
+            append: false,
+        };
+        assert_eq!(clear.apply("old").as_deref(), Some(""));
+        assert_eq!(clear.apply(""), None);
+    }
+
+    #[test]
+    fn view_tag_rules() {
+        let rules = |list: &[&str]| -> Vec<FilterRule> {
+            list.iter().map(|rule| rule.parse().unwrap()).collect()
+        };
+
+        let mut include = rules(&["tag=legacy", "glob:tag=legacy*", "remote=pve"]);
+        rename_tag_rules(&mut include, "legacy", "eol", false);
+        assert_eq!(
+            include,
+            rules(&["tag=eol", "glob:tag=legacy*", "remote=pve"])
+        );
+
+        let mut include = rules(&["tag=legacy", "remote=pve"]);
+        rename_tag_rules(&mut include, "legacy", "eol", true);
+        assert_eq!(include, rules(&["tag=legacy", "tag=eol", "remote=pve"]));
+
+        // an existing rule for the new tag is not duplicated
+        let mut include = rules(&["tag=eol", "tag=legacy"]);
+        rename_tag_rules(&mut include, "legacy", "eol", false);
+        assert_eq!(include, rules(&["tag=eol"]));
+
+        let mut include = rules(&["tag=legacy", "tag=eol"]);
+        rename_tag_rules(&mut include, "legacy", "eol", false);
+        assert_eq!(include, rules(&["tag=eol"]));
+    }
+
+    #[test]
+    fn usage() {
+        let remotes: Vec<RemoteResources> = serde_json::from_value(json!([
+            {
+                "remote": "pve-a",
+                "resources": [
+                    {
+                        "type": "pve-qemu", "id": "remote/pve-a/guest/100", "vmid": 100,
+                        "name": "a", "node": "n1", "pool": "", "status": "running",
+                        "template": false, "uptime": 0, "cpu": 0.0, "maxcpu": 1.0,
+                        "disk": 0, "maxdisk": 0, "mem": 0, "maxmem": 0,
+                        "tags": ["legacy", "web"],
+                    },
+                    {
+                        "type": "pve-lxc", "id": "remote/pve-a/guest/101", "vmid": 101,
+                        "name": "b", "node": "n1", "pool": "", "status": "running",
+                        "template": false, "uptime": 0, "cpu": 0.0, "maxcpu": 1.0,
+                        "disk": 0, "maxdisk": 0, "mem": 0, "maxmem": 0,
+                        "tags": ["web"],
+                    },
+                ],
+            },
+            {
+                "remote": "pve-b",
+                "resources": [
+                    {
+                        "type": "pve-qemu", "id": "remote/pve-b/guest/100", "vmid": 100,
+                        "name": "c", "node": "n1", "pool": "", "status": "stopped",
+                        "template": false, "uptime": 0, "cpu": 0.0, "maxcpu": 1.0,
+                        "disk": 0, "maxdisk": 0, "mem": 0, "maxmem": 0,
+                        "tags": ["web"],
+                    },
+                ],
+            },
+        ]))
+        .unwrap();
+
+        let usage = tag_usage(remotes.into_iter());
+        assert_eq!(
+            usage,
+            vec![
+                GuestTagUsage {
+                    tag: "legacy".into(),
+                    count: 1,
+                    remotes: vec!["pve-a".into()],
+                },
+                GuestTagUsage {
+                    tag: "web".into(),
+                    count: 3,
+                    remotes: vec!["pve-a".into(), "pve-b".into()],
+                },
+            ]
+        );
+    }
+}
@@ -6,7 +6,6 @@ use std::error::Error as _;
 use anyhow::{Context, Error, bail, format_err};
 use serde::{Deserialize, Serialize};
 
-use proxmox_access_control::CachedUserInfo;
 use proxmox_router::{
     Permission, Router, RpcEnvironment, SubdirMap, http_bail, http_err, list_subdirs_api_method,
 };
@@ -19,21 +18,25 @@ use proxmox_sortable_macro::sortable;
 use proxmox_time::{epoch_i64, epoch_to_rfc2822};
 
 use pdm_api_types::remotes::{
-    REMOTE_ID_SCHEMA, Remote, RemoteType, RemoteUpdater, TlsProbeOutcome,
+    REMOTE_ID_SCHEMA, REMOTE_TFA_CHALLENGE_SCHEMA, REMOTE_TFA_RESPONSE_SCHEMA, Remote,
+    RemoteTfaChallenge, RemoteType, RemoteUpdater, TlsProbeOutcome, folder_acl_path,
 };
 use pdm_api_types::rrddata::RemoteDatapoint;
 use pdm_api_types::{Authid, ConfigDigest, PRIV_RESOURCE_AUDIT, PRIV_RESOURCE_MODIFY};
 
+use crate::acl::CachedUserInfo;
 use crate::metric_collection::trigger_remote_metric_collection;
-use crate::{connection, pbs_client};
+use crate::{connection, pbs_client, pmg_client};
 
 use super::pve;
 use super::rrd_common;
 use super::rrd_common::DataPoint;
 
 pub(crate) mod metric_collection;
+pub(crate) mod quick_add;
 pub(crate) mod shell;
 pub(crate) mod tasks;
+pub(crate) mod token_rotation;
 pub(crate) mod updates;
 
 pub const ROUTER: Router = Router::new()
@@ -42,7 +45,9 @@ pub const ROUTER: Router = Router::new()
 
 #[sortable]
 const SUBDIRS: SubdirMap = &sorted!([
+    ("quick-add", &quick_add::ROUTER),
     ("remote", &REMOTE_ROUTER),
+    ("rotate-tokens", &token_rotation::ALL_ROUTER),
     ("updates", &updates::ROUTER),
     ("tasks", &tasks::ROUTER),
     ("metric-collection", &metric_collection::ROUTER),
@@ -71,6 +76,7 @@ const REMOTE_SUBDIRS: SubdirMap = &sorted!([
         "rrddata",
         &Router::new().get(&API_METHOD_GET_PER_REMOTE_RRD_DATA)
     ),
+    ("rotate-token", &token_rotation::ROUTER),
 ]);
 
 pub fn get_remote<'a>(
@@ -257,10 +263,24 @@ const CREATE_TOKEN_SCHEMA: Schema = pdm_api_types::PROXMOX_TOKEN_NAME_SCHEMA
                 optional: true,
                 schema: CREATE_TOKEN_SCHEMA,
             },
+            "tfa-challenge": {
+                schema: REMOTE_TFA_CHALLENGE_SCHEMA,
+                optional: true,
+            },
+            "tfa-response": {
+                schema: REMOTE_TFA_RESPONSE_SCHEMA,
+                optional: true,
+            },
         },
     },
     access: {
         permission: &Permission::Privilege(&["resource"], PRIV_RESOURCE_MODIFY, false),
+        description: "Adding the remote to a folder additionally requires `Resource.Modify` on \
+            `/resource/folder/{folder}`.",
+    },
+    returns: {
+        type: RemoteTfaChallenge,
+        optional: true,
     },
 )]
 /// Add a new remote to manage.
@@ -268,7 +288,39 @@ const CREATE_TOKEN_SCHEMA: Schema = pdm_api_types::PROXMOX_TOKEN_NAME_SCHEMA
 /// If `create-token` is specified, a new API token is generated on the target
 /// remote with the given name and used instead of the existing authentication
 /// details in the entry.
-pub async fn add_remote(mut entry: Remote, create_token: Option<String>) -> Result<(), Error> {
+///
+/// If logging in to create the token requires a second factor, nothing is added and the TFA
+/// challenge is returned. Repeat the request with the response to it to add the remote.
+pub async fn add_remote(
+    mut entry: Remote,
+    create_token: Option<String>,
+    tfa_challenge: Option<String>,
+    tfa_response: Option<String>,
+    rpcenv: &mut dyn RpcEnvironment,
+) -> Result<Option<RemoteTfaChallenge>, Error> {
+    // privileges on the folder are passed on to the remote
+    if let Some(folder) = &entry.folder {
+        let auth_id: Authid = rpcenv
+            .get_auth_id()
+            .context("no authid available")?
+            .parse()?;
+        check_folder_privs(&auth_id, folder)?;
+    }
+
+    if entry.ty == RemoteType::Pmg && (create_token.is_some() || entry.authid.is_token()) {
+        http_bail!(BAD_REQUEST, "{}", pmg_client::NO_API_TOKENS);
+    }
+
+    // log in before locking the config, the user might need a while for the second factor
+    if create_token.is_some() {
+        let challenge =
+            connection::login_remote(&entry, tfa_challenge.as_deref(), tfa_response.as_deref())
+                .await?;
+        if challenge.is_some() {
+            return Ok(challenge);
+        }
+    }
+
     let _lock = pdm_config::remotes::lock_config()?;
     let (mut remotes, _) = pdm_config::remotes::config()?;
 
@@ -326,11 +378,15 @@ pub async fn add_remote(mut entry: Remote, create_token: Option<String>) -> Resu
                     .map_err(short_create_err)?;
                 (token.tokenid.parse()?, token.value)
             }
+            RemoteType::Pmg => bail!("{}", pmg_client::NO_API_TOKENS),
         };
         entry.authid = authid;
         entry.token = token;
     }
 
+    // the age of passwords is not tracked, they are not rotated
+    entry.token_created = entry.authid.is_token().then(epoch_i64);
+
     let name = entry.id.clone();
     let is_pve = entry.ty == RemoteType::Pve;
     remotes.insert(entry.id.to_owned(), entry);
@@ -352,7 +408,7 @@ pub async fn add_remote(mut entry: Remote, create_token: Option<String>) -> Resu
         });
     }
 
-    Ok(())
+    Ok(None)
 }
 
 #[api()]
@@ -362,6 +418,24 @@ pub async fn add_remote(mut entry: Remote, create_token: Option<String>) -> Resu
 pub enum DeletableProperty {
     /// Delete the web-url property.
     WebUrl,
+    /// Delete the folder property.
+    Folder,
+    /// Delete the timeout property.
+    Timeout,
+    /// Delete the http-proxy property.
+    HttpProxy,
+    /// Delete the max-connections property.
+    MaxConnections,
+}
+
+/// Check `Resource.Modify` on a folder, its privileges are passed on to the remotes in it.
+fn check_folder_privs(auth_id: &Authid, folder: &str) -> Result<(), Error> {
+    CachedUserInfo::new()?.check_privs(
+        auth_id,
+        &folder_acl_path(folder),
+        PRIV_RESOURCE_MODIFY,
+        false,
+    )
 }
 
 // FIXME: Support `OneOf` in schema so we can use a derived Updater for all product types?
@@ -390,6 +464,8 @@ pub enum DeletableProperty {
     },
     access: {
         permission: &Permission::Privilege(&["resource", "{id}"], PRIV_RESOURCE_MODIFY, false),
+        description: "Moving the remote into or out of a folder additionally requires \
+            `Resource.Modify` on that folder, `/resource/folder/{folder}`.",
     },
 )]
 /// Update an existing managed remote.
@@ -398,7 +474,13 @@ pub fn update_remote(
     updater: RemoteUpdater,
     delete: Option<Vec<DeletableProperty>>,
     digest: Option<ConfigDigest>,
+    rpcenv: &mut dyn RpcEnvironment,
 ) -> Result<(), Error> {
+    let auth_id: Authid = rpcenv
+        .get_auth_id()
+        .context("no authid available")?
+        .parse()?;
+
     let _lock = pdm_config::remotes::lock_config()?;
     let (mut remotes, config_digest) = pdm_config::remotes::config()?;
     config_digest.detect_modification(digest.as_ref())?;
@@ -407,12 +489,42 @@ pub fn update_remote(
         .get_mut(&id)
         .ok_or_else(|| http_err!(NOT_FOUND, "no such remote {id:?}"))?;
 
+    // privileges on the folder are passed on to the remote, so both the folder the remote is
+    // moved into and the one it is moved out of are checked
+    let delete_folder = delete
+        .iter()
+        .flatten()
+        .any(|prop| matches!(prop, DeletableProperty::Folder));
+    if let Some(folder) = &updater.folder {
+        check_folder_privs(&auth_id, folder)?;
+    }
+    let old_folder = match (&entry.folder, &updater.folder) {
+        (Some(old), Some(new)) if old != new => Some(old),
+        (Some(old), None) if delete_folder => Some(old),
+        _ => None,
+    };
+    if let Some(folder) = old_folder {
+        check_folder_privs(&auth_id, folder)?;
+    }
+
     if let Some(delete) = delete {
         for delete_prop in delete {
             match delete_prop {
                 DeletableProperty::WebUrl => {
                     entry.web_url = None;
                 }
+                DeletableProperty::Folder => {
+                    entry.folder = None;
+                }
+                DeletableProperty::Timeout => {
+                    entry.timeout = None;
+                }
+                DeletableProperty::HttpProxy => {
+                    entry.http_proxy = None;
+                }
+                DeletableProperty::MaxConnections => {
+                    entry.max_connections = None;
+                }
             }
         }
     }
@@ -421,15 +533,31 @@ pub fn update_remote(
         entry.nodes = v;
     }
     if let Some(v) = updater.authid {
+        if entry.ty == RemoteType::Pmg && v.is_token() {
+            http_bail!(BAD_REQUEST, "{}", pmg_client::NO_API_TOKENS);
+        }
         entry.authid = v;
     }
     if let Some(v) = updater.token {
         entry.token = v;
+        entry.token_created = entry.authid.is_token().then(epoch_i64);
     }
 
     if updater.web_url.is_some() {
         entry.web_url = updater.web_url;
     }
+    if updater.folder.is_some() {
+        entry.folder = updater.folder;
+    }
+    if updater.timeout.is_some() {
+        entry.timeout = updater.timeout;
+    }
+    if updater.http_proxy.is_some() {
+        entry.http_proxy = updater.http_proxy;
+    }
+    if updater.max_connections.is_some() {
+        entry.max_connections = updater.max_connections;
+    }
 
     pdm_config::remotes::save_config(remotes)?;
 
@@ -501,6 +629,10 @@ pub async fn remove_remote(id: String, delete_token: bool) -> Result<(), Error>
             format_err!("error deleting token: {}", err.source().unwrap_or(&err))
         };
 
+        if remote.ty == RemoteType::Pmg {
+            http_bail!(BAD_REQUEST, "{}", pmg_client::NO_API_TOKENS);
+        }
+
         let token_name = remote
             .authid
             .tokenname()
@@ -522,6 +654,7 @@ pub async fn remove_remote(id: String, delete_token: bool) -> Result<(), Error>
                     .await
                     .map_err(short_delete_err)?
             }
+            RemoteType::Pmg => bail!("{}", pmg_client::NO_API_TOKENS),
         };
     }
 
@@ -563,6 +696,7 @@ pub async fn version(id: String) -> Result<pve_api_types::VersionResponse, Error
     match remote.ty {
         RemoteType::Pve => Ok(connection::make_pve_client(remote)?.version().await?),
         RemoteType::Pbs => Ok(connection::make_pbs_client(remote)?.version().await?),
+        RemoteType::Pmg => Ok(connection::make_pmg_client(remote)?.version().await?),
     }
 }
 
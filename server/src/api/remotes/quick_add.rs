@@ -0,0 +1,189 @@
+//! Add a remote from pasted join information.
+
+use anyhow::{Error, format_err};
+
+use proxmox_router::{Permission, Router, RpcEnvironment, http_bail, http_err};
+use proxmox_schema::api;
+use proxmox_schema::property_string::PropertyString;
+use proxmox_time::{epoch_i64, epoch_to_rfc2822};
+
+use pdm_api_types::PRIV_RESOURCE_MODIFY;
+use pdm_api_types::remotes::{NodeUrl, REMOTE_ID_SCHEMA, Remote, RemoteJoinInfo, TlsProbeOutcome};
+
+use crate::{connection, remote_tokens};
+
+use super::CREATE_TOKEN_SCHEMA;
+
+pub const ROUTER: Router = Router::new().post(&API_METHOD_QUICK_ADD_REMOTE);
+
+/// Check that the fingerprint of a node's certificate matches the join information.
+fn verify_fingerprint(node: &NodeUrl, actual: Option<&str>) -> Result<String, Error> {
+    let Some(expected) = &node.fingerprint else {
+        http_bail!(
+            BAD_REQUEST,
+            "certificate of node '{}' is not trusted and the join information contains no \
+            fingerprint for it",
+            node.hostname
+        );
+    };
+
+    match actual {
+        Some(actual) if actual.eq_ignore_ascii_case(expected) => Ok(actual.to_string()),
+        actual => http_bail!(
+            BAD_REQUEST,
+            "fingerprint mismatch for node '{}' - expected {expected}, got {}",
+            node.hostname,
+            actual.unwrap_or("none"),
+        ),
+    }
+}
+
+#[api(
+    input: {
+        properties: {
+            id: { schema: REMOTE_ID_SCHEMA },
+            "join-info": {
+                type: String,
+                description: "The join information, as base64 encoded or plain JSON.",
+            },
+            "create-token": {
+                schema: CREATE_TOKEN_SCHEMA,
+                optional: true,
+            },
+        },
+    },
+    access: {
+        permission: &Permission::Privilege(&["resource"], PRIV_RESOURCE_MODIFY, false),
+    },
+)]
+/// Add a remote from pasted join information.
+///
+/// The certificate fingerprints of all nodes are verified against the join information, then
+/// the short-lived join token is used to create the API token which is stored for the remote.
+/// The new token gets the privileges of the join token, which is deleted afterwards. Defaults
+/// to a token named 'pdm-<nodename>'. This works for clusters and single nodes alike.
+pub async fn quick_add_remote(
+    id: String,
+    join_info: String,
+    create_token: Option<String>,
+    rpcenv: &mut dyn RpcEnvironment,
+) -> Result<(), Error> {
+    let info = RemoteJoinInfo::decode(&join_info).map_err(|err| http_err!(BAD_REQUEST, "{err}"))?;
+
+    if info.is_expired(epoch_i64()) {
+        http_bail!(BAD_REQUEST, "the join information expired");
+    }
+
+    let mut nodes = Vec::with_capacity(info.nodes.len());
+    for node in info.nodes {
+        let (outcome, leaf_fingerprint) =
+            connection::probe_tls_connection_fingerprint(info.ty, node.hostname.clone())
+                .await
+                .map_err(|err| {
+                    http_err!(
+                        BAD_REQUEST,
+                        "could not connect to node '{}' - {err}",
+                        node.hostname
+                    )
+                })?;
+
+        // trusted certificates don't need a pinned fingerprint, but must still match one given
+        let fingerprint = match outcome {
+            TlsProbeOutcome::TrustedCertificate => {
+                if node.fingerprint.is_some() {
+                    verify_fingerprint(&node, leaf_fingerprint.as_deref())?;
+                }
+                None
+            }
+            TlsProbeOutcome::UntrustedCertificate(_) => {
+                Some(verify_fingerprint(&node, leaf_fingerprint.as_deref())?)
+            }
+        };
+        nodes.push(PropertyString::new(NodeUrl {
+            hostname: node.hostname,
+            fingerprint,
+        }));
+    }
+
+    let entry = Remote {
+        ty: info.ty,
+        id,
+        nodes,
+        authid: info.authid,
+        token: info.secret,
+        web_url: None,
+        folder: None,
+        timeout: None,
+        http_proxy: None,
+        max_connections: None,
+        token_created: None,
+    };
+
+    let (remotes, _) = pdm_config::remotes::config()?;
+    if remotes.contains_key(&entry.id) {
+        http_bail!(BAD_REQUEST, "entry {:?} already exists", entry.id);
+    }
+
+    let nodename = proxmox_sys::nodename();
+    let name = create_token.unwrap_or_else(|| format!("pdm-{nodename}"));
+    let comment = Some(format!(
+        "auto-generated by PDM host '{nodename}' on {}",
+        epoch_to_rfc2822(epoch_i64())?
+    ));
+
+    let (authid, token) = remote_tokens::create_scoped_token(&entry, &name, comment)
+        .await
+        .map_err(|err| format_err!("error creating token: {err}"))?;
+
+    let new_entry = Remote {
+        authid: authid.clone(),
+        token,
+        ..entry.clone()
+    };
+
+    if let Err(err) = super::add_remote(new_entry.clone(), None, None, None, rpcenv).await {
+        if let Err(err) = remote_tokens::delete_token(&entry, &authid).await {
+            log::warn!("could not delete the new token '{authid}' again - {err}");
+        }
+        return Err(err);
+    }
+
+    // the join token is short-lived, but it should not outlive the join
+    if let Err(err) = remote_tokens::delete_token(&new_entry, &entry.authid).await {
+        log::warn!(
+            "could not delete join token '{}', please delete it manually - {err}",
+            entry.authid
+        );
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FINGERPRINT: &str = "9F:7A:AA:1B:3D:B5:49:62:9B:C5:0A:A2:4D:A1:4B:B9:\
+        6D:1B:C6:EC:2D:7C:C3:80:A6:12:E0:E6:EC:B8:49:74";
+
+    #[test]
+    fn verify_fingerprints() {
+        let node = NodeUrl {
+            hostname: "pve1".into(),
+            fingerprint: Some(FINGERPRINT.to_lowercase()),
+        };
+
+        assert_eq!(
+            verify_fingerprint(&node, Some(FINGERPRINT)).unwrap(),
+            FINGERPRINT
+        );
+        assert!(verify_fingerprint(&node, Some(&FINGERPRINT.replace("9F", "00"))).is_err());
+        assert!(verify_fingerprint(&node, None).is_err());
+
+        let node = NodeUrl {
+            hostname: "pve1".into(),
+            fingerprint: None,
+        };
+        assert!(verify_fingerprint(&node, Some(FINGERPRINT)).is_err());
+    }
+}
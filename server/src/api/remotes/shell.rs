@@ -264,7 +264,7 @@ macro_rules! upgrade_to_websocket_impl {
                 let ws_key = proxmox_sys::linux::random_data(16)?;
                 let ws_key = proxmox_base64::encode(&ws_key);
 
-                let api_url = raw_client.api_url().clone().into_parts();
+                let api_url = raw_client.client().api_url().clone().into_parts();
 
                 let mut builder = http::uri::Builder::new();
                 if let Some(scheme) = api_url.scheme {
@@ -279,7 +279,7 @@ macro_rules! upgrade_to_websocket_impl {
                     .build()
                     .map_err(|err| ::anyhow::format_err!("failed to build Uri - {err}"))?;
 
-                let auth = raw_client.login_auth()?;
+                let auth = raw_client.client().login_auth()?;
                 let req = http::Request::builder()
                     .method(http::Method::GET)
                     .uri(uri)
@@ -289,7 +289,7 @@ macro_rules! upgrade_to_websocket_impl {
 
                 let req = auth.set_auth_headers(req).body(Body::empty())?;
 
-                let res = raw_client.http_client().request(req).await?;
+                let res = raw_client.client().http_client().request(req).await?;
                 if res.status() != http::StatusCode::SWITCHING_PROTOCOLS {
                     bail!("server didn't upgrade: {}", res.status());
                 }
@@ -360,6 +360,11 @@ upgrade_to_websocket_impl! {
                 let pbs_term_ticket = pbs.node_shell_termproxy().await?;
                 (pbs_term_ticket.ticket, pbs_term_ticket.port as i64, (), true)
             }
+            RemoteType::Pmg => {
+                let pmg = crate::connection::make_pmg_client(remote)?;
+                let pmg_term_ticket = pmg.node_shell_termproxy(node).await?;
+                (pmg_term_ticket.ticket, pmg_term_ticket.port as i64, (), true)
+            }
         })
     },
     (|node: String, _: (), ticket: &str, port: i64| {
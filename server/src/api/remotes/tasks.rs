@@ -6,7 +6,6 @@ use pdm_api_types::{
     Authid, PRIV_RESOURCE_AUDIT, PRIV_RESOURCE_MODIFY, RemoteUpid, TaskCount, TaskFilters,
     TaskListItem, TaskStateType, TaskStatistics, UPID, VIEW_ID_SCHEMA, remotes::REMOTE_ID_SCHEMA,
 };
-use proxmox_access_control::CachedUserInfo;
 use proxmox_rest_server::WorkerTask;
 use proxmox_router::{
     Permission, Router, RpcEnvironment, SubdirMap, http_bail, http_err, list_subdirs_api_method,
@@ -14,6 +13,7 @@ use proxmox_router::{
 use proxmox_schema::api;
 use proxmox_sortable_macro::sortable;
 
+use crate::acl::CachedUserInfo;
 use crate::remote_tasks;
 
 pub const ROUTER: Router = Router::new()
@@ -0,0 +1,86 @@
+//! Manually rotate the API tokens of remotes, see [`crate::token_rotation`].
+
+use anyhow::{Context, Error};
+
+use proxmox_rest_server::WorkerTask;
+use proxmox_router::{Permission, Router, RpcEnvironment};
+use proxmox_schema::api;
+
+use pdm_api_types::remotes::REMOTE_ID_SCHEMA;
+use pdm_api_types::{Authid, PRIV_RESOURCE_MODIFY, UPID};
+
+use crate::token_rotation::{self, WORKER_TYPE};
+
+pub const ROUTER: Router = Router::new().post(&API_METHOD_ROTATE_TOKEN);
+
+pub const ALL_ROUTER: Router = Router::new().post(&API_METHOD_ROTATE_TOKENS);
+
+#[api(
+    input: {
+        properties: {
+            id: { schema: REMOTE_ID_SCHEMA },
+        },
+    },
+    returns: { type: UPID },
+    access: {
+        permission: &Permission::Privilege(&["resource", "{id}"], PRIV_RESOURCE_MODIFY, false),
+    },
+)]
+/// Rotate the API token of a remote now.
+///
+/// Creates a new token with the same privileges on the remote, replaces the old token with it
+/// once it was verified to work and deletes the old token.
+pub fn rotate_token(id: String, rpcenv: &mut dyn RpcEnvironment) -> Result<UPID, Error> {
+    let auth_id: Authid = rpcenv
+        .get_auth_id()
+        .context("no authid available")?
+        .parse()?;
+
+    // fail early instead of in the worker
+    let (remotes, _) = pdm_config::remotes::config()?;
+    super::get_remote(&remotes, &id)?;
+
+    let upid_str = WorkerTask::spawn(
+        WORKER_TYPE,
+        Some(id.clone()),
+        auth_id.to_string(),
+        true,
+        move |_worker| async move { token_rotation::rotate_token(&id).await },
+    )?;
+
+    upid_str.parse()
+}
+
+#[api(
+    input: {
+        properties: {
+            "max-age": {
+                description: "Only rotate tokens older than this many days.",
+                type: Integer,
+                minimum: 1,
+                optional: true,
+            },
+        },
+    },
+    returns: { type: UPID },
+    access: {
+        permission: &Permission::Privilege(&["resource"], PRIV_RESOURCE_MODIFY, false),
+    },
+)]
+/// Rotate the API tokens of all remotes now.
+pub fn rotate_tokens(max_age: Option<u64>, rpcenv: &mut dyn RpcEnvironment) -> Result<UPID, Error> {
+    let auth_id: Authid = rpcenv
+        .get_auth_id()
+        .context("no authid available")?
+        .parse()?;
+
+    let upid_str = WorkerTask::spawn(
+        WORKER_TYPE,
+        None,
+        auth_id.to_string(),
+        true,
+        move |_worker| async move { token_rotation::rotate_tokens(max_age).await },
+    )?;
+
+    upid_str.parse()
+}
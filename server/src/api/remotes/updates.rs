@@ -8,7 +8,6 @@ use pdm_api_types::{
     APTRepositoriesResult, NODE_SCHEMA, PRIV_RESOURCE_MODIFY, RemoteUpid, UPID,
     remote_updates::UpdateSummary, remotes::REMOTE_ID_SCHEMA,
 };
-use proxmox_access_control::CachedUserInfo;
 use proxmox_apt_api_types::{APTGetChangelogOptions, APTUpdateInfo};
 use proxmox_rest_server::WorkerTask;
 use proxmox_router::{
@@ -17,6 +16,7 @@ use proxmox_router::{
 use proxmox_schema::api;
 use proxmox_sortable_macro::sortable;
 
+use crate::acl::CachedUserInfo;
 use crate::{connection, remote_updates};
 
 use super::get_remote;
@@ -234,6 +234,10 @@ async fn get_apt_repositories(
             let client = connection::make_pbs_client(remote)?;
             client.get_apt_repositories().await?
         }
+        RemoteType::Pmg => {
+            let client = connection::make_pmg_client(remote)?;
+            client.get_apt_repositories(&node).await?
+        }
     })
 }
 
@@ -8,21 +8,20 @@ use futures::future::join_all;
 use pbs_api_types::{
     DataStoreStatusListItem, DatastoreBackendConfig, DatastoreBackendType, NodeStatus,
 };
-use pdm_api_types::remotes::{Remote, RemoteType};
+use pdm_api_types::remotes::{Remote, RemoteType, folder_and_parents};
 use pdm_api_types::resource::{
     FailedRemote, NetworkFabricResource, NetworkZoneResource, PBS_DATASTORE_HIGH_USAGE_THRESHOLD,
-    PbsDatastoreResource, PbsNodeResource, PveLxcResource, PveNetworkResource, PveNodeResource,
-    PveQemuResource, PveStorageResource, RemoteInfo, RemoteResources, RemoteStatus, Resource,
-    ResourceType, ResourcesStatus, SdnStatus, TopEntities,
+    PbsDatastoreResource, PbsNodeResource, PmgNodeResource, PveLxcResource, PveNetworkResource,
+    PveNodeResource, PveQemuResource, PveStorageResource, RemoteInfo, RemoteResources,
+    RemoteStatus, Resource, ResourceType, ResourcesStatus, SdnStatus, TopEntities,
 };
 use pdm_api_types::subscription::{
     NodeSubscriptionInfo, RemoteSubscriptionState, RemoteSubscriptions, SubscriptionLevel,
 };
 use pdm_api_types::{Authid, CachedLocationInfo, PRIV_RESOURCE_AUDIT, VIEW_ID_SCHEMA};
-use pdm_search::{Search, SearchTerm};
-use proxmox_access_control::CachedUserInfo;
+use pdm_search::{Expression, Operator, Search, SearchTerm};
 use proxmox_router::{
-    Permission, Router, RpcEnvironment, SubdirMap, http_bail, list_subdirs_api_method,
+    Permission, Router, RpcEnvironment, SubdirMap, http_bail, http_err, list_subdirs_api_method,
 };
 use proxmox_rrd_api_types::RrdTimeframe;
 use proxmox_schema::{api, parse_boolean};
@@ -31,7 +30,8 @@ use proxmox_subscription::SubscriptionStatus;
 use pve_api_types::{ClusterResource, ClusterResourceNetworkType, ClusterResourceType};
 use serde::{Deserialize, Serialize};
 
-use crate::metric_collection::top_entities;
+use crate::acl::CachedUserInfo;
+use crate::metric_collection::{forecast, top_entities};
 use crate::{api_cache, connection, views};
 
 pub const ROUTER: Router = Router::new()
@@ -65,8 +65,16 @@ enum MatchCategory {
     Template,
     Remote,
     RemoteType,
+    Folder,
     Property,
     View,
+    Cpu,
+    MaxCpu,
+    Mem,
+    MaxMem,
+    Disk,
+    MaxDisk,
+    Uptime,
 }
 
 impl std::str::FromStr for MatchCategory {
@@ -82,8 +90,16 @@ impl std::str::FromStr for MatchCategory {
             "template" => MatchCategory::Template,
             "remote" => MatchCategory::Remote,
             "remote-type" => MatchCategory::RemoteType,
+            "folder" => MatchCategory::Folder,
             "property" => MatchCategory::Property,
             "view" => MatchCategory::View,
+            "cpu" => MatchCategory::Cpu,
+            "maxcpu" => MatchCategory::MaxCpu,
+            "mem" => MatchCategory::Mem,
+            "maxmem" => MatchCategory::MaxMem,
+            "disk" => MatchCategory::Disk,
+            "maxdisk" => MatchCategory::MaxDisk,
+            "uptime" => MatchCategory::Uptime,
             _ => bail!("invalid category"),
         };
         Ok(category)
@@ -96,9 +112,10 @@ impl MatchCategory {
             MatchCategory::Type | MatchCategory::Status | MatchCategory::NetworkType => value
                 .to_lowercase()
                 .starts_with(&search_term.to_lowercase()),
-            MatchCategory::Name | MatchCategory::Id | MatchCategory::Remote => {
-                value.to_lowercase().contains(&search_term.to_lowercase())
-            }
+            MatchCategory::Name
+            | MatchCategory::Id
+            | MatchCategory::Remote
+            | MatchCategory::Folder => value.to_lowercase().contains(&search_term.to_lowercase()),
             MatchCategory::Template => match (parse_boolean(value), parse_boolean(search_term)) {
                 (Ok(a), Ok(b)) => a == b,
                 _ => false,
@@ -115,11 +132,151 @@ impl MatchCategory {
                 .split(",")
                 .any(|property| property == search_term.to_lowercase()),
             MatchCategory::View => true,
+            MatchCategory::Cpu
+            | MatchCategory::MaxCpu
+            | MatchCategory::Mem
+            | MatchCategory::MaxMem
+            | MatchCategory::Disk
+            | MatchCategory::MaxDisk
+            | MatchCategory::Uptime => false,
+        }
+    }
+
+    /// Like [`MatchCategory::matches`], but honors the operator of the search term.
+    ///
+    /// `=` requires the whole value to match, ordering operators never match text values.
+    fn matches_term(&self, value: &str, term: &SearchTerm) -> bool {
+        match term.operator {
+            Operator::Match => self.matches(value, &term.value),
+            Operator::Equal => match self {
+                MatchCategory::Type
+                | MatchCategory::Status
+                | MatchCategory::NetworkType
+                | MatchCategory::Name
+                | MatchCategory::Id
+                | MatchCategory::Remote => value.to_lowercase() == term.value.to_lowercase(),
+                // remotes in subfolders are contained in the folder as well
+                MatchCategory::Folder => folder_and_parents(value)
+                    .any(|folder| folder.to_lowercase() == term.value.to_lowercase()),
+                _ => self.matches(value, &term.value),
+            },
+            _ => false,
+        }
+    }
+
+    /// Compare a numeric resource value against the search term, parsing the term's value
+    /// in the unit of the category, e.g. `16G` for memory or `1h` for the uptime.
+    fn matches_numeric(&self, value: Option<f64>, term: &SearchTerm) -> bool {
+        let term_value = match self {
+            MatchCategory::Cpu | MatchCategory::MaxCpu => term.value_as_number(),
+            MatchCategory::Mem
+            | MatchCategory::MaxMem
+            | MatchCategory::Disk
+            | MatchCategory::MaxDisk => term.value_as_bytes(),
+            MatchCategory::Uptime => term.value_as_seconds(),
+            _ => None,
+        };
+
+        match (value, term_value) {
+            (Some(value), Some(term_value)) => term.operator.compare(value, term_value),
+            _ => false,
         }
     }
 }
 
-// returns None if we can't decide if it matches, currently only for the `RemoteType` category
+// returns the value of a numeric category for a resource, if the resource has such a value
+fn resource_numeric_value(resource: &Resource, category: &MatchCategory) -> Option<f64> {
+    let (cpu, maxcpu, mem, maxmem, disk, maxdisk, uptime) = match resource {
+        Resource::PveQemu(PveQemuResource {
+            cpu,
+            maxcpu,
+            mem,
+            maxmem,
+            disk,
+            maxdisk,
+            uptime,
+            ..
+        })
+        | Resource::PveLxc(PveLxcResource {
+            cpu,
+            maxcpu,
+            mem,
+            maxmem,
+            disk,
+            maxdisk,
+            uptime,
+            ..
+        }) => (
+            Some(*cpu),
+            Some(*maxcpu),
+            Some(*mem),
+            Some(*maxmem),
+            Some(*disk),
+            Some(*maxdisk),
+            Some(*uptime),
+        ),
+        Resource::PveNode(node) => (
+            Some(node.cpu),
+            Some(node.maxcpu),
+            Some(node.mem),
+            Some(node.maxmem),
+            None,
+            None,
+            Some(node.uptime),
+        ),
+        Resource::PbsNode(node) => (
+            Some(node.cpu),
+            Some(node.maxcpu),
+            Some(node.mem),
+            Some(node.maxmem),
+            None,
+            None,
+            Some(node.uptime),
+        ),
+        Resource::PmgNode(node) => (
+            Some(node.cpu),
+            Some(node.maxcpu),
+            Some(node.mem),
+            Some(node.maxmem),
+            None,
+            None,
+            Some(node.uptime),
+        ),
+        Resource::PveStorage(storage) => (
+            None,
+            None,
+            None,
+            None,
+            Some(storage.disk),
+            Some(storage.maxdisk),
+            None,
+        ),
+        Resource::PbsDatastore(datastore) => (
+            None,
+            None,
+            None,
+            None,
+            Some(datastore.disk),
+            Some(datastore.maxdisk),
+            None,
+        ),
+        Resource::PveNetwork(_) => (None, None, None, None, None, None, None),
+    };
+
+    match category {
+        MatchCategory::Cpu => cpu,
+        MatchCategory::MaxCpu => maxcpu,
+        MatchCategory::Mem => mem.map(|v| v as f64),
+        MatchCategory::MaxMem => maxmem.map(|v| v as f64),
+        MatchCategory::Disk => disk.map(|v| v as f64),
+        MatchCategory::MaxDisk => maxdisk.map(|v| v as f64),
+        MatchCategory::Uptime => uptime.map(|v| v as f64),
+        _ => None,
+    }
+}
+
+// returns None if we can't decide if it matches, currently only for the `RemoteType`, `Folder` and
+// `View` categories
 fn resource_matches_search_term(
     remote_name: &str,
     resource: &Resource,
@@ -127,27 +284,36 @@ fn resource_matches_search_term(
 ) -> Option<bool> {
     let matches = match term.category.as_deref().map(|c| c.parse::<MatchCategory>()) {
         Some(Ok(category)) => match category {
-            MatchCategory::Type => category.matches(resource.resource_type().as_str(), &term.value),
-            MatchCategory::Name => category.matches(resource.name(), &term.value),
-            MatchCategory::Id => category.matches(&resource.id(), &term.value),
-            MatchCategory::Status => category.matches(resource.status(), &term.value),
-            MatchCategory::Property => category.matches(&resource.properties(), &term.value),
+            MatchCategory::Type => category.matches_term(resource.resource_type().as_str(), term),
+            MatchCategory::Name => category.matches_term(resource.name(), term),
+            MatchCategory::Id => category.matches_term(&resource.id(), term),
+            MatchCategory::Status => category.matches_term(resource.status(), term),
+            MatchCategory::Property => category.matches_term(&resource.properties(), term),
             MatchCategory::Template => match resource {
                 Resource::PveQemu(PveQemuResource { template, .. })
                 | Resource::PveLxc(PveLxcResource { template, .. }) => {
-                    category.matches(&template.to_string(), &term.value)
+                    category.matches_term(&template.to_string(), term)
                 }
                 _ => false,
             },
-            MatchCategory::Remote => category.matches(remote_name, &term.value),
-            MatchCategory::RemoteType => return None,
+            MatchCategory::Remote => category.matches_term(remote_name, term),
+            MatchCategory::RemoteType | MatchCategory::Folder => return None,
             MatchCategory::NetworkType => match resource {
                 Resource::PveNetwork(network_resource) => {
-                    category.matches(network_resource.network_type().as_str(), &term.value)
+                    category.matches_term(network_resource.network_type().as_str(), term)
                 }
                 _ => false,
             },
             MatchCategory::View => return None,
+            MatchCategory::Cpu
+            | MatchCategory::MaxCpu
+            | MatchCategory::Mem
+            | MatchCategory::MaxMem
+            | MatchCategory::Disk
+            | MatchCategory::MaxDisk
+            | MatchCategory::Uptime => {
+                category.matches_numeric(resource_numeric_value(resource, &category), term)
+            }
         },
         Some(Err(_)) => false,
         None => {
@@ -166,20 +332,31 @@ fn remote_matches_search_term(
 ) -> bool {
     match term.category.as_deref().map(|c| c.parse::<MatchCategory>()) {
         Some(Ok(category)) => match category {
-            MatchCategory::Type => category.matches("remote", &term.value),
+            MatchCategory::Type => category.matches_term("remote", term),
             MatchCategory::Name | MatchCategory::Remote | MatchCategory::Id => {
-                category.matches(remote_name, &term.value)
+                category.matches_term(remote_name, term)
             }
             MatchCategory::Status => match online {
-                Some(true) => category.matches("online", &term.value),
-                Some(false) => category.matches("offline", &term.value),
+                Some(true) => category.matches_term("online", term),
+                Some(false) => category.matches_term("offline", term),
                 None => true,
             },
             MatchCategory::Property => false,
             MatchCategory::Template => false,
-            MatchCategory::RemoteType => category.matches(&remote.ty.to_string(), &term.value),
+            MatchCategory::RemoteType => category.matches_term(&remote.ty.to_string(), term),
+            MatchCategory::Folder => remote
+                .folder
+                .as_deref()
+                .is_some_and(|folder| category.matches_term(folder, term)),
             MatchCategory::NetworkType => false,
             MatchCategory::View => true,
+            MatchCategory::Cpu
+            | MatchCategory::MaxCpu
+            | MatchCategory::Mem
+            | MatchCategory::MaxMem
+            | MatchCategory::Disk
+            | MatchCategory::MaxDisk
+            | MatchCategory::Uptime => false,
         },
         Some(Err(_)) => false,
         None => {
@@ -189,10 +366,15 @@ fn remote_matches_search_term(
     }
 }
 
-fn remote_type_matches_search_term(remote_type: RemoteType, term: &SearchTerm) -> bool {
+// checks the terms about properties of the remote itself, which apply to all of its resources
+fn remote_properties_match_search_term(remote: &Remote, term: &SearchTerm) -> bool {
     match term.category.as_deref().map(|c| c.parse::<MatchCategory>()) {
         Some(Ok(category)) => match category {
-            MatchCategory::RemoteType => category.matches(&remote_type.to_string(), &term.value),
+            MatchCategory::RemoteType => category.matches_term(&remote.ty.to_string(), term),
+            MatchCategory::Folder => remote
+                .folder
+                .as_deref()
+                .is_some_and(|folder| category.matches_term(folder, term)),
             _ => true,
         },
         Some(Err(_)) => false,
@@ -256,7 +438,7 @@ fn check_all_remotes_allowed(
                 optional: true,
             },
             "search": {
-                description: "Search term to filter for, uses special syntax e.g. <TODO>",
+                description: "Search expression to filter for, e.g. 'type:qemu AND (cpu>0.8 OR mem>=16G)'.",
                 optional: true,
             },
             "resource-type": {
@@ -300,28 +482,29 @@ pub async fn get_resources(
 // helper to determine if the combination of search terms requires the results
 // to be remotes, so we can skip looking at resources
 fn is_remotes_only(filters: &Search) -> bool {
-    let mut is_required = false;
-    let mut optional_matches = 0;
-    let mut optional_terms = 0;
-    filters.matches(|term| {
-        if term.is_optional() {
-            optional_terms += 1;
-        }
-        match term.category.as_deref().map(|c| c.parse::<MatchCategory>()) {
-            Some(Ok(MatchCategory::Type)) if MatchCategory::Type.matches("remote", &term.value) => {
-                if !term.is_optional() {
-                    is_required = true;
-                } else {
-                    optional_matches += 1;
-                }
-            }
-            _ => {}
-        }
-        // search is short-circuited, so to iterate over all, return true on required and false on optional
-        !term.is_optional()
-    });
+    !filters.is_empty() && search_requires_remote(filters)
+}
+
+fn search_requires_remote(search: &Search) -> bool {
+    search.required().iter().any(expression_requires_remote)
+        || (!search.optional().is_empty()
+            && search.optional().iter().all(expression_requires_remote))
+}
 
-    is_required || (optional_matches > 0 && optional_matches == optional_terms)
+fn expression_requires_remote(expr: &Expression) -> bool {
+    match expr {
+        Expression::Term(term) => {
+            matches!(
+                term.category.as_deref().map(|c| c.parse::<MatchCategory>()),
+                Some(Ok(MatchCategory::Type))
+            ) && MatchCategory::Type.matches_term("remote", term)
+        }
+        Expression::Group(search) => search_requires_remote(search),
+        // a negated term can match anything
+        Expression::Not(_) => false,
+        Expression::And(list) => list.iter().any(expression_requires_remote),
+        Expression::Or(list) => list.iter().all(expression_requires_remote),
+    }
 }
 
 // called from resource_cache where no RPCEnvironment is initialized..
@@ -354,17 +537,20 @@ pub(crate) async fn get_resources_impl(
     let (remotes_config, _) = pdm_config::remotes::config()?;
     let mut join_handles = Vec::new();
 
-    let filters = search.map(Search::from).unwrap_or_default();
+    let filters = match search {
+        Some(search) => search
+            .parse::<Search>()
+            .map_err(|err| http_err!(BAD_REQUEST, "{err}"))?,
+        None => Search::new(),
+    };
 
     let view = views::get_optional_view(view)?;
 
-    let mut view_filter_from_search = None;
-    filters.matches(|term| {
-        if let Some("view") = term.category.as_deref() {
-            view_filter_from_search = Some(term.value.to_string());
-        }
-        true
-    });
+    let view_filter_from_search = filters
+        .terms()
+        .filter(|term| term.category.as_deref() == Some("view"))
+        .last()
+        .map(|term| term.value.clone());
 
     let view = view.or(views::get_optional_view(
         view_filter_from_search.as_deref(),
@@ -384,7 +570,7 @@ pub(crate) async fn get_resources_impl(
             }
         }
 
-        if !filters.matches(|term| remote_type_matches_search_term(remote.ty, term)) {
+        if !filters.matches(|term| remote_properties_match_search_term(&remote, term)) {
             continue;
         }
 
@@ -421,6 +607,8 @@ pub(crate) async fn get_resources_impl(
                 });
             }
 
+            forecast::set_estimated_full(&remote_name, &mut resources).await;
+
             RemoteWithResources {
                 remote_name,
                 remote,
@@ -633,6 +821,17 @@ pub async fn get_status(
                     counts.pbs_storage_stats.used += r.disk;
                     counts.pbs_storage_stats.avail += r.maxdisk - r.disk;
                 }
+                Resource::PmgNode(r) => {
+                    if r.uptime > 0 {
+                        counts.pmg_nodes.online += 1;
+                    } else {
+                        if remote_status == RemoteStatus::Good {
+                            remote_status = RemoteStatus::Warning;
+                        }
+                        remote_messages.push(format!("Node '{}' is offline", r.node));
+                        counts.pmg_nodes.offline += 1;
+                    }
+                }
             }
         }
 
@@ -1030,6 +1229,35 @@ async fn fetch_remote_subscription_info(
 
             list.insert("localhost".to_string(), info);
         }
+        RemoteType::Pmg => {
+            let client = connection::make_pmg_client(remote)?;
+
+            let nodes = client.list_nodes().await?;
+            let futures = nodes.iter().map(|node| {
+                let node_name = node.node.clone();
+                client
+                    .get_subscription(&node.node)
+                    .map(move |res| (node_name, res.ok()))
+            });
+
+            for (node_name, remote_info) in join_all(futures).await {
+                list.insert(
+                    node_name,
+                    remote_info.map(|info| {
+                        let level = SubscriptionLevel::from_key(info.key.as_deref());
+                        NodeSubscriptionInfo {
+                            status: info.status,
+                            sockets: None,
+                            key: info.key,
+                            level,
+                            serverid: info.serverid,
+                            check_time: info.checktime,
+                            next_due_date: info.nextduedate,
+                        }
+                    }),
+                );
+            }
+        }
     };
 
     Ok(list)
@@ -1168,6 +1396,24 @@ async fn fetch_remote_resource(remote: &Remote) -> Result<Vec<Resource>, Error>
                 ));
             }
         }
+        RemoteType::Pmg => {
+            let client = connection::make_pmg_client(remote)?;
+
+            for node in client.list_nodes().await? {
+                // an unreachable cluster node must not hide the others, report it as offline
+                let status = client
+                    .node_status(&node.node)
+                    .await
+                    .inspect_err(|err| {
+                        log::debug!(
+                            "could not get status of node '{}' of remote '{remote_name}': {err}",
+                            node.node
+                        )
+                    })
+                    .unwrap_or_default();
+                resources.push(map_pmg_node_status(&remote_name, node.node, status));
+            }
+        }
     }
 
     Ok(resources)
@@ -1275,6 +1521,7 @@ pub(super) fn map_pve_storage(
             node: resource.node.unwrap_or_default(),
             status: resource.status.unwrap_or_default(),
             shared: resource.shared.unwrap_or_default(),
+            estimated_full: None,
         }),
         _ => None,
     }
@@ -1369,6 +1616,22 @@ fn map_pbs_node_status(remote: &str, status: NodeStatus) -> Resource {
     })
 }
 
+fn map_pmg_node_status(
+    remote: &str,
+    node: String,
+    status: pdm_api_types::pmg::NodeStatus,
+) -> Resource {
+    Resource::PmgNode(PmgNodeResource {
+        cpu: status.cpu,
+        maxcpu: status.cpuinfo.cpus as f64,
+        id: format!("remote/{remote}/node/{node}"),
+        node,
+        mem: status.memory.used,
+        maxmem: status.memory.total,
+        uptime: status.uptime,
+    })
+}
+
 fn map_pbs_datastore_status(
     remote: &str,
     status: DataStoreStatusListItem,
@@ -1404,6 +1667,7 @@ fn map_pbs_datastore_status(
             maintenance: store_config.maintenance_mode.clone(),
             backing_device: store_config.backing_device.clone(),
             backend_type,
+            estimated_full: None,
         })
     } else {
         Resource::PbsDatastore(PbsDatastoreResource {
@@ -1488,7 +1752,11 @@ async fn get_location_info(
 
 #[cfg(test)]
 mod tests {
-    use crate::api::resources::is_remotes_only;
+    use crate::api::resources::{
+        is_remotes_only, remote_properties_match_search_term, resource_matches_search_term,
+    };
+    use pdm_api_types::remotes::Remote;
+    use pdm_api_types::resource::{PveQemuResource, Resource};
     use pdm_search::{Search, SearchTerm};
 
     #[test]
@@ -1548,4 +1816,102 @@ mod tests {
             assert_eq!(is_remotes_only(&search), expected, "case: {count}");
         }
     }
+
+    #[test]
+    fn is_remote_only_expression() {
+        let cases = [
+            ("type:remote OR type:re", true),
+            ("type:remote OR name:foo", false),
+            ("type:remote AND name:foo", true),
+            ("NOT type:remote", false),
+            ("(type:remote name:foo) +status:online", false),
+            ("+(type:remote name:foo) status:online", false),
+            ("+(type:remote type:re) status:online", true),
+        ];
+
+        for (search, expected) in cases {
+            let search: Search = search.parse().unwrap();
+            assert_eq!(is_remotes_only(&search), expected, "search: {search}");
+        }
+    }
+
+    #[test]
+    fn numeric_search() {
+        let resource = Resource::PveQemu(PveQemuResource {
+            cpu: 0.9,
+            maxcpu: 4.0,
+            disk: 0,
+            maxdisk: 32 << 30,
+            id: "qemu/100".into(),
+            maxmem: 16 << 30,
+            mem: 8 << 30,
+            name: "vm".into(),
+            node: "node1".into(),
+            pool: String::new(),
+            status: "running".into(),
+            tags: Vec::new(),
+            template: false,
+            uptime: 7200,
+            vmid: 100,
+        });
+
+        let cases = [
+            ("cpu>0.8", true),
+            ("cpu>95%", false),
+            ("maxcpu>=4", true),
+            ("mem>=8G", true),
+            ("mem>8G", false),
+            ("maxmem=16GiB", true),
+            ("uptime<1h", false),
+            ("uptime>=2h", true),
+            ("maxdisk<1T", true),
+            ("cpu>foo", false),
+            ("name=vm", true),
+            ("name=v", false),
+            ("name>v", false),
+            ("cpu>0.8 AND NOT status:stopped", true),
+            ("uptime<1h OR mem<1G", false),
+        ];
+
+        for (search, expected) in cases {
+            let search: Search = search.parse().unwrap();
+            let matches = search.matches(|term| {
+                resource_matches_search_term("remote", &resource, term).unwrap_or(true)
+            });
+            assert_eq!(matches, expected, "search: {search}");
+        }
+    }
+
+    #[test]
+    fn folder_search() {
+        let mut remote: Remote = serde_json::from_value(serde_json::json!({
+            "type": "pve",
+            "id": "pve1",
+            "nodes": ["pve1.example.com"],
+            "authid": "root@pam",
+            "token": "-",
+            "folder": "site-a/rack1",
+        }))
+        .unwrap();
+
+        let cases = [
+            ("folder:rack", true),
+            ("folder=site-a", true),
+            ("folder=site-a/rack1", true),
+            ("folder=rack1", false),
+            ("folder:site-b", false),
+            ("NOT folder=site-a", false),
+            ("folder=site-b OR remote-type:pve", true),
+        ];
+
+        for (search, expected) in cases {
+            let search: Search = search.parse().unwrap();
+            let matches = search.matches(|term| remote_properties_match_search_term(&remote, term));
+            assert_eq!(matches, expected, "search: {search}");
+        }
+
+        remote.folder = None;
+        let search: Search = "folder:site".parse().unwrap();
+        assert!(!search.matches(|term| remote_properties_match_search_term(&remote, term)));
+    }
 }
@@ -4,11 +4,11 @@ use anyhow::{Context, Error};
 
 use pbs_api_types::REMOTE_ID_SCHEMA;
 use pdm_api_types::{Authid, PRIV_RESOURCE_AUDIT, remotes::RemoteType, sdn::ListController};
-use proxmox_access_control::CachedUserInfo;
 use proxmox_router::{Permission, Router, RpcEnvironment, http_bail};
 use proxmox_schema::api;
 use pve_api_types::ListControllersType;
 
+use crate::acl::CachedUserInfo;
 use crate::api::pve;
 use crate::api::remotes::RemoteIterator;
 use crate::parallel_fetcher::ParallelFetcher;
@@ -7,12 +7,12 @@ use pdm_api_types::{
     remotes::RemoteType,
     sdn::{CreateVnetRemote, ListVnet, SDN_ID_SCHEMA, VXLAN_ID_SCHEMA},
 };
-use proxmox_access_control::CachedUserInfo;
 use proxmox_rest_server::WorkerTask;
 use proxmox_router::{Permission, Router, RpcEnvironment, http_bail};
 use proxmox_schema::api;
 use pve_api_types::{CreateVnet, SdnVnetType};
 
+use crate::acl::CachedUserInfo;
 use crate::api::pve;
 use crate::api::remotes::RemoteIterator;
 use crate::{parallel_fetcher::ParallelFetcher, sdn_client::LockedSdnClients};
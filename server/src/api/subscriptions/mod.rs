@@ -9,7 +9,6 @@ use std::collections::HashSet;
 use anyhow::{Context, Error, bail, format_err};
 use futures::future::join_all;
 
-use proxmox_access_control::CachedUserInfo;
 use proxmox_config_digest::ConfigDigest;
 use proxmox_log::{info, warn};
 use proxmox_router::{
@@ -29,6 +28,7 @@ use pdm_api_types::{
     Authid, NODE_SCHEMA, PRIV_RESOURCE_AUDIT, PRIV_RESOURCE_MODIFY, PRIV_SYS_AUDIT, PRIV_SYS_MODIFY,
 };
 
+use crate::acl::CachedUserInfo;
 use crate::api::resources::{
     get_subscription_info_for_remote, invalidate_subscription_info_for_remote,
 };
@@ -850,7 +850,16 @@ async fn check_subscription_on_remote(
                 .check_subscription(proxmox_subscription::UpdateSubscription { force: Some(true) })
                 .await?;
         }
-        ProductType::Pmg | ProductType::Pom => {
+        ProductType::Pmg => {
+            let client = crate::connection::make_pmg_client(remote)?;
+            client
+                .check_subscription(
+                    node_name,
+                    proxmox_subscription::UpdateSubscription { force: Some(true) },
+                )
+                .await?;
+        }
+        ProductType::Pom => {
             bail!("PDM cannot check '{product_type}' keys: no remote support yet");
         }
     }
@@ -1066,6 +1075,7 @@ async fn check_subscription(
     let product_type = match remote_entry.ty {
         pdm_api_types::remotes::RemoteType::Pve => ProductType::Pve,
         pdm_api_types::remotes::RemoteType::Pbs => ProductType::Pbs,
+        pdm_api_types::remotes::RemoteType::Pmg => ProductType::Pmg,
     };
 
     check_subscription_on_remote(remote_entry, product_type, &node)
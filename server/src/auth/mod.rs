@@ -9,7 +9,6 @@ use anyhow::{Error, bail};
 
 use const_format::concatcp;
 use ldap::{AdAuthenticator, LdapAuthenticator};
-use proxmox_access_control::CachedUserInfo;
 use proxmox_auth_api::api::{Authenticator, LockedTfaConfig};
 use proxmox_auth_api::ticket::Ticket;
 use proxmox_auth_api::types::Authid;
@@ -21,6 +20,8 @@ use proxmox_tfa::api::{OpenUserChallengeData, TfaConfig};
 
 use pdm_api_types::{OpenIdRealmConfig, RealmRef, Userid, UsernameRef};
 
+use crate::acl::CachedUserInfo;
+
 pub mod certs;
 pub mod csrf;
 pub mod key;
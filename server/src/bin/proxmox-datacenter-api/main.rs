@@ -146,6 +146,7 @@ async fn get_index_future(env: RestEnvironment, parts: Parts) -> Response<proxmo
 
 async fn run(debug: bool) -> Result<(), Error> {
     auth::init(false);
+    server::notifications::init();
 
     proxmox_acme_api::init(configdir!("/acme"), false)?;
 
@@ -345,6 +346,7 @@ async fn run(debug: bool) -> Result<(), Error> {
     tasks::remote_tasks::start_task()?;
     tasks::remote_updates::start_task()?;
     tasks::ceph_detection::start_task();
+    tasks::alerts::start_task();
 
     server.await?;
     log::info!("server shutting down, waiting for active workers to complete");
@@ -394,6 +396,8 @@ async fn schedule_tasks() -> Result<(), Error> {
     // - stats (rrd) collection
     // - ...?
     tasks::logrotate::schedule_task_log_rotate().await;
+    tasks::guest_schedules::schedule_guest_schedules().await;
+    tasks::token_rotation::schedule_token_rotation().await;
 
     Ok(())
 }
@@ -0,0 +1,44 @@
+//! Periodic evaluation of the alert rules.
+//!
+//! Runs in the API daemon since the evaluation reads the RRD cache and keeps the alert state in
+//! memory, see [`server::alerts`].
+
+use std::future::Future;
+use std::pin::pin;
+
+use tokio::task::JoinHandle;
+
+use server::task_utils;
+
+/// How often to evaluate the alert rules, in seconds.
+const EVALUATION_INTERVAL: u64 = 60;
+
+fn spawn_aborted_on_shutdown<F>(future: F) -> JoinHandle<()>
+where
+    F: Future + Send + 'static,
+{
+    tokio::spawn(async move {
+        let future = pin!(future);
+        let abort_future = pin!(proxmox_daemon::shutdown_future());
+        futures::future::select(future, abort_future).await;
+    })
+}
+
+pub fn start_task() {
+    spawn_aborted_on_shutdown(run());
+}
+
+async fn run() {
+    loop {
+        let delay_target = task_utils::next_aligned_instant(EVALUATION_INTERVAL);
+        tokio::time::sleep_until(tokio::time::Instant::from_std(delay_target)).await;
+        run_once().await;
+    }
+}
+
+#[tracing::instrument(skip_all, name = "alerts")]
+async fn run_once() {
+    if let Err(err) = server::alerts::evaluate().await {
+        log::error!("alert evaluation failed: {err:#}");
+    }
+}
@@ -0,0 +1,63 @@
+//! Start the due guest schedules, see [`server::guest_schedules`].
+
+use proxmox_rest_server::WorkerTask;
+
+use pdm_api_types::Authid;
+use pdm_api_types::guest_schedules::GuestScheduleConfigEntry;
+use server::guest_schedules::WORKER_TYPE;
+use server::jobstate::Job;
+
+use super::check_schedule;
+
+/// Start a worker task for every enabled guest schedule which is due.
+pub async fn schedule_guest_schedules() {
+    let config = match pdm_config::guest_schedules::config() {
+        Ok((config, _digest)) => config,
+        Err(err) => {
+            eprintln!("unable to read guest schedule config - {err}");
+            return;
+        }
+    };
+
+    for (id, entry) in config {
+        let GuestScheduleConfigEntry::GuestSchedule(schedule) = entry;
+
+        if !schedule.enabled() || !check_schedule(WORKER_TYPE, &schedule.schedule, &id) {
+            continue;
+        }
+
+        let mut job = match Job::new(WORKER_TYPE, &id) {
+            Ok(job) => job,
+            Err(_) => continue, // could not get lock, the previous run is still active
+        };
+
+        if let Err(err) = WorkerTask::spawn(
+            WORKER_TYPE,
+            Some(id.clone()),
+            Authid::root_auth_id().to_string(),
+            false,
+            move |worker| async move {
+                job.start(&worker.upid().to_string())?;
+                log::info!(
+                    "running guest schedule '{}' - action '{}'",
+                    schedule.id,
+                    schedule.action,
+                );
+
+                let result = server::guest_schedules::run(&schedule).await;
+
+                let status = worker.create_state(&result);
+                if let Err(err) = job.finish(status) {
+                    eprintln!(
+                        "could not finish job state for {WORKER_TYPE} {}: {err}",
+                        schedule.id
+                    );
+                }
+
+                result
+            },
+        ) {
+            eprintln!("unable to start guest schedule {id}: {err}");
+        }
+    }
+}
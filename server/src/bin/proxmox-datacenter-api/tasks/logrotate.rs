@@ -3,10 +3,11 @@ use anyhow::{Error, format_err};
 use proxmox_lang::try_block;
 use proxmox_rest_server::WorkerTask;
 use proxmox_sys::logrotate::LogRotate;
-use proxmox_time::CalendarEvent;
 
 use pdm_api_types::Authid;
-use server::jobstate::{self, Job, JobState};
+use server::jobstate::{Job, JobState};
+
+use super::check_schedule;
 
 /// Rotate task logs, auth logs and access logs.
 ///
@@ -163,33 +164,3 @@ async fn command_reopen_auth_logfiles() -> Result<(), Error> {
         _ => Ok(()),
     }
 }
-
-fn check_schedule(worker_type: &str, event_str: &str, id: &str) -> bool {
-    let event: CalendarEvent = match event_str.parse() {
-        Ok(event) => event,
-        Err(err) => {
-            eprintln!("unable to parse schedule '{event_str}' - {err}");
-            return false;
-        }
-    };
-
-    let last = match jobstate::last_run_time(worker_type, id) {
-        Ok(time) => time,
-        Err(err) => {
-            eprintln!("could not get last run time of {worker_type} {id}: {err}");
-            return false;
-        }
-    };
-
-    let next = match event.compute_next_event(last) {
-        Ok(Some(next)) => next,
-        Ok(None) => return false,
-        Err(err) => {
-            eprintln!("compute_next_event for '{event_str}' failed - {err}");
-            return false;
-        }
-    };
-
-    let now = proxmox_time::epoch_i64();
-    next <= now
-}
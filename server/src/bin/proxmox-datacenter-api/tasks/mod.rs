@@ -1,6 +1,44 @@
+use proxmox_time::CalendarEvent;
+
+use server::jobstate;
+
 pub mod logrotate;
 
+pub mod alerts;
 pub mod ceph_detection;
+pub mod guest_schedules;
 pub mod remote_node_mapping;
 pub mod remote_tasks;
 pub mod remote_updates;
+pub mod token_rotation;
+
+/// Check whether a job with the given calendar event is due, based on its last run time.
+pub(crate) fn check_schedule(worker_type: &str, event_str: &str, id: &str) -> bool {
+    let event: CalendarEvent = match event_str.parse() {
+        Ok(event) => event,
+        Err(err) => {
+            eprintln!("unable to parse schedule '{event_str}' - {err}");
+            return false;
+        }
+    };
+
+    let last = match jobstate::last_run_time(worker_type, id) {
+        Ok(time) => time,
+        Err(err) => {
+            eprintln!("could not get last run time of {worker_type} {id}: {err}");
+            return false;
+        }
+    };
+
+    let next = match event.compute_next_event(last) {
+        Ok(Some(next)) => next,
+        Ok(None) => return false,
+        Err(err) => {
+            eprintln!("compute_next_event for '{event_str}' failed - {err}");
+            return false;
+        }
+    };
+
+    let now = proxmox_time::epoch_i64();
+    next <= now
+}
@@ -0,0 +1,79 @@
+//! Rotate the API tokens of remotes once they are too old, see [`server::token_rotation`].
+
+use proxmox_rest_server::WorkerTask;
+
+use pdm_api_types::Authid;
+use server::jobstate::{Job, JobState};
+use server::token_rotation::{self, WORKER_TYPE};
+
+use super::check_schedule;
+
+const JOB_ID: &str = "remotes";
+
+/// Check for remotes with too old API tokens once a day and rotate them.
+///
+/// Does nothing unless a maximum token age is set in the node config.
+pub async fn schedule_token_rotation() {
+    let max_age_days = match pdm_config::node::config() {
+        Ok((config, _digest)) => match config.remote_token_max_age {
+            Some(max_age_days) => max_age_days,
+            None => return,
+        },
+        Err(err) => {
+            eprintln!("unable to read node config - {err}");
+            return;
+        }
+    };
+
+    if !check_schedule(WORKER_TYPE, "daily", JOB_ID) {
+        // if we never checked the tokens, schedule instantly
+        match JobState::load(WORKER_TYPE, JOB_ID) {
+            Ok(JobState::Created { .. }) => {}
+            _ => return,
+        }
+    }
+
+    let remotes = match pdm_config::remotes::config() {
+        Ok((remotes, _digest)) => remotes,
+        Err(err) => {
+            eprintln!("unable to read remote config - {err}");
+            return;
+        }
+    };
+
+    // don't start a task just to find out there is nothing to do
+    let now = proxmox_time::epoch_i64();
+    if !remotes
+        .iter()
+        .any(|(_, remote)| token_rotation::needs_rotation(remote, max_age_days, now))
+    {
+        return;
+    }
+
+    let mut job = match Job::new(WORKER_TYPE, JOB_ID) {
+        Ok(job) => job,
+        Err(_) => return, // could not get lock, the previous run is still active
+    };
+
+    if let Err(err) = WorkerTask::spawn(
+        WORKER_TYPE,
+        None,
+        Authid::root_auth_id().to_string(),
+        false,
+        move |worker| async move {
+            job.start(&worker.upid().to_string())?;
+            log::info!("rotating API tokens older than {max_age_days} days");
+
+            let result = token_rotation::rotate_tokens(Some(max_age_days)).await;
+
+            let status = worker.create_state(&result);
+            if let Err(err) = job.finish(status) {
+                eprintln!("could not finish job state for {WORKER_TYPE}: {err}");
+            }
+
+            result
+        },
+    ) {
+        eprintln!("unable to start token rotation: {err}");
+    }
+}
@@ -1,7 +1,6 @@
-use anyhow::Error;
+use anyhow::{Error, bail};
 use serde_json::json;
 
-//use proxmox_notify::context::pbs::PBS_CONTEXT;
 use proxmox_router::{ApiHandler, RpcEnvironment, cli::*};
 use proxmox_subscription::SubscriptionStatus;
 use proxmox_sys::fs::CreateOptions;
@@ -41,7 +40,7 @@ async fn do_update(rpcenv: &mut dyn RpcEnvironment) -> Result<(), Error> {
 
     println!("updating apt package database");
     let param = json!({
-        "notify": true, // TODO: not yet supported anyway?!
+        "notify": true,
     });
     let method = &api::nodes::apt::API_METHOD_APT_UPDATE_DATABASE;
     match method.handler {
@@ -57,6 +56,7 @@ async fn do_update(rpcenv: &mut dyn RpcEnvironment) -> Result<(), Error> {
     println!("check if any ACME-managed certificate requires renewal");
     if let Err(err) = check_acme_certificates(rpcenv).await {
         log::error!("error checking certificates: {err}");
+        server::notifications::send_certificate_renewal_failed(&err);
     }
 
     // TODO: cleanup tasks like in PVE?
@@ -82,7 +82,15 @@ async fn check_acme_certificates(rpcenv: &mut dyn RpcEnvironment) -> Result<(),
         ApiHandler::Sync(handler) => (handler)(json!({}), info, rpcenv)?,
         _ => unreachable!(),
     };
-    wait_for_local_worker(result.as_str().unwrap()).await?;
+    let upid_str = result.as_str().unwrap();
+    wait_for_local_worker(upid_str).await?;
+
+    let upid: pbs_api_types::UPID = upid_str.parse()?;
+    if let proxmox_rest_server::TaskState::Error { message, .. } =
+        proxmox_rest_server::upid_read_status(&upid)?
+    {
+        bail!("certificate renewal failed - {message}");
+    }
 
     Ok(())
 }
@@ -96,7 +104,7 @@ async fn run(rpcenv: &mut dyn RpcEnvironment) -> Result<(), Error> {
     proxmox_rest_server::register_task_control_commands(&mut command_sock)?;
     command_sock.spawn(proxmox_rest_server::last_worker_future())?;
 
-    //proxmox_notify::context::set_context(&PBS_CONTEXT);
+    server::notifications::init();
 
     proxmox_product_config::init(pdm_config::api_user()?, pdm_config::priv_user()?);
     proxmox_acme_api::init(pdm_buildcfg::configdir!("/acme"), false)?;
@@ -110,12 +110,14 @@ fn create_directories() -> Result<(), Error> {
     )?;
 
     server::jobstate::create_jobstate_dir()?;
+    server::notifications::create_spool_dir()?;
 
     Ok(())
 }
 
 async fn run() -> Result<(), Error> {
     auth::init(true);
+    server::notifications::init();
 
     proxmox_acme_api::init(configdir!("/acme"), true)?;
     pdm_config::domains::add_default_realms()?;
@@ -258,6 +260,7 @@ async fn run() -> Result<(), Error> {
     });
 
     start_task_scheduler();
+    start_notification_worker();
 
     server.await?;
     log::info!("server shutting down, waiting for active workers to complete");
@@ -276,6 +279,14 @@ fn start_task_scheduler() {
     });
 }
 
+fn start_notification_worker() {
+    tokio::spawn(async move {
+        let notification_worker = pin!(server::notifications::notification_worker());
+        let abort_future = pin!(proxmox_daemon::shutdown_future());
+        futures::future::select(notification_worker, abort_future).await;
+    });
+}
+
 use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
 fn next_minute() -> Instant {
@@ -16,13 +16,14 @@
 
 use anyhow::Error;
 
-use proxmox_access_control::CachedUserInfo;
 use proxmox_router::http_bail;
 
 use pdm_api_types::Authid;
 use pdm_api_types::ceph::{CephCluster, CephMember, CephMemberKind};
 use pdm_config::ceph::CephClustersConfig;
 
+use crate::acl::CachedUserInfo;
+
 /// Whether the caller holds `privs` on the remote backing `member`.
 ///
 /// Standalone members have no remote to derive access from, so they are inaccessible: there is no
@@ -17,49 +17,75 @@ use http::Method;
 use http::uri::Authority;
 use openssl::x509::X509StoreContextRef;
 use serde::Serialize;
+use tokio::sync::{Semaphore, SemaphorePermit};
 
 use proxmox_acme_api::CertificateInfo;
 use proxmox_client::{Client, HttpApiClient, HttpApiResponse, HttpApiResponseStream, TlsOptions};
+use proxmox_http::{HttpOptions, ProxyConfig};
 use proxmox_time::epoch_i64;
 
-use pdm_api_types::remotes::{NodeUrl, Remote, RemoteType, TlsProbeOutcome};
+use pdm_api_types::remotes::{NodeUrl, Remote, RemoteTfaChallenge, RemoteType, TlsProbeOutcome};
 use pve_api_types::client::PveClientImpl;
 
 use crate::pbs_client::PbsClient;
+use crate::pmg_client::PmgClient;
 use crate::remote_cache::ConnectionState;
+use crate::socks_proxy;
 
 static INSTANCE: OnceLock<Box<dyn ClientFactory + Send + Sync>> = OnceLock::new();
 
+/// Timeout for API requests to remotes which don't configure their own.
+const DEFAULT_REQUEST_TIMEOUT: Duration = Duration::from_secs(60);
+
 /// Connection Info returned from [`prepare_connect_client`]
 struct ConnectInfo {
     prefix: String,
     perl_compat: bool,
     pve_compat: bool,
     default_port: u16,
+    proxy_config: Option<ProxyConfig>,
 }
 
 impl ConnectInfo {
-    fn for_remote(remote: &Remote) -> Self {
+    fn for_remote(remote: &Remote) -> Result<Self, Error> {
         let (prefix, perl_compat, pve_compat) = match remote.ty {
             RemoteType::Pve => ("PVEAPIToken".to_string(), true, true),
             RemoteType::Pbs => ("PBSAPIToken".to_string(), false, false),
+            // PMG has no API tokens, only tickets from logging in with a user and password
+            RemoteType::Pmg => {
+                if remote.authid.is_token() {
+                    bail!(
+                        "Proxmox Mail Gateway has no API tokens, use a user and password instead"
+                    );
+                }
+                (String::new(), true, true)
+            }
         };
 
-        ConnectInfo {
+        let proxy_config = match remote.http_proxy.as_deref() {
+            Some(url) if socks_proxy::is_socks_url(url) => Some(
+                socks_proxy::proxy_config(url)
+                    .map_err(|err| format_err!("invalid SOCKS proxy for remote - {err}"))?,
+            ),
+            Some(url) => Some(
+                ProxyConfig::parse_proxy_url(url)
+                    .map_err(|err| format_err!("invalid HTTP proxy for remote - {err}"))?,
+            ),
+            None => None,
+        };
+
+        Ok(ConnectInfo {
             prefix,
             perl_compat,
             pve_compat,
             default_port: remote.ty.default_port(),
-        }
+            proxy_config,
+        })
     }
 }
 ///
 /// Returns a [`proxmox_client::Client`] set up to connect to a specific node.
-fn prepare_connect_client_to_node(
-    node: &NodeUrl,
-    default_port: u16,
-    pve_compat: bool,
-) -> Result<Client, Error> {
+fn prepare_connect_client_to_node(node: &NodeUrl, info: &ConnectInfo) -> Result<Client, Error> {
     let mut options = TlsOptions::default();
 
     if let Some(fp) = &node.fingerprint {
@@ -71,13 +97,17 @@ fn prepare_connect_client_to_node(
     let uri: http::uri::Uri = format!(
         "https://{}:{}",
         host_port.host(),
-        host_port.port_u16().unwrap_or(default_port)
+        host_port.port_u16().unwrap_or(info.default_port)
     )
     .parse()?;
 
-    let mut client =
-        proxmox_client::Client::with_options(uri.clone(), options, Default::default())?;
-    client.set_pve_compatibility(pve_compat);
+    let http_options = HttpOptions {
+        proxy_config: info.proxy_config.clone(),
+        ..Default::default()
+    };
+
+    let mut client = proxmox_client::Client::with_options(uri.clone(), options, http_options)?;
+    client.set_pve_compatibility(info.pve_compat);
     Ok(client)
 }
 
@@ -99,18 +129,18 @@ fn prepare_connect_client(
             None => format_err!("no nodes configured for remote"),
         })?;
 
-    let info = ConnectInfo::for_remote(remote);
+    let info = ConnectInfo::for_remote(remote)?;
 
-    let client = prepare_connect_client_to_node(node, info.default_port, info.pve_compat)?;
+    let client = prepare_connect_client_to_node(node, &info)?;
 
     Ok((client, info))
 }
 
-/// Constructs a [`Client`] for the given [`Remote`] for an API token
+/// Constructs a [`LimitedClient`] for the given [`Remote`] for an API token
 ///
 /// It does not actually opens a connection there, but prepares the client with the correct
 /// authentication information and settings for the [`RemoteType`]
-fn connect(remote: &Remote, target_endpoint: Option<&str>) -> Result<Client, anyhow::Error> {
+fn connect(remote: &Remote, target_endpoint: Option<&str>) -> Result<LimitedClient, anyhow::Error> {
     let (client, info) = prepare_connect_client(remote, target_endpoint)?;
     let token = pdm_config::remotes::get_secret_token(remote)?;
 
@@ -120,7 +150,7 @@ fn connect(remote: &Remote, target_endpoint: Option<&str>) -> Result<Client, any
         prefix: info.prefix,
         perl_compat: info.perl_compat,
     });
-    Ok(client)
+    Ok(LimitedClient::new(remote, client))
 }
 
 /// Returns a [`MultiClient`] and connection info required to set token authentication
@@ -130,30 +160,35 @@ fn prepare_connect_multi_client(remote: &Remote) -> Result<(MultiClient, Connect
         bail!("no nodes configured for remote");
     };
 
-    let info = ConnectInfo::for_remote(remote);
+    let info = ConnectInfo::for_remote(remote)?;
 
     let mut clients = Vec::new();
 
     for node in &remote.nodes {
         clients.push(MultiClientEntry {
-            client: Arc::new(prepare_connect_client_to_node(
-                node,
-                info.default_port,
-                info.pve_compat,
-            )?),
+            client: Arc::new(prepare_connect_client_to_node(node, &info)?),
             hostname: node.hostname.clone(),
         });
     }
 
-    Ok((MultiClient::new(remote.id.clone(), clients), info))
+    Ok((MultiClient::new(remote, clients), info))
 }
 
 /// Like [`connect()`], but with failover support for remotes which can have multiple nodes.
+///
+/// Remotes with a user instead of an API token, like all PMG remotes, log in on the first request.
 fn multi_connect(remote: &Remote) -> Result<MultiClient, anyhow::Error> {
     let (client, info) = prepare_connect_multi_client(remote)?;
 
     let token = pdm_config::remotes::get_secret_token(remote)?;
 
+    if !remote.authid.is_token() {
+        return Ok(client.with_login(Remote {
+            token,
+            ..remote.clone()
+        }));
+    }
+
     client.for_each_client(|client| {
         client.set_authentication(proxmox_client::Token {
             userid: remote.authid.to_string(),
@@ -166,7 +201,228 @@ fn multi_connect(remote: &Remote) -> Result<MultiClient, anyhow::Error> {
     Ok(client)
 }
 
-/// Constructs a [`Client`] for the given [`Remote`] for an API token or user
+/// How long (in seconds) pending and finished logins of [`login_remote`] are kept.
+const REMOTE_LOGIN_TIMEOUT: i64 = 10 * 60;
+
+/// Identifies the credentials of a login and the nodes it was made to, the password is only kept
+/// as digest.
+#[derive(Clone, PartialEq)]
+struct LoginKey {
+    ty: RemoteType,
+    nodes: Vec<(String, Option<String>)>,
+    authid: String,
+    password: [u8; 32],
+}
+
+impl LoginKey {
+    fn for_remote(remote: &Remote) -> Self {
+        Self {
+            ty: remote.ty,
+            nodes: remote
+                .nodes
+                .iter()
+                .map(|node| (node.hostname.clone(), node.fingerprint.clone()))
+                .collect(),
+            authid: remote.authid.to_string(),
+            password: openssl::sha::sha256(remote.token.as_bytes()),
+        }
+    }
+}
+
+/// A login which waits for the response to its second factor challenge.
+struct PendingLogin {
+    key: LoginKey,
+    client: Client,
+    challenge: proxmox_login::SecondFactorChallenge,
+    started: i64,
+}
+
+/// A successful login, so that the following requests with the same credentials (e.g. creating
+/// the API token after scanning the remote) don't have to log in again.
+struct FinishedLogin {
+    key: LoginKey,
+    auth: Arc<proxmox_client::Authentication>,
+    finished: i64,
+}
+
+#[derive(Default)]
+struct RemoteLogins {
+    pending: HashMap<String, PendingLogin>,
+    finished: Vec<FinishedLogin>,
+}
+
+impl RemoteLogins {
+    fn expire(&mut self, now: i64) {
+        self.pending
+            .retain(|_, login| now - login.started < REMOTE_LOGIN_TIMEOUT);
+        self.finished
+            .retain(|login| now - login.finished < REMOTE_LOGIN_TIMEOUT);
+    }
+
+    fn finished_login(
+        &mut self,
+        key: &LoginKey,
+        now: i64,
+    ) -> Option<Arc<proxmox_client::Authentication>> {
+        self.expire(now);
+        self.finished
+            .iter()
+            .rev()
+            .find(|login| login.key == *key)
+            .map(|login| Arc::clone(&login.auth))
+    }
+}
+
+static REMOTE_LOGINS: LazyLock<StdMutex<RemoteLogins>> = LazyLock::new(Default::default);
+
+/// Keep the ticket of a successful login for the following requests.
+fn remember_login(key: LoginKey, client: &Client) -> Result<(), Error> {
+    let auth = client.login_auth()?;
+    REMOTE_LOGINS.lock().unwrap().finished.push(FinishedLogin {
+        key,
+        auth,
+        finished: epoch_i64(),
+    });
+    Ok(())
+}
+
+fn map_login_error(err: proxmox_client::Error) -> Error {
+    match err {
+        // FIXME: check why Api with 401 is returned instead of an Authentication error
+        proxmox_client::Error::Api(code, _) if code.as_u16() == 401 => {
+            format_err!("authentication failed")
+        }
+        proxmox_client::Error::Authentication(_) => format_err!("authentication failed"),
+        _ => err.into(),
+    }
+}
+
+/// Make sure `client` has a ticket for the user and password of `remote`.
+///
+/// Reuses the ticket of a recent login with the same credentials, so that not every request logs
+/// in again.
+async fn ensure_login(client: &Client, remote: &Remote) -> Result<(), proxmox_client::Error> {
+    let key = LoginKey::for_remote(remote);
+    let auth = REMOTE_LOGINS
+        .lock()
+        .unwrap()
+        .finished_login(&key, epoch_i64());
+    if let Some(auth) = auth {
+        client.set_authentication((*auth).clone());
+        return Ok(());
+    }
+
+    let result = match login(client, remote).await {
+        Ok(None) => remember_login(key, client),
+        Ok(Some(_challenge)) => Err(format_err!("two factor authentication required")),
+        Err(err) => Err(err),
+    };
+    // keep connection errors as they are, so that the `MultiClient` fails over to another node
+    result.map_err(|err| match err.downcast::<proxmox_client::Error>() {
+        Ok(err) => err,
+        Err(err) => proxmox_client::Error::Anyhow(err.into()),
+    })
+}
+
+/// Log in with the user and password of the remote, returns the challenge if a second factor is
+/// required.
+async fn login(
+    client: &Client,
+    remote: &Remote,
+) -> Result<Option<proxmox_login::SecondFactorChallenge>, Error> {
+    let login = client.login(proxmox_login::Login::new(
+        client.api_url().to_string(),
+        remote.authid.to_string(),
+        remote.token.to_string(),
+    ));
+    match tokio::time::timeout(request_timeout(remote), login).await {
+        Ok(result) => result.map_err(map_login_error),
+        Err(_) => bail!("login timed out"),
+    }
+}
+
+/// Log in to a remote with a user and password, with support for two factor authentication.
+///
+/// If the user requires a second factor, the login is kept pending and the challenge is returned.
+/// Call this again with the challenge's id and the response to it to finish the login.
+/// For a while afterwards, [`connect_or_login`] reuses the ticket of the login if the remote has
+/// the same credentials.
+///
+/// Does nothing if the remote uses an API token.
+pub async fn login_remote(
+    remote: &Remote,
+    tfa_challenge: Option<&str>,
+    tfa_response: Option<&str>,
+) -> Result<Option<RemoteTfaChallenge>, Error> {
+    if remote.authid.is_token() {
+        return Ok(None);
+    }
+
+    let key = LoginKey::for_remote(remote);
+
+    let tfa = match (tfa_challenge, tfa_response) {
+        (Some(id), Some(response)) => Some((id, response)),
+        (None, None) => None,
+        _ => bail!("the TFA challenge and the response to it have to be given together"),
+    };
+
+    if let Some((id, response)) = tfa {
+        let pending = {
+            let mut logins = REMOTE_LOGINS.lock().unwrap();
+            logins.expire(epoch_i64());
+            logins.pending.remove(id)
+        };
+        let pending = match pending {
+            Some(pending) if pending.key == key => pending,
+            _ => bail!("no pending login for the TFA challenge, it may have expired"),
+        };
+
+        let request = pending.challenge.respond_raw(response);
+        pending
+            .client
+            .login_tfa(pending.challenge, request)
+            .await
+            .map_err(map_login_error)?;
+
+        remember_login(key, &pending.client)?;
+        return Ok(None);
+    }
+
+    if REMOTE_LOGINS
+        .lock()
+        .unwrap()
+        .finished_login(&key, epoch_i64())
+        .is_some()
+    {
+        return Ok(None);
+    }
+
+    let (client, _info) = prepare_connect_client(remote, None)?;
+    let Some(challenge) = login(&client, remote).await? else {
+        remember_login(key, &client)?;
+        return Ok(None);
+    };
+
+    let id = proxmox_uuid::Uuid::generate().to_string();
+    let tfa_challenge = RemoteTfaChallenge {
+        id: id.clone(),
+        challenge: serde_json::to_string(&challenge.challenge)?,
+    };
+
+    REMOTE_LOGINS.lock().unwrap().pending.insert(
+        id,
+        PendingLogin {
+            key,
+            client,
+            challenge,
+            started: epoch_i64(),
+        },
+    );
+
+    Ok(Some(tfa_challenge))
+}
+
+/// Constructs a [`LimitedClient`] for the given [`Remote`] for an API token or user
 ///
 /// In case the remote has a user configured (instead of an API token), it will connect and get a
 /// ticket, so that further connections are properly authenticated. Otherwise it behaves
@@ -174,37 +430,30 @@ fn multi_connect(remote: &Remote) -> Result<MultiClient, anyhow::Error> {
 ///
 /// This is intended for API calls that accept a user in addition to tokens.
 ///
-/// Note: currently does not support two factor authentication.
+/// Users with two factor authentication have to log in with [`login_remote`] first.
 async fn connect_or_login(
     remote: &Remote,
     target_endpoint: Option<&str>,
-) -> Result<Client, anyhow::Error> {
+) -> Result<LimitedClient, anyhow::Error> {
     if remote.authid.is_token() {
         connect(remote, target_endpoint)
     } else {
         let (client, _info) = prepare_connect_client(remote, target_endpoint)?;
-        match client
-            .login(proxmox_login::Login::new(
-                client.api_url().to_string(),
-                remote.authid.to_string(),
-                remote.token.to_string(),
-            ))
-            .await
-        {
-            Ok(Some(_)) => bail!("two factor auth not supported"),
-            Ok(None) => {}
-            Err(err) => match err {
-                // FIXME: check why Api with 401 is returned instead of an Authentication error
-                proxmox_client::Error::Api(code, _) if code.as_u16() == 401 => {
-                    bail!("authentication failed")
-                }
-                proxmox_client::Error::Authentication(_) => {
-                    bail!("authentication failed")
-                }
-                _ => return Err(err.into()),
-            },
+
+        let key = LoginKey::for_remote(remote);
+        let auth = REMOTE_LOGINS
+            .lock()
+            .unwrap()
+            .finished_login(&key, epoch_i64());
+        if let Some(auth) = auth {
+            client.set_authentication((*auth).clone());
+            return Ok(LimitedClient::new(remote, client));
         }
-        Ok(client)
+
+        if login(&client, remote).await?.is_some() {
+            bail!("two factor authentication required");
+        }
+        Ok(LimitedClient::new(remote, client))
     }
 }
 
@@ -217,6 +466,9 @@ pub trait ClientFactory {
     /// Create a new API client for PBS remotes
     fn make_pbs_client(&self, remote: &Remote) -> Result<Box<PbsClient>, Error>;
 
+    /// Create a new API client for PMG remotes
+    fn make_pmg_client(&self, remote: &Remote) -> Result<Box<PmgClient>, Error>;
+
     /// Create a new API client for PVE remotes, but with a specific endpoint.
     fn make_pve_client_with_endpoint(
         &self,
@@ -245,7 +497,7 @@ pub trait ClientFactory {
     ///
     /// This is intended for API calls that accept a user in addition to tokens.
     ///
-    /// Note: currently does not support two factor authentication.
+    /// Users with two factor authentication have to log in with [`login_remote`] first.
     async fn make_pve_client_and_login(&self, remote: &Remote) -> Result<Arc<PveClient>, Error>;
 
     /// Create a new API client for PBS remotes.
@@ -256,14 +508,28 @@ pub trait ClientFactory {
     ///
     /// This is intended for API calls that accept a user in addition to tokens.
     ///
-    /// Note: currently does not support two factor authentication.
+    /// Users with two factor authentication have to log in with [`login_remote`] first.
     async fn make_pbs_client_and_login(
         &self,
         remote: &Remote,
-    ) -> Result<Box<PbsClient<Client>>, Error>;
+    ) -> Result<Box<PbsClient<LimitedClient>>, Error>;
+
+    /// Create a new API client for PMG remotes.
+    ///
+    /// In case the remote has a user configured (instead of an API token), it will connect and get
+    /// a ticket, so that further connections are properly authenticated. Otherwise it behaves
+    /// identically as [`make_pmg_client`].
+    ///
+    /// This is intended for API calls that accept a user in addition to tokens.
+    ///
+    /// Users with two factor authentication have to log in with [`login_remote`] first.
+    async fn make_pmg_client_and_login(
+        &self,
+        remote: &Remote,
+    ) -> Result<Box<PmgClient<LimitedClient>>, Error>;
 
     /// Create a new API client for raw access to the given remote
-    fn make_raw_client(&self, remote: &Remote) -> Result<Box<Client>, Error>;
+    fn make_raw_client(&self, remote: &Remote) -> Result<Box<LimitedClient>, Error>;
 }
 
 /// Default production client factory
@@ -358,7 +624,7 @@ impl ClientFactory for DefaultClientFactory {
         ConnectionCache::get().make_pve_client(remote)
     }
 
-    fn make_raw_client(&self, remote: &Remote) -> Result<Box<Client>, Error> {
+    fn make_raw_client(&self, remote: &Remote) -> Result<Box<LimitedClient>, Error> {
         Ok(Box::new(crate::connection::connect(remote, None)?))
     }
 
@@ -367,6 +633,11 @@ impl ClientFactory for DefaultClientFactory {
         Ok(Box::new(PbsClient(client)))
     }
 
+    fn make_pmg_client(&self, remote: &Remote) -> Result<Box<PmgClient>, Error> {
+        let client = crate::connection::multi_connect(remote)?;
+        Ok(Box::new(PmgClient(client)))
+    }
+
     fn make_pve_client_with_endpoint(
         &self,
         remote: &Remote,
@@ -398,10 +669,18 @@ impl ClientFactory for DefaultClientFactory {
     async fn make_pbs_client_and_login(
         &self,
         remote: &Remote,
-    ) -> Result<Box<PbsClient<Client>>, Error> {
+    ) -> Result<Box<PbsClient<LimitedClient>>, Error> {
         let client = connect_or_login(remote, None).await?;
         Ok(Box::new(PbsClient(client)))
     }
+
+    async fn make_pmg_client_and_login(
+        &self,
+        remote: &Remote,
+    ) -> Result<Box<PmgClient<LimitedClient>>, Error> {
+        let client = connect_or_login(remote, None).await?;
+        Ok(Box::new(PmgClient(client)))
+    }
 }
 
 fn instance() -> &'static (dyn ClientFactory + Send + Sync) {
@@ -437,7 +716,12 @@ pub fn make_pbs_client(remote: &Remote) -> Result<Box<PbsClient>, Error> {
     instance().make_pbs_client(remote)
 }
 
-pub fn make_raw_client(remote: &Remote) -> Result<Box<Client>, Error> {
+/// Create a new API client for PMG remotes
+pub fn make_pmg_client(remote: &Remote) -> Result<Box<PmgClient>, Error> {
+    instance().make_pmg_client(remote)
+}
+
+pub fn make_raw_client(remote: &Remote) -> Result<Box<LimitedClient>, Error> {
     instance().make_raw_client(remote)
 }
 
@@ -449,7 +733,7 @@ pub fn make_raw_client(remote: &Remote) -> Result<Box<Client>, Error> {
 ///
 /// This is intended for API calls that accept a user in addition to tokens.
 ///
-/// Note: currently does not support two factor authentication.
+/// Users with two factor authentication have to log in with [`login_remote`] first.
 pub async fn make_pve_client_and_login(remote: &Remote) -> Result<Arc<PveClient>, Error> {
     instance().make_pve_client_and_login(remote).await
 }
@@ -462,11 +746,28 @@ pub async fn make_pve_client_and_login(remote: &Remote) -> Result<Arc<PveClient>
 ///
 /// This is intended for API calls that accept a user in addition to tokens.
 ///
-/// Note: currently does not support two factor authentication.
-pub async fn make_pbs_client_and_login(remote: &Remote) -> Result<Box<PbsClient<Client>>, Error> {
+/// Users with two factor authentication have to log in with [`login_remote`] first.
+pub async fn make_pbs_client_and_login(
+    remote: &Remote,
+) -> Result<Box<PbsClient<LimitedClient>>, Error> {
     instance().make_pbs_client_and_login(remote).await
 }
 
+/// Create a new API client for PMG remotes.
+///
+/// In case the remote has a user configured (instead of an API token), it will connect and get a
+/// ticket, so that further connections are properly authenticated. Otherwise it behaves
+/// identically as [`make_pmg_client`].
+///
+/// This is intended for API calls that accept a user in addition to tokens.
+///
+/// Users with two factor authentication have to log in with [`login_remote`] first.
+pub async fn make_pmg_client_and_login(
+    remote: &Remote,
+) -> Result<Box<PmgClient<LimitedClient>>, Error> {
+    instance().make_pmg_client_and_login(remote).await
+}
+
 /// Initialize the [`ClientFactory`] instance.
 ///
 /// Will panic if the instance has already been set.
@@ -476,6 +777,134 @@ pub fn init(instance: Box<dyn ClientFactory + Send + Sync>) {
     }
 }
 
+/// The semaphores limiting the concurrent requests to remotes with `max-connections` set, together
+/// with the limit they were created for.
+static REQUEST_LIMITS: LazyLock<StdMutex<HashMap<String, (u64, Arc<Semaphore>)>>> =
+    LazyLock::new(Default::default);
+
+/// Get the semaphore limiting the concurrent requests to a remote, if it has a limit.
+///
+/// All clients of a remote share the same semaphore, it is replaced when the limit changes.
+fn request_limit(remote: &Remote) -> Option<Arc<Semaphore>> {
+    let mut limits = REQUEST_LIMITS.lock().unwrap();
+
+    let Some(max_connections) = remote.max_connections else {
+        limits.remove(&remote.id);
+        return None;
+    };
+
+    match limits.get(&remote.id) {
+        Some((limit, semaphore)) if *limit == max_connections => Some(Arc::clone(semaphore)),
+        _ => {
+            let semaphore = Arc::new(Semaphore::new(max_connections as usize));
+            limits.insert(remote.id.clone(), (max_connections, Arc::clone(&semaphore)));
+            Some(semaphore)
+        }
+    }
+}
+
+/// The timeout for API requests to a remote.
+fn request_timeout(remote: &Remote) -> Duration {
+    remote
+        .timeout
+        .map_or(DEFAULT_REQUEST_TIMEOUT, Duration::from_secs)
+}
+
+/// Wait for a permit of the remote's request limit, if it has one.
+async fn acquire_request_permit(
+    request_limit: &Option<Arc<Semaphore>>,
+) -> Result<Option<SemaphorePermit<'_>>, proxmox_client::Error> {
+    match request_limit {
+        Some(limit) => {
+            Ok(Some(limit.acquire().await.map_err(|err| {
+                proxmox_client::Error::Anyhow(err.into())
+            })?))
+        }
+        None => Ok(None),
+    }
+}
+
+/// A [`Client`] for a single node of a remote.
+///
+/// Like the [`MultiClient`], it applies the request timeout and the request limit of the remote,
+/// but it doesn't fail over to other nodes.
+pub struct LimitedClient {
+    client: Client,
+    timeout: Duration,
+    request_limit: Option<Arc<Semaphore>>,
+}
+
+impl LimitedClient {
+    fn new(remote: &Remote, client: Client) -> Self {
+        Self {
+            client,
+            timeout: request_timeout(remote),
+            request_limit: request_limit(remote),
+        }
+    }
+
+    /// The underlying client, for requests which are not made through [`HttpApiClient`].
+    pub fn client(&self) -> &Client {
+        &self.client
+    }
+
+    async fn limited<F, R>(&self, request: F) -> Result<R, proxmox_client::Error>
+    where
+        F: Future<Output = Result<R, proxmox_client::Error>>,
+    {
+        let _permit = acquire_request_permit(&self.request_limit).await?;
+        match tokio::time::timeout(self.timeout, request).await {
+            Ok(result) => result,
+            Err(_) => Err(proxmox_client::Error::Other(
+                "failed to perform API request: timed out",
+            )),
+        }
+    }
+}
+
+impl HttpApiClient for LimitedClient {
+    type ResponseFuture<'a> =
+        Pin<Box<dyn Future<Output = Result<HttpApiResponse, proxmox_client::Error>> + Send + 'a>>;
+
+    type ResponseStreamFuture<'a> = Pin<
+        Box<
+            dyn Future<Output = Result<HttpApiResponseStream<Self::Body>, proxmox_client::Error>>
+                + Send
+                + 'a,
+        >,
+    >;
+    type Body = proxmox_http::Body;
+
+    fn request<'a, T>(
+        &'a self,
+        method: Method,
+        path_and_query: &'a str,
+        params: Option<T>,
+    ) -> Self::ResponseFuture<'a>
+    where
+        T: Serialize + 'a,
+    {
+        Box::pin(self.limited(self.client.request(method, path_and_query, params)))
+    }
+
+    fn streaming_request<'a, T>(
+        &'a self,
+        method: Method,
+        path_and_query: &'a str,
+        params: Option<T>,
+    ) -> Self::ResponseStreamFuture<'a>
+    where
+        T: Serialize + 'a,
+    {
+        Box::pin(
+            self.limited(
+                self.client
+                    .streaming_request(method, path_and_query, params),
+            ),
+        )
+    }
+}
+
 /// In order to allow the [`MultiClient`] to check the cached reachability state of a client, we
 /// need to know which remote it belongs to, so store the metadata alongside the actual `Client`
 /// struct.
@@ -495,17 +924,29 @@ pub struct MultiClient {
     state: StdMutex<MultiClientState>,
     remote: String,
     timeout: Duration,
+    request_limit: Option<Arc<Semaphore>>,
+    /// The remote with its password, for remotes which are accessed with a user.
+    login: Option<Remote>,
 }
 
 impl MultiClient {
-    fn new(remote: String, entries: Vec<MultiClientEntry>) -> Self {
+    fn new(remote: &Remote, entries: Vec<MultiClientEntry>) -> Self {
         Self {
-            state: StdMutex::new(MultiClientState::new(remote.clone(), entries)),
-            remote,
-            timeout: Duration::from_secs(60),
+            state: StdMutex::new(MultiClientState::new(remote.id.clone(), entries)),
+            remote: remote.id.clone(),
+            timeout: request_timeout(remote),
+            request_limit: request_limit(remote),
+            login: None,
         }
     }
 
+    /// Log in with the user and password of `remote` before the requests, instead of using an API
+    /// token.
+    fn with_login(mut self, remote: Remote) -> Self {
+        self.login = Some(remote);
+        self
+    }
+
     fn for_each_client<F>(&self, func: F)
     where
         F: Fn(&Arc<Client>),
@@ -745,6 +1186,9 @@ macro_rules! try_request {
                 .transpose()
                 .map_err(|err| proxmox_client::Error::Anyhow(err.into()))?;
 
+            // held until the request is done, including the retries on other nodes
+            let _permit = acquire_request_permit(&$self.request_limit).await?;
+
             let mut last_err: Option<proxmox_client::Error> = None;
             let mut timed_out = false;
             // remember the first endpoint to retry once at the end if it only failed to connect
@@ -785,7 +1229,14 @@ macro_rules! try_request {
                     log::error!("client timed out on request {path}, trying another remote");
                 }
 
-                let request = client.$how($method.clone(), $path_and_query, params.as_ref());
+                let request = async {
+                    if let Some(remote) = &$self.login {
+                        ensure_login(&client, remote).await?;
+                    }
+                    client
+                        .$how($method.clone(), $path_and_query, params.as_ref())
+                        .await
+                };
                 match tokio::time::timeout($self.timeout, request).await {
                     // Connection error: the request never reached the server, so failing over to
                     // another node is safe for any method. Remember the first endpoint for retry.
@@ -854,7 +1305,14 @@ macro_rules! try_request {
             if let Some((client, hostname)) = connect_retry {
                 let path = $path_and_query;
                 log::warn!("all endpoints failed, retrying {hostname:?} once - {path}");
-                let request = client.$how($method.clone(), $path_and_query, params.as_ref());
+                let request = async {
+                    if let Some(remote) = &$self.login {
+                        ensure_login(&client, remote).await?;
+                    }
+                    client
+                        .$how($method.clone(), $path_and_query, params.as_ref())
+                        .await
+                };
                 if let Ok(result) = tokio::time::timeout($self.timeout, request).await {
                     if result.is_ok() {
                         if let Ok(mut cache) = crate::remote_cache::RemoteMappingCache::write() {
@@ -955,6 +1413,24 @@ pub async fn probe_tls_connection(
     hostname: String,
     fingerprint: Option<String>,
 ) -> Result<TlsProbeOutcome, Error> {
+    let (outcome, _leaf_fingerprint) = probe_tls(remote_type, hostname, fingerprint).await?;
+    Ok(outcome)
+}
+
+/// Like [`probe_tls_connection`] without a fingerprint, but also returns the fingerprint of the
+/// node's certificate, which is known even for trusted certificates.
+pub async fn probe_tls_connection_fingerprint(
+    remote_type: RemoteType,
+    hostname: String,
+) -> Result<(TlsProbeOutcome, Option<String>), Error> {
+    probe_tls(remote_type, hostname, None).await
+}
+
+async fn probe_tls(
+    remote_type: RemoteType,
+    hostname: String,
+    fingerprint: Option<String>,
+) -> Result<(TlsProbeOutcome, Option<String>), Error> {
     let host_port: Authority = hostname.parse()?;
 
     let uri: http::uri::Uri = format!(
@@ -966,19 +1442,28 @@ pub async fn probe_tls_connection(
 
     // to save the invalid cert we find
     let invalid_cert = Arc::new(StdMutex::new(None));
+    // the fingerprint of the node's own certificate, trusted or not
+    let leaf_fingerprint = Arc::new(StdMutex::new(None));
 
     let options = if let Some(fp) = &fingerprint {
         TlsOptions::parse_fingerprint(fp)?
     } else {
         TlsOptions::Callback(Box::new({
             let invalid_cert = invalid_cert.clone();
+            let leaf_fingerprint = leaf_fingerprint.clone();
             move |valid: bool, chain: &mut X509StoreContextRef| {
+                let depth = chain.error_depth();
                 if let Some(cert) = chain.current_cert() {
+                    let cert = cert
+                        .to_pem()
+                        .map_err(Error::from)
+                        .and_then(|pem| CertificateInfo::from_pem("", &pem));
+                    if depth == 0 {
+                        if let Ok(info) = &cert {
+                            *leaf_fingerprint.lock().unwrap() = info.fingerprint.clone();
+                        }
+                    }
                     if !valid {
-                        let cert = cert
-                            .to_pem()
-                            .map_err(Error::from)
-                            .and_then(|pem| CertificateInfo::from_pem("", &pem));
                         *invalid_cert.lock().unwrap() = Some(cert);
                     }
                 }
@@ -1005,5 +1490,64 @@ pub async fn probe_tls_connection(
     } else {
         TlsProbeOutcome::TrustedCertificate
     };
-    Ok(outcome)
+    let leaf_fingerprint = leaf_fingerprint.lock().unwrap().take();
+    Ok((outcome, leaf_fingerprint))
+}
+
+#[cfg(test)]
+mod tests {
+    use pdm_api_types::Authid;
+
+    use super::*;
+
+    fn remote(id: &str, timeout: Option<u64>, max_connections: Option<u64>) -> Remote {
+        Remote {
+            ty: RemoteType::Pve,
+            id: id.into(),
+            nodes: Vec::new(),
+            authid: Authid::root_auth_id().clone(),
+            token: "".into(),
+            web_url: None,
+            folder: None,
+            timeout,
+            http_proxy: None,
+            max_connections,
+            token_created: None,
+        }
+    }
+
+    #[test]
+    fn timeouts() {
+        assert_eq!(
+            request_timeout(&remote("timeout-default", None, None)),
+            DEFAULT_REQUEST_TIMEOUT
+        );
+        assert_eq!(
+            request_timeout(&remote("timeout-set", Some(300), None)),
+            Duration::from_secs(300)
+        );
+    }
+
+    #[test]
+    fn request_limits() {
+        assert!(request_limit(&remote("limit-none", None, None)).is_none());
+
+        let limited = remote("limit", None, Some(2));
+        let semaphore = request_limit(&limited).unwrap();
+        assert_eq!(semaphore.available_permits(), 2);
+
+        // all clients of the remote share the semaphore
+        let shared = request_limit(&limited).unwrap();
+        assert!(Arc::ptr_eq(&semaphore, &shared));
+
+        // a new limit replaces it
+        let changed = request_limit(&remote("limit", None, Some(4))).unwrap();
+        assert!(!Arc::ptr_eq(&semaphore, &changed));
+        assert_eq!(changed.available_permits(), 4);
+
+        // removing the limit drops it
+        assert!(request_limit(&remote("limit", None, None)).is_none());
+        let readded = request_limit(&remote("limit", None, Some(4))).unwrap();
+        assert!(!Arc::ptr_eq(&changed, &readded));
+    }
 }
@@ -0,0 +1,138 @@
+//! Run the scheduled guest power actions and snapshots, see [`GuestSchedule`].
+
+use anyhow::Error;
+
+use pdm_api_types::guest_schedules::{GuestSchedule, ScheduledGuestAction};
+use pdm_api_types::resource::{GuestAction, GuestType, RemoteResources, Resource};
+
+use crate::api::pve::bulk::{self, BulkAction, BulkGuest, GuestsByNode, SnapshotRetention};
+
+/// The worker and job state type of guest schedule runs.
+pub const WORKER_TYPE: &str = "guest-schedule";
+
+/// Maximum age of the cached resources used to look up the guests.
+const RESOURCE_MAX_AGE: u64 = 60;
+
+/// Maximum number of parallel requests to all remotes.
+const MAX_CONNECTIONS: usize = 10;
+/// Maximum number of parallel requests to a single remote.
+const MAX_CONNECTIONS_PER_REMOTE: usize = 2;
+
+/// The prefix of the snapshots taken by the runs of the job `id`.
+///
+/// Retention only deletes snapshots with the prefix of its own job, see [`SnapshotRetention`].
+fn snapshot_prefix(id: &str) -> String {
+    format!("pdm_{id}_")
+}
+
+/// Check whether the action has to be applied to a guest with the given status.
+///
+/// Guests already in the desired state are skipped, so a run does not fail just because some
+/// guests were started or stopped manually in the meantime. Templates can't be started or
+/// snapshotted and are always skipped.
+fn needs_action(action: ScheduledGuestAction, status: &str, template: bool) -> bool {
+    if template {
+        return false;
+    }
+    match action {
+        ScheduledGuestAction::Start => status != "running",
+        ScheduledGuestAction::Shutdown | ScheduledGuestAction::Stop => status == "running",
+        ScheduledGuestAction::Snapshot => true,
+    }
+}
+
+/// Look up the guests of a job and apply its action to them.
+///
+/// Meant to be run in a worker task. Waits for the remote tasks, logs their outcome for every
+/// guest and fails if the action failed for any of them.
+pub async fn run(job: &GuestSchedule) -> Result<(), Error> {
+    let action = match job.action {
+        ScheduledGuestAction::Start => BulkAction::Power(GuestAction::Start),
+        ScheduledGuestAction::Shutdown => BulkAction::Power(GuestAction::Shutdown),
+        ScheduledGuestAction::Stop => BulkAction::Power(GuestAction::Stop),
+        ScheduledGuestAction::Snapshot => BulkAction::Snapshot {
+            name: bulk::snapshot_name(&snapshot_prefix(&job.id), proxmox_time::epoch_i64())?,
+            retention: job.keep_snapshots.map(|keep| SnapshotRetention {
+                prefix: snapshot_prefix(&job.id),
+                keep: keep as usize,
+            }),
+        },
+    };
+
+    let remote_resources = crate::api::resources::get_resources_impl(
+        RESOURCE_MAX_AGE,
+        job.search.clone(),
+        None,
+        job.view.as_deref(),
+        None,
+    )
+    .await?;
+
+    let mut guests = GuestsByNode::new();
+    let mut skipped = 0;
+
+    for remote in remote_resources {
+        let remote: RemoteResources = remote.into();
+        if let Some(err) = &remote.error {
+            log::warn!(
+                "could not get the guests of remote '{}': {err}",
+                remote.remote
+            );
+        }
+
+        for resource in remote.resources {
+            let (id, node, vmid, ty, status, template) = match resource {
+                Resource::PveQemu(r) => {
+                    (r.id, r.node, r.vmid, GuestType::Qemu, r.status, r.template)
+                }
+                Resource::PveLxc(r) => (r.id, r.node, r.vmid, GuestType::Lxc, r.status, r.template),
+                _ => continue,
+            };
+
+            if !needs_action(job.action, &status, template) {
+                log::info!("{id}: skipped - status is '{status}'");
+                skipped += 1;
+                continue;
+            }
+
+            guests
+                .entry((remote.remote.clone(), node))
+                .or_default()
+                .push(BulkGuest { id, vmid, ty });
+        }
+    }
+
+    if guests.is_empty() {
+        log::info!("nothing to do, skipped {skipped} guest(s)");
+        return Ok(());
+    }
+
+    bulk::run_bulk_action(
+        action,
+        guests,
+        Vec::new(),
+        true,
+        MAX_CONNECTIONS,
+        MAX_CONNECTIONS_PER_REMOTE,
+    )
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn skip_guests_in_desired_state() {
+        use ScheduledGuestAction::*;
+
+        assert!(needs_action(Start, "stopped", false));
+        assert!(!needs_action(Start, "running", false));
+        assert!(needs_action(Shutdown, "running", false));
+        assert!(!needs_action(Shutdown, "stopped", false));
+        assert!(!needs_action(Stop, "stopped", false));
+        assert!(needs_action(Snapshot, "stopped", false));
+        assert!(!needs_action(Snapshot, "stopped", true));
+        assert!(!needs_action(Start, "stopped", true));
+    }
+}
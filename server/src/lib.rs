@@ -1,28 +1,35 @@
 //! Common API crate for PDM.
 
 pub mod acl;
+pub mod alerts;
 pub mod api;
 pub mod api_cache;
 pub mod auth;
 pub mod ceph;
 pub mod context;
 pub mod env;
+pub mod guest_schedules;
 pub mod jobstate;
 pub mod location_cache;
 pub mod metric_collection;
 pub mod namespaced_cache;
+pub mod notifications;
 pub mod parallel_fetcher;
 pub mod remote_cache;
 pub mod remote_tasks;
+pub mod remote_tokens;
 pub mod remote_updates;
 pub mod report;
 pub mod resource_cache;
 pub mod task_utils;
+pub mod token_rotation;
 pub mod views;
 
 pub mod connection;
 pub mod pbs_client;
+pub mod pmg_client;
 pub mod sdn_client;
+pub mod socks_proxy;
 
 #[cfg(any(remote_config = "faked", test))]
 pub mod test_support;
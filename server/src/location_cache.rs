@@ -161,5 +161,7 @@ async fn fetch_remote_location_info(remote: &Remote) -> Result<Option<CachedLoca
 
             Ok(loc)
         }
+        // PMG has no location setting in its node config.
+        RemoteType::Pmg => Ok(None),
     }
 }
@@ -0,0 +1,493 @@
+//! Load balancing recommendations based on the collected CPU and memory history.
+
+use std::collections::{HashMap, HashSet};
+
+use proxmox_rrd_api_types::{RrdMode, RrdTimeframe};
+
+use pdm_api_types::pve::{BalancingNodeLoad, BalancingRecommendation};
+use pdm_api_types::remotes::{Remote, RemoteType};
+use pdm_api_types::resource::{GuestType, Resource};
+
+use super::rrd_cache::{self, RrdCache};
+
+/// Upper limit for the number of recommendations per calculation.
+const MAX_RECOMMENDATIONS: usize = 20;
+
+const GIB: f64 = 1024.0 * 1024.0 * 1024.0;
+
+/// When to consider a node overloaded or a cluster imbalanced, all values are fractions.
+#[derive(Clone, Copy, Debug)]
+pub struct Thresholds {
+    /// Average CPU usage above which a node is overloaded.
+    pub cpu: f64,
+    /// Average memory usage above which a node is overloaded.
+    pub mem: f64,
+    /// Difference in load between the busiest and the least busy node of a cluster above which
+    /// the cluster is imbalanced.
+    pub imbalance: f64,
+}
+
+impl Default for Thresholds {
+    fn default() -> Self {
+        Self {
+            cpu: 0.8,
+            mem: 0.85,
+            imbalance: 0.3,
+        }
+    }
+}
+
+/// The averaged load of a node.
+#[derive(Clone, Debug)]
+struct NodeLoad {
+    remote: String,
+    node: String,
+    /// The hostname to use as remote migration endpoint, if the node is configured as one.
+    endpoint: Option<String>,
+    /// CPU usage in number of CPUs.
+    cpu: f64,
+    maxcpu: f64,
+    mem: f64,
+    maxmem: f64,
+}
+
+impl NodeLoad {
+    fn cpu_ratio(&self) -> f64 {
+        self.cpu / self.maxcpu.max(1.0)
+    }
+
+    fn mem_ratio(&self) -> f64 {
+        self.mem / self.maxmem.max(1.0)
+    }
+
+    fn score(&self) -> f64 {
+        self.cpu_ratio().max(self.mem_ratio())
+    }
+
+    fn is_overloaded(&self, thresholds: &Thresholds) -> bool {
+        self.cpu_ratio() > thresholds.cpu || self.mem_ratio() > thresholds.mem
+    }
+
+    fn with_guest(&self, guest: &GuestLoad, add: bool) -> Self {
+        let sign = if add { 1.0 } else { -1.0 };
+        Self {
+            cpu: (self.cpu + sign * guest.cpu).max(0.0),
+            mem: (self.mem + sign * guest.mem).max(0.0),
+            ..self.clone()
+        }
+    }
+}
+
+/// The averaged load of a running guest.
+#[derive(Clone, Debug)]
+struct GuestLoad {
+    remote: String,
+    node: String,
+    vmid: u32,
+    guest_type: GuestType,
+    name: String,
+    /// CPU usage in number of CPUs.
+    cpu: f64,
+    mem: f64,
+}
+
+fn percent(ratio: f64) -> String {
+    format!("{:.0}%", ratio * 100.0)
+}
+
+/// Compute recommendations for the nodes of all remotes the user can see.
+pub fn calculate_recommendations(
+    remotes: &HashMap<String, Remote>,
+    timeframe: RrdTimeframe,
+    thresholds: Thresholds,
+    check_remote_privs: impl Fn(&str) -> bool,
+    is_resource_included: impl Fn(&str, &Resource) -> bool,
+) -> Vec<BalancingRecommendation> {
+    let cache = rrd_cache::get_cache();
+    let mapping = crate::remote_cache::RemoteMappingCache::get();
+
+    let mut nodes = Vec::new();
+    let mut guests = Vec::new();
+
+    for (remote_name, remote) in remotes {
+        if remote.ty != RemoteType::Pve || !check_remote_privs(remote_name) {
+            continue;
+        }
+
+        let Ok(Some(data)) =
+            crate::api::resources::get_cached_resources_blocking(remote_name, i64::MAX as u64)
+        else {
+            continue;
+        };
+
+        for resource in &data.resources {
+            if !is_resource_included(remote_name, resource) {
+                continue;
+            }
+
+            match resource {
+                Resource::PveNode(node) if node.status == "online" => {
+                    let base = format!("pve/{remote_name}/node/{}", node.node);
+                    let cpu = average(&cache, &base, "cpu_current", timeframe).unwrap_or(node.cpu);
+                    let mem =
+                        average(&cache, &base, "mem_used", timeframe).unwrap_or(node.mem as f64);
+                    nodes.push(NodeLoad {
+                        remote: remote_name.clone(),
+                        node: node.node.clone(),
+                        endpoint: mapping
+                            .node_name_to_hostname(remote_name, &node.node)
+                            .map(str::to_string),
+                        cpu: cpu * node.maxcpu,
+                        maxcpu: node.maxcpu,
+                        mem,
+                        maxmem: node.maxmem as f64,
+                    });
+                }
+                Resource::PveQemu(qemu) if qemu.status == "running" && !qemu.template => {
+                    let base = format!("pve/{remote_name}/qemu/{}", qemu.vmid);
+                    let cpu = average(&cache, &base, "cpu_current", timeframe).unwrap_or(qemu.cpu);
+                    guests.push(GuestLoad {
+                        remote: remote_name.clone(),
+                        node: qemu.node.clone(),
+                        vmid: qemu.vmid,
+                        guest_type: GuestType::Qemu,
+                        name: qemu.name.clone(),
+                        cpu: cpu * qemu.maxcpu,
+                        mem: average(&cache, &base, "mem_used", timeframe)
+                            .unwrap_or(qemu.mem as f64),
+                    });
+                }
+                Resource::PveLxc(lxc) if lxc.status == "running" && !lxc.template => {
+                    let base = format!("pve/{remote_name}/lxc/{}", lxc.vmid);
+                    let cpu = average(&cache, &base, "cpu_current", timeframe).unwrap_or(lxc.cpu);
+                    guests.push(GuestLoad {
+                        remote: remote_name.clone(),
+                        node: lxc.node.clone(),
+                        vmid: lxc.vmid,
+                        guest_type: GuestType::Lxc,
+                        name: lxc.name.clone(),
+                        cpu: cpu * lxc.maxcpu,
+                        mem: average(&cache, &base, "mem_used", timeframe)
+                            .unwrap_or(lxc.mem as f64),
+                    });
+                }
+                _ => {}
+            }
+        }
+    }
+
+    recommend(nodes, &guests, &thresholds)
+}
+
+/// The average of all finite values of a metric in the timeframe.
+fn average(cache: &RrdCache, base: &str, metric: &str, timeframe: RrdTimeframe) -> Option<f64> {
+    let entry = cache
+        .extract_data(base, metric, timeframe, RrdMode::Average)
+        .ok()??;
+
+    let values: Vec<f64> = entry
+        .data
+        .iter()
+        .flatten()
+        .copied()
+        .filter(|value| value.is_finite())
+        .collect();
+    if values.is_empty() {
+        return None;
+    }
+    Some(values.iter().sum::<f64>() / values.len() as f64)
+}
+
+/// Greedily recommend migrations off the busiest nodes.
+///
+/// A node is a migration source if it is overloaded, or if its load exceeds the one of the least
+/// busy node of its cluster by more than the imbalance threshold. Guests are moved within the
+/// cluster if possible. Guests of overloaded nodes without any in-cluster target are moved to a
+/// node of another remote which is configured as migration endpoint. A migration is only
+/// recommended if it lowers the load of the busier of the two nodes and keeps the target below
+/// the thresholds.
+fn recommend(
+    mut nodes: Vec<NodeLoad>,
+    guests: &[GuestLoad],
+    thresholds: &Thresholds,
+) -> Vec<BalancingRecommendation> {
+    let mut recommendations = Vec::new();
+    let mut moved: HashSet<(&str, u32)> = HashSet::new();
+    let mut exhausted: HashSet<usize> = HashSet::new();
+
+    while recommendations.len() < MAX_RECOMMENDATIONS {
+        let Some((source, reason)) = find_source(&nodes, &exhausted, thresholds) else {
+            break;
+        };
+
+        let candidates: Vec<&GuestLoad> = guests
+            .iter()
+            .filter(|guest| {
+                guest.remote == nodes[source].remote
+                    && guest.node == nodes[source].node
+                    && !moved.contains(&(guest.remote.as_str(), guest.vmid))
+            })
+            .collect();
+
+        let in_cluster =
+            |index: usize| index != source && nodes[index].remote == nodes[source].remote;
+        let other_remote = |index: usize| {
+            nodes[index].remote != nodes[source].remote && nodes[index].endpoint.is_some()
+        };
+
+        let mut best = best_move(&nodes, source, &candidates, thresholds, in_cluster);
+        if best.is_none() && nodes[source].is_overloaded(thresholds) {
+            best = best_move(&nodes, source, &candidates, thresholds, other_remote);
+        }
+
+        let Some((target, guest)) = best else {
+            exhausted.insert(source);
+            continue;
+        };
+
+        let new_source = nodes[source].with_guest(guest, false);
+        let new_target = nodes[target].with_guest(guest, true);
+
+        let reason = format!(
+            "{reason}; moving guest {} (average {:.1} CPUs, {:.1} GiB memory) to '{}' brings '{}' \
+            to {} CPU / {} memory and '{}' from {} to {} load",
+            guest.vmid,
+            guest.cpu,
+            guest.mem / GIB,
+            nodes[target].node,
+            nodes[source].node,
+            percent(new_source.cpu_ratio()),
+            percent(new_source.mem_ratio()),
+            nodes[target].node,
+            percent(nodes[target].score()),
+            percent(new_target.score()),
+        );
+
+        let remote_migration = nodes[target].remote != nodes[source].remote;
+        recommendations.push(BalancingRecommendation {
+            remote: guest.remote.clone(),
+            vmid: guest.vmid,
+            guest_type: guest.guest_type,
+            name: guest.name.clone(),
+            guest_cpu: guest.cpu,
+            guest_mem: guest.mem as u64,
+            target_remote: nodes[target].remote.clone(),
+            target_endpoint: if remote_migration {
+                nodes[target].endpoint.clone()
+            } else {
+                None
+            },
+            source: node_evidence(&nodes[source], &new_source),
+            target: node_evidence(&nodes[target], &new_target),
+            reason,
+        });
+
+        moved.insert((guest.remote.as_str(), guest.vmid));
+        nodes[source] = new_source;
+        nodes[target] = new_target;
+    }
+
+    recommendations
+}
+
+fn node_evidence(before: &NodeLoad, after: &NodeLoad) -> BalancingNodeLoad {
+    BalancingNodeLoad {
+        node: before.node.clone(),
+        cpu: before.cpu_ratio(),
+        mem: before.mem_ratio(),
+        projected_cpu: after.cpu_ratio(),
+        projected_mem: after.mem_ratio(),
+    }
+}
+
+/// Find the busiest node which should get rid of a guest, with the reason why.
+fn find_source(
+    nodes: &[NodeLoad],
+    exhausted: &HashSet<usize>,
+    thresholds: &Thresholds,
+) -> Option<(usize, String)> {
+    let mut order: Vec<usize> = (0..nodes.len())
+        .filter(|index| !exhausted.contains(index))
+        .collect();
+    order.sort_by(|a, b| nodes[*b].score().total_cmp(&nodes[*a].score()));
+
+    for index in order {
+        let node = &nodes[index];
+        if node.is_overloaded(thresholds) {
+            return Some((
+                index,
+                format!(
+                    "node '{}' of remote '{}' averaged {} CPU and {} memory usage, above the \
+                    thresholds of {} and {}",
+                    node.node,
+                    node.remote,
+                    percent(node.cpu_ratio()),
+                    percent(node.mem_ratio()),
+                    percent(thresholds.cpu),
+                    percent(thresholds.mem),
+                ),
+            ));
+        }
+
+        let least_busy = nodes
+            .iter()
+            .filter(|other| other.remote == node.remote)
+            .min_by(|a, b| a.score().total_cmp(&b.score()))?;
+        let difference = node.score() - least_busy.score();
+        if difference > thresholds.imbalance {
+            return Some((
+                index,
+                format!(
+                    "node '{}' of remote '{}' averaged {} load while '{}' averaged {}, a \
+                    difference above {}",
+                    node.node,
+                    node.remote,
+                    percent(node.score()),
+                    least_busy.node,
+                    percent(least_busy.score()),
+                    percent(thresholds.imbalance),
+                ),
+            ));
+        }
+    }
+
+    None
+}
+
+/// Find the guest and target node which lower the load of the busier node the most.
+fn best_move<'a>(
+    nodes: &[NodeLoad],
+    source: usize,
+    candidates: &[&'a GuestLoad],
+    thresholds: &Thresholds,
+    is_target: impl Fn(usize) -> bool,
+) -> Option<(usize, &'a GuestLoad)> {
+    let current = nodes[source].score();
+    let mut best: Option<(f64, usize, &GuestLoad)> = None;
+
+    for target in (0..nodes.len()).filter(|index| is_target(*index)) {
+        for guest in candidates {
+            let new_target = nodes[target].with_guest(guest, true);
+            if new_target.is_overloaded(thresholds) || new_target.mem > new_target.maxmem {
+                continue;
+            }
+            let new_source = nodes[source].with_guest(guest, false);
+            let peak = new_source.score().max(new_target.score());
+            if peak >= current {
+                continue;
+            }
+            if best.is_none_or(|(best_peak, _, _)| peak < best_peak) {
+                best = Some((peak, target, guest));
+            }
+        }
+    }
+
+    best.map(|(_, target, guest)| (target, guest))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node(remote: &str, node: &str, cpu: f64, mem: f64, endpoint: bool) -> NodeLoad {
+        NodeLoad {
+            remote: remote.to_string(),
+            node: node.to_string(),
+            endpoint: endpoint.then(|| format!("{node}.example.com")),
+            cpu,
+            maxcpu: 10.0,
+            mem: mem * GIB,
+            maxmem: 100.0 * GIB,
+        }
+    }
+
+    fn guest(remote: &str, node: &str, vmid: u32, cpu: f64, mem: f64) -> GuestLoad {
+        GuestLoad {
+            remote: remote.to_string(),
+            node: node.to_string(),
+            vmid,
+            guest_type: GuestType::Qemu,
+            name: format!("vm{vmid}"),
+            cpu,
+            mem: mem * GIB,
+        }
+    }
+
+    #[test]
+    fn balanced_cluster_has_no_recommendations() {
+        let nodes = vec![
+            node("a", "n1", 5.0, 50.0, false),
+            node("a", "n2", 4.0, 40.0, false),
+        ];
+        let guests = [guest("a", "n1", 100, 2.0, 10.0)];
+
+        assert!(recommend(nodes, &guests, &Thresholds::default()).is_empty());
+    }
+
+    #[test]
+    fn moves_guest_off_overloaded_node() {
+        let nodes = vec![
+            node("a", "n1", 9.0, 60.0, false),
+            node("a", "n2", 2.0, 30.0, false),
+            node("a", "n3", 5.0, 50.0, false),
+        ];
+        let guests = [
+            guest("a", "n1", 100, 1.0, 5.0),
+            guest("a", "n1", 101, 3.0, 10.0),
+            guest("a", "n2", 102, 1.0, 5.0),
+        ];
+
+        let recommendations = recommend(nodes, &guests, &Thresholds::default());
+        assert_eq!(recommendations.len(), 1);
+
+        let recommendation = &recommendations[0];
+        assert_eq!(recommendation.vmid, 101);
+        assert_eq!(recommendation.source.node, "n1");
+        assert_eq!(recommendation.target.node, "n2");
+        assert!(!recommendation.is_remote_migration());
+        assert_eq!(recommendation.source.cpu, 0.9);
+        assert_eq!(recommendation.source.projected_cpu, 0.6);
+        assert!(recommendation.reason.contains("above the thresholds"));
+    }
+
+    #[test]
+    fn falls_back_to_remote_migration() {
+        let nodes = vec![
+            node("a", "n1", 9.5, 60.0, false),
+            node("a", "n2", 9.0, 60.0, false),
+            node("b", "m1", 1.0, 20.0, false),
+            node("b", "m2", 1.0, 20.0, true),
+        ];
+        let guests = [guest("a", "n1", 100, 3.0, 10.0)];
+
+        let recommendations = recommend(nodes, &guests, &Thresholds::default());
+        assert_eq!(recommendations.len(), 1);
+
+        let recommendation = &recommendations[0];
+        assert!(recommendation.is_remote_migration());
+        // only nodes configured as endpoint are considered
+        assert_eq!(recommendation.target.node, "m2");
+        assert_eq!(
+            recommendation.target_endpoint.as_deref(),
+            Some("m2.example.com")
+        );
+    }
+
+    #[test]
+    fn evens_out_imbalanced_cluster() {
+        let nodes = vec![
+            node("a", "n1", 7.0, 70.0, false),
+            node("a", "n2", 1.0, 10.0, false),
+        ];
+        let guests = [
+            guest("a", "n1", 100, 3.0, 30.0),
+            guest("a", "n1", 101, 1.0, 5.0),
+        ];
+
+        let recommendations = recommend(nodes, &guests, &Thresholds::default());
+        assert_eq!(recommendations.len(), 1);
+        assert_eq!(recommendations[0].vmid, 100);
+        assert_eq!(recommendations[0].target.node, "n2");
+        assert!(recommendations[0].reason.contains("a difference above"));
+    }
+}
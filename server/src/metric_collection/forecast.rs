@@ -0,0 +1,240 @@
+//! Forecasting of the usage of storages and datastores, based on their RRD history.
+
+use std::collections::HashMap;
+use std::sync::{LazyLock, Mutex};
+
+use proxmox_rrd_api_types::{RrdMode, RrdTimeframe};
+
+use pdm_api_types::resource::Resource;
+
+use super::rrd_cache::{self, RrdCache};
+
+/// Estimates further away than this are considered meaningless and capped, in seconds.
+pub const MAX_FORECAST: i64 = 10 * 365 * 86400;
+
+/// How long (in seconds) a computed estimate is reused before it is recomputed.
+const ESTIMATE_MAX_AGE: i64 = 15 * 60;
+
+/// The maximum number of points used for the robust regression, which is quadratic in the
+/// number of points.
+const MAX_ROBUST_POINTS: usize = 120;
+
+static ESTIMATES: LazyLock<Mutex<HashMap<String, (i64, Option<i64>)>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+fn median(values: &mut [f64]) -> f64 {
+    values.sort_unstable_by(f64::total_cmp);
+    let mid = values.len() / 2;
+    if values.len() % 2 == 0 {
+        (values[mid - 1] + values[mid]) / 2.0
+    } else {
+        values[mid]
+    }
+}
+
+/// Fit a line through `(x, y)` points with the Theil-Sen estimator and return its slope and
+/// intercept.
+///
+/// The slope is the median of the slopes between all pairs of points, so single outliers, like
+/// a large backup which is pruned again shortly after, do not skew the result like they do with
+/// least squares. If there are more than [`MAX_ROBUST_POINTS`] points, an evenly spaced subset is
+/// used.
+///
+/// Returns `None` if there are less than two distinct x values.
+pub fn robust_regression(points: &[(f64, f64)]) -> Option<(f64, f64)> {
+    let step = points.len().div_ceil(MAX_ROBUST_POINTS).max(1);
+    let points: Vec<(f64, f64)> = points.iter().step_by(step).copied().collect();
+
+    let mut slopes = Vec::with_capacity(points.len() * points.len().saturating_sub(1) / 2);
+    for (i, (x1, y1)) in points.iter().enumerate() {
+        for (x2, y2) in &points[i + 1..] {
+            if x1 != x2 {
+                slopes.push((y2 - y1) / (x2 - x1));
+            }
+        }
+    }
+
+    if slopes.is_empty() {
+        return None;
+    }
+
+    let slope = median(&mut slopes);
+    let mut intercepts: Vec<f64> = points.iter().map(|(x, y)| y - slope * x).collect();
+
+    Some((slope, median(&mut intercepts)))
+}
+
+/// Estimate when the usage given by `(time, used)` points reaches `total`.
+///
+/// Returns the estimated time (UNIX epoch), `now` if `total` is already reached, and `None` if
+/// the usage does not grow or the estimate is more than [`MAX_FORECAST`] in the future.
+pub fn estimate_full_time(points: &[(f64, f64)], total: f64, now: i64) -> Option<i64> {
+    let (slope, intercept) = robust_regression(points)?;
+    if slope <= 0.0 {
+        return None;
+    }
+
+    let full = ((total - intercept) / slope) as i64;
+    if full <= now {
+        Some(now)
+    } else if full - now > MAX_FORECAST {
+        None
+    } else {
+        Some(full)
+    }
+}
+
+/// Collect the `(time, value)` points of a metric from the RRD cache.
+pub fn rrd_points(
+    cache: &RrdCache,
+    basedir: &str,
+    metric: &str,
+    timeframe: RrdTimeframe,
+) -> Vec<(f64, f64)> {
+    let entry = match cache.extract_data(basedir, metric, timeframe, RrdMode::Average) {
+        Ok(Some(entry)) => entry,
+        Ok(None) => return Vec::new(),
+        Err(err) => {
+            log::error!("could not read RRD data of '{basedir}/{metric}' - {err}");
+            return Vec::new();
+        }
+    };
+
+    entry
+        .data
+        .iter()
+        .enumerate()
+        .filter_map(|(i, value)| {
+            let value = (*value)?;
+            value
+                .is_finite()
+                .then(|| ((entry.start + i as u64 * entry.resolution) as f64, value))
+        })
+        .collect()
+}
+
+/// Estimate when the disk of the storage or datastore with the RRD base directory `basedir` is
+/// full, based on the usage of the last month.
+pub fn estimate_disk_full(cache: &RrdCache, basedir: &str, now: i64) -> Option<i64> {
+    let used = rrd_points(cache, basedir, "disk_used", RrdTimeframe::Month);
+    let total = rrd_points(cache, basedir, "disk_total", RrdTimeframe::Hour)
+        .last()
+        .map(|(_, total)| *total)?;
+
+    estimate_full_time(&used, total, now)
+}
+
+/// The RRD base directory of a storage or datastore resource.
+pub fn disk_rrd_basedir(remote: &str, resource: &Resource) -> Option<String> {
+    match resource {
+        Resource::PveStorage(storage) => Some(format!(
+            "pve/{remote}/storage/{}/{}",
+            storage.node, storage.storage
+        )),
+        Resource::PbsDatastore(datastore) => {
+            Some(format!("pbs/{remote}/datastore/{}", datastore.name))
+        }
+        _ => None,
+    }
+}
+
+/// Like [`estimate_disk_full`], but reuses estimates computed in the last
+/// [`ESTIMATE_MAX_AGE`] seconds.
+pub fn cached_estimate_disk_full(cache: &RrdCache, basedir: &str, now: i64) -> Option<i64> {
+    if let Some((time, estimate)) = ESTIMATES.lock().unwrap().get(basedir) {
+        if now - time < ESTIMATE_MAX_AGE {
+            return *estimate;
+        }
+    }
+
+    let estimate = estimate_disk_full(cache, basedir, now);
+
+    let mut estimates = ESTIMATES.lock().unwrap();
+    estimates.retain(|_, (time, _)| now - *time < ESTIMATE_MAX_AGE);
+    estimates.insert(basedir.to_string(), (now, estimate));
+
+    estimate
+}
+
+/// Set the estimated full date of all storage and datastore resources of a remote.
+///
+/// The RRD data is read and evaluated in a blocking task, so this is safe to call from the
+/// per-remote tasks of the resource API.
+pub async fn set_estimated_full(remote: &str, resources: &mut [Resource]) {
+    let basedirs: Vec<(usize, String)> = resources
+        .iter()
+        .enumerate()
+        .filter_map(|(i, resource)| Some((i, disk_rrd_basedir(remote, resource)?)))
+        .collect();
+
+    if basedirs.is_empty() {
+        return;
+    }
+
+    let estimates = tokio::task::spawn_blocking(move || {
+        let cache = rrd_cache::get_cache();
+        let now = proxmox_time::epoch_i64();
+
+        basedirs
+            .into_iter()
+            .map(|(i, basedir)| (i, cached_estimate_disk_full(&cache, &basedir, now)))
+            .collect::<Vec<_>>()
+    })
+    .await;
+
+    let estimates = match estimates {
+        Ok(estimates) => estimates,
+        Err(err) => {
+            log::error!("could not estimate disk usage of remote '{remote}' - {err}");
+            return;
+        }
+    };
+
+    for (i, estimate) in estimates {
+        match &mut resources[i] {
+            Resource::PveStorage(storage) => storage.estimated_full = estimate,
+            Resource::PbsDatastore(datastore) => datastore.estimated_full = estimate,
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn robust() {
+        let points = [(0.0, 1.0), (1.0, 3.0), (2.0, 5.0), (3.0, 7.0)];
+        assert_eq!(robust_regression(&points), Some((2.0, 1.0)));
+
+        // a single outlier does not change the fit
+        let points = [(0.0, 1.0), (1.0, 3.0), (2.0, 100.0), (3.0, 7.0), (4.0, 9.0)];
+        assert_eq!(robust_regression(&points), Some((2.0, 1.0)));
+
+        assert_eq!(robust_regression(&[(1.0, 1.0)]), None);
+        assert_eq!(robust_regression(&[(1.0, 1.0), (1.0, 2.0)]), None);
+
+        // large inputs are thinned out
+        let points: Vec<(f64, f64)> = (0..1000).map(|x| (x as f64, 3.0 * x as f64)).collect();
+        assert_eq!(robust_regression(&points), Some((3.0, 0.0)));
+    }
+
+    #[test]
+    fn full_time() {
+        let day = 86400.0;
+        let points = [(0.0, 10.0), (day, 20.0), (2.0 * day, 30.0)];
+
+        assert_eq!(estimate_full_time(&points, 100.0, 0), Some(9 * 86400));
+        assert_eq!(
+            estimate_full_time(&points, 25.0, 3 * 86400),
+            Some(3 * 86400)
+        );
+
+        let shrinking = [(0.0, 30.0), (day, 20.0)];
+        assert_eq!(estimate_full_time(&shrinking, 100.0, 0), None);
+
+        let slow = [(0.0, 0.0), (day, 0.0001)];
+        assert_eq!(estimate_full_time(&slow, 100.0, 0), None);
+    }
+}
@@ -0,0 +1,402 @@
+//! Forwarding of collected metrics to external metric servers (InfluxDB and Graphite).
+//!
+//! Every datapoint that ends up in the RRD cache is also queued here, as long as at least one
+//! metric server is enabled. A background task periodically sends the queued datapoints to all
+//! enabled metric servers. Datapoints which could not be sent are kept in a queue per metric
+//! server and are retried with the next batch, up to [`MAX_QUEUED_DATAPOINTS`].
+
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use anyhow::{Error, format_err};
+use serde_json::{Map, Value};
+use tokio::io::AsyncWriteExt;
+use tokio::net::{TcpStream, UdpSocket};
+
+use proxmox_metrics::{Metrics, MetricsData};
+use proxmox_section_config::typed::SectionConfigData;
+
+use pdm_api_types::metric_servers::{Graphite, GraphiteProtocol, MetricServerEntry};
+
+use super::rrd_name::{MetricSource, RrdName};
+
+/// Interval in which queued datapoints are sent.
+const FLUSH_INTERVAL: Duration = Duration::from_secs(10);
+/// Timeout for sending a batch of datapoints to a single metric server.
+const SEND_TIMEOUT: Duration = Duration::from_secs(30);
+/// Maximum number of datapoints kept for a metric server which cannot be reached.
+const MAX_QUEUED_DATAPOINTS: usize = 500_000;
+
+const DEFAULT_INFLUXDB_ORGANIZATION: &str = "proxmox";
+const DEFAULT_INFLUXDB_BUCKET: &str = "proxmox";
+const DEFAULT_INFLUXDB_MAX_BODY_SIZE: usize = 25_000_000;
+const DEFAULT_GRAPHITE_PATH: &str = "proxmox";
+const DEFAULT_MTU: u16 = 1500;
+/// Size of the chunks written to a graphite TCP connection.
+const GRAPHITE_TCP_CHUNK_SIZE: usize = 64 * 1024;
+/// Maximum size of IP and UDP headers, subtracted from the MTU.
+const UDP_HEADER_SIZE: usize = 48;
+
+/// Whether any metric server is enabled, datapoints are only queued if set.
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static PENDING: Mutex<Vec<Arc<Datapoint>>> = Mutex::new(Vec::new());
+
+#[derive(Debug)]
+struct Datapoint {
+    name: String,
+    value: f64,
+    timestamp: i64,
+}
+
+/// Queue a datapoint to be sent to the enabled metric servers.
+pub(super) fn record_value(name: &str, value: f64, timestamp: i64) {
+    if !ENABLED.load(Ordering::Relaxed) || !value.is_finite() {
+        return;
+    }
+
+    PENDING.lock().unwrap().push(Arc::new(Datapoint {
+        name: name.to_string(),
+        value,
+        timestamp,
+    }));
+}
+
+struct ServerState {
+    config: MetricServerEntry,
+    queue: VecDeque<Arc<Datapoint>>,
+    failing: bool,
+}
+
+impl ServerState {
+    fn new(config: MetricServerEntry) -> Self {
+        Self {
+            config,
+            queue: VecDeque::new(),
+            failing: false,
+        }
+    }
+
+    fn enqueue(&mut self, datapoints: &[Arc<Datapoint>]) {
+        for datapoint in datapoints {
+            let include = match RrdName::parse(&datapoint.name).map(|name| name.source) {
+                Some(MetricSource::Remote(remote)) => self.config.includes_remote(remote),
+                Some(MetricSource::Host) => true,
+                None => false,
+            };
+            if include {
+                self.queue.push_back(Arc::clone(datapoint));
+            }
+        }
+
+        if self.queue.len() > MAX_QUEUED_DATAPOINTS {
+            let dropped = self.queue.len() - MAX_QUEUED_DATAPOINTS;
+            self.queue.drain(..dropped);
+            log::warn!(
+                "metric server '{}': queue full, dropped {dropped} datapoints",
+                self.config.name()
+            );
+        }
+    }
+
+    async fn flush(&mut self) {
+        if self.queue.is_empty() {
+            return;
+        }
+
+        let result = match tokio::time::timeout(
+            SEND_TIMEOUT,
+            send(&self.config, self.queue.make_contiguous()),
+        )
+        .await
+        {
+            Ok(result) => result,
+            Err(_) => Err(format_err!("timeout")),
+        };
+
+        let name = self.config.name();
+        match result {
+            Ok(()) => {
+                self.queue.clear();
+                if self.failing {
+                    log::info!("metric server '{name}': sending metrics succeeded again");
+                    self.failing = false;
+                }
+            }
+            // Parts of the batch might have been sent already, sending them again just
+            // overwrites the values with the same timestamp.
+            Err(err) => {
+                if !self.failing {
+                    log::warn!(
+                        "metric server '{name}': sending metrics failed, will retry - {err:#}"
+                    );
+                    self.failing = true;
+                }
+            }
+        }
+    }
+}
+
+/// Periodically send the queued datapoints to all enabled metric servers.
+pub(super) async fn metric_server_task() {
+    let mut servers: HashMap<String, ServerState> = HashMap::new();
+
+    let mut interval = tokio::time::interval(FLUSH_INTERVAL);
+    interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+
+    loop {
+        interval.tick().await;
+
+        match pdm_config::metric_servers::config() {
+            Ok((config, _)) => update_servers(&mut servers, config),
+            Err(err) => log::error!("could not load metric server config - {err:#}"),
+        }
+        ENABLED.store(!servers.is_empty(), Ordering::Relaxed);
+
+        let datapoints = std::mem::take(&mut *PENDING.lock().unwrap());
+        for server in servers.values_mut() {
+            server.enqueue(&datapoints);
+        }
+        drop(datapoints);
+
+        futures::future::join_all(servers.values_mut().map(|server| server.flush())).await;
+    }
+}
+
+// Sync the server states with the config, queues are kept for servers which were only modified.
+fn update_servers(
+    servers: &mut HashMap<String, ServerState>,
+    config: SectionConfigData<MetricServerEntry>,
+) {
+    let mut enabled: HashMap<String, MetricServerEntry> = config
+        .into_iter()
+        .filter(|(_, entry)| entry.enabled())
+        .collect();
+
+    servers.retain(|name, server| match enabled.remove(name) {
+        Some(entry) => {
+            server.config = entry;
+            true
+        }
+        None => false,
+    });
+
+    for (name, entry) in enabled {
+        servers.insert(name, ServerState::new(entry));
+    }
+}
+
+async fn send(config: &MetricServerEntry, datapoints: &[Arc<Datapoint>]) -> Result<(), Error> {
+    match config {
+        MetricServerEntry::InfluxDbHttp(config) => {
+            let metrics = proxmox_metrics::influxdb_http(
+                &config.url,
+                config
+                    .organization
+                    .as_deref()
+                    .unwrap_or(DEFAULT_INFLUXDB_ORGANIZATION),
+                config.bucket.as_deref().unwrap_or(DEFAULT_INFLUXDB_BUCKET),
+                config.token.as_deref(),
+                config.verify_tls.unwrap_or(true),
+                config
+                    .max_body_size
+                    .unwrap_or(DEFAULT_INFLUXDB_MAX_BODY_SIZE),
+            )?;
+            send_influxdb(metrics, datapoints).await
+        }
+        MetricServerEntry::InfluxDbUdp(config) => {
+            let metrics = proxmox_metrics::influxdb_udp(
+                &config.host,
+                Some(config.mtu.unwrap_or(DEFAULT_MTU)),
+            );
+            send_influxdb(metrics, datapoints).await
+        }
+        MetricServerEntry::Graphite(config) => send_graphite(config, datapoints).await,
+    }
+}
+
+async fn send_influxdb(metrics: Metrics, datapoints: &[Arc<Datapoint>]) -> Result<(), Error> {
+    for data in influxdb_data(datapoints)? {
+        metrics.send_data(Arc::new(data)).await?;
+    }
+    metrics.join().await
+}
+
+// Datapoints of the same object and timestamp are combined into one measurement with multiple
+// fields, e.g. `pdm_pve_node,remote=a,node=n1 cpu_current=0.5,mem_used=1024`.
+fn influxdb_data(datapoints: &[Arc<Datapoint>]) -> Result<Vec<MetricsData>, Error> {
+    type Key<'a> = (String, Vec<(&'static str, &'a str)>, i64);
+    let mut grouped: BTreeMap<Key, Map<String, Value>> = BTreeMap::new();
+
+    for datapoint in datapoints {
+        let Some(name) = RrdName::parse(&datapoint.name) else {
+            continue;
+        };
+        let measurement = match name.kind {
+            Some(kind) => format!("pdm_{kind}"),
+            None => "pdm".to_string(),
+        };
+        grouped
+            .entry((measurement, name.labels, datapoint.timestamp))
+            .or_default()
+            .insert(name.metric.to_string(), datapoint.value.into());
+    }
+
+    grouped
+        .into_iter()
+        .map(|((measurement, labels, timestamp), fields)| {
+            let mut data = MetricsData::new(&measurement, timestamp, fields)?;
+            for (tag, value) in labels {
+                data = data.tag(tag, value);
+            }
+            Ok(data)
+        })
+        .collect()
+}
+
+async fn send_graphite(config: &Graphite, datapoints: &[Arc<Datapoint>]) -> Result<(), Error> {
+    let path = config.path.as_deref().unwrap_or(DEFAULT_GRAPHITE_PATH);
+    let lines = graphite_lines(path, datapoints);
+
+    match config.proto.unwrap_or_default() {
+        GraphiteProtocol::Tcp => {
+            let mut stream = TcpStream::connect(&config.host).await?;
+            for chunk in chunk_lines(&lines, GRAPHITE_TCP_CHUNK_SIZE) {
+                stream.write_all(chunk.as_bytes()).await?;
+            }
+            stream.shutdown().await?;
+        }
+        GraphiteProtocol::Udp => {
+            let addr = tokio::net::lookup_host(&config.host)
+                .await?
+                .next()
+                .ok_or_else(|| format_err!("could not resolve '{}'", config.host))?;
+            let bind_addr: SocketAddr = match addr {
+                SocketAddr::V4(_) => "0.0.0.0:0".parse()?,
+                SocketAddr::V6(_) => "[::]:0".parse()?,
+            };
+            let socket = UdpSocket::bind(bind_addr).await?;
+            socket.connect(addr).await?;
+
+            let max_size = usize::from(config.mtu.unwrap_or(DEFAULT_MTU)) - UDP_HEADER_SIZE;
+            for chunk in chunk_lines(&lines, max_size) {
+                socket.send(chunk.as_bytes()).await?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+// Format datapoints with the graphite plaintext protocol, e.g.
+// `proxmox.pve_node.cluster-a.node1.cpu_current 0.5 1700000000`.
+fn graphite_lines(path: &str, datapoints: &[Arc<Datapoint>]) -> Vec<String> {
+    let mut lines = Vec::with_capacity(datapoints.len());
+
+    for datapoint in datapoints {
+        let Some(name) = RrdName::parse(&datapoint.name) else {
+            continue;
+        };
+
+        let mut line = sanitize_graphite_path(path);
+        if let Some(kind) = name.kind {
+            line.push('.');
+            line.push_str(kind);
+        }
+        for (_, value) in &name.labels {
+            line.push('.');
+            line.push_str(&sanitize_graphite_path(value));
+        }
+        line.push('.');
+        line.push_str(&sanitize_graphite_path(name.metric));
+        line.push_str(&format!(" {} {}\n", datapoint.value, datapoint.timestamp));
+
+        lines.push(line);
+    }
+
+    lines
+}
+
+fn sanitize_graphite_path(component: &str) -> String {
+    component
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+// Combine lines into chunks of at most `max_size` bytes, longer lines get a chunk of their own.
+fn chunk_lines(lines: &[String], max_size: usize) -> Vec<String> {
+    let mut chunks = Vec::new();
+    let mut current = String::new();
+
+    for line in lines {
+        if !current.is_empty() && current.len() + line.len() > max_size {
+            chunks.push(std::mem::take(&mut current));
+        }
+        current.push_str(line);
+    }
+    if !current.is_empty() {
+        chunks.push(current);
+    }
+
+    chunks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn datapoint(name: &str, value: f64, timestamp: i64) -> Arc<Datapoint> {
+        Arc::new(Datapoint {
+            name: name.to_string(),
+            value,
+            timestamp,
+        })
+    }
+
+    #[test]
+    fn graphite_format() {
+        let datapoints = [
+            datapoint("pve/cluster-a/node/node1/cpu_current", 0.5, 100),
+            datapoint(
+                "pve/cluster-a/storage/node1/local.lvm/disk_used",
+                1024.0,
+                100,
+            ),
+            datapoint("nodes/localhost/metric-collection-total-time", 2.5, 101),
+            datapoint("unknown", 1.0, 101),
+        ];
+
+        assert_eq!(
+            graphite_lines("pdm", &datapoints),
+            vec![
+                "pdm.pve_node.cluster-a.node1.cpu_current 0.5 100\n",
+                "pdm.pve_storage.cluster-a.node1.local_lvm.disk_used 1024 100\n",
+                "pdm.metric-collection-total-time 2.5 101\n",
+            ]
+        );
+    }
+
+    #[test]
+    fn chunking() {
+        let lines: Vec<String> = ["aaaa\n", "bb\n", "cccccccc\n", "d\n"]
+            .into_iter()
+            .map(String::from)
+            .collect();
+
+        assert_eq!(
+            chunk_lines(&lines, 8),
+            vec!["aaaa\nbb\n", "cccccccc\n", "d\n"]
+        );
+        assert_eq!(chunk_lines(&lines, 100), vec!["aaaa\nbb\ncccccccc\nd\n"]);
+        assert!(chunk_lines(&[], 100).is_empty());
+    }
+}
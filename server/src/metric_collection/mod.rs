@@ -10,9 +10,14 @@ use tokio::sync::oneshot;
 use pdm_api_types::RemoteMetricCollectionStatus;
 use pdm_buildcfg::PDM_STATE_DIR_M;
 
+pub mod balancing;
+pub mod forecast;
 mod local_collection_task;
+mod metric_servers;
+pub mod openmetrics;
 mod remote_collection_task;
 pub mod rrd_cache;
+mod rrd_name;
 mod rrd_task;
 mod state;
 pub mod top_entities;
@@ -67,6 +72,12 @@ pub fn start_task() -> Result<(), Error> {
         futures::future::select(metric_collection_task_future, abort_future).await;
     });
 
+    tokio::spawn(async move {
+        let metric_server_future = pin!(metric_servers::metric_server_task());
+        let abort_future = pin!(proxmox_daemon::shutdown_future());
+        futures::future::select(metric_server_future, abort_future).await;
+    });
+
     tokio::spawn(async move {
         let metric_collection_task_future =
             pin!(async move { LocalMetricCollectionTask::new(metric_data_tx).run().await });
@@ -0,0 +1,338 @@
+//! Export of the most recent metric datapoints in the OpenMetrics text format.
+//!
+//! Every value that ends up in the RRD cache is also remembered here, keyed by its RRD name.
+//! When rendering, the RRD names are mapped to metric families with labels, e.g.
+//! `pve/<remote>/qemu/<vmid>/cpu_current` becomes
+//! `pdm_pve_guest_cpu_current{remote="<remote>",type="qemu",vmid="<vmid>"}`.
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::Mutex;
+
+use proxmox_rrd::rrd::DataSourceType;
+
+pub use super::rrd_name::MetricSource;
+use super::rrd_name::RrdName;
+
+/// Datapoints older than this (in seconds) are not exported anymore, e.g. for removed guests.
+const MAX_DATAPOINT_AGE: i64 = 15 * 60;
+
+/// How often (in seconds) datapoints older than [`MAX_DATAPOINT_AGE`] are dropped.
+const PRUNE_INTERVAL: i64 = 60;
+
+static LATEST_VALUES: Mutex<LatestValues> = Mutex::new(LatestValues {
+    values: BTreeMap::new(),
+    last_prune: 0,
+});
+
+struct LatestValues {
+    values: BTreeMap<String, LatestValue>,
+    last_prune: i64,
+}
+
+impl LatestValues {
+    /// Drop the datapoints which are not updated anymore, e.g. of removed remotes and resources.
+    fn prune(&mut self, now: i64) {
+        if now - self.last_prune < PRUNE_INTERVAL {
+            return;
+        }
+        self.last_prune = now;
+        self.values
+            .retain(|_, value| now - value.timestamp <= MAX_DATAPOINT_AGE);
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum MetricType {
+    Gauge,
+    Counter,
+}
+
+impl MetricType {
+    fn as_str(self) -> &'static str {
+        match self {
+            MetricType::Gauge => "gauge",
+            MetricType::Counter => "counter",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+struct LatestValue {
+    value: f64,
+    timestamp: i64,
+    ty: MetricType,
+}
+
+// A single sample of a metric family.
+#[derive(Debug, PartialEq)]
+struct Sample<'a> {
+    family: String,
+    source: MetricSource<'a>,
+    labels: Vec<(&'static str, &'a str)>,
+}
+
+/// Remember the latest value of an RRD datapoint.
+pub(super) fn record_value(name: &str, value: f64, timestamp: i64, ty: &DataSourceType) {
+    let ty = match ty {
+        DataSourceType::Gauge => MetricType::Gauge,
+        _ => MetricType::Counter,
+    };
+
+    let mut latest_values = LATEST_VALUES.lock().unwrap();
+    latest_values.prune(proxmox_time::epoch_i64());
+
+    let values = &mut latest_values.values;
+    match values.get_mut(name) {
+        Some(latest) if latest.timestamp > timestamp => {}
+        Some(latest) => {
+            *latest = LatestValue {
+                value,
+                timestamp,
+                ty,
+            }
+        }
+        None => {
+            values.insert(
+                name.to_string(),
+                LatestValue {
+                    value,
+                    timestamp,
+                    ty,
+                },
+            );
+        }
+    }
+}
+
+/// Render all recent datapoints for which `include` returns true.
+pub fn render(include: impl Fn(&MetricSource) -> bool) -> String {
+    let now = proxmox_time::epoch_i64();
+    let values = LATEST_VALUES.lock().unwrap().values.clone();
+    render_values(
+        values
+            .iter()
+            .filter(|(_, value)| now - value.timestamp <= MAX_DATAPOINT_AGE)
+            .map(|(name, value)| (name.as_str(), *value)),
+        include,
+    )
+}
+
+fn render_values<'a>(
+    values: impl Iterator<Item = (&'a str, LatestValue)>,
+    include: impl Fn(&MetricSource) -> bool,
+) -> String {
+    // families have to be contiguous in the output
+    let mut families: BTreeMap<String, (MetricType, Vec<(Sample, LatestValue)>)> = BTreeMap::new();
+
+    for (name, value) in values {
+        let Some(sample) = parse_rrd_name(name) else {
+            continue;
+        };
+        if !include(&sample.source) {
+            continue;
+        }
+        families
+            .entry(sample.family.clone())
+            .or_insert_with(|| (value.ty, Vec::new()))
+            .1
+            .push((sample, value));
+    }
+
+    let mut output = String::new();
+    for (family, (ty, samples)) in families {
+        let _ = writeln!(output, "# TYPE {family} {}", ty.as_str());
+
+        let suffix = match ty {
+            MetricType::Gauge => "",
+            MetricType::Counter => "_total",
+        };
+
+        for (sample, value) in samples {
+            output.push_str(&family);
+            output.push_str(suffix);
+            if !sample.labels.is_empty() {
+                output.push('{');
+                for (i, (label, label_value)) in sample.labels.iter().enumerate() {
+                    if i > 0 {
+                        output.push(',');
+                    }
+                    let _ = write!(output, "{label}=\"{}\"", escape_label_value(label_value));
+                }
+                output.push('}');
+            }
+            let _ = writeln!(output, " {} {}", format_value(value.value), value.timestamp);
+        }
+    }
+    output.push_str("# EOF\n");
+
+    output
+}
+
+// Map an RRD name as used in `rrd_task` to a metric family and its labels.
+fn parse_rrd_name(name: &str) -> Option<Sample<'_>> {
+    let name = RrdName::parse(name)?;
+
+    let family = match name.kind {
+        Some(kind) => format!("pdm_{kind}_{}", name.metric),
+        None => format!("pdm_{}", name.metric),
+    };
+
+    Some(Sample {
+        family: sanitize_metric_name(&family),
+        source: name.source,
+        labels: name.labels,
+    })
+}
+
+fn sanitize_metric_name(name: &str) -> String {
+    name.chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect()
+}
+
+fn escape_label_value(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '"' => escaped.push_str("\\\""),
+            '\n' => escaped.push_str("\\n"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn format_value(value: f64) -> String {
+    if value.is_nan() {
+        "NaN".to_string()
+    } else if value.is_infinite() {
+        if value > 0.0 { "+Inf" } else { "-Inf" }.to_string()
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn gauge(value: f64, timestamp: i64) -> LatestValue {
+        LatestValue {
+            value,
+            timestamp,
+            ty: MetricType::Gauge,
+        }
+    }
+
+    #[test]
+    fn prune_values() {
+        let mut latest = LatestValues {
+            values: BTreeMap::new(),
+            last_prune: 0,
+        };
+        let now = 10_000;
+        latest
+            .values
+            .insert("old".into(), gauge(1.0, now - MAX_DATAPOINT_AGE - 1));
+        latest
+            .values
+            .insert("recent".into(), gauge(1.0, now - MAX_DATAPOINT_AGE));
+
+        latest.prune(now);
+        assert_eq!(latest.values.keys().collect::<Vec<_>>(), ["recent"]);
+        assert_eq!(latest.last_prune, now);
+
+        // not pruned again before the interval passed
+        latest.prune(now + PRUNE_INTERVAL - 1);
+        assert_eq!(latest.values.len(), 1);
+        assert_eq!(latest.last_prune, now);
+
+        latest.prune(now + PRUNE_INTERVAL);
+        assert!(latest.values.is_empty());
+    }
+
+    #[test]
+    fn parse_names() {
+        let sample = parse_rrd_name("pve/cluster-a/qemu/100/cpu_current").unwrap();
+        assert_eq!(sample.family, "pdm_pve_guest_cpu_current");
+        assert_eq!(sample.source, MetricSource::Remote("cluster-a"));
+        assert_eq!(
+            sample.labels,
+            vec![("remote", "cluster-a"), ("type", "qemu"), ("vmid", "100")]
+        );
+
+        let sample = parse_rrd_name("pve/cluster-a/storage/node1/local/disk_used").unwrap();
+        assert_eq!(sample.family, "pdm_pve_storage_disk_used");
+        assert_eq!(
+            sample.labels,
+            vec![
+                ("remote", "cluster-a"),
+                ("node", "node1"),
+                ("storage", "local")
+            ]
+        );
+
+        let sample = parse_rrd_name("pbs/backup/datastore/store1/disk_total").unwrap();
+        assert_eq!(sample.family, "pdm_pbs_datastore_disk_total");
+
+        let sample = parse_rrd_name("remotes/backup/metric-collection-response-time").unwrap();
+        assert_eq!(sample.family, "pdm_remote_metric_collection_response_time");
+        assert_eq!(sample.source, MetricSource::Remote("backup"));
+
+        let sample = parse_rrd_name("nodes/localhost/metric-collection-total-time").unwrap();
+        assert_eq!(sample.family, "pdm_metric_collection_total_time");
+        assert_eq!(sample.source, MetricSource::Host);
+
+        let sample = parse_rrd_name("nodes/localhost/mem-used").unwrap();
+        assert_eq!(sample.family, "pdm_host_mem_used");
+        assert!(sample.labels.is_empty());
+
+        assert!(parse_rrd_name("pve/cluster-a/unknown/1/2/3/4").is_none());
+        assert!(parse_rrd_name("something").is_none());
+    }
+
+    #[test]
+    fn render_families() {
+        let values = [
+            ("pve/a/node/n1/cpu_current", gauge(0.5, 100)),
+            ("nodes/localhost/cpu-current", gauge(0.25, 100)),
+            ("pve/b/node/n\"1/cpu_current", gauge(1.0, 101)),
+            (
+                "pve/a/node/n1/net_in",
+                LatestValue {
+                    value: 1024.0,
+                    timestamp: 100,
+                    ty: MetricType::Counter,
+                },
+            ),
+            ("pve/a/qemu/100/cpu_current", gauge(f64::NAN, 100)),
+        ];
+
+        let output = render_values(values.iter().map(|(n, v)| (*n, *v)), |_| true);
+        assert_eq!(
+            output,
+            "# TYPE pdm_host_cpu_current gauge\n\
+             pdm_host_cpu_current 0.25 100\n\
+             # TYPE pdm_pve_guest_cpu_current gauge\n\
+             pdm_pve_guest_cpu_current{remote=\"a\",type=\"qemu\",vmid=\"100\"} NaN 100\n\
+             # TYPE pdm_pve_node_cpu_current gauge\n\
+             pdm_pve_node_cpu_current{remote=\"a\",node=\"n1\"} 0.5 100\n\
+             pdm_pve_node_cpu_current{remote=\"b\",node=\"n\\\"1\"} 1 101\n\
+             # TYPE pdm_pve_node_net_in counter\n\
+             pdm_pve_node_net_in_total{remote=\"a\",node=\"n1\"} 1024 100\n\
+             # EOF\n"
+        );
+
+        let output = render_values(values.iter().map(|(n, v)| (*n, *v)), |source| {
+            *source == MetricSource::Remote("b")
+        });
+        assert_eq!(
+            output,
+            "# TYPE pdm_pve_node_cpu_current gauge\n\
+             pdm_pve_node_cpu_current{remote=\"b\",node=\"n\\\"1\"} 1 101\n\
+             # EOF\n"
+        );
+    }
+}
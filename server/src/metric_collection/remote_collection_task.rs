@@ -33,6 +33,9 @@ const METRIC_COLLECTION_STATE_FILE: &str = concat!(
     "/metric-collection-state.json"
 );
 
+/// Maximum number of remotes metrics are collected from concurrently.
+///
+/// The timeout, proxy and concurrency limit of each remote are applied by its API client.
 pub const MAX_CONCURRENT_CONNECTIONS: usize = 20;
 
 /// Default metric collection interval.
@@ -349,6 +352,12 @@ impl RemoteMetricCollectionTask {
                         })
                         .await?;
                 }
+                RemoteType::Pmg => {
+                    // PMG has no metric export API (yet), so there is nothing to store.
+                    let _ = result_tx.send(RrdStoreResult {
+                        most_recent_timestamp: status.most_recent_datapoint,
+                    });
+                }
             };
 
             result_rx.await.map_err(Error::from)
@@ -362,6 +371,10 @@ impl RemoteMetricCollectionTask {
                 status.error = None;
             }
             Err(err) => {
+                // only notify once, not on every failed collection attempt
+                if status.error.is_none() {
+                    crate::notifications::send_metric_collection_failed(&remote.id, &err);
+                }
                 status.error = Some(err.to_string());
                 log::error!("could not fetch metrics from '{}': {err}", remote.id);
             }
@@ -392,13 +405,13 @@ pub(super) mod tests {
     use http::StatusCode;
 
     use pdm_api_types::Authid;
-    use proxmox_client::Client;
     use pve_api_types::{ClusterMetrics, ClusterMetricsData};
 
     use crate::{
-        connection::{ClientFactory, PveClient},
+        connection::{ClientFactory, LimitedClient, PveClient},
         metric_collection::rrd_task::RrdStoreResult,
         pbs_client::PbsClient,
+        pmg_client::PmgClient,
         test_support::temp::NamedTempFile,
     };
 
@@ -436,7 +449,11 @@ pub(super) mod tests {
             bail!("not implemented")
         }
 
-        fn make_raw_client(&self, _remote: &Remote) -> Result<Box<Client>, Error> {
+        fn make_pmg_client(&self, _remote: &Remote) -> Result<Box<PmgClient>, Error> {
+            bail!("not implemented")
+        }
+
+        fn make_raw_client(&self, _remote: &Remote) -> Result<Box<LimitedClient>, Error> {
             bail!("not implemented")
         }
 
@@ -450,7 +467,14 @@ pub(super) mod tests {
         async fn make_pbs_client_and_login(
             &self,
             _remote: &Remote,
-        ) -> Result<Box<PbsClient<Client>>, Error> {
+        ) -> Result<Box<PbsClient<LimitedClient>>, Error> {
+            bail!("not implemented")
+        }
+
+        async fn make_pmg_client_and_login(
+            &self,
+            _remote: &Remote,
+        ) -> Result<Box<PmgClient<LimitedClient>>, Error> {
             bail!("not implemented")
         }
     }
@@ -521,6 +545,11 @@ pub(super) mod tests {
                     authid: Authid::root_auth_id().clone(),
                     token: "".into(),
                     web_url: None,
+                    folder: None,
+                    timeout: None,
+                    http_proxy: None,
+                    max_connections: None,
+                    token_created: None,
                 },
             );
         }
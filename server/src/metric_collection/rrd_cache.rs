@@ -135,6 +135,9 @@ impl RrdCache {
         timestamp: i64,
         datasource_type: DataSourceType,
     ) {
+        super::openmetrics::record_value(name, value, timestamp, &datasource_type);
+        super::metric_servers::record_value(name, value, timestamp);
+
         if let Err(err) =
             self.cache
                 .update_value_ignore_old(name, timestamp as f64, value, datasource_type)
@@ -0,0 +1,84 @@
+//! Structured view on the RRD names used in `rrd_task`, shared by the metric exports.
+
+/// Where a metric comes from, used to filter exported metrics.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MetricSource<'a> {
+    /// A datapoint collected from a remote.
+    Remote(&'a str),
+    /// A datapoint about the PDM host itself.
+    Host,
+}
+
+/// A parsed RRD name, e.g. `pve/<remote>/qemu/<vmid>/cpu_current`.
+#[derive(Debug, PartialEq)]
+pub struct RrdName<'a> {
+    /// The kind of object the metric belongs to, e.g. `pve_guest`.
+    ///
+    /// `None` for metrics about the metric collection itself.
+    pub kind: Option<&'static str>,
+    pub source: MetricSource<'a>,
+    /// Labels identifying the object, in a stable order.
+    pub labels: Vec<(&'static str, &'a str)>,
+    /// The name of the metric itself, e.g. `cpu_current`.
+    pub metric: &'a str,
+}
+
+impl<'a> RrdName<'a> {
+    /// Parse an RRD name, returns `None` for unknown names.
+    pub fn parse(name: &'a str) -> Option<Self> {
+        let parts: Vec<&str> = name.split('/').collect();
+
+        let (kind, source, labels, metric) = match parts.as_slice() {
+            ["pve", remote, "node", node, metric] => (
+                Some("pve_node"),
+                MetricSource::Remote(remote),
+                vec![("remote", *remote), ("node", *node)],
+                metric,
+            ),
+            ["pve", remote, ty @ ("qemu" | "lxc"), vmid, metric] => (
+                Some("pve_guest"),
+                MetricSource::Remote(remote),
+                vec![("remote", *remote), ("type", *ty), ("vmid", *vmid)],
+                metric,
+            ),
+            ["pve", remote, "storage", node, storage, metric] => (
+                Some("pve_storage"),
+                MetricSource::Remote(remote),
+                vec![("remote", *remote), ("node", *node), ("storage", *storage)],
+                metric,
+            ),
+            ["pbs", remote, "host", metric] => (
+                Some("pbs_node"),
+                MetricSource::Remote(remote),
+                vec![("remote", *remote)],
+                metric,
+            ),
+            ["pbs", remote, "datastore", datastore, metric] => (
+                Some("pbs_datastore"),
+                MetricSource::Remote(remote),
+                vec![("remote", *remote), ("datastore", *datastore)],
+                metric,
+            ),
+            ["remotes", remote, metric] => (
+                Some("remote"),
+                MetricSource::Remote(remote),
+                vec![("remote", *remote)],
+                metric,
+            ),
+            ["nodes", "localhost", metric] if metric.starts_with("metric-collection-") => {
+                (None, MetricSource::Host, Vec::new(), metric)
+            }
+            ["nodes", "localhost", metric] => {
+                (Some("host"), MetricSource::Host, Vec::new(), metric)
+            }
+            _ => return None,
+        };
+
+        Some(Self {
+            kind,
+            source,
+            labels,
+            metric,
+        })
+    }
+}
@@ -2,7 +2,7 @@ use std::collections::HashMap;
 
 use pdm_api_types::resource::{Resource, ResourceRrdData, TopEntities, TopEntity};
 
-use super::rrd_cache;
+use super::{forecast, rrd_cache};
 
 fn insert_sorted<T>(vec: &mut Vec<(usize, T)>, value: (usize, T), limit: usize) {
     let index = match vec.binary_search_by_key(&value.0, |(idx, _)| *idx) {
@@ -42,6 +42,8 @@ pub fn calculate_top(
     let mut guest_cpu = Vec::new();
     let mut node_cpu = Vec::new();
     let mut node_memory = Vec::new();
+    let mut soonest_full = Vec::new();
+    let now = proxmox_time::epoch_i64();
 
     for (remote_name, remote) in remotes {
         if !check_remote_privs(remote_name) {
@@ -59,7 +61,13 @@ pub fn calculate_top(
                 let id = res.id().to_string();
                 let name = format!("pve/{remote_name}/{id}");
                 match &res {
-                    Resource::PveStorage(_) => {}
+                    Resource::PveStorage(_) | Resource::PbsDatastore(_) => {
+                        if let Some(entity) =
+                            get_soonest_full_entity(timeframe, remote_name, res, now)
+                        {
+                            insert_sorted(&mut soonest_full, entity, num);
+                        }
+                    }
                     Resource::PveQemu(_) | Resource::PveLxc(_) => {
                         if let Some(entity) =
                             get_entity(timeframe, remote_name, res, name, "cpu_current")
@@ -117,7 +125,7 @@ pub fn calculate_top(
                         }
                     }
                     Resource::PveNetwork(_) => {}
-                    Resource::PbsDatastore(_) => {}
+                    Resource::PmgNode(_) => {}
                 }
             }
         }
@@ -127,7 +135,63 @@ pub fn calculate_top(
         guest_cpu: guest_cpu.into_iter().map(|(_, entity)| entity).collect(),
         node_cpu: node_cpu.into_iter().map(|(_, entity)| entity).collect(),
         node_memory: node_memory.into_iter().map(|(_, entity)| entity).collect(),
+        soonest_full: soonest_full.into_iter().map(|(_, entity)| entity).collect(),
+    }
+}
+
+// the coefficient is higher the sooner the storage is estimated to be full, the rrd data is the
+// disk usage
+fn get_soonest_full_entity(
+    timeframe: proxmox_rrd_api_types::RrdTimeframe,
+    remote_name: &str,
+    mut res: Resource,
+    now: i64,
+) -> Option<(usize, TopEntity)> {
+    let cache = rrd_cache::get_cache();
+    let name = forecast::disk_rrd_basedir(remote_name, &res)?;
+
+    let full = forecast::cached_estimate_disk_full(&cache, &name, now)?;
+    match &mut res {
+        Resource::PveStorage(storage) => storage.estimated_full = Some(full),
+        Resource::PbsDatastore(datastore) => datastore.estimated_full = Some(full),
+        _ => {}
     }
+    let coefficient = (forecast::MAX_FORECAST - (full - now).max(0)) as usize;
+
+    let mode = proxmox_rrd_api_types::RrdMode::Average;
+    let used = cache
+        .extract_data(&name, "disk_used", timeframe, mode)
+        .ok()??;
+    let total = cache
+        .extract_data(&name, "disk_total", timeframe, mode)
+        .ok()??;
+    // skip if we don't have the same amount of data for used and total
+    if used.data.len() != total.data.len() {
+        return None;
+    }
+
+    let data = used
+        .data
+        .iter()
+        .zip(total.data.iter())
+        .map(|(used, total)| match (used, total) {
+            (Some(used), Some(total)) if *total > 0.0 => Some(used / total),
+            _ => None,
+        })
+        .collect();
+
+    Some((
+        coefficient,
+        TopEntity {
+            remote: remote_name.to_string(),
+            resource: res,
+            rrd_data: ResourceRrdData {
+                start: used.start,
+                resolution: used.resolution,
+                data,
+            },
+        },
+    ))
 }
 
 fn get_entity(
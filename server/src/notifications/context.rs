@@ -0,0 +1,79 @@
+//! The [`Context`] implementation providing PDM specific defaults to `proxmox-notify`.
+
+use std::path::Path;
+
+use proxmox_access_control::types::User;
+use proxmox_notify::Error;
+use proxmox_notify::context::Context;
+use proxmox_notify::renderer::TemplateSource;
+
+const DEFAULT_CONFIG: &str = "\
+sendmail: mail-to-root
+    comment Send mails to root@pam's email address
+    mailto-user root@pam
+
+
+matcher: default-matcher
+    mode all
+    target mail-to-root
+    comment Route all notifications to mail-to-root
+";
+
+#[derive(Debug)]
+pub(super) struct PdmContext;
+
+pub(super) static PDM_CONTEXT: PdmContext = PdmContext;
+
+impl Context for PdmContext {
+    fn lookup_email_for_user(&self, user: &str) -> Option<String> {
+        let (config, _digest) = match proxmox_access_control::user::config() {
+            Ok(config) => config,
+            Err(err) => {
+                log::error!("failed to read user config - {err}");
+                return None;
+            }
+        };
+
+        let user: User = config.lookup("user", user).ok()?;
+        user.email
+    }
+
+    fn default_sendmail_author(&self) -> String {
+        format!("Proxmox Datacenter Manager - {}", proxmox_sys::nodename())
+    }
+
+    fn default_sendmail_from(&self) -> String {
+        pdm_config::node::config()
+            .ok()
+            .and_then(|(config, _)| config.email_from)
+            .unwrap_or_else(|| "root".to_string())
+    }
+
+    fn http_proxy_config(&self) -> Option<String> {
+        pdm_config::node::config()
+            .ok()
+            .and_then(|(config, _)| config.http_proxy)
+    }
+
+    fn default_config(&self) -> &'static str {
+        DEFAULT_CONFIG
+    }
+
+    fn lookup_template(
+        &self,
+        filename: &str,
+        namespace: Option<&str>,
+        source: TemplateSource,
+    ) -> Result<Option<String>, Error> {
+        let base = match source {
+            TemplateSource::Vendor => pdm_buildcfg::PDM_BASE_TEMPLATE_DIR,
+            TemplateSource::Override => pdm_buildcfg::PDM_OVERRIDE_TEMPLATE_DIR,
+        };
+        let path = Path::new(base)
+            .join(namespace.unwrap_or("default"))
+            .join(filename);
+
+        proxmox_sys::fs::file_read_optional_string(path)
+            .map_err(|err| Error::Generic(format!("could not load template - {err}")))
+    }
+}
@@ -0,0 +1,301 @@
+//! Notifications about events on PDM and its remotes, sent via `proxmox-notify`.
+//!
+//! The secrets of the notification targets are only readable by root. Notifications created by
+//! the unprivileged API daemon are therefore written to a spool directory, from where the
+//! privileged API daemon picks them up and sends them, see [`notification_worker`].
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+use anyhow::Error;
+use nix::sys::stat::Mode;
+use serde_json::json;
+
+use proxmox_apt_api_types::APTUpdateInfo;
+use proxmox_notify::{Notification, Severity};
+use proxmox_sys::fs::CreateOptions;
+
+use pdm_api_types::alerts::{Alert, AlertSeverity, AlertState};
+use pdm_api_types::{RemoteUpid, TaskStateType};
+
+mod context;
+
+const SPOOL_DIR: &str = pdm_buildcfg::PDM_NOTIFICATION_SPOOL_DIR;
+
+/// Interval in which the spool directory is checked for new notifications.
+const SPOOL_POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Set if this process sends the queued notifications, new ones are queued as well then, so
+/// that sending them never blocks the caller.
+static WORKER_RUNNING: AtomicBool = AtomicBool::new(false);
+
+/// Only notify about failed remote tasks which ended within this time frame (in seconds), this
+/// avoids notifications about old tasks, e.g. when a remote is added.
+const MAX_FAILED_TASK_AGE: i64 = 3600;
+
+/// Notification types, used as the `type` metadata field which matchers can filter on.
+pub const NOTIFICATION_TYPES: &[&str] = &[
+    "acme",
+    "alert",
+    "metric-collection",
+    "package-updates",
+    "remote-task",
+    "remote-unreachable",
+];
+
+/// Set the `proxmox-notify` context, needs to be called once before sending notifications.
+pub fn init() {
+    proxmox_notify::context::set_context(&context::PDM_CONTEXT);
+}
+
+/// Create the spool directory for queued notifications.
+pub fn create_spool_dir() -> Result<(), Error> {
+    let api_user = pdm_config::api_user()?;
+    pdm_config::setup::mkdir_perms(SPOOL_DIR, api_user.uid, api_user.gid, 0o750)
+}
+
+/// Periodically send the notifications queued by the unprivileged API daemon.
+pub async fn notification_worker() {
+    WORKER_RUNNING.store(true, Ordering::Relaxed);
+
+    let mut interval = tokio::time::interval(SPOOL_POLL_INTERVAL);
+    interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+    loop {
+        interval.tick().await;
+
+        match tokio::task::spawn_blocking(send_queued_notifications).await {
+            Ok(Ok(())) => {}
+            Ok(Err(err)) => log::error!("could not send queued notifications - {err:#}"),
+            Err(err) => log::error!("notification worker panicked - {err}"),
+        }
+    }
+}
+
+fn send_queued_notifications() -> Result<(), Error> {
+    let mut files: Vec<PathBuf> = match std::fs::read_dir(SPOOL_DIR) {
+        Ok(entries) => entries
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+            .collect(),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+        Err(err) => return Err(err.into()),
+    };
+
+    if files.is_empty() {
+        return Ok(());
+    }
+    files.sort();
+
+    let config = pdm_config::notifications::config()?;
+
+    for path in files {
+        let notification = load_queued_notification(&path);
+
+        // remove the file in any case, a broken notification would be retried forever otherwise
+        if let Err(err) = std::fs::remove_file(&path) {
+            log::error!("could not remove queued notification {path:?} - {err}");
+            continue;
+        }
+
+        match notification {
+            Ok(notification) => {
+                if let Err(err) = proxmox_notify::api::common::send(&config, &notification) {
+                    log::error!(
+                        "could not send notification (id={}) - {err}",
+                        notification.id()
+                    );
+                }
+            }
+            Err(err) => log::error!("could not load queued notification {path:?} - {err:#}"),
+        }
+    }
+
+    Ok(())
+}
+
+fn load_queued_notification(path: &Path) -> Result<Notification, Error> {
+    let data = std::fs::read(path)?;
+    Ok(serde_json::from_slice(&data)?)
+}
+
+/// Send a notification, or queue it for the [`notification_worker`].
+///
+/// Notifications are sent directly only if we have access to the notification secrets and no
+/// worker is running in this process, e.g. in the daily update.
+pub fn send_notification(notification: Notification) -> Result<(), Error> {
+    if cfg!(test) {
+        return Ok(());
+    }
+
+    if nix::unistd::Uid::current().is_root() && !WORKER_RUNNING.load(Ordering::Relaxed) {
+        let config = pdm_config::notifications::config()?;
+        proxmox_notify::api::common::send(&config, &notification)?;
+    } else {
+        let data = serde_json::to_vec(&notification)?;
+        let path = Path::new(SPOOL_DIR).join(format!("{}.json", notification.id()));
+
+        let api_user = pdm_config::api_user()?;
+        let options = CreateOptions::new()
+            .owner(api_user.uid)
+            .group(api_user.gid)
+            .perm(Mode::from_bits_truncate(0o600));
+        proxmox_sys::fs::replace_file(path, &data, options, false)?;
+
+        log::info!("queued notification (id={})", notification.id());
+    }
+
+    Ok(())
+}
+
+fn send_or_log(notification: Notification) {
+    if let Err(err) = send_notification(notification) {
+        log::error!("could not send notification - {err:#}");
+    }
+}
+
+fn metadata<const N: usize>(ty: &str, fields: [(&str, &str); N]) -> HashMap<String, String> {
+    let mut metadata = HashMap::from([
+        ("type".to_string(), ty.to_string()),
+        ("hostname".to_string(), proxmox_sys::nodename().to_string()),
+    ]);
+    for (key, value) in fields {
+        metadata.insert(key.to_string(), value.to_string());
+    }
+    metadata
+}
+
+/// Notify about a remote from which metrics could not be collected anymore.
+pub fn send_metric_collection_failed(remote: &str, error: &Error) {
+    let data = json!({
+        "hostname": proxmox_sys::nodename(),
+        "remote": remote,
+        "error": format!("{error:#}"),
+    });
+
+    send_or_log(Notification::from_template(
+        Severity::Error,
+        "metric-collection-err",
+        data,
+        metadata("metric-collection", [("remote", remote)]),
+    ));
+}
+
+/// Notify about a remote which is not reachable anymore.
+pub fn send_remote_unreachable(remote: &str, error: &str) {
+    let data = json!({
+        "hostname": proxmox_sys::nodename(),
+        "remote": remote,
+        "error": error,
+    });
+
+    send_or_log(Notification::from_template(
+        Severity::Error,
+        "remote-unreachable",
+        data,
+        metadata("remote-unreachable", [("remote", remote)]),
+    ));
+}
+
+/// Notify about failed remote tasks which ended recently.
+///
+/// `tasks` contains the UPID, the end time and the status of finished tasks.
+pub fn send_remote_tasks_failed<'a>(tasks: impl Iterator<Item = (&'a RemoteUpid, i64, &'a str)>) {
+    let now = proxmox_time::epoch_i64();
+
+    for (upid, endtime, status) in tasks {
+        if now - endtime > MAX_FAILED_TASK_AGE
+            || TaskStateType::new_from_str(status) != TaskStateType::Error
+        {
+            continue;
+        }
+
+        let native_upid = match upid.native_upid() {
+            Ok(native_upid) => native_upid,
+            Err(err) => {
+                log::warn!("could not parse UPID '{}' - {err}", upid.upid());
+                continue;
+            }
+        };
+        let task_type = native_upid.worker_type();
+
+        let data = json!({
+            "hostname": proxmox_sys::nodename(),
+            "remote": upid.remote(),
+            "node": native_upid.node(),
+            "upid": upid.upid(),
+            "task-type": task_type,
+            "status": status,
+        });
+
+        send_or_log(Notification::from_template(
+            Severity::Error,
+            "remote-task-err",
+            data,
+            metadata(
+                "remote-task",
+                [("remote", upid.remote()), ("task-type", task_type)],
+            ),
+        ));
+    }
+}
+
+/// Notify about a failed renewal of the ACME certificate.
+pub fn send_certificate_renewal_failed(error: &Error) {
+    let data = json!({
+        "hostname": proxmox_sys::nodename(),
+        "error": format!("{error:#}"),
+    });
+
+    send_or_log(Notification::from_template(
+        Severity::Error,
+        "acme-err",
+        data,
+        metadata("acme", []),
+    ));
+}
+
+/// Notify about new available package updates.
+pub fn send_updates_available(updates: &[&APTUpdateInfo]) {
+    let data = json!({
+        "hostname": proxmox_sys::nodename(),
+        "updates": updates,
+    });
+
+    send_or_log(Notification::from_template(
+        Severity::Info,
+        "package-updates",
+        data,
+        metadata("package-updates", []),
+    ));
+}
+
+/// Notify about a fired or resolved alert.
+pub fn send_alert(alert: &Alert) {
+    let (template, severity) = match (alert.state, alert.severity) {
+        (AlertState::Resolved, _) => ("alert-resolved", Severity::Info),
+        (_, AlertSeverity::Warning) => ("alert-firing", Severity::Warning),
+        (_, AlertSeverity::Critical) => ("alert-firing", Severity::Error),
+    };
+
+    let data = json!({
+        "hostname": proxmox_sys::nodename(),
+        "rule": alert.rule,
+        "resource": alert.resource,
+        "remote": alert.remote,
+        "metric": alert.metric,
+        "unit": alert.metric.unit(),
+        "severity": alert.severity,
+        "value": format!("{:.2}", alert.value),
+        "threshold": alert.threshold,
+    });
+
+    send_or_log(Notification::from_template(
+        severity,
+        template,
+        data,
+        metadata("alert", [("remote", &alert.remote), ("rule", &alert.rule)]),
+    ));
+}
@@ -21,6 +21,7 @@
 //!                 Ok(42)
 //!             },
 //!             RemoteType::Pbs => Ok(42),
+//!             RemoteType::Pmg => Ok(42),
 //!         }
 //!     }
 //!
@@ -71,7 +72,6 @@ use tokio::sync::{OwnedSemaphorePermit, Semaphore};
 use tokio::task::JoinSet;
 
 use proxmox_log::LogContext;
-use pve_api_types::ClusterNodeIndexResponse;
 
 use pdm_api_types::remotes::{Remote, RemoteType};
 
@@ -205,8 +205,8 @@ impl<T> NodeResponse<T> {
     /// Name of the node.
     ///
     /// At the moment, this is always `localhost` if `do_for_all_remotes` was used.
-    /// If `do_for_all_remote_nodes` is used, this is the actual nodename for PVE remotes and
-    /// `localhost` for PBS remotes.
+    /// If `do_for_all_remote_nodes` is used, this is the actual nodename for PVE and PMG remotes
+    /// and `localhost` for PBS remotes.
     pub fn node_name(&self) -> &str {
         &self.node_name
     }
@@ -227,6 +227,15 @@ impl<T> NodeResponse<T> {
     }
 }
 
+/// The number of parallel connections to the nodes of `remote`, lowered to its `max-connections`
+/// setting if it has one.
+fn connections_per_remote(max_connections_per_remote: usize, remote: &Remote) -> usize {
+    match remote.max_connections {
+        Some(limit) => max_connections_per_remote.min(limit as usize),
+        None => max_connections_per_remote,
+    }
+}
+
 /// Builder for the [`ParallelFetcher`] struct.
 pub struct ParallelFetcherBuilder<C> {
     max_connections: Option<usize>,
@@ -251,7 +260,8 @@ impl<C> ParallelFetcherBuilder<C> {
 
     /// Set the maximum number of parallel connections per remote.
     ///
-    /// This only really affects PVE remotes with multiple cluster members.
+    /// This only really affects PVE remotes with multiple cluster members. Remotes with a lower
+    /// `max-connections` setting are limited to that instead.
     pub fn max_connections_per_remote(mut self, limit: usize) -> Self {
         self.max_connections_per_remote = Some(limit);
         self
@@ -353,17 +363,31 @@ impl<C: Clone + Send + 'static> ParallelFetcher<C> {
         let mut node_responses = Vec::new();
 
         let mut permit = Some(Arc::clone(&semaphore).acquire_owned().await.unwrap());
+        let max_connections_per_remote =
+            connections_per_remote(max_connections_per_remote, &remote);
         let per_remote_semaphore = Arc::new(Semaphore::new(max_connections_per_remote));
 
         match remote.ty {
-            RemoteType::Pve => {
+            RemoteType::Pve | RemoteType::Pmg => {
                 let remote_clone = remote.clone();
 
                 let nodes = match async move {
-                    let client = connection::make_pve_client(&remote_clone)?;
-                    let nodes = client.list_nodes().await?;
+                    let nodes = match remote_clone.ty {
+                        RemoteType::Pmg => connection::make_pmg_client(&remote_clone)?
+                            .list_nodes()
+                            .await?
+                            .into_iter()
+                            .map(|node| node.node)
+                            .collect(),
+                        _ => connection::make_pve_client(&remote_clone)?
+                            .list_nodes()
+                            .await?
+                            .into_iter()
+                            .map(|node| node.node)
+                            .collect(),
+                    };
 
-                    Ok::<Vec<ClusterNodeIndexResponse>, Error>(nodes)
+                    Ok::<Vec<String>, Error>(nodes)
                 }
                 .await
                 {
@@ -379,7 +403,7 @@ impl<C: Clone + Send + 'static> ParallelFetcher<C> {
 
                 let mut nodes_join_set = JoinSet::new();
 
-                for node in nodes {
+                for node_name in nodes {
                     let permit = if let Some(permit) = permit.take() {
                         permit
                     } else {
@@ -393,7 +417,6 @@ impl<C: Clone + Send + 'static> ParallelFetcher<C> {
 
                     let func_clone = func.clone();
                     let remote_clone = remote.clone();
-                    let node_name = node.node.clone();
                     let context_clone = context.clone();
 
                     let future = Self::fetch_node(
@@ -536,3 +559,34 @@ impl<C: Clone + Send + 'static> ParallelFetcher<C> {
         FetcherResponse { remote_responses }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use pdm_api_types::Authid;
+
+    use super::*;
+
+    fn remote(max_connections: Option<u64>) -> Remote {
+        Remote {
+            ty: RemoteType::Pve,
+            id: "pve".into(),
+            nodes: Vec::new(),
+            authid: Authid::root_auth_id().clone(),
+            token: "".into(),
+            web_url: None,
+            folder: None,
+            timeout: None,
+            http_proxy: None,
+            max_connections,
+            token_created: None,
+        }
+    }
+
+    #[test]
+    fn per_remote_connections() {
+        assert_eq!(connections_per_remote(5, &remote(None)), 5);
+        assert_eq!(connections_per_remote(5, &remote(Some(2))), 2);
+        // the remote setting can't raise the limit of the fetcher
+        assert_eq!(connections_per_remote(5, &remote(Some(10))), 5);
+    }
+}
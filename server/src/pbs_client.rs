@@ -30,7 +30,7 @@ pub fn get_remote<'a>(
 
 pub async fn connect_or_login(
     remote: &Remote,
-) -> Result<Box<PbsClient<proxmox_client::Client>>, anyhow::Error> {
+) -> Result<Box<PbsClient<crate::connection::LimitedClient>>, anyhow::Error> {
     crate::connection::make_pbs_client_and_login(remote).await
 }
 
@@ -55,7 +55,7 @@ pub fn connect_to_remote_by_id(id: &str) -> Result<Box<PbsClient>, anyhow::Error
 ///
 /// Defaults to wrapping a `MultiClient` so it shares the same per-request timeout, node failover
 /// and reachability tracking as the PVE client; the login path (`make_pbs_client_and_login`) wraps
-/// a raw `proxmox_client::Client` instead.
+/// a single node `LimitedClient` instead.
 pub struct PbsClient<C: HttpApiClient = crate::connection::MultiClient>(pub C);
 
 #[api]
@@ -152,6 +152,44 @@ pub struct TaskLogLine {
     pub t: String,
 }
 
+// Path of a datastore API call addressing a snapshot.
+fn snapshot_path(
+    datastore: &str,
+    call: &str,
+    namespace: Option<&str>,
+    backup_dir: &pbs_api_types::BackupDir,
+) -> ApiPathBuilder {
+    ApiPathBuilder::new(format!("/api2/extjs/admin/datastore/{datastore}/{call}"))
+        .maybe_arg("ns", &namespace)
+        .arg("backup-type", backup_dir.group.ty)
+        .arg("backup-id", &backup_dir.group.id)
+        .arg("backup-time", backup_dir.time)
+}
+
+fn catalog_path(
+    datastore: &str,
+    namespace: Option<&str>,
+    backup_dir: &pbs_api_types::BackupDir,
+    filepath: &str,
+) -> String {
+    snapshot_path(datastore, "catalog", namespace, backup_dir)
+        .arg("filepath", filepath)
+        .build()
+}
+
+fn pxar_file_download_path(
+    datastore: &str,
+    namespace: Option<&str>,
+    backup_dir: &pbs_api_types::BackupDir,
+    filepath: &str,
+    tar: bool,
+) -> String {
+    snapshot_path(datastore, "pxar-file-download", namespace, backup_dir)
+        .arg("filepath", filepath)
+        .arg("tar", tar)
+        .build()
+}
+
 impl<C: HttpApiClient<Body = proxmox_http::Body>> PbsClient<C> {
     /// API version details, including some parts of the global datacenter config.
     pub async fn version(&self) -> Result<pve_api_types::VersionResponse, Error> {
@@ -184,6 +222,78 @@ impl<C: HttpApiClient<Body = proxmox_http::Body>> PbsClient<C> {
         Ok(self.0.get(&path).await?.expect_json()?.data)
     }
 
+    /// List the backup groups of a datastore's namespace.
+    pub async fn list_groups(
+        &self,
+        datastore: &str,
+        namespace: Option<&str>,
+    ) -> Result<Vec<pbs_api_types::GroupListItem>, Error> {
+        let path = ApiPathBuilder::new(format!("/api2/extjs/admin/datastore/{datastore}/groups"))
+            .maybe_arg("ns", &namespace)
+            .build();
+        Ok(self.0.get(&path).await?.expect_json()?.data)
+    }
+
+    /// List the files of a snapshot.
+    pub async fn list_snapshot_files(
+        &self,
+        datastore: &str,
+        namespace: Option<&str>,
+        backup_dir: &pbs_api_types::BackupDir,
+    ) -> Result<Vec<pbs_api_types::BackupContent>, Error> {
+        let path = snapshot_path(datastore, "files", namespace, backup_dir).build();
+        Ok(self.0.get(&path).await?.expect_json()?.data)
+    }
+
+    /// List the entries of a directory in the catalog of a snapshot.
+    ///
+    /// The `filepath` is base64 encoded, `root` lists the archives of the snapshot.
+    pub async fn catalog(
+        &self,
+        datastore: &str,
+        namespace: Option<&str>,
+        backup_dir: &pbs_api_types::BackupDir,
+        filepath: &str,
+    ) -> Result<Vec<pdm_api_types::pbs::CatalogEntry>, Error> {
+        let path = catalog_path(datastore, namespace, backup_dir, filepath);
+        Ok(self.0.get(&path).await?.expect_json()?.data)
+    }
+
+    /// Download a single file, or a directory as zip or zstd compressed tar archive, from a pxar
+    /// archive of a snapshot.
+    ///
+    /// The `filepath` is base64 encoded and starts with the name of the archive.
+    pub async fn pxar_file_download(
+        &self,
+        datastore: &str,
+        namespace: Option<&str>,
+        backup_dir: &pbs_api_types::BackupDir,
+        filepath: &str,
+        tar: bool,
+    ) -> Result<proxmox_client::HttpApiResponseStream<proxmox_http::Body>, anyhow::Error> {
+        let path = pxar_file_download_path(datastore, namespace, backup_dir, filepath, tar);
+        let response = self
+            .0
+            .streaming_request(http::Method::GET, &path, None::<()>)
+            .await?;
+
+        if response.status != 200 {
+            let data = match response.body {
+                Some(body) => body
+                    .collect()
+                    .await
+                    .map_err(|err| {
+                        Error::Anyhow(Box::new(err).context("failed to retrieve response body"))
+                    })?
+                    .to_bytes(),
+                None => Default::default(),
+            };
+            bail!("{}", String::from_utf8_lossy(&data));
+        }
+
+        Ok(response)
+    }
+
     /// List a datastore's snapshots.
     pub async fn list_snapshots(
         &self,
@@ -264,6 +374,50 @@ impl<C: HttpApiClient<Body = proxmox_http::Body>> PbsClient<C> {
         Ok(token)
     }
 
+    /// Create an API-Token on the PBS remote without giving out any ACL.
+    pub async fn create_token(
+        &self,
+        userid: &Userid,
+        tokenid: &TokennameRef,
+        params: CreateToken,
+    ) -> Result<CreateTokenResponse, Error> {
+        let path = format!(
+            "/api2/extjs/access/users/{}/token/{}",
+            percent_encoding::percent_encode(
+                userid.as_str().as_bytes(),
+                percent_encoding::NON_ALPHANUMERIC
+            ),
+            tokenid.as_str()
+        );
+        Ok(self.0.post(&path, &params).await?.expect_json()?.data)
+    }
+
+    /// Give another auth id the same ACL entries as `from`.
+    pub async fn copy_acls(&self, from: &Authid, to: &Authid) -> Result<(), Error> {
+        let acls: Vec<pbs_api_types::AclListItem> = self
+            .0
+            .get("/api2/extjs/access/acl")
+            .await?
+            .expect_json()?
+            .data;
+
+        let from = from.to_string();
+        for acl in acls.into_iter().filter(|acl| acl.ugid == from) {
+            let params = serde_json::json!({
+                "path": acl.path,
+                "auth-id": to,
+                "role": acl.roleid,
+                "propagate": acl.propagate,
+            });
+            self.0
+                .put("/api2/extjs/access/acl", &params)
+                .await?
+                .nodata()?;
+        }
+
+        Ok(())
+    }
+
     /// Delete API token from the PBS remote.
     pub async fn delete_token(&self, userid: &Userid, tokenid: &TokennameRef) -> Result<(), Error> {
         let path = format!(
@@ -426,6 +580,64 @@ impl<C: HttpApiClient<Body = proxmox_http::Body>> PbsClient<C> {
         Ok(self.0.get(url).await?.expect_json()?.data)
     }
 
+    /// List the garbage collection status of all datastores.
+    pub async fn list_gc_jobs(
+        &self,
+    ) -> Result<Vec<pbs_api_types::GarbageCollectionJobStatus>, Error> {
+        Ok(self
+            .0
+            .get("/api2/extjs/admin/gc")
+            .await?
+            .expect_json()?
+            .data)
+    }
+
+    /// List the prune jobs with their status.
+    pub async fn list_prune_jobs(&self) -> Result<Vec<pbs_api_types::PruneJobStatus>, Error> {
+        Ok(self
+            .0
+            .get("/api2/extjs/admin/prune")
+            .await?
+            .expect_json()?
+            .data)
+    }
+
+    /// List the sync jobs with their status.
+    pub async fn list_sync_jobs(&self) -> Result<Vec<pbs_api_types::SyncJobStatus>, Error> {
+        Ok(self
+            .0
+            .get("/api2/extjs/admin/sync")
+            .await?
+            .expect_json()?
+            .data)
+    }
+
+    /// List the verification jobs with their status.
+    pub async fn list_verify_jobs(
+        &self,
+    ) -> Result<Vec<pbs_api_types::VerificationJobStatus>, Error> {
+        Ok(self
+            .0
+            .get("/api2/extjs/admin/verify")
+            .await?
+            .expect_json()?
+            .data)
+    }
+
+    /// Start a garbage collection on a datastore.
+    pub async fn start_gc(&self, datastore: &str) -> Result<pbs_api_types::UPID, Error> {
+        let path = format!("/api2/extjs/admin/datastore/{datastore}/gc");
+        Ok(self.0.post_without_body(&path).await?.expect_json()?.data)
+    }
+
+    /// Run a prune, sync or verification job now.
+    ///
+    /// `job_type` is the path component of the job type, e.g. `prune`.
+    pub async fn run_job(&self, job_type: &str, id: &str) -> Result<pbs_api_types::UPID, Error> {
+        let path = format!("/api2/extjs/admin/{job_type}/{id}/run");
+        Ok(self.0.post_without_body(&path).await?.expect_json()?.data)
+    }
+
     /// Get list of tasks.
     ///
     /// `params`: Filters specifying which tasks to get.
@@ -481,3 +693,49 @@ impl<C: HttpApiClient<Body = proxmox_http::Body>> PbsClient<C> {
 struct JsonData<T> {
     data: T,
 }
+
+#[cfg(test)]
+mod tests {
+    use pbs_api_types::{BackupDir, BackupGroup, BackupType};
+
+    use super::{catalog_path, pxar_file_download_path};
+
+    fn backup_dir() -> BackupDir {
+        BackupDir {
+            group: BackupGroup {
+                ty: BackupType::Vm,
+                id: "100".to_string(),
+            },
+            time: 1700000000,
+        }
+    }
+
+    #[test]
+    fn snapshot_paths() {
+        let filepath = proxmox_base64::encode("root.pxar.didx/etc/hosts");
+        assert_eq!(filepath, "cm9vdC5weGFyLmRpZHgvZXRjL2hvc3Rz");
+
+        assert_eq!(
+            catalog_path("store", None, &backup_dir(), "Lw=="),
+            "/api2/extjs/admin/datastore/store/catalog\
+             ?backup-type=vm&backup-id=100&backup-time=1700000000&filepath=Lw%3D%3D",
+        );
+
+        let path = pxar_file_download_path("store", Some("a/b"), &backup_dir(), &filepath, false);
+        assert!(
+            path.starts_with(
+                "/api2/extjs/admin/datastore/store/pxar-file-download\
+                 ?ns=a%2Fb&backup-type=vm&backup-id=100&backup-time=1700000000\
+                 &filepath=cm9vdC5weGFyLmRpZHgvZXRjL2hvc3Rz&tar="
+            ),
+            "unexpected path {path:?}",
+        );
+
+        // base64 padding and separators must not end up unescaped in the query
+        let path = pxar_file_download_path("store", None, &backup_dir(), "a+b/c=", true);
+        assert!(
+            path.contains("&filepath=a%2Bb%2Fc%3D&"),
+            "unexpected path {path:?}"
+        );
+    }
+}
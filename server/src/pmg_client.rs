@@ -0,0 +1,228 @@
+//! Manage PMG instances.
+//!
+//! There is no generated client for the Proxmox Mail Gateway API, so this contains the handful of
+//! calls PDM needs, modeled after the [`PbsClient`](crate::pbs_client::PbsClient).
+//!
+//! PMG has no API tokens, PMG remotes are always accessed with a user and password.
+
+use anyhow::bail; // don't import Error as default error in here
+use serde::{Deserialize, Serialize};
+
+use proxmox_client::{ApiPathBuilder, ApiResponseData, Error, HttpApiClient};
+use proxmox_section_config::typed::SectionConfigData;
+
+use pdm_api_types::remotes::{Remote, RemoteType};
+use pdm_api_types::{APTRepositoriesResult, APTUpdateInfo};
+
+/// The error for attempts to use or create API tokens for PMG remotes.
+pub(crate) const NO_API_TOKENS: &str =
+    "Proxmox Mail Gateway has no API tokens, use a user and password instead";
+
+pub fn get_remote<'a>(
+    config: &'a SectionConfigData<Remote>,
+    id: &str,
+) -> Result<&'a Remote, anyhow::Error> {
+    let remote = crate::api::remotes::get_remote(config, id)?;
+    if remote.ty != RemoteType::Pmg {
+        bail!("remote {id:?} is not a pmg remote");
+    }
+    Ok(remote)
+}
+
+pub async fn connect_or_login(
+    remote: &Remote,
+) -> Result<Box<PmgClient<crate::connection::LimitedClient>>, anyhow::Error> {
+    crate::connection::make_pmg_client_and_login(remote).await
+}
+
+pub fn connect(remote: &Remote) -> Result<Box<PmgClient>, anyhow::Error> {
+    crate::connection::make_pmg_client(remote)
+}
+
+pub fn connect_to_remote(
+    config: &SectionConfigData<Remote>,
+    id: &str,
+) -> Result<Box<PmgClient>, anyhow::Error> {
+    connect(get_remote(config, id)?)
+}
+
+/// Load remote config, look up a PMG remote by id and connect.
+pub fn connect_to_remote_by_id(id: &str) -> Result<Box<PmgClient>, anyhow::Error> {
+    let (remotes, _) = pdm_config::remotes::config()?;
+    connect_to_remote(&remotes, id)
+}
+
+/// A PMG API client.
+///
+/// Like the [`PbsClient`](crate::pbs_client::PbsClient) this defaults to wrapping a
+/// `MultiClient`, the login path (`make_pmg_client_and_login`) wraps a single node
+/// `LimitedClient` instead.
+pub struct PmgClient<C: HttpApiClient = crate::connection::MultiClient>(pub C);
+
+/// Parameters for updating the APT database
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct AptUpdateParams {
+    /// Send notification in case of new updates.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub notify: Option<bool>,
+    /// Don't show progress information in the output.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub quiet: Option<bool>,
+}
+
+// NOTE: Like for PBS, this only contains the parameters needed for remote task fetching.
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct ListTasks {
+    /// Only list this number of tasks.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub limit: Option<u64>,
+
+    /// Only list tasks since this UNIX epoch.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub since: Option<i64>,
+}
+
+impl<C: HttpApiClient<Body = proxmox_http::Body>> PmgClient<C> {
+    /// API version details.
+    pub async fn version(&self) -> Result<pve_api_types::VersionResponse, Error> {
+        Ok(self.0.get("/api2/extjs/version").await?.expect_json()?.data)
+    }
+
+    /// List the nodes of the PMG cluster.
+    pub async fn list_nodes(&self) -> Result<Vec<pdm_api_types::pmg::NodeIndexEntry>, Error> {
+        Ok(self.0.get("/api2/extjs/nodes").await?.expect_json()?.data)
+    }
+
+    /// Return the status of a PMG node.
+    pub async fn node_status(&self, node: &str) -> Result<pdm_api_types::pmg::NodeStatus, Error> {
+        let path = format!("/api2/extjs/nodes/{node}/status");
+        Ok(self.0.get(&path).await?.expect_json()?.data)
+    }
+
+    /// Return a term ticket for calling the vncwebsocket endpoint
+    pub async fn node_shell_termproxy(
+        &self,
+        node: &str,
+    ) -> Result<pbs_api_types::NodeShellTicket, Error> {
+        let path = format!("/api2/extjs/nodes/{node}/termproxy");
+        Ok(self.0.post_without_body(&path).await?.expect_json()?.data)
+    }
+
+    /// Return the subscription info of a PMG node.
+    pub async fn get_subscription(
+        &self,
+        node: &str,
+    ) -> Result<proxmox_subscription::SubscriptionInfo, Error> {
+        let path = format!("/api2/extjs/nodes/{node}/subscription");
+        Ok(self.0.get(&path).await?.expect_json()?.data)
+    }
+
+    /// Trigger a fresh shop-side check of the stored subscription on a PMG node.
+    pub async fn check_subscription(
+        &self,
+        node: &str,
+        params: proxmox_subscription::UpdateSubscription,
+    ) -> Result<(), Error> {
+        let path = format!("/api2/extjs/nodes/{node}/subscription");
+        self.0.post(&path, &params).await?;
+        Ok(())
+    }
+
+    /// Return a list of available system updates.
+    pub async fn list_available_updates(&self, node: &str) -> Result<Vec<APTUpdateInfo>, Error> {
+        let path = format!("/api2/extjs/nodes/{node}/apt/update");
+        Ok(self.0.get(&path).await?.expect_json()?.data)
+    }
+
+    /// Update the APT database.
+    pub async fn update_apt_database(
+        &self,
+        node: &str,
+        params: AptUpdateParams,
+    ) -> Result<pve_api_types::PveUpid, Error> {
+        let path = format!("/api2/extjs/nodes/{node}/apt/update");
+        Ok(self.0.post(&path, &params).await?.expect_json()?.data)
+    }
+
+    /// Get changelog for a single package.
+    ///
+    /// `package`: Package name to get the changelog of.
+    /// `version`: Package version to get changelog of. Omit to use candidate version.
+    pub async fn get_package_changelog(
+        &self,
+        node: &str,
+        package: String,
+        version: Option<String>,
+    ) -> Result<String, Error> {
+        let path = ApiPathBuilder::new(format!("/api2/extjs/nodes/{node}/apt/changelog"))
+            .arg("name", &package)
+            .maybe_arg("version", &version)
+            .build();
+
+        Ok(self.0.get(&path).await?.expect_json()?.data)
+    }
+
+    /// Return a list of the most important package versions.
+    pub async fn get_package_versions(&self, node: &str) -> Result<Vec<APTUpdateInfo>, Error> {
+        let path = format!("/api2/extjs/nodes/{node}/apt/versions");
+        Ok(self.0.get(&path).await?.expect_json()?.data)
+    }
+
+    /// Get APT repository information.
+    pub async fn get_apt_repositories(&self, node: &str) -> Result<APTRepositoriesResult, Error> {
+        let path = format!("/api2/extjs/nodes/{node}/apt/repositories");
+        Ok(self.0.get(&path).await?.expect_json()?.data)
+    }
+
+    /// Get list of tasks of a node.
+    ///
+    /// PMG shares the task list format with PVE.
+    pub async fn get_task_list(
+        &self,
+        node: &str,
+        params: ListTasks,
+    ) -> Result<Vec<pve_api_types::ListTasksResponse>, Error> {
+        let ListTasks { limit, since } = params;
+
+        let url = ApiPathBuilder::new(format!("/api2/extjs/nodes/{node}/tasks"))
+            .maybe_arg("limit", &limit)
+            .maybe_arg("since", &since)
+            .build();
+
+        Ok(self.0.get(&url).await?.expect_json()?.data)
+    }
+
+    /// Read task log.
+    pub async fn get_task_log(
+        &self,
+        node: &str,
+        upid: &str,
+        download: Option<bool>,
+        limit: Option<u64>,
+        start: Option<u64>,
+    ) -> Result<ApiResponseData<Vec<pve_api_types::TaskLogLine>>, Error> {
+        let url = ApiPathBuilder::new(format!("/api2/extjs/nodes/{node}/tasks/{upid}/log"))
+            .maybe_bool_arg("download", download)
+            .maybe_arg("limit", &limit)
+            .maybe_arg("start", &start)
+            .build();
+
+        self.0.get(&url).await?.expect_json()
+    }
+
+    /// Read task status.
+    pub async fn get_task_status(
+        &self,
+        node: &str,
+        upid: &str,
+    ) -> Result<pve_api_types::TaskStatus, Error> {
+        let url = format!("/api2/extjs/nodes/{node}/tasks/{upid}/status");
+        Ok(self.0.get(&url).await?.expect_json()?.data)
+    }
+
+    /// Stop a task.
+    pub async fn stop_task(&self, node: &str, upid: &str) -> Result<(), Error> {
+        let url = format!("/api2/extjs/nodes/{node}/tasks/{upid}");
+        self.0.delete(&url).await?.nodata()
+    }
+}
@@ -235,7 +235,12 @@ impl RemoteMappingCache {
         hostname: &str,
         connection_state: ConnectionState,
     ) {
-        let unreachable = matches!(&connection_state, ConnectionState::Unreachable(_));
+        let error = match &connection_state {
+            ConnectionState::Unreachable(err) => Some(err.clone()),
+            ConnectionState::Reachable => None,
+        };
+        let unreachable = error.is_some();
+        let was_reachable = self.remote_is_reachable(remote_name);
 
         let found = if let Some(info) = self.info_by_hostname_mut(remote_name, hostname) {
             if let Some(next_try) = info.set_reachable(connection_state) {
@@ -255,6 +260,7 @@ impl RemoteMappingCache {
         // unknown host could reset the back-off of all remotes without any host having recovered
         if found {
             self.set_or_reset_canary(remote_name, unreachable);
+            self.notify_if_unreachable(remote_name, was_reachable, error);
         }
     }
 
@@ -265,7 +271,12 @@ impl RemoteMappingCache {
         node_name: &str,
         connection_state: ConnectionState,
     ) {
-        let unreachable = matches!(&connection_state, ConnectionState::Unreachable(_));
+        let error = match &connection_state {
+            ConnectionState::Unreachable(err) => Some(err.clone()),
+            ConnectionState::Reachable => None,
+        };
+        let unreachable = error.is_some();
+        let was_reachable = self.remote_is_reachable(remote_name);
 
         let found = if let Some(info) = self.info_by_node_name_mut(remote_name, node_name) {
             if let Some(next_try) = info.set_reachable(connection_state) {
@@ -285,9 +296,26 @@ impl RemoteMappingCache {
         // unknown node could reset the back-off of all remotes without any host having recovered
         if found {
             self.set_or_reset_canary(remote_name, unreachable);
+            self.notify_if_unreachable(remote_name, was_reachable, error);
+        }
+    }
+
+    // sends a notification if the last reachable host of a remote became unreachable
+    fn notify_if_unreachable(&self, remote_name: &str, was_reachable: bool, error: Option<String>) {
+        if let Some(error) = error {
+            if was_reachable && !self.remote_is_reachable(remote_name) {
+                crate::notifications::send_remote_unreachable(remote_name, &error);
+            }
         }
     }
 
+    /// Check if any host of a remote is reachable.
+    pub fn remote_is_reachable(&self, remote: &str) -> bool {
+        self.remotes
+            .get(remote)
+            .is_none_or(|remote| remote.is_reachable())
+    }
+
     /// Update the node name for a host, if the remote and host exist (otherwise this does
     /// nothing).
     pub fn set_node_name(&mut self, remote_name: &str, hostname: &str, node_name: Option<String>) {
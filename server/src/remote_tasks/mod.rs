@@ -1,7 +1,9 @@
 use std::sync::LazyLock;
+use std::time::Duration;
 
-use anyhow::Error;
+use anyhow::{Error, bail};
 
+use pdm_api_types::remotes::RemoteType;
 use pdm_api_types::{NativeUpid, RemoteUpid, TaskFilters, TaskListItem, TaskStateType};
 use pve_api_types::PveUpid;
 
@@ -10,6 +12,7 @@ pub mod task_cache;
 
 use task_cache::{GetTasks, TaskCache, TaskCacheItem};
 
+use crate::connection::PveClient;
 use crate::views;
 
 /// Base directory for the remote task cache.
@@ -61,7 +64,7 @@ pub async fn get_tasks(
                 }
 
                 match task.upid.native_upid() {
-                    Ok(NativeUpid::PveUpid(pve_upid)) => {
+                    Ok(NativeUpid::PveUpid(pve_upid)) | Ok(NativeUpid::PmgUpid(pve_upid)) => {
                         if let Some(view) = &view {
                             if !view.is_node_included(task.upid.remote(), &pve_upid.node) {
                                 return None;
@@ -228,6 +231,63 @@ pub async fn track_running_pbs_task(
     .await?
 }
 
+/// Insert a newly created PMG task into the list of tracked tasks.
+///
+/// Any tracked task will be polled with a short interval until the task
+/// has finished.
+///
+/// PMG UPIDs share their format with PVE, so the remote type cannot be deduced from the UPID and
+/// is set explicitly here.
+///
+/// This function returns the [`RemoteUpid`] of the tracked PMG task.
+pub async fn track_running_pmg_task(remote: String, upid: PveUpid) -> Result<RemoteUpid, Error> {
+    tokio::task::spawn_blocking(move || {
+        let remote_upid = RemoteUpid::new(remote, RemoteType::Pmg, upid.to_string());
+        let cache = get_cache().write()?;
+
+        let task = TaskCacheItem {
+            upid: remote_upid.clone(),
+            starttime: upid.starttime,
+            status: None,
+            endtime: None,
+        };
+        cache.add_tracked_task(task)?;
+
+        Ok(remote_upid)
+    })
+    .await?
+}
+
+// pve-http-server TCP connection timeout is 5 seconds, use a lower amount with some margin for
+// latency in order to avoid re-opening TCP connections for every polling request.
+const PVE_TASK_POLLING_INTERVAL: Duration = Duration::from_secs(3);
+
+/// Wait for a task of a PVE remote to finish.
+///
+/// # Errors
+///
+/// This function will return an error if:
+/// * There was a problem querying the task status (this does not necessarily mean the task failed).
+/// * The task finished unsuccessfully.
+pub async fn wait_for_pve_task(client: &PveClient, upid: &RemoteUpid) -> Result<(), Error> {
+    let node = upid.pve_upid()?.node;
+    loop {
+        tokio::time::sleep(PVE_TASK_POLLING_INTERVAL).await;
+
+        let status = client.get_task_status(&node, upid.upid()).await?;
+        if !status.is_running() {
+            if status.finished_successfully() == Some(true) {
+                return Ok(());
+            }
+            let exitstatus = status.exitstatus.as_deref().unwrap_or("unknown");
+            bail!(
+                "task did not finish successfully on remote {} - {exitstatus}",
+                upid.remote()
+            );
+        }
+    }
+}
+
 /// Get a reference to the [`TaskCache`] instance.
 pub fn get_cache() -> &'static TaskCache {
     static CACHE: LazyLock<TaskCache> = LazyLock::new(|| {
@@ -5,14 +5,15 @@ use std::time::{Duration, Instant};
 use anyhow::Error;
 use tokio::{sync::Semaphore, task::JoinSet};
 
-use pdm_api_types::RemoteUpid;
 use pdm_api_types::remotes::{Remote, RemoteType};
+use pdm_api_types::{RemoteUpid, TaskStateType};
 use proxmox_section_config::typed::SectionConfigData;
 
 use crate::api;
 use crate::connection;
 use crate::parallel_fetcher::ParallelFetcher;
 use crate::pbs_client;
+use crate::pmg_client;
 use crate::remote_tasks::{
     KEEP_OLD_FILES, ROTATE_AFTER,
     task_cache::{GetTasks, NodeFetchSuccessMap, State, TaskCache, TaskCacheItem},
@@ -326,6 +327,24 @@ async fn fetch_tasks_from_single_node(
 
             Ok(task_list)
         }
+        RemoteType::Pmg => {
+            let params = pmg_client::ListTasks {
+                since: Some(since),
+                // If `limit` is not provided, we only receive 50 tasks
+                limit: Some(MAX_TASKS_TO_FETCH),
+            };
+
+            let client = connection::make_pmg_client(&remote)?;
+
+            let task_list = client
+                .get_task_list(&node, params)
+                .await?
+                .into_iter()
+                .map(|task| map_pmg_task(task, remote.id.clone()))
+                .collect();
+
+            Ok(task_list)
+        }
     }
 }
 
@@ -495,6 +514,29 @@ async fn poll_single_tracked_task(remote: Remote, task: RemoteUpid) -> (RemoteUp
                 PollResult::Running
             };
 
+            (task, result)
+        }
+        RemoteType::Pmg => {
+            let status = match api::pmg::tasks::get_task_status(
+                remote.id.clone(),
+                task.clone(),
+                false,
+            )
+            .await
+            {
+                Ok(status) => status,
+                Err(err) => {
+                    log::error!("could not get status from remote: {err:#}");
+                    return (task, PollResult::RequestError);
+                }
+            };
+
+            let result = if status.exitstatus.is_some() {
+                PollResult::Finished
+            } else {
+                PollResult::Running
+            };
+
             (task, result)
         }
     }
@@ -524,6 +566,20 @@ fn map_pbs_task(task: pbs_api_types::TaskListItem, remote: String) -> TaskCacheI
     }
 }
 
+/// Map a PMG task list entry to `TaskCacheItem`
+///
+/// PMG shares the task list format with PVE.
+fn map_pmg_task(task: pve_api_types::ListTasksResponse, remote: String) -> TaskCacheItem {
+    let remote_upid = RemoteUpid::new(remote, RemoteType::Pmg, task.upid);
+
+    TaskCacheItem {
+        upid: remote_upid,
+        starttime: task.starttime,
+        endtime: task.endtime,
+        status: task.status,
+    }
+}
+
 /// Update task cache with results from tracked task polling & regular task fetching.
 async fn update_task_cache(
     new_tasks: Vec<TaskCacheItem>,
@@ -531,6 +587,28 @@ async fn update_task_cache(
     poll_results: HashMap<RemoteUpid, PollResult>,
 ) -> Result<(), Error> {
     tokio::task::spawn_blocking(move || {
+        let cache = super::get_cache().write()?;
+
+        // Tasks are fetched starting with the cut-off timestamp, so the same finished tasks are
+        // fetched again in the following cycles. Only notify about the failed ones which are not
+        // in the cache yet.
+        let mut failed: HashMap<RemoteUpid, (i64, String)> = new_tasks
+            .iter()
+            .filter_map(|task| {
+                let endtime = task.endtime?;
+                let status = task.status.as_deref()?;
+                (TaskStateType::new_from_str(status) == TaskStateType::Error)
+                    .then(|| (task.upid.clone(), (endtime, status.to_string())))
+            })
+            .collect();
+        if !failed.is_empty() {
+            for task in cache.get_tasks(GetTasks::All)? {
+                if task.endtime.is_some() {
+                    failed.remove(&task.upid);
+                }
+            }
+        }
+
         let drop_tracked = poll_results
             .into_iter()
             .filter_map(|(upid, result)| match result {
@@ -541,9 +619,13 @@ async fn update_task_cache(
             })
             .collect();
 
-        super::get_cache()
-            .write()?
-            .update(new_tasks, &update_state_for_remote, drop_tracked)?;
+        cache.update(new_tasks, &update_state_for_remote, drop_tracked)?;
+
+        crate::notifications::send_remote_tasks_failed(
+            failed
+                .iter()
+                .map(|(upid, (endtime, status))| (upid, *endtime, status.as_str())),
+        );
 
         Ok(())
     })
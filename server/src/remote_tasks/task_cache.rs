@@ -490,7 +490,7 @@ impl<'a> WritableTaskCache<'a> {
             };
 
             let node = match task.upid.remote_type() {
-                RemoteType::Pve => native_upid.node(),
+                RemoteType::Pve | RemoteType::Pmg => native_upid.node(),
                 // The node success map uses 'localhost' as a node name for PBS remotes,
                 // not the one from the UPID.
                 RemoteType::Pbs => "localhost",
@@ -520,7 +520,7 @@ impl<'a> WritableTaskCache<'a> {
             };
 
             let node = match task.upid.remote_type() {
-                RemoteType::Pve => native_upid.node(),
+                RemoteType::Pve | RemoteType::Pmg => native_upid.node(),
                 // The node success map uses 'localhost' as a node name for PBS remotes,
                 // not the one from the UPID.
                 RemoteType::Pbs => "localhost",
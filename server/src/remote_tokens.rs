@@ -0,0 +1,239 @@
+//! Create and delete the API tokens used to access remotes.
+
+use std::error::Error as _;
+
+use anyhow::{Error, bail, format_err};
+use serde::Deserialize;
+
+use proxmox_client::HttpApiClient;
+
+use pdm_api_types::Authid;
+use pdm_api_types::remotes::{Remote, RemoteType};
+
+use crate::{connection, pbs_client, pmg_client};
+
+/// With the `Client`'s error type the message gets a bit long, shorten it.
+fn short_err(err: proxmox_client::Error) -> Error {
+    format_err!("{}", err.source().unwrap_or(&err))
+}
+
+fn encode_userid(authid: &Authid) -> String {
+    percent_encoding::percent_encode(
+        authid.user().as_str().as_bytes(),
+        percent_encoding::NON_ALPHANUMERIC,
+    )
+    .to_string()
+}
+
+// Only contains the properties of `/access/users/{userid}/token/{tokenid}` PDM actually uses.
+#[derive(Deserialize)]
+struct PveTokenInfo {
+    #[serde(deserialize_with = "proxmox_serde::perl::deserialize_bool")]
+    privsep: bool,
+}
+
+// Only contains the properties of `/access/users/{userid}` PDM actually uses.
+#[derive(Deserialize)]
+struct PveUserInfo {
+    #[serde(default)]
+    groups: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct PveAclEntry {
+    path: String,
+    #[serde(rename = "type")]
+    ty: String,
+    ugid: String,
+    roleid: String,
+    #[serde(deserialize_with = "proxmox_serde::perl::deserialize_bool")]
+    propagate: bool,
+}
+
+/// Create a new token with the same privilege separation and ACL entries as the current one.
+///
+/// With `scoped` the new token always uses privilege separation. If the current token has
+/// none, the ACL entries of its user and the user's groups are copied instead. Fails if this leaves
+/// the new token without any ACL entries.
+pub(crate) async fn create_pve_token(
+    remote: &Remote,
+    name: &str,
+    comment: Option<String>,
+    scoped: bool,
+) -> Result<(Authid, String), Error> {
+    let old = remote
+        .authid
+        .tokenname()
+        .ok_or_else(|| format_err!("remote does not use an API token"))?;
+    let raw_client = connection::make_raw_client(remote)?;
+
+    let info: PveTokenInfo = raw_client
+        .get(&format!(
+            "/api2/extjs/access/users/{}/token/{}",
+            encode_userid(&remote.authid),
+            old.as_str()
+        ))
+        .await?
+        .expect_json()?
+        .data;
+
+    let token = connection::make_pve_client(remote)?
+        .create_token(
+            remote.authid.user().as_str(),
+            name,
+            pve_api_types::CreateToken {
+                comment,
+                expire: None,
+                privsep: Some(info.privsep || scoped),
+            },
+        )
+        .await
+        .map_err(short_err)?;
+    let authid: Authid = token.full_tokenid.parse()?;
+
+    // without privilege separation the token has the privileges of the user
+    if info.privsep || scoped {
+        let result = match copy_pve_acls(&raw_client, remote, info.privsep, &authid).await {
+            Ok(0) if scoped => Err(format_err!(
+                "the new token would have no privileges, there are no ACL entries of '{}' or its \
+                 groups to copy (like for 'root@pam', whose privileges are implicit)",
+                remote.authid
+            )),
+            Ok(_) => Ok(()),
+            Err(err) => Err(err),
+        };
+        if let Err(err) = result {
+            if let Err(delete_err) = delete_token(remote, &authid).await {
+                log::warn!("could not delete the new token '{authid}' again - {delete_err}");
+            }
+            return Err(err);
+        }
+    }
+
+    Ok((authid, token.value))
+}
+
+/// Copy the ACL entries of the current token of `remote` to the token `authid`.
+///
+/// Without privilege separation of the current token, the ACL entries of its user and the user's
+/// groups are copied. Returns the number of copied entries.
+async fn copy_pve_acls(
+    raw_client: &connection::LimitedClient,
+    remote: &Remote,
+    privsep: bool,
+    authid: &Authid,
+) -> Result<usize, Error> {
+    let groups = if privsep {
+        Vec::new()
+    } else {
+        let user: PveUserInfo = raw_client
+            .get(&format!(
+                "/api2/extjs/access/users/{}",
+                encode_userid(&remote.authid)
+            ))
+            .await?
+            .expect_json()?
+            .data;
+        user.groups
+    };
+
+    let acls: Vec<PveAclEntry> = raw_client
+        .get("/api2/extjs/access/acl")
+        .await?
+        .expect_json()?
+        .data;
+
+    let mut copied = 0;
+    for acl in acls {
+        let matches = match acl.ty.as_str() {
+            "token" => privsep && acl.ugid == remote.authid.to_string(),
+            "user" => !privsep && acl.ugid == remote.authid.user().as_str(),
+            "group" => groups.contains(&acl.ugid),
+            _ => false,
+        };
+        if !matches {
+            continue;
+        }
+        let params = serde_json::json!({
+            "path": acl.path,
+            "roles": acl.roleid,
+            "tokens": authid.to_string(),
+            "propagate": acl.propagate,
+        });
+        raw_client
+            .put("/api2/extjs/access/acl", &params)
+            .await?
+            .nodata()?;
+        copied += 1;
+    }
+
+    Ok(copied)
+}
+
+/// Create a new token with the same ACL entries as the current one.
+pub(crate) async fn create_pbs_token(
+    remote: &Remote,
+    name: &str,
+    comment: Option<String>,
+) -> Result<(Authid, String), Error> {
+    let client = connection::make_pbs_client(remote)?;
+
+    let tokenname = pbs_api_types::Tokenname::try_from(name.to_string())?;
+    let token = client
+        .create_token(
+            remote.authid.user(),
+            &tokenname,
+            pbs_client::CreateToken {
+                comment,
+                enable: Some(true),
+                expire: None,
+            },
+        )
+        .await
+        .map_err(short_err)?;
+    let authid: Authid = token.tokenid.parse()?;
+
+    client
+        .copy_acls(&remote.authid, &authid)
+        .await
+        .map_err(short_err)?;
+
+    Ok((authid, token.value))
+}
+
+/// Create a new token with the privileges of the current token of `remote`.
+///
+/// For PVE remotes the new token always uses privilege separation, see [`create_pve_token`].
+pub(crate) async fn create_scoped_token(
+    remote: &Remote,
+    name: &str,
+    comment: Option<String>,
+) -> Result<(Authid, String), Error> {
+    match remote.ty {
+        RemoteType::Pve => create_pve_token(remote, name, comment, true).await,
+        RemoteType::Pbs => create_pbs_token(remote, name, comment).await,
+        RemoteType::Pmg => bail!("{}", pmg_client::NO_API_TOKENS),
+    }
+}
+
+/// Delete the token `authid` on the remote, using the credentials of `remote`.
+pub(crate) async fn delete_token(remote: &Remote, authid: &Authid) -> Result<(), Error> {
+    let user = authid.user();
+    let tokenname = authid
+        .tokenname()
+        .ok_or_else(|| format_err!("'{authid}' is not an API token"))?;
+
+    match remote.ty {
+        RemoteType::Pve => connection::make_pve_client(remote)?
+            .delete_token(user.as_str(), tokenname.as_str())
+            .await
+            .map_err(short_err)?,
+        RemoteType::Pbs => connection::make_pbs_client(remote)?
+            .delete_token(user, tokenname)
+            .await
+            .map_err(short_err)?,
+        RemoteType::Pmg => bail!("{}", pmg_client::NO_API_TOKENS),
+    }
+
+    Ok(())
+}
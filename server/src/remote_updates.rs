@@ -98,6 +98,17 @@ pub async fn update_apt_database(remote: &Remote, node: &str) -> Result<RemoteUp
 
             crate::api::pbs::new_remote_upid(remote.id.clone(), upid).await
         }
+        RemoteType::Pmg => {
+            let client = connection::make_pmg_client(remote)?;
+
+            let params = crate::pmg_client::AptUpdateParams {
+                notify: Some(false),
+                quiet: Some(false),
+            };
+            let upid = client.update_apt_database(node, params).await?;
+
+            crate::api::pmg::new_remote_upid(remote.id.clone(), upid).await
+        }
     }
 }
 
@@ -120,6 +131,14 @@ pub async fn get_changelog(remote: &Remote, node: &str, package: String) -> Resu
                 .await
                 .map_err(Into::into)
         }
+        RemoteType::Pmg => {
+            let client = connection::make_pmg_client(remote)?;
+
+            client
+                .get_package_changelog(node, package, None)
+                .await
+                .map_err(Into::into)
+        }
     }
 }
 
@@ -389,6 +408,32 @@ async fn fetch_available_updates(
 
             let repository_status = check_repository_status(&repos, has_active_subscription);
 
+            Ok(NodeUpdateInfo {
+                last_refresh: proxmox_time::epoch_i64(),
+                updates,
+                versions,
+                repository_status,
+            })
+        }
+        RemoteType::Pmg => {
+            let client = connection::make_pmg_client(&remote)?;
+            let updates = client.list_available_updates(&node).await?;
+
+            let versions = client.get_package_versions(&node).await?;
+            let versions = versions
+                .into_iter()
+                .filter(|v| v.package == "pmg-api")
+                .map(map_pmg_package_version)
+                .collect();
+
+            let repos = client.get_apt_repositories(&node).await?;
+            let subscription_info = client.get_subscription(&node).await?;
+
+            let has_active_subscription =
+                subscription_info.status == proxmox_subscription::SubscriptionStatus::Active;
+
+            let repository_status = check_repository_status(&repos, has_active_subscription);
+
             Ok(NodeUpdateInfo {
                 last_refresh: proxmox_time::epoch_i64(),
                 updates,
@@ -428,6 +473,13 @@ fn map_pbs_package_version(info: pbs_api_types::APTUpdateInfo) -> PackageVersion
     }
 }
 
+fn map_pmg_package_version(info: APTUpdateInfo) -> PackageVersion {
+    PackageVersion {
+        package: info.package,
+        version: info.old_version.unwrap_or_default(),
+    }
+}
+
 fn check_repository_status(
     config: &APTRepositoriesResult,
     active_subscription: bool,
@@ -1,6 +1,5 @@
 use std::error::Error as StdError;
 use std::sync::Arc;
-use std::time::Duration;
 
 use anyhow::{self, Context, bail};
 
@@ -12,6 +11,7 @@ use pve_api_types::{
 };
 
 use crate::api::pve::{connect, get_remote};
+use crate::remote_tasks::wait_for_pve_task;
 
 /// Wrapper for [`PveClient`] for representing a locked SDN configuration.
 ///
@@ -316,42 +316,6 @@ impl<C> LockedSdnClients<C> {
         Ok(self)
     }
 
-    // pve-http-server TCP connection timeout is 5 seconds, use a lower amount with some margin for
-    // latency in order to avoid re-opening TCP connections for every polling request.
-    const POLLING_INTERVAL: Duration = Duration::from_secs(3);
-
-    /// Convenience function for polling a running task on a PVE remote.
-    ///
-    /// It polls a given task on a given node, waiting for the task to finish successfully.
-    ///
-    /// # Errors
-    ///
-    /// This function will return an error if:
-    /// * There was a problem querying the task status (this does not necessarily mean the task failed).
-    /// * The task finished unsuccessfully.
-    async fn poll_task(
-        node: String,
-        upid: RemoteUpid,
-        client: Arc<dyn PveClient + Send + Sync>,
-    ) -> Result<RemoteUpid, anyhow::Error> {
-        loop {
-            tokio::time::sleep(Self::POLLING_INTERVAL).await;
-
-            let status = client.get_task_status(&node, upid.upid()).await?;
-
-            if !status.is_running() {
-                if status.finished_successfully() == Some(true) {
-                    return Ok(upid);
-                } else {
-                    bail!(
-                        "task did not finish successfully on remote {}",
-                        upid.remote()
-                    );
-                }
-            }
-        }
-    }
-
     /// Applies and Reloads the SDN configuration for all locked clients.
     ///
     /// This function tries to apply the SDN configuration for all supplied locked clients and, if
@@ -395,10 +359,12 @@ impl<C> LockedSdnClients<C> {
                         continue;
                     };
 
-                    reload_futures.push(
-                        Self::poll_task(upid.node.clone(), remote_upid, client)
-                            .map_err(move |err| (err, ctx)),
-                    );
+                    reload_futures.push(async move {
+                        match wait_for_pve_task(client.as_ref(), &remote_upid).await {
+                            Ok(()) => Ok(remote_upid),
+                            Err(err) => Err((err, ctx)),
+                        }
+                    });
                 }
                 Err((error, ctx)) => {
                     proxmox_log::error!(
@@ -0,0 +1,471 @@
+//! Connect to remotes through SOCKS5 proxies.
+//!
+//! The HTTP client only supports HTTP proxies. For a SOCKS5 proxy, a bridge listening on the
+//! loopback interface accepts the HTTP `CONNECT` requests of the client and opens the tunnels
+//! through the SOCKS5 proxy instead. TLS still happens end-to-end between the client and the
+//! remote, so certificate verification is not affected.
+
+use std::collections::HashMap;
+use std::net::{IpAddr, Ipv4Addr};
+use std::sync::{Arc, LazyLock, Mutex};
+use std::time::Duration;
+
+use anyhow::{Error, bail, format_err};
+use http::Uri;
+use http::uri::Authority;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::net::TcpStream;
+
+use proxmox_http::ProxyConfig;
+
+const DEFAULT_PORT: u16 = 1080;
+
+/// Limit for the HTTP `CONNECT` request sent to the bridge.
+const MAX_REQUEST_HEAD: usize = 8 * 1024;
+
+const SOCKS_VERSION: u8 = 5;
+const AUTH_NONE: u8 = 0;
+const AUTH_PASSWORD: u8 = 2;
+const AUTH_PASSWORD_VERSION: u8 = 1;
+const CMD_CONNECT: u8 = 1;
+const ATYP_IPV4: u8 = 1;
+const ATYP_DOMAIN: u8 = 3;
+const ATYP_IPV6: u8 = 4;
+
+/// The bridges started so far, by SOCKS proxy URL.
+static BRIDGES: LazyLock<Mutex<HashMap<String, ProxyConfig>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// Check whether `url` refers to a SOCKS5 proxy.
+pub fn is_socks_url(url: &str) -> bool {
+    url.starts_with("socks5://") || url.starts_with("socks5h://")
+}
+
+/// The HTTP proxy configuration to connect through the SOCKS5 proxy at `url`.
+///
+/// The bridge for a proxy is started on first use and runs on the current tokio runtime.
+pub fn proxy_config(url: &str) -> Result<ProxyConfig, Error> {
+    let mut bridges = BRIDGES.lock().unwrap();
+    if let Some(config) = bridges.get(url) {
+        return Ok(config.clone());
+    }
+
+    let config = start_bridge(SocksProxy::parse(url)?)?;
+    bridges.insert(url.to_string(), config.clone());
+    Ok(config)
+}
+
+#[derive(Debug, PartialEq)]
+struct SocksProxy {
+    host: String,
+    port: u16,
+    /// Let the proxy resolve host names (`socks5h`).
+    remote_dns: bool,
+    credentials: Option<(String, String)>,
+}
+
+#[derive(Debug, PartialEq)]
+enum TargetAddr {
+    Ip(IpAddr),
+    Domain(String),
+}
+
+fn percent_decode(s: &str) -> Result<String, Error> {
+    Ok(percent_encoding::percent_decode_str(s)
+        .decode_utf8()?
+        .into_owned())
+}
+
+impl SocksProxy {
+    fn parse(url: &str) -> Result<Self, Error> {
+        let uri: Uri = url.parse()?;
+        let remote_dns = match uri.scheme_str() {
+            Some("socks5") => false,
+            Some("socks5h") => true,
+            _ => bail!("unsupported SOCKS proxy URL '{url}'"),
+        };
+        let authority = uri
+            .authority()
+            .ok_or_else(|| format_err!("missing proxy authority"))?;
+
+        let credentials = match authority.as_str().rsplit_once('@') {
+            Some((userinfo, _)) => {
+                let (user, password) = userinfo.split_once(':').unwrap_or((userinfo, ""));
+                Some((percent_decode(user)?, percent_decode(password)?))
+            }
+            None => None,
+        };
+
+        Ok(Self {
+            host: authority
+                .host()
+                .trim_start_matches('[')
+                .trim_end_matches(']')
+                .to_string(),
+            port: authority.port_u16().unwrap_or(DEFAULT_PORT),
+            remote_dns,
+            credentials,
+        })
+    }
+
+    /// Open a connection to `host` and `port` through the proxy.
+    async fn connect(&self, host: &str, port: u16) -> Result<TcpStream, Error> {
+        let target = match host.parse::<IpAddr>() {
+            Ok(ip) => TargetAddr::Ip(ip),
+            Err(_) if self.remote_dns => TargetAddr::Domain(host.to_string()),
+            Err(_) => {
+                let addr = tokio::net::lookup_host((host, port))
+                    .await?
+                    .next()
+                    .ok_or_else(|| format_err!("could not resolve '{host}'"))?;
+                TargetAddr::Ip(addr.ip())
+            }
+        };
+
+        let mut stream = TcpStream::connect((self.host.as_str(), self.port))
+            .await
+            .map_err(|err| format_err!("could not connect to the SOCKS proxy - {err}"))?;
+        handshake(&mut stream, self.credentials.as_ref(), &target, port).await?;
+        Ok(stream)
+    }
+}
+
+fn reply_message(reply: u8) -> &'static str {
+    match reply {
+        1 => "general failure",
+        2 => "connection not allowed by ruleset",
+        3 => "network unreachable",
+        4 => "host unreachable",
+        5 => "connection refused",
+        6 => "TTL expired",
+        7 => "command not supported",
+        8 => "address type not supported",
+        _ => "unknown error",
+    }
+}
+
+/// Perform the SOCKS5 handshake to connect to `target` and `port`, see RFC 1928 and RFC 1929.
+async fn handshake<S>(
+    stream: &mut S,
+    credentials: Option<&(String, String)>,
+    target: &TargetAddr,
+    port: u16,
+) -> Result<(), Error>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let method = if credentials.is_some() {
+        AUTH_PASSWORD
+    } else {
+        AUTH_NONE
+    };
+    stream.write_all(&[SOCKS_VERSION, 1, method]).await?;
+
+    let mut reply = [0u8; 2];
+    stream.read_exact(&mut reply).await?;
+    if reply[0] != SOCKS_VERSION {
+        bail!("invalid reply from SOCKS proxy");
+    }
+    if reply[1] != method {
+        bail!("SOCKS proxy does not accept the authentication method");
+    }
+
+    if let Some((user, password)) = credentials {
+        if user.len() > 255 || password.len() > 255 {
+            bail!("SOCKS proxy user or password too long");
+        }
+        let mut request = vec![AUTH_PASSWORD_VERSION, user.len() as u8];
+        request.extend(user.as_bytes());
+        request.push(password.len() as u8);
+        request.extend(password.as_bytes());
+        stream.write_all(&request).await?;
+
+        stream.read_exact(&mut reply).await?;
+        if reply[1] != 0 {
+            bail!("SOCKS proxy authentication failed");
+        }
+    }
+
+    let mut request = vec![SOCKS_VERSION, CMD_CONNECT, 0];
+    match target {
+        TargetAddr::Ip(IpAddr::V4(ip)) => {
+            request.push(ATYP_IPV4);
+            request.extend(ip.octets());
+        }
+        TargetAddr::Ip(IpAddr::V6(ip)) => {
+            request.push(ATYP_IPV6);
+            request.extend(ip.octets());
+        }
+        TargetAddr::Domain(name) => {
+            if name.len() > 255 {
+                bail!("host name '{name}' too long for SOCKS proxy");
+            }
+            request.push(ATYP_DOMAIN);
+            request.push(name.len() as u8);
+            request.extend(name.as_bytes());
+        }
+    }
+    request.extend(port.to_be_bytes());
+    stream.write_all(&request).await?;
+
+    let mut head = [0u8; 4];
+    stream.read_exact(&mut head).await?;
+    if head[0] != SOCKS_VERSION {
+        bail!("invalid reply from SOCKS proxy");
+    }
+    if head[1] != 0 {
+        bail!("SOCKS proxy could not connect - {}", reply_message(head[1]));
+    }
+
+    // skip the address the proxy bound to, and its port
+    let len = match head[3] {
+        ATYP_IPV4 => 4,
+        ATYP_IPV6 => 16,
+        ATYP_DOMAIN => stream.read_u8().await? as usize,
+        _ => bail!("invalid reply from SOCKS proxy"),
+    };
+    let mut bound = vec![0u8; len + 2];
+    stream.read_exact(&mut bound).await?;
+
+    Ok(())
+}
+
+/// Parse an HTTP `CONNECT` request head, returns the target and the proxy authorization.
+fn parse_connect_request(head: &str) -> Result<(Authority, Option<&str>), Error> {
+    let mut lines = head.lines();
+    let request_line = lines.next().unwrap_or_default();
+    let target = match request_line.split_whitespace().collect::<Vec<_>>()[..] {
+        ["CONNECT", target, _version] => target,
+        _ => bail!("not a CONNECT request - '{request_line}'"),
+    };
+    let target: Authority = target.parse()?;
+    if target.port_u16().is_none() {
+        bail!("CONNECT request without port");
+    }
+
+    let authorization = lines.find_map(|line| {
+        let (name, value) = line.split_once(':')?;
+        name.eq_ignore_ascii_case("proxy-authorization")
+            .then_some(value.trim())
+    });
+
+    Ok((target, authorization))
+}
+
+async fn read_request_head(stream: &mut TcpStream) -> Result<String, Error> {
+    let mut head = Vec::new();
+    let mut buf = [0u8; 1024];
+    while !head.ends_with(b"\r\n\r\n") {
+        if head.len() > MAX_REQUEST_HEAD {
+            bail!("request too large");
+        }
+        let len = stream.read(&mut buf).await?;
+        if len == 0 {
+            bail!("connection closed");
+        }
+        head.extend_from_slice(&buf[..len]);
+    }
+    Ok(String::from_utf8(head)?)
+}
+
+/// Serve a single HTTP `CONNECT` request through the SOCKS proxy.
+async fn tunnel(
+    mut client: TcpStream,
+    proxy: &SocksProxy,
+    authorization: &str,
+) -> Result<(), Error> {
+    let head = read_request_head(&mut client).await?;
+    let target = match parse_connect_request(&head) {
+        Ok((target, auth)) if auth == Some(authorization) => target,
+        Ok(_) => {
+            client
+                .write_all(b"HTTP/1.1 407 Proxy Authentication Required\r\n\r\n")
+                .await?;
+            bail!("client not authorized");
+        }
+        Err(err) => {
+            client
+                .write_all(b"HTTP/1.1 400 Bad Request\r\n\r\n")
+                .await?;
+            return Err(err);
+        }
+    };
+
+    let host = target.host().trim_start_matches('[').trim_end_matches(']');
+    let port = target.port_u16().unwrap_or_default();
+    let mut remote = match proxy.connect(host, port).await {
+        Ok(remote) => remote,
+        Err(err) => {
+            client
+                .write_all(b"HTTP/1.1 502 Bad Gateway\r\n\r\n")
+                .await?;
+            return Err(format_err!("connecting to {target} failed - {err}"));
+        }
+    };
+
+    client
+        .write_all(b"HTTP/1.1 200 Connection established\r\n\r\n")
+        .await?;
+    tokio::io::copy_bidirectional(&mut client, &mut remote).await?;
+    Ok(())
+}
+
+/// Start a bridge to `proxy` on the loopback interface and return the configuration to use it.
+///
+/// Other local users must not be able to use the proxy through the bridge, so the client has to
+/// authenticate with a random secret.
+fn start_bridge(proxy: SocksProxy) -> Result<ProxyConfig, Error> {
+    let runtime = tokio::runtime::Handle::try_current()
+        .map_err(|_| format_err!("SOCKS proxies can only be used within a tokio runtime"))?;
+
+    let listener = std::net::TcpListener::bind((Ipv4Addr::LOCALHOST, 0))?;
+    listener.set_nonblocking(true)?;
+    let addr = listener.local_addr()?;
+
+    let credentials = format!("pdm:{}", hex::encode(proxmox_sys::linux::random_data(16)?));
+    let config = ProxyConfig::parse_proxy_url(&format!("http://{credentials}@{addr}"))?;
+    let authorization = format!("Basic {}", proxmox_base64::encode(&credentials));
+
+    let proxy = Arc::new(proxy);
+    runtime.spawn(async move {
+        let listener = match tokio::net::TcpListener::from_std(listener) {
+            Ok(listener) => listener,
+            Err(err) => {
+                log::error!("could not start bridge to SOCKS proxy - {err}");
+                return;
+            }
+        };
+        loop {
+            let client = match listener.accept().await {
+                Ok((client, _addr)) => client,
+                Err(err) => {
+                    log::error!("bridge to SOCKS proxy could not accept connection - {err}");
+                    tokio::time::sleep(Duration::from_secs(1)).await;
+                    continue;
+                }
+            };
+            let proxy = Arc::clone(&proxy);
+            let authorization = authorization.clone();
+            tokio::spawn(async move {
+                if let Err(err) = tunnel(client, &proxy, &authorization).await {
+                    log::warn!("SOCKS proxy {}:{} - {err}", proxy.host, proxy.port);
+                }
+            });
+        }
+    });
+
+    Ok(config)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_proxy_urls() {
+        assert_eq!(
+            SocksProxy::parse("socks5://proxy.example.com").unwrap(),
+            SocksProxy {
+                host: "proxy.example.com".into(),
+                port: DEFAULT_PORT,
+                remote_dns: false,
+                credentials: None,
+            }
+        );
+        assert_eq!(
+            SocksProxy::parse("socks5h://user:p%40ss@[fd00::1]:1081").unwrap(),
+            SocksProxy {
+                host: "fd00::1".into(),
+                port: 1081,
+                remote_dns: true,
+                credentials: Some(("user".into(), "p@ss".into())),
+            }
+        );
+        assert!(SocksProxy::parse("socks4://proxy.example.com").is_err());
+        assert!(SocksProxy::parse("http://proxy.example.com").is_err());
+    }
+
+    #[test]
+    fn connect_requests() {
+        let (target, auth) = parse_connect_request(
+            "CONNECT pve1.example.com:8006 HTTP/1.1\r\n\
+             Host: pve1.example.com:8006\r\n\
+             Proxy-Authorization: Basic cGRtOng=\r\n\r\n",
+        )
+        .unwrap();
+        assert_eq!(target.as_str(), "pve1.example.com:8006");
+        assert_eq!(auth, Some("Basic cGRtOng="));
+
+        let (_target, auth) =
+            parse_connect_request("CONNECT [fd00::1]:8006 HTTP/1.1\r\n\r\n").unwrap();
+        assert_eq!(auth, None);
+
+        assert!(parse_connect_request("GET / HTTP/1.1\r\n\r\n").is_err());
+        assert!(parse_connect_request("CONNECT pve1.example.com HTTP/1.1\r\n\r\n").is_err());
+    }
+
+    #[tokio::test]
+    async fn socks_handshake() {
+        let (mut client, mut server) = tokio::io::duplex(1024);
+
+        let proxy = tokio::spawn(async move {
+            let mut greeting = [0u8; 3];
+            server.read_exact(&mut greeting).await.unwrap();
+            assert_eq!(greeting, [SOCKS_VERSION, 1, AUTH_PASSWORD]);
+            server
+                .write_all(&[SOCKS_VERSION, AUTH_PASSWORD])
+                .await
+                .unwrap();
+
+            let mut auth = [0u8; 9];
+            server.read_exact(&mut auth).await.unwrap();
+            assert_eq!(&auth, b"\x01\x04user\x02pw");
+            server.write_all(&[AUTH_PASSWORD_VERSION, 0]).await.unwrap();
+
+            let mut request = [0u8; 5 + 17 + 2];
+            server.read_exact(&mut request).await.unwrap();
+            assert_eq!(
+                &request[..5],
+                [SOCKS_VERSION, CMD_CONNECT, 0, ATYP_DOMAIN, 17]
+            );
+            assert_eq!(&request[5..22], b"pve1.example.com.");
+            assert_eq!(&request[22..], 8006u16.to_be_bytes());
+
+            server
+                .write_all(&[SOCKS_VERSION, 0, 0, ATYP_IPV4, 10, 0, 0, 1, 0x1f, 0x46])
+                .await
+                .unwrap();
+        });
+
+        let credentials = ("user".to_string(), "pw".to_string());
+        let target = TargetAddr::Domain("pve1.example.com.".into());
+        handshake(&mut client, Some(&credentials), &target, 8006)
+            .await
+            .unwrap();
+        proxy.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn socks_connect_failure() {
+        let (mut client, mut server) = tokio::io::duplex(1024);
+
+        tokio::spawn(async move {
+            let mut greeting = [0u8; 3];
+            server.read_exact(&mut greeting).await.unwrap();
+            server.write_all(&[SOCKS_VERSION, AUTH_NONE]).await.unwrap();
+
+            let mut request = [0u8; 4 + 4 + 2];
+            server.read_exact(&mut request).await.unwrap();
+            server
+                .write_all(&[SOCKS_VERSION, 5, 0, ATYP_IPV4, 0, 0, 0, 0, 0, 0])
+                .await
+                .unwrap();
+        });
+
+        let target = TargetAddr::Ip("192.0.2.10".parse().unwrap());
+        let err = handshake(&mut client, None, &target, 8006)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("connection refused"));
+    }
+}
@@ -6,7 +6,6 @@ use serde_json::json;
 
 use pdm_api_types::{Authid, ConfigDigest, remotes::Remote};
 use pdm_config::remotes::RemoteConfig;
-use proxmox_client::Client;
 use proxmox_product_config::ApiLockGuard;
 use proxmox_section_config::typed::SectionConfigData;
 use pve_api_types::{
@@ -15,8 +14,9 @@ use pve_api_types::{
 };
 
 use crate::{
-    connection::{ClientFactory, PveClient},
+    connection::{ClientFactory, LimitedClient, PveClient},
     pbs_client::PbsClient,
+    pmg_client::PmgClient,
 };
 
 const KIBI: u64 = 1024;
@@ -50,6 +50,11 @@ impl RemoteConfig for FakeRemoteConfig {
                     authid: Authid::root_auth_id().clone(),
                     token: "".into(),
                     web_url: None,
+                    folder: None,
+                    timeout: None,
+                    http_proxy: None,
+                    max_connections: None,
+                    token_created: None,
                 },
             );
         }
@@ -109,7 +114,11 @@ impl ClientFactory for FakeClientFactory {
         bail!("not implemented")
     }
 
-    fn make_raw_client(&self, _remote: &Remote) -> Result<Box<Client>, Error> {
+    fn make_pmg_client(&self, _remote: &Remote) -> Result<Box<PmgClient>, Error> {
+        bail!("not implemented")
+    }
+
+    fn make_raw_client(&self, _remote: &Remote) -> Result<Box<LimitedClient>, Error> {
         bail!("not implemented")
     }
 
@@ -120,7 +129,14 @@ impl ClientFactory for FakeClientFactory {
     async fn make_pbs_client_and_login(
         &self,
         _remote: &Remote,
-    ) -> Result<Box<PbsClient<Client>>, Error> {
+    ) -> Result<Box<PbsClient<LimitedClient>>, Error> {
+        bail!("not implemented")
+    }
+
+    async fn make_pmg_client_and_login(
+        &self,
+        _remote: &Remote,
+    ) -> Result<Box<PmgClient<LimitedClient>>, Error> {
         bail!("not implemented")
     }
 }
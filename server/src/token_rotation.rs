@@ -0,0 +1,223 @@
+//! Rotate the API tokens used to access remotes.
+//!
+//! A new token with the same privileges is created on the remote and verified, then it replaces
+//! the old token in the remote config and the old token is deleted on the remote.
+
+use anyhow::{Error, bail, format_err};
+
+use proxmox_time::{epoch_i64, epoch_to_rfc2822};
+
+use pdm_api_types::Authid;
+use pdm_api_types::remotes::{Remote, RemoteType};
+
+use crate::remote_tokens::{create_pbs_token, create_pve_token, delete_token};
+use crate::{connection, pmg_client};
+
+/// The worker and job state type of token rotations.
+pub const WORKER_TYPE: &str = "rotate-remote-token";
+
+const SECONDS_PER_DAY: i64 = 24 * 60 * 60;
+
+/// Check whether the token of a remote is older than `max_age_days`.
+///
+/// Tokens of unknown age (remotes added before the age was recorded) are always due, so that
+/// enabling the rotation covers every remote. Remotes using a user and password are never due.
+pub fn needs_rotation(remote: &Remote, max_age_days: u64, now: i64) -> bool {
+    if !remote.authid.is_token() {
+        return false;
+    }
+    match remote.token_age(now) {
+        Some(age) => age >= max_age_days as i64 * SECONDS_PER_DAY,
+        None => true,
+    }
+}
+
+/// The name of the token replacing the token `old` at `now`.
+///
+/// The creation time is appended to the name, replacing the one of a previous rotation.
+fn rotated_token_name(old: &str, now: i64) -> Result<String, Error> {
+    let base = match old.rsplit_once("-r") {
+        Some((base, time)) if time.len() == 14 && time.bytes().all(|b| b.is_ascii_digit()) => base,
+        _ => old,
+    };
+    Ok(format!(
+        "{base}-r{}",
+        proxmox_time::strftime_utc("%Y%m%d%H%M%S", now)?
+    ))
+}
+
+/// Check that the remote can be accessed with its configured token.
+async fn verify_token(remote: &Remote) -> Result<(), Error> {
+    match remote.ty {
+        RemoteType::Pve => connection::make_pve_client(remote)?.version().await?,
+        RemoteType::Pbs => connection::make_pbs_client(remote)?.version().await?,
+        RemoteType::Pmg => connection::make_pmg_client(remote)?.version().await?,
+    };
+    Ok(())
+}
+
+/// Rotate the API token of a remote.
+///
+/// The new token is only stored once it was verified to work. If it can't be stored, it is
+/// deleted again and the old token stays in use. Failing to delete the old token afterwards is
+/// only logged, as the rotation itself already happened.
+pub async fn rotate_token(id: &str) -> Result<(), Error> {
+    let (remotes, _) = pdm_config::remotes::config()?;
+    let mut remote = crate::api::remotes::get_remote(&remotes, id)?.clone();
+    remote.token = pdm_config::remotes::get_secret_token(&remote)?;
+
+    let Some(old_name) = remote.authid.tokenname() else {
+        bail!("remote '{id}' does not use an API token");
+    };
+
+    let now = epoch_i64();
+    let name = rotated_token_name(old_name.as_str(), now)?;
+    let comment = Some(format!(
+        "auto-generated by PDM host '{}' on {}",
+        proxmox_sys::nodename(),
+        epoch_to_rfc2822(now)?
+    ));
+
+    let (authid, token) = match remote.ty {
+        RemoteType::Pve => create_pve_token(&remote, &name, comment, false).await,
+        RemoteType::Pbs => create_pbs_token(&remote, &name, comment).await,
+        RemoteType::Pmg => bail!("{}", pmg_client::NO_API_TOKENS),
+    }
+    .map_err(|err| format_err!("error creating token: {err}"))?;
+    log::info!("created new token '{authid}'");
+
+    let new_remote = Remote {
+        authid: authid.clone(),
+        token,
+        token_created: Some(now),
+        ..remote.clone()
+    };
+
+    if let Err(err) = store_token(id, &remote.authid, &new_remote).await {
+        if let Err(err) = delete_token(&remote, &authid).await {
+            log::warn!("could not delete the new token '{authid}' again - {err}");
+        }
+        return Err(err);
+    }
+    log::info!("replaced token '{}' with '{authid}'", remote.authid);
+
+    match delete_token(&new_remote, &remote.authid).await {
+        Ok(()) => log::info!("deleted old token '{}'", remote.authid),
+        Err(err) => log::warn!(
+            "could not delete old token '{}', please delete it manually - {err}",
+            remote.authid
+        ),
+    }
+
+    Ok(())
+}
+
+/// Verify the new token and replace the old one in the remote config.
+async fn store_token(id: &str, old_authid: &Authid, new_remote: &Remote) -> Result<(), Error> {
+    verify_token(new_remote)
+        .await
+        .map_err(|err| format_err!("could not access remote with the new token - {err}"))?;
+
+    let _lock = pdm_config::remotes::lock_config()?;
+    let (mut remotes, _) = pdm_config::remotes::config()?;
+
+    let entry = remotes
+        .get_mut(id)
+        .ok_or_else(|| format_err!("remote '{id}' was removed in the meantime"))?;
+    if entry.authid != *old_authid {
+        bail!("the token of remote '{id}' was changed in the meantime");
+    }
+
+    entry.authid = new_remote.authid.clone();
+    entry.token = new_remote.token.clone();
+    entry.token_created = new_remote.token_created;
+
+    pdm_config::remotes::save_config(remotes)
+}
+
+/// Rotate the API tokens of all remotes older than `max_age_days`, or of all remotes using an
+/// API token if `max_age_days` is `None`.
+///
+/// Meant to be run in a worker task, logs the outcome for every remote and fails if any
+/// rotation failed.
+pub async fn rotate_tokens(max_age_days: Option<u64>) -> Result<(), Error> {
+    let (remotes, _) = pdm_config::remotes::config()?;
+    let now = epoch_i64();
+
+    let mut rotated = 0;
+    let mut failed = 0;
+
+    for (id, remote) in remotes.iter() {
+        let due = match max_age_days {
+            Some(max_age_days) => needs_rotation(remote, max_age_days, now),
+            None => remote.authid.is_token(),
+        };
+        if !due {
+            continue;
+        }
+
+        log::info!("rotating API token of remote '{id}'");
+        match rotate_token(id).await {
+            Ok(()) => rotated += 1,
+            Err(err) => {
+                failed += 1;
+                log::error!("rotating API token of remote '{id}' failed - {err:#}");
+            }
+        }
+    }
+
+    log::info!("rotated {rotated} API token(s)");
+    if failed > 0 {
+        bail!("rotating {failed} API token(s) failed");
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn token_names() {
+        // 2026-10-17 12:34:56 UTC
+        let now = 1_792_240_496;
+
+        assert_eq!(
+            rotated_token_name("pdm-admin-pdm1", now).unwrap(),
+            "pdm-admin-pdm1-r20261017123456"
+        );
+        assert_eq!(
+            rotated_token_name("pdm-admin-pdm1-r20260719000000", now).unwrap(),
+            "pdm-admin-pdm1-r20261017123456"
+        );
+        assert_eq!(
+            rotated_token_name("pdm-r123", now).unwrap(),
+            "pdm-r123-r20261017123456"
+        );
+    }
+
+    #[test]
+    fn rotation_due() {
+        let mut remote: Remote = serde_json::from_value(serde_json::json!({
+            "type": "pve",
+            "id": "pve1",
+            "nodes": ["pve1.example.com"],
+            "authid": "root@pam!pdm-admin",
+            "token": "-",
+        }))
+        .unwrap();
+        let now = 100 * SECONDS_PER_DAY;
+
+        assert!(needs_rotation(&remote, 90, now));
+
+        remote.token_created = Some(now - 89 * SECONDS_PER_DAY);
+        assert!(!needs_rotation(&remote, 90, now));
+
+        remote.token_created = Some(now - 90 * SECONDS_PER_DAY);
+        assert!(needs_rotation(&remote, 90, now));
+
+        remote.authid = "root@pam".parse().unwrap();
+        assert!(!needs_rotation(&remote, 90, now));
+    }
+}
@@ -1,8 +1,11 @@
+use std::collections::HashMap;
+
 use anyhow::{Error, format_err};
 
 use pdm_api_types::{
+    remotes::folder_and_parents,
     resource::{Resource, ResourceType},
-    views::{FilterRule, ViewConfig, ViewConfigEntry},
+    views::{FilterRule, StringMatcher, ViewConfig, ViewConfigEntry},
 };
 
 #[cfg(test)]
@@ -20,9 +23,26 @@ pub fn get_view(view_id: &str) -> Result<View, Error> {
         .cloned()
         .ok_or_else(|| format_err!("unknown view: {view_id}"))?;
 
-    match entry {
-        ViewConfigEntry::View(view_config) => Ok(View::new(view_config)),
+    let view = match entry {
+        ViewConfigEntry::View(view_config) => View::new(view_config),
+    };
+
+    load_remote_folders(view)
+}
+
+/// Load the folders of the remotes into the view if any of its rules needs them.
+pub fn load_remote_folders(view: View) -> Result<View, Error> {
+    if !view.has_folder_rules() {
+        return Ok(view);
     }
+
+    let (remotes, _) = pdm_config::remotes::config()?;
+    let folders = remotes
+        .into_iter()
+        .filter_map(|(id, remote)| Some((id, remote.folder?)))
+        .collect();
+
+    Ok(view.with_remote_folders(folders))
 }
 
 /// Get (optional) view with a given ID.
@@ -40,12 +60,37 @@ pub fn get_optional_view(view_id: Option<&str>) -> Result<Option<View>, Error> {
 #[derive(Clone)]
 pub struct View {
     config: ViewConfig,
+    folders: HashMap<String, String>,
 }
 
 impl View {
     /// Create a new [`View`].
     pub fn new(config: ViewConfig) -> Self {
-        Self { config }
+        Self {
+            config,
+            folders: HashMap::new(),
+        }
+    }
+
+    /// Set the folders of the remotes, mapping remote IDs to folder paths.
+    ///
+    /// Needed to evaluate `folder` rules, remotes without folder never match them.
+    pub fn with_remote_folders(mut self, folders: HashMap<String, String>) -> Self {
+        self.folders = folders;
+        self
+    }
+
+    /// Check if any rule matches on the folder of remotes.
+    pub fn has_folder_rules(&self) -> bool {
+        self.config
+            .include
+            .iter()
+            .chain(self.config.exclude.iter())
+            .any(|rule| matches!(rule, FilterRule::Folder(_)))
+    }
+
+    fn folder_of(&self, remote: &str) -> Option<&str> {
+        self.folders.get(remote).map(String::as_str)
     }
 
     /// Check if a [`Resource`] matches the filter rules.
@@ -62,13 +107,13 @@ impl View {
     /// Check if a remote can be safely skipped based on the filter rule definition.
     ///
     /// When there are `include remote:<...>` or `exclude remote:<...>` rules, we can use these to
-    /// check if a remote needs to be considered at all.
+    /// check if a remote needs to be considered at all. The same goes for `folder:<...>` rules.
     pub fn can_skip_remote(&self, remote: &str) -> bool {
         let matches_any_exclude_remote = self
             .config
             .exclude
             .iter()
-            .any(|rule| Self::matches_remote_rule(remote, rule));
+            .any(|rule| self.matches_remote_rule(remote, rule));
 
         if matches_any_exclude_remote {
             return true;
@@ -79,8 +124,8 @@ impl View {
         }
 
         for include in &self.config.include {
-            if let FilterRule::Remote(r) = include {
-                if r.matches(remote) {
+            if let FilterRule::Remote(_) | FilterRule::Folder(_) = include {
+                if self.matches_remote_rule(remote, include) {
                     return false;
                 }
             } else {
@@ -105,14 +150,14 @@ impl View {
             self.config
                 .include
                 .iter()
-                .any(|rule| Self::matches_remote_rule(remote, rule))
+                .any(|rule| self.matches_remote_rule(remote, rule))
         };
 
         let matches_exclude_remote = self
             .config
             .exclude
             .iter()
-            .any(|rule| Self::matches_remote_rule(remote, rule));
+            .any(|rule| self.matches_remote_rule(remote, rule));
 
         included && !matches_exclude_remote
     }
@@ -142,23 +187,46 @@ impl View {
             return true;
         }
 
-        check_rules(&self.config.include, remote, resource)
+        check_rules(
+            &self.config.include,
+            remote,
+            self.folder_of(remote),
+            resource,
+        )
     }
 
     fn check_if_excluded(&self, remote: &str, resource: &ResourceData) -> bool {
-        check_rules(&self.config.exclude, remote, resource)
+        check_rules(
+            &self.config.exclude,
+            remote,
+            self.folder_of(remote),
+            resource,
+        )
     }
 
-    fn matches_remote_rule(remote: &str, rule: &FilterRule) -> bool {
-        if let FilterRule::Remote(r) = rule {
-            r.matches(remote)
-        } else {
-            false
+    fn matches_remote_rule(&self, remote: &str, rule: &FilterRule) -> bool {
+        match rule {
+            FilterRule::Remote(r) => r.matches(remote),
+            FilterRule::Folder(f) => folder_matches(f, self.folder_of(remote)),
+            _ => false,
         }
     }
 }
 
-fn check_rules(rules: &[FilterRule], remote: &str, resource: &ResourceData) -> bool {
+/// Check if a folder or any of its parent folders matches, remotes without folder never match.
+fn folder_matches(matcher: &StringMatcher, folder: Option<&str>) -> bool {
+    match folder {
+        Some(folder) => folder_and_parents(folder).any(|f| matcher.matches(f)),
+        None => false,
+    }
+}
+
+fn check_rules(
+    rules: &[FilterRule],
+    remote: &str,
+    folder: Option<&str>,
+    resource: &ResourceData,
+) -> bool {
     rules.iter().any(|rule| match rule {
         FilterRule::ResourceType(resource_type) => resource_type.matches(&resource.resource_type),
         FilterRule::ResourcePool(pool) => {
@@ -177,6 +245,7 @@ fn check_rules(rules: &[FilterRule], remote: &str, resource: &ResourceData) -> b
             }
         }
         FilterRule::Remote(included_remote) => included_remote.matches(remote),
+        FilterRule::Folder(included_folder) => folder_matches(included_folder, folder),
     })
 }
 
@@ -206,6 +275,7 @@ impl<'a> From<&'a Resource> for ResourceData<'a> {
             | Resource::PveNetwork(_)
             | Resource::PbsNode(_)
             | Resource::PbsDatastore(_)
+            | Resource::PmgNode(_)
             | Resource::PveStorage(_) => ResourceData {
                 resource_type: value.resource_type(),
                 tags: None,
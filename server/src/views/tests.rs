@@ -1,3 +1,5 @@
+use std::collections::HashMap;
+
 use pdm_api_types::{
     resource::{PveLxcResource, PveQemuResource, PveStorageResource, Resource},
     views::{ViewConfig, ViewConfigEntry},
@@ -15,6 +17,7 @@ fn make_storage_resource(remote: &str, node: &str, storage_name: &str) -> Resour
         node: node.into(),
         status: "available".into(),
         shared: false,
+        estimated_full: None,
     })
 }
 
@@ -669,3 +672,64 @@ view: test
     // Assert that is not *explicitly* included
     assert!(view.is_remote_explicitly_included("remote-b"));
 }
+
+fn folders() -> HashMap<String, String> {
+    [
+        ("remote-a", "site-a"),
+        ("remote-b", "site-a/rack1"),
+        ("remote-c", "site-b"),
+    ]
+    .into_iter()
+    .map(|(remote, folder)| (remote.to_string(), folder.to_string()))
+    .collect()
+}
+
+#[test]
+fn include_folder() {
+    let config = parse_config(
+        "
+view: test
+    include folder=site-a
+    exclude glob:folder=*/rack1
+",
+    );
+
+    let view = View::new(config).with_remote_folders(folders());
+    assert!(view.has_folder_rules());
+
+    for (remote, expected) in [
+        ("remote-a", true),
+        ("remote-b", false),
+        ("remote-c", false),
+        ("remote-d", false),
+    ] {
+        let resource = make_storage_resource(remote, NODE, STORAGE);
+        assert_eq!(view.resource_matches(remote, &resource), expected);
+    }
+
+    assert!(!view.can_skip_remote("remote-a"));
+    assert!(view.can_skip_remote("remote-b"));
+    assert!(view.can_skip_remote("remote-c"));
+    assert!(view.can_skip_remote("remote-d"));
+
+    assert!(view.is_remote_explicitly_included("remote-a"));
+    assert!(!view.is_remote_explicitly_included("remote-b"));
+}
+
+#[test]
+fn include_subfolders() {
+    let config = parse_config(
+        "
+view: test
+    include folder=site-a
+",
+    );
+
+    let view = View::new(config).with_remote_folders(folders());
+
+    assert!(view.is_remote_explicitly_included("remote-a"));
+    assert!(view.is_remote_explicitly_included("remote-b"));
+    assert!(!view.is_remote_explicitly_included("remote-c"));
+    assert!(view.is_node_included("remote-b", NODE));
+    assert!(!view.is_node_included("remote-c", NODE));
+}
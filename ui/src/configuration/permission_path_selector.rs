@@ -1,3 +1,4 @@
+use std::collections::BTreeSet;
 use std::rc::Rc;
 
 use anyhow::Error;
@@ -8,6 +9,8 @@ use pwt::{AsyncPool, prelude::*};
 
 use pwt_macros::{builder, widget};
 
+use pdm_api_types::remotes::folder_and_parents;
+
 use crate::{RemoteList, pdm_client};
 
 static PREDEFINED_PATHS: &[&str] = &[
@@ -78,6 +81,18 @@ impl PdmPermissionPathSelector {
                 .map(|remote| format!("/resource/{}", remote.id))
                 .collect();
             paths.append(&mut remote_paths);
+
+            // folders only exist implicitly, offer every folder a remote is placed in
+            let folders: BTreeSet<&str> = remotes
+                .iter()
+                .filter_map(|remote| remote.folder.as_deref())
+                .flat_map(folder_and_parents)
+                .collect();
+            paths.extend(
+                folders
+                    .into_iter()
+                    .map(|folder| format!("/resource/folder/{folder}")),
+            );
         }
 
         Ok(paths)
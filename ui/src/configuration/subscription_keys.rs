@@ -651,6 +651,8 @@ fn assign_input_panel(
                     .key("node-localhost"),
             )
             .into(),
+        // PMG keys cannot be pooled yet, see `ProductType::matches_remote_type`.
+        RemoteType::Pmg => panel.into(),
     }
 }
 
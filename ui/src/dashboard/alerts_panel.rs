@@ -0,0 +1,103 @@
+use anyhow::Error;
+
+use pdm_api_types::alerts::{Alert, AlertSeverity, AlertState};
+use proxmox_yew_comp::Status;
+use pwt::css::{self, TextAlign};
+use pwt::prelude::*;
+use pwt::state::SharedState;
+use pwt::widget::{Column, Container, Fa, List, ListTile, Panel, error_message};
+
+use crate::LoadResult;
+use crate::dashboard::create_title_with_icon;
+
+use super::loading_column;
+
+fn create_list_tile(alert: &Alert) -> ListTile {
+    let icon = match alert.severity {
+        AlertSeverity::Warning => Fa::from(Status::Warning),
+        AlertSeverity::Critical => Fa::from(Status::Error),
+    };
+
+    let mut title = alert.rule.clone();
+    if alert.state == AlertState::Pending {
+        title = format!("{title} ({})", tr!("pending"));
+    }
+
+    let unit = alert.metric.unit();
+
+    ListTile::new()
+        .with_child(icon)
+        .with_child(
+            Column::new()
+                .padding_x(2)
+                .with_child(Container::new().with_child(title))
+                .with_child(
+                    Container::new()
+                        .class("pwt-font-label-small")
+                        .with_child(alert.resource.clone()),
+                ),
+        )
+        .with_child(
+            Container::new()
+                .class(TextAlign::Right)
+                .padding_end(2)
+                .with_child(format!(
+                    "{:.1}{unit} / {:.1}{unit}",
+                    alert.value, alert.threshold
+                )),
+        )
+}
+
+/// Create a panel listing the pending and firing alerts.
+pub fn create_alerts_panel(alerts: SharedState<LoadResult<Vec<Alert>, Error>>) -> Panel {
+    let alerts = alerts.read();
+
+    let panel = Panel::new()
+        .title(create_title_with_icon("bell", tr!("Alerts")))
+        .border(true);
+
+    let Some(data) = alerts.data.as_ref() else {
+        return match &alerts.error {
+            Some(err) => panel.with_child(error_message(&err.to_string()).padding(4)),
+            None => panel.with_child(loading_column()),
+        };
+    };
+
+    let mut data: Vec<Alert> = data
+        .iter()
+        .filter(|alert| alert.state != AlertState::Resolved)
+        .cloned()
+        .collect();
+
+    if data.is_empty() {
+        return panel.with_child(
+            Column::new()
+                .padding(4)
+                .class(css::FlexFit)
+                .class(css::JustifyContent::Center)
+                .class(css::AlignItems::Center)
+                .gap(2)
+                .with_child(Fa::from(Status::Success).large_4x())
+                .with_child(tr!("No active alerts")),
+        );
+    }
+
+    data.sort_by(|a, b| {
+        b.severity
+            .cmp(&a.severity)
+            .then_with(|| (a.state == AlertState::Pending).cmp(&(b.state == AlertState::Pending)))
+            .then_with(|| a.rule.cmp(&b.rule))
+            .then_with(|| a.resource.cmp(&b.resource))
+    });
+
+    let tiles: Vec<ListTile> = data.iter().map(create_list_tile).collect();
+
+    panel.with_child(
+        List::new(tiles.len() as u64, move |idx: u64| {
+            tiles[idx as usize].clone()
+        })
+        .padding(4)
+        .class(css::Flex::Fill)
+        .grid_template_columns("auto 1fr auto"),
+    )
+}
@@ -77,6 +77,7 @@ pub fn create_gauge_panel(
     let suffix = match remote_type {
         Some(RemoteType::Pve) => " - Virtual Environment",
         Some(RemoteType::Pbs) => " - Backup Server",
+        Some(RemoteType::Pmg) => " - Mail Gateway",
         None => "",
     };
 
@@ -106,6 +107,8 @@ pub fn create_gauge_panel(
                     conf.show_storage
                         .then_some((data.pbs_storage_stats.used, data.pbs_storage_stats.total)),
                 ),
+                // PMG node usage is not aggregated into the status yet.
+                Some(RemoteType::Pmg) => (None, None, None),
                 None => (
                     conf.show_cpu.then_some((
                         data.pve_cpu_stats.used + data.pbs_cpu_stats.used,
@@ -2,6 +2,12 @@ use pwt::css;
 use pwt::prelude::*;
 use pwt::widget::{Column, Fa, Row};
 
+mod alerts_panel;
+pub use alerts_panel::create_alerts_panel;
+
+mod pbs_jobs_panel;
+pub use pbs_jobs_panel::create_pbs_failed_jobs_panel;
+
 mod top_entities;
 pub use top_entities::create_top_entities_panel;
 
@@ -140,7 +140,7 @@ fn map_status(
                 Status::Unknown.into(),
                 tr!("{0} of an unknown number of nodes online", online),
             ),
-            Some(RemoteType::Pbs) => (
+            Some(RemoteType::Pbs) | Some(RemoteType::Pmg) => (
                 Status::Warning.into(),
                 tr!(
                     "One of {0} nodes online" | "{n} of {0} nodes online" % *online,
@@ -164,6 +164,7 @@ pub fn create_node_panel(
     let (icon, title) = match remote_type {
         Some(RemoteType::Pve) => ("building", tr!("Virtual Environment Nodes")),
         Some(RemoteType::Pbs) => ("building-o", tr!("Backup Server Nodes")),
+        Some(RemoteType::Pmg) => ("envelope-o", tr!("Mail Gateway Nodes")),
         None => ("building", tr!("Nodes")),
     };
 
@@ -174,10 +175,17 @@ pub fn create_node_panel(
             let nodes_status = match remote_type {
                 Some(RemoteType::Pve) => Some(status.pve_nodes.clone()),
                 Some(RemoteType::Pbs) => Some(status.pbs_nodes.clone()),
+                Some(RemoteType::Pmg) => Some(status.pmg_nodes.clone()),
                 None => Some(NodeStatusCount {
-                    online: status.pve_nodes.online + status.pbs_nodes.online,
-                    offline: status.pve_nodes.offline + status.pbs_nodes.offline,
-                    unknown: status.pve_nodes.unknown + status.pbs_nodes.unknown,
+                    online: status.pve_nodes.online
+                        + status.pbs_nodes.online
+                        + status.pmg_nodes.online,
+                    offline: status.pve_nodes.offline
+                        + status.pbs_nodes.offline
+                        + status.pmg_nodes.offline,
+                    unknown: status.pve_nodes.unknown
+                        + status.pbs_nodes.unknown
+                        + status.pmg_nodes.unknown,
                 }),
             };
             let failed_remotes = status
@@ -0,0 +1,149 @@
+use std::rc::Rc;
+
+use anyhow::Error;
+
+use pdm_api_types::RemoteUpid;
+use pdm_api_types::pbs::{PbsJobList, PbsJobStatus, PbsJobType};
+use proxmox_yew_comp::{Status, TaskViewer};
+use pwt::css::{self, TextAlign};
+use pwt::prelude::*;
+use pwt::state::SharedState;
+use pwt::widget::{Button, Column, Container, Fa, List, ListTile, Panel, Tooltip, error_message};
+
+use crate::LoadResult;
+use crate::dashboard::create_title_with_icon;
+
+use super::loading_column;
+
+fn job_type_text(job_type: PbsJobType) -> String {
+    match job_type {
+        PbsJobType::GarbageCollection => tr!("Garbage Collection"),
+        PbsJobType::Prune => tr!("Prune Job"),
+        PbsJobType::Sync => tr!("Sync Job"),
+        PbsJobType::Verify => tr!("Verify Job"),
+    }
+}
+
+#[derive(Properties, Clone, PartialEq)]
+struct FailedJobListProps {
+    jobs: Rc<Vec<PbsJobStatus>>,
+}
+
+#[function_component]
+fn FailedJobList(props: &FailedJobListProps) -> Html {
+    let task = use_state(|| None::<(RemoteUpid, Option<i64>)>);
+
+    let tiles: Vec<ListTile> = props
+        .jobs
+        .iter()
+        .map(|job| {
+            let title = match job.job_type {
+                PbsJobType::GarbageCollection => job_type_text(job.job_type),
+                job_type => format!("{} {}", job_type_text(job_type), job.id),
+            };
+
+            let task_button = job.last_run_upid.clone().map(|upid| {
+                let task = task.clone();
+                let endtime = job.last_run_endtime;
+                Tooltip::new(
+                    Button::new_icon("fa fa-list-alt")
+                        .on_activate(move |_| task.set(Some((upid.clone(), endtime)))),
+                )
+                .tip(tr!("Show Task"))
+            });
+
+            ListTile::new()
+                .with_child(Fa::from(Status::Error))
+                .with_child(
+                    Column::new()
+                        .padding_x(2)
+                        .with_child(Container::new().with_child(title))
+                        .with_child(
+                            Container::new()
+                                .class("pwt-font-label-small")
+                                .with_child(format!("{}: {}", job.remote, job.store)),
+                        )
+                        .with_child(
+                            Container::new()
+                                .class("pwt-font-label-small")
+                                .with_child(job.last_run_state.clone().unwrap_or_default()),
+                        ),
+                )
+                .with_child(
+                    Container::new()
+                        .class(TextAlign::Right)
+                        .padding_end(2)
+                        .with_optional_child(task_button),
+                )
+        })
+        .collect();
+
+    let viewer = task.as_ref().map(|(upid, endtime)| {
+        let base_url = format!("/{}/remotes/{}/tasks", upid.remote_type(), upid.remote());
+        TaskViewer::new(upid.to_string())
+            .endtime(endtime)
+            .base_url(base_url)
+            .on_close({
+                let task = task.clone();
+                move |_| task.set(None)
+            })
+    });
+
+    Column::new()
+        .class(css::Flex::Fill)
+        .with_child(
+            List::new(tiles.len() as u64, move |idx: u64| {
+                tiles[idx as usize].clone()
+            })
+            .padding(4)
+            .class(css::Flex::Fill)
+            .grid_template_columns("auto 1fr auto"),
+        )
+        .with_optional_child(viewer)
+        .into()
+}
+
+/// Create a panel listing the PBS jobs whose last run failed.
+pub fn create_pbs_failed_jobs_panel(jobs: SharedState<LoadResult<PbsJobList, Error>>) -> Panel {
+    let jobs = jobs.read();
+
+    let panel = Panel::new()
+        .title(create_title_with_icon("tasks", tr!("Failed PBS Jobs")))
+        .border(true);
+
+    let Some(data) = jobs.data.as_ref() else {
+        return match &jobs.error {
+            Some(err) => panel.with_child(error_message(&err.to_string()).padding(4)),
+            None => panel.with_child(loading_column()),
+        };
+    };
+
+    let mut failed: Vec<PbsJobStatus> = data
+        .jobs
+        .iter()
+        .filter(|job| job.is_failed())
+        .cloned()
+        .collect();
+
+    if failed.is_empty() {
+        return panel.with_child(
+            Column::new()
+                .padding(4)
+                .class(css::FlexFit)
+                .class(css::JustifyContent::Center)
+                .class(css::AlignItems::Center)
+                .gap(2)
+                .with_child(Fa::from(Status::Success).large_4x())
+                .with_child(tr!("No failed jobs")),
+        );
+    }
+
+    failed.sort_by(|a, b| {
+        b.last_run_endtime
+            .cmp(&a.last_run_endtime)
+            .then_with(|| a.remote.cmp(&b.remote))
+            .then_with(|| a.id.cmp(&b.id))
+    });
+
+    panel.with_child(html! { <FailedJobList jobs={Rc::new(failed)} /> })
+}
@@ -272,6 +272,21 @@ fn create_tooltip(
                 .with_child(Container::from_tag("span").with_child(tr!("Time")))
                 .with_child(render_epoch(info.time)),
         )
+        .with_optional_child(estimated_full(resource).map(|full| {
+            Row::new()
+                .class(JustifyContent::SpaceBetween)
+                .gap(2)
+                .with_child(Container::from_tag("span").with_child(tr!("Estimated Full")))
+                .with_child(render_epoch(full))
+        }))
+}
+
+fn estimated_full(resource: &Resource) -> Option<i64> {
+    match resource {
+        Resource::PveStorage(storage) => storage.estimated_full,
+        Resource::PbsDatastore(datastore) => datastore.estimated_full,
+        _ => None,
+    }
 }
 
 const GOOD_COLOR: &str = "var(--pwt-color-success)";
@@ -363,6 +378,14 @@ pub fn create_top_entities_panel(
                 tr!("No nodes available"),
                 0.95,
             ),
+            LeaderboardType::SoonestFull => (
+                top_entities.data.as_ref().map(|e| e.soonest_full.clone()),
+                "database",
+                tr!("Storages Soonest to Be Full"),
+                tr!("Disk usage"),
+                tr!("No storage is estimated to be full"),
+                0.85,
+            ),
         };
     Panel::new()
         .title(create_title_with_icon(icon, title))
@@ -25,16 +25,18 @@ use crate::dashboard::refresh_config_edit::{
 use crate::dashboard::subscription_info::create_subscriptions_dialog;
 use crate::dashboard::tasks::get_task_options;
 use crate::dashboard::{
-    DashboardStatusRow, create_gauge_panel, create_guest_panel, create_map_panel,
-    create_node_panel, create_pbs_datastores_panel, create_refresh_config_edit_window,
-    create_remote_panel, create_resource_tree, create_sdn_panel, create_subscription_panel,
-    create_task_summary_panel, create_top_entities_panel,
+    DashboardStatusRow, create_alerts_panel, create_gauge_panel, create_guest_panel,
+    create_map_panel, create_node_panel, create_pbs_datastores_panel, create_pbs_failed_jobs_panel,
+    create_refresh_config_edit_window, create_remote_panel, create_resource_tree, create_sdn_panel,
+    create_subscription_panel, create_task_summary_panel, create_top_entities_panel,
 };
 use crate::remotes::AddWizard;
 use crate::renderer::empty_state;
 use crate::widget::RedrawController;
 use crate::{LoadResult, RemoteList, pdm_client};
 
+use pdm_api_types::alerts::Alert;
+use pdm_api_types::pbs::PbsJobList;
 use pdm_api_types::remotes::RemoteType;
 use pdm_api_types::resource::ResourcesStatus;
 use pdm_api_types::subscription::RemoteSubscriptions;
@@ -87,6 +89,8 @@ pub enum LoadingResult {
     TaskStatistics(Result<TaskStatistics, Error>),
     SubscriptionInfo(Result<Vec<RemoteSubscriptions>, Error>),
     Locations(Result<HashMap<String, CachedLocationInfo>, Error>),
+    Alerts(Result<Vec<Alert>, Error>),
+    PbsJobs(Result<PbsJobList, Error>),
     All,
 }
 
@@ -131,6 +135,8 @@ struct WidgetRenderArgs {
     top_entities: SharedState<LoadResult<TopEntities, proxmox_client::Error>>,
     statistics: SharedState<LoadResult<TaskStatistics, Error>>,
     locations: SharedState<LoadResult<HashMap<String, CachedLocationInfo>, Error>>,
+    alerts: SharedState<LoadResult<Vec<Alert>, Error>>,
+    pbs_jobs: SharedState<LoadResult<PbsJobList, Error>>,
     redraw_controller: RedrawController,
 }
 
@@ -146,6 +152,8 @@ fn render_widget(
         top_entities,
         statistics,
         locations,
+        alerts,
+        pbs_jobs,
         redraw_controller,
     } = render_args;
 
@@ -183,6 +191,8 @@ fn render_widget(
             remote_type,
         } => create_gauge_panel(*resource, *remote_type, status),
         WidgetType::Map => create_map_panel(status, locations),
+        WidgetType::Alerts => create_alerts_panel(alerts),
+        WidgetType::PbsFailedJobs => create_pbs_failed_jobs_panel(pbs_jobs),
         WidgetType::UnknownWidget { widget_type, .. } => create_unknown_widget_panel(widget_type),
     };
 
@@ -275,12 +285,32 @@ impl ViewComp {
                     }
                 };
 
+                let alerts_future = async {
+                    if required.alerts {
+                        let mut params = json!({});
+                        add_view_filter(&mut params);
+                        let res = http_get("/alerts", Some(params)).await;
+                        link.send_message(Msg::LoadingResult(LoadingResult::Alerts(res)));
+                    }
+                };
+
+                let pbs_jobs_future = async {
+                    if required.pbs_jobs {
+                        let mut params = json!({});
+                        add_view_filter(&mut params);
+                        let res = http_get("/pbs/jobs", Some(params)).await;
+                        link.send_message(Msg::LoadingResult(LoadingResult::PbsJobs(res)));
+                    }
+                };
+
                 join!(
                     status_future,
                     entities_future,
                     tasks_future,
                     subs_future,
-                    location_future
+                    location_future,
+                    alerts_future,
+                    pbs_jobs_future
                 );
                 link.send_message(Msg::LoadingResult(LoadingResult::All));
             });
@@ -297,6 +327,8 @@ struct RequiredApiCalls {
     top_entities: bool,
     task_statistics: bool,
     locations: bool,
+    alerts: bool,
+    pbs_jobs: bool,
 }
 
 fn required_api_calls(layout: &ViewLayout) -> RequiredApiCalls {
@@ -326,6 +358,8 @@ fn required_api_calls(layout: &ViewLayout) -> RequiredApiCalls {
                             api_calls.status = true;
                             api_calls.locations = true;
                         }
+                        WidgetType::Alerts => api_calls.alerts = true,
+                        WidgetType::PbsFailedJobs => api_calls.pbs_jobs = true,
                         WidgetType::UnknownWidget { .. } => {}
                     }
                 }
@@ -388,6 +422,8 @@ impl Component for ViewComp {
                 statistics: SharedState::new(LoadResult::new()),
                 subscriptions: SharedState::new(LoadResult::new()),
                 locations: SharedState::new(LoadResult::new()),
+                alerts: SharedState::new(LoadResult::new()),
+                pbs_jobs: SharedState::new(LoadResult::new()),
                 redraw_controller: RedrawController::new(),
             },
         }
@@ -413,6 +449,12 @@ impl Component for ViewComp {
                 LoadingResult::Locations(locations) => {
                     self.render_args.locations.write().update(locations);
                 }
+                LoadingResult::Alerts(alerts) => {
+                    self.render_args.alerts.write().update(alerts);
+                }
+                LoadingResult::PbsJobs(jobs) => {
+                    self.render_args.pbs_jobs.write().update(jobs);
+                }
                 LoadingResult::All => {
                     self.loading = false;
                     if self.load_finished_time.is_none() {
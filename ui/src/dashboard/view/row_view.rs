@@ -649,7 +649,12 @@ fn create_menu(ctx: &yew::Context<RowViewComp>, new_coords: Position) -> Menu {
                                 leaderboard_type: LeaderboardType::NodeMemory,
                             }),
                         ),
-                    ),
+                    )
+                    .with_item(MenuItem::new(tr!("Storages Soonest to Be Full")).on_select(
+                        create_callback(WidgetType::Leaderboard {
+                            leaderboard_type: LeaderboardType::SoonestFull,
+                        }),
+                    )),
             ),
         )
         .with_item(
@@ -671,6 +676,11 @@ fn create_menu(ctx: &yew::Context<RowViewComp>, new_coords: Position) -> Menu {
         )
         .with_item(MenuItem::new(tr!("SDN Panel")).on_select(create_callback(WidgetType::Sdn)))
         .with_item(MenuItem::new(tr!("Map")).on_select(create_callback(WidgetType::Map)))
+        .with_item(MenuItem::new(tr!("Alerts")).on_select(create_callback(WidgetType::Alerts)))
+        .with_item(
+            MenuItem::new(tr!("Failed PBS Jobs"))
+                .on_select(create_callback(WidgetType::PbsFailedJobs)),
+        )
         .with_item(
             MenuItem::new(tr!("Resource Tree"))
                 .on_select(create_callback(WidgetType::ResourceTree)),
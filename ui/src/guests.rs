@@ -599,7 +599,7 @@ impl LoadableComponent for GuestPanelComp {
             // resource-types would let us narrow to guests server-side (the
             // search is left empty, so failed remotes are still returned).
             // `None` lets the server apply its default cache max-age.
-            let remotes = crate::pdm_client().resources(None, None).await?;
+            let remotes = crate::pdm_client().resources(None, None, None).await?;
             link.send_message(Msg::LoadFinished(remotes));
             Ok(())
         })
@@ -126,6 +126,7 @@ pub(crate) fn get_deep_url<C: yew::Component>(
         ("", _) => String::new(),
         (id, pdm_api_types::remotes::RemoteType::Pve) => format!("v1::={id}"),
         (id, pdm_api_types::remotes::RemoteType::Pbs) => format!("DataStore-{id}"),
+        (_, pdm_api_types::remotes::RemoteType::Pmg) => String::new(),
     };
     get_deep_url_low_level(link, remote, _node, &hash)
 }
@@ -172,6 +173,7 @@ pub(crate) fn get_deep_url_low_level<C: yew::Component>(
                     let default_port = match remote.ty {
                         pdm_api_types::remotes::RemoteType::Pve => "8006",
                         pdm_api_types::remotes::RemoteType::Pbs => "8007",
+                        pdm_api_types::remotes::RemoteType::Pmg => "8006",
                     };
                     url.set_port(default_port);
                 }
@@ -230,6 +232,7 @@ pub(crate) fn get_resource_node(resource: &Resource) -> Option<&str> {
         Resource::PveNetwork(network) => Some(network.node()),
         Resource::PbsNode(_) => None,
         Resource::PbsDatastore(_) => None,
+        Resource::PmgNode(node) => Some(&node.node),
     }
 }
 
@@ -383,6 +383,13 @@ impl Component for PdmMainMenu {
                     move |_| match remote.ty {
                         RemoteType::Pve => crate::pve::PveRemote::new(remote.id.clone()).into(),
                         RemoteType::Pbs => crate::pbs::PbsRemote::new(remote.id.clone()).into(),
+                        // FIXME: add a dedicated PMG panel
+                        RemoteType::Pmg => Container::new()
+                            .padding(2)
+                            .with_child(tr!(
+                                "Use the web interface of the Proxmox Mail Gateway to manage it."
+                            ))
+                            .into(),
                     }
                 },
             );
@@ -27,7 +27,11 @@ use pdm_api_types::{
     resource::{PveLxcResource, PveNodeResource, PveQemuResource, PveResource, PveStorageResource},
 };
 
-use crate::{get_deep_url, renderer::render_tree_column, widget::MigrateWindow};
+use crate::{
+    get_deep_url,
+    renderer::render_tree_column,
+    widget::{DeployWindow, EvacuateWindow, MigrateWindow},
+};
 
 use super::{
     GuestInfo, GuestType,
@@ -130,6 +134,10 @@ pub enum ViewState {
     /// Open the migration dialog for the given guest, carrying its current node so the
     /// target-node selector can grey out (and reject) that entry.
     MigrateWindow(GuestInfo, String),
+    /// Open the deploy dialog for the given template, carrying its node as the default target.
+    DeployWindow(GuestInfo, String),
+    /// Open the dialog to review and run the evacuation plan of the given node.
+    EvacuateWindow(String),
 }
 
 pub enum Msg {
@@ -137,6 +145,8 @@ pub enum Msg {
     GuestAction(Action, String), //ID
     KeySelected(Option<Key>),
     RouteChanged(String),
+    /// Show the progress of a task (UPID) with the given task base URL.
+    ShowTask(String, AttrValue),
 }
 
 pub struct PveTreeComp {
@@ -362,7 +372,12 @@ impl LoadableComponent for PveTreeComp {
                         };
 
                         match res {
-                            Ok(upid) => link.show_task_progress(upid.to_string()),
+                            Ok(upid) => {
+                                link.send_message(Msg::ShowTask(
+                                    upid.to_string(),
+                                    get_base_url(upid.remote()),
+                                ));
+                            }
                             Err(err) => link.show_error(tr!("Error"), err.to_string(), true),
                         }
                     }),
@@ -386,13 +401,24 @@ impl LoadableComponent for PveTreeComp {
                         };
 
                         match res {
-                            Ok(upid) => link.show_task_progress(upid.to_string()),
+                            Ok(upid) => {
+                                link.send_message(Msg::ShowTask(
+                                    upid.to_string(),
+                                    get_base_url(upid.remote()),
+                                ));
+                            }
                             Err(err) => link.show_error(tr!("Error"), err.to_string(), true),
                         }
                     }),
                     _ => {}
                 }
             }
+            Msg::ShowTask(upid, base_url) => {
+                // remote guest actions and local worker tasks (e.g. deploy) use different task
+                // URLs, so set it for every task
+                self.state.set_task_base_url(base_url);
+                ctx.link().show_task_progress(upid);
+            }
             Msg::KeySelected(key) => {
                 let key = key.unwrap_or_else(|| Key::from("__root__"));
                 let store = self.store.read();
@@ -566,10 +592,58 @@ impl LoadableComponent for PveTreeComp {
                     .on_close(ctx.link().change_view_callback(|_| None))
                     .on_submit({
                         let link = ctx.link().clone();
-                        move |upid: RemoteUpid| link.show_task_progress(upid.to_string())
+                        move |upid: RemoteUpid| {
+                            link.send_message(Msg::ShowTask(
+                                upid.to_string(),
+                                get_base_url(upid.remote()),
+                            ))
+                        }
+                    })
+                    .into(),
+            ),
+            ViewState::DeployWindow(guest_info, node) => Some(
+                DeployWindow::new(props.remote.clone(), *guest_info)
+                    .node(AttrValue::from(node.clone()))
+                    .on_close(ctx.link().change_view_callback(|_| None))
+                    .on_submit({
+                        let link = ctx.link().clone();
+                        move |upid: String| {
+                            link.send_message(Msg::ShowTask(upid, "/nodes/localhost/tasks".into()))
+                        }
                     })
                     .into(),
             ),
+            ViewState::EvacuateWindow(node) => {
+                let guests = props
+                    .resources
+                    .iter()
+                    .filter_map(|resource| match resource {
+                        PveResource::Qemu(r) if &r.node == node => Some((
+                            GuestInfo::new(GuestType::Qemu, r.vmid),
+                            render_qemu_name(r, true),
+                        )),
+                        PveResource::Lxc(r) if &r.node == node => Some((
+                            GuestInfo::new(GuestType::Lxc, r.vmid),
+                            render_lxc_name(r, true),
+                        )),
+                        _ => None,
+                    })
+                    .collect();
+                Some(
+                    EvacuateWindow::new(props.remote.clone(), node.clone(), Rc::new(guests))
+                        .on_close(ctx.link().change_view_callback(|_| None))
+                        .on_submit({
+                            let link = ctx.link().clone();
+                            move |upid: String| {
+                                link.send_message(Msg::ShowTask(
+                                    upid,
+                                    "/nodes/localhost/tasks".into(),
+                                ))
+                            }
+                        })
+                        .into(),
+                )
+            }
         }
     }
 
@@ -739,6 +813,48 @@ fn columns(
                         .tip(label);
                         Some(icon)
                     }))
+                    .with_optional_child(match entry {
+                        PveTreeNode::Node(r) => Some(
+                            Tooltip::new(
+                                ActionIcon::new("fa fa-fw fa-sign-out")
+                                    .aria_label(tr!("Evacuate"))
+                                    .disabled(r.status != "online")
+                                    .on_activate({
+                                        let link = link.clone();
+                                        let node = r.node.clone();
+                                        move |_| {
+                                            link.change_view(Some(ViewState::EvacuateWindow(
+                                                node.clone(),
+                                            )))
+                                        }
+                                    }),
+                            )
+                            .tip(tr!("Evacuate")),
+                        ),
+                        _ => None,
+                    })
+                    .with_optional_child(guest_info.and_then(|(guest_info, _, template)| {
+                        if !template {
+                            return None;
+                        }
+                        let node = node.clone()?;
+                        Some(
+                            Tooltip::new(
+                                ActionIcon::new("fa fa-fw fa-clone")
+                                    .aria_label(tr!("Deploy"))
+                                    .on_activate({
+                                        let link = link.clone();
+                                        move |_| {
+                                            link.change_view(Some(ViewState::DeployWindow(
+                                                guest_info,
+                                                node.clone(),
+                                            )))
+                                        }
+                                    }),
+                            )
+                            .tip(tr!("Deploy")),
+                        )
+                    }))
                     .with_optional_child(guest_info.and_then(|(guest_info, _, _)| {
                         let source_node = node.clone()?;
                         Some(
@@ -0,0 +1,231 @@
+use std::future::Future;
+use std::pin::Pin;
+use std::rc::Rc;
+
+use anyhow::Error;
+use yew::virtual_dom::{Key, VComp, VNode};
+
+use proxmox_yew_comp::{
+    LoadableComponent, LoadableComponentContext, LoadableComponentMaster,
+    LoadableComponentScopeExt, LoadableComponentState,
+};
+use pwt::prelude::*;
+use pwt::state::{Selection, Store};
+use pwt::widget::data_table::{DataTable, DataTableColumn, DataTableHeader};
+use pwt::widget::{Button, ConfirmDialog, Toolbar};
+
+use pdm_api_types::RemoteUpid;
+use pdm_client::types::{BalancingNodeLoad, BalancingRecommendation};
+
+fn recommendation_key(recommendation: &BalancingRecommendation) -> Key {
+    Key::from(format!("{}/{}", recommendation.remote, recommendation.vmid))
+}
+
+fn render_load(load: &BalancingNodeLoad) -> String {
+    format!(
+        "{} ({}: {:.0}% → {:.0}%, {}: {:.0}% → {:.0}%)",
+        load.node,
+        tr!("CPU"),
+        load.cpu * 100.0,
+        load.projected_cpu * 100.0,
+        tr!("Memory"),
+        load.mem * 100.0,
+        load.projected_mem * 100.0,
+    )
+}
+
+#[derive(PartialEq, Clone, Properties)]
+pub struct BalancingPanel {}
+
+impl BalancingPanel {
+    pub fn new() -> Self {
+        yew::props!(Self {})
+    }
+}
+
+impl Default for BalancingPanel {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl From<BalancingPanel> for VNode {
+    fn from(val: BalancingPanel) -> Self {
+        VComp::new::<LoadableComponentMaster<BalancingPanelComp>>(Rc::new(val), None).into()
+    }
+}
+
+pub enum Msg {
+    LoadFinished(Vec<BalancingRecommendation>),
+    Apply(Key),
+    ShowTask(RemoteUpid),
+}
+
+#[derive(PartialEq)]
+pub enum ViewState {
+    Apply,
+}
+
+#[doc(hidden)]
+pub struct BalancingPanelComp {
+    state: LoadableComponentState<ViewState>,
+    store: Store<BalancingRecommendation>,
+    columns: Rc<Vec<DataTableHeader<BalancingRecommendation>>>,
+    selection: Selection,
+}
+
+pwt::impl_deref_mut_property!(BalancingPanelComp, state, LoadableComponentState<ViewState>);
+
+impl BalancingPanelComp {
+    fn columns() -> Rc<Vec<DataTableHeader<BalancingRecommendation>>> {
+        Rc::new(vec![
+            DataTableColumn::new(tr!("Remote"))
+                .flex(1)
+                .get_property(|value: &BalancingRecommendation| value.remote.as_str())
+                .into(),
+            DataTableColumn::new(tr!("Guest"))
+                .flex(1)
+                .render(|value: &BalancingRecommendation| {
+                    format!("{} ({})", value.vmid, value.name).into()
+                })
+                .into(),
+            DataTableColumn::new(tr!("Source"))
+                .flex(2)
+                .render(|value: &BalancingRecommendation| render_load(&value.source).into())
+                .into(),
+            DataTableColumn::new(tr!("Target"))
+                .flex(2)
+                .render(|value: &BalancingRecommendation| {
+                    let target = render_load(&value.target);
+                    if value.is_remote_migration() {
+                        format!("{}: {target}", value.target_remote).into()
+                    } else {
+                        target.into()
+                    }
+                })
+                .into(),
+            DataTableColumn::new(tr!("Reason"))
+                .flex(2)
+                .get_property(|value: &BalancingRecommendation| value.reason.as_str())
+                .into(),
+        ])
+    }
+}
+
+impl LoadableComponent for BalancingPanelComp {
+    type Properties = BalancingPanel;
+    type Message = Msg;
+    type ViewState = ViewState;
+
+    fn create(ctx: &LoadableComponentContext<Self>) -> Self {
+        let selection = Selection::new().on_select({
+            let link = ctx.link().clone();
+            move |_| link.send_redraw()
+        });
+        Self {
+            state: LoadableComponentState::new(),
+            store: Store::with_extract_key(recommendation_key),
+            columns: Self::columns(),
+            selection,
+        }
+    }
+
+    fn update(&mut self, ctx: &LoadableComponentContext<Self>, msg: Self::Message) -> bool {
+        match msg {
+            Msg::LoadFinished(data) => self.store.set_data(data),
+            Msg::Apply(key) => {
+                let Some(recommendation) = self.store.read().lookup_record(&key).cloned() else {
+                    return false;
+                };
+                let link = ctx.link().clone();
+                ctx.link().spawn(async move {
+                    match crate::pdm_client()
+                        .pve_apply_balancing_recommendation(&recommendation)
+                        .await
+                    {
+                        Ok(upid) => link.send_message(Msg::ShowTask(upid)),
+                        Err(err) => link.show_error(tr!("Error"), err.to_string(), true),
+                    }
+                });
+            }
+            Msg::ShowTask(upid) => {
+                // the migration task runs on the remote of the guest
+                self.set_task_base_url(format!("/pve/remotes/{}/tasks", upid.remote()).into());
+                ctx.link().show_task_progress(upid.to_string());
+            }
+        }
+        true
+    }
+
+    fn toolbar(&self, ctx: &LoadableComponentContext<Self>) -> Option<Html> {
+        let selection = self.selection.selected_key();
+        let link = ctx.link();
+        Some(
+            Toolbar::new()
+                .border_bottom(true)
+                .with_child(
+                    Button::new(tr!("Apply"))
+                        .disabled(selection.is_none())
+                        .on_activate(link.change_view_callback(|_| Some(ViewState::Apply))),
+                )
+                .with_flex_spacer()
+                .with_child(Button::refresh(self.loading()).onclick({
+                    let link = link.clone();
+                    move |_| link.send_reload()
+                }))
+                .into(),
+        )
+    }
+
+    fn load(
+        &self,
+        ctx: &LoadableComponentContext<Self>,
+    ) -> Pin<Box<dyn Future<Output = Result<(), Error>>>> {
+        let link = ctx.link().clone();
+        Box::pin(async move {
+            let data = crate::pdm_client()
+                .pve_balancing_recommendations(None, None)
+                .await?;
+            link.send_message(Msg::LoadFinished(data));
+            Ok(())
+        })
+    }
+
+    fn main_view(&self, ctx: &LoadableComponentContext<Self>) -> Html {
+        let link = ctx.link().clone();
+        DataTable::new(self.columns.clone(), self.store.clone())
+            .on_row_dblclick(move |_: &mut _| link.change_view(Some(ViewState::Apply)))
+            .selection(self.selection.clone())
+            .into()
+    }
+
+    fn dialog_view(
+        &self,
+        ctx: &LoadableComponentContext<Self>,
+        view_state: &Self::ViewState,
+    ) -> Option<Html> {
+        match view_state {
+            ViewState::Apply => {
+                let key = self.selection.selected_key()?;
+                let store = self.store.read();
+                let recommendation = store.lookup_record(&key)?;
+                Some(
+                    ConfirmDialog::new(
+                        tr!("Confirm"),
+                        tr!(
+                            "Migrate guest {0} from '{1}' to '{2}'?",
+                            recommendation.vmid,
+                            recommendation.source.node,
+                            recommendation.target.node
+                        ),
+                    )
+                    .on_confirm({
+                        let link = ctx.link().clone();
+                        move |_| link.send_message(Msg::Apply(key.clone()))
+                    })
+                    .into(),
+                )
+            }
+        }
+    }
+}
@@ -11,9 +11,10 @@ use crate::remotes::edit_remote::EditRemote;
 use crate::remotes::remove_remote::RemoveRemote;
 //use pwt::widget::form::{Field, FormContext, InputType};
 
-use pdm_api_types::remotes::Remote;
+use pdm_api_types::remotes::{Remote, RemoteTfaChallenge};
 //use proxmox_schema::{property_string::PropertyString, ApiType};
 use proxmox_yew_comp::percent_encoding::percent_encode_component;
+use proxmox_yew_comp::utils::render_epoch;
 
 //use pbs_api_types::CERT_FINGERPRINT_SHA256_SCHEMA;
 
@@ -65,6 +66,7 @@ pub async fn create_remote(mut data: Value, remote_type: RemoteType) -> Result<(
     data["type"] = match remote_type {
         RemoteType::Pve => "pve",
         RemoteType::Pbs => "pbs",
+        RemoteType::Pmg => "pmg",
     }
     .into();
 
@@ -75,7 +77,15 @@ pub async fn create_remote(mut data: Value, remote_type: RemoteType) -> Result<(
         params["create-token"] = token.into();
     }
 
-    proxmox_yew_comp::http_post("/remotes/remote", Some(params)).await
+    let tfa_challenge: Option<RemoteTfaChallenge> =
+        proxmox_yew_comp::http_post("/remotes/remote", Some(params)).await?;
+    if tfa_challenge.is_some() {
+        // the login of the scan is only reused for a while
+        anyhow::bail!(tr!(
+            "The login to the remote expired, please go back and connect again."
+        ));
+    }
+    Ok(())
 }
 
 /*
@@ -202,6 +212,13 @@ impl LoadableComponent for PbsRemoteConfigPanel {
                                 .on_select(link.change_view_callback(|_| {
                                     Some(ViewState::Add(RemoteType::Pbs))
                                 })),
+                        )
+                        .with_item(
+                            MenuItem::new("Proxmox Mail Gateway")
+                                .icon_class("fa fa-envelope-o")
+                                .on_select(link.change_view_callback(|_| {
+                                    Some(ViewState::Add(RemoteType::Pmg))
+                                })),
                         ),
                 )
             })
@@ -369,6 +386,15 @@ fn remote_list_columns() -> Rc<Vec<DataTableHeader<Remote>>> {
             })
             .sorter(|a: &Remote, b: &Remote| a.ty.cmp(&b.ty))
             .into(),
+        DataTableColumn::new(tr!("Folder"))
+            .width("150px")
+            .render(|item: &Remote| {
+                html! {
+                    item.folder.as_deref().unwrap_or("-")
+                }
+            })
+            .sorter(|a: &Remote, b: &Remote| a.folder.cmp(&b.folder))
+            .into(),
         DataTableColumn::new(tr!("AuthId"))
             .width("200px")
             .render(|item: &Remote| {
@@ -378,6 +404,24 @@ fn remote_list_columns() -> Rc<Vec<DataTableHeader<Remote>>> {
             })
             .sorter(|a: &Remote, b: &Remote| a.authid.cmp(&b.authid))
             .into(),
+        DataTableColumn::new(tr!("Token Age"))
+            .width("100px")
+            .render(|item: &Remote| {
+                match (
+                    item.token_age(proxmox_time::epoch_i64()),
+                    item.token_created,
+                ) {
+                    (Some(age), Some(created)) => {
+                        let days = age / (24 * 60 * 60);
+                        Tooltip::new(tr!("{0} days", days))
+                            .tip(tr!("Created: {0}", render_epoch(created)))
+                            .into()
+                    }
+                    _ => html! {"-"},
+                }
+            })
+            .sorter(|a: &Remote, b: &Remote| b.token_created.cmp(&a.token_created))
+            .into(),
         DataTableColumn::new(tr!("Nodes"))
             .flex(1)
             .render(|item: &Remote| {
@@ -7,7 +7,7 @@ use yew::virtual_dom::{VComp, VNode};
 
 use pwt::css::FlexFit;
 use pwt::prelude::*;
-use pwt::widget::form::{DisplayField, Field, FormContext, InputType};
+use pwt::widget::form::{DisplayField, Field, FormContext, InputType, Number};
 use pwt::widget::{Container, InputPanel};
 
 use proxmox_yew_comp::form::delete_empty_values;
@@ -78,7 +78,17 @@ impl Component for PdmEditRemote {
                     async move {
                         let data = form_ctx.get_submit_data();
 
-                        let data = delete_empty_values(&data, &["web-url"], true);
+                        let data = delete_empty_values(
+                            &data,
+                            &[
+                                "web-url",
+                                "folder",
+                                "timeout",
+                                "http-proxy",
+                                "max-connections",
+                            ],
+                            true,
+                        );
 
                         proxmox_yew_comp::http_put(&url, Some(data)).await
                     }
@@ -120,6 +130,36 @@ fn edit_remote_input_panel(_form_ctx: &FormContext, remote_id: &str) -> Html {
                 .name("web-url")
                 .placeholder(tr!("Use first endpoint.")),
         )
+        .with_field(
+            tr!("Folder"),
+            Field::new()
+                .name("folder")
+                .schema(&pdm_api_types::remotes::REMOTE_FOLDER_SCHEMA)
+                .placeholder(tr!("None")),
+        )
+        .with_field(
+            tr!("Timeout (seconds)"),
+            Number::<u64>::new()
+                .name("timeout")
+                .min(1)
+                .max(3600)
+                .placeholder("60"),
+        )
+        .with_field(
+            tr!("Proxy"),
+            Field::new()
+                .name("http-proxy")
+                .schema(&pdm_api_types::REMOTE_PROXY_SCHEMA)
+                .placeholder(tr!("None")),
+        )
+        .with_field(
+            tr!("Max. Connections"),
+            Number::<u64>::new()
+                .name("max-connections")
+                .min(1)
+                .max(64)
+                .placeholder(tr!("Default")),
+        )
         .with_custom_child(
             Container::new()
                 .key("nodes-title")
@@ -35,6 +35,9 @@ mod remove_remote;
 mod firewall;
 pub use firewall::FirewallTree;
 
+mod balancing;
+pub use balancing::BalancingPanel;
+
 mod auto_installer;
 use auto_installer::AutoInstallerPanel;
 
@@ -82,6 +85,13 @@ pub fn system_configuration() -> Html {
                 .icon_class("fa fa-shield"),
             |_| FirewallTree::new().into(),
         )
+        .with_item_builder(
+            TabBarItem::new()
+                .key("balancing")
+                .label(tr!("Load Balancing"))
+                .icon_class("fa fa-balance-scale"),
+            |_| BalancingPanel::new().into(),
+        )
         .with_item_builder(
             TabBarItem::new()
                 .key("auto-installer")
@@ -562,12 +562,21 @@ impl UpdateTreeComponent {
                                         let _ = gloo_utils::window().open_with_url(&url.href());
                                     }
                                 }
+                                RemoteType::Pmg => {
+                                    let hash = "#pmgServerAdministration:updates";
+                                    if let Some(url) =
+                                        get_deep_url_low_level(&link, &remote, None, hash)
+                                    {
+                                        let _ = gloo_utils::window().open_with_url(&url.href());
+                                    }
+                                }
                             }
                         });
 
                     let product = match ty {
                         RemoteType::Pve => ExistingProduct::PVE,
                         RemoteType::Pbs => ExistingProduct::PBS,
+                        RemoteType::Pmg => ExistingProduct::PMG,
                     };
 
                     let repo_status = Container::new().min_height(150).with_child(
@@ -839,6 +848,7 @@ fn get_product_version(node_entry: &NodeEntry) -> Option<String> {
     let package = match node_entry.ty {
         RemoteType::Pve => "pve-manager",
         RemoteType::Pbs => "proxmox-backup-server",
+        RemoteType::Pmg => "pmg-api",
     };
 
     node_entry
@@ -57,6 +57,10 @@ async fn connect(form_ctx: FormContext, remote_type: RemoteType) -> Result<TlsPr
             .pbs_probe_tls(&hostname, fingerprint.as_deref())
             .await
             .map_err(Error::from),
+        RemoteType::Pmg => pdm_client
+            .pmg_probe_tls(&hostname, fingerprint.as_deref())
+            .await
+            .map_err(Error::from),
     }
 }
 
@@ -5,7 +5,9 @@ use html::IntoEventCallback;
 use serde::{Deserialize, Serialize};
 use yew::virtual_dom::{Key, VComp, VNode};
 
+use proxmox_login::TfaChallenge;
 use proxmox_schema::property_string::PropertyString;
+use proxmox_yew_comp::tfa::TfaDialog;
 use proxmox_yew_comp::{SchemaValidation, WizardPageRenderInfo};
 use pwt::{
     AsyncPool,
@@ -17,7 +19,7 @@ use pwt::{
     },
 };
 
-use pdm_api_types::remotes::{NodeUrl, REMOTE_ID_SCHEMA, Remote, RemoteType};
+use pdm_api_types::remotes::{NodeUrl, REMOTE_ID_SCHEMA, Remote, RemoteScanResult, RemoteType};
 
 use pwt_macros::builder;
 
@@ -50,6 +52,8 @@ pub struct PdmWizardPageInfo {
     server_info: Option<Remote>,
     last_error: Option<Error>,
     credentials: Option<(String, String)>,
+    /// The id and challenge of a login waiting for the second factor.
+    tfa_challenge: Option<(String, Rc<TfaChallenge>)>,
     loading: bool,
     _form_observer: FormContextObserver,
     async_pool: AsyncPool,
@@ -59,7 +63,9 @@ pub enum Msg {
     ToggleCreateToken(bool),
     FormChange,
     Connect,
-    ConnectResult(Result<Remote, Error>),
+    ConnectResult(Result<RemoteScanResult, Error>),
+    TfaResponse(String),
+    TfaAbort,
 }
 
 #[derive(Deserialize, Serialize)]
@@ -76,7 +82,8 @@ async fn scan(
     connection_params: ConnectParams,
     form_ctx: FormContext,
     remote_type: RemoteType,
-) -> Result<Remote, Error> {
+    tfa: Option<(String, String)>,
+) -> Result<RemoteScanResult, Error> {
     let mut data = form_ctx.get_submit_data();
 
     data["hostname"] = connection_params.hostname.into();
@@ -91,25 +98,39 @@ async fn scan(
         fingerprint,
     } = serde_json::from_value(data.clone())?;
 
+    let tfa = tfa
+        .as_ref()
+        .map(|(id, response)| (id.as_str(), response.as_str()));
+
     let client = crate::pdm_client();
     let mut result = match remote_type {
         RemoteType::Pve => {
             client
-                .pve_scan_remote(&hostname, fingerprint.as_deref(), &authid, &token)
+                .pve_scan_remote(&hostname, fingerprint.as_deref(), &authid, &token, tfa)
                 .await?
         }
         RemoteType::Pbs => {
             client
-                .pbs_scan_remote(&hostname, fingerprint.as_deref(), &authid, &token)
+                .pbs_scan_remote(&hostname, fingerprint.as_deref(), &authid, &token, tfa)
+                .await?
+        }
+        RemoteType::Pmg => {
+            client
+                .pmg_scan_remote(&hostname, fingerprint.as_deref(), &authid, &token, tfa)
                 .await?
         }
     };
 
+    // the login requires a second factor, the caller has to scan again with the response
+    let Some(remote) = result.remote.as_mut() else {
+        return Ok(result);
+    };
+
     // try to deduplicate the entered info from the first page with the nodelist here
     // either via the hostname or the fingerprint. if none matches the entered info will
     // be an extra entry in the first position
     let mut found_matching_host = false;
-    for node in result.nodes.iter_mut() {
+    for node in remote.nodes.iter_mut() {
         if node.hostname == hostname {
             if fingerprint.is_none() {
                 node.fingerprint = None;
@@ -126,7 +147,7 @@ async fn scan(
         }
     }
     if !found_matching_host {
-        result.nodes.insert(
+        remote.nodes.insert(
             0,
             PropertyString::new(NodeUrl {
                 hostname,
@@ -134,11 +155,31 @@ async fn scan(
             }),
         );
     }
-    result.nodes.sort_by(|a, b| a.hostname.cmp(&b.hostname));
+    remote.nodes.sort_by(|a, b| a.hostname.cmp(&b.hostname));
     Ok(result)
 }
 
 impl PdmWizardPageInfo {
+    fn start_scan(&mut self, ctx: &Context<Self>, tfa: Option<(String, String)>) {
+        let props = ctx.props();
+        let link = ctx.link().clone();
+        let form_ctx = props.info.form_ctx.clone();
+        self.loading = true;
+        self.last_error = None;
+        props.info.page_lock(true);
+
+        if let Some(connection_info) = props.connect_info.clone() {
+            let remote_type = props.remote_type;
+
+            self.async_pool.spawn(async move {
+                let result = scan(connection_info, form_ctx, remote_type, tfa).await;
+                link.send_message(Msg::ConnectResult(result));
+            });
+        } else {
+            unreachable!("Settings page must have connection info");
+        }
+    }
+
     fn update_credentials(form_ctx: &FormContext) {
         let user = form_ctx.read().get_field_text("user");
         let realm = form_ctx.read().get_field_text("realm");
@@ -201,6 +242,7 @@ impl Component for PdmWizardPageInfo {
             last_error: None,
             loading: false,
             credentials: None,
+            tfa_challenge: None,
             async_pool: AsyncPool::new(),
         }
     }
@@ -241,31 +283,38 @@ impl Component for PdmWizardPageInfo {
                     props.info.go_to_next_page();
                     return true;
                 }
-                let link = ctx.link().clone();
                 self.update_server_info(ctx, None);
-                let form_ctx = props.info.form_ctx.clone();
-                self.loading = true;
-                self.last_error = None;
-                props.info.page_lock(true);
-
-                if let Some(connection_info) = props.connect_info.clone() {
-                    let remote_type = props.remote_type;
-
-                    self.async_pool.spawn(async move {
-                        let result = scan(connection_info, form_ctx, remote_type).await;
-                        link.send_message(Msg::ConnectResult(result));
-                    });
-                } else {
-                    unreachable!("Settings page must have connection info");
-                }
+                self.start_scan(ctx, None);
             }
-            Msg::ConnectResult(server_info) => {
+            Msg::ConnectResult(result) => {
                 self.loading = false;
                 props.info.page_lock(false);
-                match server_info {
-                    Ok(server_info) => {
+                match result {
+                    Ok(RemoteScanResult {
+                        remote: Some(server_info),
+                        ..
+                    }) => {
                         self.update_server_info(ctx, Some(server_info));
                     }
+                    Ok(RemoteScanResult {
+                        tfa_challenge: Some(tfa_challenge),
+                        ..
+                    }) => {
+                        match serde_json::from_str::<TfaChallenge>(&tfa_challenge.challenge) {
+                            Ok(challenge) => {
+                                self.tfa_challenge = Some((tfa_challenge.id, Rc::new(challenge)));
+                            }
+                            Err(err) => self.last_error = Some(err.into()),
+                        }
+                        props.info.page_lock(true);
+                        return true;
+                    }
+                    Ok(_) => {
+                        self.last_error = Some(anyhow::format_err!(tr!(
+                            "Scanning the remote returned no result."
+                        )));
+                        props.info.page_lock(true);
+                    }
                     Err(err) => {
                         self.last_error = Some(err);
                         props.info.page_lock(true);
@@ -278,6 +327,14 @@ impl Component for PdmWizardPageInfo {
                     props.info.go_to_next_page();
                 }
             }
+            Msg::TfaResponse(response) => {
+                if let Some((id, _)) = self.tfa_challenge.take() {
+                    self.start_scan(ctx, Some((id, response)));
+                }
+            }
+            Msg::TfaAbort => {
+                self.tfa_challenge = None;
+            }
         }
         true
     }
@@ -288,6 +345,13 @@ impl Component for PdmWizardPageInfo {
             Some(info) => (info.hostname, info.fingerprint),
             None => (Default::default(), None),
         };
+        // PMG has no API tokens, its remotes always use the user and password
+        let tokens = props.remote_type != RemoteType::Pmg;
+        let login_label = if tokens {
+            tr!("Login and create Token")
+        } else {
+            tr!("Login")
+        };
         let input_panel = InputPanel::new()
             .class(FlexFit)
             .padding(4)
@@ -303,7 +367,7 @@ impl Component for PdmWizardPageInfo {
                     .key("login-mode-login")
                     .name("login-mode")
                     .default(true)
-                    .box_label(tr!("Login and create Token"))
+                    .box_label(login_label)
                     .on_change(
                         ctx.link()
                             .callback(|value| Msg::ToggleCreateToken(value == "login")),
@@ -337,9 +401,9 @@ impl Component for PdmWizardPageInfo {
                 tr!("API Token Name"),
                 Field::new()
                     .name("create-token")
-                    .disabled(!self.user_mode)
-                    .required(self.user_mode)
-                    .submit(self.user_mode)
+                    .disabled(!self.user_mode || !tokens)
+                    .required(self.user_mode && tokens)
+                    .submit(self.user_mode && tokens)
                     .default(match get_nodename() {
                         Some(nodename) => format!("pdm-admin-{nodename}"),
                         None => "pdm-admin".to_string(),
@@ -350,6 +414,7 @@ impl Component for PdmWizardPageInfo {
                 RadioButton::new("token")
                     .key("login-mode-token")
                     .name("login-mode")
+                    .disabled(!tokens)
                     .box_label(tr!("Use existing Token")),
             )
             .with_right_field(
@@ -397,7 +462,15 @@ impl Component for PdmWizardPageInfo {
                             .as_deref()
                             .map(|err| error_message(&err.to_string())),
                     ),
-            );
+            )
+            .with_optional_child(self.tfa_challenge.as_ref().map(|(_, challenge)| {
+                let link = ctx.link();
+                TfaDialog::new(challenge.clone())
+                    .on_close(link.callback(|_| Msg::TfaAbort))
+                    .on_totp(link.callback(|code| Msg::TfaResponse(format!("totp:{code}"))))
+                    .on_yubico(link.callback(|code| Msg::TfaResponse(format!("yubico:{code}"))))
+                    .on_recovery(link.callback(|code| Msg::TfaResponse(format!("recovery:{code}"))))
+            }));
         Mask::new(content)
             .class(FlexFit)
             .visible(self.loading)
@@ -19,6 +19,7 @@ pub fn render_resource_name(resource: &Resource, vmid_first: bool) -> String {
         Resource::PveNetwork(network) => network.name().to_string(),
         Resource::PbsNode(node) => node.name.clone(),
         Resource::PbsDatastore(store) => store.name.clone(),
+        Resource::PmgNode(node) => node.node.clone(),
     }
 }
 
@@ -31,6 +32,7 @@ pub fn render_resource_icon(resource: &Resource) -> Fa {
         Resource::PveNetwork(_) => "fa-sdn",
         Resource::PbsNode(_) => "building-o",
         Resource::PbsDatastore(_) => "floppy-o",
+        Resource::PmgNode(_) => "envelope-o",
     };
 
     Fa::new(class)
@@ -0,0 +1,195 @@
+use anyhow::Error;
+use yew::html::{IntoEventCallback, IntoPropValue};
+use yew::virtual_dom::{VComp, VNode};
+
+use proxmox_yew_comp::EditWindow;
+use pwt::prelude::*;
+use pwt::widget::InputPanel;
+use pwt::widget::form::{Checkbox, Field, FormContext, Number, TextArea};
+use pwt_macros::builder;
+
+use pdm_client::types::{DeployTemplateParams, StorageContent};
+
+use crate::pve::{GuestInfo, GuestType};
+
+use super::{PveNodeSelector, PveStorageSelector};
+
+#[derive(Clone, PartialEq, Properties)]
+#[builder]
+/// The window to deploy a new guest from a template.
+pub struct DeployWindow {
+    /// The remote of the template
+    pub remote: AttrValue,
+
+    /// The template to clone
+    pub guest_info: GuestInfo,
+
+    /// The node the template is located on, preselected as target node.
+    #[builder(IntoPropValue, into_prop_value)]
+    #[prop_or_default]
+    pub node: Option<AttrValue>,
+
+    /// Close/Abort callback.
+    #[builder_cb(IntoEventCallback, into_event_callback, ())]
+    #[prop_or_default]
+    pub on_close: Option<Callback<()>>,
+
+    /// Submit callback, called with the UPID of the local deploy task.
+    #[builder_cb(IntoEventCallback, into_event_callback, String)]
+    #[prop_or_default]
+    pub on_submit: Option<Callback<String>>,
+}
+
+impl DeployWindow {
+    pub fn new(remote: impl Into<AttrValue>, guest_info: GuestInfo) -> Self {
+        yew::props!(Self {
+            remote: remote.into(),
+            guest_info,
+        })
+    }
+}
+
+pub struct PdmDeployWindow {}
+
+impl PdmDeployWindow {
+    async fn submit(
+        remote: AttrValue,
+        guest_info: GuestInfo,
+        on_submit: Option<Callback<String>>,
+        form_ctx: FormContext,
+    ) -> Result<(), Error> {
+        let mut data = form_ctx.get_submit_data();
+        data["template"] = guest_info.vmid.into();
+        let params: DeployTemplateParams = serde_json::from_value(data)?;
+
+        let upid = crate::pdm_client()
+            .pve_deploy_template(&remote, params)
+            .await?;
+
+        if let Some(on_submit) = on_submit {
+            on_submit.emit(upid);
+        }
+        Ok(())
+    }
+
+    fn input_panel(
+        form_ctx: &FormContext,
+        remote: AttrValue,
+        guest_info: GuestInfo,
+        node: Option<AttrValue>,
+    ) -> Html {
+        let target_node = form_ctx.read().get_field_text("target");
+        let target_node = (!target_node.is_empty()).then_some(target_node);
+        // the target storage can only be chosen for full clones
+        let full = form_ctx.read().get_field_checked("full");
+        let is_qemu = guest_info.guest_type == GuestType::Qemu;
+
+        let content_types = match guest_info.guest_type {
+            GuestType::Qemu => vec![StorageContent::Images],
+            GuestType::Lxc => vec![StorageContent::Rootdir],
+        };
+
+        let mut input = InputPanel::new()
+            .padding(4)
+            .with_field(
+                tr!("Target Node"),
+                PveNodeSelector::new(remote.clone())
+                    .name("target")
+                    .default(node)
+                    .required(true),
+            )
+            .with_right_field(
+                tr!("VM ID"),
+                Number::<u32>::new()
+                    .name("newid")
+                    .min(100)
+                    .max(999999999)
+                    .placeholder(tr!("Next free VMID")),
+            )
+            .with_field(
+                if is_qemu {
+                    tr!("Name")
+                } else {
+                    tr!("Hostname")
+                },
+                Field::new().name("name"),
+            )
+            .with_right_field(tr!("Full Clone"), Checkbox::new().name("full"))
+            .with_large_field(
+                tr!("Target Storage"),
+                PveStorageSelector::new(remote)
+                    .key(format!(
+                        "storage-{}",
+                        target_node.as_deref().unwrap_or_default()
+                    ))
+                    .name("storage")
+                    .node(target_node.map(AttrValue::from))
+                    .content_types(content_types)
+                    .autoselect(false)
+                    .placeholder(tr!("Same as source"))
+                    .disabled(!full),
+            );
+
+        if is_qemu {
+            input.add_spacer(false);
+            input.add_field(tr!("Cloud-Init User"), Field::new().name("ciuser"));
+            input.add_field_with_options(
+                pwt::widget::FieldPosition::Right,
+                false,
+                false,
+                tr!("IP Config"),
+                Field::new().name("ipconfig").placeholder("ip=dhcp"),
+            );
+            input.add_large_field(
+                false,
+                false,
+                tr!("SSH Public Keys"),
+                TextArea::new().name("sshkeys").submit_empty(false),
+            );
+        }
+
+        input.add_field(tr!("Start after deploy"), Checkbox::new().name("start"));
+
+        input.into()
+    }
+}
+
+impl Component for PdmDeployWindow {
+    type Message = ();
+    type Properties = DeployWindow;
+
+    fn create(_ctx: &Context<Self>) -> Self {
+        Self {}
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        let props = ctx.props();
+        let guest_info = props.guest_info;
+        EditWindow::new(tr!("Deploy from Template") + ": " + &guest_info.vmid.to_string())
+            .edit(false)
+            .submit_text(tr!("Deploy"))
+            .on_close(props.on_close.clone())
+            .on_submit({
+                let remote = props.remote.clone();
+                let on_submit = props.on_submit.clone();
+                move |form_ctx| {
+                    Self::submit(remote.clone(), guest_info, on_submit.clone(), form_ctx)
+                }
+            })
+            .renderer({
+                let remote = props.remote.clone();
+                let node = props.node.clone();
+                move |form_ctx| {
+                    Self::input_panel(form_ctx, remote.clone(), guest_info, node.clone())
+                }
+            })
+            .into()
+    }
+}
+
+impl From<DeployWindow> for VNode {
+    fn from(val: DeployWindow) -> Self {
+        let comp = VComp::new::<PdmDeployWindow>(std::rc::Rc::new(val), None);
+        VNode::from(comp)
+    }
+}
@@ -0,0 +1,309 @@
+use std::rc::Rc;
+
+use anyhow::{Error, bail};
+use serde_json::{Value, json};
+use yew::html::{IntoEventCallback, Scope};
+use yew::virtual_dom::{VComp, VNode};
+
+use proxmox_client::ApiResponseData;
+use proxmox_human_byte::HumanByte;
+use proxmox_schema::property_string::PropertyString;
+use proxmox_yew_comp::EditWindow;
+use pwt::css;
+use pwt::prelude::*;
+use pwt::widget::form::{Combobox, FormContext, Number};
+use pwt::widget::{Container, InputPanel};
+use pwt_macros::builder;
+
+use pdm_api_types::resource::GuestType as ApiGuestType;
+use pdm_client::types::{EvacuateNodeParams, EvacuationMigration, EvacuationPlan};
+
+use crate::pve::{GuestInfo, GuestType};
+
+#[derive(Clone, PartialEq, Properties)]
+#[builder]
+/// The window to review and edit the evacuation plan of a node before running it.
+pub struct EvacuateWindow {
+    /// The remote of the node
+    pub remote: AttrValue,
+
+    /// The node to evacuate
+    pub node: AttrValue,
+
+    /// The guests on the node with their display names.
+    pub guests: Rc<Vec<(GuestInfo, String)>>,
+
+    /// Close/Abort callback.
+    #[builder_cb(IntoEventCallback, into_event_callback, ())]
+    #[prop_or_default]
+    pub on_close: Option<Callback<()>>,
+
+    /// Submit callback, called with the UPID of the local evacuation task.
+    #[builder_cb(IntoEventCallback, into_event_callback, String)]
+    #[prop_or_default]
+    pub on_submit: Option<Callback<String>>,
+}
+
+impl EvacuateWindow {
+    pub fn new(
+        remote: impl Into<AttrValue>,
+        node: impl Into<AttrValue>,
+        guests: Rc<Vec<(GuestInfo, String)>>,
+    ) -> Self {
+        yew::props!(Self {
+            remote: remote.into(),
+            node: node.into(),
+            guests,
+        })
+    }
+}
+
+pub enum Msg {
+    /// The computed plan, used for the selectable targets and their projected load.
+    Plan(Rc<EvacuationPlan>),
+}
+
+pub struct PdmEvacuateWindow {
+    plan: Option<Rc<EvacuationPlan>>,
+}
+
+/// The form field holding the target node of a guest.
+fn target_field(vmid: u32) -> String {
+    format!("target-{vmid}")
+}
+
+impl PdmEvacuateWindow {
+    async fn load(
+        link: Scope<Self>,
+        remote: AttrValue,
+        node: AttrValue,
+    ) -> Result<ApiResponseData<Value>, Error> {
+        let plan = crate::pdm_client()
+            .pve_node_evacuation_plan(&remote, &node)
+            .await?;
+
+        // preselect the computed targets, unplaced guests are left empty
+        let mut data = json!({});
+        for migration in &plan.migrations {
+            data[target_field(migration.vmid)] = migration.target.clone().into();
+        }
+        link.send_message(Msg::Plan(Rc::new(plan)));
+
+        Ok(ApiResponseData {
+            attribs: std::collections::HashMap::new(),
+            data,
+        })
+    }
+
+    async fn submit(
+        remote: AttrValue,
+        node: AttrValue,
+        guests: Rc<Vec<(GuestInfo, String)>>,
+        on_submit: Option<Callback<String>>,
+        form_ctx: FormContext,
+    ) -> Result<(), Error> {
+        let data = form_ctx.get_submit_data();
+
+        // guests without a target are not migrated
+        let mut migrations = Vec::new();
+        for (guest_info, _) in guests.iter() {
+            let Some(target) = data[target_field(guest_info.vmid)]
+                .as_str()
+                .filter(|target| !target.is_empty())
+            else {
+                continue;
+            };
+            let guest_type = match guest_info.guest_type {
+                GuestType::Qemu => ApiGuestType::Qemu,
+                GuestType::Lxc => ApiGuestType::Lxc,
+            };
+            migrations.push(PropertyString::new(EvacuationMigration {
+                vmid: guest_info.vmid,
+                guest_type,
+                target: target.to_string(),
+            }));
+        }
+        if migrations.is_empty() {
+            bail!(tr!("No guest has a target node."));
+        }
+
+        let params = EvacuateNodeParams {
+            migrations: Some(migrations),
+            max_parallel: data["max-parallel"].as_u64().map(|n| n as usize),
+            // the field is in MiB/s, the API expects KiB/s
+            bwlimit: data["bwlimit"].as_u64().map(|limit| limit * 1024),
+            retries: data["retries"].as_u64().map(|n| n as u8),
+        };
+
+        let upid = crate::pdm_client()
+            .pve_evacuate_node(&remote, &node, params)
+            .await?;
+
+        if let Some(on_submit) = on_submit {
+            on_submit.emit(upid);
+        }
+        Ok(())
+    }
+
+    fn input_panel(guests: &[(GuestInfo, String)], plan: Option<Rc<EvacuationPlan>>) -> Html {
+        // only the nodes the plan considers are valid targets
+        let targets: Rc<Vec<AttrValue>> = Rc::new(
+            plan.iter()
+                .flat_map(|plan| plan.targets.iter())
+                .map(|target| target.node.clone().into())
+                .collect(),
+        );
+
+        let mut input = InputPanel::new().padding(4);
+
+        input.add_large_custom_child(
+            Container::new()
+                .key("guests_title")
+                .padding_bottom(1)
+                .class(css::FontStyle::TitleSmall)
+                .with_child(tr!("Target Nodes")),
+        );
+        for (guest_info, name) in guests {
+            let label = match guest_info.guest_type {
+                GuestType::Qemu => tr!("VM {0}", name),
+                GuestType::Lxc => tr!("CT {0}", name),
+            };
+            input.add_large_field(
+                false,
+                false,
+                label,
+                Combobox::new()
+                    .name(target_field(guest_info.vmid))
+                    .items(Rc::clone(&targets))
+                    .placeholder(tr!("Do not migrate")),
+            );
+        }
+
+        if let Some(plan) = &plan {
+            if !plan.unplaced.is_empty() {
+                input.add_large_custom_child(
+                    Container::new()
+                        .key("unplaced")
+                        .padding_bottom(1)
+                        .class(css::ColorScheme::WarningContainer)
+                        .with_child(tr!(
+                            "Some guests do not fit on any node, pick a target or leave them on the node."
+                        )),
+                );
+            }
+
+            input.add_large_custom_child(
+                Container::new()
+                    .key("targets_title")
+                    .padding_y(1)
+                    .class(css::FontStyle::TitleSmall)
+                    .with_child(tr!("Projected Load of the Computed Plan")),
+            );
+            for target in &plan.targets {
+                input.add_large_custom_child(
+                    Container::new()
+                        .key(format!("load-{}", target.node))
+                        .with_child(format!(
+                            "{}: {} {:.1}%, {} {} / {}",
+                            target.node,
+                            tr!("CPU"),
+                            target.cpu * 100.0,
+                            tr!("Memory"),
+                            HumanByte::from(target.mem),
+                            HumanByte::from(target.maxmem),
+                        )),
+                );
+            }
+        }
+
+        input.add_spacer(false);
+        input.add_field(
+            tr!("Parallel Migrations"),
+            Number::<u64>::new()
+                .name("max-parallel")
+                .min(1)
+                .max(16)
+                .placeholder("1"),
+        );
+        input.add_field_with_options(
+            pwt::widget::FieldPosition::Right,
+            false,
+            false,
+            tr!("Retries"),
+            Number::<u64>::new()
+                .name("retries")
+                .min(0)
+                .max(5)
+                .placeholder("1"),
+        );
+        input.add_field(
+            tr!("Bandwidth Limit (MiB/s)"),
+            Number::<u64>::new()
+                .name("bwlimit")
+                .min(1)
+                .placeholder(tr!("Default")),
+        );
+
+        input.into()
+    }
+}
+
+impl Component for PdmEvacuateWindow {
+    type Message = Msg;
+    type Properties = EvacuateWindow;
+
+    fn create(_ctx: &Context<Self>) -> Self {
+        Self { plan: None }
+    }
+
+    fn update(&mut self, _ctx: &Context<Self>, msg: Self::Message) -> bool {
+        match msg {
+            Msg::Plan(plan) => {
+                self.plan = Some(plan);
+                true
+            }
+        }
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        let props = ctx.props();
+        EditWindow::new(tr!("Evacuate Node '{0}'", props.node))
+            .edit(false)
+            .submit_text(tr!("Evacuate"))
+            .on_close(props.on_close.clone())
+            .on_submit({
+                let remote = props.remote.clone();
+                let node = props.node.clone();
+                let guests = Rc::clone(&props.guests);
+                let on_submit = props.on_submit.clone();
+                move |form_ctx| {
+                    Self::submit(
+                        remote.clone(),
+                        node.clone(),
+                        Rc::clone(&guests),
+                        on_submit.clone(),
+                        form_ctx,
+                    )
+                }
+            })
+            .loader({
+                let link = ctx.link().clone();
+                let remote = props.remote.clone();
+                let node = props.node.clone();
+                move || Self::load(link.clone(), remote.clone(), node.clone())
+            })
+            .renderer({
+                let guests = Rc::clone(&props.guests);
+                let plan = self.plan.clone();
+                move |_form_ctx| Self::input_panel(&guests, plan.clone())
+            })
+            .into()
+    }
+}
+
+impl From<EvacuateWindow> for VNode {
+    fn from(val: EvacuateWindow) -> Self {
+        let comp = VComp::new::<PdmEvacuateWindow>(Rc::new(val), None);
+        VNode::from(comp)
+    }
+}
@@ -76,6 +76,8 @@ pub enum Msg {
     NodenameResult(Result<String, proxmox_client::Error>),
     Result(RemoteUpid),
     LoadPreconditions(Option<AttrValue>),
+    /// The storage selected for intra-cluster migrations, validated with the preconditions.
+    TargetStorageChange(Option<AttrValue>),
     PreconditionResult(Result<QemuMigratePreconditions, proxmox_client::Error>),
     /// The target cluster's next free VMID, fetched after picking an external target remote.
     NextidResult(Option<u32>),
@@ -87,6 +89,11 @@ pub struct PdmMigrateWindow {
     target_remote: AttrValue,
     _async_pool: AsyncPool,
     preconditions: Option<QemuMigratePreconditions>,
+    /// Why the preconditions for the selected target node and storage are not met.
+    precondition_error: Option<String>,
+    /// The target node and storage the preconditions were (last) requested for.
+    precondition_target: Option<AttrValue>,
+    precondition_storage: Option<AttrValue>,
     target_node: Option<AttrValue>,
     /// Next free VMID on the (external) target cluster, used to prefill the target VMID field.
     target_nextid: Option<u32>,
@@ -118,9 +125,16 @@ impl PdmMigrateWindow {
         remote: String,
         guest_info: GuestInfo,
         target: String,
+        target_storage: Option<String>,
     ) -> Result<QemuMigratePreconditions, proxmox_client::Error> {
         let res = crate::pdm_client()
-            .pve_qemu_migrate_preconditions(&remote, None, guest_info.vmid, Some(target))
+            .pve_qemu_migrate_preconditions(
+                &remote,
+                None,
+                guest_info.vmid,
+                Some(target),
+                target_storage.as_deref(),
+            )
             .await?;
 
         Ok(res)
@@ -176,6 +190,9 @@ impl PdmMigrateWindow {
     ) -> Result<(), Error> {
         let value = form_ctx.get_submit_data();
         let target_remote = value["remote"].as_str().unwrap_or_default();
+        // the field is in MiB/s, the API expects KiB/s
+        let bwlimit = value["bwlimit"].as_u64().map(|limit| limit * 1024);
+        let online = value["online"].as_bool().unwrap_or(true);
 
         let upid = if target_remote != remote {
             let target_endpoint = value.get("target-endpoint").and_then(|e| e.as_str());
@@ -183,11 +200,14 @@ impl PdmMigrateWindow {
                 crate::pve::GuestType::Qemu => {
                     let mut migrate_opts = RemoteMigrateQemu::new()
                         .delete_source(value["delete-source"].as_bool().unwrap_or_default())
-                        .online(true);
+                        .online(online);
 
                     if let Some(Value::Number(vmid)) = value.get("target-vmid") {
                         migrate_opts = migrate_opts.target_vmid(vmid.as_u64().unwrap() as u32);
                     }
+                    if let Some(bwlimit) = bwlimit {
+                        migrate_opts = migrate_opts.bwlimit(bwlimit);
+                    }
 
                     if form_ctx.read().get_field_checked("detailed-mode") {
                         match value.get("detail-map") {
@@ -233,6 +253,9 @@ impl PdmMigrateWindow {
                     if let Some(Value::Number(vmid)) = value.get("target-vmid") {
                         migrate_opts = migrate_opts.target_vmid(vmid.as_u64().unwrap() as u32);
                     }
+                    if let Some(bwlimit) = bwlimit {
+                        migrate_opts = migrate_opts.bwlimit(bwlimit);
+                    }
 
                     if form_ctx.read().get_field_checked("detailed-mode") {
                         match value.get("detail-map") {
@@ -274,10 +297,15 @@ impl PdmMigrateWindow {
         } else {
             match guest_info.guest_type {
                 crate::pve::GuestType::Qemu => {
-                    let mut migrate_opts = MigrateQemu::new().online(true).with_local_disks(true);
+                    let mut migrate_opts = MigrateQemu::new()
+                        .online(online)
+                        .with_local_disks(value["with-local-disks"].as_bool().unwrap_or(true));
                     if let Some(Some(storage)) = value.get("target_storage").map(|v| v.as_str()) {
                         migrate_opts = migrate_opts.map_storage("*", storage);
                     }
+                    if let Some(bwlimit) = bwlimit {
+                        migrate_opts = migrate_opts.bwlimit(bwlimit);
+                    }
 
                     crate::pdm_client()
                         .pve_qemu_migrate(
@@ -290,13 +318,18 @@ impl PdmMigrateWindow {
                         .await?
                 }
                 crate::pve::GuestType::Lxc => {
+                    let mut migrate_opts = MigrateLxc::new().restart(true, None);
+                    if let Some(bwlimit) = bwlimit {
+                        migrate_opts = migrate_opts.bwlimit(bwlimit);
+                    }
+
                     crate::pdm_client()
                         .pve_lxc_migrate(
                             &remote,
                             None,
                             guest_info.vmid,
                             value["node"].as_str().unwrap().to_string(),
-                            MigrateLxc::new().restart(true, None),
+                            migrate_opts,
                         )
                         .await?
                 }
@@ -317,11 +350,14 @@ impl PdmMigrateWindow {
         source_node: Option<AttrValue>,
         guest_info: GuestInfo,
         preconditions: Option<QemuMigratePreconditions>,
+        precondition_error: Option<String>,
         target_node: Option<AttrValue>,
         target_nextid: Option<u32>,
         last_seeded: Option<u32>,
     ) -> Html {
         let same_remote = target_remote == source_remote;
+        // only intra-cluster migrations are validated with the preconditions
+        let precondition_error = precondition_error.filter(|_| same_remote);
         if !same_remote {
             let node = target_node.unwrap_or_default().to_string();
             form_ctx.write().set_field_value("node", node.into());
@@ -381,6 +417,15 @@ impl PdmMigrateWindow {
                 }
             }
         }
+        if let Some(error) = &precondition_error {
+            warnings.extend(error.lines().map(|line| {
+                Row::new()
+                    .gap(2)
+                    .with_child(Fa::from(Status::Error))
+                    .with_child(line.to_string())
+                    .into()
+            }));
+        }
 
         let show_target_storage =
             (same_remote && uses_local_disks && running) || (!same_remote && !detail_mode);
@@ -442,9 +487,7 @@ impl PdmMigrateWindow {
                     .disabled(same_remote),
             );
 
-        if !same_remote || uses_local_disks || uses_local_resources {
-            input.add_spacer(false);
-        }
+        input.add_spacer(false);
 
         if uses_local_resources {
             // just to prevent submitting
@@ -458,12 +501,23 @@ impl PdmMigrateWindow {
                     .validate(|_: &bool| bail!("Uses local resources")),
             );
         }
+        if precondition_error.is_some() {
+            // just to prevent submitting
+            input.add_field_with_options(
+                pwt::widget::FieldPosition::Left,
+                false,
+                true,
+                "",
+                Checkbox::new()
+                    .name("preconditions")
+                    .validate(|_: &bool| bail!("Migration preconditions not met")),
+            );
+        }
 
         input.add_custom_child(
             Container::new()
                 .key("remote_title")
                 .padding_bottom(1)
-                .class((same_remote && !show_target_storage).then_some(css::Display::None))
                 .class(css::FontStyle::TitleSmall)
                 .with_child(if same_remote {
                     tr!("Migration Settings")
@@ -472,6 +526,35 @@ impl PdmMigrateWindow {
                 }),
         );
 
+        let is_qemu = guest_info.guest_type == GuestType::Qemu;
+        input.add_field(
+            tr!("Bandwidth Limit (MiB/s)"),
+            Number::new()
+                .min(1u64)
+                .name("bwlimit")
+                .placeholder(tr!("Default")),
+        );
+        input.add_field_with_options(
+            pwt::widget::FieldPosition::Right,
+            false,
+            !is_qemu,
+            tr!("Online"),
+            Checkbox::new()
+                .name("online")
+                .default(true)
+                .disabled(!is_qemu),
+        );
+        input.add_field_with_options(
+            pwt::widget::FieldPosition::Right,
+            false,
+            !(is_qemu && same_remote),
+            tr!("With Local Disks"),
+            Checkbox::new()
+                .name("with-local-disks")
+                .default(true)
+                .disabled(!(is_qemu && same_remote)),
+        );
+
         input.add_field_with_options(
             pwt::widget::FieldPosition::Left,
             false,
@@ -517,6 +600,7 @@ impl PdmMigrateWindow {
                 .autoselect(!same_remote)
                 .content_types(content_types.clone())
                 .placeholder(tr!("Current layout"))
+                .on_change(same_remote.then(|| link.callback(Msg::TargetStorageChange)))
                 .required(show_target_storage && !same_remote),
         );
         input.add_large_field(
@@ -559,6 +643,9 @@ impl Component for PdmMigrateWindow {
             target_remote: ctx.props().remote.clone(),
             _async_pool: AsyncPool::new(),
             preconditions: None,
+            precondition_error: None,
+            precondition_target: None,
+            precondition_storage: None,
             target_node: None,
             target_nextid: None,
             last_seeded: None,
@@ -598,22 +685,39 @@ impl Component for PdmMigrateWindow {
                 if props.guest_info.guest_type == GuestType::Lxc {
                     return false;
                 }
+                self.precondition_target = target.clone();
                 if let Some(target) = target {
                     let remote = props.remote.to_string();
                     let guest_info = props.guest_info;
                     let target = target.to_string();
+                    let storage = self.precondition_storage.as_ref().map(|s| s.to_string());
                     self._async_pool
                         .send_future(ctx.link().clone(), async move {
-                            let res = Self::load_preconditions(remote, guest_info, target).await;
+                            let res =
+                                Self::load_preconditions(remote, guest_info, target, storage).await;
                             Msg::PreconditionResult(res)
                         });
                 }
 
                 false
             }
+            Msg::TargetStorageChange(storage) => {
+                if self.precondition_storage == storage {
+                    return false;
+                }
+                self.precondition_storage = storage;
+                ctx.link()
+                    .send_message(Msg::LoadPreconditions(self.precondition_target.clone()));
+                false
+            }
             Msg::PreconditionResult(res) => {
                 match res {
-                    Ok(preconditions) => self.preconditions = Some(preconditions),
+                    Ok(preconditions) => {
+                        self.preconditions = Some(preconditions);
+                        self.precondition_error = None;
+                    }
+                    // keep the last known preconditions, so the storage can still be changed
+                    Err(proxmox_client::Error::Api(_, msg)) => self.precondition_error = Some(msg),
                     Err(err) => log::warn!("could not get preconditions: {err}"),
                 }
                 true
@@ -670,6 +774,7 @@ impl Component for PdmMigrateWindow {
                 let source_node = ctx.props().source_node.clone();
                 let link = ctx.link().clone();
                 let preconditions = self.preconditions.clone();
+                let precondition_error = self.precondition_error.clone();
                 let target_node = self.target_node.clone();
                 let target_nextid = self.target_nextid;
                 let last_seeded = self.last_seeded;
@@ -682,6 +787,7 @@ impl Component for PdmMigrateWindow {
                         source_node.clone(),
                         guest_info,
                         preconditions.clone(),
+                        precondition_error.clone(),
                         target_node.clone(),
                         target_nextid,
                         last_seeded,
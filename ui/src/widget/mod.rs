@@ -1,6 +1,12 @@
 mod migrate_window;
 pub use migrate_window::MigrateWindow;
 
+mod deploy_window;
+pub use deploy_window::DeployWindow;
+
+mod evacuate_window;
+pub use evacuate_window::EvacuateWindow;
+
 mod snapshot_window;
 pub use snapshot_window::SnapshotWindow;
 
@@ -28,6 +28,7 @@ use pwt_macros::{builder, widget};
 
 use proxmox_yew_comp::{Status, http_get};
 
+use pdm_api_types::remotes::folder_and_parents;
 use pdm_api_types::resource::{RemoteResources, Resource};
 
 use crate::{
@@ -101,14 +102,21 @@ impl Default for ResourceTree {
 #[derive(Clone, PartialEq)]
 enum PdmTreeEntry {
     Root,
+    Folder(String),
     Resource(String, Resource),
     Remote(String, Option<String>),
 }
 
+// remote IDs cannot contain a slash, so this never clashes with the key of a remote
+fn folder_key(folder: &str) -> Key {
+    Key::from(format!("folder/{folder}"))
+}
+
 impl ExtractPrimaryKey for PdmTreeEntry {
     fn extract_key(&self) -> Key {
         match self {
             PdmTreeEntry::Root => Key::from("__root__"),
+            PdmTreeEntry::Folder(folder) => folder_key(folder),
             PdmTreeEntry::Resource(_, resource) => Key::from(resource.global_id()),
             PdmTreeEntry::Remote(remote, _) => Key::from(remote.as_str()),
         }
@@ -147,7 +155,14 @@ pub struct PdmResourceTree {
     async_pool: AsyncPool,
 }
 
-impl PdmResourceTree {}
+impl PdmResourceTree {
+    fn remote_folder(&self, remote: &str) -> Option<&str> {
+        self.remote_list
+            .iter()
+            .find(|entry| entry.id == remote)
+            .and_then(|entry| entry.folder.as_deref())
+    }
+}
 
 impl Component for PdmResourceTree {
     type Message = Msg;
@@ -209,9 +224,39 @@ impl Component for PdmResourceTree {
                         let mut store = self.store.write();
                         let mut root = store.set_root(PdmTreeEntry::Root);
                         for res in result.into_iter() {
-                            let mut node =
-                                root.append(PdmTreeEntry::Remote(res.remote.clone(), res.error));
-                            node.set_expanded(true);
+                            // remotes are placed below their folder, create it and its parents
+                            let mut parent = None;
+                            if let Some(folder) = self.remote_folder(&res.remote) {
+                                let mut folders: Vec<&str> = folder_and_parents(folder).collect();
+                                folders.reverse();
+                                for folder in folders {
+                                    let key = folder_key(folder);
+                                    if root.find_node_by_key_mut(&key).is_none() {
+                                        let entry = PdmTreeEntry::Folder(folder.to_string());
+                                        match parent
+                                            .as_ref()
+                                            .and_then(|p| root.find_node_by_key_mut(p))
+                                        {
+                                            Some(mut parent) => {
+                                                parent.append(entry).set_expanded(true)
+                                            }
+                                            None => root.append(entry).set_expanded(true),
+                                        }
+                                    }
+                                    parent = Some(key);
+                                }
+                            }
+
+                            let entry = PdmTreeEntry::Remote(res.remote.clone(), res.error);
+                            match parent.as_ref().and_then(|p| root.find_node_by_key_mut(p)) {
+                                Some(mut parent) => parent.append(entry).set_expanded(true),
+                                None => root.append(entry).set_expanded(true),
+                            }
+                            let Some(mut node) =
+                                root.find_node_by_key_mut(&Key::from(res.remote.as_str()))
+                            else {
+                                continue;
+                            };
                             for entry in res.resources.into_iter() {
                                 if let Resource::PbsNode(_) = entry {
                                     continue;
@@ -224,6 +269,9 @@ impl Component for PdmResourceTree {
                             (PdmTreeEntry::Root, PdmTreeEntry::Root) => Ordering::Equal,
                             (PdmTreeEntry::Root, _) => Ordering::Less,
                             (_, PdmTreeEntry::Root) => Ordering::Greater,
+                            (PdmTreeEntry::Folder(a), PdmTreeEntry::Folder(b)) => a.cmp(b),
+                            (PdmTreeEntry::Folder(_), _) => Ordering::Less,
+                            (_, PdmTreeEntry::Folder(_)) => Ordering::Greater,
                             (PdmTreeEntry::Remote(a, _), PdmTreeEntry::Remote(b, _)) => a.cmp(b),
                             (PdmTreeEntry::Remote(_, _), _) => Ordering::Less,
                             (_, PdmTreeEntry::Remote(_, _)) => Ordering::Greater,
@@ -238,7 +286,13 @@ impl Component for PdmResourceTree {
                 true
             }
             Msg::RemoteListChanged(list) => {
-                let reload = self.remote_list.len() != list.len();
+                // folders change the structure of the tree
+                let reload = self.remote_list.len() != list.len()
+                    || self
+                        .remote_list
+                        .iter()
+                        .zip(list.iter())
+                        .any(|(a, b)| a.id != b.id || a.folder != b.folder);
                 self.remote_list = list;
                 if reload && !self.remote_list.is_empty() {
                     ctx.link().send_message(Msg::Load);
@@ -252,7 +306,7 @@ impl Component for PdmResourceTree {
                 let mut navigated = false;
                 if let Some(node) = root.find_node_by_key(&key) {
                     match node.record() {
-                        PdmTreeEntry::Root => {}
+                        PdmTreeEntry::Root | PdmTreeEntry::Folder(_) => {}
                         PdmTreeEntry::Resource(remote, resource) => {
                             crate::navigate_to(ctx.link(), remote, Some(resource));
                             navigated = true;
@@ -376,6 +430,12 @@ fn columns(
                         html! {{"root"}},
                         None,
                     ),
+                    PdmTreeEntry::Folder(folder) => (
+                        Container::new().with_child(Fa::new("folder").fixed_width()),
+                        // parent folders are shown in the tree already
+                        folder.rsplit('/').next().unwrap_or(folder).into(),
+                        Some(folder.to_string()),
+                    ),
                     PdmTreeEntry::Resource(_, resource) => (
                         render_status_icon(resource),
                         Row::new()
@@ -418,7 +478,7 @@ fn columns(
             .width("150px")
             .render(|item: &PdmTreeEntry| {
                 match item {
-                    PdmTreeEntry::Root => "",
+                    PdmTreeEntry::Root | PdmTreeEntry::Folder(_) => "",
                     PdmTreeEntry::Resource(_, resource) => {
                         get_resource_node(resource).unwrap_or("")
                     }
@@ -434,7 +494,7 @@ fn columns(
                 let link = link.clone();
                 move |item: &PdmTreeEntry| {
                     let (remote, id, node) = match item {
-                        PdmTreeEntry::Root => return html! {},
+                        PdmTreeEntry::Root | PdmTreeEntry::Folder(_) => return html! {},
                         PdmTreeEntry::Resource(remote_id, resource) => {
                             (remote_id, resource.id(), get_resource_node(resource))
                         }
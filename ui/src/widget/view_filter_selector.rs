@@ -36,6 +36,7 @@ enum FilterRuleType {
     ResourceId,
     Tag,
     Remote,
+    Folder,
 }
 
 impl FromStr for FilterRuleType {
@@ -47,6 +48,7 @@ impl FromStr for FilterRuleType {
             "resource-id" => FilterRuleType::ResourceId,
             "tag" => FilterRuleType::Tag,
             "remote" => FilterRuleType::Remote,
+            "folder" => FilterRuleType::Folder,
             _ => bail!("unknown filter type"),
         })
     }
@@ -60,6 +62,7 @@ impl From<FilterRuleType> for AttrValue {
             FilterRuleType::ResourceId => "resource-id".into(),
             FilterRuleType::Tag => "tag".into(),
             FilterRuleType::Remote => "remote".into(),
+            FilterRuleType::Folder => "folder".into(),
         }
     }
 }
@@ -72,6 +75,7 @@ impl From<&FilterRule> for FilterRuleType {
             FilterRule::ResourceId(_) => FilterRuleType::ResourceId,
             FilterRule::Tag(_) => FilterRuleType::Tag,
             FilterRule::Remote(_) => FilterRuleType::Remote,
+            FilterRule::Folder(_) => FilterRuleType::Folder,
         }
     }
 }
@@ -260,6 +264,9 @@ fn columns(
                                     FilterRuleType::Remote => {
                                         FilterRule::Remote(StringMatcher::Exact(String::new()))
                                     }
+                                    FilterRuleType::Folder => {
+                                        FilterRule::Folder(StringMatcher::Exact(String::new()))
+                                    }
                                 };
 
                                 link.send_message(Msg::ChangeFilter(filter, index));
@@ -271,6 +278,7 @@ fn columns(
                             FilterRuleType::ResourceId.into(),
                             FilterRuleType::Tag.into(),
                             FilterRuleType::Remote.into(),
+                            FilterRuleType::Folder.into(),
                         ]))
                         .render_value(|value: &AttrValue| {
                             if value.as_str().is_empty() {
@@ -282,6 +290,7 @@ fn columns(
                                 Ok(FilterRuleType::ResourceId) => tr!("Resource ID"),
                                 Ok(FilterRuleType::Tag) => tr!("Tag"),
                                 Ok(FilterRuleType::Remote) => tr!("Remote"),
+                                Ok(FilterRuleType::Folder) => tr!("Folder"),
                                 Err(err) => tr!("invalid type: {0}", err.to_string()),
                             }
                             .into()
@@ -343,73 +352,28 @@ fn columns(
                                 }
                             })
                             .into(),
-                        Some(FilterRule::ResourceId(id)) => Field::new()
-                            .value(match id {
-                                StringMatcher::Exact(id) => id.clone(),
-                            })
-                            .required(true)
-                            .validate(|value: &String| {
-                                let value =
-                                    FilterRule::ResourceId(StringMatcher::Exact(value.to_owned()))
-                                        .to_string();
-                                FILTER_RULE_SCHEMA.parse_simple_value(&value)?;
-                                Ok(())
-                            })
-                            .on_change({
-                                move |value: String| {
-                                    send_change(FilterRule::ResourceId(StringMatcher::Exact(
-                                        value,
-                                    )));
-                                }
-                            })
-                            .into(),
-                        Some(FilterRule::ResourcePool(pool)) => Field::new()
-                            .value(match pool {
-                                StringMatcher::Exact(pool) => pool.clone(),
-                            })
-                            .required(true)
-                            .validate(|value: &String| {
-                                let value = FilterRule::ResourcePool(StringMatcher::Exact(
-                                    value.to_owned(),
-                                ))
-                                .to_string();
-                                FILTER_RULE_SCHEMA.parse_simple_value(&value)?;
-                                Ok(())
-                            })
-                            .on_change({
-                                move |value: String| {
-                                    send_change(FilterRule::ResourcePool(StringMatcher::Exact(
-                                        value,
-                                    )));
-                                }
-                            })
-                            .into(),
-                        Some(FilterRule::Tag(tag)) => Field::new()
-                            .value(match tag {
-                                StringMatcher::Exact(tag) => tag.clone(),
-                            })
-                            .required(true)
-                            .validate(|value: &String| {
-                                let value = FilterRule::Tag(StringMatcher::Exact(value.to_owned()))
-                                    .to_string();
-                                FILTER_RULE_SCHEMA.parse_simple_value(&value)?;
-                                Ok(())
-                            })
-                            .on_change({
-                                move |value: String| {
-                                    send_change(FilterRule::Tag(StringMatcher::Exact(value)));
-                                }
-                            })
-                            .into(),
-                        Some(FilterRule::Remote(remote)) => RemoteSelector::new()
-                            .value(match remote {
-                                StringMatcher::Exact(remote) => remote.clone(),
-                            })
-                            .required(true)
-                            .on_change(move |value| {
-                                send_change(FilterRule::Remote(StringMatcher::Exact(value)))
-                            })
-                            .into(),
+                        Some(FilterRule::ResourceId(id)) => {
+                            string_matcher_field("resource-id", id, send_change)
+                        }
+                        Some(FilterRule::ResourcePool(pool)) => {
+                            string_matcher_field("resource-pool", pool, send_change)
+                        }
+                        Some(FilterRule::Tag(tag)) => string_matcher_field("tag", tag, send_change),
+                        Some(FilterRule::Remote(StringMatcher::Exact(remote))) => {
+                            RemoteSelector::new()
+                                .value(remote.clone())
+                                .required(true)
+                                .on_change(move |value| {
+                                    send_change(FilterRule::Remote(StringMatcher::Exact(value)))
+                                })
+                                .into()
+                        }
+                        Some(FilterRule::Remote(remote)) => {
+                            string_matcher_field("remote", remote, send_change)
+                        }
+                        Some(FilterRule::Folder(folder)) => {
+                            string_matcher_field("folder", folder, send_change)
+                        }
                         None => Field::new()
                             .placeholder(tr!("Select Type first"))
                             .disabled(true)
@@ -432,3 +396,50 @@ fn columns(
 
     Rc::new(columns)
 }
+
+// Non-exact matchers are shown and entered with their `glob:` or `regex:` prefix.
+fn string_matcher_text(matcher: &StringMatcher) -> String {
+    match matcher {
+        StringMatcher::Exact(value) => value.clone(),
+        other => format!("{}:{}", other.mode(), other.value()),
+    }
+}
+
+fn parse_string_matcher_rule(kind: &str, text: &str) -> Result<FilterRule, Error> {
+    let rule = match text.split_once(':') {
+        Some((mode @ ("exact" | "glob" | "regex"), value)) => format!("{mode}:{kind}={value}"),
+        _ => format!("exact:{kind}={text}"),
+    };
+    FILTER_RULE_SCHEMA.parse_simple_value(&rule)?;
+    rule.parse()
+}
+
+fn string_matcher_field(
+    kind: &'static str,
+    matcher: &StringMatcher,
+    send_change: impl Fn(FilterRule) + 'static,
+) -> Html {
+    Field::new()
+        .value(string_matcher_text(matcher))
+        .required(true)
+        .placeholder(tr!("Value, or glob:/regex: pattern"))
+        .validate(move |value: &String| {
+            parse_string_matcher_rule(kind, value)?;
+            Ok(())
+        })
+        .on_change(move |value: String| {
+            // invalid values are flagged by the validator, keep them as-is until they are fixed
+            let rule = parse_string_matcher_rule(kind, &value).unwrap_or_else(|_| {
+                let value = StringMatcher::Exact(value);
+                match kind {
+                    "resource-id" => FilterRule::ResourceId(value),
+                    "resource-pool" => FilterRule::ResourcePool(value),
+                    "tag" => FilterRule::Tag(value),
+                    "folder" => FilterRule::Folder(value),
+                    _ => FilterRule::Remote(value),
+                }
+            });
+            send_change(rule);
+        })
+        .into()
+}